@@ -1,110 +1,281 @@
 use super::*;
+use super::daemon;
 use crate::core::repository::Repository;
-use crate::storage::{Edge, Node, NodeType, RelationType};
+use crate::error::MemexiaError;
+use crate::t;
+use crate::storage::{DanglingStrategy, Edge, EdgeEdit, EdgeField, Node, NodeType, RelationType};
 use anyhow::{bail, Result};
 use serde_json::json;
+use std::fmt::Write as _;
 use std::path::Path;
 use tracing::info;
 
+/// 是否以只读模式打开仓库（`--read-only`）
+static READ_ONLY: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// 设置之后的命令是否以只读模式打开仓库
+pub fn set_read_only(read_only: bool) {
+    READ_ONLY.store(read_only, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// 是否忽略正在运行的 `memexia serve`，直接打开仓库（`--no-daemon`）
+static NO_DAEMON: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// 设置之后的命令是否忽略正在运行的 `memexia serve`
+pub fn set_no_daemon(no_daemon: bool) {
+    NO_DAEMON.store(no_daemon, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// 按 `--read-only` 打开 `path` 所在的仓库，并立即打开图存储
+///
+/// 图存储被该仓库的 `memexia serve` 占用时以只读方式打开，见 [`daemon`]。
+/// 不读取图谱的命令使用 [`open_repository_lazy`]
+fn open_repository(path: &Path) -> Result<Repository> {
+    let repo = open_repository_lazy(path)?;
+    match repo.storage().map(|_| ()) {
+        Ok(()) => Ok(repo),
+        Err(MemexiaError::StoreBusy { path: root }) if !repo.is_read_only() => match running_daemon(&root) {
+            Some(daemon) => {
+                eprintln!("{}", t!("daemon.read_only", port = daemon.info().port));
+                let repo = Repository::open_read_only(&root)?;
+                repo.storage()?;
+                Ok(repo)
+            }
+            None => Err(MemexiaError::StoreBusy { path: root }.into()),
+        },
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// 按 `--read-only` 打开 `path` 所在的仓库，图存储和版本控制在首次使用时才打开
+///
+/// 用于只读取暂存区、Git 或配置文件的命令（`status`、`log`、`config`）
+fn open_repository_lazy(path: &Path) -> Result<Repository> {
+    if READ_ONLY.load(std::sync::atomic::Ordering::Relaxed) {
+        return Ok(Repository::open_read_only(path)?);
+    }
+    Ok(Repository::open(path)?)
+}
+
+/// `path` 所在仓库正在运行的 `memexia serve`，修改仓库的命令交给它执行
+///
+/// `--read-only`、`--no-daemon` 或没有运行中的服务时返回 None
+fn running_daemon(path: &Path) -> Option<daemon::Daemon> {
+    if READ_ONLY.load(std::sync::atomic::Ordering::Relaxed) || NO_DAEMON.load(std::sync::atomic::Ordering::Relaxed) {
+        return None;
+    }
+    daemon::Daemon::detect(&Repository::find_root(path).ok()?)
+}
+
 pub fn init_repository(args: InitArgs) -> Result<()> {
     info!("Initializing repository at {:?}", args.path);
-    Repository::init(&args.path)?;
-    println!("Repository initialized at {:?}", args.path);
+    Repository::init_with_notes(&args.path, &args.notes)?;
+    println!("{}", t!("init.done", path = format!("{:?}", args.path)));
     Ok(())
 }
 
 pub fn add_files(args: AddArgs) -> Result<()> {
     info!("Adding files: {:?}", args.files);
-    let repo = Repository::open(Path::new("."))?;
+    if let Some(daemon) = running_daemon(Path::new(".")) {
+        return daemon.add(&args.files);
+    }
+    let repo = open_repository(Path::new("."))?;
     repo.add(&args.files)?;
     Ok(())
 }
 
 pub fn status(_args: StatusArgs) -> Result<()> {
-    let repo = Repository::open(Path::new("."))?;
+    // 只读取暂存区，不打开图存储
+    let repo = open_repository_lazy(Path::new("."))?;
     let status = repo.status()?;
     println!("{}", status);
     Ok(())
 }
 
 pub fn commit(args: CommitArgs) -> Result<()> {
-    info!("Committing with message: {}", args.message);
-    let mut repo = Repository::open(Path::new("."))?;
-    let commit_hash = repo.commit(&args.message)?;
-    println!("[{}] {}", &commit_hash[..7], args.message);
+    if let Some(daemon) = running_daemon(Path::new(".")) {
+        let root = Repository::find_root(Path::new("."))?;
+        let message = match args.message {
+            Some(message) => message,
+            None => {
+                let template = std::fs::read_to_string(root.join(crate::core::repository::COMMIT_TEMPLATE_PATH)).ok();
+                edit_commit_message(&root, template)?
+            }
+        };
+        let start = std::time::Instant::now();
+        let outcome = daemon.commit(&message, args.all, args.include_untracked)?;
+        print_commit(&outcome.commit, &message, &outcome.auto_staged, start);
+        return Ok(());
+    }
+
+    let mut repo = open_repository(Path::new("."))?;
+    let auto_staged = if args.all {
+        repo.stage_changes(args.include_untracked)?
+    } else {
+        Vec::new()
+    };
+    let message = match args.message {
+        Some(message) => message,
+        None => edit_commit_message(repo.path(), repo.commit_template()?)?,
+    };
+    info!("Committing with message: {}", message);
+
+    let start = std::time::Instant::now();
+    let commit_hash = repo.commit(&message)?;
+    print_commit(&commit_hash, &message, &auto_staged, start);
+    Ok(())
+}
+
+/// 输出提交结果
+fn print_commit(commit_hash: &str, message: &str, auto_staged: &[std::path::PathBuf], start: std::time::Instant) {
+    println!("[{}] {}", &commit_hash[..7], message.lines().next().unwrap_or(""));
+    if !auto_staged.is_empty() {
+        println!("{}", t!("commit.auto_staged", count = auto_staged.len()));
+        for path in auto_staged {
+            println!("  {}", path.display());
+        }
+    }
+    println!("{}", t!("commit.elapsed", seconds = output::format_seconds(start.elapsed())));
+}
+
+/// 在编辑器中填写提交消息，以提交模板预填
+///
+/// 与 Git 相同，`#` 开头的行视为注释；清理后为空时中止提交
+fn edit_commit_message(root: &Path, template: Option<String>) -> Result<String> {
+    use crate::vcs::message::clean_message;
+
+    let path = root.join(".memexia/COMMIT_EDITMSG");
+    let template = template.unwrap_or_default();
+    std::fs::write(&path, format!("{}\n{}\n", template.trim_end(), t!("commit.editor_hint")))?;
+
+    launch_editor(&path)?;
+    let message = clean_message(&std::fs::read_to_string(&path)?);
+    if message.is_empty() {
+        return Err(invalid(t!("commit.empty_message")));
+    }
+    Ok(message)
+}
+
+/// 使用 `$VISUAL` / `$EDITOR` 打开文件，等待编辑器退出
+fn launch_editor(path: &Path) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| if cfg!(windows) { "notepad".to_string() } else { "vi".to_string() });
+    let status = std::process::Command::new(&editor).arg(path).status()?;
+    if !status.success() {
+        bail!("{}", t!("editor.failed", editor = editor, status = status));
+    }
     Ok(())
 }
 
 pub fn amend(args: AmendArgs) -> Result<()> {
     info!("Amending last commit");
-    let mut repo = Repository::open(Path::new("."))?;
+    let mut repo = open_repository(Path::new("."))?;
     repo.amend(&args.message)?;
     Ok(())
 }
 
 pub fn log(args: LogArgs) -> Result<()> {
-    let repo = Repository::open(Path::new("."))?;
+    // 只有 --node 需要图谱，其余只读取 Git 和提交对应的图快照
+    let repo = match &args.node {
+        Some(_) => open_repository(Path::new("."))?,
+        None => open_repository_lazy(Path::new("."))?,
+    };
     let limit = args.limit.unwrap_or(10);
-    let commits = repo.log(limit)?;
+    let commits = match &args.node {
+        Some(node) => repo.log_for_node(node, limit)?,
+        None => repo.log(limit)?,
+    };
+    // --node 时标出每个提交首次引入的链接
+    let introduced_links = match &args.node {
+        Some(node) => repo.storage()?.graph().get_edges_by_source(&repo.node_id_for_path(node)?)?,
+        None => Vec::new(),
+    };
+
+    // "[abcdefg] " 占 10 列，消息截断到剩余宽度
+    let message_width = output::terminal_width().saturating_sub(10).max(20);
 
+    let mut out = String::new();
     for commit in commits {
         if args.oneline {
-            println!("[{}] {}", &commit.oid[..7], commit.message);
+            let message = commit.message.lines().next().unwrap_or("");
+            let _ = writeln!(
+                out,
+                "[{}] {}",
+                &commit.oid[..7],
+                output::truncate_to_width(message, message_width)
+            );
         } else {
-            println!("=== {} ===", &commit.oid[..8]);
-            println!("Message: {}", commit.message);
-            println!("Author: {}", commit.author);
-            println!("Timestamp: {}", commit.timestamp);
-            if let Some(graph_hash) = commit.graph_hash {
-                println!("Graph snapshot: {}", &graph_hash[..12]);
+            let _ = writeln!(out, "=== {} ===", &commit.oid[..8]);
+            let _ = writeln!(out, "{}", t!("log.message", message = commit.message));
+            let _ = writeln!(out, "{}", t!("log.author", author = commit.author));
+            let timestamp = commit.time().map(|time| timefmt::format_time(&time)).unwrap_or(commit.timestamp.clone());
+            let _ = writeln!(out, "{}", t!("log.timestamp", timestamp = timestamp));
+            if let Some(graph_hash) = &commit.graph_hash {
+                let _ = writeln!(out, "{}", t!("log.snapshot", hash = &graph_hash[..12.min(graph_hash.len())]));
             }
-            println!();
+            let nodes = commit.affected_nodes();
+            if !nodes.is_empty() {
+                let _ = writeln!(out, "{}", t!("log.nodes", nodes = nodes.join(", ")));
+            }
+            let links: Vec<&str> = introduced_links
+                .iter()
+                .filter(|edge| edge.introduced_in_commit.as_deref() == Some(commit.oid.as_str()))
+                .map(|edge| edge.to.as_str())
+                .collect();
+            if !links.is_empty() {
+                let _ = writeln!(out, "{}", t!("log.links_introduced", links = links.join(", ")));
+            }
+            let _ = writeln!(out);
         }
     }
+    output::maybe_page(&out);
 
     Ok(())
 }
 
 pub fn graph_operations(args: GraphArgs) -> Result<()> {
-    let repo = Repository::open(Path::new("."))?;
-    let storage = repo.storage();
+    let mut repo = open_repository(Path::new("."))?;
+    repo.enable_node_cache();
+    let storage = repo.storage()?;
 
     match args.command {
         GraphCommands::Show(args) => {
-            let nodes = storage.graph().get_all_nodes()?;
-            let edges = storage.graph().get_all_edges()?;
+            use crate::core::language;
+
+            let mut nodes = storage.graph().get_all_nodes()?;
+            let mut edges = storage.graph().get_all_edges()?;
+            if let Some(lang) = &args.lang {
+                nodes.retain(|n| n.language().is_some_and(|language| language::matches(language, lang)));
+                let ids: std::collections::HashSet<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+                edges.retain(|e| ids.contains(e.from.as_str()) && ids.contains(e.to.as_str()));
+            }
 
             if args.json {
-                let output = json!({
-                    "nodes": nodes.iter().map(|n| json!({
-                        "id": n.id,
-                        "type": format!("{:?}", n.node_type),
-                        "label": n.title
-                    })).collect::<Vec<_>>(),
-                    "edges": edges.iter().map(|e| json!({
-                        "from": e.from,
-                        "to": e.to,
-                        "type": format!("{:?}", e.relation),
-                        "strength": e.strength
-                    })).collect::<Vec<_>>()
-                });
+                let nodes: Vec<&Node> = nodes.iter().collect();
+                let output = graph_json(&nodes, &edges, |_| false);
                 println!("{}", serde_json::to_string_pretty(&output)?);
             } else {
-                println!("=== Graph Structure ===");
-                println!("Nodes ({}):", nodes.len());
-                for node in nodes.iter().take(20) {
-                    println!("  - {} [{}]", node.id, format!("{:?}", node.node_type));
-                }
-                if nodes.len() > 20 {
-                    println!("  ... and {} more", nodes.len() - 20);
-                }
-                println!("\nEdges ({}):", edges.len());
-                for edge in edges.iter().take(20) {
-                    println!("  {} --[{}]--> {}", edge.from, format!("{:?}", edge.relation), edge.to);
+                // 标题列占用 ID 和类型之外的剩余宽度，过长时截断
+                let title_width = output::terminal_width().saturating_sub(50).clamp(16, 60);
+
+                let mut out = String::new();
+                let _ = writeln!(out, "{}", t!("graph.show.header"));
+                let _ = writeln!(out, "{}", t!("graph.show.nodes", count = nodes.len()));
+                for node in &nodes {
+                    let _ = writeln!(
+                        out,
+                        "  {}  {:<10} {}",
+                        output::fit_to_width(&node.title, title_width),
+                        format!("{:?}", node.node_type),
+                        node.id
+                    );
                 }
-                if edges.len() > 20 {
-                    println!("  ... and {} more", edges.len() - 20);
+                let _ = writeln!(out, "\n{}", t!("graph.show.edges", count = edges.len()));
+                for edge in &edges {
+                    let _ = writeln!(out, "  {} --[{}]--> {}", edge.from, edge.relation, edge.to);
                 }
+                output::maybe_page(&out);
             }
             Ok(())
         }
@@ -112,62 +283,169 @@ pub fn graph_operations(args: GraphArgs) -> Result<()> {
         GraphCommands::Dot(_args) => {
             let nodes = storage.graph().get_all_nodes()?;
             let edges = storage.graph().get_all_edges()?;
+            let nodes: Vec<&Node> = nodes.iter().collect();
+            print!("{}", crate::storage::dot::render_graph(&nodes, &edges, |_| false));
+            Ok(())
+        }
 
-            println!("digraph memexia_graph {{");
-            println!("  node [shape=box, style=filled];");
-            for node in &nodes {
-                let color = match node.node_type {
-                    NodeType::Concept => "lightblue",
-                    NodeType::Question => "lightyellow",
-                    _ => "lightgreen",
-                };
-                println!("  \"{}\" [fillcolor={}];", node.id, color);
+        GraphCommands::Mermaid(args) => {
+            use crate::storage::mermaid::{export_mermaid, MermaidOptions};
+
+            let center = args.center.as_deref().map(|center| repo.node_id_for_path(center)).transpose()?;
+            let options = MermaidOptions {
+                center,
+                depth: args.depth,
+                max_nodes: args.max_nodes,
+            };
+
+            let mut diagram = export_mermaid(storage.graph(), &options)?;
+            if args.fence {
+                diagram = format!("```mermaid\n{}```\n", diagram);
             }
-            println!();
-            for edge in &edges {
-                println!("  \"{}\" -> \"{}\" [label=\"{}\"];", edge.from, edge.to, format!("{:?}", edge.relation));
+
+            match &args.output {
+                Some(path) => {
+                    std::fs::write(path, &diagram)?;
+                    println!("{}", t!("graph.mermaid.written", path = path.display()));
+                }
+                None => print!("{}", diagram),
             }
-            println!("}}");
             Ok(())
         }
 
-        GraphCommands::Stats(_args) => {
+        GraphCommands::Stats(args) => {
+            use crate::core::graph_stats::{bucket_label, GraphDetail};
+            use std::collections::BTreeMap;
+
             let nodes = storage.graph().get_all_nodes()?;
             let edges = storage.graph().get_all_edges()?;
 
-            let mut type_counts = std::collections::HashMap::new();
+            let mut type_counts: BTreeMap<String, usize> = BTreeMap::new();
             for node in &nodes {
-                *type_counts.entry(node.node_type.clone()).or_insert(0) += 1;
+                *type_counts.entry(node.node_type.to_string()).or_insert(0) += 1;
             }
 
-            let mut relation_counts = std::collections::HashMap::new();
+            let mut relation_counts: BTreeMap<String, usize> = BTreeMap::new();
             for edge in &edges {
-                *relation_counts.entry(edge.relation).or_insert(0) += 1;
+                *relation_counts.entry(edge.relation.to_string()).or_insert(0) += 1;
+            }
+
+            let avg_strength =
+                (!edges.is_empty()).then(|| edges.iter().map(|e| e.strength).sum::<f64>() / edges.len() as f64);
+            let detail = args.detailed.then(|| GraphDetail::compute(&nodes, &edges));
+
+            if args.json {
+                let mut value = json!({
+                    "nodes": nodes.len(),
+                    "edges": edges.len(),
+                    "by_type": type_counts,
+                    "by_relation": relation_counts,
+                    "avg_strength": avg_strength,
+                });
+                if let Some(detail) = &detail {
+                    value["detailed"] = serde_json::to_value(detail)?;
+                }
+                println!("{}", serde_json::to_string_pretty(&value)?);
+                return Ok(());
             }
 
-            println!("=== Graph Statistics ===");
-            println!("Total nodes: {}", nodes.len());
-            println!("Total edges: {}", edges.len());
-            println!("\nNodes by type:");
+            println!("{}", t!("graph.stats.header"));
+            println!("{}", t!("graph.stats.total_nodes", count = nodes.len()));
+            println!("{}", t!("graph.stats.total_edges", count = edges.len()));
+            println!("\n{}", t!("graph.stats.by_type"));
             for (node_type, count) in &type_counts {
-                println!("  {:?}: {}", node_type, count);
+                println!("  {}: {}", node_type, count);
             }
-            println!("\nEdges by relation:");
+            println!("\n{}", t!("graph.stats.by_relation"));
             for (relation, count) in &relation_counts {
-                println!("  {:?}: {}", relation, count);
+                println!("  {}: {}", relation, count);
+            }
+
+            if let Some(avg_strength) = avg_strength {
+                println!("\n{}", t!("graph.stats.avg_strength", value = format!("{:.2}", avg_strength)));
             }
 
-            if !edges.is_empty() {
-                let avg_strength: f64 = edges.iter().map(|e| e.strength).sum::<f64>() / edges.len() as f64;
-                println!("\nAverage edge strength: {:.2}", avg_strength);
+            let Some(detail) = detail else {
+                return Ok(());
+            };
+            let max = detail.strength_histogram.iter().copied().max().unwrap_or(0).max(1);
+            println!("\n{}", t!("graph.stats.histogram"));
+            for (index, count) in detail.strength_histogram.iter().enumerate() {
+                let bar = "#".repeat((count * 30).div_ceil(max));
+                println!("  {}  {:>6}  {}", bucket_label(index), count, bar);
+            }
+            println!("\n{}", t!("graph.stats.matrix"));
+            for (relation, sources) in &detail.relation_matrix {
+                for (from, targets) in sources {
+                    for (to, count) in targets {
+                        println!("  {} {} {}: {}", from, relation, to, count);
+                    }
+                }
+            }
+            if detail.dangling_edges > 0 {
+                println!("  {}", t!("graph.stats.dangling", count = detail.dangling_edges));
+            }
+            println!("\n{}", t!("graph.stats.out_degree"));
+            for (node_type, degree) in &detail.avg_out_degree {
+                println!("  {}: {:.2}", node_type, degree);
             }
 
             Ok(())
         }
 
+        GraphCommands::Repl => graph_repl(&repo),
+
         GraphCommands::Query(args) => {
-            let results = storage.graph().sparql_query(&args.query)?;
-            println!("SPARQL Results:");
+            use crate::core::query_templates;
+
+            if args.list_templates {
+                for template in query_templates::list(repo.path())? {
+                    let params: Vec<String> = template
+                        .params
+                        .iter()
+                        .map(|param| match &param.default {
+                            Some(default) => format!("{}=<{}> ({})", param.name, param.kind, default),
+                            None => format!("{}=<{}>", param.name, param.kind),
+                        })
+                        .collect();
+                    println!("{:<24} {}", template.name, template.description);
+                    if !params.is_empty() {
+                        println!("{:<24} {}", "", t!("graph.query.template_params", params = params.join(" ")));
+                    }
+                }
+                return Ok(());
+            }
+
+            let query = match (&args.template, args.query) {
+                (Some(name), _) => {
+                    let mut values = std::collections::BTreeMap::new();
+                    for param in &args.params {
+                        let Some((key, value)) = param.split_once('=') else {
+                            return Err(invalid(t!("graph.query.bad_param", param = param)));
+                        };
+                        values.insert(key.trim().to_string(), value.to_string());
+                    }
+                    query_templates::load(repo.path(), name)?.render(&values)?
+                }
+                (None, Some(_)) if !args.params.is_empty() => {
+                    return Err(invalid(t!("graph.query.param_without_template")));
+                }
+                (None, Some(query)) => query,
+                (None, None) => unreachable!("clap requires a query, --template or --list-templates"),
+            };
+
+            if let Some(path) = &args.output {
+                use crate::storage::csv::{self, QueryCsvOptions};
+
+                let result = storage.graph().query(&query)?;
+                let options = QueryCsvOptions { bom: args.excel, typed: args.typed };
+                let rows = csv::export_query_csv(&result, path, options)?;
+                println!("{}", t!("graph.query.exported", count = rows, path = path.display()));
+                return Ok(());
+            }
+
+            let results = storage.graph().sparql_query(&query)?;
+            println!("{}", t!("graph.query.header"));
             for result in results {
                 println!("{}", result);
             }
@@ -178,7 +456,7 @@ pub fn graph_operations(args: GraphArgs) -> Result<()> {
             let path = storage.graph().find_path(&args.source, &args.target)?;
             match path {
                 Some(nodes) => {
-                    println!("Path found ({} hops):", nodes.len() - 1);
+                    println!("{}", t!("graph.path.found", hops = nodes.len() - 1));
                     for (i, node) in nodes.iter().enumerate() {
                         if i > 0 {
                             print!(" --> ");
@@ -188,16 +466,115 @@ pub fn graph_operations(args: GraphArgs) -> Result<()> {
                     println!();
                 }
                 None => {
-                    println!("No path found between {} and {}", args.source, args.target);
+                    println!("{}", t!("graph.path.none", source = args.source, target = args.target));
+                }
+            }
+            Ok(())
+        }
+
+        GraphCommands::Decay(args) => {
+            use crate::core::decay::{self, StrengthDistribution};
+
+            let report = decay::decay(storage.graph(), args.half_life_days, chrono::Utc::now(), args.dry_run)?;
+
+            let key = if args.dry_run { "graph.decay.would_change" } else { "graph.decay.changed" };
+            println!("{}", t!(key, changed = report.changed, total = report.edges));
+            if report.edges > 0 {
+                println!(
+                    "{}",
+                    t!(
+                        "graph.decay.mean",
+                        before = format!("{:.2}", report.before.mean),
+                        after = format!("{:.2}", report.after.mean)
+                    )
+                );
+                println!("{}", t!("graph.decay.distribution"));
+                for (i, (before, after)) in report.before.buckets.iter().zip(&report.after.buckets).enumerate() {
+                    println!("  {}  {:>5} -> {:<5}", StrengthDistribution::bucket_label(i), before, after);
                 }
             }
             Ok(())
         }
+
+        GraphCommands::Layout(args) => {
+            use crate::core::layout::LayoutOptions;
+            use anyhow::Context;
+
+            let options = LayoutOptions { iterations: args.iterations, seed: args.seed };
+            let layout = graph_layout_json(storage.graph(), &options, &|| false)?;
+            let content = serde_json::to_string_pretty(&layout)? + "\n";
+            match &args.output {
+                Some(path) => {
+                    std::fs::write(path, content).with_context(|| format!("Failed to write {:?}", path))?;
+                    let nodes = layout["nodes"].as_array().map_or(0, Vec::len);
+                    println!("{}", t!("graph.layout.written", nodes = nodes, path = path.display()));
+                }
+                None => print!("{}", content),
+            }
+            Ok(())
+        }
+    }
+}
+
+/// 运行 SPARQL 交互式查询循环
+///
+/// stdin 不是终端时不显示提示符，便于通过管道批量执行
+fn graph_repl(repo: &Repository) -> Result<()> {
+    use super::repl::{self, Outcome, ReplSession};
+    use std::io::{BufRead, IsTerminal, Write};
+
+    let interactive = std::io::stdin().is_terminal();
+    let mut session = ReplSession::new(repo.storage()?.graph(), repl::load_history(repo.path()));
+    if interactive {
+        println!("{}", t!("repl.banner"));
+    }
+
+    let mut lines = std::io::stdin().lock().lines();
+    loop {
+        if interactive {
+            print!("{}", session.prompt());
+            std::io::stdout().flush()?;
+        }
+        // 输入结束时执行未完成的查询
+        let line = lines.next().transpose()?;
+        let known = session.history().len();
+        let outcome = session.feed_line(line.as_deref().unwrap_or(""));
+
+        for query in &session.history()[known..] {
+            if let Err(e) = repl::append_history(repo.path(), query) {
+                tracing::warn!("Failed to write REPL history: {}", e);
+            }
+        }
+        match outcome {
+            Outcome::Output(text) => output::maybe_page(&format!("{}\n", text)),
+            Outcome::Quit => break,
+            Outcome::Pending | Outcome::Empty => {}
+        }
+        if line.is_none() {
+            break;
+        }
     }
+    Ok(())
 }
 
-pub fn search(_args: SearchArgs) -> Result<()> {
-    info!("Search not implemented yet");
+pub fn search(args: SearchArgs) -> Result<()> {
+    use super::exit::{Exit, ExitStatus};
+
+    let repo = open_repository(Path::new("."))?;
+    let hits = repo.search(&args.query, args.include_archived, args.lang.as_deref())?;
+    if hits.is_empty() {
+        println!("{}", t!("search.none", query = args.query));
+        return Err(Exit(ExitStatus::NoResults).into());
+    }
+
+    let mut out = String::new();
+    for hit in &hits {
+        let _ = writeln!(out, "{}  {}", hit.node.title, hit.node.id);
+        if let Some(summary) = hit.node.summary() {
+            let _ = writeln!(out, "    {}", summary);
+        }
+    }
+    output::maybe_page(&out);
     Ok(())
 }
 
@@ -206,18 +583,124 @@ pub fn sync(_args: SyncArgs) -> Result<()> {
     Ok(())
 }
 
-pub fn serve(_args: ServeArgs) -> Result<()> {
-    info!("Serve not implemented yet");
+pub fn serve(args: ServeArgs) -> Result<()> {
+    if let Some(ServeCommands::Token(token_args)) = args.command {
+        return serve_token(token_args);
+    }
+    if let Some(daemon) = running_daemon(Path::new(".")) {
+        bail!("{}", t!("serve.already_running", port = daemon.info().port, pid = daemon.info().pid));
+    }
+    let mut repo = open_repository(Path::new("."))?;
+    let config = crate::core::RepoConfig::load(repo.path())?;
+    println!("{}", t!("serve.listening", port = args.port));
+    let options = super::serve::ServeOptions {
+        metrics_public: args.metrics_public,
+        limits: config.serve,
+        token: None,
+    };
+    super::serve::run(&mut repo, &options, args.port)
+}
+
+fn serve_token(args: ServeTokenArgs) -> Result<()> {
+    use crate::core::{RepoConfig, ServeScope, ServeToken};
+
+    let ServeTokenCommands::Create(args) = args.command;
+    let mut scopes = Vec::new();
+    for name in &args.scopes {
+        let scope = ServeScope::parse(name).ok_or_else(|| invalid(t!("serve.unknown_scope", scope = name)))?;
+        if !scopes.contains(&scope) {
+            scopes.push(scope);
+        }
+    }
+
+    let repo = open_repository_lazy(Path::new("."))?;
+    let token = ServeToken::generate(scopes);
+    RepoConfig::add_serve_token(repo.path(), &token)?;
+    let scopes: Vec<&str> = token.scopes.iter().map(ServeScope::as_str).collect();
+    println!("{}", t!("serve.token_created", scopes = scopes.join(", ")));
+    println!("{}", token.token);
+    Ok(())
+}
+
+pub fn config(args: ConfigArgs) -> Result<()> {
+    use crate::core::RepoConfig;
+
+    let repo = open_repository_lazy(Path::new("."))?;
+    let root = repo.path();
+
+    match (args.key, args.value) {
+        (Some(key), Some(value)) => {
+            RepoConfig::set(root, &key, &value)?;
+            println!("{} = {}", key, value);
+        }
+        (Some(key), None) => match RepoConfig::get(root, &key)? {
+            Some(value) => println!("{}", value),
+            None => bail!("{}", t!("config.not_set", key = key)),
+        },
+        (None, _) => {
+            let entries = RepoConfig::list(root)?;
+            if entries.is_empty() {
+                println!("{}", t!("config.empty"));
+            }
+            for (key, value) in entries {
+                println!("{} = {}", key, value);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn new_note(args: NewArgs) -> Result<()> {
+    use crate::core::note::NoteOptions;
+
+    let node_type = args.node_type.as_deref().map(parse_node_type).transpose()?;
+
+    let repo = open_repository(Path::new("."))?;
+    let options = NoteOptions {
+        node_type,
+        tags: args.tags,
+        template: args.template,
+        dir: args.dir,
+        ..NoteOptions::new(args.title)
+    };
+
+    let (path, node_id) = repo.create_note(&options)?;
+    println!("{}", t!("new.created", path = path.display()));
+    println!("{}", t!("new.node_id", id = node_id));
+
+    if args.edit {
+        launch_editor(&path)?;
+    }
+
     Ok(())
 }
 
-pub fn config(_args: ConfigArgs) -> Result<()> {
-    info!("Config not implemented yet");
+/// 向笔记追加文字，可选立即提交
+pub fn append(args: AppendArgs) -> Result<()> {
+    use crate::core::capture::{self, Insert};
+
+    let mut repo = open_repository(Path::new("."))?;
+    let text = capture::read_text(&args.text, std::io::stdin().lock())?;
+    let insert = Insert {
+        prepend: args.prepend,
+        section: args.section,
+    };
+    let node_id = repo.append_note(&args.path, &text, &insert)?;
+    println!("{}", t!("append.done", path = args.path.display(), id = node_id));
+
+    if args.commit {
+        let message = args
+            .message
+            .unwrap_or_else(|| format!("Update {}", args.path.display()));
+        let commit_hash = repo.commit(&message)?;
+        println!("[{}] {}", &commit_hash[..7], message.lines().next().unwrap_or(""));
+    }
     Ok(())
 }
 
 pub fn reindex(args: ReindexArgs) -> Result<()> {
-    use crate::core::{Indexer, Repository};
+    use crate::core::{hooks, RepoConfig};
 
     let root = args.path.canonicalize()?;
 
@@ -227,208 +710,2242 @@ pub fn reindex(args: ReindexArgs) -> Result<()> {
         info!("Incremental reindex of {:?}", root);
     }
 
-    let repo = Repository::open(&root)?;
-    let storage = repo.storage();
-    let indexer = Indexer::new(storage.clone());
+    if let Some(daemon) = running_daemon(&root).filter(|_| !args.dry_run) {
+        let summary = daemon.reindex(&root, args.full, !args.no_rename_detection)?;
+        if args.full {
+            println!("{}", t!("undo.not_recorded", operation = "reindex --full"));
+        }
+        print_reindex_summary(&summary);
+        if summary.has_errors() {
+            bail!("{}", t!("reindex.failed"));
+        }
+        return Ok(());
+    }
 
+    // 全量重建不需要旧图：无法打开时先换成空存储
+    let mut repo = if args.full && !args.dry_run {
+        let mut repo = open_repository_lazy(&root)?;
+        if let Err(MemexiaError::StoreCorrupt { .. }) = repo.storage().map(|_| ()) {
+            if let Some(moved) = repo.recover_store()? {
+                eprintln!("{}", t!("reindex.store_recovered", path = moved.display()));
+            }
+        }
+        repo
+    } else {
+        open_repository(&root)?
+    };
+    if args.dry_run {
+        repo.enable_dry_run();
+    }
+    let config = RepoConfig::load(repo.path())?;
     let summary = if args.full {
-        indexer.reindex_all(&root)
+        repo.rebuild_index(&root)?
     } else {
-        indexer.index_all(&root)
-    }?;
+        repo.reindex(&root, false, !args.no_rename_detection)?
+    };
 
-    println!("\n=== Reindex Summary ===");
-    println!("Files indexed: {}", summary.files_indexed);
-    println!("Files skipped: {}", summary.files_skipped);
-    println!("Files deleted: {}", summary.files_deleted);
-    println!("Nodes created: {}", summary.nodes_created);
-    println!("Edges created: {}", summary.edges_created);
-
-    if !summary.errors.is_empty() {
-        println!("\nErrors:");
-        for (path, error) in &summary.errors {
-            println!("  - {}: {}", path, error);
+    if !args.dry_run {
+        hooks::run_post_index(&config.hooks, &summary);
+        if args.full {
+            repo.discard_undo()?;
+            println!("{}", t!("undo.not_recorded", operation = "reindex --full"));
         }
     }
 
+    print_reindex_summary(&summary);
+    if args.dry_run {
+        print_dry_run(repo.storage()?);
+    }
+
     if summary.has_errors() {
-        anyhow::bail!("Reindex completed with errors");
+        bail!("{}", t!("reindex.failed"));
     }
 
     Ok(())
 }
 
+/// 输出重新索引的统计、警告和错误
+fn print_reindex_summary(summary: &crate::core::IndexSummary) {
+    println!("\n{}", t!("reindex.header"));
+    println!("{}", t!("reindex.files_indexed", count = summary.files_indexed));
+    println!("{}", t!("reindex.files_skipped", count = summary.files_skipped));
+    if summary.files_excluded > 0 {
+        println!("{}", t!("reindex.files_excluded", count = summary.files_excluded));
+    }
+    println!("{}", t!("reindex.files_deleted", count = summary.files_deleted));
+    for (path, reason) in &summary.rejected {
+        println!("{}", t!("reindex.rejected", path = path, reason = reason));
+    }
+    println!("{}", t!("reindex.nodes_created", count = summary.nodes_created));
+    if summary.placeholders_promoted > 0 {
+        println!("{}", t!("reindex.placeholders_promoted", count = summary.placeholders_promoted));
+    }
+    println!("{}", t!("reindex.edges_created", count = summary.edges_created));
+    for rename in &summary.renamed {
+        println!(
+            "{}",
+            t!(
+                "reindex.renamed",
+                from = crate::storage::ids::display_file_id(&rename.old_id),
+                to = crate::storage::ids::display_file_id(&rename.new_id)
+            )
+        );
+    }
+
+    println!(
+        "{}",
+        t!(
+            "reindex.elapsed",
+            count = output::format_count(summary.files_indexed),
+            seconds = output::format_seconds(summary.elapsed)
+        )
+    );
+
+    if !summary.warnings.is_empty() {
+        println!("\n{}", t!("reindex.warnings", count = summary.warning_count()));
+        for (path, warnings) in &summary.warnings {
+            for warning in warnings {
+                println!("  - {}:{}: {}", path, warning.line, warning.message);
+            }
+        }
+    }
+
+    if !summary.errors.is_empty() {
+        println!("\n{}", t!("reindex.errors"));
+        for (path, error) in &summary.errors {
+            println!("  - {}: {}", path, error);
+        }
+    }
+}
+
 pub fn file_operations(args: FileArgs) -> Result<()> {
-    let repo = Repository::open(Path::new("."))?;
-    let storage = repo.storage();
+    let repo = open_repository(Path::new("."))?;
+    let storage = repo.storage()?;
 
     match args.command {
         FileCommands::Info(args) => {
             let file_path = args.path;
-            let relative = file_path.strip_prefix(repo.path()).unwrap_or(&file_path);
-            let node_id = format!("urn:memexia:file:{}", relative.to_string_lossy().replace('\\', "/"));
+            let node_id = repo.node_id_for_path(&file_path)?;
 
             if let Some(node) = storage.graph().get_node(&node_id)? {
-                println!("=== File Info ===");
-                println!("Path: {}", file_path.display());
-                println!("Node ID: {}", node.id);
-                println!("Type: {:?}", node.node_type);
-                println!("Label: {}", node.title);
+                println!("{}", t!("file.info.header"));
+                println!("{}", t!("file.info.path", path = file_path.display()));
+                println!("{}", t!("file.info.node_id", id = node.id));
+                println!("{}", t!("file.info.type", node_type = format!("{:?}", node.node_type)));
+                println!("{}", t!("file.info.label", label = node.title));
                 if !node.tags.is_empty() {
-                    println!("Tags: {}", node.tags.join(", "));
+                    println!("{}", t!("file.info.tags", tags = node.tags.join(", ")));
+                }
+                if let Some(summary) = node.summary() {
+                    let key = if node.is_auto_summary() { "file.info.summary_auto" } else { "file.info.summary" };
+                    println!("{}", t!(key, summary = summary));
                 }
+                println!("{}", t!("file.info.created", time = timefmt::format_time(&node.created_at)));
+                println!("{}", t!("file.info.updated", time = timefmt::format_time(&node.updated_at)));
             } else {
-                println!("File not indexed: {}", file_path.display());
+                println!("{}", t!("file.info.not_indexed", path = file_path.display()));
             }
             Ok(())
         }
 
         FileCommands::Links(args) => {
             let file_path = args.path;
-            let relative = file_path.strip_prefix(repo.path()).unwrap_or(&file_path);
-            let node_id = format!("urn:memexia:file:{}", relative.to_string_lossy().replace('\\', "/"));
+            let node_id = repo.node_id_for_path(&file_path)?;
+
+            if !args.at.is_empty() {
+                return file_links_at(&repo, &node_id, &file_path, &args.at);
+            }
 
             let edges = storage.graph().get_edges_by_source(&node_id)?;
 
-            println!("=== Outgoing Links ===");
+            println!("{}", t!("file.links.header"));
             if edges.is_empty() {
-                println!("No outgoing links from {}", file_path.display());
+                println!("{}", t!("file.links.none", path = file_path.display()));
             } else {
                 for edge in &edges {
-                    println!("  --[{:?}]--> {}", edge.relation, edge.to);
+                    println!("  --[{}]--> {}", edge.relation, edge.to);
+                    if args.verbose {
+                        match &edge.introduced_in_commit {
+                            Some(commit) => println!("{}", t!("file.links.introduced", commit = short_hash(commit))),
+                            None => println!("{}", t!("file.links.uncommitted")),
+                        }
+                        for change in &edge.history {
+                            println!("      {}", format_edge_change(change));
+                        }
+                    }
+                    print_latest_annotation(edge);
                 }
-                println!("\nTotal: {} outgoing links", edges.len());
+                println!("\n{}", t!("file.links.total", count = edges.len()));
+            }
+            Ok(())
+        }
+
+        FileCommands::Log(args) => file_log(&repo, args),
+
+        FileCommands::Split(args) => {
+            let node_type = args.node_type.as_deref().map(parse_node_type).transpose()?;
+            let outcome = repo.split_note(&args.path, &args.heading, node_type, args.embed)?;
+            println!("{}", t!("file.split.done", heading = args.heading.trim(), path = outcome.path));
+            println!("{}", t!("file.split.derived", child = outcome.node_id, parent = outcome.original_id));
+            Ok(())
+        }
+
+        FileCommands::RenderTemplate(args) => {
+            if repo.render_graph_template(&args.path)? {
+                println!("{}", t!("file.render_template.done", path = args.path.display()));
+            } else {
+                println!("{}", t!("file.render_template.unchanged", path = args.path.display()));
             }
             Ok(())
         }
 
         FileCommands::Backlinks(args) => {
             let file_path = args.path;
-            let relative = file_path.strip_prefix(repo.path()).unwrap_or(&file_path);
-            let node_id = format!("urn:memexia:file:{}", relative.to_string_lossy().replace('\\', "/"));
+            let node_id = repo.node_id_for_path(&file_path)?;
 
             let edges = storage.graph().get_edges_by_target(&node_id)?;
 
-            println!("=== Backlinks ===");
+            println!("{}", t!("file.backlinks.header"));
             if edges.is_empty() {
-                println!("No backlinks to {}", file_path.display());
+                println!("{}", t!("file.backlinks.none", path = file_path.display()));
             } else {
+                // 有逆关系的入边从本笔记的视角表述（`--[PartOf]--> 整体`）
                 for edge in &edges {
-                    println!("  <--[{:?}]-- {}", edge.relation, edge.from);
+                    println!("  {}", edge.describe_from(&node_id));
+                    print_latest_annotation(edge);
                 }
-                println!("\nTotal: {} backlinks", edges.len());
+                println!("\n{}", t!("file.backlinks.total", count = edges.len()));
             }
             Ok(())
         }
     }
 }
 
-pub fn link_operations(args: LinkArgs) -> Result<()> {
-    let repo = Repository::open(Path::new("."))?;
-    let storage = repo.storage();
-
-    match args.command {
-        LinkCommands::Create(args) => {
-            let relation = match args.relation.to_lowercase().as_str() {
-                "contains" | "belongsto" => RelationType::Contains,
-                "partof" | "part_of" => RelationType::PartOf,
-                "derivesfrom" | "derives_from" | "leadsto" | "leads_to" => RelationType::LeadsTo,
-                "supports" => RelationType::Supports,
-                "contradicts" => RelationType::Contradicts,
-                "refines" => RelationType::Refines,
-                "relatedto" | "related_to" | "similar" => RelationType::RelatedTo,
-                "analogousto" | "analogous_to" => RelationType::AnalogousTo,
-                "references" | "cites" => RelationType::References,
-                "instanceof" | "instance_of" => RelationType::InstanceOf,
-                "precedes" => RelationType::Precedes,
-                "follows" => RelationType::Follows,
-                "simultaneous" => RelationType::Simultaneous,
-                _ => bail!("Unknown relation type: {}", args.relation),
-            };
-
-            let source = args.source.strip_prefix(repo.path()).unwrap_or(&args.source);
-            let target = args.target.strip_prefix(repo.path()).unwrap_or(&args.target);
+fn file_log(repo: &Repository, args: FileLogArgs) -> Result<()> {
+    use crate::core::file_log::{FileChange, FileLogEvent};
 
-            let source_id = format!("urn:memexia:file:{}", source.to_string_lossy().replace('\\', "/"));
-            let target_id = format!("urn:memexia:file:{}", target.to_string_lossy().replace('\\', "/"));
+    let entries = repo.file_log(&args.path)?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+    if entries.is_empty() {
+        println!("{}", t!("file.log.none", path = args.path.display()));
+        return Ok(());
+    }
 
-            if !storage.graph().node_exists(&source_id)? {
-                let source_node = Node::new(&source_id, NodeType::Resource, &*source.to_string_lossy());
-                storage.graph().add_node(&source_node)?;
+    let display = |id: &str| crate::storage::ids::display_file_id(id);
+    let short = |hash: &str| hash.chars().take(7).collect::<String>();
+    // 相对时间长短不一，按最宽的一项对齐
+    let times: Vec<String> = entries.iter().map(|entry| timefmt::format_time(&entry.timestamp)).collect();
+    let time_width = times.iter().map(|time| output::display_width(time)).max().unwrap_or(0);
+    let mut out = String::new();
+    for (entry, time) in entries.iter().zip(&times) {
+        let (commit, summary) = match &entry.event {
+            FileLogEvent::Commit { commit, message, changes } => {
+                let mut parts: Vec<String> = changes
+                    .iter()
+                    .map(|change| match change {
+                        FileChange::Created { words } => t!("file.log.created", words = words),
+                        FileChange::Deleted => t!("file.log.deleted"),
+                        FileChange::Renamed { from } => t!("file.log.renamed", from = from),
+                        FileChange::ContentChanged { word_delta } => {
+                            t!("file.log.content_changed", delta = format!("{:+}", word_delta))
+                        }
+                        FileChange::LinkAdded { target } => t!("file.log.link_added", target = target),
+                        FileChange::LinkRemoved { target } => t!("file.log.link_removed", target = target),
+                    })
+                    .collect();
+                if !message.is_empty() {
+                    parts.push(format!("\"{}\"", message.lines().next().unwrap_or("")));
+                }
+                (commit, parts.join("; "))
             }
+            FileLogEvent::Snapshot { hash, commit } => (commit, t!("file.log.snapshot", hash = short(hash))),
+            FileLogEvent::DerivedFrom { parent, commit } => (commit, t!("file.log.derived_from", node = display(parent))),
+            FileLogEvent::DerivedInto { child, commit } => (commit, t!("file.log.derived_into", node = display(child))),
+        };
+        let _ = writeln!(
+            out,
+            "{}  {:<7}  {}",
+            output::fit_to_width(time, time_width),
+            short(commit),
+            summary
+        );
+    }
+    output::maybe_page(&out);
+    Ok(())
+}
 
-            if !storage.graph().node_exists(&target_id)? {
-                let target_node = Node::new(&target_id, NodeType::Resource, &*target.to_string_lossy());
-                storage.graph().add_node(&target_node)?;
-            }
+/// 注释时间的显示形式（本地时间）
+fn format_annotation_time(annotation: &crate::storage::EdgeAnnotation) -> String {
+    annotation.created_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string()
+}
 
-            let edge_id = format!("urn:memexia:edge:{}-{}", source_id, target_id);
-            let edge = Edge::new(&edge_id, &source_id, &target_id, relation);
-            storage.graph().add_edge(&edge)?;
+/// 边的一条修改记录：时间、字段和修改前的值
+fn format_edge_change(change: &crate::storage::EdgeChange) -> String {
+    t!(
+        "link.change",
+        time = timefmt::format_time(&change.changed_at),
+        field = change.field,
+        previous = change.previous.clone().unwrap_or_else(|| t!("link.empty_value"))
+    )
+}
 
-            println!("Link created: {} --[{:?}]--> {}", args.source.display(), relation, args.target.display());
-            Ok(())
-        }
+fn print_link_set(source: &Path, target: &Path, field: EdgeField, value: &str) {
+    println!(
+        "{}",
+        t!("link.set", field = field, source = source.display(), target = target.display(), value = value)
+    );
+}
 
-        LinkCommands::Delete(args) => {
-            let source = args.source.strip_prefix(repo.path()).unwrap_or(&args.source);
-            let target = args.target.strip_prefix(repo.path()).unwrap_or(&args.target);
+/// 在链接列表中显示边的最近一条注释
+fn print_latest_annotation(edge: &Edge) {
+    if let Some(annotation) = edge.latest_annotation() {
+        println!(
+            "{}",
+            t!("file.links.annotation", time = format_annotation_time(annotation), text = annotation.text)
+        );
+    }
+}
 
-            let source_id = format!("urn:memexia:file:{}", source.to_string_lossy().replace('\\', "/"));
-            let target_id = format!("urn:memexia:file:{}", target.to_string_lossy().replace('\\', "/"));
+/// 显示历史时间点的出链，每个时间点带说明历史上下文的横幅
+fn file_links_at(repo: &Repository, node_id: &str, file_path: &Path, at: &[String]) -> Result<()> {
+    use crate::core::time_travel::At;
 
-            let edge_id = format!("urn:memexia:edge:{}-{}", source_id, target_id);
-            storage.graph().remove_edge(&edge_id)?;
+    for (i, at) in at.iter().enumerate() {
+        let at = At::parse(at)?;
+        let view = repo.graph_at(&at)?;
+        let recorded = view
+            .link
+            .recorded_at
+            .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "?".to_string());
 
-            println!("Link deleted: {} --> {}", args.source.display(), args.target.display());
-            Ok(())
+        if i > 0 {
+            println!();
         }
+        println!(
+            "{}",
+            t!(
+                "file.links.historical",
+                at = at,
+                commit = &view.link.commit_hash[..7.min(view.link.commit_hash.len())],
+                date = recorded
+            )
+        );
 
-        LinkCommands::Query(args) => {
-            let mut edges = storage.graph().get_all_edges()?;
-
-            if let Some(source_path) = &args.source {
-                let relative = source_path.strip_prefix(repo.path()).unwrap_or(source_path);
-                let source_id = format!("urn:memexia:file:{}", relative.to_string_lossy().replace('\\', "/"));
-                edges.retain(|e| e.from == source_id);
+        let edges = view.graph().get_edges_by_source(node_id)?;
+        if edges.is_empty() {
+            println!("{}", t!("file.links.none", path = file_path.display()));
+        } else {
+            for edge in &edges {
+                println!("  --[{}]--> {}", edge.relation, edge.to);
             }
+            println!("\n{}", t!("file.links.total", count = edges.len()));
+        }
+    }
+    Ok(())
+}
 
-            if let Some(target_path) = &args.target {
-                let relative = target_path.strip_prefix(repo.path()).unwrap_or(target_path);
-                let target_id = format!("urn:memexia:file:{}", relative.to_string_lossy().replace('\\', "/"));
-                edges.retain(|e| e.to == target_id);
-            }
+pub fn link_operations(args: LinkArgs) -> Result<()> {
+    let daemon = running_daemon(Path::new("."));
+    match args.command {
+        LinkCommands::Create(args) => {
+            let relation = parse_link_relation(&args.relation)
+                .ok_or_else(|| invalid(t!("link.unknown_relation", relation = args.relation)))?;
+            let relation = match daemon {
+                Some(daemon) => daemon.create_link(&args.source, &args.target, &relation.to_string())?,
+                None => {
+                    let repo = open_repository(Path::new("."))?;
+                    repo.create_link(&args.source, &args.target, relation)?.relation.to_string()
+                }
+            };
 
-            if let Some(relation_str) = &args.relation {
-                let relation = match relation_str.to_lowercase().as_str() {
-                    "contains" | "belongsto" => RelationType::Contains,
-                    "partof" | "part_of" => RelationType::PartOf,
-                    "derivesfrom" | "derives_from" | "leadsto" | "leads_to" => RelationType::LeadsTo,
-                    "supports" => RelationType::Supports,
-                    "contradicts" => RelationType::Contradicts,
-                    "refines" => RelationType::Refines,
-                    "relatedto" | "related_to" | "similar" => RelationType::RelatedTo,
-                    "analogousto" | "analogous_to" => RelationType::AnalogousTo,
-                    "references" | "cites" => RelationType::References,
-                    "instanceof" | "instance_of" => RelationType::InstanceOf,
-                    "precedes" => RelationType::Precedes,
-                    "follows" => RelationType::Follows,
-                    "simultaneous" => RelationType::Simultaneous,
-                    _ => RelationType::RelatedTo,
-                };
-                edges.retain(|e| e.relation == relation);
-            }
+            println!(
+                "{}",
+                t!(
+                    "link.created",
+                    source = args.source.display(),
+                    relation = relation,
+                    target = args.target.display()
+                )
+            );
+            Ok(())
+        }
 
-            println!("=== Query Results ===");
-            if edges.is_empty() {
-                println!("No matching links found");
-            } else {
-                for edge in &edges {
-                    println!("  {} --[{:?}]--> {}", edge.from, edge.relation, edge.to);
+        LinkCommands::Delete(args) => {
+            match daemon.filter(|_| !args.dry_run) {
+                Some(daemon) => daemon.delete_link(&args.source, &args.target)?,
+                None => {
+                    let repo = open_repository(Path::new("."))?;
+                    if args.dry_run {
+                        let edge_id = Edge::make_id(
+                            &repo.node_id_for_path(&args.source)?,
+                            &repo.node_id_for_path(&args.target)?,
+                        );
+                        let mut storage = repo.storage()?.clone();
+                        storage.enable_dry_run();
+                        storage.graph().remove_edge(&edge_id)?;
+                        print_dry_run(&storage);
+                        return Ok(());
+                    }
+                    repo.delete_link(&args.source, &args.target)?;
                 }
-                println!("\nTotal: {} links", edges.len());
             }
+
+            println!("{}", t!("link.deleted", source = args.source.display(), target = args.target.display()));
             Ok(())
         }
+
+        LinkCommands::Apply(args) => link_apply(args),
+
+        command => link_queries(command),
     }
 }
+
+/// `memexia link apply`：预览批量链接文件，确认后写入
+fn link_apply(args: LinkApplyArgs) -> Result<()> {
+    use super::exit::{Exit, ExitStatus};
+    use crate::core::link_batch;
+    use anyhow::Context;
+    use std::io::IsTerminal;
+
+    let content = std::fs::read_to_string(&args.file)
+        .with_context(|| format!("Failed to read {}", args.file.display()))?;
+    let parsed = link_batch::parse(&content);
+    for error in &parsed.errors {
+        eprintln!("{}", t!("link.apply.line_error", line = error.line, message = error.message));
+    }
+    if args.strict && !parsed.errors.is_empty() {
+        return Err(invalid(t!("link.apply.strict_failed", count = parsed.errors.len())));
+    }
+    if parsed.links.is_empty() {
+        println!("{}", t!("link.apply.none"));
+        if !parsed.errors.is_empty() {
+            return Err(Exit(ExitStatus::Validation).into());
+        }
+        return Ok(());
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "{}", t!("link.apply.preview", count = parsed.links.len()));
+    for link in &parsed.links {
+        let relation = match link.strength {
+            Some(strength) => format!("{}({})", link.relation, strength),
+            None => link.relation.to_string(),
+        };
+        let _ = write!(out, "  {:>4}  {} --[{}]--> {}", link.line, link.source, relation, link.target);
+        match &link.description {
+            Some(description) => {
+                let _ = writeln!(out, "  # {}", description);
+            }
+            None => out.push('\n'),
+        }
+    }
+    output::maybe_page(&out);
+
+    if !args.yes {
+        if !std::io::stdin().is_terminal() {
+            return Err(invalid(t!("link.apply.needs_yes")));
+        }
+        print!("{} ", t!("link.apply.prompt", count = parsed.links.len()));
+        std::io::Write::flush(&mut std::io::stdout())?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            println!("{}", t!("link.apply.cancelled"));
+            return Ok(());
+        }
+    }
+
+    let repo = open_repository(Path::new("."))?;
+    let report = repo.apply_links(&parsed.links, args.strict)?;
+    for error in &report.errors {
+        eprintln!("{}", t!("link.apply.line_error", line = error.line, message = error.message));
+    }
+    println!("{}", t!("link.apply.done", count = report.applied.len()));
+    if !parsed.errors.is_empty() || !report.errors.is_empty() {
+        return Err(Exit(ExitStatus::Validation).into());
+    }
+    Ok(())
+}
+
+/// 解析 `link create` / `link query` 的关系名，接受常见别名（不区分大小写）
+fn parse_link_relation(relation: &str) -> Option<RelationType> {
+    Some(match relation.to_lowercase().as_str() {
+        "contains" | "belongsto" => RelationType::Contains,
+        "partof" | "part_of" => RelationType::PartOf,
+        "derivesfrom" | "derives_from" | "leadsto" | "leads_to" => RelationType::LeadsTo,
+        "supports" => RelationType::Supports,
+        "contradicts" => RelationType::Contradicts,
+        "refines" => RelationType::Refines,
+        "relatedto" | "related_to" | "similar" => RelationType::RelatedTo,
+        "analogousto" | "analogous_to" => RelationType::AnalogousTo,
+        "references" | "cites" => RelationType::References,
+        "instanceof" | "instance_of" => RelationType::InstanceOf,
+        "precedes" => RelationType::Precedes,
+        "follows" => RelationType::Follows,
+        "simultaneous" => RelationType::Simultaneous,
+        _ => return RelationType::parse(relation),
+    })
+}
+
+/// 只读取图的链接子命令
+fn link_queries(command: LinkCommands) -> Result<()> {
+    let mut repo = open_repository(Path::new("."))?;
+    repo.enable_node_cache();
+    let storage = repo.storage()?;
+
+    match command {
+        LinkCommands::Create(_) | LinkCommands::Delete(_) | LinkCommands::Apply(_) => unreachable!("handled by link_operations"),
+
+        LinkCommands::Query(args) => {
+            let mut edges = storage.graph().get_all_edges()?;
+
+            if let Some(source_path) = &args.source {
+                let source_id = repo.node_id_for_path(source_path)?;
+                edges.retain(|e| e.from == source_id);
+            }
+
+            if let Some(target_path) = &args.target {
+                let target_id = repo.node_id_for_path(target_path)?;
+                edges.retain(|e| e.to == target_id);
+            }
+
+            if let Some(relation_str) = &args.relation {
+                let relation = parse_link_relation(relation_str).unwrap_or_default();
+                edges.retain(|e| e.relation == relation);
+            }
+
+            println!("{}", t!("link.query.header"));
+            if edges.is_empty() {
+                println!("{}", t!("link.query.none"));
+            } else {
+                for edge in &edges {
+                    println!("  {} --[{}]--> {}", edge.from, edge.relation, edge.to);
+                }
+                println!("\n{}", t!("link.query.total", count = edges.len()));
+            }
+            Ok(())
+        }
+
+        LinkCommands::Annotate(args) => {
+            let edge = repo.annotate_link(&args.source, &args.target, &args.text)?;
+            println!(
+                "{}",
+                t!(
+                    "link.annotated",
+                    source = args.source.display(),
+                    target = args.target.display(),
+                    count = edge.annotations.len()
+                )
+            );
+            Ok(())
+        }
+
+        LinkCommands::Annotations(args) => {
+            let edge = repo.link_between(&args.source, &args.target)?;
+            println!(
+                "{}",
+                t!("link.annotations.header", source = args.source.display(), target = args.target.display())
+            );
+            if edge.annotations.is_empty() {
+                println!("{}", t!("link.annotations.none"));
+            }
+            for annotation in &edge.annotations {
+                println!("  [{}] {}", format_annotation_time(annotation), annotation.text);
+            }
+            if !edge.history.is_empty() {
+                println!("{}", t!("link.annotations.changes"));
+                for change in &edge.history {
+                    println!("  {}", format_edge_change(change));
+                }
+            }
+            Ok(())
+        }
+
+        LinkCommands::SetStrength(args) => {
+            let edit = EdgeEdit { strength: Some(args.value), ..Default::default() };
+            let edge = repo.edit_link(&args.source, &args.target, &edit)?;
+            print_link_set(&args.source, &args.target, EdgeField::Strength, &edge.strength.to_string());
+            Ok(())
+        }
+
+        LinkCommands::SetDescription(args) => {
+            let edit = EdgeEdit { description: Some(args.value), ..Default::default() };
+            let edge = repo.edit_link(&args.source, &args.target, &edit)?;
+            let value = edge.description.unwrap_or_else(|| t!("link.empty_value"));
+            print_link_set(&args.source, &args.target, EdgeField::Description, &value);
+            Ok(())
+        }
+
+        LinkCommands::SetRelation(args) => {
+            let relation = parse_link_relation(&args.value)
+                .ok_or_else(|| invalid(t!("link.unknown_relation", relation = args.value)))?;
+            let edit = EdgeEdit { relation: Some(relation), ..Default::default() };
+            let edge = repo.edit_link(&args.source, &args.target, &edit)?;
+            print_link_set(&args.source, &args.target, EdgeField::Relation, &edge.relation.to_string());
+            Ok(())
+        }
+
+        LinkCommands::Suggestions(args) => link_suggestions(&repo, args),
+
+        LinkCommands::Refresh(args) => {
+            let refreshed = repo.refresh_links(args.write_files)?;
+            if refreshed.is_empty() {
+                println!("{}", t!("link.refresh.none"));
+                return Ok(());
+            }
+
+            let count: usize = refreshed.iter().map(|file| file.changes.len()).sum();
+            let mut out = String::new();
+            for file in &refreshed {
+                let _ = writeln!(out, "{}", file.path.display());
+                for change in &file.changes {
+                    let _ = writeln!(out, "  {} -> {}", change.old, change.new);
+                }
+            }
+            output::maybe_page(&out);
+            if args.write_files {
+                println!("{}", t!("link.refresh.done", count = count, files = refreshed.len()));
+            } else {
+                println!("{}", t!("link.refresh.preview", count = count, files = refreshed.len()));
+            }
+            Ok(())
+        }
+
+        LinkCommands::Propose(args) => {
+            use crate::core::suggest;
+
+            let proposals = suggest::propose_filtered(storage.graph(), args.min_shared_tags, args.include_archived)?;
+            if proposals.is_empty() {
+                println!("{}", t!("propose.none"));
+                return Ok(());
+            }
+
+            let mut out = String::new();
+            let _ = writeln!(out, "{}", t!("propose.header", count = proposals.len()));
+            for proposal in &proposals {
+                let reasons: Vec<String> = proposal.reasons.iter().map(|r| r.to_string()).collect();
+                let _ = writeln!(
+                    out,
+                    "  {:.2}  {} --> {}  ({})",
+                    proposal.confidence,
+                    proposal.from,
+                    proposal.to,
+                    reasons.join("; ")
+                );
+            }
+
+            if args.apply {
+                print!("{}", out);
+                let edges = suggest::apply(storage.graph(), &proposals)?;
+                println!("\n{}", t!("propose.applied", count = edges.len()));
+            } else {
+                output::maybe_page(&out);
+            }
+            Ok(())
+        }
+    }
+}
+
+pub fn tag_operations(args: TagArgs) -> Result<()> {
+    use crate::core::retag::TagOp;
+
+    let (op, write_files, dry_run) = match args.command {
+        TagCommands::Promote(args) => return tag_promote(args),
+        TagCommands::Rename(args) => (TagOp::rename(&args.old, &args.new, args.recursive)?, args.write_files, args.dry_run),
+        TagCommands::Remove(args) => (TagOp::remove(&args.tag, args.recursive)?, args.write_files, args.dry_run),
+    };
+
+    let mut repo = open_repository(Path::new("."))?;
+    if dry_run {
+        repo.enable_dry_run();
+    }
+    let summary = repo.retag(&op, write_files)?;
+
+    if summary.nodes.is_empty() && summary.files.is_empty() {
+        println!("{}", t!("tag.none", tag = op.from));
+        return Ok(());
+    }
+    match &op.to {
+        Some(to) => println!("{}", t!("tag.renamed", old = op.from, new = to, count = summary.nodes.len())),
+        None => println!("{}", t!("tag.removed", tag = op.from, count = summary.nodes.len())),
+    }
+    for id in &summary.nodes {
+        println!("  {}", crate::core::review::node_path(id).unwrap_or_else(|| id.clone()));
+    }
+    if write_files {
+        println!("{}", t!("tag.files_rewritten", count = summary.files.len()));
+        for path in &summary.files {
+            println!("  {}", path.display());
+        }
+    }
+    if dry_run {
+        print_dry_run(repo.storage()?);
+    }
+    Ok(())
+}
+
+fn tag_promote(args: TagPromoteArgs) -> Result<()> {
+    let node_type = args.node_type.as_deref().map(parse_node_type).transpose()?;
+    let relation = parse_link_relation(&args.relation)
+        .ok_or_else(|| invalid(t!("link.unknown_relation", relation = args.relation)))?;
+
+    let repo = open_repository(Path::new("."))?;
+    let promotion = repo.promote_tag(&args.tag, node_type, relation, args.consume, args.write_files)?;
+
+    println!("{}", t!("tag.promoted", tag = args.tag, path = promotion.path.display()));
+    println!(
+        "{}",
+        t!("tag.promoted_counts", edges = promotion.edges, derivations = promotion.derivations, untagged = promotion.untagged)
+    );
+    for id in &promotion.tagged {
+        println!("  {}", crate::core::review::node_path(id).unwrap_or_else(|| id.clone()));
+    }
+    if args.write_files {
+        println!("{}", t!("tag.files_rewritten", count = promotion.files.len()));
+        for path in &promotion.files {
+            println!("  {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+pub fn export(args: ExportArgs) -> Result<()> {
+    use crate::storage::{csv, ndjson};
+    use anyhow::Context;
+
+    let mut repo = open_repository(Path::new("."))?;
+    repo.enable_node_cache();
+    let storage = repo.storage()?;
+
+    let format = args.format.to_lowercase();
+    if matches!(format.as_str(), "nquads" | "json" | "dot") {
+        return export_graph(&repo, &args, &format);
+    }
+    if args.tag.is_some() || args.query.is_some() {
+        return Err(invalid(t!("export.subgraph_format", format = format)));
+    }
+
+    match format.as_str() {
+        "csv" => {
+            if args.nodes.is_none() && args.edges.is_none() {
+                return Err(invalid(t!("export.nothing")));
+            }
+            if let Some(path) = &args.nodes {
+                let count = csv::export_nodes_csv(storage.graph(), path)?;
+                println!("{}", t!("export.nodes", count = count, path = path.display()));
+            }
+            if let Some(path) = &args.edges {
+                let count = csv::export_edges_csv(storage.graph(), path)?;
+                println!("{}", t!("export.edges", count = count, path = path.display()));
+            }
+            Ok(())
+        }
+        "ndjson" => {
+            let since = args
+                .since
+                .as_deref()
+                .map(|s| {
+                    crate::core::parser::frontmatter::parse_datetime(s)
+                        .ok_or_else(|| invalid(t!("export.invalid_since", value = s)))
+                })
+                .transpose()?;
+            let options = ndjson::NdjsonExportOptions {
+                nodes: !args.edges_only,
+                edges: !args.nodes_only,
+                since,
+            };
+            match &args.output {
+                Some(path) => {
+                    let file = std::fs::File::create(path).with_context(|| format!("Failed to create {:?}", path))?;
+                    let summary = ndjson::write_ndjson(storage.graph(), std::io::BufWriter::new(file), options)?;
+                    println!(
+                        "{}",
+                        t!("export.ndjson", nodes = summary.nodes, edges = summary.edges, path = path.display())
+                    );
+                }
+                // 数据流写到 stdout，不输出汇总以免混入
+                None => {
+                    let stdout = std::io::stdout();
+                    ndjson::write_ndjson(storage.graph(), std::io::BufWriter::new(stdout.lock()), options)?;
+                }
+            }
+            Ok(())
+        }
+        "corpus" => {
+            let Some(dir) = &args.dir else {
+                return Err(invalid(t!("export.corpus_dir")));
+            };
+            let chunk = args
+                .chunk
+                .map(|words| crate::core::corpus::ChunkOptions::new(words, args.overlap.unwrap_or(words / 10)))
+                .transpose()?;
+            let summary = repo.export_corpus(dir, chunk)?;
+            println!(
+                "{}",
+                t!("export.corpus", notes = summary.notes, files = summary.files, path = dir.display())
+            );
+            Ok(())
+        }
+        other => Err(invalid(t!("export.unsupported", format = other))),
+    }
+}
+
+/// `graph show --json` 的输出格式，`memexia export --format json` 沿用
+///
+/// 子图边界外的存根节点带 `"stub": true`
+pub(super) fn graph_json(nodes: &[&Node], edges: &[crate::storage::Edge], is_stub: impl Fn(&str) -> bool) -> serde_json::Value {
+    json!({
+        "nodes": nodes.iter().map(|n| {
+            let mut value = json!({
+                "id": n.id,
+                "type": format!("{:?}", n.node_type),
+                "label": n.title
+            });
+            if is_stub(&n.id) {
+                value["stub"] = json!(true);
+            }
+            value
+        }).collect::<Vec<_>>(),
+        "edges": edges.iter().map(|e| json!({
+            "from": e.from,
+            "to": e.to,
+            "type": e.relation.to_string(),
+            "strength": e.strength
+        })).collect::<Vec<_>>()
+    })
+}
+
+/// `graph layout` 和 `GET /api/layout` 的输出：`graph show --json` 的格式，节点另带 `x`、`y`
+///
+/// # Arguments
+///
+/// * `should_stop` - 传给 [`layout::force_layout`](crate::core::layout::force_layout)，返回 true 时中止
+pub(super) fn graph_layout_json(
+    graph: &dyn crate::storage::GraphStorage,
+    options: &crate::core::layout::LayoutOptions,
+    should_stop: &dyn Fn() -> bool,
+) -> crate::error::Result<serde_json::Value> {
+    let nodes = graph.get_all_nodes()?;
+    let edges = graph.get_all_edges()?;
+    let ids: Vec<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+    let positions = crate::core::layout::force_layout(&ids, &edges, options, should_stop)?;
+
+    let nodes: Vec<&Node> = nodes.iter().collect();
+    let mut value = graph_json(&nodes, &edges, |_| false);
+    if let Some(entries) = value["nodes"].as_array_mut() {
+        for (entry, position) in entries.iter_mut().zip(&positions) {
+            entry["x"] = json!(position.x);
+            entry["y"] = json!(position.y);
+        }
+    }
+    Ok(value)
+}
+
+/// 导出整个图或 `--tag` / `--query` 选出的子图（nquads、json、dot）
+fn export_graph(repo: &Repository, args: &ExportArgs, format: &str) -> Result<()> {
+    use crate::core::subgraph::{self, Subgraph};
+    use crate::storage::{dot, nquads};
+    use anyhow::Context;
+
+    let graph = repo.storage()?.graph();
+    let ids = match (&args.tag, &args.query) {
+        (Some(tag), _) => Some(subgraph::select_by_tag(graph, tag)?),
+        (None, Some(query)) => Some(subgraph::select_by_query(graph, query)?),
+        (None, None) => None,
+    };
+    let subgraph = match &ids {
+        Some(ids) => subgraph::extract(graph, ids, args.include_boundary)?,
+        None => Subgraph { nodes: graph.list_nodes()?, stubs: Vec::new(), edges: graph.list_edges()? },
+    };
+    let is_stub = |id: &str| subgraph.is_stub(id);
+
+    let content = match format {
+        "nquads" => {
+            let nodes: Vec<Node> = subgraph.all_nodes().into_iter().cloned().collect();
+            let mut buffer = Vec::new();
+            nquads::write_nquads(&nodes, &subgraph.edges, &mut buffer)?;
+            String::from_utf8(buffer)?
+        }
+        "json" => serde_json::to_string_pretty(&graph_json(&subgraph.all_nodes(), &subgraph.edges, is_stub))? + "\n",
+        _ => dot::render_graph(&subgraph.all_nodes(), &subgraph.edges, is_stub),
+    };
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, content).with_context(|| format!("Failed to write {:?}", path))?;
+            println!(
+                "{}",
+                t!(
+                    "export.subgraph",
+                    nodes = subgraph.nodes.len(),
+                    stubs = subgraph.stubs.len(),
+                    edges = subgraph.edges.len(),
+                    path = path.display()
+                )
+            );
+        }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
+
+pub fn import(args: ImportArgs) -> Result<()> {
+    use crate::storage::{csv, ndjson};
+    use anyhow::Context;
+
+    let mut repo = open_repository(Path::new("."))?;
+    if args.dry_run {
+        repo.enable_dry_run();
+    }
+    let storage = repo.storage()?;
+
+    let summary = match args.format.to_lowercase().as_str() {
+        "csv" => {
+            if args.nodes.is_none() && args.edges.is_none() {
+                return Err(invalid(t!("import.nothing")));
+            }
+            let summary = csv::import_csv(
+                storage.graph(),
+                args.nodes.as_deref(),
+                args.edges.as_deref(),
+                args.create_placeholders,
+            )?;
+            (summary.nodes_imported, summary.edges_imported, summary.placeholders_created)
+        }
+        "ndjson" => {
+            let summary = match &args.input {
+                Some(path) => {
+                    let file = std::fs::File::open(path).with_context(|| format!("Failed to read {:?}", path))?;
+                    ndjson::read_ndjson(storage.graph(), std::io::BufReader::new(file), args.create_placeholders)
+                        .with_context(|| format!("Invalid ndjson file {:?}", path))?
+                }
+                None => ndjson::read_ndjson(storage.graph(), std::io::stdin().lock(), args.create_placeholders)?,
+            };
+            (summary.nodes_imported, summary.edges_imported, summary.placeholders_created)
+        }
+        other => return Err(invalid(t!("import.unsupported", format = other))),
+    };
+
+    let (nodes, edges, placeholders) = summary;
+    println!("{}", t!("import.header"));
+    println!("{}", t!("import.nodes", count = nodes));
+    println!("{}", t!("import.edges", count = edges));
+    if placeholders > 0 {
+        println!("{}", t!("import.placeholders", count = placeholders));
+    }
+    if args.dry_run {
+        print_dry_run(storage);
+    } else {
+        repo.discard_undo()?;
+        println!("{}", t!("undo.not_recorded", operation = "import"));
+    }
+    Ok(())
+}
+
+/// 导入外部相似度分数为 `AnalogousTo` 建议边
+pub fn import_similarity(args: ImportSimilarityArgs) -> Result<()> {
+    use crate::core::similarity_import;
+    use crate::core::undo::UndoJournal;
+    use anyhow::Context;
+
+    if !(0.0..=1.0).contains(&args.threshold) {
+        return Err(invalid(t!("suggestions.invalid_confidence", value = args.threshold)));
+    }
+    let content =
+        std::fs::read_to_string(&args.file).with_context(|| format!("Failed to read {:?}", args.file))?;
+    let rows = similarity_import::parse_scores(&content)?;
+
+    let repo = open_repository(Path::new("."))?;
+    let graph = repo.storage()?.graph();
+    let plan = similarity_import::plan(graph, &rows, args.threshold)?;
+
+    for (line, id) in &plan.unknown {
+        eprintln!("{}", t!("similarity.unknown_node", line = line, id = id));
+    }
+    println!(
+        "{}",
+        t!(
+            "similarity.summary",
+            created = plan.edges.len() - plan.updated,
+            updated = plan.updated,
+            below = plan.below_threshold,
+            linked = plan.already_linked,
+            duplicates = plan.duplicates,
+            unknown = plan.unknown.len()
+        )
+    );
+    if args.dry_run || plan.edges.is_empty() {
+        return Ok(());
+    }
+
+    let mut journal = UndoJournal::new("import-similarity");
+    for edge in &plan.edges {
+        journal.capture_edge(graph, &edge.id)?;
+    }
+    similarity_import::apply(graph, &plan)?;
+    repo.record_undo(&journal)?;
+    println!("{}", t!("similarity.review_hint"));
+    Ok(())
+}
+
+/// 构造输入校验错误（对应退出码 4）
+/// 管理建议链接（AI 推荐或推导得出）
+fn link_suggestions(repo: &Repository, args: SuggestionsArgs) -> Result<()> {
+    use crate::core::suggestions;
+    use crate::core::undo::UndoJournal;
+
+    let graph = repo.storage()?.graph();
+    match args.command {
+        SuggestionCommands::List => {
+            let edges = suggestions::list(graph)?;
+            if edges.is_empty() {
+                println!("{}", t!("suggestions.none"));
+                return Ok(());
+            }
+
+            let mut out = String::new();
+            let _ = writeln!(out, "{}", t!("suggestions.header", count = edges.len()));
+            for edge in &edges {
+                let _ = writeln!(
+                    out,
+                    "  {:.2}  {:<8} {} --[{:?}]--> {}",
+                    edge.confidence, edge.source.to_string(), edge.from, edge.relation, edge.to
+                );
+                let _ = writeln!(out, "        {}", edge.id);
+            }
+            output::maybe_page(&out);
+        }
+        SuggestionCommands::Accept(args) => {
+            let mut journal = UndoJournal::new("link suggestions accept");
+            journal.capture_edge(graph, &args.edge_id)?;
+            let edge = suggestions::accept(graph, &args.edge_id)?;
+            repo.record_undo(&journal)?;
+            println!("{}", t!("suggestions.accepted", from = edge.from, to = edge.to));
+        }
+        SuggestionCommands::Reject(args) => {
+            let mut journal = UndoJournal::new("link suggestions reject");
+            journal.capture_edge(graph, &args.edge_id)?;
+            let edge = suggestions::reject(graph, &args.edge_id)?;
+            repo.record_undo(&journal)?;
+            println!("{}", t!("suggestions.rejected", from = edge.from, to = edge.to));
+        }
+        SuggestionCommands::AcceptAll(args) => {
+            if !(0.0..=1.0).contains(&args.min_confidence) {
+                return Err(invalid(t!("suggestions.invalid_confidence", value = args.min_confidence)));
+            }
+            let mut journal = UndoJournal::new("link suggestions accept-all");
+            for edge in suggestions::list(graph)?.iter().filter(|e| e.confidence >= args.min_confidence) {
+                journal.capture_edge(graph, &edge.id)?;
+            }
+            let accepted = suggestions::accept_all(graph, args.min_confidence)?;
+            repo.record_undo(&journal)?;
+            println!(
+                "{}",
+                t!(
+                    "suggestions.accepted_all",
+                    count = accepted.len(),
+                    min = args.min_confidence
+                )
+            );
+        }
+    }
+    Ok(())
+}
+
+/// 解析命令行中的节点类型（不区分大小写）
+fn parse_node_type(value: &str) -> Result<NodeType> {
+    match value.to_lowercase().as_str() {
+        "concept" => Ok(NodeType::Concept),
+        "question" => Ok(NodeType::Question),
+        "evidence" => Ok(NodeType::Evidence),
+        "resource" => Ok(NodeType::Resource),
+        "person" => Ok(NodeType::Person),
+        "event" => Ok(NodeType::Event),
+        "meta" => Ok(NodeType::Meta),
+        _ => Err(invalid(t!("new.unknown_type", node_type = value))),
+    }
+}
+
+fn invalid(message: impl Into<String>) -> anyhow::Error {
+    MemexiaError::Validation(message.into()).into()
+}
+
+pub fn stats(args: StatsArgs) -> Result<()> {
+    use crate::core::stats::{sparkline, MonthCount};
+
+    let mut repo = open_repository(Path::new("."))?;
+    repo.enable_node_cache();
+    if args.history {
+        return stats_history(&repo, args.json);
+    }
+    let stats = repo.stats_filtered(args.include_archived)?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "{}", t!("stats.header"));
+    let _ = writeln!(out, "{}", t!("stats.notes", count = output::format_count(stats.notes)));
+    let _ = writeln!(
+        out,
+        "{}",
+        t!("stats.total_words", count = output::format_count(stats.total_words as usize))
+    );
+    let _ = writeln!(
+        out,
+        "{}",
+        t!("stats.average_words", count = format!("{:.1}", stats.average_words))
+    );
+    let _ = writeln!(out, "{}", t!("stats.tags", count = output::format_count(stats.tags)));
+    if !stats.languages.is_empty() {
+        let languages: Vec<String> = stats
+            .languages
+            .iter()
+            .map(|(language, count)| format!("{} {}", language, output::format_count(*count)))
+            .collect();
+        let _ = writeln!(out, "{}", t!("stats.languages", languages = languages.join(", ")));
+    }
+
+    let sections = [
+        (t!("stats.notes_per_month"), &stats.notes_per_month),
+        (t!("stats.commits_per_month"), &stats.commits_per_month),
+    ];
+    for (title, months) in sections {
+        let _ = writeln!(out);
+        if months.is_empty() {
+            let _ = writeln!(out, "{}: {}", title, t!("stats.no_data"));
+            continue;
+        }
+
+        let counts: Vec<usize> = months.iter().map(|m| m.count).collect();
+        let _ = writeln!(out, "{}: {}", title, sparkline(&counts));
+
+        // 柱状条按最大值缩放到剩余宽度
+        let max = counts.iter().copied().max().unwrap_or(0).max(1);
+        let bar_width = output::terminal_width().saturating_sub(20).clamp(10, 40);
+        for MonthCount { month, count } in months {
+            let bar = "█".repeat(count * bar_width / max);
+            let _ = writeln!(out, "  {}  {:<width$} {}", month, bar, count, width = bar_width);
+        }
+    }
+    output::maybe_page(&out);
+
+    Ok(())
+}
+
+/// 输出每个提交的图规模（`memexia stats --history`）
+fn stats_history(repo: &Repository, json: bool) -> Result<()> {
+    let points = repo.stats_history()?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&points)?);
+        return Ok(());
+    }
+    if points.is_empty() {
+        println!("{}", t!("stats.history_empty"));
+        return Ok(());
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "{}", t!("stats.history_header"));
+    for point in &points {
+        let date = point
+            .recorded_at
+            .map(|at| at.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let Some(stats) = &point.stats else {
+            let _ = writeln!(out, "{}  {:<16}  {}", short_hash(&point.commit), date, t!("stats.history_pruned"));
+            continue;
+        };
+        let types: Vec<String> = stats.types.iter().map(|(name, count)| format!("{} {}", name, count)).collect();
+        let _ = writeln!(
+            out,
+            "{}  {:<16}  {:>7} {:>7}  {}",
+            short_hash(&point.commit),
+            date,
+            output::format_count(stats.nodes),
+            output::format_count(stats.edges),
+            types.join(", ")
+        );
+    }
+    output::maybe_page(&out);
+    Ok(())
+}
+
+pub fn dashboard(args: DashboardArgs) -> Result<()> {
+    use crate::core::dashboard::Metric;
+
+    let mut repo = open_repository(Path::new("."))?;
+    repo.enable_node_cache();
+    let dashboard = repo.dashboard()?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&dashboard)?);
+        return Ok(());
+    }
+
+    let percent = |ratio: f64| format!("{:.0}", ratio * 100.0);
+    let mut out = String::new();
+    let _ = writeln!(out, "{}", t!("dashboard.header", score = dashboard.score));
+    let _ = writeln!(
+        out,
+        "{}",
+        t!(
+            "dashboard.totals",
+            notes = output::format_count(dashboard.counts.notes),
+            edges = output::format_count(dashboard.counts.edges)
+        )
+    );
+    for check in &dashboard.checks {
+        let label = match check.metric {
+            Metric::Orphans => t!("dashboard.orphans"),
+            Metric::Dangling => t!("dashboard.dangling"),
+            Metric::OpenQuestions => t!("dashboard.open_questions"),
+            Metric::Stale => t!("dashboard.stale", days = dashboard.stale_days),
+        };
+        let _ = writeln!(
+            out,
+            "{}",
+            t!(
+                "dashboard.check",
+                mark = if check.ok { "✓" } else { "⚠" },
+                label = label,
+                count = output::format_count(check.count),
+                total = output::format_count(check.total),
+                percent = percent(check.ratio),
+                limit = percent(check.threshold)
+            )
+        );
+    }
+
+    let tags = if dashboard.top_tags.is_empty() {
+        t!("dashboard.none")
+    } else {
+        let tags: Vec<String> = dashboard.top_tags.iter().map(|t| format!("#{} ({})", t.tag, t.count)).collect();
+        tags.join(", ")
+    };
+    let _ = writeln!(out, "{}", t!("dashboard.top_tags", tags = tags));
+    match &dashboard.last_commit {
+        Some(commit) => {
+            let _ = writeln!(
+                out,
+                "{}",
+                t!(
+                    "dashboard.last_commit",
+                    oid = short_hash(&commit.oid),
+                    summary = commit.summary,
+                    author = commit.author,
+                    timestamp = commit.timestamp
+                )
+            );
+        }
+        None => {
+            let _ = writeln!(out, "{}", t!("dashboard.no_commits"));
+        }
+    }
+    output::maybe_page(&out);
+    Ok(())
+}
+
+pub fn feed(args: FeedArgs) -> Result<()> {
+    use crate::core::feed::FeedFormat;
+    use anyhow::Context;
+
+    let format = FeedFormat::parse(&args.format).ok_or_else(|| invalid(t!("feed.unsupported", format = args.format)))?;
+    let since = args
+        .since
+        .as_deref()
+        .map(|s| {
+            crate::core::parser::frontmatter::parse_datetime(s).ok_or_else(|| invalid(t!("feed.invalid_since", value = s)))
+        })
+        .transpose()?;
+
+    let repo = open_repository_lazy(Path::new("."))?;
+    let feed = repo.feed(since)?;
+    let xml = feed.render(format);
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, xml).with_context(|| format!("Failed to write {:?}", path))?;
+            println!("{}", t!("feed.written", count = feed.entries.len(), path = path.display()));
+        }
+        None => print!("{}", xml),
+    }
+    Ok(())
+}
+
+pub fn random(args: RandomArgs) -> Result<()> {
+    use crate::core::review::NoteFilter;
+
+    let filter = NoteFilter {
+        node_type: args.node_type.as_deref().map(parse_node_type).transpose()?,
+        tag: args.tag,
+    };
+
+    let repo = open_repository(Path::new("."))?;
+    match repo.random_note(&filter)? {
+        Some(item) => print!("{}", format_review_item(&item)),
+        None => println!("{}", t!("random.none")),
+    }
+    Ok(())
+}
+
+pub fn review(args: ReviewArgs) -> Result<()> {
+    use crate::core::review;
+    use std::io::{BufRead, IsTerminal};
+
+    let repo = open_repository(Path::new("."))?;
+
+    if let Some(path) = args.mark_reviewed {
+        let node = repo.mark_reviewed(&path)?;
+        println!("{}", t!("review.marked", title = node.title));
+        return Ok(());
+    }
+
+    let queue = repo.review_queue(args.limit, args.interval)?;
+    if queue.is_empty() {
+        println!("{}", t!("review.empty"));
+        return Ok(());
+    }
+
+    // 非交互环境只输出队列
+    if !(std::io::stdin().is_terminal() && std::io::stdout().is_terminal()) {
+        let mut out = String::new();
+        let _ = writeln!(out, "{}", t!("review.header", count = queue.len()));
+        for item in &queue {
+            let _ = writeln!(out);
+            out.push_str(&format_review_item(item));
+        }
+        output::maybe_page(&out);
+        return Ok(());
+    }
+
+    println!("{}", t!("review.header", count = queue.len()));
+    let mut reviewed = 0;
+    let mut lines = std::io::stdin().lock().lines();
+    for item in &queue {
+        println!();
+        print!("{}", format_review_item(item));
+        print!("{} ", t!("review.prompt"));
+        std::io::Write::flush(&mut std::io::stdout())?;
+
+        let answer = match lines.next() {
+            Some(line) => line?,
+            None => break,
+        };
+        match answer.trim().to_lowercase().as_str() {
+            "y" | "yes" => {
+                review::mark_reviewed(repo.storage()?.graph(), &item.node.id, chrono::Utc::now())?;
+                reviewed += 1;
+            }
+            "q" | "quit" => break,
+            _ => {}
+        }
+    }
+    println!("\n{}", t!("review.done", count = reviewed));
+    Ok(())
+}
+
+/// 格式化回顾条目：标题、路径、类型、反向链接数和摘要
+fn format_review_item(item: &crate::core::review::ReviewItem) -> String {
+    let width = output::terminal_width().saturating_sub(4).max(20);
+    let mut out = String::new();
+    let _ = writeln!(out, "{}", output::truncate_to_width(&item.node.title, width));
+    let _ = writeln!(out, "  {}", t!("review.path", path = item.path.as_deref().unwrap_or(&item.node.id)));
+    let _ = writeln!(
+        out,
+        "  {}",
+        t!(
+            "review.meta",
+            node_type = item.node.node_type,
+            backlinks = item.backlinks,
+            updated = timefmt::format_time(&item.node.updated_at)
+        )
+    );
+    if let Some(summary) = item.summary() {
+        let _ = writeln!(out, "  {}", output::truncate_to_width(summary, width));
+    }
+    out
+}
+
+pub fn validate(args: ValidateArgs) -> Result<()> {
+    use crate::core::validate::Severity;
+    use super::exit::{Exit, ExitStatus};
+
+    let min = Severity::parse(&args.severity)
+        .ok_or_else(|| invalid(t!("validate.invalid_severity", value = args.severity)))?;
+
+    let repo = open_repository(Path::new("."))?;
+    let report = repo.validate()?.filter(min);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else if report.rules.is_empty() {
+        println!("{}", t!("validate.clean"));
+    } else {
+        let mut out = String::new();
+        for rule in &report.rules {
+            let _ = writeln!(out, "{} - {}", rule.rule, rule.description);
+            for violation in &rule.violations {
+                let _ = writeln!(out, "  {:<8} {}: {}", violation.severity.to_string(), violation.target, violation.message);
+            }
+            let _ = writeln!(out);
+        }
+        let _ = writeln!(
+            out,
+            "{}",
+            t!(
+                "validate.summary",
+                errors = report.count(Severity::Error),
+                warnings = report.count(Severity::Warning)
+            )
+        );
+        output::maybe_page(&out);
+    }
+
+    // 退出码反映最严重的违规
+    match report.worst() {
+        Some(Severity::Error) => Err(Exit(ExitStatus::Validation).into()),
+        Some(Severity::Warning) => Err(Exit(ExitStatus::Warnings).into()),
+        None => Ok(()),
+    }
+}
+
+pub fn lint(args: LintArgs) -> Result<()> {
+    use crate::core::validate::Severity;
+    use super::exit::{Exit, ExitStatus};
+
+    let repo = open_repository(Path::new("."))?;
+    let report = repo.lint(&args.paths)?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else if report.diagnostics.is_empty() {
+        println!("{}", t!("lint.clean", files = report.files));
+    } else {
+        // `路径:行号: 级别 [规则] 说明`，与编译器诊断格式一致，便于编辑器跳转
+        let mut out = String::new();
+        for diagnostic in &report.diagnostics {
+            let _ = writeln!(
+                out,
+                "{}:{}: {} [{}] {}",
+                diagnostic.path, diagnostic.line, diagnostic.severity, diagnostic.rule, diagnostic.message
+            );
+        }
+        let _ = writeln!(
+            out,
+            "\n{}",
+            t!(
+                "lint.summary",
+                errors = report.count(Severity::Error),
+                warnings = report.count(Severity::Warning),
+                files = report.files
+            )
+        );
+        output::maybe_page(&out);
+    }
+
+    // 退出码反映最严重的诊断
+    match report.worst() {
+        Some(Severity::Error) => Err(Exit(ExitStatus::Validation).into()),
+        Some(Severity::Warning) => Err(Exit(ExitStatus::Warnings).into()),
+        None => Ok(()),
+    }
+}
+
+pub fn report(args: ReportArgs) -> Result<()> {
+    match args.command {
+        ReportCommands::Duplicates(args) => report_duplicates(args),
+        ReportCommands::Contradictions(args) => report_contradictions(args),
+    }
+}
+
+fn report_duplicates(args: ReportDuplicatesArgs) -> Result<()> {
+    use crate::core::duplicates::{DuplicatePair, DuplicateReason};
+
+    let repo = open_repository(Path::new("."))?;
+    let pairs = repo.find_duplicates(args.content.then_some(args.threshold))?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&pairs)?);
+        return Ok(());
+    }
+    if pairs.is_empty() {
+        println!("{}", t!("report.duplicates.none"));
+        return Ok(());
+    }
+
+    // 含空白的路径在建议命令中加引号
+    let quote = |path: &str| {
+        if path.contains(char::is_whitespace) {
+            format!("\"{}\"", path)
+        } else {
+            path.to_string()
+        }
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(out, "{}", t!("report.duplicates.header", count = pairs.len()));
+    for pair in &pairs {
+        let (a, b) = (DuplicatePair::display(&pair.a), DuplicatePair::display(&pair.b));
+        let reason = match pair.reason {
+            DuplicateReason::Title => t!("report.duplicates.title"),
+            DuplicateReason::Content => t!("report.duplicates.content"),
+        };
+        let _ = writeln!(out);
+        let _ = writeln!(out, "{:.2}  {}  <->  {}  ({})", pair.score, a, b, reason);
+        let _ = writeln!(
+            out,
+            "      {}: memexia node merge {} {}",
+            t!("report.duplicates.merge"),
+            quote(&a),
+            quote(&b)
+        );
+    }
+    output::maybe_page(&out);
+    Ok(())
+}
+
+fn report_contradictions(args: ReportContradictionsArgs) -> Result<()> {
+    use crate::core::contradictions::ContradictionCluster;
+
+    let repo = open_repository(Path::new("."))?;
+    let clusters = repo.find_contradictions(args.min_strength)?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&clusters)?);
+        return Ok(());
+    }
+    if clusters.is_empty() {
+        println!("{}", t!("report.contradictions.none"));
+        return Ok(());
+    }
+
+    let display = |id: &str| ContradictionCluster::display(id);
+    let mut out = String::new();
+    let _ = writeln!(out, "{}", t!("report.contradictions.header", count = clusters.len()));
+    for (i, cluster) in clusters.iter().enumerate() {
+        let _ = writeln!(out);
+        let _ = writeln!(
+            out,
+            "{}",
+            t!(
+                "report.contradictions.cluster",
+                index = i + 1,
+                strength = format!("{:.2}", cluster.total_strength)
+            )
+        );
+        let notes: Vec<String> = cluster.nodes.iter().map(|id| display(id)).collect();
+        let _ = writeln!(out, "  {}: {}", t!("report.contradictions.notes"), notes.join(", "));
+        for contradiction in &cluster.contradictions {
+            let _ = writeln!(
+                out,
+                "  {:.2}  {}  {}  {}",
+                contradiction.strength,
+                display(&contradiction.from),
+                t!("report.contradictions.contradicts"),
+                display(&contradiction.to)
+            );
+            if let Some(description) = &contradiction.description {
+                let _ = writeln!(out, "        {}", description);
+            }
+            for support in &contradiction.supports {
+                let _ = writeln!(
+                    out,
+                    "        {}",
+                    t!(
+                        "report.contradictions.support",
+                        from = display(&support.from),
+                        to = display(&support.to),
+                        strength = format!("{:.2}", support.strength)
+                    )
+                );
+            }
+        }
+    }
+    output::maybe_page(&out);
+    Ok(())
+}
+
+pub fn publish(args: PublishArgs) -> Result<()> {
+    let repo = open_repository(Path::new("."))?;
+    let summary = repo.publish_filtered(&args.out_dir, args.include_archived)?;
+
+    println!(
+        "{}",
+        t!("publish.done", pages = summary.pages, tags = summary.tags, path = args.out_dir.display())
+    );
+    if summary.excluded > 0 {
+        println!("{}", t!("publish.excluded", count = summary.excluded));
+    }
+    Ok(())
+}
+
+/// 归档笔记
+pub fn archive(args: ArchiveArgs) -> Result<()> {
+    let repo = open_repository(Path::new("."))?;
+    let outcome = repo.archive(&args.path)?;
+    println!("{}", t!("archive.done", from = outcome.from, to = outcome.to));
+    Ok(())
+}
+
+/// 取消归档笔记
+pub fn unarchive(args: ArchiveArgs) -> Result<()> {
+    let repo = open_repository(Path::new("."))?;
+    let outcome = repo.unarchive(&args.path)?;
+    println!("{}", t!("archive.restored", from = outcome.from, to = outcome.to));
+    Ok(())
+}
+
+pub fn watch(args: WatchArgs) -> Result<()> {
+    use crate::core::auto_snapshot::{parse_interval, SnapshotScheduler};
+    use crate::core::watch_journal::{self, WatchJournal};
+    use crate::core::{FileWatcher, FileWatcherConfig, Indexer, RepoConfig};
+    use std::sync::mpsc::RecvTimeoutError;
+    use std::time::{Duration, Instant};
+
+    let repo = open_repository(Path::new("."))?;
+    let root = repo.path().to_path_buf();
+    let config = RepoConfig::load(&root)?;
+
+    // 命令行参数优先于配置
+    let interval = args
+        .snapshot_every
+        .as_deref()
+        .or(config.watch.auto_snapshot_interval.as_deref())
+        .map(parse_interval)
+        .transpose()?;
+    let keep = config.watch.auto_snapshot_keep;
+    let mut scheduler = interval.map(|interval| SnapshotScheduler::new(interval, keep, Instant::now()));
+
+    let mut indexer = Indexer::new(repo.storage()?.clone());
+    indexer.set_zettel_ids(config.ids.zettel);
+    indexer.set_extensions(config.index.extensions.clone());
+    indexer.set_decrypt_cmd(config.index.decrypt_cmd.clone());
+    indexer.set_size_limits(config.index.max_note_bytes, config.index.content_excerpt_bytes);
+    indexer.set_default_relation(config.links.default_relation.clone());
+    indexer.set_auto_summary(config.index.auto_summary);
+    indexer.set_note_roots(config.paths.walk_dirs());
+    indexer.set_folder_defaults(crate::core::folder_defaults::FolderDefaults::load(&root)?);
+
+    let mut watcher = FileWatcher::new(FileWatcherConfig {
+        watch_config: indexer.config().clone(),
+        ..Default::default()
+    })?;
+    watcher.watch(&root)?;
+
+    // 先开始监听再补扫，补扫期间的变更由事件处理
+    let (mut journal, cursor) = WatchJournal::start_session(&root, chrono::Utc::now())?;
+    let caught_up = watch_journal::catch_up(&indexer, &root, cursor)?;
+    if caught_up.files_indexed > 0 || caught_up.files_deleted > 0 {
+        println!(
+            "{}",
+            t!("watch.caught_up", indexed = caught_up.files_indexed, deleted = caught_up.files_deleted)
+        );
+    }
+    for (path, error) in &caught_up.errors {
+        eprintln!("{}", t!("watch.index_failed", path = path, error = error));
+    }
+
+    println!("{}", t!("watch.started", path = root.display()));
+    if let Some(interval) = interval {
+        let interval = output::format_seconds(interval);
+        println!("{}", t!("watch.snapshot_every", interval = interval, keep = keep));
+    }
+
+    loop {
+        // 没有自动快照时只等待文件事件
+        let timeout = scheduler
+            .as_ref()
+            .map_or(Duration::from_secs(3600), |s| s.time_until_due(Instant::now()));
+
+        // 本轮开始前的变更在本轮结束时已全部处理
+        let waiting_since = chrono::Utc::now();
+        match watcher.receiver().recv_timeout(timeout) {
+            Ok(event) => {
+                if let Err(e) = indexer.handle_event(&event) {
+                    eprintln!("{}", t!("watch.index_failed", path = event.path(), error = e));
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                journal.checkpoint(&root, chrono::Utc::now())?;
+                return Ok(());
+            }
+        }
+        journal.checkpoint(&root, waiting_since)?;
+
+        if let Some(scheduler) = scheduler.as_mut() {
+            if let Some(link) = scheduler.tick(&repo.vcs()?.graph_history, repo.storage()?, Instant::now())? {
+                println!("{}", t!("snapshot.taken", key = link.commit_hash, hash = short_hash(&link.graph_hash)));
+            }
+        }
+    }
+}
+
+pub fn snapshot(args: SnapshotArgs) -> Result<()> {
+    let repo = open_repository(Path::new("."))?;
+
+    match args.command {
+        SnapshotCommands::Now => match repo.snapshot_now()? {
+            Some(link) => {
+                println!("{}", t!("snapshot.taken", key = link.commit_hash, hash = short_hash(&link.graph_hash)));
+            }
+            None => println!("{}", t!("snapshot.unchanged")),
+        },
+        SnapshotCommands::Verify(args) => {
+            use super::exit::{Exit, ExitStatus};
+
+            let report = repo.verify_snapshots(args.hash.as_deref(), args.repair_meta)?;
+            output::maybe_page(&format_verify_report(&report, true));
+            if !report.is_ok() {
+                if !args.repair_meta && report.snapshots.iter().flat_map(|s| &s.problems).any(|p| p.is_meta()) {
+                    println!("{}", t!("snapshot.verify.repair_hint"));
+                }
+                return Err(Exit(ExitStatus::Warnings).into());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 渲染快照校验报告：逐个快照的状态、悬空的提交关联和汇总
+///
+/// `show_ok` 为 false 时省略没有问题的快照（`memexia doctor`）
+fn format_verify_report(report: &crate::vcs::snapshot_verify::VerifyReport, show_ok: bool) -> String {
+    let mut out = String::new();
+    for status in &report.snapshots {
+        let hash = short_hash(&status.hash);
+        if let Some(problem) = &status.repaired {
+            let _ = writeln!(out, "  {}", t!("snapshot.verify.repaired", hash = hash, problem = snapshot_problem(problem)));
+        }
+        if status.is_ok() {
+            if show_ok && status.repaired.is_none() {
+                let _ = writeln!(out, "  {}", t!("snapshot.verify.ok", hash = hash));
+            }
+            continue;
+        }
+        for problem in &status.problems {
+            let _ = writeln!(out, "  {}", t!("snapshot.verify.failed", hash = hash, problem = snapshot_problem(problem)));
+        }
+    }
+    for link in &report.dangling_links {
+        let _ = writeln!(
+            out,
+            "  {}",
+            t!("snapshot.verify.dangling", commit = short_hash(&link.commit_hash), hash = short_hash(&link.graph_hash))
+        );
+    }
+    let _ = writeln!(
+        out,
+        "{}",
+        t!(
+            "snapshot.verify.summary",
+            count = report.snapshots.len(),
+            failed = report.failed(),
+            repaired = report.repaired(),
+            dangling = report.dangling_links.len()
+        )
+    );
+    out
+}
+
+fn snapshot_problem(problem: &crate::vcs::snapshot_verify::SnapshotProblem) -> String {
+    use crate::vcs::snapshot_verify::SnapshotProblem;
+
+    match problem {
+        SnapshotProblem::Unreadable { message } => t!("snapshot.problem.unreadable", error = message),
+        SnapshotProblem::HashMismatch { actual } => t!("snapshot.problem.hash_mismatch", actual = short_hash(actual)),
+        SnapshotProblem::InvalidNQuads { line, message } => {
+            t!("snapshot.problem.invalid_nquads", line = line, error = message)
+        }
+        SnapshotProblem::MissingMeta => t!("snapshot.problem.missing_meta"),
+        SnapshotProblem::StaleMeta { reason } => t!("snapshot.problem.stale_meta", reason = reason),
+    }
+}
+
+pub fn history(args: HistoryArgs) -> Result<()> {
+    let repo = open_repository(Path::new("."))?;
+
+    match args.command {
+        HistoryCommands::Export(args) => {
+            let manifest = repo.export_history(&args.file)?;
+            println!(
+                "{}",
+                t!(
+                    "history.exported",
+                    path = args.file.display(),
+                    nodes = manifest.nodes,
+                    snapshots = manifest.node_snapshots,
+                    derivations = manifest.derivations,
+                    links = manifest.commit_links
+                )
+            );
+        }
+        HistoryCommands::Import(args) => {
+            let report = repo.import_history(&args.file, args.merge)?;
+            if report.foreign_repository {
+                eprintln!("{}", t!("history.foreign_repository"));
+            }
+            if let Some(first) = report.missing_commits.first() {
+                eprintln!(
+                    "{}",
+                    t!("history.missing_commits", count = report.missing_commits.len(), commit = short_hash(first))
+                );
+            }
+            println!(
+                "{}",
+                t!(
+                    "history.imported",
+                    snapshots = report.node_snapshots,
+                    derivations = report.derivations,
+                    links = report.commit_links,
+                    skipped = report.skipped
+                )
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn derivation(args: DerivationArgs) -> Result<()> {
+    use crate::core::derivation_tree::{self, Direction};
+
+    let repo = open_repository(Path::new("."))?;
+
+    match args.command {
+        DerivationCommands::Tree(args) => {
+            let direction = Direction::parse(&args.direction)
+                .ok_or_else(|| invalid(t!("derivation.invalid_direction", value = args.direction)))?;
+            let traversal = repo.derivation_tree(&args.node, direction, args.depth)?;
+            let tree = traversal.value;
+            if args.dot {
+                print!("{}", derivation_tree::render_dot(&tree, repo.storage()?.graph())?);
+            } else {
+                print!("{}", derivation_tree::render_text(&tree));
+            }
+            print_traversal_warnings(&traversal.outcome);
+        }
+    }
+    Ok(())
+}
+
+/// 在 stderr 提示遍历触及的限制和发现的环
+fn print_traversal_warnings(outcome: &crate::vcs::TraversalOutcome) {
+    if outcome.depth_limit_hit {
+        eprintln!("{}", t!("traversal.depth_limit"));
+    }
+    if outcome.node_limit_hit {
+        eprintln!("{}", t!("traversal.node_limit"));
+    }
+    for cycle in &outcome.cycles {
+        eprintln!("{}", t!("traversal.cycle", members = cycle.join(" -> ")));
+    }
+}
+
+pub fn node(args: NodeArgs) -> Result<()> {
+    let repo = open_repository(Path::new("."))?;
+
+    match args.command {
+        NodeCommands::Restore(args) => {
+            let outcome = repo.restore_node(&args.path)?;
+            println!("{}", t!("node.restored", id = outcome.node_id));
+            if outcome.file_restored {
+                println!("{}", t!("node.file_restored", path = args.path.display()));
+            }
+        }
+        NodeCommands::Set(args) => {
+            use crate::core::writer::{NodeEdit, WriteBack};
+
+            let edit = NodeEdit {
+                title: args.title,
+                node_type: args.node_type.as_deref().map(parse_node_type).transpose()?,
+                add_tags: args.add_tags,
+                remove_tags: args.remove_tags,
+                summary: args.summary,
+                aliases: (!args.aliases.is_empty()).then_some(args.aliases),
+            };
+            if edit.is_empty() {
+                bail!("{}", t!("node.nothing_to_set"));
+            }
+            let outcome = repo.set_node(&args.target, &edit, args.write_file)?;
+            let id = &outcome.id;
+            match outcome.write_back {
+                Some(WriteBack::Conflict) => eprintln!("{}", t!("node.conflict", path = args.target)),
+                Some(WriteBack::Written) => println!("{}", t!("node.written", id = id, path = args.target)),
+                Some(WriteBack::Unchanged) | None => println!("{}", t!("node.updated", id = id)),
+            }
+            if outcome.write_back != Some(WriteBack::Conflict) {
+                for change in &outcome.changes {
+                    println!(
+                        "{}",
+                        t!("node.change", field = change.field, before = change.before, after = change.after)
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn gc(args: GcArgs) -> Result<()> {
+    use crate::core::auto_snapshot::parse_interval;
+
+    let mut repo = open_repository(Path::new("."))?;
+    if args.dry_run {
+        repo.enable_dry_run();
+    }
+
+    if !repo.is_read_only() {
+        let retention = repo.apply_retention()?;
+        if !retention.pruned.is_empty() {
+            let key = if args.dry_run { "gc.retention_dry_run" } else { "gc.retention" };
+            println!(
+                "{}",
+                t!(key, links = retention.pruned.len(), snapshots = retention.removed_snapshots.len())
+            );
+        }
+    }
+
+    if !args.purge_deleted {
+        let deleted = repo.deleted_nodes()?;
+        if deleted.is_empty() {
+            println!("{}", t!("gc.trash_empty"));
+            return Ok(());
+        }
+        println!("{}", t!("gc.trash_header", count = deleted.len()));
+        for node in &deleted {
+            let at = node.deleted_at().map(|at| at.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default();
+            println!("  {} ({})", node.id, at);
+        }
+        println!("{}", t!("gc.purge_hint"));
+        return Ok(());
+    }
+
+    let older_than = args
+        .older_than
+        .as_deref()
+        .map(parse_interval)
+        .transpose()?
+        .map(|age| chrono::Duration::from_std(age).map_err(|_| invalid(format!("Interval too large: {:?}", age))))
+        .transpose()?;
+    let purged = repo.purge_deleted(older_than)?;
+    if args.dry_run {
+        print_dry_run(repo.storage()?);
+        return Ok(());
+    }
+    for id in &purged {
+        println!("  {}", id);
+    }
+    println!("{}", t!("gc.purged", count = purged.len()));
+    Ok(())
+}
+
+pub fn undo(_args: UndoArgs) -> Result<()> {
+    let repo = open_repository(Path::new("."))?;
+    let journal = repo.undo()?;
+    println!(
+        "{}",
+        t!(
+            "undo.done",
+            operation = journal.operation,
+            time = journal.created_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S")
+        )
+    );
+    let restored = journal.restore_nodes.len() + journal.restore_edges.len();
+    let removed = journal.remove_nodes.len() + journal.remove_edges.len();
+    println!("{}", t!("undo.counts", restored = restored, removed = removed));
+    Ok(())
+}
+
+pub fn doctor(args: DoctorArgs) -> Result<()> {
+    use super::exit::{Exit, ExitStatus};
+
+    let dangling_strategy = match &args.fix_dangling {
+        Some(value) => Some(
+            DanglingStrategy::parse(value).ok_or_else(|| invalid(t!("doctor.invalid_dangling_strategy", value = value)))?,
+        ),
+        None => None,
+    };
+
+    // 图存储无法打开时仍检查快照，跳过其余需要图的检查
+    let (repo, corrupt) = match open_repository(Path::new(".")) {
+        Ok(repo) => (repo, None),
+        Err(err) => match err.downcast::<MemexiaError>() {
+            Ok(MemexiaError::StoreCorrupt { reason, .. }) => (open_repository_lazy(Path::new("."))?, Some(reason)),
+            Ok(err) => return Err(err.into()),
+            Err(err) => return Err(err),
+        },
+    };
+    let version = crate::storage::schema::read_version(repo.path())?;
+    let latest = crate::storage::schema::Migrator::default().latest();
+    println!("{}", t!("doctor.schema_version", version = version, latest = latest));
+
+    let mut problems = false;
+    let snapshots = repo.verify_snapshots(None, args.fix)?;
+    if snapshots.is_ok() && snapshots.repaired() == 0 {
+        println!("{}", t!("doctor.snapshots_ok", count = snapshots.snapshots.len()));
+    } else {
+        println!("{}", t!("doctor.snapshots"));
+        output::maybe_page(&format_verify_report(&snapshots, false));
+        problems |= !snapshots.is_ok();
+    }
+
+    if let Some(reason) = corrupt {
+        println!("{}", t!("doctor.store_corrupt", reason = reason));
+        println!("{}", t!("doctor.store_corrupt_hint"));
+        return Err(Exit(ExitStatus::Warnings).into());
+    }
+
+    let legacy = repo.legacy_ids()?;
+    if !legacy.is_empty() {
+        let mut out = String::new();
+        let _ = writeln!(out, "{}", t!("doctor.legacy_ids", count = legacy.len()));
+        for id in &legacy {
+            let _ = writeln!(out, "  {} -> {}", id.old, id.new);
+        }
+        output::maybe_page(&out);
+
+        if args.fix {
+            let summary = repo.migrate_legacy_ids(&legacy)?;
+            println!("{}", t!("doctor.fixed", nodes = summary.nodes, edges = summary.edges));
+        } else {
+            problems = true;
+        }
+    }
+
+    let title_index = repo.title_index_mismatches()?;
+    if !title_index.is_empty() {
+        let mut out = String::new();
+        let _ = writeln!(out, "{}", t!("doctor.title_index", count = title_index.len()));
+        for id in &title_index {
+            let _ = writeln!(out, "  {}", id);
+        }
+        output::maybe_page(&out);
+
+        if args.fix {
+            let fixed = repo.rebuild_title_index()?;
+            println!("{}", t!("doctor.title_index_fixed", count = fixed));
+        } else {
+            problems = true;
+        }
+    }
+
+    let dangling = repo.dangling_links()?;
+    if !dangling.is_empty() {
+        let mut out = String::new();
+        let _ = writeln!(out, "{}", t!("doctor.dangling", count = dangling.len()));
+        for edge in &dangling {
+            let _ = writeln!(out, "  {} --[{}]--> {}", edge.from, edge.relation, edge.to);
+        }
+        output::maybe_page(&out);
+
+        match dangling_strategy {
+            Some(strategy) if strategy != DanglingStrategy::ReportOnly => {
+                let repair = repo.repair_dangling_links(strategy)?;
+                println!(
+                    "{}",
+                    t!("doctor.dangling_fixed", placeholders = repair.placeholders, dropped = repair.dropped)
+                );
+            }
+            _ => {
+                println!("{}", t!("doctor.dangling_hint"));
+                problems = true;
+            }
+        }
+    }
+
+    // 冗余的逆关系边无法判断保留哪一条，只提示
+    let redundant = repo.redundant_inverse_links()?;
+    if !redundant.is_empty() {
+        let mut out = String::new();
+        let _ = writeln!(out, "{}", t!("doctor.redundant_inverses", count = redundant.len()));
+        for (edge, reverse) in &redundant {
+            let _ = writeln!(out, "  {} --[{}]--> {}", edge.from, edge.relation, edge.to);
+            let _ = writeln!(out, "  {} --[{}]--> {}", reverse.from, reverse.relation, reverse.to);
+        }
+        output::maybe_page(&out);
+        problems = true;
+    }
+
+    if problems {
+        if !args.fix {
+            println!("{}", t!("doctor.fix_hint"));
+        }
+        return Err(Exit(ExitStatus::Warnings).into());
+    }
+    if legacy.is_empty() && title_index.is_empty() && dangling.is_empty() {
+        println!("{}", t!("doctor.ok"));
+    }
+    Ok(())
+}
+
+/// 将存储结构迁移到指定版本
+pub fn migrate(args: MigrateArgs) -> Result<()> {
+    use crate::storage::schema::Migrator;
+    use crate::storage::Storage;
+
+    let root = Path::new(".");
+    if READ_ONLY.load(std::sync::atomic::Ordering::Relaxed) {
+        return Err(MemexiaError::ReadOnlyRepository { path: root.to_path_buf() }.into());
+    }
+    let storage = Storage::open_unmigrated(root)?;
+    let migrator = Migrator::default();
+    let target = if args.to == "latest" {
+        migrator.latest()
+    } else {
+        args.to
+            .parse()
+            .map_err(|_| invalid(t!("migrate.invalid_target", value = args.to)))?
+    };
+
+    let report = migrator.migrate(storage.graph_storage(), root, target)?;
+    if report.applied.is_empty() {
+        println!("{}", t!("migrate.up_to_date", version = report.to));
+        return Ok(());
+    }
+    for step in &report.applied {
+        println!("  {} {}", step.version, step.description);
+    }
+    println!("{}", t!("migrate.done", from = report.from, to = report.to));
+    Ok(())
+}
+
+/// dry-run 汇总中每种变更显示的 ID 样本数
+const DRY_RUN_SAMPLE: usize = 10;
+
+/// 打印 dry-run 期间记录的变更：按类型计数，并附上受影响 ID 的样本
+fn print_dry_run(storage: &crate::storage::Storage) {
+    use crate::storage::{write_guard, MutationKind};
+
+    println!("\n{}", t!("dry_run.header"));
+    let summaries = write_guard::summarize(&storage.mutations(), DRY_RUN_SAMPLE);
+    if summaries.is_empty() {
+        println!("{}", t!("dry_run.none"));
+        return;
+    }
+    for summary in &summaries {
+        let key = match summary.kind {
+            MutationKind::AddNode => "dry_run.add_node",
+            MutationKind::UpdateNode => "dry_run.update_node",
+            MutationKind::DeleteNode => "dry_run.delete_node",
+            MutationKind::AddEdge => "dry_run.add_edge",
+            MutationKind::DeleteEdge => "dry_run.delete_edge",
+        };
+        println!("{}", t!(key, count = summary.count));
+        for id in &summary.sample {
+            println!("    {}", id);
+        }
+        if summary.count > summary.sample.len() {
+            println!("{}", t!("dry_run.more", count = summary.count - summary.sample.len()));
+        }
+    }
+}
+
+fn short_hash(hash: &str) -> &str {
+    &hash[..hash.len().min(12)]
+}
+
+/// 处理 `-C <path>` / `--repo <name>`：切换到目标目录后再执行命令
+pub fn enter_directory(chdir: Option<&Path>, repo: Option<&str>) -> Result<()> {
+    use crate::core::registry::{self, Registry};
+    use anyhow::Context;
+
+    let dir = match (chdir, repo) {
+        (Some(path), _) => path.to_path_buf(),
+        (None, Some(name)) => {
+            let path = registry::active_path().ok_or_else(|| invalid(t!("repos.no_config_dir")))?;
+            Registry::load(&path)?.resolve(name)?
+        }
+        (None, None) => return Ok(()),
+    };
+    std::env::set_current_dir(&dir).with_context(|| format!("Cannot change directory to {:?}", dir))?;
+    Ok(())
+}
+
+pub fn repos(args: ReposArgs) -> Result<()> {
+    use crate::core::registry::{self, Registry};
+    use crate::storage::Storage;
+
+    let path = registry::active_path().ok_or_else(|| invalid(t!("repos.no_config_dir")))?;
+
+    match args.command {
+        ReposCommands::List => {
+            let registry = Registry::load(&path)?;
+            if registry.repos.is_empty() {
+                println!("{}", t!("repos.none"));
+                return Ok(());
+            }
+            let mut repos = registry.repos.clone();
+            repos.sort_by_key(|r| std::cmp::Reverse(r.last_used));
+            let width = repos.iter().map(|r| output::display_width(&r.name)).max().unwrap_or(0);
+            for repo in &repos {
+                let status = if !repo.exists() {
+                    t!("repos.missing")
+                } else {
+                    // 只读取节点数，不经过 Repository::open，避免刷新使用时间
+                    match Storage::open(&repo.path).and_then(|s| s.graph().list_nodes()) {
+                        Ok(nodes) => t!("repos.notes", count = nodes.len()),
+                        Err(_) => t!("repos.unreadable"),
+                    }
+                };
+                println!(
+                    "  {}  {}  {}  {}",
+                    output::fit_to_width(&repo.name, width),
+                    repo.last_used.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+                    status,
+                    repo.path.display()
+                );
+            }
+        }
+        ReposCommands::Add(args) => {
+            let repo = Repository::open(&args.path)?;
+            let mut registry = Registry::load(&path)?;
+            let name = registry.add(args.name.as_deref(), repo.path(), chrono::Utc::now())?.name.clone();
+            registry.save(&path)?;
+            println!("{}", t!("repos.added", name = name, path = repo.path().display()));
+        }
+        ReposCommands::Remove(args) => {
+            let mut registry = Registry::load(&path)?;
+            let removed = registry.remove(&args.name)?;
+            registry.save(&path)?;
+            println!("{}", t!("repos.removed", name = removed.name, path = removed.path.display()));
+        }
+        ReposCommands::Rename(args) => {
+            let mut registry = Registry::load(&path)?;
+            registry.rename(&args.old, &args.new)?;
+            registry.save(&path)?;
+            println!("{}", t!("repos.renamed", old = args.old, new = args.new));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "debug-tools")]
+pub fn debug(args: DebugArgs) -> Result<()> {
+    use crate::core::synthetic::{self, VaultSpec};
+
+    let DebugCommands::GenerateVault(args) = args.command;
+    let spec = VaultSpec {
+        notes: args.notes,
+        links_per_note: args.links_per_note,
+        tags: args.tags,
+        seed: args.seed,
+        broken_fraction: args.broken_fraction,
+    };
+    let vault = synthetic::generate_vault(&args.dir, &spec)?;
+    println!(
+        "{}",
+        t!("debug.vault_generated", notes = vault.files.len(), links = vault.links, tags = vault.tags, dir = args.dir.display())
+    );
+    if vault.dangling_links + vault.malformed_frontmatter > 0 {
+        println!(
+            "{}",
+            t!("debug.vault_broken", dangling = vault.dangling_links, malformed = vault.malformed_frontmatter)
+        );
+    }
+    Ok(())
+}
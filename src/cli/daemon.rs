@@ -0,0 +1,284 @@
+//! 守护进程模式
+//!
+//! RocksDB 只允许一个进程打开图存储，`memexia serve` 运行时其他命令无法再打开仓库。
+//! 服务启动时在 [`SERVE_INFO_PATH`] 写入进程号、端口和随机令牌（[`ServeInfo`]），退出时删除；
+//! 命令据此找到服务（[`Daemon::detect`]）：
+//!
+//! - 修改仓库的 `reindex`、`add`、`commit` 和 `link create/delete` 经本地 HTTP API
+//!   （`/api/daemon/*`）交给服务执行，命令本身不打开图存储
+//! - 其他命令在存储被占用时以只读方式打开仓库
+//! - `--no-daemon` 总是直接打开，存储被占用时返回 [`MemexiaError::StoreBusy`]
+//!
+//! 修改接口要求 `X-Memexia-Token` 请求头与 `serve.json` 中的令牌一致：
+//! 该文件只有当前用户可读，浏览器中的页面也无法跨域附加自定义请求头。
+//! 服务被强制结束时 `serve.json` 会残留，检测时通过 `GET /api/daemon` 确认服务仍在运行
+
+use crate::core::IndexSummary;
+use crate::error::MemexiaError;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 服务登记文件（相对仓库根目录）
+pub const SERVE_INFO_PATH: &str = ".memexia/serve.json";
+
+/// 请求服务时携带令牌的请求头
+pub const TOKEN_HEADER: &str = "X-Memexia-Token";
+
+/// 连接服务的超时；检测不到服务时命令回退为直接打开仓库
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// 正在运行的服务
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServeInfo {
+    /// 服务进程号
+    pub pid: u32,
+    /// 监听端口（`127.0.0.1`）
+    pub port: u16,
+    /// 修改接口的令牌
+    pub token: String,
+    /// 启动时间
+    pub started_at: DateTime<Utc>,
+}
+
+impl ServeInfo {
+    /// 当前进程在指定端口上的服务，生成新令牌
+    pub fn new(port: u16) -> Self {
+        Self {
+            pid: std::process::id(),
+            port,
+            token: uuid::Uuid::new_v4().simple().to_string(),
+            started_at: Utc::now(),
+        }
+    }
+
+    /// 读取仓库的服务登记，不存在或无法解析时返回 None
+    pub fn load(root: &Path) -> Option<Self> {
+        let content = fs::read_to_string(root.join(SERVE_INFO_PATH)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// 写入服务登记（Unix 上只有当前用户可读），返回的守卫离开作用域时删除登记
+    pub fn register(&self, root: &Path) -> Result<Registration> {
+        let path = root.join(SERVE_INFO_PATH);
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options
+            .open(&path)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        file.write_all(serde_json::to_string_pretty(self)?.as_bytes())?;
+        Ok(Registration { path, pid: self.pid })
+    }
+}
+
+/// 服务登记守卫，离开作用域时删除 `serve.json`（文件已被其他服务覆盖时保留）
+#[derive(Debug)]
+pub struct Registration {
+    path: PathBuf,
+    pid: u32,
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        let root = self.path.parent().and_then(Path::parent).unwrap_or(Path::new("."));
+        if ServeInfo::load(root).is_some_and(|info| info.pid == self.pid) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// `commit` 的代理结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitOutcome {
+    /// 提交哈希
+    pub commit: String,
+    /// `--all` 时自动暂存的文件
+    pub auto_staged: Vec<PathBuf>,
+}
+
+/// 可以代理命令的服务
+#[derive(Debug, Clone)]
+pub struct Daemon {
+    info: ServeInfo,
+}
+
+impl Daemon {
+    /// 查找仓库正在运行的服务
+    ///
+    /// 没有登记、登记已过期（服务已退出或端口被其他程序占用）时返回 None
+    pub fn detect(root: &Path) -> Option<Self> {
+        let daemon = Self { info: ServeInfo::load(root)? };
+        match daemon.request::<Value>("GET", "/api/daemon", None) {
+            Ok(status) if status["pid"] == json!(daemon.info.pid) => Some(daemon),
+            Ok(_) => None,
+            Err(e) => {
+                tracing::debug!("ignoring stale {}: {}", SERVE_INFO_PATH, e);
+                None
+            }
+        }
+    }
+
+    /// 服务登记
+    pub fn info(&self) -> &ServeInfo {
+        &self.info
+    }
+
+    /// 由服务重新索引（`memexia reindex`）
+    pub fn reindex(&self, path: &Path, full: bool, detect_renames: bool) -> Result<IndexSummary> {
+        let body = json!({ "path": absolute(path)?, "full": full, "detect_renames": detect_renames });
+        self.request("POST", "/api/daemon/reindex", Some(body))
+    }
+
+    /// 由服务暂存文件（`memexia add`）
+    pub fn add(&self, files: &[PathBuf]) -> Result<()> {
+        let files = files.iter().map(|file| absolute(file)).collect::<Result<Vec<_>>>()?;
+        self.request::<Value>("POST", "/api/daemon/add", Some(json!({ "files": files })))?;
+        Ok(())
+    }
+
+    /// 由服务提交（`memexia commit`）
+    pub fn commit(&self, message: &str, all: bool, include_untracked: bool) -> Result<CommitOutcome> {
+        let body = json!({ "message": message, "all": all, "include_untracked": include_untracked });
+        self.request("POST", "/api/daemon/commit", Some(body))
+    }
+
+    /// 由服务创建链接（`memexia link create`），返回实际使用的关系名
+    pub fn create_link(&self, source: &Path, target: &Path, relation: &str) -> Result<String> {
+        let body = json!({ "source": absolute(source)?, "target": absolute(target)?, "relation": relation });
+        let edge: Value = self.request("POST", "/api/daemon/links", Some(body))?;
+        Ok(edge["relation"].as_str().unwrap_or(relation).to_string())
+    }
+
+    /// 由服务删除链接（`memexia link delete`）
+    pub fn delete_link(&self, source: &Path, target: &Path) -> Result<()> {
+        let body = json!({ "source": absolute(source)?, "target": absolute(target)? });
+        self.request::<Value>("POST", "/api/daemon/links/delete", Some(body))?;
+        Ok(())
+    }
+
+    /// 发送一个请求并解析 JSON 响应
+    ///
+    /// 409 和 422 响应还原为 [`MemexiaError::NothingToCommit`] 和 [`MemexiaError::Validation`]，
+    /// 以保持与直接执行相同的退出码
+    fn request<T: DeserializeOwned>(&self, method: &str, path: &str, body: Option<Value>) -> Result<T> {
+        let address = SocketAddr::from(([127, 0, 0, 1], self.info.port));
+        let mut stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?;
+        let body = body.map(|body| body.to_string()).unwrap_or_default();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: 127.0.0.1:{}\r\n{}: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            method,
+            path,
+            self.info.port,
+            TOKEN_HEADER,
+            self.info.token,
+            body.len(),
+            body
+        )?;
+        stream.flush()?;
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        let (head, body) = response.split_once("\r\n\r\n").ok_or_else(|| anyhow!("Malformed response from memexia serve"))?;
+        let status: u16 = head
+            .split_whitespace()
+            .nth(1)
+            .and_then(|status| status.parse().ok())
+            .ok_or_else(|| anyhow!("Malformed response from memexia serve"))?;
+        let value: Value = serde_json::from_str(body).context("Malformed response from memexia serve")?;
+
+        if status != 200 {
+            let message = value["error"].as_str().unwrap_or("unknown error").to_string();
+            return Err(match status {
+                409 => MemexiaError::NothingToCommit.into(),
+                422 => MemexiaError::Validation(message).into(),
+                _ => anyhow!("memexia serve (port {}) failed: {}", self.info.port, message),
+            });
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+/// 转换为绝对路径（服务的工作目录与命令不同）
+fn absolute(path: &Path) -> Result<PathBuf> {
+    Ok(std::path::absolute(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::serve::{self, ServeOptions};
+    use crate::core::Repository;
+    use crate::storage::{ids, store_opens_on_this_thread};
+    use std::net::TcpListener;
+    use tempfile::TempDir;
+
+    /// 在后台线程中为新仓库启动服务，返回仓库目录和服务的存储
+    fn start_server(notes: &[(&str, &str)]) -> (TempDir, crate::storage::Storage) {
+        let temp = TempDir::new().unwrap();
+        let mut repo = Repository::init(temp.path()).unwrap();
+        for (name, content) in notes {
+            fs::write(temp.path().join(name), content).unwrap();
+        }
+        let storage = repo.storage().unwrap().clone();
+
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let info = ServeInfo::new(listener.local_addr().unwrap().port());
+        std::mem::forget(info.register(repo.path()).unwrap());
+        let options = ServeOptions { token: Some(info.token), ..ServeOptions::default() };
+        std::thread::spawn(move || serve::serve_listener(&mut repo, &options, listener));
+        (temp, storage)
+    }
+
+    #[test]
+    fn test_proxied_reindex() {
+        let (temp, storage) = start_server(&[("a.md", "# A\n\n[[b.md|Supports]]\n")]);
+        let root = temp.path();
+
+        let opens = store_opens_on_this_thread();
+        let daemon = Daemon::detect(root).expect("server should be detected");
+        let summary = daemon.reindex(root, false, true).unwrap();
+        assert_eq!(summary.files_indexed, 1);
+        assert_eq!(summary.edges_created, 1);
+        // 客户端没有打开图存储，图由服务更新
+        assert_eq!(store_opens_on_this_thread(), opens);
+        assert!(storage.graph().node_exists(&ids::file_id("a.md")).unwrap());
+
+        // 代理的提交保持原有错误分类
+        let err = daemon.commit("Nothing", false, false).unwrap_err();
+        assert!(matches!(err.downcast_ref::<MemexiaError>(), Some(MemexiaError::NothingToCommit)));
+        daemon.add(&[root.join("a.md")]).unwrap();
+        assert_eq!(daemon.create_link(&root.join("a.md"), &root.join("c.md"), "Refines").unwrap(), "Refines");
+    }
+
+    #[test]
+    fn test_stale_or_foreign_registration() {
+        let (temp, _storage) = start_server(&[]);
+        let root = temp.path();
+        let mut info = ServeInfo::load(root).unwrap();
+
+        // 令牌不符的请求被拒绝
+        info.token = "forged".to_string();
+        let forged = Daemon { info: info.clone() };
+        assert!(forged.request::<Value>("GET", "/api/daemon", None).is_err());
+
+        // 服务已退出
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        info.port = listener.local_addr().unwrap().port();
+        drop(listener);
+        std::mem::forget(info.register(root).unwrap());
+        assert!(Daemon::detect(root).is_none());
+    }
+}
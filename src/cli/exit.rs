@@ -0,0 +1,163 @@
+//! 退出码模块
+//!
+//! 将命令错误映射为稳定的进程退出码，便于脚本区分失败原因
+//!
+//! | 退出码 | 含义 |
+//! |--------|------|
+//! | 0 | 成功 |
+//! | 1 | 内部错误 |
+//! | 2 | 不是 Memexia 仓库 |
+//! | 3 | 没有可提交的内容 |
+//! | 4 | 输入校验失败 / 图验证或 lint 发现错误 |
+//! | 5 | 搜索无结果 |
+//! | 6 | 诊断、图验证或 lint 发现警告 |
+
+use crate::cli::i18n;
+use crate::error::MemexiaError;
+use crate::t;
+use std::fmt;
+
+/// CLI 退出码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitStatus {
+    /// 成功
+    Success = 0,
+    /// 内部错误
+    Internal = 1,
+    /// 不是 Memexia 仓库
+    NotARepository = 2,
+    /// 没有可提交的内容
+    NothingToCommit = 3,
+    /// 输入校验失败（或图验证发现错误）
+    Validation = 4,
+    /// 搜索无结果
+    NoResults = 5,
+    /// 诊断或图验证发现警告
+    Warnings = 6,
+}
+
+impl ExitStatus {
+    /// 退出码数值
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    /// 根据错误分类退出码
+    ///
+    /// 沿错误链查找 [`MemexiaError`] 或 [`Exit`]，找不到时视为内部错误
+    pub fn from_error(err: &anyhow::Error) -> Self {
+        if let Some(exit) = err.downcast_ref::<Exit>() {
+            return exit.0;
+        }
+
+        for cause in err.chain() {
+            if let Some(err) = cause.downcast_ref::<MemexiaError>() {
+                return Self::from_memexia_error(err);
+            }
+        }
+
+        Self::Internal
+    }
+
+    fn from_memexia_error(err: &MemexiaError) -> Self {
+        match err {
+            MemexiaError::RepositoryNotFound { .. } => Self::NotARepository,
+            MemexiaError::NothingToCommit => Self::NothingToCommit,
+            MemexiaError::Validation(_) | MemexiaError::ParseError { .. } => Self::Validation,
+            MemexiaError::StagedFile { source, .. } => Self::from_memexia_error(source),
+            _ => Self::Internal,
+        }
+    }
+}
+
+/// 不属于错误、但需要以非零退出码结束的结果（如搜索无结果）
+///
+/// 命令返回该值时不打印错误信息，直接以对应退出码退出
+#[derive(Debug, Clone, Copy)]
+pub struct Exit(pub ExitStatus);
+
+impl fmt::Display for Exit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "exit with status {}", self.0.code())
+    }
+}
+
+impl std::error::Error for Exit {}
+
+/// `--help` 中展示的退出码说明
+pub const EXIT_CODES_HELP: &str = "\
+Exit codes:
+  0  Success
+  1  Internal error
+  2  Not a Memexia repository (also used for invalid command-line usage)
+  3  Nothing to commit
+  4  Validation failed (including `validate` and `lint` errors)
+  5  Search returned no results
+  6  Doctor, `validate` or `lint` found warnings";
+
+/// 将命令错误输出到 stderr 并返回对应退出码
+///
+/// 默认只输出单行 `error: <message>`（按当前语言）；`verbose` 时逐行输出错误链
+pub fn report(err: &anyhow::Error, verbose: bool) -> ExitStatus {
+    let status = ExitStatus::from_error(err);
+    if err.downcast_ref::<Exit>().is_some() {
+        return status;
+    }
+
+    // 顶层错误是库错误时按当前语言输出，否则使用原始描述
+    let message = match err.chain().next().and_then(|e| e.downcast_ref::<MemexiaError>()) {
+        Some(e) => i18n::error_message(e),
+        None => err.to_string(),
+    };
+
+    eprintln!("{}: {}", t!("error.prefix"), single_line(&message));
+    if verbose {
+        for cause in err.chain().skip(1) {
+            eprintln!("  {}: {}", t!("error.caused_by"), cause);
+        }
+    }
+
+    status
+}
+
+/// 将多行消息折叠为单行
+fn single_line(message: &str) -> String {
+    message.lines().map(str::trim).filter(|l| !l.is_empty()).collect::<Vec<_>>().join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_classify_errors() {
+        let err: anyhow::Error = MemexiaError::RepositoryNotFound { path: PathBuf::from(".") }.into();
+        assert_eq!(ExitStatus::from_error(&err), ExitStatus::NotARepository);
+
+        let err: anyhow::Error = MemexiaError::NothingToCommit.into();
+        assert_eq!(ExitStatus::from_error(&err).code(), 3);
+
+        let err: anyhow::Error = MemexiaError::Validation("bad".to_string()).into();
+        assert_eq!(ExitStatus::from_error(&err), ExitStatus::Validation);
+
+        let err = anyhow::anyhow!("boom");
+        assert_eq!(ExitStatus::from_error(&err), ExitStatus::Internal);
+
+        let err: anyhow::Error = Exit(ExitStatus::NoResults).into();
+        assert_eq!(ExitStatus::from_error(&err).code(), 5);
+    }
+
+    #[test]
+    fn test_classify_through_context() {
+        let result: Result<(), MemexiaError> = Err(MemexiaError::NothingToCommit);
+        let err = result.context("Commit failed").unwrap_err();
+        assert_eq!(ExitStatus::from_error(&err), ExitStatus::NothingToCommit);
+    }
+
+    #[test]
+    fn test_single_line() {
+        assert_eq!(single_line("row 2: bad\nrow 3: worse\n"), "row 2: bad; row 3: worse");
+    }
+}
@@ -0,0 +1,939 @@
+//! 命令行消息国际化模块
+//!
+//! 以「消息键 → 模板」的简单目录实现多语言输出，目前支持英文和中文。
+//! 命令处理函数通过 [`t!`](crate::t) 宏按键名输出消息，两种语言共享同一套逻辑：
+//!
+//! ```
+//! use memexia::t;
+//!
+//! let line = t!("new.created", path = "notes/idea.md");
+//! assert!(line.contains("notes/idea.md"));
+//! ```
+//!
+//! ## 语言选择
+//!
+//! 按以下顺序取第一个可识别的值，均无法识别时使用英文：
+//!
+//! 1. `--lang` 参数
+//! 2. `MEMEXIA_LANG` 环境变量
+//! 3. 系统语言（`LC_ALL`、`LC_MESSAGES`、`LANG`）
+//!
+//! `--json` 输出不经过本模块，始终与语言无关
+
+use crate::error::MemexiaError;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// 界面语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Lang {
+    /// 英文
+    #[default]
+    En,
+    /// 简体中文
+    Zh,
+}
+
+impl Lang {
+    /// 解析语言标识
+    ///
+    /// 接受 `en`、`zh`、`zh_CN.UTF-8`、`zh-Hans` 等形式，无法识别时返回 None
+    pub fn parse(value: &str) -> Option<Self> {
+        let code = value
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or("")
+            .trim()
+            .to_lowercase();
+        match code.as_str() {
+            "en" => Some(Lang::En),
+            "zh" => Some(Lang::Zh),
+            _ => None,
+        }
+    }
+
+    /// 消息目录
+    fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Lang::En => EN,
+            Lang::Zh => ZH,
+        }
+    }
+}
+
+/// 当前语言（0 = En，1 = Zh）
+static CURRENT_LANG: AtomicU8 = AtomicU8::new(0);
+
+/// 设置当前语言
+pub fn set_lang(lang: Lang) {
+    CURRENT_LANG.store(lang as u8, Ordering::Relaxed);
+}
+
+/// 获取当前语言
+pub fn lang() -> Lang {
+    match CURRENT_LANG.load(Ordering::Relaxed) {
+        1 => Lang::Zh,
+        _ => Lang::En,
+    }
+}
+
+/// 检测界面语言
+///
+/// # Arguments
+///
+/// * `cli_lang` - `--lang` 参数值
+pub fn detect(cli_lang: Option<&str>) -> Lang {
+    let env = |name: &str| std::env::var(name).ok();
+    resolve(
+        cli_lang
+            .map(str::to_string)
+            .into_iter()
+            .chain([env("MEMEXIA_LANG"), env("LC_ALL"), env("LC_MESSAGES"), env("LANG")].into_iter().flatten()),
+    )
+}
+
+/// 从候选值中取第一个可识别的语言，均无法识别时返回英文
+pub fn resolve<I, S>(candidates: I) -> Lang
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    candidates
+        .into_iter()
+        .find_map(|c| Lang::parse(c.as_ref()))
+        .unwrap_or_default()
+}
+
+/// 按当前语言翻译消息
+///
+/// 模板中的 `{name}` 占位符替换为对应参数；当前语言缺少该键时回退到英文，
+/// 英文也缺少时返回键名本身
+///
+/// # Arguments
+///
+/// * `key` - 消息键
+/// * `args` - 命名参数
+pub fn translate(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    translate_in(lang(), key, args)
+}
+
+/// 按指定语言翻译消息
+pub fn translate_in(lang: Lang, key: &str, args: &[(&str, &dyn Display)]) -> String {
+    let template = lookup(lang.catalog(), key)
+        .or_else(|| lookup(EN, key))
+        .unwrap_or(key);
+
+    let mut message = template.to_string();
+    for (name, value) in args {
+        message = message.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    message
+}
+
+fn lookup(catalog: &[(&'static str, &'static str)], key: &str) -> Option<&'static str> {
+    catalog.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+}
+
+/// 将库错误翻译为当前语言的消息
+///
+/// 没有对应消息键的错误使用其原始描述
+pub fn error_message(err: &MemexiaError) -> String {
+    match err {
+        MemexiaError::RepositoryNotFound { path } => {
+            crate::t!("error.repository_not_found", path = path.display())
+        }
+        MemexiaError::AlreadyInitialized { path } => {
+            crate::t!("error.already_initialized", path = path.display())
+        }
+        MemexiaError::CorruptRepository { path, reason } => {
+            crate::t!("error.corrupt_repository", path = path.display(), reason = reason)
+        }
+        MemexiaError::ReadOnlyRepository { path } => {
+            crate::t!("error.read_only_repository", path = path.display())
+        }
+        MemexiaError::RepositoryLocked { path } => {
+            crate::t!("error.repository_locked", path = path.display())
+        }
+        MemexiaError::WriteAccessDenied { path } => {
+            crate::t!("error.write_access_denied", path = path.display())
+        }
+        MemexiaError::StoreBusy { path } => crate::t!("error.store_busy", path = path.display()),
+        MemexiaError::StoreCorrupt { path, reason } => {
+            crate::t!("error.store_corrupt", path = path.display(), reason = reason)
+        }
+        MemexiaError::NodeNotFound { id } => crate::t!("error.node_not_found", id = id),
+        MemexiaError::EdgeNotFound { id } => crate::t!("error.edge_not_found", id = id),
+        MemexiaError::SnapshotNotFound { hash } => crate::t!("error.snapshot_not_found", hash = hash),
+        MemexiaError::SnapshotPruned { hash } => crate::t!("error.snapshot_pruned", hash = hash),
+        MemexiaError::NothingToCommit => crate::t!("error.nothing_to_commit"),
+        MemexiaError::Cancelled => crate::t!("error.cancelled"),
+        MemexiaError::StagedFile { file, source } => {
+            crate::t!("error.staged_file", file = file, error = error_message(source))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// 按消息键输出当前语言的字符串
+///
+/// ```
+/// use memexia::t;
+///
+/// assert_eq!(t!("graph.show.nodes", count = 3), "Nodes (3):");
+/// ```
+#[macro_export]
+macro_rules! t {
+    ($key:expr) => {
+        $crate::cli::i18n::translate($key, &[])
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::cli::i18n::translate(
+            $key,
+            &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),+],
+        )
+    };
+}
+
+/// 英文消息目录
+const EN: &[(&str, &str)] = &[
+    // 错误
+    ("error.prefix", "error"),
+    ("error.caused_by", "caused by"),
+    ("error.repository_not_found", "Not a Memexia repository (or any of the parent directories): {path}"),
+    ("error.already_initialized", "Repository already exists at {path}"),
+    ("error.corrupt_repository", "Corrupt Memexia repository at {path}: {reason}"),
+    ("error.read_only_repository", "Repository at {path} is opened read-only; this operation would modify it"),
+    ("error.repository_locked", "Repository is locked by another process ({path}); retry later, or remove the lock file if no other memexia command is running"),
+    ("error.write_access_denied", "Cannot write to the repository at {path} (permission denied); retry with --read-only to open it without writing"),
+    ("error.store_busy", "The graph store of the repository at {path} is in use by another process (such as `memexia serve` or another memexia command); wait for it to finish or stop the server"),
+    ("error.store_corrupt", "The graph store of the repository at {path} cannot be opened ({reason}); rebuild it from your notes with `memexia reindex --full`, or replace .memexia/graph with a backup copy"),
+    ("error.node_not_found", "Node not found: {id}"),
+    ("error.edge_not_found", "Edge not found: {id}"),
+    ("error.snapshot_not_found", "Snapshot not found: {hash}"),
+    ("error.snapshot_pruned", "Snapshot {hash} was pruned by the retention policy (history.retention)"),
+    ("error.nothing_to_commit", "Nothing to commit"),
+    ("error.cancelled", "Operation cancelled"),
+    ("error.staged_file", "Cannot commit {file}: {error} (nothing was committed; the staged files are unchanged)"),
+    // init
+    ("init.done", "Repository initialized at {path}"),
+    // commit
+    ("commit.elapsed", "Committed in {seconds}"),
+    ("commit.editor_hint", "# Enter the commit message. Lines starting with '#' are ignored;\n# an empty message aborts the commit."),
+    ("commit.empty_message", "Aborting commit due to empty commit message"),
+    ("commit.auto_staged", "Auto-staged {count} files:"),
+    ("repl.banner", "Memexia SPARQL shell. End queries with ';' or an empty line; .help for commands, .quit to exit."),
+    ("repl.help", ".stats              Graph statistics\n.nodes <pattern>    Nodes whose ID or title contains the pattern\n.limit [N|none]     Show at most N rows\n.format [table|json|csv]  Result format\n.history           Previous queries\n.prefixes          Preloaded PREFIX declarations\n.quit               Exit"),
+    ("repl.error", "Error: {error}"),
+    ("repl.limit", "Row limit: {limit}"),
+    ("repl.invalid_limit", "Invalid limit '{value}': expected a number or 'none'"),
+    ("repl.format", "Output format: {format}"),
+    ("repl.unknown_format", "Unknown format '{format}': expected table, json or csv"),
+    ("repl.unknown_command", "Unknown command '{command}' (try .help)"),
+    ("repl.stats", "{nodes} nodes, {edges} edges"),
+    ("repl.rows", "({count} rows)"),
+    ("repl.rows_truncated", "({shown} of {total} rows; use .limit to show more)"),
+    // log
+    ("log.message", "Message: {message}"),
+    ("log.author", "Author: {author}"),
+    ("log.timestamp", "Timestamp: {timestamp}"),
+    ("log.snapshot", "Graph snapshot: {hash}"),
+    ("log.nodes", "Affected nodes: {nodes}"),
+    ("log.links_introduced", "Links introduced: {links}"),
+    // 时间
+    ("time.just_now", "just now"),
+    ("time.minutes_ago_one", "1 minute ago"),
+    ("time.minutes_ago", "{count} minutes ago"),
+    ("time.hours_ago_one", "1 hour ago"),
+    ("time.hours_ago", "{count} hours ago"),
+    ("time.days_ago_one", "1 day ago"),
+    ("time.days_ago", "{count} days ago"),
+    ("time.months_ago_one", "1 month ago"),
+    ("time.months_ago", "{count} months ago"),
+    ("time.date_format", "%b %-d, %Y %H:%M"),
+    // graph
+    ("graph.show.header", "=== Graph Structure ==="),
+    ("graph.show.nodes", "Nodes ({count}):"),
+    ("graph.show.edges", "Edges ({count}):"),
+    ("graph.mermaid.written", "Mermaid diagram written to {path}"),
+    ("graph.layout.written", "Wrote positions for {nodes} nodes to {path}"),
+    ("graph.stats.header", "=== Graph Statistics ==="),
+    ("graph.stats.total_nodes", "Total nodes: {count}"),
+    ("graph.stats.total_edges", "Total edges: {count}"),
+    ("graph.stats.by_type", "Nodes by type:"),
+    ("graph.stats.by_relation", "Edges by relation:"),
+    ("graph.stats.avg_strength", "Average edge strength: {value}"),
+    ("graph.query.header", "SPARQL Results:"),
+    ("graph.path.found", "Path found ({hops} hops):"),
+    ("graph.path.none", "No path found between {source} and {target}"),
+    ("graph.decay.changed", "Updated effective strength of {changed} of {total} edges"),
+    ("graph.decay.would_change", "Dry run: {changed} of {total} edges would change"),
+    ("graph.decay.mean", "Mean strength: {before} -> {after}"),
+    ("graph.decay.distribution", "Distribution (before -> after):"),
+    // config
+    ("config.not_set", "Config key not set: {key}"),
+    ("config.empty", "No config values set."),
+    // new
+    ("new.unknown_type", "Unknown node type: {node_type}"),
+    ("new.created", "Created: {path}"),
+    ("new.node_id", "Node ID: {id}"),
+    ("append.done", "Updated {path} ({id})"),
+    ("editor.failed", "Editor '{editor}' exited with {status}"),
+    // reindex
+    ("reindex.header", "=== Reindex Summary ==="),
+    ("reindex.files_indexed", "Files indexed: {count}"),
+    ("reindex.files_skipped", "Files skipped: {count}"),
+    ("reindex.files_excluded", "Files excluded (noindex): {count}"),
+    ("reindex.files_deleted", "Files deleted: {count}"),
+    ("reindex.rejected", "  Not indexed: {path} ({reason})"),
+    ("reindex.nodes_created", "Nodes created: {count}"),
+    ("reindex.placeholders_promoted", "Placeholders promoted: {count}"),
+    ("reindex.edges_created", "Edges created: {count}"),
+    ("reindex.elapsed", "Indexed {count} files in {seconds}"),
+    ("reindex.warnings", "Parse warnings ({count}):"),
+    ("reindex.errors", "Errors:"),
+    ("reindex.failed", "Reindex completed with errors"),
+    ("reindex.store_recovered", "The graph store could not be opened and was moved to {path}; rebuilding it from files"),
+    // file
+    ("file.info.header", "=== File Info ==="),
+    ("file.info.path", "Path: {path}"),
+    ("file.info.node_id", "Node ID: {id}"),
+    ("file.info.type", "Type: {node_type}"),
+    ("file.info.label", "Label: {label}"),
+    ("file.info.tags", "Tags: {tags}"),
+    ("file.info.summary", "Summary: {summary}"),
+    ("file.info.summary_auto", "Summary (auto): {summary}"),
+    ("file.info.created", "Created: {time}"),
+    ("file.info.updated", "Updated: {time}"),
+    ("file.info.not_indexed", "File not indexed: {path}"),
+    ("file.links.header", "=== Outgoing Links ==="),
+    ("file.links.none", "No outgoing links from {path}"),
+    ("file.links.total", "Total: {count} outgoing links"),
+    ("file.links.historical", "=== Outgoing Links as of {at} (historical: commit {commit}, recorded {date}) ==="),
+    ("file.backlinks.header", "=== Backlinks ==="),
+    ("file.backlinks.none", "No backlinks to {path}"),
+    ("file.backlinks.total", "Total: {count} backlinks"),
+    ("file.split.done", "Moved section \"{heading}\" into {path}"),
+    ("file.split.derived", "Recorded {child} as derived from {parent}; both files are staged"),
+    ("file.render_template.done", "Rendered graph blocks in {path}"),
+    ("file.render_template.unchanged", "{path} is up to date"),
+    // link
+    ("link.unknown_relation", "Unknown relation type: {relation}"),
+    ("link.created", "Link created: {source} --[{relation}]--> {target}"),
+    ("link.deleted", "Link deleted: {source} --> {target}"),
+    ("link.query.header", "=== Query Results ==="),
+    ("link.query.none", "No matching links found"),
+    ("link.query.total", "Total: {count} links"),
+    ("propose.header", "=== Proposed Links ({count}) ==="),
+    ("propose.none", "No new links to propose"),
+    ("propose.applied", "Added {count} suggested links; review them with `memexia link suggestions list`"),
+    ("suggestions.header", "=== Suggested Links ({count}) ==="),
+    ("suggestions.none", "No suggested links"),
+    ("suggestions.accepted", "Accepted: {from} --> {to}"),
+    ("suggestions.rejected", "Rejected: {from} --> {to}"),
+    ("suggestions.accepted_all", "Accepted {count} suggested links with confidence >= {min}"),
+    ("suggestions.invalid_confidence", "Confidence must be between 0.0 and 1.0, got {value}"),
+    // export / import
+    ("export.nothing", "Nothing to export: specify --nodes and/or --edges"),
+    ("export.unsupported", "Unsupported export format: {format}"),
+    ("export.nodes", "Exported {count} nodes to {path}"),
+    ("export.edges", "Exported {count} edges to {path}"),
+    ("import.nothing", "Nothing to import: specify --nodes and/or --edges"),
+    ("import.unsupported", "Unsupported import format: {format}"),
+    ("import.header", "=== Import Summary ==="),
+    ("import.nodes", "Nodes imported: {count}"),
+    ("import.edges", "Edges imported: {count}"),
+    ("import.placeholders", "Placeholder nodes created: {count}"),
+    ("stats.header", "=== Repository Statistics ==="),
+    ("stats.notes", "Notes: {count}"),
+    ("stats.total_words", "Total words: {count}"),
+    ("stats.average_words", "Average words per note: {count}"),
+    ("stats.tags", "Tags: {count}"),
+    ("stats.languages", "Languages: {languages}"),
+    ("stats.notes_per_month", "Notes per month"),
+    ("stats.commits_per_month", "Commits per month"),
+    ("stats.no_data", "(no data)"),
+    ("stats.history_header", "Commit        Date                Nodes   Edges  Types"),
+    ("stats.history_pruned", "(snapshot pruned)"),
+    ("stats.history_empty", "No commits with graph snapshots yet"),
+    ("dashboard.header", "=== Vault Health: {score}/100 ==="),
+    ("dashboard.totals", "Notes: {notes}  Edges: {edges}"),
+    ("dashboard.check", "{mark} {label}: {count} / {total} ({percent}%, limit {limit}%)"),
+    ("dashboard.orphans", "Orphan notes"),
+    ("dashboard.dangling", "Dangling links"),
+    ("dashboard.open_questions", "Open questions"),
+    ("dashboard.stale", "Not touched in {days}+ days"),
+    ("dashboard.top_tags", "Top tags: {tags}"),
+    ("dashboard.last_commit", "Last commit: {oid} {summary} ({author}, {timestamp})"),
+    ("dashboard.none", "(none)"),
+    ("dashboard.no_commits", "Last commit: (none)"),
+    ("feed.written", "Wrote {count} entries to {path}"),
+    ("debug.vault_generated", "Generated {notes} notes with {links} links and {tags} tags in {dir}"),
+    ("debug.vault_broken", "Injected {dangling} dangling links and {malformed} malformed frontmatter blocks"),
+    ("feed.unsupported", "Unsupported feed format: {format} (expected atom or rss)"),
+    ("feed.invalid_since", "Invalid --since value: {value} (expected YYYY-MM-DD or an RFC 3339 time)"),
+    ("random.none", "No notes match the given filters"),
+    ("review.header", "=== Review Queue ({count}) ==="),
+    ("review.empty", "Nothing to review: all notes were reviewed recently"),
+    ("review.path", "Path: {path}"),
+    ("review.meta", "Type: {node_type} | Backlinks: {backlinks} | Updated: {updated}"),
+    ("review.prompt", "Mark as reviewed? [y/N/q]"),
+    ("review.marked", "Marked as reviewed: {title}"),
+    ("review.done", "Reviewed {count} notes"),
+    ("validate.clean", "No violations found"),
+    ("validate.summary", "{errors} error(s), {warnings} warning(s)"),
+    ("validate.invalid_severity", "Invalid severity '{value}' (expected warn or error)"),
+    ("report.duplicates.none", "No duplicates found"),
+    ("report.duplicates.header", "=== Possible Duplicates ({count}) ==="),
+    ("report.duplicates.title", "same title"),
+    ("report.duplicates.content", "similar content"),
+    ("report.duplicates.merge", "merge with"),
+    ("publish.done", "Published {pages} notes and {tags} tag pages to {path}"),
+    ("serve.listening", "Serving the Memexia API on http://127.0.0.1:{port}"),
+    ("serve.already_running", "memexia serve is already running for this repository on port {port} (pid {pid})"),
+    ("serve.token_created", "Created a token with scopes {scopes}; send it in the X-Memexia-Token header:"),
+    ("serve.unknown_scope", "Unknown scope: {scope} (expected read, nodes:write, edges:write or admin)"),
+    ("daemon.read_only", "Note: memexia serve (port {port}) holds the repository; opening it read-only"),
+    ("publish.excluded", "Excluded {count} notes by tag"),
+    ("watch.started", "Watching {path} for changes (Ctrl-C to stop)"),
+    ("watch.snapshot_every", "Automatic snapshots every {interval} (keeping {keep})"),
+    ("watch.index_failed", "Failed to index {path}: {error}"),
+    ("snapshot.taken", "Recorded snapshot {key} ({hash})"),
+    ("snapshot.unchanged", "Graph unchanged since the last automatic snapshot"),
+    ("snapshot.verify.ok", "ok        {hash}"),
+    ("snapshot.verify.failed", "FAILED    {hash}: {problem}"),
+    ("snapshot.verify.repaired", "repaired  {hash}: {problem}"),
+    ("snapshot.verify.dangling", "dangling  commit {commit} -> {hash}: snapshot does not exist"),
+    ("snapshot.verify.summary", "{count} snapshots verified, {failed} with problems, {repaired} .meta repaired, {dangling} dangling commit links"),
+    ("snapshot.verify.repair_hint", "Run `memexia snapshot verify --all --repair-meta` to regenerate .meta files"),
+    ("snapshot.problem.unreadable", "cannot read snapshot: {error}"),
+    ("snapshot.problem.hash_mismatch", "content does not match its hash (actual {actual})"),
+    ("snapshot.problem.invalid_nquads", "invalid N-Quads at line {line}: {error}"),
+    ("snapshot.problem.missing_meta", ".meta is missing"),
+    ("snapshot.problem.stale_meta", ".meta is stale ({reason})"),
+    ("derivation.invalid_direction", "Invalid direction '{value}' (expected up, down or both)"),
+    ("traversal.depth_limit", "Warning: stopped at the maximum depth (history.traversal.max_depth); deeper derivations are not shown"),
+    ("traversal.node_limit", "Warning: stopped after history.traversal.max_nodes nodes; the result is incomplete"),
+    ("traversal.cycle", "Warning: derivation cycle: {members}"),
+    ("history.exported", "Exported history to {path}: {nodes} nodes, {snapshots} node snapshots, {derivations} derivations, {links} commit links"),
+    ("history.imported", "Imported {snapshots} node snapshots, {derivations} derivations and {links} commit links ({skipped} duplicates skipped)"),
+    ("history.foreign_repository", "warning: the bundle was exported from a different repository (root commits differ)"),
+    ("history.missing_commits", "warning: {count} referenced commits are not in the local Git repository (e.g. {commit}); fetch them so their history resolves"),
+    ("node.restored", "Restored {id} from the trash"),
+    ("node.updated", "Updated {id}"),
+    ("node.written", "Updated {id} and wrote the change to {path}"),
+    ("node.conflict", "Warning: {path} changed since it was last indexed; kept the file and discarded the edit"),
+    ("node.change", "  {field}: {before} -> {after}"),
+    ("node.nothing_to_set", "Nothing to change; pass --title, --type, --tag, --untag, --summary or --alias"),
+    ("node.file_restored", "Checked out {path} from the last commit"),
+    ("gc.trash_empty", "Trash is empty"),
+    ("gc.trash_header", "Trash ({count} nodes):"),
+    ("gc.purge_hint", "Run `memexia gc --purge-deleted [--older-than 30d]` to remove them permanently"),
+    ("gc.purged", "Purged {count} deleted nodes"),
+    ("dry_run.header", "Dry run: nothing was written"),
+    ("dry_run.none", "  No changes"),
+    ("dry_run.add_node", "  Would add/replace {count} nodes:"),
+    ("dry_run.update_node", "  Would update {count} nodes:"),
+    ("dry_run.delete_node", "  Would delete {count} nodes:"),
+    ("dry_run.add_edge", "  Would add {count} edges:"),
+    ("dry_run.delete_edge", "  Would delete {count} edges:"),
+    ("dry_run.more", "    ... and {count} more"),
+    ("undo.done", "Undid '{operation}' from {time}"),
+    ("undo.counts", "  {restored} items restored, {removed} items removed"),
+    ("undo.not_recorded", "Note: '{operation}' cannot be undone with `memexia undo`"),
+    ("doctor.ok", "No problems found"),
+    ("doctor.snapshots_ok", "Graph snapshots: {count} verified"),
+    ("doctor.snapshots", "Graph snapshots:"),
+    ("doctor.store_corrupt", "Graph store: cannot be opened ({reason}); the other graph checks were skipped"),
+    ("doctor.store_corrupt_hint", "Run `memexia reindex --full` to rebuild it from your notes, or replace .memexia/graph with a backup copy"),
+    ("doctor.legacy_ids", "Found {count} node IDs that are not percent-encoded (written by an older version):"),
+    ("doctor.fix_hint", "Run `memexia doctor --fix` to fix what can be repaired automatically"),
+    ("doctor.fixed", "Rewrote {nodes} nodes and {edges} edges"),
+    ("doctor.title_index", "The title index is out of sync with {count} node titles:"),
+    ("doctor.title_index_fixed", "Rebuilt the title index for {count} nodes"),
+    ("doctor.redundant_inverses", "Found {count} pairs of links that restate each other through an inverse relation (keep one of each pair):"),
+    ("doctor.dangling", "Found {count} links whose endpoint nodes do not exist:"),
+    ("doctor.dangling_hint", "Run `memexia doctor --fix-dangling create-placeholders` or `--fix-dangling drop` to repair them"),
+    ("doctor.dangling_fixed", "Created {placeholders} placeholder nodes and removed {dropped} links"),
+    ("doctor.invalid_dangling_strategy", "Unknown strategy '{value}' (expected create-placeholders, drop or report-only)"),
+    ("watch.caught_up", "Caught up on changes made while stopped: {indexed} indexed, {deleted} deleted"),
+    ("link.annotated", "Annotated link {source} --> {target} ({count} notes)"),
+    ("link.annotations.header", "=== Notes on {source} --> {target} ==="),
+    ("link.annotations.none", "No notes on this link"),
+    ("link.annotations.changes", "Changes:"),
+    ("link.change", "[{time}] {field} changed, was {previous}"),
+    ("link.empty_value", "(none)"),
+    ("link.set", "Set {field} of {source} --> {target} to {value}"),
+    ("file.links.annotation", "      note [{time}]: {text}"),
+    ("file.links.introduced", "      introduced in {commit}"),
+    ("file.links.uncommitted", "      not committed yet"),
+    ("repos.no_config_dir", "Cannot locate the user config directory for the repository registry"),
+    ("repos.none", "No registered repositories"),
+    ("repos.notes", "{count} notes"),
+    ("repos.missing", "missing"),
+    ("repos.unreadable", "unreadable"),
+    ("repos.added", "Registered {name}: {path}"),
+    ("repos.removed", "Forgot {name}: {path}"),
+    ("repos.renamed", "Renamed {old} to {new}"),
+    ("graph.query.exported", "Wrote {count} rows to {path}"),
+    ("graph.query.template_params", "params: {params}"),
+    ("graph.query.param_without_template", "--param can only be used with --template"),
+    ("graph.query.bad_param", "Expected --param KEY=VALUE, got \"{param}\""),
+    ("reindex.renamed", "Renamed: {from} -> {to}"),
+    ("report.contradictions.none", "No contradictions found"),
+    ("report.contradictions.header", "{count} contradiction cluster(s), strongest first:"),
+    ("report.contradictions.cluster", "Cluster {index} (total strength {strength})"),
+    ("report.contradictions.notes", "Notes"),
+    ("report.contradictions.contradicts", "contradicts"),
+    ("report.contradictions.support", "supported by {from} -> {to} ({strength})"),
+    ("file.log.none", "No history for {path}"),
+    ("file.log.created", "created ({words} words)"),
+    ("file.log.deleted", "deleted"),
+    ("file.log.renamed", "renamed from {from}"),
+    ("file.log.content_changed", "content changed ({delta} words)"),
+    ("file.log.link_added", "edge to {target} added"),
+    ("file.log.link_removed", "edge to {target} removed"),
+    ("file.log.snapshot", "node snapshot {hash}"),
+    ("file.log.derived_from", "derived from {node}"),
+    ("file.log.derived_into", "derived into {node}"),
+    ("tag.none", "No notes carry tag {tag}"),
+    ("tag.renamed", "Renamed tag {old} to {new} on {count} notes:"),
+    ("tag.removed", "Removed tag {tag} from {count} notes:"),
+    ("tag.files_rewritten", "Rewrote {count} files:"),
+    ("tag.promoted", "Promoted tag {tag} to {path}"),
+    ("tag.promoted_counts", "Added {edges} links and {derivations} derivations, untagged {untagged} notes:"),
+    ("lint.clean", "No problems found in {files} files"),
+    ("lint.summary", "{errors} errors, {warnings} warnings in {files} files"),
+    ("export.ndjson", "Exported {nodes} nodes and {edges} edges to {path}"),
+    ("export.invalid_since", "Invalid --since value: {value} (expected YYYY-MM-DD or an RFC 3339 time)"),
+    ("doctor.schema_version", "Schema version: {version} (latest {latest})"),
+    ("migrate.invalid_target", "Invalid --to value: {value} (expected a version number or `latest`)"),
+    ("migrate.up_to_date", "Schema is already at version {version}"),
+    ("migrate.done", "Migrated schema from version {from} to {to}"),
+    ("export.corpus_dir", "Specify an output directory: memexia export --format corpus <dir>"),
+    ("export.corpus", "Exported {notes} notes as {files} text files to {path}"),
+    ("export.subgraph", "Exported {nodes} nodes, {stubs} boundary stubs and {edges} edges to {path}"),
+    ("export.subgraph_format", "--tag and --query are only supported by the nquads, json and dot formats, not {format}"),
+    ("similarity.unknown_node", "row {line}: unknown node {id}"),
+    ("similarity.summary", "{created} new and {updated} updated suggestions; skipped {below} below threshold, {linked} already linked, {duplicates} duplicate and {unknown} with unknown nodes"),
+    ("similarity.review_hint", "Review them with `memexia link suggestions list`"),
+    ("archive.done", "Archived {from} -> {to}"),
+    ("archive.restored", "Unarchived {from} -> {to}"),
+    ("search.none", "No notes match \"{query}\""),
+    ("gc.retention", "Retention policy pruned {links} commit snapshots ({snapshots} snapshot files removed)"),
+    ("gc.retention_dry_run", "Retention policy would prune {links} commit snapshots ({snapshots} snapshot files)"),
+    ("graph.stats.histogram", "Edge strength histogram:"),
+    ("graph.stats.matrix", "Relations by node type (source relation target):"),
+    ("graph.stats.dangling", "{count} edges with a missing endpoint node"),
+    ("graph.stats.out_degree", "Average out-degree by node type:"),
+    ("link.refresh.none", "No links use a previous note title."),
+    ("link.refresh.preview", "{count} links in {files} files use a previous title. Run with --write-files to rewrite them."),
+    ("link.refresh.done", "Rewrote {count} links in {files} files and staged them."),
+    ("link.apply.line_error", "line {line}: {message}"),
+    ("link.apply.strict_failed", "{count} lines are invalid; nothing was applied (--strict)"),
+    ("link.apply.none", "No links to apply."),
+    ("link.apply.preview", "{count} links to apply:"),
+    ("link.apply.prompt", "Apply {count} links? [y/N]"),
+    ("link.apply.needs_yes", "Not running interactively; pass --yes to apply the links"),
+    ("link.apply.cancelled", "Cancelled; nothing was applied."),
+    ("link.apply.done", "Applied {count} links."),
+];
+
+/// 中文消息目录
+const ZH: &[(&str, &str)] = &[
+    // 错误
+    ("error.prefix", "错误"),
+    ("error.caused_by", "原因"),
+    ("error.repository_not_found", "不是 Memexia 仓库（或其任何父目录）：{path}"),
+    ("error.already_initialized", "仓库已存在：{path}"),
+    ("error.corrupt_repository", "Memexia 仓库已损坏：{path}：{reason}"),
+    ("error.read_only_repository", "仓库 {path} 以只读模式打开，该操作会修改仓库"),
+    ("error.repository_locked", "仓库被其他进程锁定（{path}）；请稍后重试，如果没有其他 memexia 命令在运行，可删除该锁文件"),
+    ("error.write_access_denied", "无法写入仓库 {path}（权限不足）；可使用 --read-only 以只读模式打开"),
+    ("error.store_busy", "仓库 {path} 的图存储正被其他进程（如 `memexia serve` 或其他 memexia 命令）使用；请等待其结束或停止服务"),
+    ("error.store_corrupt", "仓库 {path} 的图存储无法打开（{reason}）；请运行 `memexia reindex --full` 从笔记文件重建，或用备份替换 .memexia/graph"),
+    ("error.node_not_found", "节点不存在：{id}"),
+    ("error.edge_not_found", "边不存在：{id}"),
+    ("error.snapshot_not_found", "快照不存在：{hash}"),
+    ("error.snapshot_pruned", "快照 {hash} 已被保留策略（history.retention）清理"),
+    ("error.nothing_to_commit", "没有可提交的内容"),
+    ("error.cancelled", "操作已中止"),
+    ("error.staged_file", "无法提交 {file}：{error}（未提交任何内容，暂存区保持不变）"),
+    // init
+    ("init.done", "仓库已初始化：{path}"),
+    // commit
+    ("commit.elapsed", "提交耗时 {seconds}"),
+    ("commit.editor_hint", "# 请输入提交信息。以 '#' 开头的行会被忽略；\n# 提交信息为空时将中止提交。"),
+    ("commit.empty_message", "提交信息为空，已中止提交"),
+    ("commit.auto_staged", "自动暂存了 {count} 个文件："),
+    ("repl.banner", "Memexia SPARQL 交互查询。以 ';' 或空行结束查询；.help 查看命令，.quit 退出。"),
+    ("repl.help", ".stats              图谱统计\n.nodes <pattern>    ID 或标题包含模式的节点\n.limit [N|none]     最多显示 N 行\n.format [table|json|csv]  结果格式\n.history           历史查询\n.prefixes          预加载的 PREFIX 声明\n.quit               退出"),
+    ("repl.error", "错误：{error}"),
+    ("repl.limit", "行数限制：{limit}"),
+    ("repl.invalid_limit", "无效的行数限制 '{value}'：应为数字或 'none'"),
+    ("repl.format", "输出格式：{format}"),
+    ("repl.unknown_format", "未知格式 '{format}'：应为 table、json 或 csv"),
+    ("repl.unknown_command", "未知命令 '{command}'（输入 .help 查看）"),
+    ("repl.stats", "{nodes} 个节点，{edges} 条边"),
+    ("repl.rows", "（{count} 行）"),
+    ("repl.rows_truncated", "（显示 {shown} / {total} 行；使用 .limit 显示更多）"),
+    // log
+    ("log.message", "提交信息：{message}"),
+    ("log.author", "作者：{author}"),
+    ("log.timestamp", "时间：{timestamp}"),
+    ("log.snapshot", "图快照：{hash}"),
+    ("log.nodes", "涉及节点：{nodes}"),
+    ("log.links_introduced", "引入的链接：{links}"),
+    // 时间
+    ("time.just_now", "刚刚"),
+    ("time.minutes_ago_one", "1 分钟前"),
+    ("time.minutes_ago", "{count} 分钟前"),
+    ("time.hours_ago_one", "1 小时前"),
+    ("time.hours_ago", "{count} 小时前"),
+    ("time.days_ago_one", "1 天前"),
+    ("time.days_ago", "{count} 天前"),
+    ("time.months_ago_one", "1 个月前"),
+    ("time.months_ago", "{count} 个月前"),
+    ("time.date_format", "%Y年%-m月%-d日 %H:%M"),
+    // graph
+    ("graph.show.header", "=== 图结构 ==="),
+    ("graph.show.nodes", "节点（{count}）："),
+    ("graph.show.edges", "边（{count}）："),
+    ("graph.mermaid.written", "Mermaid 图已写入 {path}"),
+    ("graph.layout.written", "已将 {nodes} 个节点的坐标写入 {path}"),
+    ("graph.stats.header", "=== 图统计 ==="),
+    ("graph.stats.total_nodes", "节点总数：{count}"),
+    ("graph.stats.total_edges", "边总数：{count}"),
+    ("graph.stats.by_type", "按类型统计节点："),
+    ("graph.stats.by_relation", "按关系统计边："),
+    ("graph.stats.avg_strength", "平均边强度：{value}"),
+    ("graph.query.header", "SPARQL 查询结果："),
+    ("graph.path.found", "找到路径（{hops} 跳）："),
+    ("graph.path.none", "{source} 与 {target} 之间没有路径"),
+    ("graph.decay.changed", "已更新 {changed}/{total} 条边的有效强度"),
+    ("graph.decay.would_change", "试运行：{changed}/{total} 条边将会变化"),
+    ("graph.decay.mean", "平均强度：{before} -> {after}"),
+    ("graph.decay.distribution", "强度分布（衰减前 -> 衰减后）："),
+    // config
+    ("config.not_set", "配置项未设置：{key}"),
+    ("config.empty", "尚未设置任何配置。"),
+    // new
+    ("new.unknown_type", "未知的节点类型：{node_type}"),
+    ("new.created", "已创建：{path}"),
+    ("new.node_id", "节点 ID：{id}"),
+    ("append.done", "已更新 {path}（{id}）"),
+    ("editor.failed", "编辑器 '{editor}' 异常退出：{status}"),
+    // reindex
+    ("reindex.header", "=== 重建索引摘要 ==="),
+    ("reindex.files_indexed", "已索引文件：{count}"),
+    ("reindex.files_skipped", "跳过文件：{count}"),
+    ("reindex.files_excluded", "排除文件（noindex）：{count}"),
+    ("reindex.files_deleted", "删除文件：{count}"),
+    ("reindex.rejected", "  未索引：{path}（{reason}）"),
+    ("reindex.nodes_created", "创建节点：{count}"),
+    ("reindex.placeholders_promoted", "提升占位节点：{count}"),
+    ("reindex.edges_created", "创建边：{count}"),
+    ("reindex.elapsed", "已索引 {count} 个文件，耗时 {seconds}"),
+    ("reindex.warnings", "解析警告（{count}）："),
+    ("reindex.errors", "错误："),
+    ("reindex.failed", "重建索引完成，但存在错误"),
+    ("reindex.store_recovered", "图存储无法打开，已移到 {path}；正在从文件重建"),
+    // file
+    ("file.info.header", "=== 文件信息 ==="),
+    ("file.info.path", "路径：{path}"),
+    ("file.info.node_id", "节点 ID：{id}"),
+    ("file.info.type", "类型：{node_type}"),
+    ("file.info.label", "标题：{label}"),
+    ("file.info.tags", "标签：{tags}"),
+    ("file.info.summary", "摘要：{summary}"),
+    ("file.info.summary_auto", "摘要（自动提取）：{summary}"),
+    ("file.info.created", "创建于：{time}"),
+    ("file.info.updated", "更新于：{time}"),
+    ("file.info.not_indexed", "文件未被索引：{path}"),
+    ("file.links.header", "=== 出链 ==="),
+    ("file.links.none", "{path} 没有出链"),
+    ("file.links.total", "共 {count} 条出链"),
+    ("file.links.historical", "=== {at} 时的出链（历史视图：提交 {commit}，记录于 {date}）==="),
+    ("file.backlinks.header", "=== 反向链接 ==="),
+    ("file.backlinks.none", "没有指向 {path} 的反向链接"),
+    ("file.backlinks.total", "共 {count} 条反向链接"),
+    ("file.split.done", "已把小节“{heading}”移到 {path}"),
+    ("file.split.derived", "已记录 {child} 由 {parent} 推导而来，两个文件均已暂存"),
+    ("file.render_template.done", "已渲染 {path} 中的图谱生成块"),
+    ("file.render_template.unchanged", "{path} 已是最新"),
+    // link
+    ("link.unknown_relation", "未知的关系类型：{relation}"),
+    ("link.created", "已创建链接：{source} --[{relation}]--> {target}"),
+    ("link.deleted", "已删除链接：{source} --> {target}"),
+    ("link.query.header", "=== 查询结果 ==="),
+    ("link.query.none", "没有匹配的链接"),
+    ("link.query.total", "共 {count} 条链接"),
+    ("propose.header", "=== 推荐链接（{count}）==="),
+    ("propose.none", "没有新的推荐链接"),
+    ("propose.applied", "已添加 {count} 条建议链接，可用 `memexia link suggestions list` 查看"),
+    ("suggestions.header", "=== 建议链接（{count}）==="),
+    ("suggestions.none", "没有建议链接"),
+    ("suggestions.accepted", "已接受：{from} --> {to}"),
+    ("suggestions.rejected", "已拒绝：{from} --> {to}"),
+    ("suggestions.accepted_all", "已接受 {count} 条置信度 >= {min} 的建议链接"),
+    ("suggestions.invalid_confidence", "置信度必须在 0.0 到 1.0 之间，实际为 {value}"),
+    // export / import
+    ("export.nothing", "没有可导出的内容：请指定 --nodes 和/或 --edges"),
+    ("export.unsupported", "不支持的导出格式：{format}"),
+    ("export.nodes", "已导出 {count} 个节点到 {path}"),
+    ("export.edges", "已导出 {count} 条边到 {path}"),
+    ("import.nothing", "没有可导入的内容：请指定 --nodes 和/或 --edges"),
+    ("import.unsupported", "不支持的导入格式：{format}"),
+    ("import.header", "=== 导入摘要 ==="),
+    ("import.nodes", "已导入节点：{count}"),
+    ("import.edges", "已导入边：{count}"),
+    ("import.placeholders", "已创建占位节点：{count}"),
+    ("stats.header", "=== 仓库统计 ==="),
+    ("stats.notes", "笔记数：{count}"),
+    ("stats.total_words", "总字数：{count}"),
+    ("stats.average_words", "平均每篇字数：{count}"),
+    ("stats.tags", "标签数：{count}"),
+    ("stats.languages", "语言：{languages}"),
+    ("stats.notes_per_month", "每月新建笔记"),
+    ("stats.commits_per_month", "每月提交"),
+    ("stats.no_data", "（无数据）"),
+    ("stats.history_header", "提交          时间                 节点      边  类型"),
+    ("stats.history_pruned", "（快照已清理）"),
+    ("stats.history_empty", "还没有带图快照的提交"),
+    ("dashboard.header", "=== 笔记库健康度：{score}/100 ==="),
+    ("dashboard.totals", "笔记数：{notes}  边数：{edges}"),
+    ("dashboard.check", "{mark} {label}：{count} / {total}（{percent}%，上限 {limit}%）"),
+    ("dashboard.orphans", "孤立笔记"),
+    ("dashboard.dangling", "悬空链接"),
+    ("dashboard.open_questions", "未解决的问题"),
+    ("dashboard.stale", "{days} 天以上未修改"),
+    ("dashboard.top_tags", "常用标签：{tags}"),
+    ("dashboard.last_commit", "最近提交：{oid} {summary}（{author}，{timestamp}）"),
+    ("dashboard.none", "（无）"),
+    ("dashboard.no_commits", "最近提交：（无）"),
+    ("feed.written", "已把 {count} 个条目写入 {path}"),
+    ("debug.vault_generated", "已在 {dir} 生成 {notes} 篇笔记、{links} 条链接和 {tags} 个标签"),
+    ("debug.vault_broken", "注入了 {dangling} 条悬空链接和 {malformed} 个无法解析的 frontmatter"),
+    ("feed.unsupported", "不支持的订阅源格式：{format}（应为 atom 或 rss）"),
+    ("feed.invalid_since", "无效的 --since 值：{value}（应为 YYYY-MM-DD 或 RFC 3339 时间）"),
+    ("random.none", "没有满足条件的笔记"),
+    ("review.header", "=== 回顾队列（{count}）==="),
+    ("review.empty", "没有需要回顾的笔记：所有笔记最近都已回顾"),
+    ("review.path", "路径：{path}"),
+    ("review.meta", "类型：{node_type} | 反向链接：{backlinks} | 更新于：{updated}"),
+    ("review.prompt", "标记为已回顾？[y/N/q]"),
+    ("review.marked", "已标记为回顾：{title}"),
+    ("review.done", "已回顾 {count} 篇笔记"),
+    ("validate.clean", "未发现违规"),
+    ("validate.summary", "共 {errors} 个错误，{warnings} 个警告"),
+    ("validate.invalid_severity", "无效的严重程度 '{value}'（可选 warn、error）"),
+    ("report.duplicates.none", "未发现重复笔记"),
+    ("report.duplicates.header", "=== 疑似重复（{count}）==="),
+    ("report.duplicates.title", "标题相同"),
+    ("report.duplicates.content", "内容相似"),
+    ("report.duplicates.merge", "合并"),
+    ("publish.done", "已发布 {pages} 篇笔记和 {tags} 个标签页到 {path}"),
+    ("serve.listening", "Memexia API 服务已启动：http://127.0.0.1:{port}"),
+    ("serve.already_running", "本仓库的 memexia serve 已在端口 {port} 上运行（进程 {pid}）"),
+    ("serve.token_created", "已创建权限范围为 {scopes} 的令牌，请在 X-Memexia-Token 请求头中发送："),
+    ("serve.unknown_scope", "未知的权限范围：{scope}（可用 read、nodes:write、edges:write 或 admin）"),
+    ("daemon.read_only", "注意：仓库正由 memexia serve（端口 {port}）使用，以只读方式打开"),
+    ("publish.excluded", "按标签排除了 {count} 篇笔记"),
+    ("watch.started", "正在监听 {path} 的变化（Ctrl-C 退出）"),
+    ("watch.snapshot_every", "每 {interval} 自动快照（保留 {keep} 个）"),
+    ("watch.index_failed", "索引 {path} 失败：{error}"),
+    ("snapshot.taken", "已记录快照 {key}（{hash}）"),
+    ("snapshot.unchanged", "自上次自动快照以来图谱没有变化"),
+    ("snapshot.verify.ok", "正常      {hash}"),
+    ("snapshot.verify.failed", "有问题    {hash}：{problem}"),
+    ("snapshot.verify.repaired", "已修复    {hash}：{problem}"),
+    ("snapshot.verify.dangling", "悬空关联  提交 {commit} -> {hash}：快照不存在"),
+    ("snapshot.verify.summary", "已校验 {count} 个快照，{failed} 个有问题，修复 {repaired} 个 .meta，{dangling} 个悬空的提交关联"),
+    ("snapshot.verify.repair_hint", "运行 `memexia snapshot verify --all --repair-meta` 重新生成 .meta 文件"),
+    ("snapshot.problem.unreadable", "无法读取快照：{error}"),
+    ("snapshot.problem.hash_mismatch", "内容与哈希不符（实际为 {actual}）"),
+    ("snapshot.problem.invalid_nquads", "第 {line} 行不是合法的 N-Quads：{error}"),
+    ("snapshot.problem.missing_meta", "缺少 .meta"),
+    ("snapshot.problem.stale_meta", ".meta 已过期（{reason}）"),
+    ("derivation.invalid_direction", "无效的方向 '{value}'（应为 up、down 或 both）"),
+    ("traversal.depth_limit", "警告：已达到最大深度（history.traversal.max_depth），更深的推导未显示"),
+    ("traversal.node_limit", "警告：已达到节点数上限（history.traversal.max_nodes），结果不完整"),
+    ("traversal.cycle", "警告：推导记录成环：{members}"),
+    ("history.exported", "已导出历史到 {path}：{nodes} 个节点、{snapshots} 个节点快照、{derivations} 条推导记录、{links} 条提交关联"),
+    ("history.imported", "已导入 {snapshots} 个节点快照、{derivations} 条推导记录和 {links} 条提交关联（跳过 {skipped} 条重复记录）"),
+    ("history.foreign_repository", "警告：历史包导出自另一个仓库（根提交不同）"),
+    ("history.missing_commits", "警告：{count} 个引用的提交不在本地 Git 仓库中（如 {commit}）；请先拉取这些提交"),
+    ("node.restored", "已从回收站恢复 {id}"),
+    ("node.updated", "已更新 {id}"),
+    ("node.written", "已更新 {id} 并写回 {path}"),
+    ("node.conflict", "警告：{path} 在上次索引后被修改，已保留文件内容并放弃本次修改"),
+    ("node.change", "  {field}：{before} -> {after}"),
+    ("node.nothing_to_set", "没有要修改的内容；请指定 --title、--type、--tag、--untag、--summary 或 --alias"),
+    ("node.file_restored", "已从最近一次提交检出 {path}"),
+    ("gc.trash_empty", "回收站为空"),
+    ("gc.trash_header", "回收站（{count} 个节点）："),
+    ("gc.purge_hint", "运行 `memexia gc --purge-deleted [--older-than 30d]` 永久删除"),
+    ("gc.purged", "已永久删除 {count} 个节点"),
+    ("dry_run.header", "预演模式：未写入任何内容"),
+    ("dry_run.none", "  无变更"),
+    ("dry_run.add_node", "  将添加/替换 {count} 个节点："),
+    ("dry_run.update_node", "  将更新 {count} 个节点："),
+    ("dry_run.delete_node", "  将删除 {count} 个节点："),
+    ("dry_run.add_edge", "  将添加 {count} 条边："),
+    ("dry_run.delete_edge", "  将删除 {count} 条边："),
+    ("dry_run.more", "    …… 另有 {count} 项"),
+    ("undo.done", "已撤销 {time} 的操作 '{operation}'"),
+    ("undo.counts", "  恢复 {restored} 项，移除 {removed} 项"),
+    ("undo.not_recorded", "注意：'{operation}' 无法通过 `memexia undo` 撤销"),
+    ("doctor.ok", "未发现问题"),
+    ("doctor.snapshots_ok", "图快照：已校验 {count} 个"),
+    ("doctor.snapshots", "图快照："),
+    ("doctor.store_corrupt", "图存储：无法打开（{reason}），已跳过其余图检查"),
+    ("doctor.store_corrupt_hint", "运行 `memexia reindex --full` 从笔记文件重建，或用备份替换 .memexia/graph"),
+    ("doctor.legacy_ids", "发现 {count} 个未经 percent 编码的节点 ID（由旧版本写入）："),
+    ("doctor.fix_hint", "运行 `memexia doctor --fix` 自动修复可修复的问题"),
+    ("doctor.fixed", "已改写 {nodes} 个节点和 {edges} 条边"),
+    ("doctor.title_index", "标题索引与 {count} 个节点的标题不一致："),
+    ("doctor.title_index_fixed", "已为 {count} 个节点重建标题索引"),
+    ("doctor.redundant_inverses", "发现 {count} 对通过逆关系重复表达的链接（每对保留一条即可）："),
+    ("doctor.dangling", "发现 {count} 条端点节点不存在的链接："),
+    ("doctor.dangling_hint", "运行 `memexia doctor --fix-dangling create-placeholders` 或 `--fix-dangling drop` 修复"),
+    ("doctor.dangling_fixed", "已创建 {placeholders} 个占位节点，删除 {dropped} 条链接"),
+    ("doctor.invalid_dangling_strategy", "未知的修复方式 '{value}'（可选 create-placeholders、drop 或 report-only）"),
+    ("watch.caught_up", "已补扫停止期间的变更：索引 {indexed} 个，删除 {deleted} 个"),
+    ("link.annotated", "已为链接 {source} --> {target} 添加注释（共 {count} 条）"),
+    ("link.annotations.header", "=== {source} --> {target} 的注释 ==="),
+    ("link.annotations.none", "此链接没有注释"),
+    ("link.annotations.changes", "修改记录："),
+    ("link.change", "[{time}] 修改了 {field}，原值 {previous}"),
+    ("link.empty_value", "（无）"),
+    ("link.set", "已将 {source} --> {target} 的 {field} 设为 {value}"),
+    ("file.links.annotation", "      注释 [{time}]：{text}"),
+    ("file.links.introduced", "      首次提交于 {commit}"),
+    ("file.links.uncommitted", "      尚未提交"),
+    ("repos.no_config_dir", "找不到用户配置目录，无法使用仓库注册表"),
+    ("repos.none", "没有已登记的仓库"),
+    ("repos.notes", "{count} 篇笔记"),
+    ("repos.missing", "路径不存在"),
+    ("repos.unreadable", "无法读取"),
+    ("repos.added", "已登记 {name}：{path}"),
+    ("repos.removed", "已移除 {name}：{path}"),
+    ("repos.renamed", "已将 {old} 重命名为 {new}"),
+    ("graph.query.exported", "已将 {count} 行写入 {path}"),
+    ("graph.query.template_params", "参数：{params}"),
+    ("graph.query.param_without_template", "--param 只能与 --template 一起使用"),
+    ("graph.query.bad_param", "--param 应为 KEY=VALUE，实际为“{param}”"),
+    ("reindex.renamed", "已重命名：{from} -> {to}"),
+    ("report.contradictions.none", "未发现矛盾"),
+    ("report.contradictions.header", "共 {count} 个矛盾簇，按强度从高到低："),
+    ("report.contradictions.cluster", "簇 {index}（强度合计 {strength}）"),
+    ("report.contradictions.notes", "笔记"),
+    ("report.contradictions.contradicts", "矛盾于"),
+    ("report.contradictions.support", "支持：{from} -> {to}（{strength}）"),
+    ("file.log.none", "{path} 没有历史记录"),
+    ("file.log.created", "创建（{words} 字）"),
+    ("file.log.deleted", "删除"),
+    ("file.log.renamed", "由 {from} 重命名而来"),
+    ("file.log.content_changed", "内容变化（{delta} 字）"),
+    ("file.log.link_added", "新增指向 {target} 的边"),
+    ("file.log.link_removed", "删除指向 {target} 的边"),
+    ("file.log.snapshot", "节点快照 {hash}"),
+    ("file.log.derived_from", "推导自 {node}"),
+    ("file.log.derived_into", "推导出 {node}"),
+    ("tag.none", "没有笔记带有标签 {tag}"),
+    ("tag.renamed", "已将 {count} 篇笔记的标签 {old} 改为 {new}："),
+    ("tag.removed", "已从 {count} 篇笔记移除标签 {tag}："),
+    ("tag.files_rewritten", "改写了 {count} 个文件："),
+    ("tag.promoted", "已将标签 {tag} 升级为笔记 {path}"),
+    ("tag.promoted_counts", "新增 {edges} 条链接和 {derivations} 条推导，移除了 {untagged} 篇笔记的标签："),
+    ("lint.clean", "{files} 个文件中未发现问题"),
+    ("lint.summary", "{files} 个文件中有 {errors} 个错误、{warnings} 个警告"),
+    ("export.ndjson", "已导出 {nodes} 个节点和 {edges} 条边到 {path}"),
+    ("export.invalid_since", "无效的 --since 值：{value}（应为 YYYY-MM-DD 或 RFC 3339 时间）"),
+    ("doctor.schema_version", "存储结构版本：{version}（最新 {latest}）"),
+    ("migrate.invalid_target", "无效的 --to 值：{value}（应为版本号或 `latest`）"),
+    ("migrate.up_to_date", "存储结构已是版本 {version}"),
+    ("migrate.done", "已将存储结构从版本 {from} 迁移到 {to}"),
+    ("export.corpus_dir", "请指定输出目录：memexia export --format corpus <dir>"),
+    ("export.corpus", "已将 {notes} 篇笔记导出为 {files} 个文本文件：{path}"),
+    ("export.subgraph", "已导出 {nodes} 个节点、{stubs} 个边界存根和 {edges} 条边到 {path}"),
+    ("export.subgraph_format", "--tag 和 --query 只支持 nquads、json 和 dot 格式，不支持 {format}"),
+    ("similarity.unknown_node", "第 {line} 行：节点 {id} 不存在"),
+    ("similarity.summary", "新建 {created} 条、更新 {updated} 条建议；跳过低于阈值 {below} 条、已有链接 {linked} 条、重复 {duplicates} 条、节点不存在 {unknown} 条"),
+    ("similarity.review_hint", "使用 `memexia link suggestions list` 查看"),
+    ("archive.done", "已归档 {from} -> {to}"),
+    ("archive.restored", "已取消归档 {from} -> {to}"),
+    ("search.none", "没有匹配 \"{query}\" 的笔记"),
+    ("gc.retention", "保留策略清理了 {links} 个提交快照（删除 {snapshots} 个快照文件）"),
+    ("gc.retention_dry_run", "保留策略将清理 {links} 个提交快照（{snapshots} 个快照文件）"),
+    ("graph.stats.histogram", "边强度分布："),
+    ("graph.stats.matrix", "按节点类型统计关系（起点 关系 终点）："),
+    ("graph.stats.dangling", "{count} 条边的端点节点不存在"),
+    ("graph.stats.out_degree", "各节点类型的平均出度："),
+    ("link.refresh.none", "没有链接使用笔记的旧标题。"),
+    ("link.refresh.preview", "{files} 个文件中的 {count} 个链接使用了旧标题。加上 --write-files 以改写。"),
+    ("link.refresh.done", "已改写 {files} 个文件中的 {count} 个链接并暂存。"),
+    ("link.apply.line_error", "第 {line} 行：{message}"),
+    ("link.apply.strict_failed", "{count} 行无效，未写入任何链接（--strict）"),
+    ("link.apply.none", "没有要写入的链接。"),
+    ("link.apply.preview", "将写入 {count} 个链接："),
+    ("link.apply.prompt", "写入 {count} 个链接？[y/N]"),
+    ("link.apply.needs_yes", "当前不是交互式终端；加上 --yes 以写入链接"),
+    ("link.apply.cancelled", "已取消，未写入任何链接。"),
+    ("link.apply.done", "已写入 {count} 个链接。"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lang() {
+        assert_eq!(Lang::parse("en"), Some(Lang::En));
+        assert_eq!(Lang::parse("en_US.UTF-8"), Some(Lang::En));
+        assert_eq!(Lang::parse("zh"), Some(Lang::Zh));
+        assert_eq!(Lang::parse("zh_CN.UTF-8"), Some(Lang::Zh));
+        assert_eq!(Lang::parse("ZH-Hans"), Some(Lang::Zh));
+        assert_eq!(Lang::parse("fr_FR"), None);
+        assert_eq!(Lang::parse("C"), None);
+    }
+
+    #[test]
+    fn test_resolve_falls_back() {
+        assert_eq!(resolve(["zh_CN.UTF-8"]), Lang::Zh);
+        // 无法识别的值跳过，继续检查后面的来源
+        assert_eq!(resolve(["klingon", "zh_TW"]), Lang::Zh);
+        assert_eq!(resolve(["fr", "C.UTF-8"]), Lang::En);
+        assert_eq!(resolve(Vec::<String>::new()), Lang::En);
+    }
+
+    #[test]
+    fn test_translate_both_locales() {
+        let args: &[(&str, &dyn Display)] = &[("count", &3)];
+        assert_eq!(translate_in(Lang::En, "graph.show.nodes", args), "Nodes (3):");
+        assert_eq!(translate_in(Lang::Zh, "graph.show.nodes", args), "节点（3）：");
+
+        let args: &[(&str, &dyn Display)] = &[("source", &"a.md"), ("target", &"b.md")];
+        assert_eq!(
+            translate_in(Lang::En, "graph.path.none", args),
+            "No path found between a.md and b.md"
+        );
+        assert_eq!(translate_in(Lang::Zh, "graph.path.none", args), "a.md 与 b.md 之间没有路径");
+
+        assert_eq!(translate_in(Lang::Zh, "error.nothing_to_commit", &[]), "没有可提交的内容");
+    }
+
+    #[test]
+    fn test_unknown_key_returns_key() {
+        assert_eq!(translate_in(Lang::Zh, "no.such.key", &[]), "no.such.key");
+    }
+
+    #[test]
+    fn test_catalogs_have_same_keys() {
+        for (key, _) in EN {
+            assert!(lookup(ZH, key).is_some(), "missing zh message: {}", key);
+        }
+        for (key, _) in ZH {
+            assert!(lookup(EN, key).is_some(), "missing en message: {}", key);
+        }
+    }
+}
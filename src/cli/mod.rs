@@ -2,11 +2,19 @@ use clap::{Args, Parser, Subcommand};
 use std::path::PathBuf;
 
 pub mod commands;
+pub mod daemon;
+pub mod exit;
+pub mod i18n;
+pub mod output;
+pub mod repl;
+pub mod serve;
+pub mod timefmt;
 
 #[derive(Parser)]
 #[command(name = "memexia")]
 #[command(about = "Personal knowledge graph system")]
 #[command(version)]
+#[command(after_long_help = exit::EXIT_CODES_HELP)]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
@@ -14,6 +22,42 @@ pub struct Cli {
     /// Verbose output
     #[arg(short, long)]
     pub verbose: bool,
+
+    /// Do not pipe long output through a pager
+    #[arg(long, global = true)]
+    pub no_pager: bool,
+
+    /// Show times in UTC instead of the local timezone
+    #[arg(long, global = true)]
+    pub utc: bool,
+
+    /// Show times as RFC 3339 instead of relative times and localized dates
+    #[arg(long, global = true)]
+    pub iso: bool,
+
+    /// Interface language (en, zh); defaults to MEMEXIA_LANG or the system locale
+    #[arg(long, global = true, value_name = "LANG")]
+    pub lang: Option<String>,
+
+    /// Write tracing spans as JSON lines to a file
+    #[arg(long, global = true, value_name = "FILE")]
+    pub trace_json: Option<PathBuf>,
+
+    /// Run as if memexia was started in this directory
+    #[arg(short = 'C', global = true, value_name = "PATH")]
+    pub chdir: Option<PathBuf>,
+
+    /// Run in a registered repository (see `memexia repos list`)
+    #[arg(long, global = true, value_name = "NAME", conflicts_with = "chdir")]
+    pub repo: Option<String>,
+
+    /// Open the repository without writing to it (for read-only mounts)
+    #[arg(long, global = true)]
+    pub read_only: bool,
+
+    /// Open the repository directly even when `memexia serve` is running for it
+    #[arg(long, global = true)]
+    pub no_daemon: bool,
 }
 
 #[derive(Subcommand)]
@@ -51,6 +95,12 @@ pub enum Commands {
     /// Manage configuration
     Config(ConfigArgs),
 
+    /// Create a new note
+    New(NewArgs),
+
+    /// Append (or prepend) text to a note without opening an editor
+    Append(AppendArgs),
+
     /// Reindex all files in the repository
     Reindex(ReindexArgs),
 
@@ -59,6 +109,136 @@ pub enum Commands {
 
     /// Link operations (create, delete, query)
     Link(LinkArgs),
+
+    /// Rename or remove tags across all notes
+    Tag(TagArgs),
+
+    /// Move a note into the archive directory, keeping its links and history
+    Archive(ArchiveArgs),
+
+    /// Move an archived note back out of the archive directory
+    Unarchive(ArchiveArgs),
+
+    /// Export nodes and edges to interchange files
+    Export(ExportArgs),
+
+    /// Import nodes and edges from interchange files
+    Import(ImportArgs),
+
+    /// Import similarity scores (CSV) as AnalogousTo link suggestions
+    ImportSimilarity(ImportSimilarityArgs),
+
+    /// Show repository statistics (notes, words, tags, activity)
+    Stats(StatsArgs),
+
+    /// Show a vault health summary with an overall score
+    Dashboard(DashboardArgs),
+
+    /// Write an Atom or RSS feed of commits and the notes and links they added
+    Feed(FeedArgs),
+
+    /// Show a randomly selected note
+    Random(RandomArgs),
+
+    /// Review the least recently touched notes
+    Review(ReviewArgs),
+
+    /// Check the graph against the configured validation rules
+    Validate(ValidateArgs),
+
+    /// Check markdown sources for style and structure problems
+    Lint(LintArgs),
+
+    /// Reports about the note collection
+    Report(ReportArgs),
+
+    /// Export a read-only static HTML site of the notes
+    Publish(PublishArgs),
+
+    /// Watch the repository and index changes as they happen
+    Watch(WatchArgs),
+
+    /// Manage automatic graph snapshots
+    Snapshot(SnapshotArgs),
+
+    /// Manage individual nodes (restore from trash)
+    Node(NodeArgs),
+
+    /// Export or import node history for moving a vault to another machine
+    History(HistoryArgs),
+
+    /// Explore how ideas were derived from each other
+    Derivation(DerivationArgs),
+
+    /// Clean up repository data (purge deleted nodes)
+    Gc(GcArgs),
+
+    /// Undo the last link, suggestion or gc operation
+    Undo(UndoArgs),
+
+    /// Check the repository for problems (legacy unencoded node IDs)
+    Doctor(DoctorArgs),
+
+    /// Upgrade the storage schema to a newer version
+    Migrate(MigrateArgs),
+
+    /// Manage the registry of known repositories
+    Repos(ReposArgs),
+
+    /// Developer tools
+    #[cfg(feature = "debug-tools")]
+    Debug(DebugArgs),
+}
+
+impl Commands {
+    /// 命令名称（用于追踪 span）
+    pub fn name(&self) -> &'static str {
+        match self {
+            Commands::Init(_) => "init",
+            Commands::Add(_) => "add",
+            Commands::Status(_) => "status",
+            Commands::Commit(_) => "commit",
+            Commands::Amend(_) => "amend",
+            Commands::Log(_) => "log",
+            Commands::Graph(_) => "graph",
+            Commands::Search(_) => "search",
+            Commands::Sync(_) => "sync",
+            Commands::Serve(_) => "serve",
+            Commands::Config(_) => "config",
+            Commands::New(_) => "new",
+            Commands::Append(_) => "append",
+            Commands::Reindex(_) => "reindex",
+            Commands::File(_) => "file",
+            Commands::Link(_) => "link",
+            Commands::Tag(_) => "tag",
+            Commands::Archive(_) => "archive",
+            Commands::Unarchive(_) => "unarchive",
+            Commands::Export(_) => "export",
+            Commands::Import(_) => "import",
+            Commands::ImportSimilarity(_) => "import-similarity",
+            Commands::Stats(_) => "stats",
+            Commands::Dashboard(_) => "dashboard",
+            Commands::Feed(_) => "feed",
+            Commands::Random(_) => "random",
+            Commands::Review(_) => "review",
+            Commands::Validate(_) => "validate",
+            Commands::Lint(_) => "lint",
+            Commands::Report(_) => "report",
+            Commands::Publish(_) => "publish",
+            Commands::Watch(_) => "watch",
+            Commands::Snapshot(_) => "snapshot",
+            Commands::Node(_) => "node",
+            Commands::History(_) => "history",
+            Commands::Derivation(_) => "derivation",
+            Commands::Gc(_) => "gc",
+            Commands::Undo(_) => "undo",
+            Commands::Doctor(_) => "doctor",
+            Commands::Migrate(_) => "migrate",
+            Commands::Repos(_) => "repos",
+            #[cfg(feature = "debug-tools")]
+            Commands::Debug(_) => "debug",
+        }
+    }
 }
 
 #[derive(Args)]
@@ -66,6 +246,10 @@ pub struct InitArgs {
     /// Path to initialize the repository in
     #[arg(default_value = ".")]
     pub path: PathBuf,
+
+    /// Notes directory relative to the repository (repeatable, "." for the root; default "notes")
+    #[arg(long = "notes", value_name = "DIR")]
+    pub notes: Vec<String>,
 }
 
 #[derive(Args)]
@@ -80,9 +264,15 @@ pub struct StatusArgs {}
 
 #[derive(Args)]
 pub struct CommitArgs {
-    /// Commit message
+    /// Commit message (opens $EDITOR with .memexia/config/commit-template.md when omitted)
     #[arg(short, long)]
-    pub message: String,
+    pub message: Option<String>,
+    /// Automatically stage tracked notes whose content changed
+    #[arg(short, long)]
+    pub all: bool,
+    /// With --all, also stage new notes matching the watch whitelist
+    #[arg(long, requires = "all")]
+    pub include_untracked: bool,
 }
 
 #[derive(Args)]
@@ -100,6 +290,9 @@ pub struct LogArgs {
     /// Show in oneline format
     #[arg(short, long)]
     pub oneline: bool,
+    /// Only show commits whose Affected-Nodes trailer mentions this note
+    #[arg(long, value_name = "PATH")]
+    pub node: Option<PathBuf>,
 }
 
 /// Graph subcommands
@@ -117,6 +310,9 @@ pub enum GraphCommands {
     /// Export graph in DOT format for visualization
     Dot(GraphDotArgs),
 
+    /// Export graph as a Mermaid diagram for embedding in notes
+    Mermaid(GraphMermaidArgs),
+
     /// Show graph statistics
     Stats(GraphStatsArgs),
 
@@ -125,6 +321,15 @@ pub enum GraphCommands {
 
     /// Find path between two nodes
     Path(GraphPathArgs),
+
+    /// Recompute recency-aware effective edge strengths
+    Decay(GraphDecayArgs),
+
+    /// Compute force-directed node positions (graph show --json plus x and y)
+    Layout(GraphLayoutArgs),
+
+    /// Interactive SPARQL shell with preloaded prefixes and history
+    Repl,
 }
 
 #[derive(Args)]
@@ -132,6 +337,9 @@ pub struct GraphShowArgs {
     /// Output as JSON
     #[arg(short, long)]
     pub json: bool,
+    /// Only show notes in this language (e.g. zh, en) and the links between them
+    #[arg(long)]
+    pub lang: Option<String>,
 }
 
 #[derive(Args)]
@@ -142,13 +350,57 @@ pub struct GraphDotArgs {
 }
 
 #[derive(Args)]
-pub struct GraphStatsArgs {}
+pub struct GraphMermaidArgs {
+    /// Center the diagram on this file (default: whole graph)
+    #[arg(short, long)]
+    pub center: Option<PathBuf>,
+    /// Traversal depth from the center node
+    #[arg(short, long, default_value = "1")]
+    pub depth: usize,
+    /// Refuse whole-graph export above this many nodes
+    #[arg(long, default_value = "200")]
+    pub max_nodes: usize,
+    /// Wrap output in a ```mermaid code fence
+    #[arg(long)]
+    pub fence: bool,
+    /// Output file (default: stdout)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct GraphStatsArgs {
+    /// Also show the strength histogram, relation matrix by node type and average out-degree
+    #[arg(long)]
+    pub detailed: bool,
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
 
 #[derive(Args)]
 pub struct GraphQueryArgs {
     /// SPARQL query string
-    #[arg(required = true)]
-    pub query: String,
+    #[arg(required_unless_present_any = ["template", "list_templates"])]
+    pub query: Option<String>,
+    /// Run a named template from .memexia/queries/<name>.rq instead of a query string
+    #[arg(long, value_name = "NAME", conflicts_with = "query")]
+    pub template: Option<String>,
+    /// Template parameter (repeatable)
+    #[arg(long = "param", value_name = "KEY=VALUE", requires = "template")]
+    pub params: Vec<String>,
+    /// List available query templates and their parameters
+    #[arg(long, conflicts_with_all = ["query", "template"])]
+    pub list_templates: bool,
+    /// Write the result bindings to a CSV file
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+    /// Start the CSV with a UTF-8 BOM so Excel detects the encoding
+    #[arg(long, requires = "output")]
+    pub excel: bool,
+    /// Add a <var>_type column telling IRIs, literals and blank nodes apart
+    #[arg(long, requires = "output")]
+    pub typed: bool,
 }
 
 #[derive(Args)]
@@ -159,10 +411,39 @@ pub struct GraphPathArgs {
     pub target: String,
 }
 
+#[derive(Args)]
+pub struct GraphLayoutArgs {
+    /// Number of layout iterations
+    #[arg(long, value_name = "N", default_value_t = 300)]
+    pub iterations: usize,
+    /// Seed for the initial positions; the same seed gives the same layout
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+    /// Output file (default: stdout)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct GraphDecayArgs {
+    /// Days after which an unreinforced edge keeps half its strength
+    #[arg(long, value_name = "N")]
+    pub half_life_days: f64,
+    /// Report the changes without writing them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
 #[derive(Args)]
 pub struct SearchArgs {
     /// Search query
     pub query: String,
+    /// Include archived notes
+    #[arg(long)]
+    pub include_archived: bool,
+    /// Only search notes in this language (e.g. zh, en; "zh" also matches "zh-cn")
+    #[arg(long)]
+    pub lang: Option<String>,
 }
 
 #[derive(Args)]
@@ -173,6 +454,36 @@ pub struct ServeArgs {
     /// Port to listen on
     #[arg(short, long, default_value = "3000")]
     pub port: u16,
+    /// Expose Prometheus metrics at /metrics (the server has no authentication)
+    #[arg(long)]
+    pub metrics_public: bool,
+    #[command(subcommand)]
+    pub command: Option<ServeCommands>,
+}
+
+#[derive(Subcommand)]
+pub enum ServeCommands {
+    /// Manage scoped API tokens ([[serve.tokens]])
+    Token(ServeTokenArgs),
+}
+
+#[derive(Args)]
+pub struct ServeTokenArgs {
+    #[command(subcommand)]
+    pub command: ServeTokenCommands,
+}
+
+#[derive(Subcommand)]
+pub enum ServeTokenCommands {
+    /// Generate a token and save it to the repository config
+    Create(ServeTokenCreateArgs),
+}
+
+#[derive(Args)]
+pub struct ServeTokenCreateArgs {
+    /// Comma-separated scopes: read, nodes:write, edges:write, admin
+    #[arg(long, value_delimiter = ',', required = true)]
+    pub scopes: Vec<String>,
 }
 
 #[derive(Args)]
@@ -183,14 +494,61 @@ pub struct ConfigArgs {
     pub value: Option<String>,
 }
 
+#[derive(Args)]
+pub struct NewArgs {
+    /// Title of the note
+    pub title: String,
+    /// Node type (e.g., Concept, Question, Evidence); defaults to the folder default, then Concept
+    #[arg(short = 't', long = "type")]
+    pub node_type: Option<String>,
+    /// Tags to add (can be repeated)
+    #[arg(long = "tag")]
+    pub tags: Vec<String>,
+    /// Template name from .memexia/templates/<name>.md
+    #[arg(long)]
+    pub template: Option<String>,
+    /// Directory for the note, relative to the repository root (defaults to paths.notes)
+    #[arg(long)]
+    pub dir: Option<String>,
+    /// Open the new note in $EDITOR
+    #[arg(short, long)]
+    pub edit: bool,
+}
+
+#[derive(Args)]
+pub struct AppendArgs {
+    /// Path of the note
+    pub path: PathBuf,
+    /// Text to insert ("-" reads from stdin)
+    pub text: String,
+    /// Insert at the top of the note (below frontmatter) or directly under --section
+    #[arg(long)]
+    pub prepend: bool,
+    /// Insert under this heading, creating it at the end of the note if missing
+    #[arg(long)]
+    pub section: Option<String>,
+    /// Commit the note after inserting
+    #[arg(long)]
+    pub commit: bool,
+    /// Commit message (defaults to "Update <path>")
+    #[arg(short, long, requires = "commit")]
+    pub message: Option<String>,
+}
+
 #[derive(Args)]
 pub struct ReindexArgs {
-    /// Full reindex (delete all and rebuild)
+    /// Full reindex (rebuild the graph store from scratch)
     #[arg(short, long)]
     pub full: bool,
     /// Path to reindex (defaults to current directory)
     #[arg(default_value = ".")]
     pub path: PathBuf,
+    /// Show what would change without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Treat moved or renamed notes as new notes instead of carrying over their node
+    #[arg(long)]
+    pub no_rename_detection: bool,
 }
 
 /// File subcommands
@@ -208,6 +566,33 @@ pub enum FileCommands {
     Links(FileLinksArgs),
     /// Show incoming backlinks to a file
     Backlinks(FileBacklinksArgs),
+    /// Show the change history of a file: commits, node snapshots and derivations
+    Log(FileLogArgs),
+    /// Move an H2/H3 section into a new linked note
+    Split(FileSplitArgs),
+    /// Expand {{backlinks}}, {{related}} and {{children}} placeholders from the graph
+    RenderTemplate(FileRenderTemplateArgs),
+}
+
+#[derive(Args)]
+pub struct FileRenderTemplateArgs {
+    /// File path
+    pub path: PathBuf,
+}
+
+#[derive(Args)]
+pub struct FileSplitArgs {
+    /// File path
+    pub path: PathBuf,
+    /// Heading of the section to extract (exact match)
+    #[arg(long)]
+    pub heading: String,
+    /// Type of the new note (default: Concept)
+    #[arg(short = 't', long = "type")]
+    pub node_type: Option<String>,
+    /// Replace the section with an embed (![[...]]) instead of a Contains link
+    #[arg(long)]
+    pub embed: bool,
 }
 
 #[derive(Args)]
@@ -220,6 +605,12 @@ pub struct FileInfoArgs {
 pub struct FileLinksArgs {
     /// File path
     pub path: PathBuf,
+    /// Show links as of a date (YYYY-MM-DD) or revision (hash, HEAD~N, branch) instead (repeatable)
+    #[arg(long, value_name = "DATE|REV")]
+    pub at: Vec<String>,
+    /// Also show the commit that first introduced each link
+    #[arg(short, long)]
+    pub verbose: bool,
 }
 
 #[derive(Args)]
@@ -228,6 +619,15 @@ pub struct FileBacklinksArgs {
     pub path: PathBuf,
 }
 
+#[derive(Args)]
+pub struct FileLogArgs {
+    /// File path
+    pub path: PathBuf,
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
 /// Link subcommands
 #[derive(Args)]
 pub struct LinkArgs {
@@ -243,6 +643,24 @@ pub enum LinkCommands {
     Delete(LinkDeleteArgs),
     /// Query links
     Query(LinkQueryArgs),
+    /// Manage machine-suggested links (AI or derived)
+    Suggestions(SuggestionsArgs),
+    /// Propose links from shared tags and title mentions
+    Propose(LinkProposeArgs),
+    /// Attach a timestamped note to an existing link
+    Annotate(LinkAnnotateArgs),
+    /// List the notes attached to a link
+    Annotations(LinkAnnotationsArgs),
+    /// Change a link's strength (0.0-1.0) in place, keeping the previous value in its history
+    SetStrength(LinkSetStrengthArgs),
+    /// Change a link's description in place (empty to clear), keeping the previous value in its history
+    SetDescription(LinkSetDescriptionArgs),
+    /// Change a link's relation in place, keeping the previous value in its history
+    SetRelation(LinkSetRelationArgs),
+    /// Rewrite wiki links that still use a note's previous title
+    Refresh(LinkRefreshArgs),
+    /// Create many links from a file with one `a.md --Supports(0.8)--> b.md` per line
+    Apply(LinkApplyArgs),
 }
 
 #[derive(Args)]
@@ -251,7 +669,7 @@ pub struct LinkCreateArgs {
     pub source: PathBuf,
     /// Target file path
     pub target: PathBuf,
-    /// Relation type (e.g., RelatedTo, Supports, Contradicts)
+    /// Relation type (e.g., RelatedTo, Supports, Contradicts, or a custom name like Cites-Methodology)
     #[arg(short, long, default_value = "RelatedTo")]
     pub relation: String,
 }
@@ -262,6 +680,9 @@ pub struct LinkDeleteArgs {
     pub source: PathBuf,
     /// Target file path
     pub target: PathBuf,
+    /// Show what would change without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
 }
 
 #[derive(Args)]
@@ -273,3 +694,640 @@ pub struct LinkQueryArgs {
     /// Filter by relation type
     pub relation: Option<String>,
 }
+
+#[derive(Args)]
+pub struct LinkProposeArgs {
+    /// Write proposals as AI-suggested links for later accept/reject
+    #[arg(long)]
+    pub apply: bool,
+    /// Minimum number of shared tags for a proposal
+    #[arg(long, value_name = "N", default_value_t = crate::core::suggest::DEFAULT_MIN_SHARED_TAGS)]
+    pub min_shared_tags: usize,
+    /// Include archived notes
+    #[arg(long)]
+    pub include_archived: bool,
+}
+
+#[derive(Args)]
+pub struct SuggestionsArgs {
+    #[command(subcommand)]
+    pub command: SuggestionCommands,
+}
+
+#[derive(Subcommand)]
+pub enum SuggestionCommands {
+    /// List suggested links sorted by confidence
+    List,
+    /// Accept a suggested link (marks it explicit)
+    Accept(SuggestionIdArgs),
+    /// Reject a suggested link (deletes it)
+    Reject(SuggestionIdArgs),
+    /// Accept all suggested links above a confidence threshold
+    AcceptAll(SuggestionAcceptAllArgs),
+}
+
+#[derive(Args)]
+pub struct SuggestionIdArgs {
+    /// Edge ID (urn:memexia:edge:<from>-<to>)
+    pub edge_id: String,
+}
+
+#[derive(Args)]
+pub struct SuggestionAcceptAllArgs {
+    /// Minimum confidence of links to accept
+    #[arg(long, default_value_t = crate::core::suggestions::DEFAULT_MIN_CONFIDENCE)]
+    pub min_confidence: f64,
+}
+
+/// Tag subcommands
+#[derive(Args)]
+pub struct TagArgs {
+    #[command(subcommand)]
+    pub command: TagCommands,
+}
+
+#[derive(Subcommand)]
+pub enum TagCommands {
+    /// Rename a tag on every note that carries it
+    Rename(TagRenameArgs),
+    /// Remove a tag from every note that carries it
+    Remove(TagRemoveArgs),
+    /// Turn a tag into a concept note linked from every note that carries it
+    Promote(TagPromoteArgs),
+}
+
+#[derive(Args)]
+pub struct TagRenameArgs {
+    /// Current tag
+    pub old: String,
+    /// New tag
+    pub new: String,
+    /// Also rewrite frontmatter and inline tags in the note files and stage them
+    #[arg(long)]
+    pub write_files: bool,
+    /// Also rename hierarchical child tags (old/child -> new/child)
+    #[arg(short, long)]
+    pub recursive: bool,
+    /// Show what would change without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Args)]
+pub struct TagRemoveArgs {
+    /// Tag to remove
+    pub tag: String,
+    /// Also rewrite frontmatter and inline tags in the note files and stage them
+    #[arg(long)]
+    pub write_files: bool,
+    /// Also remove hierarchical child tags (tag/child)
+    #[arg(short, long)]
+    pub recursive: bool,
+    /// Show what would change without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Args)]
+pub struct TagPromoteArgs {
+    /// Tag to promote; the new note is titled after it
+    pub tag: String,
+    /// Node type of the new note (e.g., Concept, Question); defaults to the folder default, then Concept
+    #[arg(short = 't', long = "type")]
+    pub node_type: Option<String>,
+    /// Relation from each tagged note to the new note
+    #[arg(short, long, default_value = "RelatedTo")]
+    pub relation: String,
+    /// Remove the tag from the notes that carried it
+    #[arg(long)]
+    pub consume: bool,
+    /// With --consume, also remove the tag from the note files and stage them
+    #[arg(long, requires = "consume")]
+    pub write_files: bool,
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// Export format (csv, ndjson, corpus, nquads, json, dot)
+    #[arg(short, long, default_value = "csv")]
+    pub format: String,
+    /// Output directory (corpus)
+    pub dir: Option<PathBuf>,
+    /// Output file for nodes
+    #[arg(long)]
+    pub nodes: Option<PathBuf>,
+    /// Output file for edges
+    #[arg(long)]
+    pub edges: Option<PathBuf>,
+    /// Output file for ndjson, nquads, json and dot (defaults to stdout)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+    /// Only export nodes with this tag and the edges between them (nquads, json, dot)
+    #[arg(long, conflicts_with = "query")]
+    pub tag: Option<String>,
+    /// Only export nodes bound to ?node by this SPARQL SELECT query (nquads, json, dot)
+    #[arg(long, value_name = "SPARQL")]
+    pub query: Option<String>,
+    /// Also export edges leaving the selection, with outside nodes as stubs
+    #[arg(long)]
+    pub include_boundary: bool,
+    /// Only export nodes (ndjson)
+    #[arg(long, conflicts_with = "edges_only")]
+    pub nodes_only: bool,
+    /// Only export edges (ndjson)
+    #[arg(long)]
+    pub edges_only: bool,
+    /// Only export entities updated at or after this date or time (ndjson)
+    #[arg(long, value_name = "DATE")]
+    pub since: Option<String>,
+    /// Split notes into chunks of this many words (corpus)
+    #[arg(long, value_name = "WORDS")]
+    pub chunk: Option<usize>,
+    /// Words shared by consecutive chunks; defaults to a tenth of --chunk (corpus)
+    #[arg(long, value_name = "WORDS", requires = "chunk")]
+    pub overlap: Option<usize>,
+}
+
+#[derive(Args)]
+pub struct ImportArgs {
+    /// Import format (csv, ndjson)
+    #[arg(short, long, default_value = "csv")]
+    pub format: String,
+    /// Input file for nodes
+    #[arg(long)]
+    pub nodes: Option<PathBuf>,
+    /// Input file for edges
+    #[arg(long)]
+    pub edges: Option<PathBuf>,
+    /// Input file for ndjson (defaults to stdin)
+    #[arg(short, long)]
+    pub input: Option<PathBuf>,
+    /// Create placeholder nodes for edges referencing missing node IDs
+    #[arg(long)]
+    pub create_placeholders: bool,
+    /// Show what would change without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Args)]
+pub struct ArchiveArgs {
+    /// Path of the note
+    pub path: PathBuf,
+}
+
+#[derive(Args)]
+pub struct ImportSimilarityArgs {
+    /// CSV file with rows `node_id_a,node_id_b,score`
+    pub file: PathBuf,
+    /// Minimum score for a pair to become a suggested link
+    #[arg(long, default_value_t = crate::core::similarity_import::DEFAULT_THRESHOLD)]
+    pub threshold: f64,
+    /// Show what would change without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Args)]
+pub struct StatsArgs {
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+    /// Include archived notes
+    #[arg(long)]
+    pub include_archived: bool,
+    /// Show node and edge counts per commit, computed from graph snapshots
+    #[arg(long, conflicts_with = "include_archived")]
+    pub history: bool,
+}
+
+#[derive(Args)]
+pub struct DashboardArgs {
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args)]
+pub struct FeedArgs {
+    /// Only include commits at or after this date or time
+    #[arg(long, value_name = "DATE")]
+    pub since: Option<String>,
+    /// Feed format (atom, rss)
+    #[arg(short, long, default_value = "atom")]
+    pub format: String,
+    /// Write the feed to this file instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct RandomArgs {
+    /// Only pick notes of this type (e.g., Concept, Question)
+    #[arg(short = 't', long = "type")]
+    pub node_type: Option<String>,
+    /// Only pick notes with this tag
+    #[arg(long)]
+    pub tag: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ReviewArgs {
+    /// Number of notes to show
+    #[arg(short = 'n', long, default_value_t = 10)]
+    pub limit: usize,
+    /// Skip notes reviewed within this many days
+    #[arg(long, value_name = "DAYS", default_value_t = crate::core::review::DEFAULT_REVIEW_INTERVAL_DAYS)]
+    pub interval: i64,
+    /// Mark a note as reviewed and exit
+    #[arg(long, value_name = "PATH")]
+    pub mark_reviewed: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct ValidateArgs {
+    /// Only report violations at or above this severity (warn, error)
+    #[arg(long, default_value = "warn")]
+    pub severity: String,
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args)]
+pub struct LintArgs {
+    /// Files or directories to check (defaults to all notes)
+    pub paths: Vec<PathBuf>,
+    /// Output diagnostics as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Report subcommands
+#[derive(Args)]
+pub struct ReportArgs {
+    #[command(subcommand)]
+    pub command: ReportCommands,
+}
+
+#[derive(Subcommand)]
+pub enum ReportCommands {
+    /// Find notes that look like duplicates of each other
+    Duplicates(ReportDuplicatesArgs),
+    /// List clusters of notes connected by unresolved contradictions
+    Contradictions(ReportContradictionsArgs),
+}
+
+#[derive(Args)]
+pub struct ReportDuplicatesArgs {
+    /// Also compare note content, not just titles
+    #[arg(long)]
+    pub content: bool,
+    /// Minimum content similarity (0.0-1.0) for --content
+    #[arg(long, default_value_t = crate::core::duplicates::DEFAULT_CONTENT_THRESHOLD)]
+    pub threshold: f64,
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args)]
+pub struct ReportContradictionsArgs {
+    /// Ignore contradictions weaker than this strength (0.0-1.0)
+    #[arg(long, default_value_t = 0.0)]
+    pub min_strength: f64,
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args)]
+pub struct PublishArgs {
+    /// Output directory for the generated site
+    pub out_dir: PathBuf,
+    /// Include archived notes
+    #[arg(long)]
+    pub include_archived: bool,
+}
+
+#[derive(Args)]
+pub struct WatchArgs {
+    /// Snapshot the graph at this interval when it changed (e.g. 6h, 30m); overrides watch.auto_snapshot_interval
+    #[arg(long, value_name = "INTERVAL")]
+    pub snapshot_every: Option<String>,
+}
+
+/// Snapshot subcommands
+#[derive(Args)]
+pub struct SnapshotArgs {
+    #[command(subcommand)]
+    pub command: SnapshotCommands,
+}
+
+#[derive(Subcommand)]
+pub enum SnapshotCommands {
+    /// Take an automatic snapshot now if the graph changed since the last one
+    Now,
+    /// Check snapshot hashes, .meta records, N-Quads syntax and commit links
+    Verify(SnapshotVerifyArgs),
+}
+
+#[derive(Args)]
+pub struct SnapshotVerifyArgs {
+    /// Snapshot hash or unique prefix to verify
+    #[arg(required_unless_present = "all", conflicts_with = "all")]
+    pub hash: Option<String>,
+    /// Verify every snapshot and check that commit links point to existing snapshots
+    #[arg(long)]
+    pub all: bool,
+    /// Regenerate missing or stale .meta files from the snapshot data
+    #[arg(long)]
+    pub repair_meta: bool,
+}
+
+/// History subcommands
+#[derive(Args)]
+pub struct HistoryArgs {
+    #[command(subcommand)]
+    pub command: HistoryCommands,
+}
+
+#[derive(Subcommand)]
+pub enum HistoryCommands {
+    /// Bundle node snapshots, derivations and commit links into a tar file
+    Export(HistoryExportArgs),
+    /// Unpack a history bundle into .memexia/history
+    Import(HistoryImportArgs),
+}
+
+#[derive(Args)]
+pub struct HistoryExportArgs {
+    /// Output tar file
+    pub file: PathBuf,
+}
+
+#[derive(Args)]
+pub struct HistoryImportArgs {
+    /// History bundle created by `memexia history export`
+    pub file: PathBuf,
+    /// Merge with existing history (duplicates are skipped)
+    #[arg(long)]
+    pub merge: bool,
+}
+
+/// Derivation subcommands
+#[derive(Args)]
+pub struct DerivationArgs {
+    #[command(subcommand)]
+    pub command: DerivationCommands,
+}
+
+#[derive(Subcommand)]
+pub enum DerivationCommands {
+    /// Show the derivation tree rooted at a node
+    Tree(DerivationTreeArgs),
+}
+
+#[derive(Args)]
+pub struct DerivationTreeArgs {
+    /// Note path or node ID
+    pub node: String,
+    /// Which way to follow derivations (up, down, both)
+    #[arg(long, default_value = "down")]
+    pub direction: String,
+    /// Output Graphviz DOT instead of indented text
+    #[arg(long)]
+    pub dot: bool,
+    /// Maximum number of levels to expand
+    #[arg(long)]
+    pub depth: Option<usize>,
+}
+
+/// Node subcommands
+#[derive(Args)]
+pub struct NodeArgs {
+    #[command(subcommand)]
+    pub command: NodeCommands,
+}
+
+#[derive(Subcommand)]
+pub enum NodeCommands {
+    /// Restore a deleted file's node from the trash (and the file from git if missing)
+    Restore(NodeRestoreArgs),
+    /// Edit a note's node and write the change back to its frontmatter
+    Set(NodeSetArgs),
+}
+
+#[derive(Args)]
+pub struct NodeSetArgs {
+    /// Node ID or path of the note file
+    #[arg(value_name = "ID|PATH")]
+    pub target: String,
+    /// New title
+    #[arg(long)]
+    pub title: Option<String>,
+    /// New type (e.g., Concept, Question)
+    #[arg(short = 't', long = "type")]
+    pub node_type: Option<String>,
+    /// Add a tag (repeatable)
+    #[arg(long = "tag", visible_alias = "add-tag", value_name = "TAG")]
+    pub add_tags: Vec<String>,
+    /// Remove a tag (repeatable)
+    #[arg(long = "untag", visible_alias = "remove-tag", value_name = "TAG")]
+    pub remove_tags: Vec<String>,
+    /// New summary
+    #[arg(long)]
+    pub summary: Option<String>,
+    /// Replace the aliases (repeatable)
+    #[arg(long = "alias", value_name = "ALIAS")]
+    pub aliases: Vec<String>,
+    /// Allow changing the title of a file-backed node (written back to its frontmatter)
+    #[arg(long)]
+    pub write_file: bool,
+}
+
+#[derive(Args)]
+pub struct NodeRestoreArgs {
+    /// Path of the deleted file
+    pub path: PathBuf,
+}
+
+#[derive(Args)]
+pub struct GcArgs {
+    /// Permanently remove nodes in the trash
+    #[arg(long)]
+    pub purge_deleted: bool,
+    /// Only purge nodes deleted at least this long ago (e.g. 30d); default purges all
+    #[arg(long, value_name = "AGE", requires = "purge_deleted")]
+    pub older_than: Option<String>,
+    /// Show what would change without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Args)]
+pub struct UndoArgs {}
+
+#[derive(Args)]
+pub struct DoctorArgs {
+    /// Rewrite problems that can be fixed automatically
+    #[arg(long)]
+    pub fix: bool,
+    /// Repair links whose endpoint nodes are missing (create-placeholders, drop, report-only)
+    #[arg(long, value_name = "STRATEGY")]
+    pub fix_dangling: Option<String>,
+}
+
+#[derive(Args)]
+pub struct MigrateArgs {
+    /// Target schema version (a number or `latest`)
+    #[arg(long, default_value = "latest")]
+    pub to: String,
+}
+
+#[derive(Args)]
+pub struct LinkAnnotateArgs {
+    /// Source file path
+    pub source: PathBuf,
+    /// Target file path
+    pub target: PathBuf,
+    /// Annotation text
+    #[arg(long)]
+    pub text: String,
+}
+
+#[derive(Args)]
+pub struct LinkAnnotationsArgs {
+    /// Source file path
+    pub source: PathBuf,
+    /// Target file path
+    pub target: PathBuf,
+}
+
+#[derive(Args)]
+pub struct LinkSetStrengthArgs {
+    /// Source file path
+    pub source: PathBuf,
+    /// Target file path
+    pub target: PathBuf,
+    /// New strength between 0.0 and 1.0
+    pub value: f64,
+}
+
+#[derive(Args)]
+pub struct LinkSetDescriptionArgs {
+    /// Source file path
+    pub source: PathBuf,
+    /// Target file path
+    pub target: PathBuf,
+    /// New description; an empty string clears it
+    pub value: String,
+}
+
+#[derive(Args)]
+pub struct LinkSetRelationArgs {
+    /// Source file path
+    pub source: PathBuf,
+    /// Target file path
+    pub target: PathBuf,
+    /// New relation type (e.g., Supports, Contradicts, or a custom name)
+    pub value: String,
+}
+
+#[derive(Args)]
+pub struct LinkRefreshArgs {
+    /// Rewrite the links in the Markdown files and stage them (default: only report)
+    #[arg(long)]
+    pub write_files: bool,
+}
+
+#[derive(Args)]
+pub struct LinkApplyArgs {
+    /// File with one link per line: `<source> --Relation(strength)--> <target> # description`
+    pub file: PathBuf,
+    /// Apply without asking for confirmation
+    #[arg(short, long)]
+    pub yes: bool,
+    /// Apply nothing if any line is invalid or fails
+    #[arg(long)]
+    pub strict: bool,
+}
+
+#[derive(Args)]
+pub struct ReposArgs {
+    #[command(subcommand)]
+    pub command: ReposCommands,
+}
+
+#[derive(Subcommand)]
+pub enum ReposCommands {
+    /// List registered repositories
+    List,
+    /// Register a repository
+    Add(ReposAddArgs),
+    /// Forget a registered repository (files are kept)
+    Remove(ReposRemoveArgs),
+    /// Rename a registered repository
+    Rename(ReposRenameArgs),
+}
+
+#[derive(Args)]
+pub struct ReposAddArgs {
+    /// Repository path
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+    /// Name to register under (defaults to the directory name)
+    #[arg(long)]
+    pub name: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ReposRemoveArgs {
+    /// Registered name
+    pub name: String,
+}
+
+#[derive(Args)]
+pub struct ReposRenameArgs {
+    /// Current name
+    pub old: String,
+    /// New name
+    pub new: String,
+}
+
+/// Developer tools (`debug-tools` feature)
+#[cfg(feature = "debug-tools")]
+#[derive(Args)]
+pub struct DebugArgs {
+    #[command(subcommand)]
+    pub command: DebugCommands,
+}
+
+#[cfg(feature = "debug-tools")]
+#[derive(Subcommand)]
+pub enum DebugCommands {
+    /// Generate a deterministic synthetic vault for benchmarks and tests
+    GenerateVault(GenerateVaultArgs),
+}
+
+#[cfg(feature = "debug-tools")]
+#[derive(Args)]
+pub struct GenerateVaultArgs {
+    /// Directory to write the vault into (notes go under <dir>/notes)
+    pub dir: PathBuf,
+    /// Number of notes
+    #[arg(long, default_value_t = 1000)]
+    pub notes: usize,
+    /// Outgoing links per note
+    #[arg(long, default_value_t = 4)]
+    pub links_per_note: usize,
+    /// Size of the tag vocabulary
+    #[arg(long, default_value_t = 50)]
+    pub tags: usize,
+    /// Random seed; the same seed and options always produce identical files
+    #[arg(long, default_value_t = 42)]
+    pub seed: u64,
+    /// Fraction of notes (0-1) given a dangling link or malformed frontmatter
+    #[arg(long, default_value_t = 0.0)]
+    pub broken_fraction: f64,
+}
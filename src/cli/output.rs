@@ -0,0 +1,271 @@
+//! 终端输出模块
+//!
+//! 提供长输出分页和按终端宽度截断等共享工具：
+//!
+//! - [`maybe_page`] - 输出超过一屏且 stdout 为终端时通过分页器输出
+//! - [`truncate_to_width`] - 按显示宽度（中日韩字符占两列）截断并追加省略号
+
+use std::io::{IsTerminal, Write};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// 默认分页器
+pub const DEFAULT_PAGER: &str = "less -FRX";
+
+/// 无法获取终端尺寸时使用的默认宽度
+pub const DEFAULT_WIDTH: usize = 80;
+
+/// 无法获取终端尺寸时使用的默认高度
+pub const DEFAULT_HEIGHT: usize = 24;
+
+/// 截断时追加的省略号
+const ELLIPSIS: char = '…';
+
+/// 是否启用分页（由 `--no-pager` 关闭）
+static PAGER_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// 设置是否启用分页
+pub fn set_pager_enabled(enabled: bool) {
+    PAGER_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// 是否启用分页
+pub fn pager_enabled() -> bool {
+    PAGER_ENABLED.load(Ordering::Relaxed)
+}
+
+/// 输出内容，必要时通过分页器
+///
+/// stdout 为终端、分页未被禁用且内容行数超过终端高度时，
+/// 使用 `$PAGER`（默认 `less -FRX`）显示；分页器启动失败时直接输出
+///
+/// # Arguments
+///
+/// * `content` - 要输出的完整内容
+pub fn maybe_page(content: &str) {
+    let is_tty = std::io::stdout().is_terminal();
+    let height = terminal_size().map(|(_, h)| h).unwrap_or(DEFAULT_HEIGHT);
+
+    if should_page(content, pager_enabled(), is_tty, height) && page(content) {
+        return;
+    }
+
+    print_direct(content);
+}
+
+/// 判断是否需要分页
+///
+/// # Arguments
+///
+/// * `content` - 要输出的内容
+/// * `enabled` - 是否启用分页
+/// * `is_tty` - stdout 是否为终端
+/// * `height` - 终端高度（行数）
+pub fn should_page(content: &str, enabled: bool, is_tty: bool, height: usize) -> bool {
+    enabled && is_tty && content.lines().count() > height
+}
+
+/// 解析分页器命令
+///
+/// `$PAGER` 未设置或为空时使用 [`DEFAULT_PAGER`]
+pub fn pager_command(env_pager: Option<&str>) -> String {
+    match env_pager.map(str::trim) {
+        Some(pager) if !pager.is_empty() => pager.to_string(),
+        _ => DEFAULT_PAGER.to_string(),
+    }
+}
+
+/// 通过分页器输出，成功返回 true
+fn page(content: &str) -> bool {
+    let pager = pager_command(std::env::var("PAGER").ok().as_deref());
+
+    #[cfg(windows)]
+    let child = Command::new("cmd").args(["/C", &pager]).stdin(Stdio::piped()).spawn();
+    #[cfg(not(windows))]
+    let child = Command::new("sh").args(["-c", &pager]).stdin(Stdio::piped()).spawn();
+
+    let mut child = match child {
+        Ok(child) => child,
+        Err(_) => return false,
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // 用户提前退出分页器时会出现 BrokenPipe，忽略即可
+        let _ = stdin.write_all(content.as_bytes());
+    }
+    let _ = child.wait();
+    true
+}
+
+/// 直接输出到 stdout（忽略管道关闭错误）
+fn print_direct(content: &str) {
+    let mut stdout = std::io::stdout().lock();
+    let _ = stdout.write_all(content.as_bytes());
+    let _ = stdout.flush();
+}
+
+/// 获取终端尺寸（列数，行数）
+///
+/// 优先查询 stdout 所在终端，其次读取 `COLUMNS` / `LINES` 环境变量
+pub fn terminal_size() -> Option<(usize, usize)> {
+    query_terminal_size().or_else(|| {
+        let cols = std::env::var("COLUMNS").ok()?.parse().ok()?;
+        let rows = std::env::var("LINES").ok()?.parse().ok()?;
+        Some((cols, rows))
+    })
+}
+
+/// 获取终端宽度，无法获取时返回 [`DEFAULT_WIDTH`]
+pub fn terminal_width() -> usize {
+    terminal_size().map(|(w, _)| w).unwrap_or(DEFAULT_WIDTH)
+}
+
+#[cfg(unix)]
+fn query_terminal_size() -> Option<(usize, usize)> {
+    use std::os::unix::io::AsRawFd;
+
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let fd = std::io::stdout().as_raw_fd();
+    let ret = unsafe { libc::ioctl(fd, libc::TIOCGWINSZ, &mut size) };
+    if ret == 0 && size.ws_col > 0 && size.ws_row > 0 {
+        Some((size.ws_col as usize, size.ws_row as usize))
+    } else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+fn query_terminal_size() -> Option<(usize, usize)> {
+    None
+}
+
+/// 字符串的显示宽度（中日韩全角字符占两列）
+pub fn display_width(s: &str) -> usize {
+    UnicodeWidthStr::width(s)
+}
+
+/// 按显示宽度截断字符串
+///
+/// 超出 `max_width` 时截断并以 `…` 结尾，结果宽度不超过 `max_width`
+///
+/// # Arguments
+///
+/// * `s` - 原字符串
+/// * `max_width` - 最大显示宽度
+pub fn truncate_to_width(s: &str, max_width: usize) -> String {
+    if display_width(s) <= max_width {
+        return s.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+
+    // 为省略号预留一列
+    let budget = max_width - 1;
+    let mut result = String::new();
+    let mut width = 0;
+    for c in s.chars() {
+        let w = UnicodeWidthChar::width(c).unwrap_or(0);
+        if width + w > budget {
+            break;
+        }
+        width += w;
+        result.push(c);
+    }
+    result.push(ELLIPSIS);
+    result
+}
+
+/// 按显示宽度截断并右侧补空格到固定宽度
+pub fn fit_to_width(s: &str, width: usize) -> String {
+    let truncated = truncate_to_width(s, width);
+    let padding = width.saturating_sub(display_width(&truncated));
+    format!("{}{}", truncated, " ".repeat(padding))
+}
+
+/// 格式化计数，添加千位分隔符（如 `1,234`）
+pub fn format_count(n: usize) -> String {
+    let digits = n.to_string();
+    let mut result = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            result.push(',');
+        }
+        result.push(c);
+    }
+    result
+}
+
+/// 格式化耗时为秒（保留一位小数，如 `3.2s`）
+pub fn format_seconds(elapsed: std::time::Duration) -> String {
+    format!("{:.1}s", elapsed.as_secs_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_ascii() {
+        assert_eq!(truncate_to_width("Free Will", 20), "Free Will");
+        assert_eq!(truncate_to_width("Free Will", 9), "Free Will");
+        assert_eq!(truncate_to_width("Free Will", 6), "Free …");
+        assert_eq!(truncate_to_width("Free Will", 1), "…");
+        assert_eq!(truncate_to_width("Free Will", 0), "");
+    }
+
+    #[test]
+    fn test_truncate_wide_chars() {
+        // 每个汉字占两列
+        assert_eq!(display_width("自由意志"), 8);
+        assert_eq!(truncate_to_width("自由意志", 8), "自由意志");
+        assert_eq!(truncate_to_width("自由意志", 7), "自由意…");
+        // 剩余一列放不下一个汉字，只放省略号
+        assert_eq!(truncate_to_width("自由意志", 6), "自由…");
+        assert_eq!(display_width(&truncate_to_width("自由意志", 6)), 5);
+        assert_eq!(truncate_to_width("a自由", 4), "a自…");
+    }
+
+    #[test]
+    fn test_fit_to_width() {
+        assert_eq!(fit_to_width("ab", 4), "ab  ");
+        assert_eq!(fit_to_width("自由意志", 6), "自由… ");
+        assert_eq!(display_width(&fit_to_width("自由意志", 6)), 6);
+    }
+
+    #[test]
+    fn test_should_page() {
+        let long = "line\n".repeat(30);
+        assert!(should_page(&long, true, true, 24));
+        assert!(!should_page("short\n", true, true, 24));
+        // 非终端或 --no-pager 时直接输出
+        assert!(!should_page(&long, true, false, 24));
+        assert!(!should_page(&long, false, true, 24));
+    }
+
+    #[test]
+    fn test_no_pager_flag() {
+        set_pager_enabled(false);
+        assert!(!pager_enabled());
+        assert!(!should_page(&"x\n".repeat(100), pager_enabled(), true, 24));
+        set_pager_enabled(true);
+        assert!(pager_enabled());
+    }
+
+    #[test]
+    fn test_format_count_and_seconds() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1234), "1,234");
+        assert_eq!(format_count(1234567), "1,234,567");
+        assert_eq!(format_seconds(std::time::Duration::from_millis(3210)), "3.2s");
+    }
+
+    #[test]
+    fn test_pager_command() {
+        assert_eq!(pager_command(None), "less -FRX");
+        assert_eq!(pager_command(Some("  ")), "less -FRX");
+        assert_eq!(pager_command(Some("more")), "more");
+    }
+}
@@ -0,0 +1,498 @@
+//! SPARQL 交互式查询模块（`memexia graph repl`）
+//!
+//! 在一个进程内反复执行查询，避免每次重新打开存储：
+//!
+//! - 查询前自动加上 [`PREFIXES`]，可直接写 `memexia:title`、`rdf:type`、`file:note.md`
+//! - 多行输入以 `;` 结尾或输入空行时执行
+//! - 以 `.` 开头的元命令：`.stats`、`.nodes <pattern>`、`.limit N`、`.format table|json|csv`、
+//!   `.history`、`.help`、`.quit`
+//! - 查询错误只显示 oxigraph 的错误信息，不退出
+//! - 输入的查询追加到 `.memexia/history/repl_history`，下次启动时可用 `.history` 查看
+//!
+//! 求值逻辑在 [`ReplSession`] 中，与终端循环分离以便测试
+
+use crate::storage::csv::escape_field;
+use crate::storage::{GraphStorage, QueryResult};
+use crate::t;
+use super::output::{display_width, fit_to_width};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// 预加载的前缀声明
+pub const PREFIXES: &str = "\
+PREFIX memexia: <memexia:>
+PREFIX rdf: <rdf:>
+PREFIX file: <urn:memexia:file:>
+";
+
+/// 历史文件相对仓库根目录的路径
+pub const HISTORY_FILE: &str = ".memexia/history/repl_history";
+
+/// 默认显示的最大行数
+pub const DEFAULT_LIMIT: usize = 100;
+
+/// 表格单元格的最大显示宽度
+const MAX_CELL_WIDTH: usize = 60;
+
+/// 结果输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// 对齐的文本表格
+    Table,
+    /// JSON 对象数组
+    Json,
+    /// RFC 4180 CSV
+    Csv,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            _ => Err(t!("repl.unknown_format", format = s)),
+        }
+    }
+}
+
+/// 一行输入的处理结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// 需要显示的输出
+    Output(String),
+    /// 查询未结束，继续读取下一行
+    Pending,
+    /// 没有输出（如空行）
+    Empty,
+    /// 退出 REPL
+    Quit,
+}
+
+/// REPL 会话状态
+pub struct ReplSession<'a> {
+    /// 查询的图存储
+    graph: &'a dyn GraphStorage,
+    /// 显示的最大行数（None 表示不限制）
+    limit: Option<usize>,
+    /// 输出格式
+    format: OutputFormat,
+    /// 未结束的多行查询
+    buffer: String,
+    /// 查询历史
+    history: Vec<String>,
+}
+
+impl<'a> ReplSession<'a> {
+    /// 创建会话
+    ///
+    /// # Arguments
+    ///
+    /// * `graph` - 图存储
+    /// * `history` - 之前会话的查询历史
+    pub fn new(graph: &'a dyn GraphStorage, history: Vec<String>) -> Self {
+        Self {
+            graph,
+            limit: Some(DEFAULT_LIMIT),
+            format: OutputFormat::Table,
+            buffer: String::new(),
+            history,
+        }
+    }
+
+    /// 当前提示符（多行输入时为续行提示符）
+    pub fn prompt(&self) -> &'static str {
+        if self.buffer.is_empty() {
+            "sparql> "
+        } else {
+            "   ...> "
+        }
+    }
+
+    /// 处理一行输入
+    ///
+    /// 缓冲区为空时以 `.` 开头的行视为元命令；其余行追加到缓冲区，
+    /// 遇到以 `;` 结尾的行或空行时执行整个查询
+    pub fn feed_line(&mut self, line: &str) -> Outcome {
+        let trimmed = line.trim();
+        if self.buffer.is_empty() {
+            if trimmed.is_empty() {
+                return Outcome::Empty;
+            }
+            if trimmed.starts_with('.') {
+                return self.eval_meta(trimmed);
+            }
+        }
+
+        let complete = trimmed.is_empty() || trimmed.ends_with(';');
+        let line = trimmed.strip_suffix(';').unwrap_or(trimmed);
+        if !line.is_empty() {
+            if !self.buffer.is_empty() {
+                self.buffer.push('\n');
+            }
+            self.buffer.push_str(line);
+        }
+        if !complete {
+            return Outcome::Pending;
+        }
+
+        let query = std::mem::take(&mut self.buffer);
+        if query.trim().is_empty() {
+            return Outcome::Empty;
+        }
+        self.history.push(query.clone());
+        self.run_query(&query)
+    }
+
+    /// 本次会话新增和之前的查询历史
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// 执行元命令
+    pub fn eval_meta(&mut self, command: &str) -> Outcome {
+        let (name, arg) = match command.split_once(char::is_whitespace) {
+            Some((name, arg)) => (name, arg.trim()),
+            None => (command, ""),
+        };
+
+        match name {
+            ".quit" | ".exit" => Outcome::Quit,
+            ".help" => Outcome::Output(t!("repl.help")),
+            ".stats" => Outcome::Output(self.stats()),
+            ".nodes" => Outcome::Output(self.nodes(arg)),
+            ".history" => {
+                let start = self.history.len().saturating_sub(self.limit.unwrap_or(usize::MAX));
+                let lines: Vec<String> = self.history[start..]
+                    .iter()
+                    .enumerate()
+                    .map(|(i, q)| format!("{:>4}  {}", start + i + 1, q.replace('\n', " ")))
+                    .collect();
+                Outcome::Output(lines.join("\n"))
+            }
+            ".limit" => match arg {
+                "" => Outcome::Output(t!("repl.limit", limit = self.limit_label())),
+                "0" | "none" => {
+                    self.limit = None;
+                    Outcome::Output(t!("repl.limit", limit = self.limit_label()))
+                }
+                n => match n.parse::<usize>() {
+                    Ok(n) => {
+                        self.limit = Some(n);
+                        Outcome::Output(t!("repl.limit", limit = self.limit_label()))
+                    }
+                    Err(_) => Outcome::Output(t!("repl.invalid_limit", value = n)),
+                },
+            },
+            ".format" => match arg {
+                "" => Outcome::Output(t!("repl.format", format = format!("{:?}", self.format).to_lowercase())),
+                f => match f.parse() {
+                    Ok(format) => {
+                        self.format = format;
+                        Outcome::Output(t!("repl.format", format = f))
+                    }
+                    Err(e) => Outcome::Output(e),
+                },
+            },
+            ".prefixes" => Outcome::Output(PREFIXES.trim_end().to_string()),
+            _ => Outcome::Output(t!("repl.unknown_command", command = name)),
+        }
+    }
+
+    /// 执行查询（自动加上前缀声明），错误作为输出返回
+    pub fn run_query(&self, query: &str) -> Outcome {
+        match self.graph.query(&format!("{}{}", PREFIXES, query)) {
+            Ok(result) => Outcome::Output(self.render(&result)),
+            Err(e) => Outcome::Output(t!("repl.error", error = e)),
+        }
+    }
+
+    fn limit_label(&self) -> String {
+        self.limit.map_or_else(|| "none".to_string(), |n| n.to_string())
+    }
+
+    fn stats(&self) -> String {
+        match self.graph.get_stats() {
+            Ok(stats) => {
+                let mut out = t!("repl.stats", nodes = stats.node_count, edges = stats.edge_count);
+                let mut types = stats.node_type_counts;
+                types.sort_by_key(|t| std::cmp::Reverse(t.1));
+                for (node_type, count) in types {
+                    out.push_str(&format!("\n  {:?}: {}", node_type, count));
+                }
+                out
+            }
+            Err(e) => t!("repl.error", error = e),
+        }
+    }
+
+    /// 列出 ID 或标题包含模式（不区分大小写）的节点
+    fn nodes(&self, pattern: &str) -> String {
+        let pattern = pattern.to_lowercase();
+        let nodes = match self.graph.list_nodes() {
+            Ok(nodes) => nodes,
+            Err(e) => return t!("repl.error", error = e),
+        };
+
+        let mut matched: Vec<_> = nodes
+            .into_iter()
+            .filter(|n| n.id.to_lowercase().contains(&pattern) || n.title.to_lowercase().contains(&pattern))
+            .collect();
+        matched.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let total = matched.len();
+        let shown = self.limit.unwrap_or(total).min(total);
+        let mut lines: Vec<String> = matched[..shown].iter().map(|n| format!("{}  {}", n.id, n.title)).collect();
+        lines.push(self.footer(shown, total));
+        lines.join("\n")
+    }
+
+    /// 按当前格式渲染查询结果
+    pub fn render(&self, result: &QueryResult) -> String {
+        let total = result.bindings.len();
+        let shown = self.limit.unwrap_or(total).min(total);
+        let values: Vec<Vec<String>> = result.bindings[..shown]
+            .iter()
+            .map(|row| {
+                result
+                    .variables
+                    .iter()
+                    .map(|v| row.get(v).map(|raw| display_value(raw)).unwrap_or_default())
+                    .collect()
+            })
+            .collect();
+        let rows: Vec<Vec<&str>> = values.iter().map(|row| row.iter().map(String::as_str).collect()).collect();
+
+        match self.format {
+            OutputFormat::Json => {
+                let objects: Vec<serde_json::Value> = rows
+                    .iter()
+                    .map(|row| {
+                        let map = result
+                            .variables
+                            .iter()
+                            .zip(row)
+                            .map(|(v, value)| (v.trim_start_matches('?').to_string(), serde_json::json!(value)))
+                            .collect();
+                        serde_json::Value::Object(map)
+                    })
+                    .collect();
+                serde_json::to_string_pretty(&objects).unwrap_or_default()
+            }
+            OutputFormat::Csv => {
+                let mut lines = vec![result
+                    .variables
+                    .iter()
+                    .map(|v| escape_field(v.trim_start_matches('?')))
+                    .collect::<Vec<_>>()
+                    .join(",")];
+                lines.extend(
+                    rows.iter()
+                        .map(|row| row.iter().map(|f| escape_field(f)).collect::<Vec<_>>().join(",")),
+                );
+                lines.join("\n")
+            }
+            OutputFormat::Table => {
+                let widths: Vec<usize> = result
+                    .variables
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| {
+                        rows.iter()
+                            .map(|row| display_width(row[i]))
+                            .chain(std::iter::once(display_width(v)))
+                            .max()
+                            .unwrap_or(0)
+                            .min(MAX_CELL_WIDTH)
+                    })
+                    .collect();
+                let format_row = |cells: Vec<&str>| {
+                    cells
+                        .iter()
+                        .zip(&widths)
+                        .map(|(cell, width)| fit_to_width(cell, *width))
+                        .collect::<Vec<_>>()
+                        .join(" | ")
+                        .trim_end()
+                        .to_string()
+                };
+
+                let mut lines = vec![format_row(result.variables.iter().map(String::as_str).collect())];
+                lines.push(widths.iter().map(|w| "-".repeat(*w)).collect::<Vec<_>>().join("-+-"));
+                lines.extend(rows.into_iter().map(format_row));
+                lines.push(self.footer(shown, total));
+                lines.join("\n")
+            }
+        }
+    }
+
+    /// 行数统计（被限制截断时提示剩余行数）
+    fn footer(&self, shown: usize, total: usize) -> String {
+        if shown < total {
+            t!("repl.rows_truncated", shown = shown, total = total)
+        } else {
+            t!("repl.rows", count = total)
+        }
+    }
+}
+
+/// 绑定值的显示形式：字面量去掉引号、语言标签和数据类型，IRI 原样显示
+///
+/// 如 `"Free Will"@en` -> `Free Will`，`"3"^^<xsd:integer>` -> `3`
+pub fn display_value(raw: &str) -> String {
+    let Some(rest) = raw.strip_prefix('"') else {
+        return raw.to_string();
+    };
+    let Some(end) = rest.rfind('"') else {
+        return raw.to_string();
+    };
+
+    let mut value = String::with_capacity(end);
+    let mut chars = rest[..end].chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('t') => value.push('\t'),
+            Some('r') => value.push('\r'),
+            Some(other) => value.push(other),
+            None => value.push('\\'),
+        }
+    }
+    value
+}
+
+/// 读取历史文件（每条查询一行，多行查询中的换行保存为 `\n`）
+pub fn load_history(root: &Path) -> Vec<String> {
+    fs::read_to_string(history_path(root))
+        .map(|content| content.lines().map(|l| l.replace("\\n", "\n")).collect())
+        .unwrap_or_default()
+}
+
+/// 追加一条查询到历史文件
+pub fn append_history(root: &Path, query: &str) -> std::io::Result<()> {
+    let path = history_path(root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", query.replace('\n', "\\n"))
+}
+
+fn history_path(root: &Path) -> PathBuf {
+    root.join(HISTORY_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{Edge, Node, NodeType, RelationType, Storage};
+    use tempfile::TempDir;
+
+    fn seeded() -> (TempDir, Storage) {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let graph = storage.graph();
+        graph.add_node(&Node::new("urn:memexia:file:free-will.md", NodeType::Concept, "Free Will")).unwrap();
+        graph.add_node(&Node::new("urn:memexia:file:determinism.md", NodeType::Question, "Determinism")).unwrap();
+        let (from, to) = ("urn:memexia:file:free-will.md", "urn:memexia:file:determinism.md");
+        graph.add_edge(&Edge::new(Edge::make_id(from, to), from, to, RelationType::Contradicts)).unwrap();
+        (temp, storage)
+    }
+
+    fn output(outcome: Outcome) -> String {
+        match outcome {
+            Outcome::Output(s) => s,
+            other => panic!("expected output, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_query_with_preloaded_prefixes() {
+        let (_temp, storage) = seeded();
+        let mut session = ReplSession::new(storage.graph(), Vec::new());
+
+        let out = output(session.feed_line("SELECT ?title WHERE { ?s memexia:title ?title } ORDER BY ?title;"));
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines[0], "?title");
+        assert!(lines[2].contains("Determinism"));
+        assert!(lines[3].contains("Free Will"));
+        assert_eq!(session.history().len(), 1);
+    }
+
+    #[test]
+    fn test_multiline_input_and_errors() {
+        let (_temp, storage) = seeded();
+        let mut session = ReplSession::new(storage.graph(), Vec::new());
+
+        assert_eq!(session.feed_line("SELECT ?s"), Outcome::Pending);
+        assert_eq!(session.prompt(), "   ...> ");
+        assert_eq!(session.feed_line("WHERE { ?s rdf:type memexia:Question }"), Outcome::Pending);
+        let out = output(session.feed_line(""));
+        assert!(out.contains("urn:memexia:file:determinism.md"));
+        assert!(!out.contains("free-will"));
+        assert_eq!(session.prompt(), "sparql> ");
+        assert_eq!(session.history()[0], "SELECT ?s\nWHERE { ?s rdf:type memexia:Question }");
+
+        // 语法错误显示信息后继续
+        let out = output(session.feed_line("SELECT WHERE {;"));
+        assert!(out.contains("Invalid SPARQL query"));
+        assert_eq!(session.feed_line(""), Outcome::Empty);
+    }
+
+    #[test]
+    fn test_meta_commands() {
+        let (_temp, storage) = seeded();
+        let mut session = ReplSession::new(storage.graph(), vec!["SELECT 1".to_string()]);
+
+        assert!(output(session.feed_line(".stats")).contains('2'));
+        let nodes = output(session.feed_line(".nodes FREE"));
+        assert!(nodes.contains("urn:memexia:file:free-will.md  Free Will"));
+        assert!(!nodes.contains("determinism"));
+        assert!(output(session.feed_line(".history")).contains("SELECT 1"));
+
+        // .limit 截断结果
+        session.feed_line(".limit 1");
+        let out = output(session.feed_line("SELECT ?s WHERE { ?s memexia:title ?t };"));
+        assert_eq!(out.lines().count(), 4);
+
+        // .format 切换输出格式
+        session.feed_line(".limit none");
+        session.feed_line(".format csv");
+        let out = output(session.feed_line("SELECT ?t WHERE { ?s memexia:title ?t } ORDER BY ?t;"));
+        assert_eq!(out, "t\nDeterminism\nFree Will");
+
+        session.feed_line(".format json");
+        let out = output(session.feed_line("SELECT ?t WHERE { ?s memexia:title ?t } ORDER BY ?t;"));
+        let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(json[1]["t"], "Free Will");
+
+        assert_eq!(session.feed_line(".format yaml"), Outcome::Output(t!("repl.unknown_format", format = "yaml")));
+        assert_eq!(session.feed_line(".bogus"), Outcome::Output(t!("repl.unknown_command", command = ".bogus")));
+        assert_eq!(session.feed_line(".quit"), Outcome::Quit);
+    }
+
+    #[test]
+    fn test_display_value() {
+        assert_eq!(display_value("urn:memexia:file:a.md"), "urn:memexia:file:a.md");
+        assert_eq!(display_value("\"Free Will\""), "Free Will");
+        assert_eq!(display_value("\"Libre\"@fr"), "Libre");
+        assert_eq!(display_value("\"3\"^^<http://www.w3.org/2001/XMLSchema#integer"), "3");
+        assert_eq!(display_value("\"say \\\"hi\\\"\\nbye\""), "say \"hi\"\nbye");
+    }
+
+    #[test]
+    fn test_history_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        assert!(load_history(temp.path()).is_empty());
+        append_history(temp.path(), "SELECT ?s\nWHERE { ?s ?p ?o }").unwrap();
+        append_history(temp.path(), "ASK {}").unwrap();
+        assert_eq!(load_history(temp.path()), vec!["SELECT ?s\nWHERE { ?s ?p ?o }", "ASK {}"]);
+    }
+}
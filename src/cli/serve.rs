@@ -0,0 +1,916 @@
+//! 本地 API 服务（`memexia serve`）
+//!
+//! 供 GUI 使用的最小 HTTP/1.1 服务：只监听 `127.0.0.1`，逐个处理请求，API 响应均为 JSON
+//!
+//! | 方法 | 路径 | 说明 |
+//! |------|------|------|
+//! | GET | `/api/nodes/{id}/html` | 笔记渲染为 HTML，返回 `{html, toc}`，见 [`Repository::render_node_html`] |
+//! | GET | `/api/nodes/{id}/links` | 节点的出链，返回 `{links}`（含 `introduced_in_commit`），见 [`Repository::node_links`] |
+//! | GET | `/api/titles/{title}` | 标题相同（不区分大小写）的笔记，返回 `{nodes: [{id, title}]}`，供输入标题时补全和查重，见 [`GraphStorage::find_by_title`](crate::storage::GraphStorage::find_by_title) |
+//! | GET | `/api/layout?iterations=N&seed=S` | 整个图的力导向布局：`graph show --json` 的格式，节点另带 `x`、`y`，见 [`crate::core::layout`]；超过 [`LAYOUT_TIMEOUT`] 时中止并返回 503 |
+//! | GET | `/metrics` | Prometheus 文本格式的指标，见 [`crate::metrics`]；服务没有认证机制，需要 `--metrics-public` 开启，否则返回 403 |
+//! | GET | `/api/daemon` | 服务进程号和仓库根路径 |
+//! | POST | `/api/daemon/reindex` | `{path, full, detect_renames}`，返回索引汇总 |
+//! | POST | `/api/daemon/add` | `{files}`（绝对路径） |
+//! | POST | `/api/daemon/commit` | `{message, all, include_untracked}`，返回 `{commit, auto_staged}`；没有可提交内容时返回 409 |
+//! | POST | `/api/daemon/links` | `{source, target, relation}`，返回创建的边 |
+//! | POST | `/api/daemon/links/delete` | `{source, target}` |
+//! | PATCH | `/api/daemon/nodes/{id}` | `{type, title, add_tags, remove_tags, write_file}`，返回 `{id, changes, write_back}`，见 [`Repository::set_node`] |
+//! | DELETE | `/api/daemon/nodes/{id}` | 把节点移入回收站，返回 `{id}`，见 [`Repository::trash_node`] |
+//! | PATCH | `/api/edges/{id}` | `{strength, description, relation}`，原地修改边并保留修改记录，返回修改后的边，见 [`Repository::edit_edge`] |
+//!
+//! `/api/daemon` 下的接口供其他 memexia 命令代理执行（见 [`super::daemon`]），
+//! 需要 `X-Memexia-Token` 请求头中的令牌，否则返回 403：
+//!
+//! - 启动时写入 `.memexia/serve.json` 的令牌拥有全部权限
+//! - `[[serve.tokens]]` 中的令牌（[`ServeToken`](crate::core::ServeToken)）只拥有配置的权限范围，缺少接口所需的范围时
+//!   返回 403 并指出缺少的范围：读取接口和 `GET /api/daemon` 需要 `read`，`/api/daemon/nodes/{id}`
+//!   需要 `nodes:write`，`/api/daemon/links*` 和 `PATCH /api/edges/{id}` 需要 `edges:write`，索引、暂存和提交需要 `admin`
+//! - 没有配置 `[[serve.tokens]]` 时读取接口不需要令牌，`PATCH /api/edges/{id}` 与 `/api/daemon` 一样总是需要令牌
+//!
+//! 以 `--read-only` 启动的服务不登记，修改接口返回 403
+//!
+//! 路径中的节点 ID 需做 percent 编码（如 `urn%3Amemexia%3Afile%3Anotes%2Fa.md`），
+//! 也可以直接写 wiki 链接目标（`notes%2Fa.md`）。出错时返回 `{"error": "..."}`
+//!
+//! 路由前由 [`Limiter`] 按 `[serve]` 配置（[`ServeConfig`]）拒绝超限请求：
+//!
+//! - 同一客户端（按 IP）超过 `rate_limit`/`rate_burst` 令牌桶时返回 429
+//! - `Content-Length` 超过 `max_body_bytes` 时返回 413，不读取请求体
+//!
+//! 服务逐个处理连接，SPARQL 求值本身不会并发，因此没有单独的并发上限
+//!
+//! 路由逻辑在 [`handle`] 中，与网络循环分离以便测试
+
+use super::daemon::{self, CommitOutcome, ServeInfo};
+use crate::core::{hooks, RepoConfig, Repository, ServeConfig, ServeScope};
+use crate::error::MemexiaError;
+use crate::metrics;
+use crate::storage::{ids, EdgeEdit, RelationType};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::net::{IpAddr, TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+/// 超过该数量的客户端令牌桶时，清理已经回满的桶
+const MAX_TRACKED_CLIENTS: usize = 4096;
+
+/// `/api/layout` 的计算时限；服务逐个处理连接，过长的布局会阻塞其他请求
+pub const LAYOUT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 服务选项
+#[derive(Debug, Clone, Default)]
+pub struct ServeOptions {
+    /// 是否开放 `/metrics`
+    pub metrics_public: bool,
+    /// 限流、请求体大小限制和带权限范围的令牌
+    pub limits: ServeConfig,
+    /// 拥有全部权限的令牌（启动时登记），为 None 时只接受 `[[serve.tokens]]` 中的令牌
+    pub token: Option<String>,
+}
+
+/// 响应
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    /// HTTP 状态码
+    pub status: u16,
+    /// Content-Type
+    pub content_type: &'static str,
+    /// 响应体
+    pub body: String,
+}
+
+impl Response {
+    fn json(status: u16, body: Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json; charset=utf-8",
+            body: body.to_string(),
+        }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self::json(status, json!({ "error": message.into() }))
+    }
+}
+
+/// 一个客户端的令牌桶
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// 请求准入检查：按客户端限流并限制请求体大小
+#[derive(Debug)]
+pub struct Limiter {
+    config: ServeConfig,
+    buckets: HashMap<IpAddr, Bucket>,
+}
+
+impl Limiter {
+    /// 按配置创建
+    pub fn new(config: ServeConfig) -> Self {
+        Self {
+            config,
+            buckets: HashMap::new(),
+        }
+    }
+
+    /// 在路由前检查请求；超限时返回拒绝响应，并计入 `memexia_http_requests_total`
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - 客户端地址
+    /// * `target` - 请求路径（用于指标的路由标签）
+    /// * `content_length` - 请求头中的 `Content-Length`
+    /// * `now` - 当前时间
+    pub fn check(&mut self, client: IpAddr, target: &str, content_length: Option<u64>, now: Instant) -> Option<Response> {
+        let response = if !self.take_token(client, now) {
+            Response::error(429, "Too many requests; slow down and retry")
+        } else if content_length.is_some_and(|length| length > self.config.max_body_bytes) {
+            Response::error(
+                413,
+                format!("Request body exceeds {} bytes", self.config.max_body_bytes),
+            )
+        } else {
+            return None;
+        };
+        metrics::record_http_request(route_label(target), response.status);
+        Some(response)
+    }
+
+    /// 从客户端的令牌桶取一个令牌
+    fn take_token(&mut self, client: IpAddr, now: Instant) -> bool {
+        let rate = self.config.rate_limit;
+        if rate <= 0.0 {
+            return true;
+        }
+        let burst = f64::from(self.config.rate_burst.max(1));
+        if self.buckets.len() > MAX_TRACKED_CLIENTS {
+            self.buckets.retain(|_, bucket| {
+                bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * rate < burst
+            });
+        }
+
+        let bucket = self.buckets.entry(client).or_insert(Bucket { tokens: burst, updated: now });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(burst);
+        bucket.updated = now;
+        if bucket.tokens < 1.0 {
+            return false;
+        }
+        bucket.tokens -= 1.0;
+        true
+    }
+}
+
+/// 请求路径对应的路由标签（见 [`metrics::ROUTES`]）
+fn route_label(target: &str) -> &'static str {
+    let path = target.split('?').next().unwrap_or_default();
+    if path == "/metrics" {
+        "/metrics"
+    } else if path.starts_with("/api/nodes/") && path.ends_with("/html") {
+        "/api/nodes/{id}/html"
+    } else if path.starts_with("/api/nodes/") && path.ends_with("/links") {
+        "/api/nodes/{id}/links"
+    } else if path.starts_with("/api/titles/") {
+        "/api/titles/{title}"
+    } else if path == "/api/layout" {
+        "/api/layout"
+    } else if path == "/api/daemon" || path.starts_with("/api/daemon/") {
+        "/api/daemon"
+    } else if path.starts_with(EDGES_PREFIX) {
+        "/api/edges/{id}"
+    } else {
+        "other"
+    }
+}
+
+/// 接口所需的权限范围；None 表示不需要令牌（`/metrics` 由 `--metrics-public` 控制）
+fn required_scope(method: &str, path: &str) -> Option<ServeScope> {
+    if path == "/metrics" {
+        return None;
+    }
+    if path.starts_with(EDGES_PREFIX) {
+        return Some(ServeScope::EdgesWrite);
+    }
+    if route_label(path) != "/api/daemon" {
+        return Some(ServeScope::Read);
+    }
+    Some(match (method, path) {
+        ("GET", "/api/daemon") => ServeScope::Read,
+        (_, "/api/daemon/links" | "/api/daemon/links/delete") => ServeScope::EdgesWrite,
+        _ if path.starts_with(DAEMON_NODES_PREFIX) => ServeScope::NodesWrite,
+        _ => ServeScope::Admin,
+    })
+}
+
+/// 校验请求头中的令牌是否拥有接口所需的权限范围，不通过时返回 403 响应
+///
+/// # Arguments
+///
+/// * `options` - 服务选项（含令牌）
+/// * `method` - 请求方法
+/// * `path` - 请求路径（不含查询串）
+/// * `token` - 请求头中的令牌
+pub fn authorize(options: &ServeOptions, method: &str, path: &str, token: Option<&str>) -> Option<Response> {
+    let scope = required_scope(method, path)?;
+    if scope == ServeScope::Read && route_label(path) != "/api/daemon" && options.limits.tokens.is_empty() {
+        return None;
+    }
+    let token = token.filter(|token| !token.is_empty());
+    if token.is_some() && token == options.token.as_deref() {
+        return None;
+    }
+    match options.limits.tokens.iter().find(|t| Some(t.token.as_str()) == token) {
+        Some(t) if t.allows(scope) => None,
+        Some(_) => Some(Response::error(403, format!("Token lacks the `{}` scope", scope))),
+        None => Some(Response::error(403, "Missing or invalid X-Memexia-Token")),
+    }
+}
+
+/// 处理一个请求，并计入 `memexia_http_requests_total`
+///
+/// # Arguments
+///
+/// * `repo` - 仓库
+/// * `options` - 服务选项
+/// * `method` - 请求方法
+/// * `target` - 请求路径（可带查询串，只有 `/api/layout` 读取查询串）
+pub fn handle(repo: &Repository, options: &ServeOptions, method: &str, target: &str) -> Response {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let (route, response) = route(repo, options, method, path, query);
+    metrics::record_http_request(route, response.status);
+    response
+}
+
+/// 按路径分发请求，返回路由标签（见 [`metrics::ROUTES`]）和响应
+fn route(repo: &Repository, options: &ServeOptions, method: &str, path: &str, query: &str) -> (&'static str, Response) {
+    if path == "/metrics" {
+        return ("/metrics", serve_metrics(repo, options, method));
+    }
+    if path == "/api/layout" {
+        return ("/api/layout", graph_layout(repo, method, query));
+    }
+    if let Some(title) = path.strip_prefix("/api/titles/") {
+        return ("/api/titles/{title}", find_titles(repo, method, title));
+    }
+    let Some(rest) = path.strip_prefix("/api/nodes/") else {
+        return ("other", Response::error(404, format!("No route for {}", path)));
+    };
+    let (route, id, links) = if let Some(id) = rest.strip_suffix("/html") {
+        ("/api/nodes/{id}/html", id, false)
+    } else if let Some(id) = rest.strip_suffix("/links") {
+        ("/api/nodes/{id}/links", id, true)
+    } else {
+        return ("other", Response::error(404, format!("No route for {}", path)));
+    };
+    if method != "GET" {
+        return (route, Response::error(405, format!("Method {} is not allowed", method)));
+    }
+
+    let id = ids::decode_component(id);
+    let result = if links {
+        repo.node_links(&id).map(|links| json!({ "links": links }))
+    } else {
+        repo.render_node_html(&id).map(|rendered| json!(rendered))
+    };
+    let response = match result {
+        Ok(body) => Response::json(200, body),
+        Err(e @ MemexiaError::NodeNotFound { .. }) => Response::error(404, e.to_string()),
+        Err(e @ MemexiaError::Validation(_)) => Response::error(422, e.to_string()),
+        Err(e) => Response::error(500, e.to_string()),
+    };
+    (route, response)
+}
+
+/// `GET /api/titles/{title}`：经标题索引查找标题相同的笔记
+fn find_titles(repo: &Repository, method: &str, title: &str) -> Response {
+    if method != "GET" {
+        return Response::error(405, format!("Method {} is not allowed", method));
+    }
+    match repo.storage().and_then(|storage| storage.graph().find_by_title(&ids::decode_component(title), false)) {
+        Ok(nodes) => {
+            let nodes: Vec<Value> = nodes.iter().map(|node| json!({ "id": node.id, "title": node.title })).collect();
+            Response::json(200, json!({ "nodes": nodes }))
+        }
+        Err(e) => Response::error(500, e.to_string()),
+    }
+}
+
+/// `GET /api/layout`：计算整个图的力导向布局，超过 [`LAYOUT_TIMEOUT`] 时中止
+fn graph_layout(repo: &Repository, method: &str, query: &str) -> Response {
+    use crate::core::layout::LayoutOptions;
+
+    if method != "GET" {
+        return Response::error(405, format!("Method {} is not allowed", method));
+    }
+    let mut options = LayoutOptions::default();
+    for (key, value) in query.split('&').filter_map(|pair| pair.split_once('=')) {
+        let parsed = match key {
+            "iterations" => value.parse().map(|n| options.iterations = n).is_ok(),
+            "seed" => value.parse().map(|n| options.seed = n).is_ok(),
+            _ => true,
+        };
+        if !parsed {
+            return Response::error(400, format!("Invalid {}: {}", key, value));
+        }
+    }
+
+    let start = Instant::now();
+    let should_stop = || start.elapsed() > LAYOUT_TIMEOUT;
+    match repo.storage().and_then(|storage| super::commands::graph_layout_json(storage.graph(), &options, &should_stop)) {
+        Ok(body) => Response::json(200, body),
+        Err(MemexiaError::Cancelled) => Response::error(
+            503,
+            format!("Layout did not finish within {} seconds; retry with fewer iterations", LAYOUT_TIMEOUT.as_secs()),
+        ),
+        Err(e) => Response::error(500, e.to_string()),
+    }
+}
+
+/// `GET /metrics`：刷新图规模仪表后输出全部指标
+fn serve_metrics(repo: &Repository, options: &ServeOptions, method: &str) -> Response {
+    if method != "GET" {
+        return Response::error(405, format!("Method {} is not allowed", method));
+    }
+    if !options.metrics_public {
+        return Response::error(403, "Metrics are disabled; restart with --metrics-public to expose them");
+    }
+    let counts = repo.storage().and_then(|storage| Ok((storage.graph().list_nodes()?, storage.graph().list_edges()?)));
+    match counts {
+        Ok((nodes, edges)) => metrics::set_graph_size(nodes.len(), edges.len()),
+        Err(e) => tracing::warn!("Failed to count graph for metrics: {}", e),
+    }
+    Response {
+        status: 200,
+        content_type: "text/plain; version=0.0.4; charset=utf-8",
+        body: metrics::render(),
+    }
+}
+
+/// 处理修改接口（`/api/daemon` 下的请求和 `PATCH /api/edges/{id}`），并计入 `memexia_http_requests_total`
+///
+/// # Arguments
+///
+/// * `repo` - 仓库
+/// * `options` - 服务选项（含令牌）
+/// * `method` - 请求方法
+/// * `path` - 请求路径
+/// * `token` - 请求头中的令牌
+/// * `body` - 请求体（JSON）
+pub fn handle_daemon(
+    repo: &mut Repository,
+    options: &ServeOptions,
+    method: &str,
+    path: &str,
+    token: Option<&str>,
+    body: &str,
+) -> Response {
+    let response = if let Some(denied) = authorize(options, method, path, token) {
+        denied
+    } else if repo.is_read_only() && required_scope(method, path) != Some(ServeScope::Read) {
+        Response::error(403, "The repository is opened read-only")
+    } else {
+        match (method, path) {
+            ("GET", "/api/daemon") => {
+                Response::json(200, json!({ "pid": std::process::id(), "root": repo.path() }))
+            }
+            ("POST" | "PATCH" | "DELETE", _) => match serde_json::from_str::<Value>(if body.is_empty() { "{}" } else { body }) {
+                Ok(body) => daemon_command(repo, method, path, &body).unwrap_or_else(|e| match e.downcast::<MemexiaError>() {
+                    Ok(e @ MemexiaError::NothingToCommit) => Response::error(409, e.to_string()),
+                    Ok(e @ (MemexiaError::Validation(_) | MemexiaError::ParseError { .. } | MemexiaError::StagedFile { .. })) => {
+                        Response::error(422, e.to_string())
+                    }
+                    Ok(e @ (MemexiaError::NodeNotFound { .. } | MemexiaError::EdgeNotFound { .. })) => {
+                        Response::error(404, e.to_string())
+                    }
+                    Ok(e) => Response::error(500, e.to_string()),
+                    Err(e) => Response::error(500, format!("{:#}", e)),
+                }),
+                Err(e) => Response::error(400, format!("Invalid JSON body: {}", e)),
+            },
+            _ if path == "/api/daemon" || daemon_command_exists(path) => {
+                Response::error(405, format!("Method {} is not allowed", method))
+            }
+            _ => Response::error(404, format!("No route for {}", path)),
+        }
+    };
+    metrics::record_http_request(route_label(path), response.status);
+    response
+}
+
+/// 代理执行的命令
+const DAEMON_COMMANDS: [&str; 5] = [
+    "/api/daemon/reindex",
+    "/api/daemon/add",
+    "/api/daemon/commit",
+    "/api/daemon/links",
+    "/api/daemon/links/delete",
+];
+
+/// 修改节点的接口前缀（`PATCH /api/daemon/nodes/{id}`）
+const DAEMON_NODES_PREFIX: &str = "/api/daemon/nodes/";
+
+/// 修改边的接口前缀（`PATCH /api/edges/{id}`）
+const EDGES_PREFIX: &str = "/api/edges/";
+
+fn daemon_command_exists(path: &str) -> bool {
+    DAEMON_COMMANDS.contains(&path) || path.starts_with(DAEMON_NODES_PREFIX) || path.starts_with(EDGES_PREFIX)
+}
+
+/// 执行一个代理命令，与对应的 CLI 命令直接执行时的行为一致
+fn daemon_command(repo: &mut Repository, method: &str, path: &str, body: &Value) -> anyhow::Result<Response> {
+    if let Some(id) = path.strip_prefix(EDGES_PREFIX) {
+        return match method {
+            "PATCH" => Ok(Response::json(200, set_edge(repo, &ids::decode_component(id), body)?)),
+            _ => Ok(Response::error(405, format!("Method {} is not allowed", method))),
+        };
+    }
+    if method == "PATCH" || method == "DELETE" {
+        return match path.strip_prefix(DAEMON_NODES_PREFIX) {
+            Some(id) if method == "PATCH" => Ok(Response::json(200, set_node(repo, &ids::decode_component(id), body)?)),
+            Some(id) => Ok(Response::json(200, json!({ "id": repo.trash_node(&ids::decode_component(id))? }))),
+            None if daemon_command_exists(path) => Ok(Response::error(405, format!("Method {} is not allowed", method))),
+            None => Ok(Response::error(404, format!("No route for {}", path))),
+        };
+    }
+    if path.starts_with(DAEMON_NODES_PREFIX) {
+        return Ok(Response::error(405, format!("Method {} is not allowed", method)));
+    }
+
+    let string = |key: &str| body[key].as_str().map(str::to_string);
+    let path_of = |key: &str| {
+        string(key)
+            .map(PathBuf::from)
+            .ok_or_else(|| MemexiaError::Validation(format!("Missing `{}`", key)))
+    };
+    let flag = |key: &str| body[key].as_bool().unwrap_or(false);
+
+    let body = match path {
+        "/api/daemon/reindex" => {
+            let root = path_of("path")?;
+            let full = flag("full");
+            let summary = if full {
+                repo.rebuild_index(&root)?
+            } else {
+                repo.reindex(&root, false, body["detect_renames"].as_bool().unwrap_or(true))?
+            };
+            hooks::run_post_index(&RepoConfig::load(repo.path())?.hooks, &summary);
+            if full {
+                repo.discard_undo()?;
+            }
+            serde_json::to_value(summary)?
+        }
+        "/api/daemon/add" => {
+            let files: Vec<PathBuf> = serde_json::from_value(body["files"].clone())?;
+            repo.add(&files)?;
+            json!({})
+        }
+        "/api/daemon/commit" => {
+            let message = string("message").ok_or_else(|| MemexiaError::Validation("Missing `message`".to_string()))?;
+            let auto_staged = if flag("all") { repo.stage_changes(flag("include_untracked"))? } else { Vec::new() };
+            let commit = repo.commit(&message)?;
+            serde_json::to_value(CommitOutcome { commit, auto_staged })?
+        }
+        "/api/daemon/links" => {
+            let relation = string("relation").unwrap_or_else(|| "RelatedTo".to_string());
+            let relation = RelationType::parse(&relation)
+                .ok_or_else(|| MemexiaError::Validation(format!("Unknown relation: {}", relation)))?;
+            serde_json::to_value(repo.create_link(&path_of("source")?, &path_of("target")?, relation)?)?
+        }
+        "/api/daemon/links/delete" => {
+            repo.delete_link(&path_of("source")?, &path_of("target")?)?;
+            json!({})
+        }
+        _ => return Ok(Response::error(404, format!("No route for {}", path))),
+    };
+    Ok(Response::json(200, body))
+}
+
+/// `PATCH /api/daemon/nodes/{id}`：与 `memexia node set` 相同，见 [`Repository::set_node`]
+fn set_node(repo: &Repository, id: &str, body: &Value) -> anyhow::Result<Value> {
+    use crate::core::writer::NodeEdit;
+
+    let tags = |key: &str| match &body[key] {
+        Value::Null => Ok(Vec::new()),
+        value => serde_json::from_value(value.clone())
+            .map_err(|_| MemexiaError::Validation(format!("`{}` must be a list of tags", key))),
+    };
+    let node_type = match body["type"].as_str() {
+        Some(value) => Some(serde_json::from_value(json!(value)).map_err(|_| {
+            MemexiaError::Validation(format!("Unknown node type: {}", value))
+        })?),
+        None => None,
+    };
+    let edit = NodeEdit {
+        title: body["title"].as_str().map(str::to_string),
+        node_type,
+        add_tags: tags("add_tags")?,
+        remove_tags: tags("remove_tags")?,
+        ..NodeEdit::default()
+    };
+    if edit.is_empty() {
+        return Err(MemexiaError::Validation("Nothing to change; pass type, title, add_tags or remove_tags".to_string()).into());
+    }
+    let outcome = repo.set_node(id, &edit, body["write_file"].as_bool().unwrap_or(false))?;
+    Ok(serde_json::to_value(outcome)?)
+}
+
+/// `PATCH /api/edges/{id}`：与 `memexia link set-*` 相同，见 [`Repository::edit_edge`]
+fn set_edge(repo: &Repository, id: &str, body: &Value) -> anyhow::Result<Value> {
+    let strength = match &body["strength"] {
+        Value::Null => None,
+        value => Some(value.as_f64().ok_or_else(|| MemexiaError::Validation("`strength` must be a number".to_string()))?),
+    };
+    let relation = match body["relation"].as_str() {
+        Some(value) => Some(
+            RelationType::parse(value).ok_or_else(|| MemexiaError::Validation(format!("Unknown relation: {}", value)))?,
+        ),
+        None => None,
+    };
+    let edit = EdgeEdit { strength, description: body["description"].as_str().map(str::to_string), relation };
+    if edit.is_empty() {
+        return Err(MemexiaError::Validation("Nothing to change; pass strength, description or relation".to_string()).into());
+    }
+    Ok(serde_json::to_value(repo.edit_edge(id, &edit)?)?)
+}
+
+/// 在指定端口上运行服务，直到进程退出
+///
+/// 可写的仓库在 `.memexia/serve.json` 登记服务，其他命令据此代理到本服务（见 [`daemon`]）
+pub fn run(repo: &mut Repository, options: &ServeOptions, port: u16) -> anyhow::Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    if repo.is_read_only() {
+        return serve_listener(repo, options, listener);
+    }
+    let info = ServeInfo::new(listener.local_addr()?.port());
+    let _registration = info.register(repo.path())?;
+    let options = ServeOptions {
+        token: Some(info.token),
+        ..options.clone()
+    };
+    serve_listener(repo, &options, listener)
+}
+
+/// 在已绑定的监听器上逐个处理连接
+pub fn serve_listener(repo: &mut Repository, options: &ServeOptions, listener: TcpListener) -> anyhow::Result<()> {
+    let mut limiter = Limiter::new(options.limits.clone());
+    for stream in listener.incoming() {
+        let result = stream
+            .map_err(anyhow::Error::from)
+            .and_then(|stream| serve_connection(repo, options, &mut limiter, stream));
+        if let Err(e) = result {
+            tracing::warn!("Failed to serve request: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// 读取一个请求并写回响应（不支持 keep-alive）
+fn serve_connection(
+    repo: &mut Repository,
+    options: &ServeOptions,
+    limiter: &mut Limiter,
+    mut stream: TcpStream,
+) -> anyhow::Result<()> {
+    let client = stream.peer_addr()?.ip();
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // 请求头只关心 Content-Length 和令牌，请求体只有修改接口读取
+    let mut content_length = None;
+    let mut token = None;
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim_end().is_empty() {
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse::<u64>().ok();
+            } else if name.trim().eq_ignore_ascii_case(daemon::TOKEN_HEADER) {
+                token = Some(value.trim().to_string());
+            }
+        }
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => match limiter.check(client, target, content_length, Instant::now()) {
+            Some(rejected) => rejected,
+            None if matches!(route_label(target), "/api/daemon" | "/api/edges/{id}") => {
+                let mut body = vec![0; content_length.unwrap_or(0) as usize];
+                reader.read_exact(&mut body)?;
+                let path = target.split('?').next().unwrap_or_default();
+                let body = String::from_utf8_lossy(&body);
+                handle_daemon(repo, options, method, path, token.as_deref(), &body)
+            }
+            None => {
+                let path = target.split('?').next().unwrap_or_default();
+                match authorize(options, method, path, token.as_deref()) {
+                    Some(denied) => {
+                        metrics::record_http_request(route_label(target), denied.status);
+                        denied
+                    }
+                    None => handle(repo, options, method, target),
+                }
+            }
+        },
+        _ => Response::error(400, "Malformed request line"),
+    };
+    tracing::info!(request = %request_line.trim_end(), status = response.status, "serve");
+
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason(response.status),
+        response.content_type,
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// 状态码对应的原因短语
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        422 => "Unprocessable Entity",
+        429 => "Too Many Requests",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Indexer;
+    use tempfile::TempDir;
+
+    fn repo_with_note() -> (TempDir, Repository) {
+        let temp = TempDir::new().unwrap();
+        let repo = Repository::init(temp.path()).unwrap();
+        let root = repo.path().to_path_buf();
+        std::fs::create_dir_all(root.join("notes")).unwrap();
+        std::fs::write(root.join("notes/a.md"), "# A\n\n[[notes/b.md]]\n").unwrap();
+        Indexer::new(repo.storage().unwrap().clone()).index_file(&root.join("notes/a.md")).unwrap();
+        (temp, repo)
+    }
+
+    /// 解析 Prometheus 文本格式，返回 (序列, 值)；格式错误时 panic
+    fn parse_exposition(text: &str) -> Vec<(String, f64)> {
+        let mut samples = Vec::new();
+        for line in text.lines() {
+            if let Some(comment) = line.strip_prefix("# ") {
+                let mut parts = comment.splitn(3, ' ');
+                assert!(matches!(parts.next(), Some("HELP" | "TYPE")), "bad comment: {}", line);
+                assert!(parts.next().is_some_and(|name| name.starts_with("memexia_")), "bad comment: {}", line);
+                continue;
+            }
+            let (series, value) = line.rsplit_once(' ').unwrap_or_else(|| panic!("bad sample: {}", line));
+            let name_end = series.find('{').unwrap_or(series.len());
+            assert!(series[..name_end].chars().all(|c| c.is_ascii_alphanumeric() || c == '_'), "bad name: {}", line);
+            if name_end < series.len() {
+                assert!(series.ends_with('}'), "bad labels: {}", line);
+            }
+            samples.push((series.to_string(), value.parse().unwrap_or_else(|_| panic!("bad value: {}", line))));
+        }
+        samples
+    }
+
+    fn sample(samples: &[(String, f64)], series: &str) -> f64 {
+        samples.iter().find(|(s, _)| s == series).map(|(_, v)| *v).unwrap_or(0.0)
+    }
+
+    #[test]
+    fn test_patch_node_endpoint() {
+        let (_temp, mut repo) = repo_with_note();
+        let options = ServeOptions { token: Some("secret".to_string()), ..ServeOptions::default() };
+        let mut patch = |path: &str, body: &str| handle_daemon(&mut repo, &options, "PATCH", path, Some("secret"), body);
+
+        // 占位节点没有笔记文件，只修改图
+        let response = patch(
+            "/api/daemon/nodes/urn%3Amemexia%3Afile%3Anotes%2Fb.md",
+            r#"{"type": "Question", "title": "B?", "add_tags": ["open"]}"#,
+        );
+        assert_eq!(response.status, 200, "{}", response.body);
+        let body: Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(body["write_back"], Value::Null);
+        let fields: Vec<&str> = body["changes"].as_array().unwrap().iter().map(|c| c["field"].as_str().unwrap()).collect();
+        assert_eq!(fields, ["title", "type", "tags"]);
+
+        // 文件节点改标题需要 write_file
+        assert_eq!(patch("/api/daemon/nodes/notes%2Fa.md", r#"{"title": "Alpha"}"#).status, 422);
+        assert_eq!(patch("/api/daemon/nodes/notes%2Fa.md", r#"{"type": "Nope"}"#).status, 422);
+        assert_eq!(patch("/api/daemon/nodes/notes%2Fa.md", "{}").status, 422);
+        assert_eq!(patch("/api/daemon/nodes/notes%2Fmissing.md", r#"{"type": "Meta"}"#).status, 404);
+        assert_eq!(patch("/api/daemon/commit", "{}").status, 405);
+
+        let post = handle_daemon(&mut repo, &options, "POST", "/api/daemon/nodes/notes%2Fa.md", Some("secret"), "{}");
+        assert_eq!(post.status, 405);
+        let node = repo.storage().unwrap().graph().get_node(&ids::file_id("notes/b.md")).unwrap().unwrap();
+        assert_eq!(node.title, "B?");
+    }
+
+    #[test]
+    fn test_scoped_tokens() {
+        use crate::core::ServeToken;
+
+        let (_temp, mut repo) = repo_with_note();
+        std::fs::write(repo.path().join("notes/b.md"), "# B\n").unwrap();
+        let tablet = ServeToken { token: "tablet".to_string(), scopes: vec![ServeScope::EdgesWrite] };
+        let limits = ServeConfig { tokens: vec![tablet], ..ServeConfig::default() };
+        let options = ServeOptions { token: Some("secret".to_string()), limits, ..ServeOptions::default() };
+
+        // 只有 edges:write 的令牌可以建链接，不能删除节点或读取
+        let denied = handle_daemon(&mut repo, &options, "DELETE", "/api/daemon/nodes/notes%2Fa.md", Some("tablet"), "");
+        assert_eq!(denied.status, 403);
+        assert!(denied.body.contains("nodes:write"), "{}", denied.body);
+        let link = r#"{"source": "notes/a.md", "target": "notes/b.md", "relation": "Supports"}"#;
+        let created = handle_daemon(&mut repo, &options, "POST", "/api/daemon/links", Some("tablet"), link);
+        assert_eq!(created.status, 200, "{}", created.body);
+        let read = authorize(&options, "GET", "/api/nodes/notes%2Fa.md/html", Some("tablet")).unwrap();
+        assert!(read.body.contains("`read`"), "{}", read.body);
+        assert_eq!(authorize(&options, "GET", "/api/layout", None).unwrap().status, 403);
+        assert_eq!(handle_daemon(&mut repo, &options, "GET", "/api/daemon", Some("other"), "").status, 403);
+
+        // 启动时登记的令牌拥有全部权限
+        assert!(authorize(&options, "GET", "/api/layout", Some("secret")).is_none());
+        let deleted = handle_daemon(&mut repo, &options, "DELETE", "/api/daemon/nodes/notes%2Fa.md", Some("secret"), "");
+        assert_eq!(deleted.status, 200, "{}", deleted.body);
+        assert!(repo.storage().unwrap().graph().get_node(&ids::file_id("notes/a.md")).unwrap().unwrap().is_deleted());
+
+        // 没有配置带权限范围的令牌时读取接口保持开放
+        assert!(authorize(&ServeOptions::default(), "GET", "/api/layout", None).is_none());
+    }
+
+    #[test]
+    fn test_patch_edge() {
+        let (_temp, mut repo) = repo_with_note();
+        let options = ServeOptions { token: Some("secret".to_string()), ..ServeOptions::default() };
+        let id = crate::storage::Edge::make_id(&ids::file_id("notes/a.md"), &ids::file_id("notes/b.md"));
+        let path = format!("/api/edges/{}", ids::encode_component(&id));
+        let mut patch = |token: Option<&str>, body: &str| handle_daemon(&mut repo, &options, "PATCH", &path, token, body);
+
+        let response = patch(Some("secret"), r#"{"strength": 0.3, "relation": "Supports"}"#);
+        assert_eq!(response.status, 200, "{}", response.body);
+        let body: Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!((body["strength"].as_f64(), body["relation"].as_str()), (Some(0.3), Some("Supports")));
+        assert_eq!(body["history"].as_array().unwrap().len(), 2);
+
+        assert_eq!(patch(Some("secret"), r#"{"strength": 2}"#).status, 422);
+        assert_eq!(patch(Some("secret"), "{}").status, 422);
+        // 修改接口总是需要令牌
+        assert_eq!(patch(None, r#"{"strength": 0.5}"#).status, 403);
+        let missing = crate::storage::Edge::make_id(&ids::file_id("notes/a.md"), &ids::file_id("notes/c.md"));
+        let missing = format!("/api/edges/{}", ids::encode_component(&missing));
+        let response = handle_daemon(&mut repo, &options, "PATCH", &missing, Some("secret"), r#"{"strength": 0.5}"#);
+        assert_eq!(response.status, 404);
+        assert_eq!(handle_daemon(&mut repo, &options, "GET", &path, Some("secret"), "").status, 405);
+    }
+
+    #[test]
+    fn test_handle_routes() {
+        let (_temp, repo) = repo_with_note();
+        let options = ServeOptions::default();
+
+        let response = handle(&repo, &options, "GET", "/api/nodes/urn%3Amemexia%3Afile%3Anotes%2Fa.md/html?x=1");
+        assert_eq!(response.status, 200);
+        let body: Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(body["toc"][0]["anchor"], "a");
+        assert!(body["html"].as_str().unwrap().contains("class=\"broken\""));
+
+        assert_eq!(handle(&repo, &options, "GET", "/api/nodes/notes%2Fa.md/html").status, 200);
+        assert_eq!(handle(&repo, &options, "GET", "/api/nodes/notes%2Fb.md/html").status, 422);
+        assert_eq!(handle(&repo, &options, "GET", "/api/nodes/notes%2Fc.md/html").status, 404);
+        assert_eq!(handle(&repo, &options, "POST", "/api/nodes/notes%2Fa.md/html").status, 405);
+        assert_eq!(handle(&repo, &options, "GET", "/api/other").status, 404);
+
+        let response = handle(&repo, &options, "GET", "/api/nodes/notes%2Fa.md/links");
+        assert_eq!(response.status, 200);
+        let body: Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(body["links"][0]["to"], "urn:memexia:file:notes/b.md");
+        assert_eq!(handle(&repo, &options, "GET", "/api/nodes/notes%2Fc.md/links").status, 404);
+
+        let response = handle(&repo, &options, "GET", "/api/titles/a");
+        assert_eq!(response.status, 200);
+        let body: Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(body["nodes"][0]["id"], "urn:memexia:file:notes/a.md");
+        assert_eq!(handle(&repo, &options, "GET", "/api/titles/missing").body, r#"{"nodes":[]}"#);
+    }
+
+    #[test]
+    fn test_layout_endpoint() {
+        let (_temp, repo) = repo_with_note();
+        let options = ServeOptions::default();
+
+        let response = handle(&repo, &options, "GET", "/api/layout?iterations=20&seed=3");
+        assert_eq!(response.status, 200);
+        let layout: Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(response.body, handle(&repo, &options, "GET", "/api/layout?iterations=20&seed=3").body);
+
+        // 与 `graph show --json` 的格式一致，节点另带坐标
+        let graph = repo.storage().unwrap().graph();
+        let nodes = graph.get_all_nodes().unwrap();
+        let nodes: Vec<&crate::storage::Node> = nodes.iter().collect();
+        let show = super::super::commands::graph_json(&nodes, &graph.get_all_edges().unwrap(), |_| false);
+        assert_eq!(layout["edges"], show["edges"]);
+        let layout_nodes = layout["nodes"].as_array().unwrap();
+        assert_eq!(layout_nodes.len(), show["nodes"].as_array().unwrap().len());
+        for (with_position, node) in layout_nodes.iter().zip(show["nodes"].as_array().unwrap()) {
+            assert!(with_position["x"].is_f64() && with_position["y"].is_f64());
+            let mut without_position = with_position.clone();
+            let fields = without_position.as_object_mut().unwrap();
+            fields.remove("x");
+            fields.remove("y");
+            assert_eq!(&without_position, node);
+        }
+
+        assert_eq!(handle(&repo, &options, "GET", "/api/layout?iterations=many").status, 400);
+        assert_eq!(handle(&repo, &options, "POST", "/api/layout").status, 405);
+    }
+
+    #[test]
+    fn test_metrics_endpoint() {
+        let (_temp, repo) = repo_with_note();
+        assert_eq!(handle(&repo, &ServeOptions::default(), "GET", "/metrics").status, 403);
+
+        let options = ServeOptions {
+            metrics_public: true,
+            ..Default::default()
+        };
+        let before = parse_exposition(&handle(&repo, &options, "GET", "/metrics").body);
+        handle(&repo, &options, "GET", "/api/nodes/notes%2Fa.md/html");
+        handle(&repo, &options, "GET", "/api/nodes/notes%2Fa.md/html");
+        handle(&repo, &options, "GET", "/api/nodes/notes%2Fmissing.md/html");
+        repo.storage().unwrap().graph().query("SELECT ?s WHERE { ?s ?p ?o } LIMIT 1").unwrap();
+
+        let response = handle(&repo, &options, "GET", "/metrics");
+        assert_eq!(response.status, 200);
+        assert!(response.content_type.starts_with("text/plain; version=0.0.4"));
+        let after = parse_exposition(&response.body);
+        let delta = |series: &str| sample(&after, series) - sample(&before, series);
+        assert!(delta("memexia_http_requests_total{route=\"/api/nodes/{id}/html\",status=\"200\"}") >= 2.0);
+        assert!(delta("memexia_http_requests_total{route=\"/api/nodes/{id}/html\",status=\"404\"}") >= 1.0);
+        assert!(delta("memexia_http_requests_total{route=\"/metrics\",status=\"200\"}") >= 1.0);
+        assert!(delta("memexia_sparql_queries_total") >= 1.0);
+        assert!(sample(&after, "memexia_graph_nodes") >= 1.0);
+        assert!(after.iter().any(|(series, _)| series == "memexia_websocket_clients"));
+    }
+
+    #[test]
+    fn test_limiter_rate_limits_per_client() {
+        use std::time::Duration;
+
+        let mut limiter = Limiter::new(ServeConfig {
+            rate_limit: 2.0,
+            rate_burst: 3,
+            ..Default::default()
+        });
+        let a: IpAddr = "127.0.0.1".parse().unwrap();
+        let b: IpAddr = "127.0.0.2".parse().unwrap();
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter.check(a, "/metrics", None, start).is_none());
+        }
+        let rejected = limiter.check(a, "/metrics", None, start).unwrap();
+        assert_eq!(rejected.status, 429);
+        // 其他客户端不受影响
+        assert!(limiter.check(b, "/metrics", None, start).is_none());
+        // 每秒补充 2 个令牌
+        let later = start + Duration::from_millis(500);
+        assert!(limiter.check(a, "/metrics", None, later).is_none());
+        assert_eq!(limiter.check(a, "/metrics", None, later).unwrap().status, 429);
+
+        let mut unlimited = Limiter::new(ServeConfig {
+            rate_limit: 0.0,
+            ..Default::default()
+        });
+        assert!((0..100).all(|_| unlimited.check(a, "/", None, start).is_none()));
+    }
+
+    #[test]
+    fn test_limiter_caps_body_size() {
+        let mut limiter = Limiter::new(ServeConfig {
+            max_body_bytes: 1024,
+            ..Default::default()
+        });
+        let client: IpAddr = "127.0.0.1".parse().unwrap();
+        let now = Instant::now();
+        assert!(limiter.check(client, "/api/nodes/a/html", Some(1024), now).is_none());
+        let rejected = limiter.check(client, "/api/nodes/a/html", Some(1025), now).unwrap();
+        assert_eq!(rejected.status, 413);
+        assert!(rejected.body.contains("1024"));
+    }
+}
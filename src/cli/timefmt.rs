@@ -0,0 +1,153 @@
+//! 时间显示模块
+//!
+//! 命令行输出中的时间统一经由 [`format_time`] 显示：
+//!
+//! - 默认：一年内显示相对时间（"3 hours ago"、"2 个月前"），更早的显示本地时区的日期
+//! - `--utc`：不做时区转换，显示 `2026-03-01 08:30:00 UTC`
+//! - `--iso`：RFC 3339，带本地时区偏移；与 `--utc` 同用时为 `2026-03-01T08:30:00Z`
+//!
+//! 相对时间和日期格式随界面语言变化（见 [`i18n`](super::i18n)）；
+//! `--json` 输出不经过本模块，始终为 RFC 3339 UTC
+
+use super::i18n::{self, Lang};
+use chrono::{DateTime, Duration, Local, SecondsFormat, TimeZone, Utc};
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 超过该天数的时间显示为绝对日期
+pub const RELATIVE_LIMIT_DAYS: i64 = 365;
+
+/// 是否显示 UTC 时间（`--utc`）
+static UTC: AtomicBool = AtomicBool::new(false);
+
+/// 是否显示 RFC 3339 时间（`--iso`）
+static ISO: AtomicBool = AtomicBool::new(false);
+
+/// 时间显示方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TimeStyle {
+    /// 不转换到本地时区
+    pub utc: bool,
+    /// 使用 RFC 3339
+    pub iso: bool,
+}
+
+/// 设置时间显示方式
+pub fn set_style(style: TimeStyle) {
+    UTC.store(style.utc, Ordering::Relaxed);
+    ISO.store(style.iso, Ordering::Relaxed);
+}
+
+/// 当前时间显示方式
+pub fn style() -> TimeStyle {
+    TimeStyle {
+        utc: UTC.load(Ordering::Relaxed),
+        iso: ISO.load(Ordering::Relaxed),
+    }
+}
+
+/// 按当前语言和显示方式格式化时间
+pub fn format_time(time: &DateTime<Utc>) -> String {
+    format_time_in(i18n::lang(), style(), time, Utc::now())
+}
+
+/// 按指定语言、显示方式和当前时间格式化时间
+///
+/// # Arguments
+///
+/// * `lang` - 界面语言
+/// * `style` - 显示方式
+/// * `time` - 要显示的时间
+/// * `now` - 计算相对时间使用的当前时间
+pub fn format_time_in(lang: Lang, style: TimeStyle, time: &DateTime<Utc>, now: DateTime<Utc>) -> String {
+    match (style.iso, style.utc) {
+        (true, true) => time.to_rfc3339_opts(SecondsFormat::Secs, true),
+        (true, false) => time.with_timezone(&Local).to_rfc3339_opts(SecondsFormat::Secs, false),
+        (false, true) => time.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        (false, false) => relative(lang, time, now).unwrap_or_else(|| absolute(lang, &time.with_timezone(&Local))),
+    }
+}
+
+/// 相对时间（"3 hours ago"）
+///
+/// 一分钟以内（含不超过一分钟的时钟偏差）为 "just now"；
+/// 更早于 [`RELATIVE_LIMIT_DAYS`] 天或明显在未来的时间返回 None
+pub fn relative(lang: Lang, time: &DateTime<Utc>, now: DateTime<Utc>) -> Option<String> {
+    let elapsed = now.signed_duration_since(*time);
+    let (unit, count) = if elapsed < -Duration::minutes(1) {
+        return None;
+    } else if elapsed < Duration::minutes(1) {
+        return Some(i18n::translate_in(lang, "time.just_now", &[]));
+    } else if elapsed < Duration::hours(1) {
+        ("minutes", elapsed.num_minutes())
+    } else if elapsed < Duration::days(1) {
+        ("hours", elapsed.num_hours())
+    } else if elapsed < Duration::days(30) {
+        ("days", elapsed.num_days())
+    } else if elapsed < Duration::days(RELATIVE_LIMIT_DAYS) {
+        ("months", elapsed.num_days() / 30)
+    } else {
+        return None;
+    };
+    let key = if count == 1 {
+        format!("time.{}_ago_one", unit)
+    } else {
+        format!("time.{}_ago", unit)
+    };
+    Some(i18n::translate_in(lang, &key, &[("count", &count)]))
+}
+
+/// 按语言的日期格式（`time.date_format`）显示绝对时间
+pub fn absolute<Tz: TimeZone>(lang: Lang, time: &DateTime<Tz>) -> String
+where
+    Tz::Offset: Display,
+{
+    time.format(&i18n::translate_in(lang, "time.date_format", &[])).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_relative_boundaries() {
+        let now = at("2026-03-01T12:00:00Z");
+        let ago = |duration: Duration| relative(Lang::En, &(now - duration), now);
+
+        assert_eq!(ago(Duration::seconds(59)).as_deref(), Some("just now"));
+        assert_eq!(ago(Duration::seconds(-30)).as_deref(), Some("just now"));
+        assert_eq!(ago(Duration::minutes(-2)), None);
+        assert_eq!(ago(Duration::seconds(60)).as_deref(), Some("1 minute ago"));
+        assert_eq!(ago(Duration::minutes(59)).as_deref(), Some("59 minutes ago"));
+        assert_eq!(ago(Duration::minutes(60)).as_deref(), Some("1 hour ago"));
+        assert_eq!(ago(Duration::hours(3)).as_deref(), Some("3 hours ago"));
+        assert_eq!(ago(Duration::hours(24)).as_deref(), Some("1 day ago"));
+        assert_eq!(ago(Duration::days(29)).as_deref(), Some("29 days ago"));
+        assert_eq!(ago(Duration::days(30)).as_deref(), Some("1 month ago"));
+        assert_eq!(ago(Duration::days(75)).as_deref(), Some("2 months ago"));
+        assert_eq!(ago(Duration::days(RELATIVE_LIMIT_DAYS - 1)).as_deref(), Some("12 months ago"));
+        assert_eq!(ago(Duration::days(RELATIVE_LIMIT_DAYS)), None);
+
+        assert_eq!(relative(Lang::Zh, &(now - Duration::hours(3)), now).as_deref(), Some("3 小时前"));
+    }
+
+    #[test]
+    fn test_absolute_and_forced_styles() {
+        let time = at("2024-03-01T08:30:00Z");
+        let now = at("2026-03-01T12:00:00Z");
+        assert_eq!(absolute(Lang::En, &time), "Mar 1, 2024 08:30");
+        assert_eq!(absolute(Lang::Zh, &time), "2024年3月1日 08:30");
+
+        let utc = TimeStyle { utc: true, iso: false };
+        assert_eq!(format_time_in(Lang::En, utc, &time, now), "2024-03-01 08:30:00 UTC");
+        let iso = TimeStyle { utc: true, iso: true };
+        assert_eq!(format_time_in(Lang::Zh, iso, &time, now), "2024-03-01T08:30:00Z");
+        // 默认显示方式在一年内为相对时间
+        let recent = now - Duration::hours(2);
+        assert_eq!(format_time_in(Lang::En, TimeStyle::default(), &recent, now), "2 hours ago");
+    }
+}
@@ -0,0 +1,129 @@
+//! 笔记归档模块
+//!
+//! `memexia archive <path>` 把已完成或放弃的笔记移到归档目录（`paths.archive`，默认 `archive/`），
+//! `memexia unarchive <path>` 移回原位置。移动复用重命名机制（[`rename_node`](super::rename::rename_node)），
+//! 节点的创建时间、元数据、历史和两个方向的边都会保留，指向归档笔记的反向链接仍然有效
+//!
+//! 归档节点带 `archived` 标签和 `memexia:archivedAt` 属性，搜索、统计、发布和链接推荐
+//! 默认排除它们（`--include-archived` 时包含）
+
+use crate::error::{MemexiaError, Result};
+use crate::storage::{GraphStorage, Node};
+use chrono::{DateTime, Utc};
+
+/// 默认归档目录
+pub const DEFAULT_ARCHIVE_DIR: &str = "archive";
+
+/// 一次归档或取消归档
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveOutcome {
+    /// 原路径（相对仓库根目录）
+    pub from: String,
+    /// 新路径（相对仓库根目录）
+    pub to: String,
+    /// 移动后的节点 ID
+    pub node_id: String,
+    /// 迁移到新 ID 的边数
+    pub edges: usize,
+}
+
+/// 归档后的路径：`<archive_dir>/<relative>`
+///
+/// # Arguments
+///
+/// * `relative` - 笔记路径（相对仓库根目录，以 `/` 分隔）
+/// * `archive_dir` - 归档目录
+///
+/// # Returns
+///
+/// 归档路径；笔记已在归档目录中时返回错误
+pub fn archived_path(relative: &str, archive_dir: &str) -> Result<String> {
+    if strip_archive_dir(relative, archive_dir).is_some() {
+        return Err(MemexiaError::Validation(format!("Note is already archived: {}", relative)));
+    }
+    Ok(format!("{}/{}", archive_dir, relative))
+}
+
+/// 取消归档后的路径：去掉开头的归档目录
+///
+/// # Returns
+///
+/// 原路径；笔记不在归档目录中时返回错误
+pub fn unarchived_path(relative: &str, archive_dir: &str) -> Result<String> {
+    strip_archive_dir(relative, archive_dir)
+        .map(str::to_string)
+        .ok_or_else(|| MemexiaError::Validation(format!("Note is not in the archive directory: {}", relative)))
+}
+
+fn strip_archive_dir<'a>(relative: &'a str, archive_dir: &str) -> Option<&'a str> {
+    relative
+        .strip_prefix(archive_dir)
+        .and_then(|rest| rest.strip_prefix('/'))
+        .filter(|rest| !rest.is_empty())
+}
+
+/// 标记节点为归档
+///
+/// # Returns
+///
+/// 更新后的节点；节点不存在时返回错误
+pub fn mark_archived(graph: &dyn GraphStorage, id: &str, at: DateTime<Utc>) -> Result<Node> {
+    let mut node = graph.get_node(id)?.ok_or_else(|| MemexiaError::NodeNotFound { id: id.to_string() })?;
+    node.set_archived_at(at);
+    graph.update_node(&node)?;
+    Ok(node)
+}
+
+/// 清除节点的归档标记
+///
+/// # Returns
+///
+/// 更新后的节点；节点不存在时返回错误
+pub fn clear_archived(graph: &dyn GraphStorage, id: &str) -> Result<Node> {
+    let mut node = graph.get_node(id)?.ok_or_else(|| MemexiaError::NodeNotFound { id: id.to_string() })?;
+    node.clear_archived();
+    graph.update_node(&node)?;
+    Ok(node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{NodeType, Storage, ARCHIVED_TAG};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_archive_paths() {
+        assert_eq!(archived_path("notes/a.md", "archive").unwrap(), "archive/notes/a.md");
+        assert!(archived_path("archive/notes/a.md", "archive").is_err());
+        // 名字以归档目录开头的普通目录不受影响
+        assert_eq!(archived_path("archived/a.md", "archive").unwrap(), "archive/archived/a.md");
+
+        assert_eq!(unarchived_path("archive/notes/a.md", "archive").unwrap(), "notes/a.md");
+        assert!(unarchived_path("notes/a.md", "archive").is_err());
+        assert!(unarchived_path("archive/", "archive").is_err());
+    }
+
+    #[test]
+    fn test_mark_and_clear() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let graph = storage.graph();
+        let node = Node::builder("urn:memexia:file:a.md", NodeType::Concept, "A").tags(["topic"]).build().unwrap();
+        graph.add_node(&node).unwrap();
+
+        let at = Utc::now();
+        mark_archived(graph, &node.id, at).unwrap();
+        let stored = graph.get_node(&node.id).unwrap().unwrap();
+        assert!(stored.is_archived());
+        assert_eq!(stored.archived_at().unwrap().timestamp(), at.timestamp());
+        assert!(stored.tags.contains(&ARCHIVED_TAG.to_string()));
+
+        clear_archived(graph, &node.id).unwrap();
+        let stored = graph.get_node(&node.id).unwrap().unwrap();
+        assert!(!stored.is_archived());
+        assert_eq!(stored.tags, ["topic"]);
+
+        assert!(mark_archived(graph, "urn:memexia:file:missing.md", at).is_err());
+    }
+}
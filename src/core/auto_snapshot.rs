@@ -0,0 +1,151 @@
+//! 自动快照调度模块
+//!
+//! 图快照原本只在提交时创建，监听进程长时间运行时未提交的改动没有可恢复的历史。
+//! `memexia watch --snapshot-every 6h`（或配置 `watch.auto_snapshot_interval`）按间隔调用
+//! [`GraphHistory::auto_snapshot`]：图有变化时以 `auto:<时间>` 为键记录快照，并只保留最近若干个
+//!
+//! 间隔格式为数字加单位：`s`、`m`、`h`、`d`，如 `90s`、`30m`、`24h`、`1d`
+
+use crate::error::{MemexiaError, Result};
+use crate::storage::Storage;
+use crate::vcs::{CommitLink, GraphHistory};
+use std::time::{Duration, Instant};
+
+/// 解析时间间隔（快照间隔、回收站清理期限等）
+///
+/// # Arguments
+///
+/// * `s` - 间隔字符串，如 `24h`、`30d`
+///
+/// # Returns
+///
+/// 间隔时长；格式错误或为 0 时返回校验错误
+pub fn parse_interval(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let invalid = || {
+        MemexiaError::Validation(format!(
+            "Invalid interval '{}': expected a number followed by s, m, h or d (e.g. 24h)",
+            s
+        ))
+    };
+
+    let split = s.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+    let (number, unit) = s.split_at(split);
+    let number: u64 = number.parse().map_err(|_| invalid())?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86_400,
+        _ => return Err(invalid()),
+    };
+    if number == 0 {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs(number * seconds))
+}
+
+/// 自动快照调度器
+///
+/// 由监听循环周期性调用 [`SnapshotScheduler::tick`]，到期时尝试创建快照
+#[derive(Debug, Clone)]
+pub struct SnapshotScheduler {
+    /// 快照间隔
+    interval: Duration,
+    /// 保留的自动快照个数
+    keep: usize,
+    /// 下一次到期时间
+    next_due: Instant,
+}
+
+impl SnapshotScheduler {
+    /// 创建调度器，第一次快照在 `now + interval` 到期
+    pub fn new(interval: Duration, keep: usize, now: Instant) -> Self {
+        Self {
+            interval,
+            keep,
+            next_due: now + interval,
+        }
+    }
+
+    /// 快照间隔
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// 距离下一次到期的时长（已到期时为 0）
+    pub fn time_until_due(&self, now: Instant) -> Duration {
+        self.next_due.saturating_duration_since(now)
+    }
+
+    /// 到期时创建自动快照并安排下一次
+    ///
+    /// # Returns
+    ///
+    /// 新记录的快照关联；未到期或图未变化时返回 None
+    pub fn tick(&mut self, history: &GraphHistory, storage: &Storage, now: Instant) -> Result<Option<CommitLink>> {
+        if now < self.next_due {
+            return Ok(None);
+        }
+        self.next_due = now + self.interval;
+        history.auto_snapshot(storage, self.keep)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{Node, NodeType};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(parse_interval("24h").unwrap(), Duration::from_secs(86_400));
+        assert_eq!(parse_interval("6h").unwrap(), Duration::from_secs(6 * 3600));
+        assert_eq!(parse_interval(" 30m ").unwrap(), Duration::from_secs(1800));
+        assert_eq!(parse_interval("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_interval("2d").unwrap(), Duration::from_secs(2 * 86_400));
+
+        for bad in ["", "h", "24", "0h", "1w", "1.5h", "-1h"] {
+            assert!(matches!(parse_interval(bad), Err(MemexiaError::Validation(_))), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_scheduler_snapshots_changed_graph_and_prunes() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let history = GraphHistory::init(temp.path()).unwrap();
+        let add = |name: &str| {
+            let id = format!("urn:memexia:file:{}.md", name);
+            storage.graph().add_node(&Node::new(&id, NodeType::Concept, name)).unwrap();
+        };
+
+        // 已有两个自动快照，只保留两个
+        add("old1");
+        history.auto_snapshot(&storage, 2).unwrap();
+        add("old2");
+        history.auto_snapshot(&storage, 2).unwrap();
+        let before = history.auto_snapshots().unwrap();
+
+        let start = Instant::now();
+        let interval = Duration::from_millis(20);
+        let mut scheduler = SnapshotScheduler::new(interval, 2, start);
+
+        // 未到期不快照
+        add("new");
+        assert!(scheduler.tick(&history, &storage, start).unwrap().is_none());
+        assert_eq!(scheduler.time_until_due(start), interval);
+
+        // 到期且图已变化：恰好一个新快照，最早的被淘汰
+        let link = scheduler.tick(&history, &storage, start + interval).unwrap().unwrap();
+        let after = history.auto_snapshots().unwrap();
+        assert_eq!(after.len(), 2);
+        assert_eq!(after[0].commit_hash, before[1].commit_hash);
+        assert_eq!(after[1].commit_hash, link.commit_hash);
+
+        // 下一次到期时图未变化，不再快照
+        assert!(scheduler.tick(&history, &storage, start + interval * 2).unwrap().is_none());
+        assert_eq!(history.auto_snapshots().unwrap().len(), 2);
+    }
+}
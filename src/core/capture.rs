@@ -0,0 +1,144 @@
+//! 快速记录模块（`memexia append`）
+//!
+//! 不打开编辑器，直接向笔记追加或前置一段文字：
+//!
+//! - 追加：写在文件末尾
+//! - 前置（`--prepend`）：写在 frontmatter 之后、正文之前
+//! - 指定小节（`--section`）：追加到小节末尾（下一个同级或更高级标题之前），
+//!   前置时紧跟在小节标题之后；小节不存在时在文件末尾新建 `## 标题` 小节
+//!
+//! 插入的文字与前后内容之间恰好保留一个空行；文件原有的换行风格（LF/CRLF）
+//! 以及末尾是否有换行保持不变
+
+use crate::core::retag::split_frontmatter;
+use crate::core::split::headings;
+use crate::error::{MemexiaError, Result};
+use std::io::Read;
+
+/// 插入位置
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Insert {
+    /// 插入到开头（或小节标题之后）而不是末尾
+    pub prepend: bool,
+    /// 目标小节标题（精确匹配，任意级别）
+    pub section: Option<String>,
+}
+
+/// 读取要插入的文字：`-` 表示从 `stdin` 读取
+///
+/// # Arguments
+///
+/// * `text` - 命令行参数
+/// * `stdin` - 标准输入
+pub fn read_text(text: &str, mut stdin: impl Read) -> Result<String> {
+    if text != "-" {
+        return Ok(text.to_string());
+    }
+    let mut buffer = String::new();
+    stdin.read_to_string(&mut buffer)?;
+    Ok(buffer)
+}
+
+/// 把文字插入笔记内容
+///
+/// # Returns
+///
+/// 插入后的内容；文字为空时返回 `Validation` 错误
+pub fn insert_text(content: &str, text: &str, insert: &Insert) -> Result<String> {
+    let newline = if content.contains("\r\n") { "\r\n" } else { "\n" };
+    let block = text.trim_matches(|c| c == '\n' || c == '\r');
+    if block.trim().is_empty() {
+        return Err(MemexiaError::Validation("Nothing to insert: text is empty".to_string()));
+    }
+    let block = block.replace("\r\n", "\n").replace('\n', newline);
+    let trailing = if content.is_empty() || content.ends_with('\n') { newline } else { "" };
+
+    let Some(heading) = insert.section.as_deref().map(str::trim) else {
+        let split_at = if insert.prepend { split_frontmatter(content).0.len() } else { content.len() };
+        let (before, after) = content.split_at(split_at);
+        return Ok(join(before, &block, after, newline, trailing));
+    };
+
+    let all = headings(content);
+    let Some(index) = all.iter().position(|(_, text, _)| text == heading) else {
+        // 小节不存在：在末尾新建
+        let block = format!("## {}{}{}{}", heading, newline, newline, block);
+        return Ok(join(content, &block, "", newline, trailing));
+    };
+    let (level, _, start) = all[index];
+    let split_at = if insert.prepend {
+        content[start..].find('\n').map_or(content.len(), |offset| start + offset + 1)
+    } else {
+        all[index + 1..]
+            .iter()
+            .find(|(next_level, _, _)| *next_level <= level)
+            .map_or(content.len(), |(_, _, offset)| *offset)
+    };
+    let (before, after) = content.split_at(split_at);
+    Ok(join(before, &block, after, newline, trailing))
+}
+
+/// 以恰好一个空行拼接前文、插入块和后文
+fn join(before: &str, block: &str, after: &str, newline: &str, trailing: &str) -> String {
+    let before = before.trim_end_matches(['\n', '\r']);
+    let after = after.trim_start_matches(['\n', '\r']);
+    let mut result = String::with_capacity(before.len() + block.len() + after.len() + 8);
+    result.push_str(before);
+    if !before.is_empty() {
+        result.push_str(newline);
+        result.push_str(newline);
+    }
+    result.push_str(block);
+    if after.is_empty() {
+        result.push_str(trailing);
+    } else {
+        result.push_str(newline);
+        result.push_str(newline);
+        result.push_str(after);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "---\ntitle: Log\n---\n\n# Log\n\n## Ideas\n\n- first\n\n## Done\n\n- shipped\n";
+
+    fn section(name: &str, prepend: bool) -> Insert {
+        Insert { prepend, section: Some(name.to_string()) }
+    }
+
+    #[test]
+    fn test_append_and_prepend() {
+        let appended = insert_text(NOTE, "New line.\n\n", &Insert::default()).unwrap();
+        assert!(appended.ends_with("- shipped\n\nNew line.\n"));
+
+        let prepended = insert_text(NOTE, "Top.", &Insert { prepend: true, section: None }).unwrap();
+        assert!(prepended.starts_with("---\ntitle: Log\n---\n\nTop.\n\n# Log\n"));
+
+        // 没有 frontmatter、没有末尾换行
+        assert_eq!(insert_text("Body", "More", &Insert::default()).unwrap(), "Body\n\nMore");
+        assert_eq!(insert_text("", "First", &Insert::default()).unwrap(), "First\n");
+        assert_eq!(insert_text("a\r\n", "b\nc", &Insert::default()).unwrap(), "a\r\n\r\nb\r\nc\r\n");
+        assert!(insert_text(NOTE, " \n", &Insert::default()).is_err());
+    }
+
+    #[test]
+    fn test_insert_into_section() {
+        let appended = insert_text(NOTE, "- second", &section("Ideas", false)).unwrap();
+        assert!(appended.contains("## Ideas\n\n- first\n\n- second\n\n## Done\n"));
+
+        let prepended = insert_text(NOTE, "- zeroth", &section("Ideas", true)).unwrap();
+        assert!(prepended.contains("## Ideas\n\n- zeroth\n\n- first\n\n## Done\n"));
+
+        let created = insert_text(NOTE, "- maybe", &section("Later", false)).unwrap();
+        assert!(created.ends_with("- shipped\n\n## Later\n\n- maybe\n"));
+    }
+
+    #[test]
+    fn test_read_text_from_stdin() {
+        assert_eq!(read_text("inline", "ignored".as_bytes()).unwrap(), "inline");
+        assert_eq!(read_text("-", "piped\n".as_bytes()).unwrap(), "piped\n");
+    }
+}
@@ -0,0 +1,788 @@
+//! 仓库配置模块
+//!
+//! 管理存储在 `.memexia/config/config.toml` 中的仓库级配置
+//!
+//! ## 配置格式
+//!
+//! ```toml
+//! [ids]
+//! zettel = true
+//!
+//! [index]
+//! extensions = ["md", "org"]
+//! decrypt_cmd = "age -d -i ~/.age/key.txt"
+//! max_note_bytes = 4194304         # 超过该大小的文件不索引
+//! content_excerpt_bytes = 65536    # 节点上最多保存的正文长度
+//! auto_summary = true              # 为没有 summary 的笔记提取第一段作为摘要
+//!
+//! [links]
+//! default_relation = "References"  # 未写关系的 wiki 链接使用的关系（默认 RelatedTo）
+//!
+//! [hooks]
+//! post_commit_url = "http://localhost:8080/rebuild"
+//! post_commit_cmd = "./scripts/on-commit.sh"
+//! timeout_ms = 5000
+//! pre_commit_validate = true
+//!
+//! [validate.rules]
+//! require_tags = "warn"
+//!
+//! [lint]
+//! max_words = 3000
+//!
+//! [lint.rules]
+//! missing_title = "off"
+//!
+//! [publish]
+//! exclude_tags = ["private"]
+//! base_url = "https://notes.example.com"  # `memexia feed` 的条目链接到发布站点
+//!
+//! [render]
+//! sanitize_html = false  # `memexia serve` 渲染笔记时保留原始 HTML（默认转义）
+//!
+//! [serve]
+//! rate_limit = 10        # 每个客户端每秒请求数，0 表示不限流
+//! rate_burst = 20        # 允许的突发请求数
+//! max_body_bytes = 1048576
+//!
+//! [[serve.tokens]]           # 带权限范围的令牌，可用 `memexia serve token create` 生成
+//! token = "3f2a..."
+//! scopes = ["read", "edges:write"]
+//!
+//! [dashboard]
+//! stale_days = 90          # `memexia dashboard` 的阈值和权重，见 [`crate::core::dashboard`]
+//!
+//! [cache]
+//! nodes = 10000             # 读取密集的命令缓存的节点数，0 表示不缓存
+//!
+//! [changes]
+//! log = true                # 把图的变更以 NDJSON 追加到 .memexia/changes.log
+//! max_log_bytes = 10485760  # 超过后轮转为 changes.log.1
+//!
+//! [watch]
+//! auto_snapshot_interval = "24h"
+//! auto_snapshot_keep = 7
+//!
+//! [paths]
+//! notes = ["content", "journal"]  # 也可以是单个字符串，"." 表示仓库根目录
+//! archive = "archive"              # `memexia archive` 移入的目录
+//!
+//! [history]
+//! retention = { keep_last = 100 }  # 或 "keep_all"、{ daily = 30, weekly = 52, monthly = "all" }
+//! derivations = "commit"          # 由 DerivesFrom 链接自动记录推导：commit（默认）、index 或 off
+//!
+//! [history.traversal]
+//! max_depth = 1000   # 推导遍历的最大深度
+//! max_nodes = 10000  # 推导遍历最多访问的节点数
+//! ```
+//!
+//! 通过 `memexia config <key> [value]` 以点分键名读写，例如 `hooks.post_commit_url`
+
+use crate::core::dashboard::DashboardConfig;
+use crate::core::lint::LintConfig;
+use crate::core::publish::PublishConfig;
+use crate::core::render::RenderConfig;
+use crate::core::retention::RetentionPolicy;
+use crate::core::validate::ValidateConfig;
+use crate::storage::RelationType;
+use crate::vcs::TraversalLimits;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 仓库配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepoConfig {
+    /// 节点 ID 配置
+    #[serde(default)]
+    pub ids: IdsConfig,
+    /// 索引配置
+    #[serde(default)]
+    pub index: IndexConfig,
+    /// 链接配置
+    #[serde(default)]
+    pub links: LinksConfig,
+    /// 钩子配置
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    /// 图验证规则配置
+    #[serde(default)]
+    pub validate: ValidateConfig,
+    /// 笔记 lint 配置
+    #[serde(default)]
+    pub lint: LintConfig,
+    /// 静态站点发布配置
+    #[serde(default)]
+    pub publish: PublishConfig,
+    /// 笔记 HTML 渲染配置
+    #[serde(default)]
+    pub render: RenderConfig,
+    /// 本地 API 服务配置
+    #[serde(default)]
+    pub serve: ServeConfig,
+    /// 变更日志配置
+    #[serde(default)]
+    pub changes: ChangesConfig,
+    /// 仓库概览配置
+    #[serde(default)]
+    pub dashboard: DashboardConfig,
+    /// 节点读缓存配置
+    #[serde(default)]
+    pub cache: CacheConfig,
+    /// 监听进程配置
+    #[serde(default)]
+    pub watch: WatchDaemonConfig,
+    /// 目录配置
+    #[serde(default)]
+    pub paths: PathsConfig,
+    /// 图历史配置
+    #[serde(default)]
+    pub history: HistoryConfig,
+}
+
+/// 图历史配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryConfig {
+    /// 提交快照的保留策略，见 [`crate::core::retention`]
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+    /// 推导链、推导树和回退预览的遍历限制，见 [`crate::vcs::traversal`]
+    #[serde(default)]
+    pub traversal: TraversalLimits,
+    /// 何时由 `DerivesFrom` 链接自动记录推导关系
+    #[serde(default)]
+    pub derivations: DerivationRecording,
+}
+
+/// 由 `[[来源|DerivesFrom]]` 链接自动记录推导关系的时机
+///
+/// 同一对子节点和父节点只记录一次，见 [`GraphHistory::record_new_derivations`](crate::vcs::GraphHistory::record_new_derivations)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DerivationRecording {
+    /// 提交时为新引入的 `DerivesFrom` 边记录，关联该提交
+    #[default]
+    Commit,
+    /// `reindex` 后为图中的 `DerivesFrom` 边记录，关联 HEAD
+    Index,
+    /// 不自动记录
+    Off,
+}
+
+/// 目录配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PathsConfig {
+    /// 笔记根目录（相对仓库根目录），可配置多个
+    ///
+    /// 未设置时 `init` 和 `new` 使用 `notes/`，自动遍历（`reindex`、`watch`、`publish`）覆盖整个仓库；
+    /// 设置后自动遍历只覆盖这些目录，其他位置的文件仍可通过 `memexia add` 显式加入
+    #[serde(default, deserialize_with = "deserialize_notes", skip_serializing_if = "Option::is_none")]
+    pub notes: Option<Vec<String>>,
+    /// 归档目录（相对仓库根目录），未设置时为 `archive/`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive: Option<String>,
+}
+
+impl PathsConfig {
+    /// 用于创建目录和新笔记的笔记根目录（相对仓库根目录）
+    pub fn note_dirs(&self) -> Vec<String> {
+        match &self.notes {
+            Some(notes) if !notes.is_empty() => notes.clone(),
+            _ => vec![crate::core::note::NOTES_DIR.to_string()],
+        }
+    }
+
+    /// `memexia new` 写入的目录（第一个笔记根目录）
+    pub fn new_note_dir(&self) -> String {
+        self.note_dirs().swap_remove(0)
+    }
+
+    /// `memexia archive` 移入的目录（相对仓库根目录，以 `/` 分隔，不带末尾斜杠）
+    pub fn archive_dir(&self) -> String {
+        let dir = self.archive.as_deref().unwrap_or(crate::core::archive::DEFAULT_ARCHIVE_DIR);
+        dir.trim_matches('/').to_string()
+    }
+
+    /// 自动遍历的根目录（相对仓库根目录）；未配置时为空，表示不限制
+    pub fn walk_dirs(&self) -> &[String] {
+        self.notes.as_deref().unwrap_or_default()
+    }
+}
+
+/// `paths.notes` 既可以是单个字符串也可以是字符串数组
+fn deserialize_notes<'de, D>(deserializer: D) -> std::result::Result<Option<Vec<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(Some(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(dir) => vec![dir],
+        OneOrMany::Many(dirs) => dirs,
+    }))
+}
+
+/// 节点 ID 配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IdsConfig {
+    /// 启用 Zettelkasten ID（从文件名前缀提取时间戳 ID 作为节点身份）
+    #[serde(default)]
+    pub zettel: bool,
+}
+
+/// 链接配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LinksConfig {
+    /// 未写关系（`[[目标]]`）或关系名无法识别的 wiki 链接使用的关系，可以是内置或自定义关系；
+    /// 只影响之后解析的笔记，N-Quads 导入和已有的边不变
+    #[serde(default)]
+    pub default_relation: RelationType,
+}
+
+/// 索引配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexConfig {
+    /// 参与索引的文件扩展名（不含点），支持 `md` 和 `org`
+    #[serde(default = "default_index_extensions")]
+    pub extensions: Vec<String>,
+    /// 解密 `.md.age` / `.md.gpg` 笔记的命令（密文从 stdin 传入，明文从 stdout 读取）；
+    /// 未设置时加密笔记只生成占位节点
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decrypt_cmd: Option<String>,
+    /// 文件大小上限（字节），超过时跳过索引
+    #[serde(default = "default_max_note_bytes")]
+    pub max_note_bytes: u64,
+    /// 节点上保存的正文长度上限（字节），更长的笔记只保存开头部分
+    #[serde(default = "default_content_excerpt_bytes")]
+    pub content_excerpt_bytes: usize,
+    /// 是否为没有 frontmatter `summary:` 的笔记自动提取摘要，见 [`crate::core::summary`]
+    #[serde(default)]
+    pub auto_summary: bool,
+}
+
+fn default_index_extensions() -> Vec<String> {
+    vec!["md".to_string()]
+}
+
+fn default_max_note_bytes() -> u64 {
+    crate::core::indexer::DEFAULT_MAX_NOTE_BYTES
+}
+
+fn default_content_excerpt_bytes() -> usize {
+    crate::core::indexer::DEFAULT_CONTENT_EXCERPT_BYTES
+}
+
+impl Default for IndexConfig {
+    fn default() -> Self {
+        Self {
+            extensions: default_index_extensions(),
+            decrypt_cmd: None,
+            max_note_bytes: default_max_note_bytes(),
+            content_excerpt_bytes: default_content_excerpt_bytes(),
+            auto_summary: false,
+        }
+    }
+}
+
+/// 监听进程配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchDaemonConfig {
+    /// 自动快照间隔（如 `24h`），未设置时不自动快照
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_snapshot_interval: Option<String>,
+    /// 保留的自动快照个数
+    #[serde(default = "default_auto_snapshot_keep")]
+    pub auto_snapshot_keep: usize,
+}
+
+fn default_auto_snapshot_keep() -> usize {
+    7
+}
+
+impl Default for WatchDaemonConfig {
+    fn default() -> Self {
+        Self {
+            auto_snapshot_interval: None,
+            auto_snapshot_keep: default_auto_snapshot_keep(),
+        }
+    }
+}
+
+/// 节点读缓存配置，见 [`crate::storage::CachedStorage`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheConfig {
+    /// 读取密集的命令（`graph`、`export`、`stats` 等）最多缓存的节点数，`0` 表示不缓存
+    #[serde(default = "default_cache_nodes")]
+    pub nodes: usize,
+}
+
+fn default_cache_nodes() -> usize {
+    crate::storage::cache::DEFAULT_NODE_CACHE_CAPACITY
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self { nodes: default_cache_nodes() }
+    }
+}
+
+/// 变更日志配置，见 [`crate::storage::ChangeLog`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangesConfig {
+    /// 是否把图的变更追加到 `.memexia/changes.log`
+    #[serde(default)]
+    pub log: bool,
+    /// 日志轮转的大小上限（字节）
+    #[serde(default = "default_max_log_bytes")]
+    pub max_log_bytes: u64,
+}
+
+fn default_max_log_bytes() -> u64 {
+    crate::storage::observer::DEFAULT_CHANGE_LOG_MAX_BYTES
+}
+
+impl Default for ChangesConfig {
+    fn default() -> Self {
+        Self {
+            log: false,
+            max_log_bytes: default_max_log_bytes(),
+        }
+    }
+}
+
+/// 本地 API 服务配置（`memexia serve`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServeConfig {
+    /// 每个客户端（按 IP）每秒允许的请求数，`0` 表示不限流
+    #[serde(default = "default_rate_limit")]
+    pub rate_limit: f64,
+    /// 令牌桶容量：空闲客户端可连续发出的请求数
+    #[serde(default = "default_rate_burst")]
+    pub rate_burst: u32,
+    /// 请求体大小上限（字节），超过时返回 413
+    #[serde(default = "default_max_body_bytes")]
+    pub max_body_bytes: u64,
+    /// 带权限范围的访问令牌；配置后读取接口也要求带 `read` 权限的令牌
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tokens: Vec<ServeToken>,
+}
+
+/// `memexia serve` 接口的权限范围，每个接口声明所需的范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ServeScope {
+    /// 读取笔记、链接和布局
+    #[serde(rename = "read")]
+    Read,
+    /// 修改和删除节点
+    #[serde(rename = "nodes:write")]
+    NodesWrite,
+    /// 创建和删除链接
+    #[serde(rename = "edges:write")]
+    EdgesWrite,
+    /// 索引、暂存和提交；拥有全部权限
+    #[serde(rename = "admin")]
+    Admin,
+}
+
+impl ServeScope {
+    /// 全部权限范围
+    pub const ALL: [ServeScope; 4] = [ServeScope::Read, ServeScope::NodesWrite, ServeScope::EdgesWrite, ServeScope::Admin];
+
+    /// 配置中的名称（如 `edges:write`）
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ServeScope::Read => "read",
+            ServeScope::NodesWrite => "nodes:write",
+            ServeScope::EdgesWrite => "edges:write",
+            ServeScope::Admin => "admin",
+        }
+    }
+
+    /// 按名称解析
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|scope| scope.as_str() == s.trim())
+    }
+}
+
+impl std::fmt::Display for ServeScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 带权限范围的访问令牌（`[[serve.tokens]]`），请求时放在 `X-Memexia-Token` 请求头中
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServeToken {
+    /// 令牌
+    pub token: String,
+    /// 授予的权限范围
+    pub scopes: Vec<ServeScope>,
+}
+
+impl ServeToken {
+    /// 生成随机令牌
+    pub fn generate(scopes: Vec<ServeScope>) -> Self {
+        Self {
+            token: uuid::Uuid::new_v4().simple().to_string(),
+            scopes,
+        }
+    }
+
+    /// 是否授予了指定权限（`admin` 授予全部权限）
+    pub fn allows(&self, scope: ServeScope) -> bool {
+        self.scopes.contains(&scope) || self.scopes.contains(&ServeScope::Admin)
+    }
+}
+
+fn default_rate_limit() -> f64 {
+    10.0
+}
+
+fn default_rate_burst() -> u32 {
+    20
+}
+
+fn default_max_body_bytes() -> u64 {
+    1024 * 1024
+}
+
+impl Default for ServeConfig {
+    fn default() -> Self {
+        Self {
+            rate_limit: default_rate_limit(),
+            rate_burst: default_rate_burst(),
+            max_body_bytes: default_max_body_bytes(),
+            tokens: Vec::new(),
+        }
+    }
+}
+
+/// 钩子配置
+///
+/// 提交或索引完成后向外部通知
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HooksConfig {
+    /// 提交成功后 POST 的 URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_commit_url: Option<String>,
+    /// 索引批次完成后 POST 的 URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_index_url: Option<String>,
+    /// 提交成功后执行的本地命令（payload 通过 stdin 传入）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_commit_cmd: Option<String>,
+    /// 请求/命令超时（毫秒）
+    #[serde(default = "default_hook_timeout_ms")]
+    pub timeout_ms: u64,
+    /// 提交前运行图验证规则，存在错误时中止提交
+    #[serde(default)]
+    pub pre_commit_validate: bool,
+}
+
+fn default_hook_timeout_ms() -> u64 {
+    5000
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            post_commit_url: None,
+            post_index_url: None,
+            post_commit_cmd: None,
+            timeout_ms: default_hook_timeout_ms(),
+            pre_commit_validate: false,
+        }
+    }
+}
+
+impl RepoConfig {
+    /// 配置文件路径
+    pub fn path(root: &Path) -> PathBuf {
+        root.join(".memexia/config/config.toml")
+    }
+
+    /// 加载仓库配置
+    ///
+    /// 配置文件不存在时返回默认配置
+    pub fn load(root: &Path) -> Result<Self> {
+        let table = load_table(root)?;
+        table_to_config(table)
+    }
+
+    /// 保存仓库配置
+    pub fn save(&self, root: &Path) -> Result<()> {
+        let content = toml::to_string_pretty(self)?;
+        write_config_file(root, &content)
+    }
+
+    /// 读取点分键名对应的值
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - 仓库根目录
+    /// * `key` - 点分键名，如 `hooks.post_commit_url`
+    ///
+    /// # Returns
+    ///
+    /// 值的字符串形式，键不存在时返回 None
+    pub fn get(root: &Path, key: &str) -> Result<Option<String>> {
+        let table = load_table(root)?;
+        let mut current = toml::Value::Table(table);
+
+        for part in key.split('.') {
+            match current {
+                toml::Value::Table(mut t) => match t.remove(part) {
+                    Some(v) => current = v,
+                    None => return Ok(None),
+                },
+                _ => return Ok(None),
+            }
+        }
+
+        Ok(Some(match current {
+            toml::Value::String(s) => s,
+            other => other.to_string(),
+        }))
+    }
+
+    /// 设置点分键名对应的值
+    ///
+    /// 值按 TOML 字面量解析（布尔、整数、浮点），否则作为字符串；
+    /// 写入前会校验整体配置能否被正确解析
+    pub fn set(root: &Path, key: &str, value: &str) -> Result<()> {
+        Self::set_value(root, key, parse_value(value))
+    }
+
+    /// 设置点分键名对应的 TOML 值（如数组），写入前同样校验整体配置
+    pub fn set_value(root: &Path, key: &str, value: toml::Value) -> Result<()> {
+        let mut table = load_table(root)?;
+        let parts: Vec<&str> = key.split('.').collect();
+        if parts.iter().any(|p| p.is_empty()) {
+            bail!("Invalid config key: {}", key);
+        }
+
+        let (last, parents) = parts.split_last().unwrap();
+        let mut current = &mut table;
+        for part in parents {
+            let entry = current
+                .entry(part.to_string())
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            current = match entry {
+                toml::Value::Table(t) => t,
+                _ => bail!("Config key '{}' is not a table", part),
+            };
+        }
+        current.insert(last.to_string(), value);
+
+        // 校验
+        table_to_config(table.clone()).with_context(|| format!("Invalid value for {}", key))?;
+
+        write_config_file(root, &toml::to_string_pretty(&table)?)
+    }
+
+    /// 追加一个 `[[serve.tokens]]` 令牌（`memexia serve token create`）
+    pub fn add_serve_token(root: &Path, token: &ServeToken) -> Result<()> {
+        let table = load_table(root)?;
+        let mut tokens = match table.get("serve").and_then(|serve| serve.get("tokens")) {
+            Some(toml::Value::Array(tokens)) => tokens.clone(),
+            Some(_) => bail!("Config key 'serve.tokens' is not an array"),
+            None => Vec::new(),
+        };
+        tokens.push(toml::Value::try_from(token)?);
+        Self::set_value(root, "serve.tokens", toml::Value::Array(tokens))
+    }
+
+    /// 列出所有已设置的键值对（点分键名）
+    pub fn list(root: &Path) -> Result<Vec<(String, String)>> {
+        let table = load_table(root)?;
+        let mut entries = Vec::new();
+        flatten_table("", &table, &mut entries);
+        Ok(entries)
+    }
+}
+
+/// 读取配置文件为 TOML 表
+fn load_table(root: &Path) -> Result<toml::Table> {
+    let path = RepoConfig::path(root);
+    if !path.exists() {
+        return Ok(toml::Table::new());
+    }
+
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {:?}", path))?;
+    toml::from_str(&content).with_context(|| format!("Failed to parse {:?}", path))
+}
+
+/// 将 TOML 表转换为类型化配置
+fn table_to_config(table: toml::Table) -> Result<RepoConfig> {
+    Ok(toml::Value::Table(table).try_into()?)
+}
+
+/// 写入配置文件
+fn write_config_file(root: &Path, content: &str) -> Result<()> {
+    let path = RepoConfig::path(root);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, content).with_context(|| format!("Failed to write {:?}", path))
+}
+
+/// 将命令行字符串解析为 TOML 值
+fn parse_value(value: &str) -> toml::Value {
+    if let Ok(b) = value.parse::<bool>() {
+        toml::Value::Boolean(b)
+    } else if let Ok(i) = value.parse::<i64>() {
+        toml::Value::Integer(i)
+    } else if let Ok(f) = value.parse::<f64>() {
+        toml::Value::Float(f)
+    } else {
+        toml::Value::String(value.to_string())
+    }
+}
+
+/// 展开嵌套表为点分键名
+fn flatten_table(prefix: &str, table: &toml::Table, entries: &mut Vec<(String, String)>) {
+    for (key, value) in table {
+        let full_key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            toml::Value::Table(t) => flatten_table(&full_key, t, entries),
+            toml::Value::String(s) => entries.push((full_key, s.clone())),
+            other => entries.push((full_key, other.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_missing_returns_default() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = RepoConfig::load(temp_dir.path()).unwrap();
+
+        assert!(config.hooks.post_commit_url.is_none());
+        assert_eq!(config.hooks.timeout_ms, 5000);
+    }
+
+    #[test]
+    fn test_set_and_get() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        RepoConfig::set(root, "hooks.post_commit_url", "http://localhost:9000/hook").unwrap();
+        RepoConfig::set(root, "hooks.timeout_ms", "1500").unwrap();
+
+        assert_eq!(
+            RepoConfig::get(root, "hooks.post_commit_url").unwrap(),
+            Some("http://localhost:9000/hook".to_string())
+        );
+        assert_eq!(RepoConfig::get(root, "hooks.timeout_ms").unwrap(), Some("1500".to_string()));
+        assert_eq!(RepoConfig::get(root, "hooks.missing").unwrap(), None);
+
+        let config = RepoConfig::load(root).unwrap();
+        assert_eq!(config.hooks.post_commit_url.as_deref(), Some("http://localhost:9000/hook"));
+        assert_eq!(config.hooks.timeout_ms, 1500);
+
+        let entries = RepoConfig::list(root).unwrap();
+        assert_eq!(entries.len(), 2);
+    }
+
+    #[test]
+    fn test_paths_notes_string_or_array() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        let config = RepoConfig::load(root).unwrap();
+        assert_eq!(config.paths.note_dirs(), vec!["notes"]);
+        assert!(config.paths.walk_dirs().is_empty());
+
+        RepoConfig::set(root, "paths.notes", "content").unwrap();
+        let config = RepoConfig::load(root).unwrap();
+        assert_eq!(config.paths.note_dirs(), vec!["content"]);
+        assert_eq!(config.paths.walk_dirs(), ["content"]);
+
+        write_config_file(root, "[paths]\nnotes = [\".\", \"journal\"]\n").unwrap();
+        let config = RepoConfig::load(root).unwrap();
+        assert_eq!(config.paths.new_note_dir(), ".");
+        assert_eq!(config.paths.walk_dirs(), [".", "journal"]);
+    }
+
+    #[test]
+    fn test_set_invalid_type_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        let result = RepoConfig::set(root, "hooks.timeout_ms", "soon");
+        assert!(result.is_err());
+        assert!(!RepoConfig::path(root).exists());
+    }
+
+    #[test]
+    fn test_links_default_relation() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        assert_eq!(RepoConfig::load(root).unwrap().links.default_relation, RelationType::RelatedTo);
+
+        RepoConfig::set(root, "links.default_relation", "references").unwrap();
+        assert_eq!(RepoConfig::load(root).unwrap().links.default_relation, RelationType::References);
+        RepoConfig::set(root, "links.default_relation", "Cites-Methodology").unwrap();
+        assert!(RepoConfig::load(root).unwrap().links.default_relation.is_custom());
+
+        assert!(RepoConfig::set(root, "links.default_relation", "not a relation").is_err());
+        write_config_file(root, "[links]\ndefault_relation = \"支持度\"\n").unwrap();
+        assert!(RepoConfig::load(root).is_err());
+    }
+    #[test]
+    fn test_history_traversal_limits() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        assert_eq!(RepoConfig::load(root).unwrap().history.traversal, TraversalLimits::default());
+
+        write_config_file(root, "[history.traversal]\nmax_depth = 50\n").unwrap();
+        let limits = RepoConfig::load(root).unwrap().history.traversal;
+        assert_eq!((limits.max_depth, limits.max_nodes), (50, crate::vcs::traversal::DEFAULT_MAX_NODES));
+
+        RepoConfig::set(root, "history.traversal.max_nodes", "200").unwrap();
+        assert_eq!(RepoConfig::load(root).unwrap().history.traversal.max_nodes, 200);
+    }
+
+    #[test]
+    fn test_cache_nodes() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        assert_eq!(RepoConfig::load(root).unwrap().cache.nodes, crate::storage::cache::DEFAULT_NODE_CACHE_CAPACITY);
+
+        RepoConfig::set(root, "cache.nodes", "0").unwrap();
+        assert_eq!(RepoConfig::load(root).unwrap().cache.nodes, 0);
+    }
+
+    #[test]
+    fn test_add_serve_token() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        let read = ServeToken::generate(vec![ServeScope::Read]);
+        let admin = ServeToken::generate(vec![ServeScope::Admin]);
+        RepoConfig::add_serve_token(root, &read).unwrap();
+        RepoConfig::add_serve_token(root, &admin).unwrap();
+        assert_eq!(RepoConfig::load(root).unwrap().serve.tokens, [read.clone(), admin.clone()]);
+        assert!(!read.allows(ServeScope::EdgesWrite));
+        assert!(admin.allows(ServeScope::EdgesWrite));
+        assert_eq!(ServeScope::parse("nodes:write"), Some(ServeScope::NodesWrite));
+        assert_eq!(ServeScope::parse("write"), None);
+    }
+}
@@ -0,0 +1,234 @@
+//! 矛盾报告模块
+//!
+//! 为 `memexia report contradictions` 汇总图中尚未解决的冲突：
+//!
+//! - 通过 Contradicts / Supports 边相连的笔记归入同一簇
+//! - 每个簇列出参与的笔记、每条矛盾的强度，以及指向矛盾任一端的 Supports 边
+//! - 簇按矛盾强度之和降序排列
+//!
+//! 只包含至少一条矛盾边（强度不低于阈值）的簇
+
+use crate::core::review::node_path;
+use crate::error::{MemexiaError, Result};
+use crate::storage::{Edge, GraphStorage, RelationType};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// 支持矛盾某一端的边
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SupportEdge {
+    /// 源节点 ID
+    pub from: String,
+    /// 被支持的节点 ID（矛盾的一端）
+    pub to: String,
+    /// 关系强度
+    pub strength: f64,
+}
+
+/// 一条矛盾边
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Contradiction {
+    /// 源节点 ID
+    pub from: String,
+    /// 目标节点 ID
+    pub to: String,
+    /// 关系强度
+    pub strength: f64,
+    /// 关系描述
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// 支持矛盾任一端的 Supports 边
+    pub supports: Vec<SupportEdge>,
+}
+
+/// 矛盾簇
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ContradictionCluster {
+    /// 参与的节点 ID（按 ID 排序）
+    pub nodes: Vec<String>,
+    /// 簇内的矛盾（按强度降序）
+    pub contradictions: Vec<Contradiction>,
+    /// 矛盾强度之和
+    pub total_strength: f64,
+}
+
+impl ContradictionCluster {
+    /// 用于展示的笔记位置：文件路径，Zettel ID 节点使用原始 ID
+    pub fn display(id: &str) -> String {
+        node_path(id).unwrap_or_else(|| id.to_string())
+    }
+}
+
+/// 查找矛盾簇
+///
+/// # Arguments
+///
+/// * `graph` - 图存储
+/// * `min_strength` - 矛盾边的最低强度（`0.0..=1.0`），更弱的矛盾不计入也不参与分簇
+///
+/// # Returns
+///
+/// 按矛盾强度之和降序排列的簇
+pub fn find_contradictions(graph: &dyn GraphStorage, min_strength: f64) -> Result<Vec<ContradictionCluster>> {
+    if !(0.0..=1.0).contains(&min_strength) {
+        return Err(MemexiaError::Validation(format!(
+            "Minimum strength must be between 0 and 1, got {}",
+            min_strength
+        )));
+    }
+
+    let mut contradicts = Vec::new();
+    let mut supports = Vec::new();
+    for edge in graph.list_edges()? {
+        match edge.relation {
+            RelationType::Contradicts if edge.strength >= min_strength => contradicts.push(edge),
+            RelationType::Supports => supports.push(edge),
+            _ => {}
+        }
+    }
+    if contradicts.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // 并查集：矛盾和支持链上的节点归入同一簇
+    let mut parent: BTreeMap<String, String> = BTreeMap::new();
+    for edge in contradicts.iter().chain(&supports) {
+        union(&mut parent, &edge.from, &edge.to);
+    }
+
+    let mut clusters: BTreeMap<String, ContradictionCluster> = BTreeMap::new();
+    for edge in &contradicts {
+        let root = find(&mut parent, &edge.from);
+        let cluster = clusters.entry(root).or_insert_with(|| ContradictionCluster {
+            nodes: Vec::new(),
+            contradictions: Vec::new(),
+            total_strength: 0.0,
+        });
+        cluster.total_strength += edge.strength;
+        cluster.contradictions.push(contradiction(edge, &supports));
+    }
+
+    let ids: Vec<String> = parent.keys().cloned().collect();
+    for id in ids {
+        let root = find(&mut parent, &id);
+        if let Some(cluster) = clusters.get_mut(&root) {
+            cluster.nodes.push(id);
+        }
+    }
+
+    let mut clusters: Vec<ContradictionCluster> = clusters.into_values().collect();
+    for cluster in &mut clusters {
+        cluster
+            .contradictions
+            .sort_by(|x, y| y.strength.total_cmp(&x.strength).then_with(|| (&x.from, &x.to).cmp(&(&y.from, &y.to))));
+    }
+    clusters.sort_by(|x, y| y.total_strength.total_cmp(&x.total_strength).then_with(|| x.nodes.cmp(&y.nodes)));
+    Ok(clusters)
+}
+
+fn contradiction(edge: &Edge, supports: &[Edge]) -> Contradiction {
+    let ends: BTreeSet<&str> = [edge.from.as_str(), edge.to.as_str()].into();
+    let mut backing: Vec<SupportEdge> = supports
+        .iter()
+        .filter(|s| ends.contains(s.to.as_str()))
+        .map(|s| SupportEdge {
+            from: s.from.clone(),
+            to: s.to.clone(),
+            strength: s.strength,
+        })
+        .collect();
+    backing.sort_by(|x, y| y.strength.total_cmp(&x.strength).then_with(|| x.from.cmp(&y.from)));
+
+    Contradiction {
+        from: edge.from.clone(),
+        to: edge.to.clone(),
+        strength: edge.strength,
+        description: edge.description.clone(),
+        supports: backing,
+    }
+}
+
+fn find(parent: &mut BTreeMap<String, String>, id: &str) -> String {
+    let next = parent.entry(id.to_string()).or_insert_with(|| id.to_string()).clone();
+    if next == id {
+        return next;
+    }
+    let root = find(parent, &next);
+    parent.insert(id.to_string(), root.clone());
+    root
+}
+
+fn union(parent: &mut BTreeMap<String, String>, a: &str, b: &str) {
+    let (ra, rb) = (find(parent, a), find(parent, b));
+    // 较小的 ID 作为根，结果与边的遍历顺序无关
+    match ra.cmp(&rb) {
+        std::cmp::Ordering::Less => {
+            parent.insert(rb, ra);
+        }
+        std::cmp::Ordering::Greater => {
+            parent.insert(ra, rb);
+        }
+        std::cmp::Ordering::Equal => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{Node, NodeType, Storage};
+    use tempfile::TempDir;
+
+    fn link(graph: &dyn GraphStorage, from: &str, to: &str, relation: RelationType, strength: f64) {
+        let (from, to) = (format!("urn:memexia:file:{}.md", from), format!("urn:memexia:file:{}.md", to));
+        for id in [&from, &to] {
+            if !graph.node_exists(id).unwrap() {
+                graph.add_node(&Node::new(id, NodeType::Concept, id)).unwrap();
+            }
+        }
+        let edge = Edge::builder(&from, &to, relation).strength(strength).build().unwrap();
+        graph.add_edge(&edge).unwrap();
+    }
+
+    #[test]
+    fn test_find_contradictions_clusters_and_orders() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        let graph = storage.graph();
+
+        // 矛盾三角：a、b、c 两两矛盾，d 支持 a
+        link(graph, "a", "b", RelationType::Contradicts, 0.9);
+        link(graph, "b", "c", RelationType::Contradicts, 0.6);
+        link(graph, "c", "a", RelationType::Contradicts, 0.3);
+        link(graph, "d", "a", RelationType::Supports, 0.7);
+        // 另一个更弱的簇：x 与 y 的矛盾，经 z 的支持链相连
+        link(graph, "x", "y", RelationType::Contradicts, 0.5);
+        link(graph, "z", "y", RelationType::Supports, 0.4);
+        // 无关的边不参与
+        link(graph, "a", "q", RelationType::RelatedTo, 1.0);
+
+        let clusters = find_contradictions(graph, 0.0).unwrap();
+        assert_eq!(clusters.len(), 2);
+
+        let names = |ids: &[String]| -> Vec<String> {
+            ids.iter().map(|id| ContradictionCluster::display(id)).collect()
+        };
+        assert_eq!(names(&clusters[0].nodes), ["a.md", "b.md", "c.md", "d.md"]);
+        assert!((clusters[0].total_strength - 1.8).abs() < 1e-9);
+        let strengths: Vec<f64> = clusters[0].contradictions.iter().map(|c| c.strength).collect();
+        assert_eq!(strengths, [0.9, 0.6, 0.3]);
+        // d 支持 a：a 参与的两条矛盾都附上这条支持边
+        assert_eq!(clusters[0].contradictions[0].supports.len(), 1);
+        assert!(clusters[0].contradictions[1].supports.is_empty());
+        assert_eq!(clusters[0].contradictions[2].supports[0].from, "urn:memexia:file:d.md");
+
+        assert_eq!(names(&clusters[1].nodes), ["x.md", "y.md", "z.md"]);
+
+        // 过滤弱矛盾后三角被拆开，只剩最强的一对
+        let clusters = find_contradictions(graph, 0.7).unwrap();
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].contradictions.len(), 1);
+        assert_eq!(names(&clusters[0].nodes), ["a.md", "b.md", "d.md"]);
+
+        assert!(matches!(find_contradictions(graph, 1.5), Err(MemexiaError::Validation(_))));
+    }
+}
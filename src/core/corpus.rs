@@ -0,0 +1,312 @@
+//! 语料导出模块
+//!
+//! 为 `memexia export --format corpus <dir>` 生成供向量化（embedding）流程使用的纯文本语料：
+//!
+//! ```text
+//! <dir>/
+//! ├── manifest.jsonl      # 每个文本文件一行：文件名、节点 ID、标题、类型、标签
+//! └── <path>.txt          # 每篇笔记一个文件（保持原目录结构）
+//! ```
+//!
+//! 文本由标题、frontmatter 中的 `summary` 和正文组成，wiki 链接替换为显示文本。
+//! 指定分块大小时，长笔记按词切分为相互重叠的块（`<path>.<n>.txt`），清单中记录块序号和词范围
+
+use crate::core::parser::frontmatter::{extract_frontmatter, parse_frontmatter};
+use crate::core::parser::wiki_link::replace_wiki_links_with_text;
+use crate::error::{MemexiaError, Result};
+use crate::storage::{Node, NodeType};
+use anyhow::Context;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// 清单文件名
+pub const MANIFEST_FILE: &str = "manifest.jsonl";
+
+/// 待导出的笔记
+#[derive(Debug, Clone)]
+pub struct CorpusNote {
+    /// 相对仓库根目录的文件路径（以 `/` 分隔）
+    pub path: String,
+    /// 对应的图节点
+    pub node: Node,
+    /// 文件原始内容（含 frontmatter）
+    pub content: String,
+}
+
+/// 分块选项
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkOptions {
+    /// 每块的词数
+    pub words: usize,
+    /// 相邻块重叠的词数（必须小于 `words`）
+    pub overlap: usize,
+}
+
+impl ChunkOptions {
+    /// 创建分块选项
+    ///
+    /// # Arguments
+    ///
+    /// * `words` - 每块的词数（大于 0）
+    /// * `overlap` - 相邻块重叠的词数（小于 `words`）
+    pub fn new(words: usize, overlap: usize) -> Result<Self> {
+        if words == 0 {
+            return Err(MemexiaError::Validation("Chunk size must be greater than 0".to_string()));
+        }
+        if overlap >= words {
+            return Err(MemexiaError::Validation(format!(
+                "Chunk overlap ({}) must be smaller than the chunk size ({})",
+                overlap, words
+            )));
+        }
+        Ok(Self { words, overlap })
+    }
+
+    /// 按词数计算各块的词范围 `[start, end)`
+    ///
+    /// 每块从上一块起点后移 `words - overlap` 个词开始，最后一块到文本末尾为止；
+    /// 文本不超过一块时只有一块
+    pub fn spans(&self, word_count: usize) -> Vec<(usize, usize)> {
+        let step = self.words - self.overlap;
+        let mut spans = Vec::new();
+        let mut start = 0;
+        loop {
+            let end = (start + self.words).min(word_count);
+            spans.push((start, end));
+            if end >= word_count {
+                break;
+            }
+            start += step;
+        }
+        spans
+    }
+}
+
+/// 清单中的一行
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// 文本文件（相对输出目录，以 `/` 分隔）
+    pub file: String,
+    /// 节点 ID
+    pub id: String,
+    /// 标题
+    pub title: String,
+    /// 节点类型
+    pub node_type: NodeType,
+    /// 标签
+    pub tags: Vec<String>,
+    /// 源文件（相对仓库根目录）
+    pub source: String,
+    /// 块序号（从 0 开始；未分块时省略）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk: Option<usize>,
+    /// 该笔记的块数（未分块时省略）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunks: Option<usize>,
+    /// 块在笔记文本中的词范围 `[start, end)`（未分块时省略）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub words: Option<(usize, usize)>,
+}
+
+/// 导出结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CorpusSummary {
+    /// 导出的笔记数
+    pub notes: usize,
+    /// 写出的文本文件数
+    pub files: usize,
+}
+
+/// 笔记的纯文本：标题、摘要和去掉 wiki 链接标记的正文，以空行分隔
+pub fn note_text(note: &CorpusNote) -> String {
+    let summary = parse_frontmatter(&note.content).and_then(|fm| fm.summary);
+    let (_, body) = extract_frontmatter(&note.content);
+
+    let mut parts = vec![note.node.title.trim().to_string()];
+    parts.extend(summary.map(|s| s.trim().to_string()));
+    parts.push(replace_wiki_links_with_text(&body).trim().to_string());
+    parts.retain(|part| !part.is_empty());
+    let mut text = parts.join("\n\n");
+    text.push('\n');
+    text
+}
+
+/// 按分块选项切分文本，返回每块的词范围和原文片段
+///
+/// 块内保留原文的换行和空白，块之间按词重叠
+pub fn chunk_text(text: &str, options: ChunkOptions) -> Vec<((usize, usize), String)> {
+    let words: Vec<(usize, usize)> = Regex::new(r"\S+")
+        .unwrap()
+        .find_iter(text)
+        .map(|m| (m.start(), m.end()))
+        .collect();
+    if words.is_empty() {
+        return vec![((0, 0), String::new())];
+    }
+
+    options
+        .spans(words.len())
+        .into_iter()
+        .map(|(start, end)| {
+            let slice = &text[words[start].0..words[end - 1].1];
+            ((start, end), format!("{}\n", slice))
+        })
+        .collect()
+}
+
+/// 写出语料目录
+///
+/// # Arguments
+///
+/// * `notes` - 待导出的笔记
+/// * `chunk` - 分块选项；`None` 时每篇笔记一个文件
+/// * `out` - 输出目录（不存在时创建，已有文件会被覆盖）
+pub fn export_corpus(notes: &[CorpusNote], chunk: Option<ChunkOptions>, out: &Path) -> Result<CorpusSummary> {
+    fs::create_dir_all(out).with_context(|| format!("Failed to create {:?}", out))?;
+    let manifest_path = out.join(MANIFEST_FILE);
+    let mut manifest = BufWriter::new(
+        fs::File::create(&manifest_path).with_context(|| format!("Failed to create {:?}", manifest_path))?,
+    );
+
+    let mut summary = CorpusSummary::default();
+    for note in notes {
+        let text = note_text(note);
+        let stem = note.path.strip_suffix(".md").unwrap_or(&note.path);
+        let entry = |file: String| ManifestEntry {
+            file,
+            id: note.node.id.clone(),
+            title: note.node.title.clone(),
+            node_type: note.node.node_type.clone(),
+            tags: note.node.tags.clone(),
+            source: note.path.clone(),
+            chunk: None,
+            chunks: None,
+            words: None,
+        };
+
+        let files: Vec<(ManifestEntry, String)> = match chunk {
+            None => vec![(entry(format!("{}.txt", stem)), text)],
+            Some(options) => {
+                let chunks = chunk_text(&text, options);
+                let count = chunks.len();
+                chunks
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, (words, content))| {
+                        let entry = ManifestEntry {
+                            chunk: Some(index),
+                            chunks: Some(count),
+                            words: Some(words),
+                            ..entry(format!("{}.{}.txt", stem, index))
+                        };
+                        (entry, content)
+                    })
+                    .collect()
+            }
+        };
+
+        for (entry, content) in files {
+            let path = out.join(&entry.file);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, content).with_context(|| format!("Failed to write {:?}", path))?;
+            serde_json::to_writer(&mut manifest, &entry)?;
+            manifest.write_all(b"\n")?;
+            summary.files += 1;
+        }
+        summary.notes += 1;
+    }
+
+    manifest.flush()?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn note(path: &str, title: &str, content: &str) -> CorpusNote {
+        let node = Node::builder(format!("urn:memexia:file:{}", path), NodeType::Concept, title)
+            .tags(["topic"])
+            .build()
+            .unwrap();
+        CorpusNote {
+            path: path.to_string(),
+            node,
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_note_text_strips_markup() {
+        let note = note(
+            "notes/a.md",
+            "Alpha",
+            "---\ntitle: Alpha\nsummary: Short version\ntags: [topic]\n---\nSee [[b.md|Beta]] and [[c.md]] for more.\n",
+        );
+        assert_eq!(note_text(&note), "Alpha\n\nShort version\n\nSee Beta and  for more.\n");
+    }
+
+    #[test]
+    fn test_chunk_spans_overlap() {
+        let options = ChunkOptions::new(4, 1).unwrap();
+        assert_eq!(options.spans(10), vec![(0, 4), (3, 7), (6, 10)]);
+        assert_eq!(options.spans(11), vec![(0, 4), (3, 7), (6, 10), (9, 11)]);
+        assert_eq!(options.spans(3), vec![(0, 3)]);
+        assert_eq!(ChunkOptions::new(5, 0).unwrap().spans(10), vec![(0, 5), (5, 10)]);
+
+        assert!(ChunkOptions::new(0, 0).is_err());
+        assert!(ChunkOptions::new(3, 3).is_err());
+
+        let chunks = chunk_text("one two\nthree four five six", ChunkOptions::new(3, 1).unwrap());
+        let texts: Vec<&str> = chunks.iter().map(|(_, text)| text.as_str()).collect();
+        assert_eq!(texts, ["one two\nthree\n", "three four five\n", "five six\n"]);
+        assert_eq!(chunks[2].0, (4, 6));
+    }
+
+    #[test]
+    fn test_export_manifest() {
+        let out = TempDir::new().unwrap();
+        let notes = [
+            note("notes/a.md", "Alpha", "# Alpha\n\nSee [[b.md|Beta]].\n"),
+            note("b.md", "Beta", "one two three four five six seven\n"),
+        ];
+
+        let summary = export_corpus(&notes, None, out.path()).unwrap();
+        assert_eq!(summary, CorpusSummary { notes: 2, files: 2 });
+        let text = fs::read_to_string(out.path().join("notes/a.txt")).unwrap();
+        assert!(text.contains("See Beta."));
+        assert!(!text.contains("[["));
+
+        let manifest: Vec<ManifestEntry> = fs::read_to_string(out.path().join(MANIFEST_FILE))
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(manifest[0].file, "notes/a.txt");
+        assert_eq!(manifest[0].id, "urn:memexia:file:notes/a.md");
+        assert_eq!(manifest[0].tags, ["topic"]);
+        assert_eq!(manifest[0].chunk, None);
+
+        // 分块：标题 1 词 + 正文 7 词，每块 5 词、重叠 2 词
+        let chunked = TempDir::new().unwrap();
+        let summary = export_corpus(&notes[1..], Some(ChunkOptions::new(5, 2).unwrap()), chunked.path()).unwrap();
+        assert_eq!(summary, CorpusSummary { notes: 1, files: 2 });
+        let manifest: Vec<ManifestEntry> = fs::read_to_string(chunked.path().join(MANIFEST_FILE))
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let ranges: Vec<_> = manifest.iter().map(|e| (e.file.as_str(), e.chunk, e.chunks, e.words)).collect();
+        assert_eq!(
+            ranges,
+            [("b.0.txt", Some(0), Some(2), Some((0, 5))), ("b.1.txt", Some(1), Some(2), Some((3, 8)))]
+        );
+        assert_eq!(fs::read_to_string(chunked.path().join("b.1.txt")).unwrap(), "three four five six seven\n");
+    }
+}
@@ -0,0 +1,463 @@
+//! 仓库概览模块（`memexia dashboard`）
+//!
+//! 把几份报告汇总成一屏：笔记数和边数、孤立笔记、悬空链接、未解决的问题、久未修改的笔记、
+//! 最常用的标签和最近一次提交。前四项各自算出占比，与 `[dashboard]` 中的阈值比较后标记
+//! ✓ 或 ⚠，再按权重合成 0–100 的健康分：
+//!
+//! | 检查项 | 占比 |
+//! |--------|------|
+//! | `orphans` | 没有任何边的笔记 / 笔记数 |
+//! | `dangling` | 端点不存在的边（见 [`GraphStorage::find_dangling_edges`]）/ 边数 |
+//! | `open_questions` | 没有入边的 Question 笔记 / Question 笔记数 |
+//! | `stale` | 超过 `stale_days` 天未修改的笔记 / 笔记数 |
+//!
+//! 占比不超过阈值时该项得满分，超过后线性下降，占比为 1 时得 0 分。占位节点和已归档的笔记不计入
+//!
+//! ```toml
+//! [dashboard]
+//! stale_days = 90
+//!
+//! [dashboard.thresholds]   # 可接受的最大占比
+//! orphans = 0.1
+//! dangling = 0.0
+//! open_questions = 0.5
+//! stale = 0.5
+//!
+//! [dashboard.weights]
+//! orphans = 30
+//! dangling = 30
+//! open_questions = 10
+//! stale = 30
+//! ```
+//!
+//! [`GraphStorage::find_dangling_edges`]: crate::storage::GraphStorage::find_dangling_edges
+
+use crate::storage::{Edge, Node, NodeType};
+use crate::vcs::CommitInfo;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/// 概览中列出的标签数
+pub const TOP_TAGS: usize = 5;
+
+/// 默认的久未修改天数
+pub const DEFAULT_STALE_DAYS: i64 = 90;
+
+/// 概览配置（`[dashboard]`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardConfig {
+    /// 超过该天数未修改的笔记视为久未修改
+    #[serde(default = "default_stale_days")]
+    pub stale_days: i64,
+    /// 各检查项可接受的最大占比
+    #[serde(default)]
+    pub thresholds: DashboardThresholds,
+    /// 各检查项在健康分中的权重
+    #[serde(default)]
+    pub weights: DashboardWeights,
+}
+
+impl Default for DashboardConfig {
+    fn default() -> Self {
+        Self {
+            stale_days: DEFAULT_STALE_DAYS,
+            thresholds: DashboardThresholds::default(),
+            weights: DashboardWeights::default(),
+        }
+    }
+}
+
+fn default_stale_days() -> i64 {
+    DEFAULT_STALE_DAYS
+}
+
+/// 各检查项可接受的最大占比（0.0–1.0）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DashboardThresholds {
+    /// 孤立笔记
+    #[serde(default = "default_orphans_threshold")]
+    pub orphans: f64,
+    /// 悬空链接
+    #[serde(default)]
+    pub dangling: f64,
+    /// 未解决的问题
+    #[serde(default = "default_half")]
+    pub open_questions: f64,
+    /// 久未修改的笔记
+    #[serde(default = "default_half")]
+    pub stale: f64,
+}
+
+impl Default for DashboardThresholds {
+    fn default() -> Self {
+        Self {
+            orphans: default_orphans_threshold(),
+            dangling: 0.0,
+            open_questions: default_half(),
+            stale: default_half(),
+        }
+    }
+}
+
+fn default_orphans_threshold() -> f64 {
+    0.1
+}
+
+fn default_half() -> f64 {
+    0.5
+}
+
+/// 各检查项的权重
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DashboardWeights {
+    /// 孤立笔记
+    #[serde(default = "default_major_weight")]
+    pub orphans: u32,
+    /// 悬空链接
+    #[serde(default = "default_major_weight")]
+    pub dangling: u32,
+    /// 未解决的问题
+    #[serde(default = "default_minor_weight")]
+    pub open_questions: u32,
+    /// 久未修改的笔记
+    #[serde(default = "default_major_weight")]
+    pub stale: u32,
+}
+
+impl Default for DashboardWeights {
+    fn default() -> Self {
+        Self {
+            orphans: default_major_weight(),
+            dangling: default_major_weight(),
+            open_questions: default_minor_weight(),
+            stale: default_major_weight(),
+        }
+    }
+}
+
+fn default_major_weight() -> u32 {
+    30
+}
+
+fn default_minor_weight() -> u32 {
+    10
+}
+
+/// 检查项
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    /// 孤立笔记
+    Orphans,
+    /// 悬空链接
+    Dangling,
+    /// 未解决的问题
+    OpenQuestions,
+    /// 久未修改的笔记
+    Stale,
+}
+
+impl Metric {
+    /// 所有检查项（按显示顺序）
+    pub const ALL: [Metric; 4] = [Metric::Orphans, Metric::Dangling, Metric::OpenQuestions, Metric::Stale];
+
+    fn threshold(self, thresholds: &DashboardThresholds) -> f64 {
+        match self {
+            Metric::Orphans => thresholds.orphans,
+            Metric::Dangling => thresholds.dangling,
+            Metric::OpenQuestions => thresholds.open_questions,
+            Metric::Stale => thresholds.stale,
+        }
+    }
+
+    fn weight(self, weights: &DashboardWeights) -> u32 {
+        match self {
+            Metric::Orphans => weights.orphans,
+            Metric::Dangling => weights.dangling,
+            Metric::OpenQuestions => weights.open_questions,
+            Metric::Stale => weights.stale,
+        }
+    }
+}
+
+/// 评分的输入计数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct HealthCounts {
+    /// 笔记数
+    pub notes: usize,
+    /// 边数
+    pub edges: usize,
+    /// 孤立笔记数
+    pub orphans: usize,
+    /// 悬空链接数
+    pub dangling: usize,
+    /// Question 笔记数
+    pub questions: usize,
+    /// 未解决的问题数
+    pub open_questions: usize,
+    /// 久未修改的笔记数
+    pub stale: usize,
+}
+
+impl HealthCounts {
+    /// 检查项的计数和分母
+    fn of(&self, metric: Metric) -> (usize, usize) {
+        match metric {
+            Metric::Orphans => (self.orphans, self.notes),
+            Metric::Dangling => (self.dangling, self.edges),
+            Metric::OpenQuestions => (self.open_questions, self.questions),
+            Metric::Stale => (self.stale, self.notes),
+        }
+    }
+}
+
+/// 单个检查项的结果
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Check {
+    /// 检查项
+    pub metric: Metric,
+    /// 计数
+    pub count: usize,
+    /// 分母（笔记数、边数或 Question 数）
+    pub total: usize,
+    /// 占比，分母为 0 时为 0
+    pub ratio: f64,
+    /// 可接受的最大占比
+    pub threshold: f64,
+    /// 是否在阈值以内
+    pub ok: bool,
+    /// 子分数（0.0–1.0）
+    pub score: f64,
+}
+
+/// 按阈值计算子分数：占比不超过阈值时为 1，超过后线性下降，占比为 1 时为 0
+pub fn sub_score(ratio: f64, threshold: f64) -> f64 {
+    if ratio <= threshold {
+        1.0
+    } else {
+        ((1.0 - ratio) / (1.0 - threshold)).clamp(0.0, 1.0)
+    }
+}
+
+/// 计算各检查项
+pub fn checks(counts: &HealthCounts, thresholds: &DashboardThresholds) -> Vec<Check> {
+    Metric::ALL
+        .iter()
+        .map(|&metric| {
+            let (count, total) = counts.of(metric);
+            let ratio = if total == 0 { 0.0 } else { count as f64 / total as f64 };
+            let threshold = metric.threshold(thresholds);
+            Check {
+                metric,
+                count,
+                total,
+                ratio,
+                threshold,
+                ok: ratio <= threshold,
+                score: sub_score(ratio, threshold),
+            }
+        })
+        .collect()
+}
+
+/// 按权重合成 0–100 的健康分，权重全为 0 时为 100
+pub fn health_score(checks: &[Check], weights: &DashboardWeights) -> u8 {
+    let total: u32 = checks.iter().map(|c| c.metric.weight(weights)).sum();
+    if total == 0 {
+        return 100;
+    }
+    let weighted: f64 = checks.iter().map(|c| c.metric.weight(weights) as f64 * c.score).sum();
+    (weighted / total as f64 * 100.0).round() as u8
+}
+
+/// 标签及使用次数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TagCount {
+    /// 标签
+    pub tag: String,
+    /// 使用该标签的笔记数
+    pub count: usize,
+}
+
+/// 最近一次提交
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LastCommit {
+    /// Git OID
+    pub oid: String,
+    /// 提交消息的第一行
+    pub summary: String,
+    /// 作者
+    pub author: String,
+    /// 时间戳
+    pub timestamp: String,
+}
+
+impl From<&CommitInfo> for LastCommit {
+    fn from(commit: &CommitInfo) -> Self {
+        Self {
+            oid: commit.oid.clone(),
+            summary: commit.message.lines().next().unwrap_or_default().to_string(),
+            author: commit.author.clone(),
+            timestamp: commit.timestamp.clone(),
+        }
+    }
+}
+
+/// 仓库概览
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Dashboard {
+    /// 0–100 的健康分
+    pub score: u8,
+    /// 评分的输入计数
+    pub counts: HealthCounts,
+    /// 超过该天数未修改的笔记计入 `stale`
+    pub stale_days: i64,
+    /// 各检查项（顺序同 [`Metric::ALL`]）
+    pub checks: Vec<Check>,
+    /// 最常用的标签（按次数降序）
+    pub top_tags: Vec<TagCount>,
+    /// 最近一次提交
+    pub last_commit: Option<LastCommit>,
+}
+
+impl Dashboard {
+    /// 汇总概览
+    ///
+    /// # Arguments
+    ///
+    /// * `nodes` - 图谱中的所有节点（占位节点和已归档的笔记不计入）
+    /// * `edges` - 图谱中的所有边
+    /// * `dangling` - 悬空链接数
+    /// * `last_commit` - 最近一次提交
+    /// * `now` - 判断久未修改的当前时间
+    /// * `config` - 概览配置
+    pub fn compute(
+        nodes: &[Node],
+        edges: &[Edge],
+        dangling: usize,
+        last_commit: Option<&CommitInfo>,
+        now: DateTime<Utc>,
+        config: &DashboardConfig,
+    ) -> Self {
+        let notes: Vec<&Node> = nodes.iter().filter(|n| !n.is_placeholder() && !n.is_archived()).collect();
+
+        let mut linked = HashSet::new();
+        let mut has_incoming = HashSet::new();
+        for edge in edges.iter().filter(|e| e.from != e.to) {
+            linked.insert(edge.from.as_str());
+            linked.insert(edge.to.as_str());
+            has_incoming.insert(edge.to.as_str());
+        }
+
+        let questions: Vec<&&Node> = notes.iter().filter(|n| n.node_type == NodeType::Question).collect();
+        let stale_before = now - Duration::days(config.stale_days);
+        let counts = HealthCounts {
+            notes: notes.len(),
+            edges: edges.len(),
+            orphans: notes.iter().filter(|n| !linked.contains(n.id.as_str())).count(),
+            dangling,
+            questions: questions.len(),
+            open_questions: questions.iter().filter(|n| !has_incoming.contains(n.id.as_str())).count(),
+            stale: notes.iter().filter(|n| n.updated_at < stale_before).count(),
+        };
+
+        let mut tags: BTreeMap<&str, usize> = BTreeMap::new();
+        for tag in notes.iter().flat_map(|n| &n.tags) {
+            *tags.entry(tag.as_str()).or_default() += 1;
+        }
+        let mut top_tags: Vec<TagCount> =
+            tags.into_iter().map(|(tag, count)| TagCount { tag: tag.to_string(), count }).collect();
+        top_tags.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+        top_tags.truncate(TOP_TAGS);
+
+        let checks = checks(&counts, &config.thresholds);
+        Self {
+            score: health_score(&checks, &config.weights),
+            counts,
+            stale_days: config.stale_days,
+            checks,
+            top_tags,
+            last_commit: last_commit.map(LastCommit::from),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::RelationType;
+
+    fn counts(orphans: usize, dangling: usize, open_questions: usize, stale: usize) -> HealthCounts {
+        HealthCounts { notes: 10, edges: 10, orphans, dangling, questions: 10, open_questions, stale }
+    }
+
+    fn score(counts: &HealthCounts) -> u8 {
+        let config = DashboardConfig::default();
+        health_score(&checks(counts, &config.thresholds), &config.weights)
+    }
+
+    #[test]
+    fn test_sub_score_boundaries() {
+        assert_eq!(sub_score(0.0, 0.0), 1.0);
+        assert_eq!(sub_score(0.1, 0.1), 1.0);
+        assert_eq!(sub_score(1.0, 0.1), 0.0);
+        assert!((sub_score(0.55, 0.1) - 0.5).abs() < 1e-9);
+        assert_eq!(sub_score(1.0, 1.0), 1.0);
+    }
+
+    #[test]
+    fn test_health_score() {
+        assert_eq!(score(&HealthCounts::default()), 100);
+        assert_eq!(score(&counts(0, 0, 0, 0)), 100);
+        assert_eq!(score(&counts(10, 10, 10, 10)), 0);
+
+        // 恰好在阈值上仍得满分
+        assert_eq!(score(&counts(1, 0, 5, 5)), 100);
+        // 孤立笔记全军覆没，扣掉 30 / 100
+        assert_eq!(score(&counts(10, 0, 0, 0)), 70);
+        // 悬空链接阈值为 0，一条也扣分
+        let checks = checks(&counts(0, 1, 0, 0), &DashboardThresholds::default());
+        assert!(!checks[1].ok);
+        assert!((checks[1].score - 0.9).abs() < 1e-9);
+
+        let weights = DashboardWeights { orphans: 0, dangling: 0, open_questions: 0, stale: 0 };
+        assert_eq!(health_score(&checks, &weights), 100);
+    }
+
+    #[test]
+    fn test_dashboard_compute() {
+        let now = Utc::now();
+        let note = |id: &str, node_type: NodeType, days: i64, tags: &[&str]| {
+            Node::builder(id, node_type, id)
+                .content("text")
+                .tags(tags.iter().copied())
+                .created_at(now - Duration::days(365))
+                .updated_at(now - Duration::days(days))
+                .build()
+                .unwrap()
+        };
+        let nodes = vec![
+            note("a", NodeType::Concept, 1, &["x", "y"]),
+            note("b", NodeType::Question, 100, &["x"]),
+            note("q", NodeType::Question, 1, &["z"]),
+            note("lonely", NodeType::Concept, 1, &["x"]),
+            Node::new("placeholder", NodeType::Concept, "Placeholder"),
+        ];
+        let edges = vec![
+            Edge::new(Edge::make_id("a", "b"), "a", "b", RelationType::Supports),
+            Edge::new(Edge::make_id("q", "a"), "q", "a", RelationType::References),
+        ];
+
+        let dashboard = Dashboard::compute(&nodes, &edges, 0, None, now, &DashboardConfig::default());
+        assert_eq!(
+            dashboard.counts,
+            HealthCounts { notes: 4, edges: 2, orphans: 1, dangling: 0, questions: 2, open_questions: 1, stale: 1 }
+        );
+        assert_eq!(dashboard.top_tags[0], TagCount { tag: "x".to_string(), count: 3 });
+        assert_eq!(dashboard.top_tags.len(), 3);
+        assert!(!dashboard.checks[0].ok);
+        assert!(dashboard.checks[2].ok);
+        assert!(dashboard.score < 100);
+    }
+}
@@ -0,0 +1,243 @@
+//! 边权重衰减模块
+//!
+//! 为 `memexia graph decay` 按边的年龄重新计算有效强度：
+//!
+//! ```text
+//! effectiveStrength = strength * 0.5 ^ (age_days / half_life_days)
+//! ```
+//!
+//! 结果写入独立的 `memexia:effectiveStrength` 属性，用户设置的 `strength` 保持不变；
+//! 排序和加权寻路通过 [`Edge::weight`] 优先使用有效强度
+
+use crate::error::{MemexiaError, Result};
+use crate::storage::GraphStorage;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// 分布统计的分桶数（每桶宽 0.2）
+const BUCKETS: usize = 5;
+
+/// 比较有效强度时的精度（写入前四舍五入到 4 位小数）
+const PRECISION: f64 = 10_000.0;
+
+/// 强度分布
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StrengthDistribution {
+    /// 平均值
+    pub mean: f64,
+    /// 各区间的边数：`[0, 0.2)`、`[0.2, 0.4)` … `[0.8, 1.0]`
+    pub buckets: [usize; BUCKETS],
+}
+
+impl StrengthDistribution {
+    /// 统计强度分布
+    pub fn from_values(values: &[f64]) -> Self {
+        let mut buckets = [0; BUCKETS];
+        for &value in values {
+            let index = ((value * BUCKETS as f64) as usize).min(BUCKETS - 1);
+            buckets[index] += 1;
+        }
+        let mean = if values.is_empty() {
+            0.0
+        } else {
+            values.iter().sum::<f64>() / values.len() as f64
+        };
+        Self { mean, buckets }
+    }
+
+    /// 区间标签，如 `0.2-0.4`
+    pub fn bucket_label(index: usize) -> String {
+        let width = 1.0 / BUCKETS as f64;
+        format!("{:.1}-{:.1}", index as f64 * width, (index + 1) as f64 * width)
+    }
+}
+
+/// 衰减报告
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DecayReport {
+    /// 边总数
+    pub edges: usize,
+    /// 有效强度发生变化的边数
+    pub changed: usize,
+    /// 衰减前的强度分布（已有有效强度时按有效强度统计）
+    pub before: StrengthDistribution,
+    /// 衰减后的有效强度分布
+    pub after: StrengthDistribution,
+}
+
+/// 计算衰减后的强度
+///
+/// # Arguments
+///
+/// * `strength` - 原始强度
+/// * `created_at` - 边的创建时间
+/// * `now` - 当前时间（早于创建时间时按年龄 0 处理）
+/// * `half_life_days` - 半衰期（天）
+pub fn decayed_strength(strength: f64, created_at: DateTime<Utc>, now: DateTime<Utc>, half_life_days: f64) -> f64 {
+    let age_days = (now - created_at).num_seconds().max(0) as f64 / 86_400.0;
+    let value = strength * 0.5_f64.powf(age_days / half_life_days);
+    (value * PRECISION).round() / PRECISION
+}
+
+/// 重新计算所有边的有效强度
+///
+/// # Arguments
+///
+/// * `graph` - 图存储
+/// * `half_life_days` - 半衰期（天），必须大于 0
+/// * `now` - 当前时间
+/// * `dry_run` - 只计算报告，不写入
+pub fn decay(graph: &dyn GraphStorage, half_life_days: f64, now: DateTime<Utc>, dry_run: bool) -> Result<DecayReport> {
+    if !(half_life_days.is_finite() && half_life_days > 0.0) {
+        return Err(MemexiaError::Validation(format!(
+            "Half-life must be a positive number of days, got {}",
+            half_life_days
+        )));
+    }
+
+    let edges = graph.get_all_edges()?;
+    let mut before = Vec::with_capacity(edges.len());
+    let mut after = Vec::with_capacity(edges.len());
+    let mut changed = 0;
+
+    for mut edge in edges.into_iter() {
+        let effective = decayed_strength(edge.strength, edge.created_at, now, half_life_days);
+        before.push(edge.weight());
+        after.push(effective);
+
+        if edge.effective_strength != Some(effective) {
+            changed += 1;
+            if !dry_run {
+                edge.effective_strength = Some(effective);
+                graph.add_edge(&edge)?;
+            }
+        }
+    }
+
+    Ok(DecayReport {
+        edges: before.len(),
+        changed,
+        before: StrengthDistribution::from_values(&before),
+        after: StrengthDistribution::from_values(&after),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{Edge, Node, NodeType, RelationType, Storage};
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_decayed_strength_math() {
+        let created = at("2024-01-01T00:00:00Z");
+
+        assert_eq!(decayed_strength(0.8, created, created, 30.0), 0.8);
+        assert_eq!(decayed_strength(0.8, created, created + Duration::days(30), 30.0), 0.4);
+        assert_eq!(decayed_strength(1.0, created, created + Duration::days(60), 30.0), 0.25);
+        assert_eq!(decayed_strength(0.8, created, created + Duration::days(15), 30.0), 0.5657);
+        // 创建时间晚于当前时间时不增长
+        assert_eq!(decayed_strength(0.5, created, created - Duration::days(10), 30.0), 0.5);
+    }
+
+    #[test]
+    fn test_decay_is_non_destructive() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        let graph = storage.graph();
+
+        for id in ["urn:memexia:file:a.md", "urn:memexia:file:b.md", "urn:memexia:file:c.md"] {
+            graph.add_node(&Node::new(id, NodeType::Concept, id)).unwrap();
+        }
+        let old = Edge::builder("urn:memexia:file:a.md", "urn:memexia:file:b.md", RelationType::Supports)
+            .strength(0.8)
+            .created_at(at("2023-01-01T00:00:00Z"))
+            .build()
+            .unwrap();
+        let fresh = Edge::builder("urn:memexia:file:a.md", "urn:memexia:file:c.md", RelationType::RelatedTo)
+            .created_at(at("2024-01-01T00:00:00Z"))
+            .build()
+            .unwrap();
+        graph.add_edge(&old).unwrap();
+        graph.add_edge(&fresh).unwrap();
+
+        let now = at("2024-01-01T00:00:00Z");
+
+        // dry-run 只报告
+        let report = decay(graph, 365.0, now, true).unwrap();
+        assert_eq!((report.edges, report.changed), (2, 2));
+        assert!(graph.require_edge(&old.id).unwrap().effective_strength.is_none());
+
+        let report = decay(graph, 365.0, now, false).unwrap();
+        assert_eq!(report.changed, 2);
+        assert_eq!(report.before.mean, 0.9);
+        assert_eq!(report.after.buckets, [0, 0, 1, 0, 1]);
+
+        // 原始强度和创建时间保持不变，有效强度单独保存
+        let stored = graph.require_edge(&old.id).unwrap();
+        assert_eq!(stored.strength, 0.8);
+        assert_eq!(stored.created_at, old.created_at);
+        assert_eq!(stored.effective_strength, Some(0.4));
+        assert_eq!(stored.weight(), 0.4);
+        assert_eq!(graph.require_edge(&fresh.id).unwrap().weight(), 1.0);
+
+        // 再次运行没有变化
+        let report = decay(graph, 365.0, now, false).unwrap();
+        assert_eq!(report.changed, 0);
+        assert_eq!(report.before, report.after);
+
+        assert!(matches!(decay(graph, 0.0, now, true), Err(MemexiaError::Validation(_))));
+    }
+
+    #[test]
+    fn test_readd_keeps_earliest_created_at() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        let graph = storage.graph();
+
+        let edge = Edge::builder("urn:memexia:file:a.md", "urn:memexia:file:b.md", RelationType::Supports)
+            .created_at(at("2020-05-01T00:00:00Z"))
+            .build()
+            .unwrap();
+        graph.add_edge(&edge).unwrap();
+
+        // 重新索引会用当前时间重建边
+        let reindexed = Edge::builder("urn:memexia:file:a.md", "urn:memexia:file:b.md", RelationType::Supports)
+            .build()
+            .unwrap();
+        graph.add_edge(&reindexed).unwrap();
+
+        assert_eq!(graph.require_edge(&edge.id).unwrap().created_at, edge.created_at);
+    }
+
+    #[test]
+    fn test_find_path_prefers_stronger_edges() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        let graph = storage.graph();
+
+        let id = |name: &str| format!("urn:memexia:file:{}.md", name);
+        let edge = |from: &str, to: &str, effective: Option<f64>| {
+            let mut edge = Edge::builder(id(from), id(to), RelationType::RelatedTo).build().unwrap();
+            edge.effective_strength = effective;
+            edge
+        };
+
+        // a -> b -> d 与 a -> c -> d 等长，a -> b 已衰减
+        for edge in [
+            edge("a", "b", Some(0.1)),
+            edge("a", "c", Some(0.9)),
+            edge("b", "d", None),
+            edge("c", "d", None),
+        ] {
+            graph.add_edge(&edge).unwrap();
+        }
+
+        assert_eq!(graph.find_path(&id("a"), &id("d")).unwrap().unwrap(), vec![id("a"), id("c"), id("d")]);
+    }
+}
@@ -0,0 +1,438 @@
+//! 推导树模块（`memexia derivation tree`）
+//!
+//! 以某个节点为根，沿推导记录展开推导 DAG：
+//!
+//! - `up`：推导来源（父节点、祖父节点……）
+//! - `down`：由它推导出的节点
+//! - `both`：两者，来源在前
+//!
+//! 文本输出每层缩进两格，`<-` 表示来源、`->` 表示推导出的节点，后面是推导记录的提交短哈希和时间。
+//! 同一节点经多条路径可达（菱形）时在每条路径下各出现一次；出现在自身祖先路径上的节点标记为
+//! `(cycle)` 且不再展开。超过深度限制仍有推导记录的节点以 `...` 结尾
+//!
+//! 展开层数和树中节点总数受 `[history.traversal]` 限制（见 [`traversal`](crate::vcs::traversal)），
+//! 触及限制和发现的环随树一起返回，由调用方提示
+//!
+//! DOT 输出沿用 `memexia graph dot` 的节点样式（见 [`dot`]），推导边为紫色虚线，
+//! 树中节点之间的内容链接为普通实线并标注关系类型
+
+use crate::error::Result;
+use crate::storage::{dot, GraphStorage, NodeType};
+use crate::vcs::{DerivationEntry, DerivationIndex, GraphHistory, Traversal, TraversalLimits, TraversalOutcome};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+
+/// 展开方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// 推导来源
+    Up,
+    /// 推导出的节点
+    Down,
+    /// 两者
+    Both,
+}
+
+impl Direction {
+    /// 解析方向（`up` / `down` / `both`）
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "up" => Some(Direction::Up),
+            "down" => Some(Direction::Down),
+            "both" => Some(Direction::Both),
+            _ => None,
+        }
+    }
+}
+
+/// 推导树中的一个节点
+#[derive(Debug, Clone)]
+pub struct TreeNode {
+    /// 节点 ID
+    pub id: String,
+    /// 标题（图中没有该节点时为 ID）
+    pub title: String,
+    /// 节点类型（图中没有该节点时为 None）
+    pub node_type: Option<NodeType>,
+    /// 与上一层节点之间的推导记录（根节点为 None）
+    pub derivation: Option<DerivationEntry>,
+    /// 是否为来源方向（`<-`）
+    pub upstream: bool,
+    /// 已出现在自身祖先路径上，不再展开
+    pub cycle: bool,
+    /// 因深度限制未展开，但仍有推导记录
+    pub truncated: bool,
+    /// 下一层节点
+    pub children: Vec<TreeNode>,
+}
+
+/// 构建推导树
+///
+/// 展开过程使用显式栈，不依赖递归深度；展开层数和树中节点总数受 `limits` 限制，
+/// 遇到的环记录在返回的 [`TraversalOutcome`] 中
+///
+/// # Arguments
+///
+/// * `history` - 图历史（推导记录）
+/// * `graph` - 图存储（解析标题和类型）
+/// * `root_id` - 根节点 ID
+/// * `direction` - 展开方向
+/// * `depth` - 最大展开层数（None 时使用 `limits.max_depth`；显式指定时截断不视为触及限制）
+/// * `limits` - 遍历限制
+pub fn build(
+    history: &GraphHistory,
+    graph: &dyn GraphStorage,
+    root_id: &str,
+    direction: Direction,
+    depth: Option<usize>,
+    limits: &TraversalLimits,
+) -> Result<Traversal<TreeNode>> {
+    let index = history.derivation_index()?;
+    let mut expansion = Expansion {
+        index: &index,
+        graph,
+        max_depth: depth.unwrap_or(limits.max_depth),
+        explicit_depth: depth.is_some(),
+        budget: limits.max_nodes,
+        outcome: TraversalOutcome::default(),
+    };
+    let mut root = tree_node(graph, root_id, None, false)?;
+    if direction != Direction::Down {
+        let up = expansion.expand(root_id, true)?;
+        root.children.extend(up.children);
+        root.truncated |= up.truncated;
+    }
+    if direction != Direction::Up {
+        let down = expansion.expand(root_id, false)?;
+        root.children.extend(down.children);
+        root.truncated |= down.truncated;
+    }
+    Ok(Traversal { value: root, outcome: expansion.outcome })
+}
+
+/// 展开状态（两个方向共享节点数上限）
+struct Expansion<'a> {
+    index: &'a DerivationIndex,
+    graph: &'a dyn GraphStorage,
+    max_depth: usize,
+    explicit_depth: bool,
+    /// 剩余可加入树中的节点数
+    budget: usize,
+    outcome: TraversalOutcome,
+}
+
+impl Expansion<'_> {
+    /// 展开一个方向
+    ///
+    /// 节点先按深度优先顺序平铺（记录父节点下标，子节点总在父节点之后），最后自底向上组装成树
+    fn expand(&mut self, root_id: &str, upstream: bool) -> Result<TreeNode> {
+        let mut nodes = vec![tree_node(self.graph, root_id, None, false)?];
+        let mut parents = vec![0];
+        // 当前路径上的节点 ID 及其在路径中的位置，用于识别环
+        let mut path = vec![root_id.to_string()];
+        let mut on_path: HashMap<String, usize> = HashMap::from([(root_id.to_string(), 0)]);
+        // 路径上每个节点尚未展开的推导记录（倒序，便于弹出）
+        let mut stack: Vec<(usize, Vec<DerivationEntry>)> = Vec::new();
+
+        let entries = related(self.index, root_id, upstream);
+        if !entries.is_empty() {
+            if self.max_depth == 0 {
+                self.truncate(&mut nodes[0]);
+            } else {
+                stack.push((0, entries.into_iter().rev().collect()));
+            }
+        }
+
+        while let Some((parent, pending)) = stack.last_mut() {
+            let parent = *parent;
+            let Some(entry) = pending.pop() else {
+                stack.pop();
+                if let Some(id) = path.pop() {
+                    on_path.remove(&id);
+                }
+                continue;
+            };
+            if self.budget == 0 {
+                self.outcome.node_limit_hit = true;
+                break;
+            }
+            self.budget -= 1;
+
+            let id = if upstream { entry.parent_id.clone() } else { entry.child_id.clone() };
+            let mut child = tree_node(self.graph, &id, Some(entry), upstream)?;
+            let index = nodes.len();
+            if let Some(&position) = on_path.get(&id) {
+                child.cycle = true;
+                self.outcome.record_cycle(path[position..].to_vec());
+            } else {
+                let entries = related(self.index, &id, upstream);
+                if !entries.is_empty() {
+                    if path.len() >= self.max_depth {
+                        self.truncate(&mut child);
+                    } else {
+                        on_path.insert(id.clone(), path.len());
+                        path.push(id);
+                        stack.push((index, entries.into_iter().rev().collect()));
+                    }
+                }
+            }
+            nodes.push(child);
+            parents.push(parent);
+        }
+
+        let mut children: Vec<Vec<TreeNode>> = (0..nodes.len()).map(|_| Vec::new()).collect();
+        while let Some(mut node) = nodes.pop() {
+            let index = nodes.len();
+            node.children = std::mem::take(&mut children[index]).into_iter().rev().collect();
+            if index == 0 {
+                return Ok(node);
+            }
+            children[parents[index]].push(node);
+        }
+        unreachable!("推导树至少包含根节点")
+    }
+
+    /// 因深度限制不再展开
+    fn truncate(&mut self, node: &mut TreeNode) {
+        node.truncated = true;
+        if !self.explicit_depth {
+            self.outcome.depth_limit_hit = true;
+        }
+    }
+}
+
+/// 一个节点的推导来源或推导出的节点（同一对节点的重复记录只保留最早的一条）
+fn related(index: &DerivationIndex, id: &str, upstream: bool) -> Vec<DerivationEntry> {
+    let mut entries = if upstream { index.parents(id) } else { index.children(id) }.to_vec();
+    entries.sort_by_key(|entry| entry.timestamp);
+    let mut seen = BTreeSet::new();
+    entries.retain(|entry| seen.insert((entry.parent_id.clone(), entry.child_id.clone())));
+    entries
+}
+
+fn tree_node(graph: &dyn GraphStorage, id: &str, derivation: Option<DerivationEntry>, upstream: bool) -> Result<TreeNode> {
+    let node = graph.get_node(id)?;
+    Ok(TreeNode {
+        id: id.to_string(),
+        title: node.as_ref().map_or_else(|| id.to_string(), |n| n.title.clone()),
+        node_type: node.map(|n| n.node_type),
+        derivation,
+        upstream,
+        cycle: false,
+        truncated: false,
+        children: Vec::new(),
+    })
+}
+
+/// 渲染为缩进文本
+pub fn render_text(root: &TreeNode) -> String {
+    let mut out = String::new();
+    let mut stack = vec![(root, 0)];
+    while let Some((node, level)) = stack.pop() {
+        write_line(&mut out, node, level);
+        stack.extend(node.children.iter().rev().map(|child| (child, level + 1)));
+    }
+    out
+}
+
+fn write_line(out: &mut String, node: &TreeNode, level: usize) {
+    let _ = write!(out, "{}", "  ".repeat(level));
+    if level > 0 {
+        out.push_str(if node.upstream { "<- " } else { "-> " });
+    }
+    out.push_str(&node.title);
+    if let Some(entry) = &node.derivation {
+        if !entry.commit_hash.is_empty() {
+            let _ = write!(out, "  {}", &entry.commit_hash[..entry.commit_hash.len().min(7)]);
+        }
+        let _ = write!(out, "  {}", format_time(entry.timestamp));
+    }
+    if node.cycle {
+        out.push_str("  (cycle)");
+    }
+    if node.truncated {
+        out.push_str("  ...");
+    }
+    out.push('\n');
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%d %H:%M").to_string()
+}
+
+/// 渲染为 Graphviz DOT
+///
+/// 推导边从来源指向推导出的节点；树中节点之间的内容链接一并输出
+pub fn render_dot(root: &TreeNode, graph: &dyn GraphStorage) -> Result<String> {
+    let mut nodes: BTreeMap<&str, &TreeNode> = BTreeMap::new();
+    let mut derivations: BTreeSet<(&str, &str)> = BTreeSet::new();
+    collect(root, &mut nodes, &mut derivations);
+
+    let mut out = String::from("digraph memexia_derivations {\n");
+    let _ = writeln!(out, "  {}", dot::NODE_DEFAULTS);
+    for node in nodes.values() {
+        let color = node.node_type.as_ref().map_or("white", dot::fill_color);
+        let _ = writeln!(out, "  {} [label={}, fillcolor={}];", dot::quote(&node.id), dot::quote(&node.title), color);
+    }
+    out.push('\n');
+    for (parent, child) in &derivations {
+        let _ = writeln!(
+            out,
+            "  {} -> {} [label=\"derived\", style=dashed, color=purple];",
+            dot::quote(parent),
+            dot::quote(child)
+        );
+    }
+    for id in nodes.keys() {
+        for edge in graph.get_edges_by_source(id)? {
+            if nodes.contains_key(edge.to.as_str()) {
+                let _ = writeln!(
+                    out,
+                    "  {} -> {} [label={}];",
+                    dot::quote(&edge.from),
+                    dot::quote(&edge.to),
+                    dot::quote(&edge.relation.to_string())
+                );
+            }
+        }
+    }
+    out.push_str("}\n");
+    Ok(out)
+}
+
+fn collect<'a>(root: &'a TreeNode, nodes: &mut BTreeMap<&'a str, &'a TreeNode>, derivations: &mut BTreeSet<(&'a str, &'a str)>) {
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        nodes.entry(node.id.as_str()).or_insert(node);
+        if let Some(entry) = &node.derivation {
+            derivations.insert((entry.parent_id.as_str(), entry.child_id.as_str()));
+        }
+        stack.extend(node.children.iter().rev());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{ids, Edge, Node, RelationType, Storage};
+    use tempfile::TempDir;
+
+    fn id(name: &str) -> String {
+        ids::file_id(&format!("{}.md", name))
+    }
+
+    /// a 推导出 b、c，b 和 c 共同推导出 d（菱形）；d 不在图中
+    fn diamond() -> (TempDir, Storage, GraphHistory) {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        for (name, title, node_type) in [
+            ("a", "Idea A", NodeType::Concept),
+            ("b", "Idea B", NodeType::Question),
+            ("c", "Idea C", NodeType::Concept),
+        ] {
+            storage.graph().add_node(&Node::new(id(name), node_type, title)).unwrap();
+        }
+        let edge = Edge::new(Edge::make_id(&id("a"), &id("b")), id("a"), id("b"), RelationType::Supports);
+        storage.graph().add_edge(&edge).unwrap();
+        let history = GraphHistory::init(temp.path()).unwrap();
+        history.record_derivation(&id("b"), &id("a"), "1111111aaaa").unwrap();
+        history.record_derivation(&id("c"), &id("a"), "2222222bbbb").unwrap();
+        history.record_derivation(&id("d"), &id("b"), "3333333cccc").unwrap();
+        history.record_derivation(&id("d"), &id("c"), "3333333cccc").unwrap();
+        (temp, storage, history)
+    }
+
+    /// 去掉时间列，便于比较
+    fn shape(text: &str) -> Vec<String> {
+        text.lines()
+            .map(|line| {
+                let mut parts: Vec<&str> = line.split("  ").collect();
+                parts.retain(|part| !part.starts_with("20"));
+                parts.join("  ")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_text_tree() {
+        let (_temp, storage, history) = diamond();
+        let d = id("d");
+        let tree = build(&history, storage.graph(), &id("a"), Direction::Down, None, &TraversalLimits::default()).unwrap().value;
+        assert_eq!(
+            shape(&render_text(&tree)),
+            [
+                "Idea A".to_string(),
+                "  -> Idea B  1111111".to_string(),
+                format!("    -> {}  3333333", d),
+                "  -> Idea C  2222222".to_string(),
+                format!("    -> {}  3333333", d),
+            ]
+        );
+
+        let tree = build(&history, storage.graph(), &d, Direction::Up, Some(1), &TraversalLimits::default()).unwrap().value;
+        assert_eq!(
+            shape(&render_text(&tree)),
+            [d.clone(), "  <- Idea B  3333333  ...".to_string(), "  <- Idea C  3333333  ...".to_string()]
+        );
+
+        let tree = build(&history, storage.graph(), &id("b"), Direction::Both, None, &TraversalLimits::default()).unwrap().value;
+        assert_eq!(
+            shape(&render_text(&tree)),
+            ["Idea B".to_string(), "  <- Idea A  1111111".to_string(), format!("  -> {}  3333333", d)]
+        );
+    }
+
+    #[test]
+    fn test_cycle_marker() {
+        let (_temp, storage, history) = diamond();
+        history.record_derivation(&id("a"), &id("d"), "4444444dddd").unwrap();
+        let traversal = build(&history, storage.graph(), &id("a"), Direction::Down, None, &TraversalLimits::default()).unwrap();
+        assert_eq!(traversal.outcome.cycles, vec![vec![id("a"), id("b"), id("d")], vec![id("a"), id("c"), id("d")]]);
+        let tree = traversal.value;
+        let text = render_text(&tree);
+        assert!(
+            text.lines().any(|line| line.starts_with("      -> Idea A") && line.ends_with("(cycle)")),
+            "{}",
+            text
+        );
+    }
+
+    #[test]
+    fn test_limits() {
+        let (_temp, storage, history) = diamond();
+        let explicit = build(&history, storage.graph(), &id("a"), Direction::Down, Some(1), &TraversalLimits::default()).unwrap();
+        assert!(explicit.outcome.is_complete());
+
+        let limits = TraversalLimits::default().with_max_depth(1);
+        let limited = build(&history, storage.graph(), &id("a"), Direction::Down, None, &limits).unwrap();
+        assert!(limited.outcome.depth_limit_hit);
+        assert!(limited.value.children.iter().all(|child| child.truncated));
+
+        let limits = TraversalLimits { max_nodes: 3, ..TraversalLimits::default() };
+        let capped = build(&history, storage.graph(), &id("a"), Direction::Down, None, &limits).unwrap();
+        assert!(capped.outcome.node_limit_hit);
+        assert_eq!(render_text(&capped.value).lines().count(), 4);
+    }
+
+    #[test]
+    fn test_dot_edges() {
+        let (_temp, storage, history) = diamond();
+        let tree = build(&history, storage.graph(), &id("a"), Direction::Down, None, &TraversalLimits::default()).unwrap().value;
+        let dot = render_dot(&tree, storage.graph()).unwrap();
+        let edges: BTreeSet<&str> = dot.lines().filter(|line| line.contains(" -> ")).map(str::trim).collect();
+        let derived = |from: &str, to: &str| {
+            format!("\"{}\" -> \"{}\" [label=\"derived\", style=dashed, color=purple];", id(from), id(to))
+        };
+        let expected = [
+            derived("a", "b"),
+            derived("a", "c"),
+            derived("b", "d"),
+            derived("c", "d"),
+            format!("\"{}\" -> \"{}\" [label=\"Supports\"];", id("a"), id("b")),
+        ];
+        assert_eq!(edges, expected.iter().map(String::as_str).collect());
+        assert!(dot.contains(&format!("\"{}\" [label=\"Idea B\", fillcolor=lightyellow];", id("b"))));
+        assert!(dot.contains(&format!("\"{}\" [label=\"{}\", fillcolor=white];", id("d"), id("d"))));
+    }
+}
@@ -0,0 +1,189 @@
+//! 仓库健康检查模块（`memexia doctor`）
+//!
+//! 目前检查旧版本遗留的未编码文件节点 ID：早期部分代码路径直接把文件名拼进 ID，
+//! 同一篇含中文、空格、`%` 或 `#` 的笔记可能对应多个节点。`memexia doctor --fix`
+//! 把这些 ID 改写为 [`ids::file_id`] 的规范形式，并同步改写相关的边
+//!
+//! 另外提示冗余的逆关系边（`A Contains B` 与 `B PartOf A` 同时存在）：两条边表达同一件事，
+//! 查询时已通过 [`GraphStorage::get_related`] 双向匹配，保留一条即可。哪一条是笔记作者
+//! 的本意无法自动判断，因此只提示不修复
+//!
+//! 端点节点不存在的悬空边由 [`GraphStorage::find_dangling_edges`] 查找，
+//! `memexia doctor --fix-dangling` 按所选方式修复
+
+use crate::error::Result;
+use crate::storage::{ids, Edge, GraphStorage};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// 需要迁移的旧版 ID
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LegacyId {
+    /// 旧 ID
+    pub old: String,
+    /// 规范化后的 ID
+    pub new: String,
+}
+
+/// 迁移结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationSummary {
+    /// 改写的节点数（与已有规范节点合并的也计入）
+    pub nodes: usize,
+    /// 改写的边数
+    pub edges: usize,
+}
+
+/// 查找图中未编码的旧版文件节点 ID（包括只作为边端点出现的 ID）
+pub fn find_legacy_ids(graph: &dyn GraphStorage) -> Result<Vec<LegacyId>> {
+    let mut candidates = BTreeSet::new();
+    for node in graph.list_nodes_filtered(true)? {
+        candidates.insert(node.id);
+    }
+    for edge in graph.list_edges_filtered(true)? {
+        candidates.insert(edge.from);
+        candidates.insert(edge.to);
+    }
+
+    Ok(candidates
+        .into_iter()
+        .filter(|id| ids::is_legacy_file_id(id))
+        .map(|old| LegacyId { new: ids::canonical_file_id(&old), old })
+        .collect())
+}
+
+/// 把旧版 ID 改写为规范形式
+///
+/// 规范 ID 的节点已存在时保留已有节点，只删除旧节点；边的端点和 ID 一并改写
+pub fn migrate_legacy_ids(graph: &dyn GraphStorage, legacy: &[LegacyId]) -> Result<MigrationSummary> {
+    let renames: BTreeMap<&str, &str> = legacy.iter().map(|l| (l.old.as_str(), l.new.as_str())).collect();
+    let mut summary = MigrationSummary::default();
+
+    for edge in graph.list_edges_filtered(true)? {
+        let from = renames.get(edge.from.as_str()).copied();
+        let to = renames.get(edge.to.as_str()).copied();
+        if from.is_none() && to.is_none() {
+            continue;
+        }
+
+        let mut rewritten = edge.clone();
+        // 边 ID 内嵌了未编码的端点，整体补全编码即与新端点一致
+        rewritten.id = ids::canonical_component(&edge.id);
+        if let Some(new) = from {
+            rewritten.from = new.to_string();
+        }
+        if let Some(new) = to {
+            rewritten.to = new.to_string();
+        }
+        graph.delete_edge(&edge.id)?;
+        graph.add_edge(&rewritten)?;
+        summary.edges += 1;
+    }
+
+    for LegacyId { old, new } in legacy {
+        let Some(mut node) = graph.get_node(old)? else {
+            continue;
+        };
+        if !graph.node_exists(new)? {
+            node.id = new.clone();
+            graph.add_node(&node)?;
+        }
+        graph.delete_node(old)?;
+        summary.nodes += 1;
+    }
+    Ok(summary)
+}
+
+/// 查找互为逆关系的边对（如 `A Contains B` 与 `B PartOf A`）
+///
+/// # Returns
+///
+/// 每对边只出现一次，按第一条边的 ID 排序
+pub fn find_redundant_inverses(graph: &dyn GraphStorage) -> Result<Vec<(Edge, Edge)>> {
+    let mut pairs = Vec::new();
+    for edge in graph.list_edges()? {
+        let Some(inverse) = edge.relation.inverse() else {
+            continue;
+        };
+        let reverse_id = Edge::make_id(&edge.to, &edge.from);
+        if edge.id >= reverse_id {
+            continue;
+        }
+        if let Some(reverse) = graph.get_edge(&reverse_id)?.filter(|reverse| reverse.relation == inverse) {
+            pairs.push((edge, reverse));
+        }
+    }
+    pairs.sort_by(|a, b| a.0.id.cmp(&b.0.id));
+    Ok(pairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{Edge, Node, NodeType, RelationType, Storage};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_and_migrate_legacy_ids() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let graph = storage.graph();
+
+        // 旧版本写入的未编码节点，以及已存在的规范节点
+        let legacy_a = "urn:memexia:file:自由意志.md";
+        let legacy_b = "urn:memexia:file:决定论";
+        graph.add_node(&Node::new(legacy_a, NodeType::Concept, "自由意志")).unwrap();
+        graph.add_node(&Node::new(legacy_b, NodeType::Concept, "决定论")).unwrap();
+        graph.add_node(&Node::new(ids::file_id("决定论"), NodeType::Concept, "决定论")).unwrap();
+        graph.add_edge(&Edge::from_link(legacy_a, "决定论|矛盾")).unwrap();
+        graph
+            .add_edge(&Edge::new(Edge::make_id(legacy_a, legacy_b), legacy_a, legacy_b, RelationType::Supports))
+            .unwrap();
+
+        let legacy = find_legacy_ids(graph).unwrap();
+        let olds: Vec<_> = legacy.iter().map(|l| l.old.as_str()).collect();
+        assert_eq!(olds.len(), 2);
+        assert!(olds.contains(&legacy_a) && olds.contains(&legacy_b));
+
+        let summary = migrate_legacy_ids(graph, &legacy).unwrap();
+        assert_eq!(summary, MigrationSummary { nodes: 2, edges: 2 });
+        assert!(find_legacy_ids(graph).unwrap().is_empty());
+
+        let a = ids::file_id("自由意志.md");
+        let b = ids::file_id("决定论");
+        assert!(graph.node_exists(&a).unwrap());
+        assert!(!graph.node_exists(legacy_a).unwrap());
+        assert_eq!(graph.list_nodes().unwrap().len(), 2);
+
+        // 两条旧边指向同一对规范节点，合并为一条
+        let edges = graph.list_edges().unwrap();
+        assert_eq!(edges.len(), 1);
+        for edge in &edges {
+            assert_eq!(edge.from, a);
+            assert_eq!(edge.to, b);
+            assert!(edge.id.is_ascii(), "{}", edge.id);
+            assert!(graph.edge_exists(&edge.id).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_find_redundant_inverses() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let graph = storage.graph();
+
+        let (book, chapter, essay) = (ids::file_id("book.md"), ids::file_id("chapter.md"), ids::file_id("essay.md"));
+        for (from, to, relation) in [
+            (&book, &chapter, RelationType::Contains),
+            (&chapter, &book, RelationType::PartOf),
+            (&book, &essay, RelationType::Contains),
+            (&essay, &book, RelationType::Supports),
+        ] {
+            graph.add_edge(&Edge::builder(from, to, relation).build().unwrap()).unwrap();
+        }
+
+        let pairs = find_redundant_inverses(graph).unwrap();
+        assert_eq!(pairs.len(), 1);
+        let (first, second) = &pairs[0];
+        assert_eq!((&first.from, &second.from), (&book, &chapter));
+    }
+}
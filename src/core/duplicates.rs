@@ -0,0 +1,182 @@
+//! 重复笔记检测模块
+//!
+//! 为 `memexia report duplicates` 查找疑似重复的笔记对：
+//!
+//! - 标题归一化后完全相同（忽略大小写和标点）
+//! - 可选：正文 3-shingle Jaccard 相似度不低于阈值（见 [`crate::core::similarity`]）
+//!
+//! 只比较已索引的笔记（带字数的节点），链接产生的占位节点不参与
+
+use crate::core::review::node_path;
+use crate::core::similarity::{content_shingles, jaccard, normalize_title};
+use crate::error::{MemexiaError, Result};
+use crate::storage::{GraphStorage, Node};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// 默认正文相似度阈值
+pub const DEFAULT_CONTENT_THRESHOLD: f64 = 0.8;
+
+/// 判定为重复的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateReason {
+    /// 归一化标题相同
+    Title,
+    /// 正文相似度超过阈值
+    Content,
+}
+
+/// 疑似重复的笔记对
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicatePair {
+    /// 第一篇笔记的节点 ID（按 ID 排序较小者）
+    pub a: String,
+    /// 第二篇笔记的节点 ID
+    pub b: String,
+    /// 相似度分数（标题相同时为 1.0）
+    pub score: f64,
+    /// 判定原因
+    pub reason: DuplicateReason,
+}
+
+impl DuplicatePair {
+    /// 用于展示的笔记位置：文件路径，Zettel ID 节点使用原始 ID
+    pub fn display(id: &str) -> String {
+        node_path(id).unwrap_or_else(|| id.to_string())
+    }
+}
+
+/// 查找疑似重复的笔记对
+///
+/// # Arguments
+///
+/// * `graph` - 图存储
+/// * `threshold` - 正文相似度阈值（`0.0..=1.0`）；为 None 时只比较标题
+///
+/// # Returns
+///
+/// 按分数降序排列的笔记对；同一对只出现一次，标题相同优先于正文相似
+pub fn find_duplicates(graph: &dyn GraphStorage, threshold: Option<f64>) -> Result<Vec<DuplicatePair>> {
+    if threshold.is_some_and(|t| !(0.0..=1.0).contains(&t)) {
+        return Err(MemexiaError::Validation(format!(
+            "Similarity threshold must be between 0 and 1, got {}",
+            threshold.unwrap_or_default()
+        )));
+    }
+
+    let mut notes: Vec<Node> = graph
+        .get_all_nodes()?
+        .into_iter()
+        .filter(|node| node.word_count().is_some())
+        .collect();
+    notes.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let mut pairs = Vec::new();
+
+    // 标题相同
+    let mut by_title: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, node) in notes.iter().enumerate() {
+        let title = normalize_title(&node.title);
+        if !title.is_empty() {
+            by_title.entry(title).or_default().push(i);
+        }
+    }
+    for group in by_title.values() {
+        for (n, &i) in group.iter().enumerate() {
+            for &j in &group[n + 1..] {
+                pairs.push(pair(&notes[i], &notes[j], 1.0, DuplicateReason::Title));
+            }
+        }
+    }
+
+    // 正文相似
+    if let Some(threshold) = threshold {
+        let shingles: Vec<_> = notes
+            .iter()
+            .map(|node| content_shingles(node.content.as_deref().unwrap_or("")))
+            .collect();
+        for i in 0..notes.len() {
+            for j in i + 1..notes.len() {
+                if normalize_title(&notes[i].title) == normalize_title(&notes[j].title) {
+                    continue;
+                }
+                let score = jaccard(&shingles[i], &shingles[j]);
+                if score > 0.0 && score >= threshold {
+                    pairs.push(pair(&notes[i], &notes[j], score, DuplicateReason::Content));
+                }
+            }
+        }
+    }
+
+    pairs.sort_by(|x, y| y.score.total_cmp(&x.score).then_with(|| (&x.a, &x.b).cmp(&(&y.a, &y.b))));
+    Ok(pairs)
+}
+
+fn pair(a: &Node, b: &Node, score: f64, reason: DuplicateReason) -> DuplicatePair {
+    DuplicatePair {
+        a: a.id.clone(),
+        b: b.id.clone(),
+        score,
+        reason,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{NodeType, Storage, WORD_COUNT_KEY};
+    use tempfile::TempDir;
+
+    fn note(graph: &dyn GraphStorage, name: &str, title: &str, content: &str) -> String {
+        let id = format!("urn:memexia:file:{}.md", name);
+        let mut node = Node::new(&id, NodeType::Concept, title);
+        node.content = Some(content.to_string());
+        node.metadata.insert(WORD_COUNT_KEY.to_string(), content.split_whitespace().count().into());
+        graph.add_node(&node).unwrap();
+        id
+    }
+
+    #[test]
+    fn test_find_duplicates_fixture() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        let graph = storage.graph();
+
+        let text = "spaced repetition schedules reviews at increasing intervals so that memories are refreshed just before they fade";
+        let a = note(graph, "a", "Spaced Repetition", text);
+        let b = note(graph, "b", "spaced-repetition!", "a short stub");
+        // 真正的重复：正文只改动了结尾一个词
+        let c = note(graph, "c", "SRS", &text.replace("fade", "vanish"));
+        // 相似但低于阈值：只共享前半句
+        let d = note(
+            graph,
+            "d",
+            "Review Intervals",
+            "spaced repetition schedules reviews at increasing intervals while interleaving mixes different topics in one session",
+        );
+        // 占位节点不参与
+        graph
+            .add_node(&Node::new("urn:memexia:file:e.md", NodeType::Concept, "Spaced Repetition"))
+            .unwrap();
+
+        let pairs = find_duplicates(graph, None).unwrap();
+        assert_eq!(pairs.len(), 1);
+        assert_eq!((pairs[0].a.as_str(), pairs[0].b.as_str()), (a.as_str(), b.as_str()));
+        assert_eq!(pairs[0].reason, DuplicateReason::Title);
+
+        let pairs = find_duplicates(graph, Some(DEFAULT_CONTENT_THRESHOLD)).unwrap();
+        assert_eq!(pairs.len(), 2);
+        let content = pairs.iter().find(|p| p.reason == DuplicateReason::Content).unwrap();
+        assert_eq!((content.a.as_str(), content.b.as_str()), (a.as_str(), c.as_str()));
+        assert!(content.score >= DEFAULT_CONTENT_THRESHOLD && content.score < 1.0);
+        assert!(pairs.iter().all(|p| p.a != d && p.b != d));
+
+        // 降低阈值后近似笔记也会被标记
+        let pairs = find_duplicates(graph, Some(0.2)).unwrap();
+        assert!(pairs.iter().any(|p| p.b == d));
+
+        assert_eq!(DuplicatePair::display(&a), "a.md");
+        assert!(matches!(find_duplicates(graph, Some(1.5)), Err(MemexiaError::Validation(_))));
+    }
+}
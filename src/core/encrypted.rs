@@ -0,0 +1,133 @@
+//! 加密笔记模块
+//!
+//! 识别 `.md.age` / `.md.gpg` 加密笔记，避免把密文当作 Markdown 解析：
+//!
+//! - 默认生成 Resource 类型的占位节点（标题取自文件名，`encrypted = true`，没有正文），
+//!   文件仍参与图谱但不泄露明文
+//! - 配置 `index.decrypt_cmd` 后，文件内容通过 stdin 传给该命令，stdout 作为明文完整解析
+//!   （标题、标签、链接、字数）；明文只在内存中使用，节点同样不保存正文，
+//!   因此不会进入对象存储或图快照
+
+use crate::error::{MemexiaError, Result};
+use crate::storage::{Node, NodeType, ENCRYPTED_KEY, SUMMARY_KEY, SUMMARY_SOURCE_KEY};
+use anyhow::Context;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// 支持的加密扩展名（接在笔记扩展名之后）
+pub const ENCRYPTED_EXTENSIONS: &[&str] = &["age", "gpg"];
+
+/// 是否为加密笔记（`.md.age` / `.md.gpg`）
+pub fn is_encrypted(path: &Path) -> bool {
+    plain_name(&path.to_string_lossy()).is_some()
+}
+
+/// 去掉加密扩展名后的文件名：`a/secret.md.age` -> `a/secret.md`
+///
+/// 不是加密笔记时返回 None
+pub fn plain_name(name: &str) -> Option<&str> {
+    ENCRYPTED_EXTENSIONS
+        .iter()
+        .find_map(|ext| name.strip_suffix(ext)?.strip_suffix('.'))
+        .filter(|plain| plain.ends_with(".md"))
+}
+
+/// 创建加密笔记的占位节点
+///
+/// # Arguments
+///
+/// * `node_id` - 节点 ID
+/// * `file_name` - 文件相对路径（标题取文件名去掉扩展名的部分）
+pub fn stub_node(node_id: &str, file_name: &str) -> Node {
+    let base = file_name.rsplit('/').next().unwrap_or(file_name);
+    let title = base.split('.').next().unwrap_or(base);
+    let mut node = Node::new(node_id, NodeType::Resource, title);
+    mark_encrypted(&mut node);
+    node
+}
+
+/// 标记节点来自加密文件，并去掉正文和摘要
+pub fn mark_encrypted(node: &mut Node) {
+    node.content = None;
+    node.metadata.remove(SUMMARY_KEY);
+    node.metadata.remove(SUMMARY_SOURCE_KEY);
+    node.metadata.insert(ENCRYPTED_KEY.to_string(), true.into());
+}
+
+/// 通过用户命令解密文件
+///
+/// 文件内容写入命令的 stdin，stdout 作为明文返回；命令以 `sh -c`（Windows 为 `cmd /C`）执行
+///
+/// # Arguments
+///
+/// * `cmd` - 解密命令，如 `age -d -i ~/.age/key.txt`
+/// * `path` - 加密文件路径
+pub fn decrypt(cmd: &str, path: &Path) -> Result<String> {
+    let ciphertext = std::fs::read(path)?;
+
+    #[cfg(windows)]
+    let mut command = {
+        let mut c = Command::new("cmd");
+        c.args(["/C", cmd]);
+        c
+    };
+    #[cfg(not(windows))]
+    let mut command = {
+        let mut c = Command::new("sh");
+        c.args(["-c", cmd]);
+        c
+    };
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to spawn decrypt command")?;
+
+    // 单独线程写入 stdin，避免大文件时与读取 stdout 互相阻塞
+    let mut stdin = child.stdin.take().context("Failed to open decrypt command stdin")?;
+    let writer = std::thread::spawn(move || stdin.write_all(&ciphertext));
+
+    let output = child.wait_with_output()?;
+    let _ = writer.join();
+
+    if !output.status.success() {
+        return Err(MemexiaError::Validation(format!(
+            "Decrypt command failed for {} ({}): {}",
+            path.display(),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    String::from_utf8(output.stdout).map_err(|_| {
+        MemexiaError::Validation(format!("Decrypt command produced non-UTF-8 output for {}", path.display()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_name() {
+        assert_eq!(plain_name("secret.md.age"), Some("secret.md"));
+        assert_eq!(plain_name("dir/secret.md.gpg"), Some("dir/secret.md"));
+        assert_eq!(plain_name("secret.md"), None);
+        assert_eq!(plain_name("photo.png.age"), None);
+        assert_eq!(plain_name("notes.mdage"), None);
+        assert!(is_encrypted(Path::new("a/b.md.age")));
+    }
+
+    #[test]
+    fn test_stub_node() {
+        let node = stub_node("urn:memexia:file:dir/secret.md.age", "dir/secret.md.age");
+        assert_eq!(node.title, "secret");
+        assert_eq!(node.node_type, NodeType::Resource);
+        assert!(node.content.is_none());
+        assert!(node.is_encrypted());
+        assert!(node.word_count().is_none());
+    }
+}
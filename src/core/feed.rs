@@ -0,0 +1,301 @@
+//! 变更订阅源模块（`memexia feed`）
+//!
+//! 把提交历史导出为 Atom 或 RSS 文档，每个提交一个条目，从新到旧排列：
+//!
+//! - 条目标题为提交消息的第一行
+//! - 正文由该提交的图差异归纳而来（见 [`GraphDelta::summarize`](crate::vcs::graph_history::GraphDelta::summarize)）：
+//!   新增的笔记及其标题、新增的边（如 "A now Supports B"）
+//! - 配置了 `publish.base_url` 时，条目和新笔记链接到发布站点中的页面（见 [`PublishConfig::page_url`]）
+//!
+//! 日期按各自的规范书写：Atom 使用 RFC 3339，RSS 使用 RFC 822（RFC 2822）
+
+use crate::core::publish::{escape_html, PublishConfig};
+use crate::storage::ids;
+use crate::vcs::graph_history::DeltaSummary;
+use crate::vcs::CommitInfo;
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// 订阅源格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FeedFormat {
+    /// Atom 1.0
+    #[default]
+    Atom,
+    /// RSS 2.0
+    Rss,
+}
+
+impl FeedFormat {
+    /// 解析 `--format` 参数（不区分大小写）
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "atom" => Some(FeedFormat::Atom),
+            "rss" => Some(FeedFormat::Rss),
+            _ => None,
+        }
+    }
+}
+
+/// 条目中的一篇新笔记
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedNote {
+    /// 节点 ID
+    pub id: String,
+    /// 标题
+    pub title: String,
+    /// 发布站点中的页面（只有文件节点有）
+    pub url: Option<String>,
+}
+
+/// 条目中的一条新边，端点为节点标题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedEdge {
+    /// 源节点标题
+    pub from: String,
+    /// 关系名（如 `Supports`）
+    pub relation: String,
+    /// 目标节点标题
+    pub to: String,
+}
+
+/// 一个提交对应的条目
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedEntry {
+    /// 提交哈希
+    pub commit: String,
+    /// 标题（提交消息的第一行）
+    pub title: String,
+    /// 作者
+    pub author: String,
+    /// 提交时间
+    pub updated: DateTime<Utc>,
+    /// 条目链接：第一篇有页面的新笔记，否则为站点首页
+    pub link: Option<String>,
+    /// 新增的笔记
+    pub new_notes: Vec<FeedNote>,
+    /// 新增的边
+    pub new_edges: Vec<FeedEdge>,
+}
+
+impl FeedEntry {
+    /// 由提交和它的图差异生成条目
+    ///
+    /// # Arguments
+    ///
+    /// * `commit` - 提交
+    /// * `updated` - 提交时间
+    /// * `delta` - 该提交相对上一个提交的图差异
+    /// * `titles` - 提交后图中的节点标题，用于显示边的端点
+    /// * `publish` - 发布配置（`base_url`）
+    pub fn new(
+        commit: &CommitInfo,
+        updated: DateTime<Utc>,
+        delta: &DeltaSummary,
+        titles: &BTreeMap<String, String>,
+        publish: &PublishConfig,
+    ) -> Self {
+        let title_of = |id: &str| {
+            delta
+                .titles
+                .get(id)
+                .or_else(|| titles.get(id))
+                .cloned()
+                .unwrap_or_else(|| ids::display_file_id(id))
+        };
+        let new_notes: Vec<FeedNote> = delta
+            .added_nodes
+            .iter()
+            .map(|id| FeedNote {
+                id: id.clone(),
+                title: title_of(id),
+                url: id
+                    .starts_with(ids::FILE_PREFIX)
+                    .then(|| publish.page_url(&ids::display_file_id(id)))
+                    .flatten(),
+            })
+            .collect();
+        let new_edges = delta
+            .added_edges
+            .iter()
+            .map(|edge| FeedEdge {
+                from: title_of(&edge.from),
+                relation: edge.relation_type().map(|r| r.to_string()).unwrap_or_else(|| edge.relation.clone()),
+                to: title_of(&edge.to),
+            })
+            .collect();
+
+        Self {
+            commit: commit.oid.clone(),
+            title: commit.message.lines().next().unwrap_or_default().to_string(),
+            author: commit.author.clone(),
+            updated,
+            link: new_notes.iter().find_map(|note| note.url.clone()).or_else(|| publish.site_url()),
+            new_notes,
+            new_edges,
+        }
+    }
+
+    /// 条目正文（HTML）
+    pub fn body_html(&self) -> String {
+        if self.new_notes.is_empty() && self.new_edges.is_empty() {
+            return "<p>No new notes or links.</p>".to_string();
+        }
+        let mut out = String::new();
+        if !self.new_notes.is_empty() {
+            out.push_str("<p>New notes:</p>\n<ul>\n");
+            for note in &self.new_notes {
+                let title = escape_html(&note.title);
+                match &note.url {
+                    Some(url) => writeln!(out, "<li><a href=\"{}\">{}</a></li>", escape_html(url), title),
+                    None => writeln!(out, "<li>{}</li>", title),
+                }
+                .unwrap();
+            }
+            out.push_str("</ul>\n");
+        }
+        if !self.new_edges.is_empty() {
+            out.push_str("<p>New links:</p>\n<ul>\n");
+            for edge in &self.new_edges {
+                writeln!(
+                    out,
+                    "<li>{} now {} {}</li>",
+                    escape_html(&edge.from),
+                    escape_html(&edge.relation),
+                    escape_html(&edge.to)
+                )
+                .unwrap();
+            }
+            out.push_str("</ul>\n");
+        }
+        out
+    }
+}
+
+/// 订阅源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Feed {
+    /// 标题
+    pub title: String,
+    /// 站点链接（`publish.base_url`）
+    pub link: Option<String>,
+    /// 条目（从新到旧）
+    pub entries: Vec<FeedEntry>,
+}
+
+impl Feed {
+    /// 渲染为 XML 文档
+    pub fn render(&self, format: FeedFormat) -> String {
+        match format {
+            FeedFormat::Atom => self.render_atom(),
+            FeedFormat::Rss => self.render_rss(),
+        }
+    }
+
+    /// Atom 1.0（RFC 4287）；没有条目时以 Unix 纪元作为更新时间
+    fn render_atom(&self) -> String {
+        let rfc3339 = |time: &DateTime<Utc>| time.to_rfc3339_opts(SecondsFormat::Secs, true);
+        let updated = self.entries.first().map(|e| e.updated).unwrap_or_default();
+
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        out.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+        writeln!(out, "  <id>{}</id>", escape_html(self.link.as_deref().unwrap_or("urn:memexia:feed"))).unwrap();
+        writeln!(out, "  <title>{}</title>", escape_html(&self.title)).unwrap();
+        writeln!(out, "  <updated>{}</updated>", rfc3339(&updated)).unwrap();
+        if let Some(link) = &self.link {
+            writeln!(out, "  <link href=\"{}\"/>", escape_html(link)).unwrap();
+        }
+        for entry in &self.entries {
+            out.push_str("  <entry>\n");
+            writeln!(out, "    <id>urn:memexia:commit:{}</id>", entry.commit).unwrap();
+            writeln!(out, "    <title>{}</title>", escape_html(&entry.title)).unwrap();
+            writeln!(out, "    <updated>{}</updated>", rfc3339(&entry.updated)).unwrap();
+            writeln!(out, "    <author><name>{}</name></author>", escape_html(&entry.author)).unwrap();
+            if let Some(link) = &entry.link {
+                writeln!(out, "    <link href=\"{}\"/>", escape_html(link)).unwrap();
+            }
+            writeln!(out, "    <content type=\"html\">{}</content>", escape_html(&entry.body_html())).unwrap();
+            out.push_str("  </entry>\n");
+        }
+        out.push_str("</feed>\n");
+        out
+    }
+
+    /// RSS 2.0；频道链接为必填项，未配置 `base_url` 时为空
+    fn render_rss(&self) -> String {
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        out.push_str("<rss version=\"2.0\">\n<channel>\n");
+        writeln!(out, "  <title>{}</title>", escape_html(&self.title)).unwrap();
+        writeln!(out, "  <link>{}</link>", escape_html(self.link.as_deref().unwrap_or_default())).unwrap();
+        writeln!(out, "  <description>{}</description>", escape_html(&self.title)).unwrap();
+        if let Some(entry) = self.entries.first() {
+            writeln!(out, "  <lastBuildDate>{}</lastBuildDate>", entry.updated.to_rfc2822()).unwrap();
+        }
+        for entry in &self.entries {
+            out.push_str("  <item>\n");
+            writeln!(out, "    <title>{}</title>", escape_html(&entry.title)).unwrap();
+            writeln!(out, "    <guid isPermaLink=\"false\">urn:memexia:commit:{}</guid>", entry.commit).unwrap();
+            writeln!(out, "    <pubDate>{}</pubDate>", entry.updated.to_rfc2822()).unwrap();
+            if let Some(link) = &entry.link {
+                writeln!(out, "    <link>{}</link>", escape_html(link)).unwrap();
+            }
+            writeln!(out, "    <description>{}</description>", escape_html(&entry.body_html())).unwrap();
+            out.push_str("  </item>\n");
+        }
+        out.push_str("</channel>\n</rss>\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vcs::graph_history::DeltaEdge;
+
+    fn sample_entry(publish: &PublishConfig) -> FeedEntry {
+        let commit = CommitInfo::from_raw("abc123".to_string(), "Learn <things>\n\nBody", "Ada".to_string(), String::new());
+        let delta = DeltaSummary {
+            added_nodes: vec![ids::file_id("notes/b.md")],
+            added_edges: vec![DeltaEdge {
+                from: ids::file_id("notes/a.md"),
+                relation: "supports".to_string(),
+                to: ids::file_id("notes/b.md"),
+            }],
+            titles: BTreeMap::from([(ids::file_id("notes/b.md"), "B & C".to_string())]),
+            ..Default::default()
+        };
+        let titles = BTreeMap::from([(ids::file_id("notes/a.md"), "A".to_string())]);
+        let updated = DateTime::parse_from_rfc3339("2026-03-01T08:30:00Z").unwrap().with_timezone(&Utc);
+        FeedEntry::new(&commit, updated, &delta, &titles, publish)
+    }
+
+    #[test]
+    fn test_entry_from_delta() {
+        let publish = PublishConfig { base_url: Some("https://notes.example.com".to_string()), ..Default::default() };
+        let entry = sample_entry(&publish);
+        assert_eq!(entry.title, "Learn <things>");
+        assert_eq!(entry.link.as_deref(), Some("https://notes.example.com/notes/notes/b.html"));
+        assert_eq!(
+            entry.new_edges,
+            [FeedEdge { from: "A".to_string(), relation: "Supports".to_string(), to: "B & C".to_string() }]
+        );
+        let body = entry.body_html();
+        assert!(body.contains("<a href=\"https://notes.example.com/notes/notes/b.html\">B &amp; C</a>"));
+        assert!(body.contains("<li>A now Supports B &amp; C</li>"));
+
+        assert_eq!(sample_entry(&PublishConfig::default()).link, None);
+    }
+
+    #[test]
+    fn test_render_dates_per_format() {
+        let feed = Feed { title: "notes".to_string(), link: None, entries: vec![sample_entry(&PublishConfig::default())] };
+        let atom = feed.render(FeedFormat::Atom);
+        assert!(atom.contains("<updated>2026-03-01T08:30:00Z</updated>"));
+        assert!(atom.contains("<title>Learn &lt;things&gt;</title>"));
+        let rss = feed.render(FeedFormat::Rss);
+        assert!(rss.contains("<pubDate>Sun, 1 Mar 2026 08:30:00 +0000</pubDate>"));
+        assert_eq!(FeedFormat::parse("RSS"), Some(FeedFormat::Rss));
+        assert_eq!(FeedFormat::parse("json"), None);
+    }
+}
@@ -0,0 +1,224 @@
+//! 单篇笔记变更日志模块（`memexia file log`）
+//!
+//! 把三类记录合并成一条按时间排列的日志：
+//!
+//! - 修改过文件的 Git 提交（跟随重命名），比较前后两个版本得出字数变化和链接增删
+//! - 图历史中的节点快照
+//! - 节点作为子节点或父节点出现的推导记录
+
+use crate::core::parser::parse_file;
+use crate::vcs::{DerivationEntry, FileRevision, NodeHistoryEntry};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// 一次提交对文件的变更
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum FileChange {
+    /// 文件被创建
+    Created {
+        /// 字数
+        words: usize,
+    },
+    /// 文件被删除
+    Deleted,
+    /// 文件由重命名而来
+    Renamed {
+        /// 原路径
+        from: String,
+    },
+    /// 正文变化
+    ContentChanged {
+        /// 字数变化
+        word_delta: i64,
+    },
+    /// 新增链接
+    LinkAdded {
+        /// 链接目标
+        target: String,
+    },
+    /// 删除链接
+    LinkRemoved {
+        /// 链接目标
+        target: String,
+    },
+}
+
+/// 日志事件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FileLogEvent {
+    /// 修改过文件的 Git 提交
+    Commit {
+        /// 提交哈希
+        commit: String,
+        /// 提交消息（不含 trailer）
+        message: String,
+        /// 文件变更
+        changes: Vec<FileChange>,
+    },
+    /// 节点快照
+    Snapshot {
+        /// 快照哈希
+        hash: String,
+        /// 关联的提交哈希
+        commit: String,
+    },
+    /// 本节点由另一个节点推导而来
+    DerivedFrom {
+        /// 父节点 ID
+        parent: String,
+        /// 关联的提交哈希
+        commit: String,
+    },
+    /// 另一个节点由本节点推导而来
+    DerivedInto {
+        /// 子节点 ID
+        child: String,
+        /// 关联的提交哈希
+        commit: String,
+    },
+}
+
+/// 日志条目
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileLogEntry {
+    /// 发生时间
+    pub timestamp: DateTime<Utc>,
+    /// 事件
+    #[serde(flatten)]
+    pub event: FileLogEvent,
+}
+
+/// 比较提交前后的文件版本
+pub fn commit_changes(revision: &FileRevision) -> Vec<FileChange> {
+    let name = revision.path.to_string_lossy();
+    let parse = |bytes: &Option<Vec<u8>>| bytes.as_ref().map(|b| parse_file(&String::from_utf8_lossy(b), &name).0);
+    let (before, after) = (parse(&revision.previous), parse(&revision.content));
+
+    let mut changes = Vec::new();
+    if let Some(from) = &revision.renamed_from {
+        changes.push(FileChange::Renamed {
+            from: from.to_string_lossy().replace('\\', "/"),
+        });
+    }
+    match (&before, &after) {
+        (None, Some(doc)) => changes.push(FileChange::Created { words: doc.word_count }),
+        (Some(_), None) => changes.push(FileChange::Deleted),
+        (Some(old), Some(new)) if revision.previous != revision.content => changes.push(FileChange::ContentChanged {
+            word_delta: new.word_count as i64 - old.word_count as i64,
+        }),
+        _ => {}
+    }
+
+    let links = |doc: &Option<crate::core::parser::ParsedDoc>| -> BTreeSet<String> {
+        doc.iter().flat_map(|d| d.wiki_links.iter().map(|l| l.target.clone())).collect()
+    };
+    let (old_links, new_links) = (links(&before), links(&after));
+    if after.is_some() {
+        for target in new_links.difference(&old_links) {
+            changes.push(FileChange::LinkAdded { target: target.clone() });
+        }
+        for target in old_links.difference(&new_links) {
+            changes.push(FileChange::LinkRemoved { target: target.clone() });
+        }
+    }
+    changes
+}
+
+/// 合并各类记录
+///
+/// # Arguments
+///
+/// * `revisions` - 修改过文件的提交（从新到旧）
+/// * `snapshots` - 节点快照记录
+/// * `derived_from` - 本节点作为子节点的推导记录
+/// * `derived_into` - 本节点作为父节点的推导记录
+///
+/// # Returns
+///
+/// 从新到旧排列的日志；同一时间的提交保持 Git 遍历顺序
+pub fn build_file_log(
+    revisions: &[FileRevision],
+    snapshots: &[NodeHistoryEntry],
+    derived_from: &[DerivationEntry],
+    derived_into: &[DerivationEntry],
+) -> Vec<FileLogEntry> {
+    let mut entries: Vec<FileLogEntry> = revisions
+        .iter()
+        .map(|revision| FileLogEntry {
+            timestamp: revision.time,
+            event: FileLogEvent::Commit {
+                commit: revision.oid.clone(),
+                message: revision.message.clone(),
+                changes: commit_changes(revision),
+            },
+        })
+        .collect();
+    entries.extend(snapshots.iter().map(|entry| FileLogEntry {
+        timestamp: entry.timestamp,
+        event: FileLogEvent::Snapshot {
+            hash: entry.hash.clone(),
+            commit: entry.commit_hash.clone(),
+        },
+    }));
+    entries.extend(derived_from.iter().map(|entry| FileLogEntry {
+        timestamp: entry.timestamp,
+        event: FileLogEvent::DerivedFrom {
+            parent: entry.parent_id.clone(),
+            commit: entry.commit_hash.clone(),
+        },
+    }));
+    entries.extend(derived_into.iter().map(|entry| FileLogEntry {
+        timestamp: entry.timestamp,
+        event: FileLogEvent::DerivedInto {
+            child: entry.child_id.clone(),
+            commit: entry.commit_hash.clone(),
+        },
+    }));
+
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.timestamp));
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn revision(previous: Option<&str>, content: Option<&str>) -> FileRevision {
+        FileRevision {
+            oid: "0123456789".to_string(),
+            message: "edit".to_string(),
+            time: Utc::now(),
+            path: PathBuf::from("note.md"),
+            renamed_from: None,
+            content: content.map(|c| c.as_bytes().to_vec()),
+            previous: previous.map(|c| c.as_bytes().to_vec()),
+        }
+    }
+
+    #[test]
+    fn test_commit_changes() {
+        let created = commit_changes(&revision(None, Some("# Note\n\none two [[a]]\n")));
+        assert_eq!(created[0], FileChange::Created { words: 4 });
+        assert_eq!(created[1], FileChange::LinkAdded { target: "a".to_string() });
+
+        let edited = commit_changes(&revision(Some("# Note\n\none two [[a]]\n"), Some("# Note\n\none [[b]]\n")));
+        assert_eq!(
+            edited,
+            [
+                FileChange::ContentChanged { word_delta: -1 },
+                FileChange::LinkAdded { target: "b".to_string() },
+                FileChange::LinkRemoved { target: "a".to_string() },
+            ]
+        );
+
+        let mut moved = revision(Some("same"), Some("same"));
+        moved.renamed_from = Some(PathBuf::from("old.md"));
+        assert_eq!(commit_changes(&moved), [FileChange::Renamed { from: "old.md".to_string() }]);
+
+        assert_eq!(commit_changes(&revision(Some("gone [[a]]"), None)), [FileChange::Deleted]);
+    }
+}
@@ -0,0 +1,199 @@
+//! 目录默认值模块
+//!
+//! `.memexia/templates/_defaults.toml` 按目录 glob 为笔记指定默认 frontmatter 片段和模板：
+//!
+//! ```toml
+//! [[folder]]
+//! glob = "people/**"
+//! frontmatter = { type = "Person" }
+//!
+//! [[folder]]
+//! glob = "literature"
+//! template = "literature"
+//! frontmatter = { type = "Resource", source = "" }
+//! ```
+//!
+//! - `memexia new` 在匹配的目录中创建笔记时写入默认 frontmatter，未指定 `--template` 时使用默认模板
+//! - 索引没有 frontmatter 的文件时，节点类型取目录默认值而不是 Concept
+//!
+//! 文件中显式写出的 frontmatter 总是优先。多条规则匹配时取第一条
+
+use crate::core::note::TEMPLATES_DIR;
+use crate::error::Result;
+use crate::storage::NodeType;
+use anyhow::Context;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 目录默认值文件名（位于模板目录中）
+pub const DEFAULTS_FILE: &str = "_defaults.toml";
+
+/// 一条目录规则
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct FolderDefault {
+    /// 目录 glob（相对仓库根目录）；不含通配符时匹配该目录及其子目录下的所有文件
+    pub glob: String,
+    /// 默认模板名称
+    #[serde(default)]
+    pub template: Option<String>,
+    /// 默认 frontmatter 片段
+    #[serde(default)]
+    pub frontmatter: toml::Table,
+}
+
+impl FolderDefault {
+    /// 相对路径是否匹配该规则
+    pub fn matches(&self, relative: &str) -> bool {
+        let pattern = self.glob.trim_end_matches('/');
+        if !pattern.contains(['*', '?']) {
+            return pattern.is_empty()
+                || pattern == "."
+                || relative.strip_prefix(pattern).is_some_and(|rest| rest.starts_with('/'));
+        }
+        let mut regex = String::from("^");
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                // `**` 跨越目录，`*` 不跨越
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    regex.push_str(".*");
+                }
+                '*' => regex.push_str("[^/]*"),
+                '?' => regex.push_str("[^/]"),
+                c => regex.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        // 目录 glob 同样匹配其下的文件
+        regex.push_str("(/.*)?$");
+        regex::Regex::new(&regex).is_ok_and(|re| re.is_match(relative))
+    }
+
+    /// 默认节点类型（frontmatter 片段中的 `type`）
+    pub fn node_type(&self) -> Option<NodeType> {
+        self.frontmatter.get("type").and_then(|v| v.as_str()).map(parse_node_type)
+    }
+
+    /// 默认标签
+    pub fn tags(&self) -> Vec<String> {
+        match self.frontmatter.get("tags") {
+            Some(toml::Value::Array(tags)) => tags.iter().filter_map(|t| t.as_str().map(str::to_string)).collect(),
+            Some(toml::Value::String(tag)) => vec![tag.clone()],
+            _ => Vec::new(),
+        }
+    }
+
+    /// 除 `title`、`type`、`tags` 外的其余字段，渲染为 YAML 行
+    pub fn extra_yaml(&self) -> String {
+        let mut yaml = String::new();
+        for (key, value) in &self.frontmatter {
+            if matches!(key.as_str(), "title" | "type" | "tags") {
+                continue;
+            }
+            yaml.push_str(&format!("{}: {}\n", key, yaml_value(value)));
+        }
+        yaml
+    }
+}
+
+/// 目录默认值
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct FolderDefaults {
+    /// 规则列表（按声明顺序匹配）
+    #[serde(default, rename = "folder")]
+    pub folders: Vec<FolderDefault>,
+}
+
+impl FolderDefaults {
+    /// 读取仓库的目录默认值，文件不存在时返回空规则
+    pub fn load(root: &Path) -> Result<Self> {
+        let path = defaults_path(root);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)?;
+        Ok(toml::from_str(&content).with_context(|| format!("Failed to parse {:?}", path))?)
+    }
+
+    /// 查找匹配相对路径的第一条规则
+    pub fn lookup(&self, relative: &str) -> Option<&FolderDefault> {
+        self.folders.iter().find(|folder| folder.matches(relative))
+    }
+
+    /// 相对路径对应的默认节点类型
+    pub fn node_type_for(&self, relative: &str) -> Option<NodeType> {
+        self.lookup(relative).and_then(FolderDefault::node_type)
+    }
+}
+
+/// 目录默认值文件路径
+pub fn defaults_path(root: &Path) -> PathBuf {
+    root.join(TEMPLATES_DIR).join(DEFAULTS_FILE)
+}
+
+fn parse_node_type(s: &str) -> NodeType {
+    match s.to_lowercase().as_str() {
+        "question" => NodeType::Question,
+        "evidence" => NodeType::Evidence,
+        "resource" => NodeType::Resource,
+        "person" => NodeType::Person,
+        "event" => NodeType::Event,
+        "meta" => NodeType::Meta,
+        _ => NodeType::Concept,
+    }
+}
+
+fn yaml_value(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(s) => format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"")),
+        toml::Value::Array(items) => format!("[{}]", items.iter().map(yaml_value).collect::<Vec<_>>().join(", ")),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lookup_and_fragments() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join(TEMPLATES_DIR)).unwrap();
+        fs::write(
+            defaults_path(temp.path()),
+            r#"
+[[folder]]
+glob = "people/**"
+frontmatter = { type = "Person", tags = ["contact"] }
+
+[[folder]]
+glob = "literature"
+template = "literature"
+frontmatter = { type = "Resource", source = "", year = 2024 }
+
+[[folder]]
+glob = "projects/*/log"
+frontmatter = { type = "Event" }
+"#,
+        )
+        .unwrap();
+
+        let defaults = FolderDefaults::load(temp.path()).unwrap();
+        assert_eq!(defaults.node_type_for("people/ada.md"), Some(NodeType::Person));
+        assert_eq!(defaults.node_type_for("people/team/bob.md"), Some(NodeType::Person));
+        assert_eq!(defaults.node_type_for("literature/sicp.md"), Some(NodeType::Resource));
+        assert_eq!(defaults.node_type_for("projects/x/log/day.md"), Some(NodeType::Event));
+        assert_eq!(defaults.node_type_for("projects/x/y/log/day.md"), None);
+        assert_eq!(defaults.node_type_for("notes/people/ada.md"), None);
+        assert_eq!(defaults.node_type_for("literature.md"), None);
+
+        let literature = defaults.lookup("literature/sicp.md").unwrap();
+        assert_eq!(literature.template.as_deref(), Some("literature"));
+        assert_eq!(literature.extra_yaml(), "source: \"\"\nyear: 2024\n");
+        assert_eq!(defaults.lookup("people/ada.md").unwrap().tags(), ["contact"]);
+
+        assert!(FolderDefaults::load(&temp.path().join("missing")).unwrap().folders.is_empty());
+    }
+}
@@ -0,0 +1,161 @@
+//! 图结构统计模块
+//!
+//! 为 `memexia graph stats --detailed` 统计关系类型的实际使用情况：
+//!
+//! - 所有边的强度直方图（0–0.2、…、0.8–1.0 五个区间）
+//! - 按起点节点类型 × 关系 × 终点节点类型的计数矩阵（如 Evidence Supports Concept 的次数）
+//! - 每种节点类型的平均出度
+//!
+//! 端点类型来自预先建立的节点 ID → 类型映射，只遍历一次边，不逐条查询节点
+
+use crate::storage::{Edge, Node, NodeType};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// 强度直方图的区间数（每个区间宽 0.2）
+pub const STRENGTH_BUCKETS: usize = 5;
+
+/// 关系矩阵：关系 → 起点类型 → 终点类型 → 边数
+pub type RelationMatrix = BTreeMap<String, BTreeMap<String, BTreeMap<String, usize>>>;
+
+/// 详细图统计
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct GraphDetail {
+    /// 强度直方图，第 i 个区间为 `[0.2 * i, 0.2 * (i + 1))`，最后一个区间包含 1.0
+    pub strength_histogram: [usize; STRENGTH_BUCKETS],
+    /// 关系矩阵
+    pub relation_matrix: RelationMatrix,
+    /// 每种节点类型的平均出度
+    pub avg_out_degree: BTreeMap<String, f64>,
+    /// 端点节点不存在、未计入矩阵的边数
+    pub dangling_edges: usize,
+}
+
+impl GraphDetail {
+    /// 计算详细统计
+    ///
+    /// # Arguments
+    ///
+    /// * `nodes` - 所有节点
+    /// * `edges` - 所有边
+    pub fn compute(nodes: &[Node], edges: &[Edge]) -> Self {
+        let types: HashMap<&str, &NodeType> = nodes.iter().map(|n| (n.id.as_str(), &n.node_type)).collect();
+
+        let mut detail = GraphDetail::default();
+        let mut out_degree: HashMap<&NodeType, usize> = HashMap::new();
+        for edge in edges {
+            detail.strength_histogram[strength_bucket(edge.strength)] += 1;
+
+            let from = types.get(edge.from.as_str());
+            if let Some(from) = from {
+                *out_degree.entry(*from).or_default() += 1;
+            }
+            let (Some(from), Some(to)) = (from, types.get(edge.to.as_str())) else {
+                detail.dangling_edges += 1;
+                continue;
+            };
+            *detail
+                .relation_matrix
+                .entry(edge.relation.to_string())
+                .or_default()
+                .entry(from.to_string())
+                .or_default()
+                .entry(to.to_string())
+                .or_default() += 1;
+        }
+
+        let mut type_counts: HashMap<&NodeType, usize> = HashMap::new();
+        for node in nodes {
+            *type_counts.entry(&node.node_type).or_default() += 1;
+        }
+        detail.avg_out_degree = type_counts
+            .into_iter()
+            .map(|(node_type, count)| {
+                let edges = out_degree.get(node_type).copied().unwrap_or(0);
+                (node_type.to_string(), edges as f64 / count as f64)
+            })
+            .collect();
+        detail
+    }
+
+    /// 指定关系从起点类型到终点类型的边数
+    pub fn relation_count(&self, relation: &str, from: &str, to: &str) -> usize {
+        self.relation_matrix
+            .get(relation)
+            .and_then(|sources| sources.get(from))
+            .and_then(|targets| targets.get(to))
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+/// 强度所在的直方图区间（超出 0.0 - 1.0 的值归入两端）
+pub fn strength_bucket(strength: f64) -> usize {
+    let index = (strength * STRENGTH_BUCKETS as f64).floor();
+    if index.is_nan() || index < 0.0 {
+        0
+    } else {
+        (index as usize).min(STRENGTH_BUCKETS - 1)
+    }
+}
+
+/// 区间标签，如 `0.2–0.4`
+pub fn bucket_label(index: usize) -> String {
+    let width = 1.0 / STRENGTH_BUCKETS as f64;
+    format!("{:.1}–{:.1}", index as f64 * width, (index + 1) as f64 * width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::RelationType;
+
+    fn edge(from: &str, to: &str, relation: RelationType, strength: f64) -> Edge {
+        Edge::builder(from, to, relation).strength(strength).build().unwrap()
+    }
+
+    #[test]
+    fn test_strength_buckets() {
+        assert_eq!(strength_bucket(0.0), 0);
+        assert_eq!(strength_bucket(0.19), 0);
+        assert_eq!(strength_bucket(0.2), 1);
+        assert_eq!(strength_bucket(0.79), 3);
+        assert_eq!(strength_bucket(1.0), 4);
+        assert_eq!(strength_bucket(-0.5), 0);
+        assert_eq!(bucket_label(1), "0.2–0.4");
+    }
+
+    #[test]
+    fn test_matrix_and_out_degree() {
+        let nodes = [
+            Node::new("e1", NodeType::Evidence, "E1"),
+            Node::new("e2", NodeType::Evidence, "E2"),
+            Node::new("c1", NodeType::Concept, "C1"),
+            Node::new("c2", NodeType::Concept, "C2"),
+            Node::new("q1", NodeType::Question, "Q1"),
+        ];
+        let edges = [
+            edge("e1", "c1", RelationType::Supports, 0.9),
+            edge("e2", "c1", RelationType::Supports, 0.7),
+            edge("e2", "c2", RelationType::Supports, 0.3),
+            edge("c1", "c2", RelationType::Contains, 0.5),
+            edge("q1", "c1", RelationType::References, 1.0),
+            edge("c2", "ghost", RelationType::RelatedTo, 0.1),
+        ];
+
+        let detail = GraphDetail::compute(&nodes, &edges);
+        assert_eq!(detail.relation_count("Supports", "Evidence", "Concept"), 3);
+        assert_eq!(detail.relation_count("Contains", "Concept", "Concept"), 1);
+        assert_eq!(detail.relation_count("Supports", "Concept", "Evidence"), 0);
+        assert_eq!(detail.dangling_edges, 1);
+        assert_eq!(detail.strength_histogram, [1, 1, 1, 1, 2]);
+
+        assert_eq!(detail.avg_out_degree["Evidence"], 1.5);
+        // 指向缺失节点的边也计入出度
+        assert_eq!(detail.avg_out_degree["Concept"], 1.0);
+        assert_eq!(detail.avg_out_degree["Question"], 1.0);
+
+        let json = serde_json::to_value(&detail).unwrap();
+        assert_eq!(json["relation_matrix"]["Supports"]["Evidence"]["Concept"], 3);
+    }
+}
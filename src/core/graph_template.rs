@@ -0,0 +1,284 @@
+//! 图谱模板模块
+//!
+//! 笔记中独占一行的图谱占位符在渲染时由实时的图查询展开为 Markdown 列表：
+//!
+//! - `{{backlinks}}` - 链接到本笔记的笔记，可用 `relation=<关系>` 过滤
+//! - `{{related limit=5}}` - 与本笔记共享标签的笔记，共享标签多的在前（默认最多 10 条）
+//! - `{{children relation=Contains}}` - 本笔记以指定关系链接的笔记（默认 `Contains`），
+//!   也包括以逆关系链接到本笔记的笔记（如 `[[本笔记|PartOf]]`）
+//!
+//! 所有占位符都接受 `limit=<N>`。展开结果由标记注释包围，注释中保留原占位符：
+//!
+//! ```markdown
+//! <!-- memexia:begin backlinks -->
+//! - [[notes/smith-2020.md]] Smith 2020
+//! <!-- memexia:end -->
+//! ```
+//!
+//! 再次渲染时替换标记之间的内容而不是重复插入。渲染只由 `memexia new --template`
+//! 和 `memexia file render-template` 显式触发，索引时不改动文件；
+//! 生成块中的链接不参与索引，见 [`mask_generated`]
+
+use crate::error::{MemexiaError, Result};
+use crate::storage::{ids, GraphStorage, Node, RelationType};
+use crate::core::zettel;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
+
+/// 生成块开始标记的前缀，其后是占位符内容和 ` -->`
+pub const BEGIN_MARKER: &str = "<!-- memexia:begin ";
+
+/// 生成块结束标记
+pub const END_MARKER: &str = "<!-- memexia:end -->";
+
+/// `{{related}}` 未指定 `limit` 时的条数上限
+pub const DEFAULT_RELATED_LIMIT: usize = 10;
+
+/// 图谱占位符
+#[derive(Debug, Clone, PartialEq)]
+pub enum Directive {
+    /// 反向链接
+    Backlinks { relation: Option<RelationType>, limit: Option<usize> },
+    /// 共享标签的笔记
+    Related { limit: usize },
+    /// 以指定关系链出（或以逆关系链入）的笔记
+    Children { relation: RelationType, limit: Option<usize> },
+}
+
+impl Directive {
+    /// 解析占位符内容（不含 `{{` `}}`）
+    ///
+    /// # Returns
+    ///
+    /// 不是图谱占位符（如 `{{title}}`）时返回 None；选项无效时返回校验错误
+    pub fn parse(text: &str) -> Result<Option<Self>> {
+        let mut words = text.split_whitespace();
+        let Some(name) = words.next() else {
+            return Ok(None);
+        };
+        if !matches!(name, "backlinks" | "related" | "children") {
+            return Ok(None);
+        }
+
+        let mut relation = None;
+        let mut limit = None;
+        for option in words {
+            let invalid = || MemexiaError::Validation(format!("Invalid option `{}` in {{{{{}}}}}", option, text.trim()));
+            let (key, value) = option.split_once('=').ok_or_else(invalid)?;
+            match key {
+                "limit" => limit = Some(value.parse::<usize>().map_err(|_| invalid())?),
+                "relation" if name != "related" => relation = Some(RelationType::parse(value).ok_or_else(invalid)?),
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(Some(match name {
+            "backlinks" => Directive::Backlinks { relation, limit },
+            "related" => Directive::Related { limit: limit.unwrap_or(DEFAULT_RELATED_LIMIT) },
+            _ => Directive::Children { relation: relation.unwrap_or(RelationType::Contains), limit },
+        }))
+    }
+
+    /// 查询图谱得到列表中的笔记（已排序、截断）
+    ///
+    /// # Arguments
+    ///
+    /// * `graph` - 图存储
+    /// * `id` - 本笔记的节点 ID
+    /// * `tags` - 本笔记的标签（取自文件，笔记尚未索引时也可用）
+    pub fn query(&self, graph: &dyn GraphStorage, id: &str, tags: &[String]) -> Result<Vec<Node>> {
+        let (mut nodes, limit) = match self {
+            Directive::Backlinks { relation, limit } => {
+                let sources = graph
+                    .get_edges_by_target(id)?
+                    .into_iter()
+                    .filter(|edge| edge.from != id && relation.as_ref().is_none_or(|r| &edge.relation == r))
+                    .map(|edge| edge.from);
+                (sorted_nodes(graph, sources)?, *limit)
+            }
+            Directive::Children { relation, limit } => {
+                let related = graph.get_related(id, relation)?;
+                (sorted_nodes(graph, related.into_iter())?, *limit)
+            }
+            Directive::Related { limit } => (related_nodes(graph, id, tags)?, Some(*limit)),
+        };
+        if let Some(limit) = limit {
+            nodes.truncate(limit);
+        }
+        Ok(nodes)
+    }
+}
+
+/// 按 ID 去重取出节点，按标题排序；图中不存在或已删除的节点跳过
+fn sorted_nodes(graph: &dyn GraphStorage, ids: impl Iterator<Item = String>) -> Result<Vec<Node>> {
+    let mut nodes = Vec::new();
+    for id in ids.collect::<BTreeSet<_>>() {
+        if let Some(node) = graph.get_node(&id)?.filter(|node| !node.is_deleted()) {
+            nodes.push(node);
+        }
+    }
+    nodes.sort_by(|a, b| a.title.cmp(&b.title).then_with(|| a.id.cmp(&b.id)));
+    Ok(nodes)
+}
+
+/// 与本笔记共享标签的节点，共享标签多的在前，其次按标题
+fn related_nodes(graph: &dyn GraphStorage, id: &str, tags: &[String]) -> Result<Vec<Node>> {
+    if tags.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut shared: HashMap<String, usize> = HashMap::new();
+    let mut nodes = Vec::new();
+    graph.for_each_node(&mut |node| {
+        let count = node.tags.iter().filter(|tag| tags.contains(tag)).count();
+        if count > 0 && node.id != id && !node.is_deleted() {
+            shared.insert(node.id.clone(), count);
+            nodes.push(node);
+        }
+        Ok(())
+    })?;
+    nodes.sort_by(|a, b| {
+        shared[&b.id]
+            .cmp(&shared[&a.id])
+            .then_with(|| a.title.cmp(&b.title))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    Ok(nodes)
+}
+
+/// 节点 ID 对应的 wiki 链接目标：文件节点为相对路径，Zettel 节点为 ID
+pub fn link_target(id: &str) -> String {
+    match id.strip_prefix(zettel::ZETTEL_ID_PREFIX) {
+        Some(zettel_id) => zettel_id.to_string(),
+        None => ids::display_file_id(id),
+    }
+}
+
+/// 一个节点的列表项：`- [[目标]] 标题`
+fn list_item(node: &Node) -> String {
+    format!("- [[{}]] {}", link_target(&node.id), node.title)
+}
+
+/// 独占一行的占位符内容；不是占位符行时返回 None
+fn placeholder(line: &str) -> Option<&str> {
+    line.trim().strip_prefix("{{")?.strip_suffix("}}")
+}
+
+/// 生成块开始标记中的占位符内容；不是开始标记时返回 None
+fn begin_marker(line: &str) -> Option<&str> {
+    line.trim().strip_prefix(BEGIN_MARKER)?.strip_suffix("-->").map(str::trim)
+}
+
+fn is_fence(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("```") || line.starts_with("~~~")
+}
+
+/// 展开笔记中的图谱占位符，并重新生成已有的生成块
+///
+/// 代码块中的占位符保持原样，不是图谱占位符的 `{{...}}` 保持原样
+///
+/// # Arguments
+///
+/// * `graph` - 图存储
+/// * `id` - 本笔记的节点 ID
+/// * `tags` - 本笔记的标签
+/// * `content` - 笔记内容
+///
+/// # Returns
+///
+/// 渲染后的内容；生成块缺少结束标记或占位符选项无效时返回校验错误
+pub fn render(graph: &dyn GraphStorage, id: &str, tags: &[String], content: &str) -> Result<String> {
+    let mut output = String::with_capacity(content.len());
+    let mut lines = content.split_inclusive('\n').enumerate();
+    let mut in_fence = false;
+
+    while let Some((number, line)) = lines.next() {
+        if is_fence(line) {
+            in_fence = !in_fence;
+        }
+        let text = if in_fence { None } else { begin_marker(line).or_else(|| placeholder(line)) };
+        let Some(directive) = text.map(Directive::parse).transpose()?.flatten() else {
+            output.push_str(line);
+            continue;
+        };
+        let text = text.unwrap_or_default().trim();
+
+        // 已有的生成块：丢弃旧内容直到结束标记
+        if begin_marker(line).is_some() && !lines.by_ref().any(|(_, line)| line.trim() == END_MARKER) {
+            return Err(MemexiaError::Validation(format!(
+                "Generated block at line {} has no closing `{}`",
+                number + 1,
+                END_MARKER
+            )));
+        }
+
+        let indent = &line[..line.len() - line.trim_start().len()];
+        output.push_str(&format!("{}{}{} -->\n", indent, BEGIN_MARKER, text));
+        for node in directive.query(graph, id, tags)? {
+            output.push_str(&format!("{}{}\n", indent, list_item(&node)));
+        }
+        output.push_str(&format!("{}{}\n", indent, END_MARKER));
+    }
+
+    Ok(output)
+}
+
+/// 把生成块（含标记行）替换为空行，行号保持不变
+///
+/// 生成块中的链接由图谱推出，索引时不应再作为本笔记的出链
+pub fn mask_generated(content: &str) -> Cow<'_, str> {
+    if !content.contains(BEGIN_MARKER) {
+        return Cow::Borrowed(content);
+    }
+
+    let mut output = String::with_capacity(content.len());
+    let mut in_block = false;
+    for line in content.split_inclusive('\n') {
+        if !in_block && begin_marker(line).is_some() {
+            in_block = true;
+        }
+        if in_block {
+            if line.trim() == END_MARKER {
+                in_block = false;
+            }
+            if line.ends_with('\n') {
+                output.push('\n');
+            }
+        } else {
+            output.push_str(line);
+        }
+    }
+    Cow::Owned(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_directive() {
+        assert_eq!(Directive::parse("title").unwrap(), None);
+        assert_eq!(
+            Directive::parse("related").unwrap(),
+            Some(Directive::Related { limit: DEFAULT_RELATED_LIMIT })
+        );
+        assert_eq!(
+            Directive::parse(" children limit=3 ").unwrap(),
+            Some(Directive::Children { relation: RelationType::Contains, limit: Some(3) })
+        );
+        assert_eq!(
+            Directive::parse("backlinks relation=Supports").unwrap(),
+            Some(Directive::Backlinks { relation: Some(RelationType::Supports), limit: None })
+        );
+        assert!(Directive::parse("related relation=Supports").is_err());
+        assert!(Directive::parse("backlinks limit=many").is_err());
+        assert!(Directive::parse("children sort").is_err());
+    }
+
+    #[test]
+    fn test_mask_generated() {
+        let content = "# A\n<!-- memexia:begin backlinks -->\n- [[b.md]] B\n<!-- memexia:end -->\n[[c.md]]\n";
+        assert_eq!(mask_generated(content), "# A\n\n\n\n[[c.md]]\n");
+        assert!(matches!(mask_generated("# A\n[[c.md]]"), Cow::Borrowed(_)));
+    }
+}
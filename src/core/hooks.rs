@@ -0,0 +1,449 @@
+//! 钩子模块
+//!
+//! 提交或索引完成后通知外部系统（如静态站点重建、仪表盘刷新）
+//!
+//! ## 支持的钩子
+//!
+//! - `hooks.post_commit_url` - 提交成功后 POST JSON payload
+//! - `hooks.post_index_url` - 索引批次完成后 POST JSON payload
+//! - `hooks.post_commit_cmd` - 提交成功后执行本地命令，payload 通过 stdin 传入
+//! - `hooks.pre_commit_validate` - 提交前运行图验证规则
+//!
+//! 通知类钩子失败只记录警告，不会导致命令失败；HTTP 请求失败时最多重试一次。
+//! 提交前验证发现错误级别违规时中止提交
+
+use crate::core::config::{HooksConfig, RepoConfig};
+use crate::core::indexer::IndexSummary;
+use crate::core::validate::{Severity, Validator};
+use crate::error::MemexiaError;
+use crate::events::{EventBus, RepoEvent};
+use crate::storage::GraphStorage;
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// 提交钩子 payload
+#[derive(Debug, Clone, Serialize)]
+pub struct CommitPayload {
+    /// 事件名称，固定为 `post_commit`
+    pub event: &'static str,
+    /// 提交 OID
+    pub commit: String,
+    /// 提交消息
+    pub message: String,
+    /// 提交的文件（相对仓库根目录）
+    pub files: Vec<String>,
+    /// 图变更统计
+    pub graph_delta: GraphDeltaStats,
+}
+
+/// 图变更统计
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct GraphDeltaStats {
+    /// 新增的三元组数
+    pub added: usize,
+    /// 删除的三元组数
+    pub removed: usize,
+}
+
+/// 索引钩子 payload
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexPayload {
+    /// 事件名称，固定为 `post_index`
+    pub event: &'static str,
+    /// 索引的文件数
+    pub files_indexed: usize,
+    /// 跳过的文件数
+    pub files_skipped: usize,
+    /// 删除的文件数
+    pub files_deleted: usize,
+    /// 创建的节点数
+    pub nodes_created: usize,
+    /// 创建的边数
+    pub edges_created: usize,
+    /// 错误数
+    pub errors: usize,
+}
+
+impl CommitPayload {
+    /// 创建提交 payload
+    pub fn new(commit: &str, message: &str, files: Vec<String>, graph_delta: GraphDeltaStats) -> Self {
+        Self {
+            event: "post_commit",
+            commit: commit.to_string(),
+            message: message.to_string(),
+            files,
+            graph_delta,
+        }
+    }
+}
+
+impl From<&IndexSummary> for IndexPayload {
+    fn from(summary: &IndexSummary) -> Self {
+        Self {
+            event: "post_index",
+            files_indexed: summary.files_indexed,
+            files_skipped: summary.files_skipped,
+            files_deleted: summary.files_deleted,
+            nodes_created: summary.nodes_created,
+            edges_created: summary.edges_created,
+            errors: summary.errors.len(),
+        }
+    }
+}
+
+/// 提交前验证
+///
+/// `hooks.pre_commit_validate` 开启时按 `[validate]` 配置运行验证规则，
+/// 存在错误级别违规时发出 [`RepoEvent::ValidationFailed`] 并返回 `Validation` 错误；警告只记录日志
+pub fn run_pre_commit(
+    config: &RepoConfig,
+    graph: &dyn GraphStorage,
+    validator: &Validator,
+    events: &EventBus,
+) -> crate::error::Result<()> {
+    if !config.hooks.pre_commit_validate {
+        return Ok(());
+    }
+
+    let report = validator.run(graph, &config.validate)?;
+    let warnings = report.count(Severity::Warning);
+    if warnings > 0 {
+        warn!("pre_commit validation found {} warning(s)", warnings);
+    }
+
+    let errors = report.count(Severity::Error);
+    if errors > 0 {
+        events.emit(RepoEvent::ValidationFailed {
+            errors: report
+                .violations()
+                .filter(|v| v.severity == Severity::Error)
+                .map(|v| format!("{}: {}", v.target, v.message))
+                .collect(),
+        });
+        return Err(MemexiaError::Validation(format!(
+            "Pre-commit validation failed with {} error(s); run `memexia validate` for details",
+            errors
+        )));
+    }
+    Ok(())
+}
+
+/// 触发提交钩子
+///
+/// 依次执行 `post_commit_url` 和 `post_commit_cmd`，失败只记录警告
+pub fn run_post_commit(config: &HooksConfig, payload: &CommitPayload) {
+    let body = match serde_json::to_string(payload) {
+        Ok(body) => body,
+        Err(e) => {
+            warn!("Failed to serialize post_commit payload: {}", e);
+            return;
+        }
+    };
+    let timeout = Duration::from_millis(config.timeout_ms);
+
+    if let Some(url) = &config.post_commit_url {
+        if let Err(e) = post_json_with_retry(url, &body, timeout) {
+            warn!("post_commit hook to {} failed: {:#}", url, e);
+        }
+    }
+
+    if let Some(cmd) = &config.post_commit_cmd {
+        if let Err(e) = run_command(cmd, &body, timeout) {
+            warn!("post_commit command '{}' failed: {:#}", cmd, e);
+        }
+    }
+}
+
+/// 触发索引钩子
+pub fn run_post_index(config: &HooksConfig, summary: &IndexSummary) {
+    let Some(url) = &config.post_index_url else {
+        return;
+    };
+
+    let body = match serde_json::to_string(&IndexPayload::from(summary)) {
+        Ok(body) => body,
+        Err(e) => {
+            warn!("Failed to serialize post_index payload: {}", e);
+            return;
+        }
+    };
+
+    if let Err(e) = post_json_with_retry(url, &body, Duration::from_millis(config.timeout_ms)) {
+        warn!("post_index hook to {} failed: {:#}", url, e);
+    }
+}
+
+/// POST JSON，失败时最多重试一次
+fn post_json_with_retry(url: &str, body: &str, timeout: Duration) -> Result<()> {
+    match post_json(url, body, timeout) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            debug!("Hook request to {} failed, retrying: {:#}", url, e);
+            post_json(url, body, timeout)
+        }
+    }
+}
+
+/// 通过 HTTP/1.1 POST JSON
+///
+/// 仅支持 `http://` URL
+fn post_json(url: &str, body: &str, timeout: Duration) -> Result<()> {
+    let (host, port, path) = parse_http_url(url)?;
+
+    let addr = (host.as_str(), port)
+        .to_socket_addrs()
+        .with_context(|| format!("Failed to resolve {}", host))?
+        .next()
+        .with_context(|| format!("No address found for {}", host))?;
+
+    let mut stream = TcpStream::connect_timeout(&addr, timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+
+    let request = format!(
+        "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\nUser-Agent: memexia/{}\r\n\r\n{}",
+        path,
+        host,
+        port,
+        body.len(),
+        env!("CARGO_PKG_VERSION"),
+        body
+    );
+    stream.write_all(request.as_bytes())?;
+    stream.flush()?;
+
+    let mut response = Vec::new();
+    let mut buf = [0u8; 1024];
+    // 只需读取状态行
+    while !response.windows(2).any(|w| w == b"\r\n") {
+        let n = stream.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        response.extend_from_slice(&buf[..n]);
+    }
+
+    let response = String::from_utf8_lossy(&response);
+    let status_line = response.lines().next().unwrap_or_default();
+    let status: u16 = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .with_context(|| format!("Invalid HTTP response: {:?}", status_line))?;
+
+    if !(200..300).contains(&status) {
+        bail!("Server responded with status {}", status);
+    }
+
+    Ok(())
+}
+
+/// 解析 `http://host[:port][/path]`
+fn parse_http_url(url: &str) -> Result<(String, u16, String)> {
+    let Some(rest) = url.strip_prefix("http://") else {
+        bail!("Unsupported hook URL (only http:// is supported): {}", url);
+    };
+
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], rest[i..].to_string()),
+        None => (rest, "/".to_string()),
+    };
+
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (
+            host.to_string(),
+            port.parse().with_context(|| format!("Invalid port in URL: {}", url))?,
+        ),
+        None => (authority.to_string(), 80),
+    };
+
+    if host.is_empty() {
+        bail!("Missing host in URL: {}", url);
+    }
+
+    Ok((host, port, path))
+}
+
+/// 执行本地命令并通过 stdin 传入 payload
+fn run_command(cmd: &str, body: &str, timeout: Duration) -> Result<()> {
+    #[cfg(windows)]
+    let mut command = {
+        let mut c = Command::new("cmd");
+        c.args(["/C", cmd]);
+        c
+    };
+    #[cfg(not(windows))]
+    let mut command = {
+        let mut c = Command::new("sh");
+        c.args(["-c", cmd]);
+        c
+    };
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .context("Failed to spawn hook command")?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(body.as_bytes())?;
+    }
+
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            if !status.success() {
+                bail!("Command exited with {}", status);
+            }
+            return Ok(());
+        }
+        if start.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            bail!("Command timed out after {:?}", timeout);
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::thread;
+
+    /// 启动本地测试服务器，按顺序返回给定状态码，并通过通道回传请求体
+    fn spawn_server(statuses: Vec<u16>) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            for status in statuses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut data = Vec::new();
+                let mut buf = [0u8; 4096];
+                loop {
+                    let n = stream.read(&mut buf).unwrap();
+                    data.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&data).to_string();
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let len: usize = head
+                            .lines()
+                            .find_map(|l| l.strip_prefix("Content-Length: "))
+                            .and_then(|v| v.trim().parse().ok())
+                            .unwrap_or(0);
+                        if body.len() >= len {
+                            tx.send(body.to_string()).unwrap();
+                            break;
+                        }
+                    }
+                    if n == 0 {
+                        break;
+                    }
+                }
+                let response = format!("HTTP/1.1 {} OK\r\nContent-Length: 0\r\n\r\n", status);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        (url, rx)
+    }
+
+    fn sample_payload() -> CommitPayload {
+        CommitPayload::new(
+            "abc123",
+            "Add notes",
+            vec!["notes/a.md".to_string()],
+            GraphDeltaStats { added: 3, removed: 1 },
+        )
+    }
+
+    #[test]
+    fn test_post_commit_url_receives_payload() {
+        let (url, rx) = spawn_server(vec![200]);
+        let config = HooksConfig {
+            post_commit_url: Some(url),
+            ..Default::default()
+        };
+
+        run_post_commit(&config, &sample_payload());
+
+        let body = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["event"], "post_commit");
+        assert_eq!(json["commit"], "abc123");
+        assert_eq!(json["files"][0], "notes/a.md");
+        assert_eq!(json["graph_delta"]["added"], 3);
+    }
+
+    #[test]
+    fn test_post_retries_once() {
+        let (url, rx) = spawn_server(vec![500, 200]);
+
+        assert!(post_json_with_retry(&url, "{}", Duration::from_secs(5)).is_ok());
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), "{}");
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), "{}");
+    }
+
+    #[test]
+    fn test_post_index_url_receives_summary() {
+        let (url, rx) = spawn_server(vec![200]);
+        let config = HooksConfig {
+            post_index_url: Some(url),
+            ..Default::default()
+        };
+        let summary = IndexSummary {
+            files_indexed: 2,
+            nodes_created: 2,
+            edges_created: 5,
+            ..Default::default()
+        };
+
+        run_post_index(&config, &summary);
+
+        let body = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["event"], "post_index");
+        assert_eq!(json["edges_created"], 5);
+    }
+
+    #[test]
+    fn test_unreachable_url_does_not_panic() {
+        let config = HooksConfig {
+            post_commit_url: Some("http://127.0.0.1:1/hook".to_string()),
+            timeout_ms: 200,
+            ..Default::default()
+        };
+        run_post_commit(&config, &sample_payload());
+
+        assert!(parse_http_url("https://example.com").is_err());
+        assert_eq!(
+            parse_http_url("http://localhost:8080/a/b").unwrap(),
+            ("localhost".to_string(), 8080, "/a/b".to_string())
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_post_commit_cmd_receives_stdin() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let out = temp_dir.path().join("payload.json");
+        let config = HooksConfig {
+            post_commit_cmd: Some(format!("cat > '{}'", out.display())),
+            ..Default::default()
+        };
+
+        run_post_commit(&config, &sample_payload());
+
+        let content = std::fs::read_to_string(&out).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(json["message"], "Add notes");
+    }
+}
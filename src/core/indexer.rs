@@ -9,13 +9,33 @@
 //! - 增量更新
 //! - 变更检测
 
-use crate::core::parser::parse_markdown;
+use crate::core::encrypted;
+use crate::core::folder_defaults::FolderDefaults;
+use crate::core::link_refresh;
+use crate::core::object;
+use crate::core::parser::{parse_file, parse_file_with_default, ParseWarning};
+use crate::core::paths;
+use crate::core::summary;
 use crate::core::watch_config::WatchConfig;
-use crate::storage::{Node, NodeType, Storage};
+use crate::core::zettel;
+use crate::events::RepoEvent;
+use crate::storage::{ids, GraphStorage, Node, NodeType, RelationType, Storage};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use walkdir::WalkDir;
 
+/// 默认的文件大小上限（字节）
+pub const DEFAULT_MAX_NOTE_BYTES: u64 = 4 * 1024 * 1024;
+
+/// 默认保存在节点上的正文长度（字节）
+pub const DEFAULT_CONTENT_EXCERPT_BYTES: usize = 64 * 1024;
+
+/// 检测二进制内容时检查的开头字节数
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+
 /// 索引器
 ///
 /// 负责将文件系统中的 Markdown 文件解析并索引到知识图谱
@@ -24,6 +44,28 @@ pub struct Indexer {
     storage: Storage,
     /// 文件监听配置
     config: WatchConfig,
+    /// 是否启用 Zettelkasten ID
+    zettel_ids: bool,
+    /// 参与索引的文件扩展名
+    extensions: Vec<String>,
+    /// 加密笔记的解密命令
+    decrypt_cmd: Option<String>,
+    /// 自动遍历的笔记根目录（相对仓库根目录），为空时不限制
+    note_roots: Vec<String>,
+    /// 全量遍历时是否检测重命名的文件
+    rename_detection: bool,
+    /// 外部提供的重命名线索（旧路径，新路径），如 Git 的重命名检测结果
+    rename_hints: Vec<(PathBuf, PathBuf)>,
+    /// 目录默认值（没有 frontmatter 的文件取目录默认的节点类型）
+    folder_defaults: FolderDefaults,
+    /// 文件大小上限（字节）
+    max_note_bytes: u64,
+    /// 节点上保存的正文长度上限（字节）
+    content_excerpt_bytes: usize,
+    /// 未写关系的 wiki 链接使用的关系
+    default_relation: RelationType,
+    /// 是否为没有摘要的笔记自动提取摘要
+    auto_summary: bool,
 }
 
 impl Indexer {
@@ -40,6 +82,17 @@ impl Indexer {
         Self {
             storage,
             config: WatchConfig::new(),
+            zettel_ids: false,
+            extensions: vec!["md".to_string()],
+            decrypt_cmd: None,
+            note_roots: Vec::new(),
+            rename_detection: true,
+            rename_hints: Vec::new(),
+            folder_defaults: FolderDefaults::default(),
+            max_note_bytes: DEFAULT_MAX_NOTE_BYTES,
+            content_excerpt_bytes: DEFAULT_CONTENT_EXCERPT_BYTES,
+            default_relation: RelationType::RelatedTo,
+            auto_summary: false,
         }
     }
 
@@ -54,7 +107,21 @@ impl Indexer {
     ///
     /// 索引器实例
     pub fn with_config(storage: Storage, config: WatchConfig) -> Self {
-        Self { storage, config }
+        Self {
+            storage,
+            config,
+            zettel_ids: false,
+            extensions: vec!["md".to_string()],
+            decrypt_cmd: None,
+            note_roots: Vec::new(),
+            rename_detection: true,
+            rename_hints: Vec::new(),
+            folder_defaults: FolderDefaults::default(),
+            max_note_bytes: DEFAULT_MAX_NOTE_BYTES,
+            content_excerpt_bytes: DEFAULT_CONTENT_EXCERPT_BYTES,
+            default_relation: RelationType::RelatedTo,
+            auto_summary: false,
+        }
     }
 
     /// 更新配置
@@ -66,6 +133,138 @@ impl Indexer {
         self.config = config;
     }
 
+    /// 启用或禁用 Zettelkasten ID
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - 是否从文件名前缀提取时间戳 ID 作为节点身份
+    pub fn set_zettel_ids(&mut self, enabled: bool) {
+        self.zettel_ids = enabled;
+    }
+
+    /// 设置参与索引的文件扩展名（不含点，如 `md`、`org`）
+    ///
+    /// 监听配置的白名单非空时，同时为新增扩展名加入 `*.<ext>` 模式
+    ///
+    /// # Arguments
+    ///
+    /// * `extensions` - 扩展名列表
+    pub fn set_extensions(&mut self, extensions: Vec<String>) {
+        for ext in &extensions {
+            let pattern = format!("*.{}", ext);
+            if !self.config.whitelist.is_empty() && !self.config.whitelist.contains(&pattern) {
+                self.config.add_whitelist(pattern);
+            }
+        }
+        self.extensions = extensions;
+    }
+
+    /// 设置加密笔记的解密命令
+    ///
+    /// # Arguments
+    ///
+    /// * `cmd` - 解密命令；为 None 时加密笔记只生成占位节点
+    pub fn set_decrypt_cmd(&mut self, cmd: Option<String>) {
+        self.decrypt_cmd = cmd;
+    }
+
+    /// 设置自动遍历的笔记根目录
+    ///
+    /// 设置后 [`index_all`](Self::index_all) 和文件事件只处理这些目录下的文件；
+    /// 显式调用 [`index_file`](Self::index_file) 不受限制
+    ///
+    /// # Arguments
+    ///
+    /// * `dirs` - 相对仓库根目录的目录列表，`.` 表示仓库根目录；为空时不限制
+    pub fn set_note_roots(&mut self, dirs: &[String]) {
+        let root = self.storage.root();
+        self.note_roots = dirs
+            .iter()
+            .map(|dir| paths::normalize_input(&root.join(dir), root).into_string())
+            .collect();
+    }
+
+    /// 启用或禁用重命名检测（默认启用）
+    ///
+    /// 启用时 [`index_all`](Self::index_all) 把新出现的文件与文件已缺失的节点配对，
+    /// 迁移节点身份而不是新建节点
+    pub fn set_rename_detection(&mut self, enabled: bool) {
+        self.rename_detection = enabled;
+    }
+
+    /// 设置重命名线索
+    ///
+    /// 内容哈希无法配对的文件（如重命名同时修改了内容）按线索配对
+    ///
+    /// # Arguments
+    ///
+    /// * `hints` - （旧路径，新路径）列表，相对路径相对仓库根目录
+    pub fn set_rename_hints(&mut self, hints: Vec<(PathBuf, PathBuf)>) {
+        self.rename_hints = hints;
+    }
+
+    /// 设置目录默认值
+    ///
+    /// 没有 frontmatter 的文件使用匹配目录的默认节点类型，见 [`crate::core::folder_defaults`]
+    pub fn set_folder_defaults(&mut self, defaults: FolderDefaults) {
+        self.folder_defaults = defaults;
+    }
+
+    /// 设置文件大小上限和节点正文长度上限
+    ///
+    /// # Arguments
+    ///
+    /// * `max_note_bytes` - 超过该大小的文件跳过索引（[`SkipReason::TooLarge`]）
+    /// * `content_excerpt_bytes` - 更长的正文在节点上只保存开头部分，见 [`Node::truncate_content`]
+    pub fn set_size_limits(&mut self, max_note_bytes: u64, content_excerpt_bytes: usize) {
+        self.max_note_bytes = max_note_bytes;
+        self.content_excerpt_bytes = content_excerpt_bytes;
+    }
+
+    /// 设置未写关系的 wiki 链接使用的关系（配置 `links.default_relation`）
+    pub fn set_default_relation(&mut self, relation: RelationType) {
+        self.default_relation = relation;
+    }
+
+    /// 启用或禁用自动摘要（配置 `index.auto_summary`，见 [`crate::core::summary`]）
+    pub fn set_auto_summary(&mut self, enabled: bool) {
+        self.auto_summary = enabled;
+    }
+
+    /// 文件是否位于笔记根目录下（未设置根目录时总是 true）
+    pub fn in_note_roots(&self, path: &Path) -> bool {
+        if self.note_roots.is_empty() {
+            return true;
+        }
+        let rel = paths::normalize_input(path, self.storage.root());
+        if rel.is_external() {
+            return false;
+        }
+        let rel = rel.as_str();
+        self.note_roots.iter().any(|dir| {
+            dir == "." || rel == dir || rel.strip_prefix(dir.as_str()).is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+
+    /// 文件扩展名是否参与索引
+    pub fn accepts(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| self.extensions.iter().any(|e| e == ext))
+    }
+
+    /// 文件是否会被索引
+    ///
+    /// 需通过黑白名单，且扩展名参与索引（默认只有 Markdown；加密笔记总是识别）
+    pub fn is_indexable(&self, path: &Path) -> bool {
+        self.config.is_allowed(path) && (self.accepts(path) || encrypted::is_encrypted(path))
+    }
+
+    /// 获取存储后端
+    pub fn storage(&self) -> &Storage {
+        &self.storage
+    }
+
     /// 获取当前配置
     ///
     /// # Returns
@@ -85,48 +284,212 @@ impl Indexer {
     ///
     /// 索引结果
     pub fn index_file(&self, path: &Path) -> anyhow::Result<IndexResult> {
-        // 检查文件是否被允许
-        if !self.config.is_allowed(path) {
+        self.index_file_replacing(path, None)
+    }
+
+    /// 索引单个文件，并在同一个图事务中删除被它取代的旧节点
+    ///
+    /// 节点、占位目标和边的写入要么全部生效，要么全部撤销
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 文件路径
+    /// * `replaces` - 要删除的旧节点 ID（如文件重命名前的节点）
+    fn index_file_replacing(&self, path: &Path, replaces: Option<&str>) -> anyhow::Result<IndexResult> {
+        if !self.is_indexable(path) {
             return Ok(IndexResult::Skipped);
         }
+        let is_encrypted = encrypted::is_encrypted(path);
 
-        // 检查是否为 Markdown 文件
-        if path.extension().and_then(|e| e.to_str()) != Some("md") {
-            return Ok(IndexResult::Skipped);
+        let relative_path = paths::normalize_input(path, self.storage.root()).into_string();
+        // 超过大小上限的文件不读入内存
+        let size = fs::metadata(path)?.len();
+        if let Some(reason) = check_note(size, &[], self.max_note_bytes) {
+            return Ok(self.reject(relative_path, reason));
+        }
+        let bytes = fs::read(path)?;
+        if !is_encrypted {
+            if let Some(reason) = check_note(size, &bytes, self.max_note_bytes) {
+                return Ok(self.reject(relative_path, reason));
+            }
+        }
+        let content_hash = object::hash_content(&bytes);
+
+        // 未配置解密命令的加密笔记只生成占位节点
+        if is_encrypted && self.decrypt_cmd.is_none() {
+            let mut node = encrypted::stub_node(&self.path_to_id(path), &relative_path);
+            node.set_content_hash(content_hash);
+            let replaced = self.node_if_subscribed(replaces)?;
+            self.storage.graph().transaction(|graph| {
+                if let Some(old_id) = replaces {
+                    graph.delete_node(old_id)?;
+                }
+                graph.add_node(&node)
+            })?;
+            self.emit_deleted(replaced);
+            self.storage.events().emit(RepoEvent::node_indexed(&node, &relative_path));
+            return Ok(IndexResult::Indexed {
+                path: relative_path,
+                node_count: 1,
+                edge_count: 0,
+                warnings: Vec::new(),
+            });
         }
 
-        // 读取文件内容
-        let content = fs::read_to_string(path)?;
-        let relative_path = self.get_relative_path(path)?;
+        // 读取文件内容（加密笔记解密到内存）
+        let content = match &self.decrypt_cmd {
+            Some(cmd) if is_encrypted => encrypted::decrypt(cmd, path)?,
+            _ => String::from_utf8(bytes)?,
+        };
 
         // 解析文档
-        let doc = parse_markdown(&content, &relative_path);
-
-        // 创建节点
-        let node = doc.to_node();
-        self.storage.graph().add_node(&node)?;
-
-        // 创建边
-        for link in &doc.wiki_links {
-            let target_id = self.get_target_id(&link.target);
+        let (doc, warnings) = tracing::info_span!("parse", file = %relative_path)
+            .in_scope(|| parse_file_with_default(&content, &relative_path, &self.default_relation));
+        for warning in &warnings {
+            tracing::warn!(file = %relative_path, line = warning.line, kind = %warning.kind, "{}", warning.message);
+        }
 
-            // 确保目标节点存在
-            if !self.storage.graph().node_exists(&target_id)? {
-                let target_node = Node::new(&target_id, NodeType::Concept, &link.target);
-                self.storage.graph().add_node(&target_node)?;
+        let _write_span = tracing::info_span!("storage_write", file = %relative_path).entered();
+
+        // noindex 笔记不生成节点和边，之前的索引结果一并移除
+        if doc.noindex {
+            let node_id = self.path_to_id(path);
+            let replaced = self.node_if_subscribed(replaces)?;
+            let events = self.storage.events();
+            let excluded = self.node_if_subscribed(Some(&node_id))?.filter(|node| !node.is_excluded());
+            let edges = events.outgoing_edges(self.storage.graph(), &node_id)?;
+            self.storage.graph().transaction(|graph| {
+                if let Some(old_id) = replaces {
+                    graph.delete_node(old_id)?;
+                }
+                exclude_node(graph, &node_id, &doc.get_title())
+            })?;
+            self.emit_deleted(replaced);
+            if let Some(node) = excluded {
+                for edge in &edges {
+                    events.emit(RepoEvent::edge_removed(edge));
+                }
+                events.emit(RepoEvent::node_deleted(&node));
             }
+            return Ok(IndexResult::Excluded(relative_path));
+        }
 
-            let edge = link.to_edge(&node.id);
-            self.storage.graph().add_edge(&edge)?;
+        // 创建节点（加密笔记不保存正文）
+        let mut node = if self.zettel_ids {
+            doc.to_zettel_node()
+        } else {
+            doc.to_node()
+        };
+        if self.auto_summary {
+            summary::apply(&mut node, &content, &relative_path);
         }
+        if is_encrypted {
+            encrypted::mark_encrypted(&mut node);
+        }
+        if doc.frontmatter.is_none() {
+            if let Some(node_type) = self.folder_defaults.node_type_for(&relative_path) {
+                node.node_type = node_type;
+            }
+        }
+        node.truncate_content(self.content_excerpt_bytes, &relative_path);
+        node.set_content_hash(content_hash);
+        let replaced = self.node_if_subscribed(replaces)?;
+        let events = self.storage.events();
+        let edges_before = events.outgoing_edges(self.storage.graph(), &node.id)?;
+        let promoted = self.storage.graph().transaction(|graph| {
+            if let Some(old_id) = replaces {
+                graph.delete_node(old_id)?;
+            }
+            link_refresh::track_title_change(graph, &mut node)?;
+            let promoted = promote_placeholder(graph, &mut node)?;
+
+            // 创建边
+            for link in &doc.wiki_links {
+                let target_id = self.get_target_id(&link.target);
+
+                // 确保目标节点存在（指向 noindex 笔记的占位节点带排除标记）
+                if !graph.node_exists(&target_id)? {
+                    let mut target_node = Node::builder(&target_id, NodeType::Concept, &link.target).build()?;
+                    if self.is_excluded_target(&link.target, &target_id) {
+                        target_node.set_excluded();
+                    }
+                    graph.add_node(&target_node)?;
+                }
 
+                let edge = link.to_edge_with_target(&node.id, &target_id)?;
+                graph.add_edge(&edge)?;
+            }
+            Ok(promoted)
+        })?;
+        self.emit_deleted(replaced);
+        events.emit(RepoEvent::node_indexed(&node, &relative_path));
+        events.emit_edge_changes(self.storage.graph(), &node.id, &edges_before)?;
+
+        if promoted {
+            return Ok(IndexResult::Promoted {
+                path: relative_path,
+                edge_count: doc.wiki_links.len(),
+                warnings,
+            });
+        }
         Ok(IndexResult::Indexed {
             path: relative_path,
             node_count: 1,
             edge_count: doc.wiki_links.len(),
+            warnings,
         })
     }
 
+    /// 拒绝索引文件
+    fn reject(&self, path: String, reason: SkipReason) -> IndexResult {
+        tracing::warn!(file = %path, "skipped: {}", reason);
+        IndexResult::Rejected { path, reason }
+    }
+
+    /// 即将删除的节点，用于之后发出删除事件；没有事件订阅者时不查询
+    fn node_if_subscribed(&self, id: Option<&str>) -> anyhow::Result<Option<Node>> {
+        match id {
+            Some(id) if self.storage.events().is_active() => Ok(self.storage.graph().get_node(id)?),
+            _ => Ok(None),
+        }
+    }
+
+    /// 发出节点删除事件
+    fn emit_deleted(&self, node: Option<Node>) {
+        if let Some(node) = node {
+            self.storage.events().emit(RepoEvent::node_deleted(&node));
+        }
+    }
+
+    /// 文件内容是否与上次索引时一致
+    ///
+    /// 比较文件当前内容哈希与节点记录的 `memexia:contentHash`；
+    /// 节点不存在、已软删除或没有哈希记录时视为已变更
+    pub fn is_unchanged(&self, path: &Path) -> anyhow::Result<bool> {
+        let Some(node) = self.storage.graph().get_node(&self.path_to_id(path))? else {
+            return Ok(false);
+        };
+        if node.is_deleted() {
+            return Ok(false);
+        }
+        let Some(stored) = node.content_hash() else {
+            return Ok(false);
+        };
+        Ok(object::hash_content(&fs::read(path)?) == stored)
+    }
+
+    /// 增量索引单个文件：内容未变化时跳过
+    ///
+    /// # Returns
+    ///
+    /// 内容未变化或不可索引时返回 [`IndexResult::Skipped`]
+    pub fn index_changed(&self, path: &Path) -> anyhow::Result<IndexResult> {
+        if !self.is_indexable(path) || self.is_unchanged(path)? {
+            return Ok(IndexResult::Skipped);
+        }
+        self.index_file(path)
+    }
+
     /// 索引单个文件（异步）
     ///
     /// # Arguments
@@ -140,9 +503,9 @@ impl Indexer {
         self.index_file(path)
     }
 
-    /// 全量索引目录
+    /// 增量索引目录
     ///
-    /// 遍历目录中的所有文件并索引
+    /// 遍历目录中的所有文件，内容自上次索引以来未变化的文件被跳过
     ///
     /// # Arguments
     ///
@@ -152,27 +515,115 @@ impl Indexer {
     ///
     /// 索引汇总结果
     pub fn index_all(&self, root: &Path) -> anyhow::Result<IndexSummary> {
+        let start = Instant::now();
         let mut summary = IndexSummary::default();
 
-        for entry in WalkDir::new(root)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let path = entry.path();
+        let files: Vec<PathBuf> = tracing::info_span!("walkdir", root = %root.display()).in_scope(|| {
+            WalkDir::new(root)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.path().is_file() && self.in_note_roots(e.path()))
+                .map(|e| e.into_path())
+                .collect()
+        });
 
-            if !path.is_file() {
+        let renamed = if self.rename_detection {
+            self.apply_renames(&files, &mut summary)?
+        } else {
+            HashSet::new()
+        };
+
+        for path in files.iter().filter(|path| !renamed.contains(*path)) {
+            match self.index_changed(path) {
+                Ok(result) => summary.add(result),
+                Err(e) => {
+                    crate::metrics::record_index_error();
+                    summary.errors.push((path.to_string_lossy().into_owned(), e.to_string()));
+                }
+            }
+        }
+
+        summary.elapsed = start.elapsed();
+        Ok(summary)
+    }
+
+    /// 检测并执行重命名
+    ///
+    /// 候选旧节点是带内容哈希、文件已不存在的文件节点（含已软删除的）；候选新文件是图中
+    /// 还没有节点的文件。先按重命名线索配对，再按内容哈希唯一配对。配对成功的新文件
+    /// 立即被索引，随后旧节点的身份迁移到新节点
+    ///
+    /// # Returns
+    ///
+    /// 已处理的新文件路径
+    fn apply_renames(&self, files: &[PathBuf], summary: &mut IndexSummary) -> anyhow::Result<HashSet<PathBuf>> {
+        let root = self.storage.root();
+        let graph = self.storage.graph();
+
+        let mut missing: HashMap<String, String> = HashMap::new();
+        for node in graph.list_nodes_filtered(true)? {
+            if !node.id.starts_with(ids::FILE_PREFIX) || root.join(ids::display_file_id(&node.id)).exists() {
                 continue;
             }
+            if let Some(hash) = node.content_hash() {
+                missing.insert(node.id.clone(), hash.to_string());
+            }
+        }
+        if missing.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let mut fresh: Vec<&PathBuf> = Vec::new();
+        for path in files {
+            if self.is_indexable(path) && !graph.node_exists(&self.path_to_id(path))? {
+                fresh.push(path);
+            }
+        }
+
+        let mut pairs: Vec<(String, &PathBuf)> = Vec::new();
+        for (from, to) in &self.rename_hints {
+            let old_id = self.path_to_id(&root.join(from));
+            let to = root.join(to);
+            let new_id = self.path_to_id(&to);
+            if let Some(index) = fresh.iter().position(|path| self.path_to_id(path) == new_id) {
+                if missing.remove(&old_id).is_some() {
+                    pairs.push((old_id, fresh.remove(index)));
+                }
+            }
+        }
+
+        // 内容哈希只在两边都唯一时配对，避免把相同内容的模板笔记错配
+        let mut by_hash: HashMap<&str, Vec<&String>> = HashMap::new();
+        for (id, hash) in &missing {
+            by_hash.entry(hash.as_str()).or_default().push(id);
+        }
+        let mut fresh_hashes: HashMap<String, Vec<&PathBuf>> = HashMap::new();
+        for path in fresh {
+            fresh_hashes.entry(object::hash_content(&fs::read(path)?)).or_default().push(path);
+        }
+        for (hash, paths) in &fresh_hashes {
+            if let (Some([old_id]), [path]) = (by_hash.get(hash.as_str()).map(Vec::as_slice), paths.as_slice()) {
+                pairs.push(((*old_id).clone(), *path));
+            }
+        }
 
+        let mut handled = HashSet::new();
+        for (old_id, path) in pairs {
             match self.index_file(path) {
                 Ok(result) => summary.add(result),
                 Err(e) => {
                     summary.errors.push((path.to_string_lossy().into_owned(), e.to_string()));
+                    continue;
                 }
             }
+            let new_id = self.path_to_id(path);
+            if new_id != old_id {
+                super::rename::rename_node(graph, &old_id, &new_id)?;
+                summary.renamed.push(super::rename::NodeRename { old_id, new_id });
+            }
+            handled.insert(path.clone());
         }
-
-        Ok(summary)
+        Ok(handled)
     }
 
     /// 重新索引（删除后重建）
@@ -193,13 +644,15 @@ impl Indexer {
         // 删除属于该目录的节点
         let root_prefix = format!("urn:memexia:file:");
         for node in existing_nodes {
-            if node.id.starts_with(&root_prefix) {
+            if node.id.starts_with(&root_prefix) || node.id.starts_with(zettel::ZETTEL_ID_PREFIX) {
                 self.storage.graph().delete_node(&node.id)?;
             }
         }
 
-        // 重新索引
-        self.index_all(root)
+        // 重新索引，并按写入后的标题重建标题索引
+        let summary = self.index_all(root)?;
+        self.storage.graph().rebuild_title_index()?;
+        Ok(summary)
     }
 
     /// 处理文件变更事件
@@ -210,95 +663,177 @@ impl Indexer {
     ///
     /// # Returns
     ///
-    /// 处理结果
+    /// 处理结果，同时计入 [`crate::metrics`] 的索引事件计数
     pub fn handle_event(&self, event: &super::watcher::FileEvent) -> anyhow::Result<IndexResult> {
-        match event {
-            super::watcher::FileEvent::Created(path) => {
-                let path = Path::new(path);
-                self.index_file(path)
+        let result = self.process_event(event);
+        crate::metrics::record_index_event(&result);
+        result
+    }
+
+    /// 处理文件事件（不记录指标）
+    fn process_event(&self, event: &super::watcher::FileEvent) -> anyhow::Result<IndexResult> {
+        // 笔记根目录之外的新增和修改不自动索引
+        if let super::watcher::FileEvent::Created(path) | super::watcher::FileEvent::Modified(path) = event {
+            if !self.in_note_roots(Path::new(path)) {
+                return Ok(IndexResult::Skipped);
             }
-            super::watcher::FileEvent::Modified(path) => {
-                let path = Path::new(path);
-                self.index_file(path)
+        }
+
+        match event {
+            // 内容与节点记录的哈希一致（如 `memexia node set` 写回的文件）时无需重新索引
+            super::watcher::FileEvent::Created(path) | super::watcher::FileEvent::Modified(path) => {
+                self.index_changed(Path::new(path))
             }
             super::watcher::FileEvent::Deleted(path) => {
-                // 删除节点
-                let node_id = self.path_to_id_string(path);
-                if self.storage.graph().node_exists(&node_id)? {
-                    self.storage.graph().delete_node(&node_id)?;
+                // 软删除节点：移入回收站，边进入墓碑状态，可通过 `memexia node restore` 恢复
+                let node_id = self.path_to_id(Path::new(path));
+                let node = self.node_if_subscribed(Some(&node_id))?;
+                if super::trash::soft_delete(self.storage.graph(), &node_id, chrono::Utc::now())? {
+                    self.emit_deleted(node);
                 }
                 Ok(IndexResult::Deleted(node_id))
             }
             super::watcher::FileEvent::Renamed(from, to) => {
-                // 先删除旧节点，再索引新文件
-                let from_id = self.path_to_id_string(from);
-                if self.storage.graph().node_exists(&from_id)? {
-                    self.storage.graph().delete_node(&from_id)?;
-                }
+                // 删除旧节点并索引新文件（同一个事务）
+                // Zettel 模式下仅重命名标题部分时身份不变，无需删除
+                let from_id = self.path_to_id(Path::new(from));
+                let replaces = (from_id != self.path_to_id(Path::new(to)) && self.storage.graph().node_exists(&from_id)?)
+                    .then_some(from_id.as_str());
                 let path = Path::new(to);
-                self.index_file(path)
+                if !self.in_note_roots(path) || !self.is_indexable(path) {
+                    if let Some(old_id) = replaces {
+                        self.storage.graph().delete_node(old_id)?;
+                    }
+                    return Ok(IndexResult::Skipped);
+                }
+                // 原子写回（临时文件重命名为笔记）后内容已同步
+                if replaces.is_none() && self.is_unchanged(path)? {
+                    return Ok(IndexResult::Skipped);
+                }
+                self.index_file_replacing(path, replaces)
             }
         }
     }
 
-    /// 获取相对路径
-    fn get_relative_path(&self, path: &Path) -> anyhow::Result<String> {
-        // 获取相对于仓库根目录的路径
-        let root = self.storage.root();
-
-        if let Ok(rel_path) = path.strip_prefix(root) {
-            Ok(rel_path.to_string_lossy().replace('\\', "/"))
-        } else {
-            // 如果不在根目录下，使用完整路径
-            Ok(path.to_string_lossy().replace('\\', "/"))
-        }
+    /// 将文件路径转换为节点 ID（带 URL 编码）
+    ///
+    /// 路径先经 [`paths::normalize_input`] 规范化，同一文件的不同输入形式得到相同 ID；
+    /// 启用 Zettelkasten ID 时优先使用文件名中的 ID
+    pub fn path_to_id(&self, path: &Path) -> String {
+        let rel_path = paths::normalize_input(path, self.storage.root());
+        self.path_to_id_string(rel_path.as_str())
     }
 
-    /// 将文件路径转换为节点 ID（带 URL 编码）
-    fn path_to_id(&self, path: &Path) -> String {
-        let rel_path = self.get_relative_path(path).unwrap_or_else(|_| {
-            path.to_string_lossy().replace('\\', "/")
-        });
-        let encoded = encode_iri_component(&rel_path);
-        format!("urn:memexia:file:{}", encoded)
+    /// 链接目标是否为带 `noindex` 标记的笔记文件
+    ///
+    /// 只识别以仓库相对路径书写的链接目标（`[[notes/private.md]]`）
+    fn is_excluded_target(&self, target: &str, target_id: &str) -> bool {
+        let path = self.storage.root().join(target);
+        if !path.is_file() || !self.is_indexable(&path) || self.path_to_id(&path) != target_id {
+            return false;
+        }
+        fs::read_to_string(&path)
+            .map(|content| parse_file(&content, target).0.noindex)
+            .unwrap_or(false)
     }
 
     /// 将字符串路径转换为节点 ID（带 URL 编码）
     fn path_to_id_string(&self, path_str: &str) -> String {
-        let encoded = encode_iri_component(path_str);
-        format!("urn:memexia:file:{}", encoded)
+        if self.zettel_ids {
+            if let Some(id) = zettel::extract_zettel_id(path_str) {
+                return zettel::zettel_node_id(id);
+            }
+        }
+
+        ids::file_id(path_str)
     }
 
-    /// 获取目标节点 ID
-    fn get_target_id(&self, target: &str) -> String {
+    /// 获取 wiki 链接目标对应的节点 ID
+    pub(crate) fn get_target_id(&self, target: &str) -> String {
         // 如果 target 已经是 URN 格式，直接使用
         if target.starts_with("urn:memexia:") {
             return target.to_string();
         }
 
+        // Zettel 模式下，以 ID 开头的目标解析到对应节点
+        if self.zettel_ids {
+            if let Some(id) = zettel::resolve_link_target(target) {
+                return id;
+            }
+        }
+
         // 否则作为文件名处理（需要编码）
-        let encoded = encode_iri_component(target);
-        format!("urn:memexia:file:{}", encoded)
+        ids::file_id(target)
     }
 }
 
-/// 对 IRI 路径组件进行 percent 编码
-fn encode_iri_component(s: &str) -> String {
-    let mut result = String::new();
-    for c in s.chars() {
-        // 只对 ASCII 字母数字和安全的符号不编码
-        if c.is_ascii_alphanumeric() || "-_.~!$&'()*+,;=:@/".contains(c) {
-            result.push(c);
-        } else {
-            let mut buf = [0u8; 4];
-            let encoded = c.encode_utf8(&mut buf);
-            for byte in encoded.as_bytes() {
-                result.push('%');
-                result.push_str(&format!("{:02X}", byte));
-            }
+/// 检查文件是否可以索引
+///
+/// # Arguments
+///
+/// * `size` - 文件大小（字节）
+/// * `bytes` - 文件内容（只检查开头 8 KB；为空时只检查大小）
+/// * `max_note_bytes` - 文件大小上限
+///
+/// # Returns
+///
+/// 不应索引时返回原因：超过大小上限，或开头含有 NUL 字节（很可能是被改成 `.md` 的二进制文件）
+pub fn check_note(size: u64, bytes: &[u8], max_note_bytes: u64) -> Option<SkipReason> {
+    if size > max_note_bytes {
+        return Some(SkipReason::TooLarge { size, limit: max_note_bytes });
+    }
+    bytes[..bytes.len().min(BINARY_SNIFF_BYTES)]
+        .contains(&0)
+        .then_some(SkipReason::Binary)
+}
+
+/// 写入文件节点；同 ID 的占位节点存在时将其提升为文件节点
+///
+/// 笔记先被链接、后被创建时，图中已有以链接文本为标题的占位节点。提升时文件的标题、
+/// 类型和属性整体替换占位默认值，创建时间取两者中较早的（想法在被链接时就已存在），
+/// 占位节点上的标签保留，指向它的入边不受影响
+///
+/// # Returns
+///
+/// 是否提升了占位节点
+fn promote_placeholder(graph: &dyn GraphStorage, node: &mut Node) -> crate::error::Result<bool> {
+    let Some(existing) = graph.get_node(&node.id)?.filter(Node::is_placeholder) else {
+        graph.add_node(node)?;
+        return Ok(false);
+    };
+    node.created_at = node.created_at.min(existing.created_at);
+    for tag in existing.tags {
+        if !node.tags.contains(&tag) {
+            node.tags.push(tag);
         }
     }
-    result
+    graph.update_node(node)?;
+    Ok(true)
+}
+
+/// 移除 noindex 笔记的节点和出边
+///
+/// 其他笔记仍链接到它时保留一个带排除标记的占位节点，链接意图不会丢失
+///
+/// # Arguments
+///
+/// * `graph` - 图存储（通常为事务句柄）
+/// * `id` - 笔记的节点 ID
+/// * `title` - 占位节点的标题
+fn exclude_node(graph: &dyn GraphStorage, id: &str, title: &str) -> crate::error::Result<()> {
+    if !graph.node_exists(id)? {
+        return Ok(());
+    }
+    for edge in graph.get_edges_by_source(id)? {
+        graph.delete_edge(&edge.id)?;
+    }
+    graph.delete_node(id)?;
+    if !graph.get_edges_by_target(id)?.is_empty() {
+        let mut placeholder = Node::new(id, NodeType::Concept, title);
+        placeholder.set_excluded();
+        graph.add_node(&placeholder)?;
+    }
+    Ok(())
 }
 
 /// 索引结果
@@ -312,15 +847,60 @@ pub enum IndexResult {
         node_count: usize,
         /// 创建的边数
         edge_count: usize,
+        /// 解析警告
+        warnings: Vec<ParseWarning>,
+    },
+    /// 文件被索引，并提升了此前由链接生成的同 ID 占位节点
+    Promoted {
+        /// 文件相对路径
+        path: String,
+        /// 创建的边数
+        edge_count: usize,
+        /// 解析警告
+        warnings: Vec<ParseWarning>,
     },
     /// 文件被跳过（不符合条件）
     Skipped,
+    /// 文件过大或是二进制文件，拒绝索引
+    Rejected {
+        /// 文件相对路径
+        path: String,
+        /// 拒绝原因
+        reason: SkipReason,
+    },
+    /// 文件带 `noindex` 标记，未生成节点（文件相对路径）
+    Excluded(String),
     /// 文件被删除
     Deleted(String),
 }
 
-/// 索引汇总
-#[derive(Debug, Default)]
+/// 拒绝索引文件的原因
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SkipReason {
+    /// 文件超过 `index.max_note_bytes`
+    TooLarge {
+        /// 文件大小（字节）
+        size: u64,
+        /// 大小上限（字节）
+        limit: u64,
+    },
+    /// 文件开头含有 NUL 字节
+    Binary,
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::TooLarge { size, limit } => {
+                write!(f, "file is {} bytes, larger than index.max_note_bytes ({})", size, limit)
+            }
+            SkipReason::Binary => write!(f, "file looks binary (contains NUL bytes)"),
+        }
+    }
+}
+
+/// 索引汇总（可序列化，供 `memexia serve` 返回给代理的 `reindex`）
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct IndexSummary {
     /// 索引的文件数
     pub files_indexed: usize,
@@ -328,26 +908,56 @@ pub struct IndexSummary {
     pub nodes_created: usize,
     /// 创建的边总数
     pub edges_created: usize,
-    /// 跳过的文件数
+    /// 提升为文件节点的占位节点数
+    pub placeholders_promoted: usize,
+    /// 跳过的文件数（含拒绝索引的文件）
     pub files_skipped: usize,
+    /// 拒绝索引的文件（文件相对路径，原因）
+    pub rejected: Vec<(String, SkipReason)>,
+    /// 因 `noindex` 标记排除的文件数
+    pub files_excluded: usize,
     /// 删除的文件数
     pub files_deleted: usize,
+    /// 检测到的重命名
+    pub renamed: Vec<super::rename::NodeRename>,
     /// 错误列表
     pub errors: Vec<(String, String)>,
+    /// 解析警告（文件相对路径，该文件的警告）
+    pub warnings: Vec<(String, Vec<ParseWarning>)>,
+    /// 索引耗时
+    pub elapsed: Duration,
 }
 
 impl IndexSummary {
     /// 添加索引结果
     pub fn add(&mut self, result: IndexResult) {
         match result {
-            IndexResult::Indexed { path: _, node_count, edge_count } => {
+            IndexResult::Indexed { path, node_count, edge_count, warnings } => {
                 self.files_indexed += 1;
                 self.nodes_created += node_count;
                 self.edges_created += edge_count;
+                if !warnings.is_empty() {
+                    self.warnings.push((path, warnings));
+                }
+            }
+            IndexResult::Promoted { path, edge_count, warnings } => {
+                self.files_indexed += 1;
+                self.placeholders_promoted += 1;
+                self.edges_created += edge_count;
+                if !warnings.is_empty() {
+                    self.warnings.push((path, warnings));
+                }
             }
             IndexResult::Skipped => {
                 self.files_skipped += 1;
             }
+            IndexResult::Rejected { path, reason } => {
+                self.files_skipped += 1;
+                self.rejected.push((path, reason));
+            }
+            IndexResult::Excluded(_) => {
+                self.files_excluded += 1;
+            }
             IndexResult::Deleted(_) => {
                 self.files_deleted += 1;
             }
@@ -358,11 +968,17 @@ impl IndexSummary {
     pub fn has_errors(&self) -> bool {
         !self.errors.is_empty()
     }
+
+    /// 解析警告总数
+    pub fn warning_count(&self) -> usize {
+        self.warnings.iter().map(|(_, warnings)| warnings.len()).sum()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::parser::ParseWarningKind;
     use tempfile::TempDir;
 
     #[test]
@@ -371,7 +987,7 @@ mod tests {
         let storage = Storage::init(temp_dir.path()).unwrap();
         let indexer = Indexer::new(storage);
 
-        assert_eq!(indexer.config().whitelist.len(), 1);
+        assert_eq!(indexer.config().whitelist, vec!["*.md", "*.md.age", "*.md.gpg"]);
     }
 
     #[test]
@@ -399,8 +1015,9 @@ tags: [test]
         let result = indexer.index_file(&test_file).unwrap();
 
         match result {
-            IndexResult::Indexed { path, node_count, edge_count } => {
+            IndexResult::Indexed { path, node_count, edge_count, warnings } => {
                 assert!(path.contains("test.md"));
+                assert!(warnings.is_empty());
                 assert_eq!(node_count, 1);
                 assert_eq!(edge_count, 1);
             }
@@ -472,6 +1089,357 @@ tags: [test]
         assert!(matches!(result, IndexResult::Indexed { .. }));
     }
 
+    #[test]
+    fn test_index_zettel_links() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        let mut indexer = Indexer::new(storage.clone());
+        indexer.set_zettel_ids(true);
+
+        let target = temp_dir.path().join("202403151030 Free Will.md");
+        std::fs::write(&target, "# Free Will").unwrap();
+        let source = temp_dir.path().join("202403161200 Determinism.md");
+        std::fs::write(&source, "[[202403151030|Contradicts]] [[202403151030 Old Title]]").unwrap();
+
+        indexer.index_all(temp_dir.path()).unwrap();
+
+        let graph = storage.graph();
+        assert!(graph.node_exists("urn:memexia:zettel:202403151030").unwrap());
+        assert!(graph.node_exists("urn:memexia:zettel:202403161200").unwrap());
+        assert_eq!(graph.list_nodes().unwrap().len(), 2);
+
+        let edges = graph.get_edges_by_source("urn:memexia:zettel:202403161200").unwrap();
+        assert!(!edges.is_empty());
+        assert!(edges.iter().all(|e| e.to == "urn:memexia:zettel:202403151030"));
+    }
+
+    #[test]
+    fn test_index_org_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        let mut indexer = Indexer::new(storage.clone());
+
+        let file = temp_dir.path().join("free-will.org");
+        std::fs::write(
+            &file,
+            "#+TITLE: Free Will\n#+FILETAGS: :philosophy:\n\n* Notes :draft:\n\
+             See [[file:determinism.org][determinism]] and [[id:abc-123]].\n",
+        )
+        .unwrap();
+
+        // 默认只索引 Markdown
+        assert_eq!(indexer.index_file(&file).unwrap(), IndexResult::Skipped);
+
+        indexer.set_extensions(vec!["md".to_string(), "org".to_string()]);
+        assert!(matches!(indexer.index_file(&file).unwrap(), IndexResult::Indexed { edge_count: 2, .. }));
+
+        let graph = storage.graph();
+        let node = graph.require_node("urn:memexia:file:free-will.org").unwrap();
+        assert_eq!(node.title, "Free Will");
+        assert!(node.tags.contains(&"philosophy".to_string()));
+        assert!(node.tags.contains(&"draft".to_string()));
+
+        let mut targets: Vec<_> = graph
+            .get_edges_by_source(&node.id)
+            .unwrap()
+            .into_iter()
+            .map(|e| (e.to, e.relation))
+            .collect();
+        targets.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            targets,
+            vec![
+                ("urn:memexia:file:determinism.org".to_string(), RelationType::RelatedTo),
+                ("urn:memexia:org:abc-123".to_string(), RelationType::RelatedTo),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_index_encrypted_stub() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        let indexer = Indexer::new(storage.clone());
+
+        let file = temp_dir.path().join("secret.md.age");
+        std::fs::write(&file, [0x61, 0x67, 0x65, 0x00, 0xff, 0xfe, 0x80]).unwrap();
+
+        assert!(matches!(indexer.index_file(&file).unwrap(), IndexResult::Indexed { edge_count: 0, .. }));
+
+        let node = storage.graph().require_node("urn:memexia:file:secret.md.age").unwrap();
+        assert_eq!(node.title, "secret");
+        assert_eq!(node.node_type, NodeType::Resource);
+        assert!(node.is_encrypted());
+        assert!(node.content.is_none());
+        assert!(node.word_count().is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_index_encrypted_with_decrypt_cmd() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        let mut indexer = Indexer::new(storage.clone());
+        // `cat` 原样输出，相当于“解密”后的明文
+        indexer.set_decrypt_cmd(Some("cat".to_string()));
+
+        let file = temp_dir.path().join("diary.md.gpg");
+        std::fs::write(&file, "# Private Diary\n\nSee [[other.md]] #personal\n").unwrap();
+
+        assert!(matches!(indexer.index_file(&file).unwrap(), IndexResult::Indexed { edge_count: 1, .. }));
+
+        let node = storage.graph().require_node("urn:memexia:file:diary.md.gpg").unwrap();
+        assert_eq!(node.title, "Private Diary");
+        assert!(node.tags.contains(&"personal".to_string()));
+        assert!(node.is_encrypted());
+        assert!(node.content.is_none());
+
+        // 解密失败时报错而不是写入乱码
+        indexer.set_decrypt_cmd(Some("exit 1".to_string()));
+        assert!(indexer.index_file(&file).is_err());
+    }
+
+    #[test]
+    fn test_index_noindex_excluded() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        let indexer = Indexer::new(storage.clone());
+
+        let file = temp_dir.path().join("journal.md");
+        std::fs::write(&file, "---\nmemexia: noindex\n---\n\n# Journal\n\nSee [[other]]\n").unwrap();
+
+        assert_eq!(indexer.index_file(&file).unwrap(), IndexResult::Excluded("journal.md".to_string()));
+        assert!(storage.graph().list_nodes().unwrap().is_empty());
+        assert!(storage.graph().list_edges().unwrap().is_empty());
+
+        let summary = indexer.index_all(temp_dir.path()).unwrap();
+        assert_eq!(summary.files_excluded, 1);
+        assert_eq!(summary.files_indexed, 0);
+    }
+
+    #[test]
+    fn test_index_noindex_removes_existing_node() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        let indexer = Indexer::new(storage.clone());
+
+        let file = temp_dir.path().join("scratch.md");
+        std::fs::write(&file, "# Scratch\n\nSee [[other]]\n").unwrap();
+        indexer.index_file(&file).unwrap();
+        assert!(storage.graph().node_exists("urn:memexia:file:scratch.md").unwrap());
+        assert_eq!(storage.graph().list_edges().unwrap().len(), 1);
+
+        std::fs::write(&file, "---\nindex: false\n---\n\n# Scratch\n\nSee [[other]]\n").unwrap();
+        assert!(matches!(indexer.index_file(&file).unwrap(), IndexResult::Excluded(_)));
+
+        assert!(!storage.graph().node_exists("urn:memexia:file:scratch.md").unwrap());
+        assert!(storage.graph().list_edges().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_index_link_to_noindex_placeholder() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        let indexer = Indexer::new(storage.clone());
+
+        let private = temp_dir.path().join("private.md");
+        std::fs::write(&private, "---\nmemexia: noindex\n---\n\n# Private\n").unwrap();
+        let public = temp_dir.path().join("public.md");
+        std::fs::write(&public, "# Public\n\nSee [[private.md]]\n").unwrap();
+
+        indexer.index_file(&private).unwrap();
+        indexer.index_file(&public).unwrap();
+
+        let placeholder = storage.graph().require_node("urn:memexia:file:private.md").unwrap();
+        assert!(placeholder.is_excluded());
+        assert_eq!(storage.graph().get_edges_by_target(&placeholder.id).unwrap().len(), 1);
+
+        // 被链接的笔记后来加上 noindex：节点退化为带排除标记的占位节点，入边保留
+        std::fs::write(&public, "# Public\n\nSee [[notes.md]]\n").unwrap();
+        let notes = temp_dir.path().join("notes.md");
+        std::fs::write(&notes, "# Notes\n").unwrap();
+        indexer.index_file(&notes).unwrap();
+        indexer.index_file(&public).unwrap();
+        std::fs::write(&notes, "---\nmemexia: noindex\n---\n\n# Notes\n").unwrap();
+        indexer.index_file(&notes).unwrap();
+
+        let node = storage.graph().require_node("urn:memexia:file:notes.md").unwrap();
+        assert!(node.is_excluded());
+        assert_eq!(storage.graph().get_edges_by_target(&node.id).unwrap().len(), 1);
+
+        // 去掉 noindex 后重新索引清除排除标记
+        std::fs::write(&notes, "# Notes\n").unwrap();
+        indexer.index_file(&notes).unwrap();
+        assert!(!storage.graph().require_node("urn:memexia:file:notes.md").unwrap().is_excluded());
+    }
+
+    #[test]
+    fn test_placeholder_promotion_converges() {
+        fn graph_state(storage: &Storage) -> (Vec<Node>, Vec<(String, String, RelationType)>) {
+            let epoch = chrono::DateTime::<chrono::Utc>::UNIX_EPOCH;
+            let mut nodes = storage.graph().list_nodes().unwrap();
+            for node in &mut nodes {
+                node.created_at = epoch;
+                node.updated_at = epoch;
+            }
+            nodes.sort_by(|a, b| a.id.cmp(&b.id));
+            let mut edges: Vec<_> = storage
+                .graph()
+                .list_edges()
+                .unwrap()
+                .into_iter()
+                .map(|edge| (edge.from, edge.to, edge.relation))
+                .collect();
+            edges.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+            (nodes, edges)
+        }
+
+        let a = "# A\n\nSee [[b.md|Supports]]\n";
+        let b = "---\ntitle: Bee\ntype: Evidence\ntags: [late]\n---\n\nBody [[c.md]]\n";
+
+        // 先链接后创建：b.md 的节点由占位节点提升而来
+        let linked_first = TempDir::new().unwrap();
+        let storage = Storage::init(linked_first.path()).unwrap();
+        let indexer = Indexer::new(storage.clone());
+        std::fs::write(linked_first.path().join("a.md"), a).unwrap();
+        indexer.index_file(&linked_first.path().join("a.md")).unwrap();
+        let placeholder = storage.graph().require_node("urn:memexia:file:b.md").unwrap();
+        assert!(placeholder.is_placeholder());
+
+        std::fs::write(linked_first.path().join("b.md"), b).unwrap();
+        let result = indexer.index_file(&linked_first.path().join("b.md")).unwrap();
+        assert!(matches!(result, IndexResult::Promoted { edge_count: 1, .. }), "{:?}", result);
+        let promoted = storage.graph().require_node("urn:memexia:file:b.md").unwrap();
+        assert_eq!((promoted.title.as_str(), &promoted.node_type), ("Bee", &NodeType::Evidence));
+        assert_eq!(promoted.created_at, placeholder.created_at);
+        assert_eq!(storage.graph().get_edges_by_target(&promoted.id).unwrap().len(), 1);
+
+        // 先创建后链接
+        let created_first = TempDir::new().unwrap();
+        let other = Storage::init(created_first.path()).unwrap();
+        let other_indexer = Indexer::new(other.clone());
+        std::fs::write(created_first.path().join("b.md"), b).unwrap();
+        std::fs::write(created_first.path().join("a.md"), a).unwrap();
+        assert!(matches!(
+            other_indexer.index_file(&created_first.path().join("b.md")).unwrap(),
+            IndexResult::Indexed { .. }
+        ));
+        other_indexer.index_file(&created_first.path().join("a.md")).unwrap();
+
+        assert_eq!(graph_state(&storage), graph_state(&other));
+
+        // 已是文件节点时重新索引不再算作提升
+        std::fs::write(linked_first.path().join("b.md"), format!("{}more\n", b)).unwrap();
+        assert!(matches!(
+            indexer.index_file(&linked_first.path().join("b.md")).unwrap(),
+            IndexResult::Indexed { .. }
+        ));
+
+        let mut summary = IndexSummary::default();
+        summary.add(result);
+        assert_eq!((summary.files_indexed, summary.placeholders_promoted, summary.nodes_created), (1, 1, 0));
+    }
+
+    #[test]
+    fn test_configured_default_relation() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let storage = Storage::init(root).unwrap();
+        let mut indexer = Indexer::new(storage.clone());
+        indexer.set_default_relation(RelationType::References);
+
+        let note = root.join("a.md");
+        std::fs::write(&note, "# A\n\n[[b.md]] [[c.md|RelatedTo]] [[d.md|Supports]]\n").unwrap();
+        indexer.index_file(&note).unwrap();
+
+        let mut relations: Vec<_> = storage
+            .graph()
+            .get_edges_by_source("urn:memexia:file:a.md")
+            .unwrap()
+            .into_iter()
+            .map(|edge| (edge.to, edge.relation))
+            .collect();
+        relations.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            relations,
+            [
+                ("urn:memexia:file:b.md".to_string(), RelationType::References),
+                ("urn:memexia:file:c.md".to_string(), RelationType::RelatedTo),
+                ("urn:memexia:file:d.md".to_string(), RelationType::Supports),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_index_size_and_binary_guards() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let storage = Storage::init(root).unwrap();
+        let mut indexer = Indexer::new(storage.clone());
+        indexer.set_size_limits(4096, 1000);
+
+        // 超过大小上限：跳过并给出原因，不生成节点
+        let big = root.join("big.md");
+        std::fs::write(&big, "x".repeat(4097)).unwrap();
+        assert_eq!(
+            indexer.index_file(&big).unwrap(),
+            IndexResult::Rejected {
+                path: "big.md".to_string(),
+                reason: SkipReason::TooLarge { size: 4097, limit: 4096 }
+            }
+        );
+
+        // 改成 .md 的二进制文件
+        let binary = root.join("dump.md");
+        std::fs::write(&binary, b"# Dump\n\0\x01\x02").unwrap();
+        assert!(matches!(
+            indexer.index_file(&binary).unwrap(),
+            IndexResult::Rejected { reason: SkipReason::Binary, .. }
+        ));
+        assert!(storage.graph().list_nodes().unwrap().is_empty());
+
+        // 刚好不超过上限：正常索引，节点只保存正文开头，搜索仍能命中结尾
+        let long = root.join("long.md");
+        let body = format!("# Long\n\n{}\n\n尾声 needle [[other.md]]\n", "中文 ".repeat(500));
+        std::fs::write(&long, &body).unwrap();
+        assert!(body.len() <= 4096);
+        assert!(matches!(indexer.index_file(&long).unwrap(), IndexResult::Indexed { edge_count: 1, .. }));
+        let node = storage.graph().require_node("urn:memexia:file:long.md").unwrap();
+        assert!(node.content.as_ref().unwrap().len() <= 1000);
+        assert_eq!(node.excerpt_of(), Some("long.md"));
+        let hits = crate::core::search::search(storage.graph(), root, "needle", false, None).unwrap();
+        assert_eq!(hits.len(), 1);
+
+        let summary = indexer.index_all(root).unwrap();
+        assert_eq!(summary.rejected.len(), 2);
+        assert_eq!(summary.files_indexed, 0);
+
+        // 笔记缩短后重新索引，摘录标记清除
+        std::fs::write(&long, "# Long\n\nshort now\n").unwrap();
+        indexer.index_file(&long).unwrap();
+        let node = storage.graph().require_node("urn:memexia:file:long.md").unwrap();
+        assert_eq!(node.excerpt_of(), None);
+        assert!(!node.content.unwrap().contains("中文"));
+    }
+
+    #[test]
+    fn test_index_all_collects_parse_warnings() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        let indexer = Indexer::new(storage);
+        std::fs::write(temp_dir.path().join("clean.md"), "# Clean\n\n[[a|Supports]]\n").unwrap();
+        std::fs::write(temp_dir.path().join("messy.md"), "# Messy\n\n[[a|leads to]] and [[b\n").unwrap();
+
+        let summary = indexer.index_all(temp_dir.path()).unwrap();
+
+        assert_eq!(summary.files_indexed, 2);
+        assert_eq!(summary.warnings.len(), 1);
+        let (path, warnings) = &summary.warnings[0];
+        assert_eq!(path, "messy.md");
+        let kinds: Vec<_> = warnings.iter().map(|w| (w.line, w.kind)).collect();
+        assert_eq!(kinds, [(3, ParseWarningKind::UnclosedLink), (3, ParseWarningKind::UnknownRelation)]);
+    }
+
     #[test]
     fn test_index_summary() {
         let mut summary = IndexSummary::default();
@@ -480,17 +1448,23 @@ tags: [test]
             path: "a.md".to_string(),
             node_count: 1,
             edge_count: 2,
+            warnings: Vec::new(),
         });
         summary.add(IndexResult::Indexed {
             path: "b.md".to_string(),
             node_count: 1,
             edge_count: 0,
+            warnings: vec![ParseWarning::new(3, ParseWarningKind::UnclosedLink, "unclosed")],
         });
         summary.add(IndexResult::Skipped);
+        summary.add(IndexResult::Excluded("c.md".to_string()));
 
         assert_eq!(summary.files_indexed, 2);
         assert_eq!(summary.nodes_created, 2);
         assert_eq!(summary.edges_created, 2);
         assert_eq!(summary.files_skipped, 1);
+        assert_eq!(summary.files_excluded, 1);
+        assert_eq!(summary.warning_count(), 1);
+        assert_eq!(summary.warnings[0].0, "b.md");
     }
 }
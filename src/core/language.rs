@@ -0,0 +1,94 @@
+//! 笔记语言检测模块
+//!
+//! 索引时按字符统计粗略判断笔记正文的主要语言，结果存为节点的 `memexia:language` 属性：
+//!
+//! - 汉字、假名、谚文各算一个词，拉丁字母连续的一段算一个词
+//! - CJK 字符不少于拉丁词时判为 CJK 语言：假名占一成以上为 `ja`，谚文占多数为 `ko`，否则为 `zh`
+//! - 否则判为 `en`（不区分其他拉丁字母语言）；正文没有文字时不设置语言
+//!
+//! 混合笔记取占多数的一方。frontmatter 的 `lang:` 覆盖检测结果
+
+/// 检测文本的主要语言
+///
+/// # Returns
+///
+/// 语言代码（`zh`、`ja`、`ko`、`en`）；没有可判断的文字时为 None
+pub fn detect(text: &str) -> Option<&'static str> {
+    let (mut han, mut kana, mut hangul, mut latin_words) = (0usize, 0usize, 0usize, 0usize);
+    let mut in_word = false;
+    for c in text.chars() {
+        match c {
+            '\u{3040}'..='\u{30FF}' | '\u{31F0}'..='\u{31FF}' => kana += 1,
+            '\u{AC00}'..='\u{D7AF}' | '\u{1100}'..='\u{11FF}' | '\u{3130}'..='\u{318F}' => hangul += 1,
+            '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '\u{F900}'..='\u{FAFF}' | '\u{20000}'..='\u{2FA1F}' => {
+                han += 1
+            }
+            c if c.is_alphabetic() => {
+                if !in_word {
+                    latin_words += 1;
+                }
+                in_word = true;
+                continue;
+            }
+            _ => {}
+        }
+        in_word = false;
+    }
+
+    let cjk = han + kana + hangul;
+    if cjk == 0 && latin_words == 0 {
+        return None;
+    }
+    if cjk < latin_words {
+        return Some("en");
+    }
+    if kana * 10 >= cjk {
+        Some("ja")
+    } else if hangul * 2 > cjk {
+        Some("ko")
+    } else {
+        Some("zh")
+    }
+}
+
+/// 规范化语言代码（frontmatter `lang:` 或 `--lang`）：去掉空白、转小写、`_` 换成 `-`
+pub fn normalize(code: &str) -> Option<String> {
+    let code = code.trim().to_lowercase().replace('_', "-");
+    (!code.is_empty()).then_some(code)
+}
+
+/// 节点语言是否符合过滤条件
+///
+/// 只给出主语言时匹配所有地区变体：`zh` 匹配 `zh-cn`，`zh-cn` 不匹配 `zh-tw`
+pub fn matches(language: &str, filter: &str) -> bool {
+    let (Some(language), Some(filter)) = (normalize(language), normalize(filter)) else {
+        return false;
+    };
+    language == filter || language.strip_prefix(&filter).is_some_and(|rest| rest.starts_with('-'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(detect("自由意志与决定论是否相容？"), Some("zh"));
+        assert_eq!(detect("Free will and determinism are compatible."), Some("en"));
+        assert_eq!(detect("今天学习了 Rust 的所有权和借用。"), Some("zh"));
+        assert_eq!(detect("Notes on the 道德经 translation by Ursula Le Guin."), Some("en"));
+        assert_eq!(detect("これは日本語の文章です。"), Some("ja"));
+        assert_eq!(detect("한국어 문장입니다"), Some("ko"));
+        assert_eq!(detect("# 42\n\n- [ ] 1. 2."), None);
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("zh", "ZH"));
+        assert!(matches("zh-CN", "zh"));
+        assert!(matches("zh_cn", "zh-cn"));
+        assert!(!matches("zh-cn", "zh-tw"));
+        assert!(!matches("zh", "zh-cn"));
+        assert!(!matches("zhx", "zh"));
+    }
+}
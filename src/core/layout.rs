@@ -0,0 +1,278 @@
+//! 力导向布局模块（`memexia graph layout`、`GET /api/layout`）
+//!
+//! 浏览器端的力导向布局在几千个节点时明显卡顿，由服务端预先计算坐标后 GUI 可以直接绘制。
+//! 算法为 Fruchterman–Reingold：
+//!
+//! - 相连的节点互相吸引，吸引力 `weight * d² / k`，`weight` 取边的强度（[`Edge::weight`]）
+//! - 所有节点互相排斥，排斥力 `k² / d`；按 `2k` 边长分格，只计算相邻格子内的节点对，
+//!   大图每轮接近线性
+//! - 每轮位移不超过温度，温度随轮数线性降到 0
+//!
+//! 初始位置由 `seed` 决定，节点按 ID 排序后取随机数，同一输入和种子得到逐位相同的结果
+
+use crate::error::{MemexiaError, Result};
+use crate::storage::Edge;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+
+/// 理想边长
+const IDEAL_DISTANCE: f64 = 100.0;
+
+/// 超过该节点数时按 info 级别报告进度
+const LARGE_GRAPH: usize = 1000;
+
+/// 输出坐标保留的精度（两位小数）
+const PRECISION: f64 = 100.0;
+
+/// 布局选项
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayoutOptions {
+    /// 迭代轮数
+    pub iterations: usize,
+    /// 初始位置的随机种子
+    pub seed: u64,
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        Self { iterations: 300, seed: 0 }
+    }
+}
+
+/// 节点坐标
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    /// 横坐标
+    pub x: f64,
+    /// 纵坐标
+    pub y: f64,
+}
+
+/// 计算力导向布局
+///
+/// # Arguments
+///
+/// * `nodes` - 节点 ID
+/// * `edges` - 边；端点不在 `nodes` 中的边和自环被忽略
+/// * `options` - 布局选项
+/// * `should_stop` - 每轮开始前调用，返回 true 时中止
+///
+/// # Returns
+///
+/// 与 `nodes` 一一对应的坐标，以原点为中心；中止时返回 [`MemexiaError::Cancelled`]
+pub fn force_layout(
+    nodes: &[&str],
+    edges: &[Edge],
+    options: &LayoutOptions,
+    should_stop: &dyn Fn() -> bool,
+) -> Result<Vec<Point>> {
+    let n = nodes.len();
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+
+    let index: HashMap<&str, usize> = nodes.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+    let springs: Vec<(usize, usize, f64)> = edges
+        .iter()
+        .filter_map(|edge| {
+            let (from, to) = (*index.get(edge.from.as_str())?, *index.get(edge.to.as_str())?);
+            (from != to).then(|| (from, to, edge.weight().clamp(0.0, 1.0)))
+        })
+        .collect();
+
+    // 初始位置与输入顺序无关
+    let side = IDEAL_DISTANCE * (n as f64).sqrt();
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by_key(|&i| nodes[i]);
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let mut positions = vec![Point { x: 0.0, y: 0.0 }; n];
+    for i in order {
+        positions[i] = Point {
+            x: rng.random_range(-side / 2.0..side / 2.0),
+            y: rng.random_range(-side / 2.0..side / 2.0),
+        };
+    }
+
+    let k = IDEAL_DISTANCE;
+    let initial_temperature = side / 10.0;
+    let level = if n >= LARGE_GRAPH { tracing::Level::INFO } else { tracing::Level::DEBUG };
+    let report_every = (options.iterations / 10).max(1);
+    let mut displacement = vec![Point { x: 0.0, y: 0.0 }; n];
+
+    for iteration in 0..options.iterations {
+        if should_stop() {
+            return Err(MemexiaError::Cancelled);
+        }
+        if iteration % report_every == 0 {
+            if level == tracing::Level::INFO {
+                tracing::info!(iteration, total = options.iterations, nodes = n, "Computing layout");
+            } else {
+                tracing::debug!(iteration, total = options.iterations, nodes = n, "Computing layout");
+            }
+        }
+
+        displacement.iter_mut().for_each(|d| *d = Point { x: 0.0, y: 0.0 });
+        repel(&positions, &mut displacement, k, &mut rng);
+        for &(from, to, weight) in &springs {
+            let (dx, dy) = (positions[from].x - positions[to].x, positions[from].y - positions[to].y);
+            let distance = (dx * dx + dy * dy).sqrt().max(0.01);
+            let force = weight * distance * distance / k;
+            let (fx, fy) = (dx / distance * force, dy / distance * force);
+            displacement[from].x -= fx;
+            displacement[from].y -= fy;
+            displacement[to].x += fx;
+            displacement[to].y += fy;
+        }
+
+        let temperature = initial_temperature * (1.0 - iteration as f64 / options.iterations as f64);
+        for (position, d) in positions.iter_mut().zip(&displacement) {
+            let length = (d.x * d.x + d.y * d.y).sqrt();
+            if length > 0.0 {
+                let step = length.min(temperature);
+                position.x += d.x / length * step;
+                position.y += d.y / length * step;
+            }
+        }
+    }
+
+    // 以原点为中心，坐标保留两位小数
+    let (cx, cy) = positions.iter().fold((0.0, 0.0), |(x, y), p| (x + p.x, y + p.y));
+    let (cx, cy) = (cx / n as f64, cy / n as f64);
+    Ok(positions
+        .into_iter()
+        .map(|p| Point {
+            x: ((p.x - cx) * PRECISION).round() / PRECISION,
+            y: ((p.y - cy) * PRECISION).round() / PRECISION,
+        })
+        .collect())
+}
+
+/// 累加排斥力：按 `2k` 边长分格，只计算相邻格子内的节点对
+fn repel(positions: &[Point], displacement: &mut [Point], k: f64, rng: &mut StdRng) {
+    let cell_size = 2.0 * k;
+    let cell_of = |p: &Point| ((p.x / cell_size).floor() as i64, (p.y / cell_size).floor() as i64);
+    let mut grid: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    for (i, p) in positions.iter().enumerate() {
+        grid.entry(cell_of(p)).or_default().push(i);
+    }
+
+    for (i, p) in positions.iter().enumerate() {
+        let (cx, cy) = cell_of(p);
+        for gx in cx - 1..=cx + 1 {
+            for gy in cy - 1..=cy + 1 {
+                let Some(cell) = grid.get(&(gx, gy)) else {
+                    continue;
+                };
+                for &j in cell {
+                    if j == i {
+                        continue;
+                    }
+                    let (mut dx, mut dy) = (p.x - positions[j].x, p.y - positions[j].y);
+                    if dx == 0.0 && dy == 0.0 {
+                        // 重合的节点随机错开
+                        dx = rng.random_range(-1.0..1.0);
+                        dy = rng.random_range(-1.0..1.0);
+                    }
+                    let distance = (dx * dx + dy * dy).sqrt().max(0.01);
+                    if distance > cell_size {
+                        continue;
+                    }
+                    let force = k * k / distance;
+                    displacement[i].x += dx / distance * force;
+                    displacement[i].y += dy / distance * force;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::RelationType;
+    use std::cell::Cell;
+
+    fn edge(from: &str, to: &str) -> Edge {
+        Edge::new(Edge::make_id(from, to), from, to, RelationType::Supports)
+    }
+
+    /// 两条互不相连的链：a0 - a1 - … - a4，b0 - b1 - … - b4
+    fn two_chains() -> (Vec<String>, Vec<Edge>) {
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+        for chain in ["a", "b"] {
+            for i in 0..5 {
+                nodes.push(format!("{}{}", chain, i));
+                if i > 0 {
+                    edges.push(edge(&format!("{}{}", chain, i - 1), &format!("{}{}", chain, i)));
+                }
+            }
+        }
+        (nodes, edges)
+    }
+
+    fn distance(a: Point, b: Point) -> f64 {
+        ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+    }
+
+    #[test]
+    fn test_layout_is_deterministic() {
+        let (nodes, edges) = two_chains();
+        let ids: Vec<&str> = nodes.iter().map(String::as_str).collect();
+        let options = LayoutOptions { iterations: 50, seed: 7 };
+
+        let first = force_layout(&ids, &edges, &options, &|| false).unwrap();
+        let second = force_layout(&ids, &edges, &options, &|| false).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first.len(), 10);
+
+        // 输入顺序不影响结果
+        let reversed: Vec<&str> = ids.iter().rev().copied().collect();
+        let third = force_layout(&reversed, &edges, &options, &|| false).unwrap();
+        assert_eq!(third.into_iter().rev().collect::<Vec<_>>(), first);
+
+        let other_seed = force_layout(&ids, &edges, &LayoutOptions { seed: 8, ..options }, &|| false).unwrap();
+        assert_ne!(other_seed, first);
+    }
+
+    #[test]
+    fn test_layout_pulls_connected_nodes_together() {
+        let (nodes, edges) = two_chains();
+        let ids: Vec<&str> = nodes.iter().map(String::as_str).collect();
+        let positions = force_layout(&ids, &edges, &LayoutOptions::default(), &|| false).unwrap();
+
+        let linked = |i: usize, j: usize| {
+            edges.iter().any(|e| (e.from == ids[i] && e.to == ids[j]) || (e.from == ids[j] && e.to == ids[i]))
+        };
+        let (mut connected, mut disconnected) = (Vec::new(), Vec::new());
+        for i in 0..ids.len() {
+            for j in i + 1..ids.len() {
+                let d = distance(positions[i], positions[j]);
+                if linked(i, j) {
+                    connected.push(d);
+                } else {
+                    disconnected.push(d);
+                }
+            }
+        }
+        let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+        assert!(mean(&connected) < mean(&disconnected), "{} >= {}", mean(&connected), mean(&disconnected));
+    }
+
+    #[test]
+    fn test_layout_ignores_unknown_endpoints_and_cancels() {
+        let positions = force_layout(&["a"], &[edge("a", "missing"), edge("a", "a")], &LayoutOptions::default(), &|| false).unwrap();
+        assert_eq!(positions, [Point { x: 0.0, y: 0.0 }]);
+        assert!(force_layout(&[], &[], &LayoutOptions::default(), &|| true).unwrap().is_empty());
+
+        let calls = Cell::new(0);
+        let stop_after_three = || {
+            calls.set(calls.get() + 1);
+            calls.get() > 3
+        };
+        let result = force_layout(&["a", "b"], &[edge("a", "b")], &LayoutOptions::default(), &stop_after_three);
+        assert!(matches!(result, Err(MemexiaError::Cancelled)));
+        assert_eq!(calls.get(), 4);
+    }
+}
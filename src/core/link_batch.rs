@@ -0,0 +1,204 @@
+//! 批量链接模块（`memexia link apply`）
+//!
+//! 整理完一批阅读笔记后，把要建立的关系写成纯文本文件一次性导入，每行一条：
+//!
+//! ```text
+//! # 读书会 2024-05
+//! notes/a.md --Supports(0.8)--> notes/b.md # 实验数据支持该结论
+//! notes/b.md --Contradicts--> notes/c.md
+//! ```
+//!
+//! - 箭头中间是关系名，可带括号中的强度（0.0 - 1.0，默认 1.0）；关系名按
+//!   [`RelationType::parse`] 解析，自定义关系同样可用
+//! - 路径相对仓库根目录；` # ` 之后是边的描述，空行和以 `#` 开头的行被忽略
+//! - 解析不会因为某一行出错而中断，每个错误带行号返回，由调用方决定是否继续
+//!
+//! 写入见 [`Repository::apply_links`](crate::core::Repository::apply_links)
+
+use crate::storage::{Edge, RelationType};
+use std::fmt;
+
+/// 一行链接
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkLine {
+    /// 行号（从 1 开始）
+    pub line: usize,
+    /// 源笔记路径（相对仓库根目录）
+    pub source: String,
+    /// 目标笔记路径（相对仓库根目录）
+    pub target: String,
+    /// 关系类型
+    pub relation: RelationType,
+    /// 关系强度；未写时为 None（使用默认强度）
+    pub strength: Option<f64>,
+    /// 边的描述
+    pub description: Option<String>,
+}
+
+/// 某一行的错误
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineError {
+    /// 行号（从 1 开始）
+    pub line: usize,
+    /// 错误描述
+    pub message: String,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// 解析结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedLinks {
+    /// 解析成功的行（按行号顺序）
+    pub links: Vec<LinkLine>,
+    /// 解析失败的行
+    pub errors: Vec<LineError>,
+}
+
+/// 写入结果
+#[derive(Debug, Clone, Default)]
+pub struct ApplyReport {
+    /// 写入的边
+    pub applied: Vec<Edge>,
+    /// 写入失败的行（路径在仓库外等）
+    pub errors: Vec<LineError>,
+}
+
+/// 解析批量链接文件
+pub fn parse(content: &str) -> ParsedLinks {
+    let mut parsed = ParsedLinks::default();
+    for (index, text) in content.lines().enumerate() {
+        let line = index + 1;
+        match parse_line(line, text) {
+            Ok(Some(link)) => parsed.links.push(link),
+            Ok(None) => {}
+            Err(message) => parsed.errors.push(LineError { line, message }),
+        }
+    }
+    parsed
+}
+
+/// 解析一行；空行和注释行返回 None
+fn parse_line(line: usize, text: &str) -> std::result::Result<Option<LinkLine>, String> {
+    let text = text.trim();
+    if text.is_empty() || text.starts_with('#') {
+        return Ok(None);
+    }
+
+    // ` # ` 之后是描述（路径中可以出现不带前导空白的 `#`）
+    let (body, description) = match text.find(" #") {
+        Some(at) => {
+            let description = text[at + 2..].trim();
+            (text[..at].trim(), (!description.is_empty()).then(|| description.to_string()))
+        }
+        None => (text, None),
+    };
+
+    let malformed = || format!("expected `<source> --Relation--> <target>`, found `{}`", body);
+    let end = body.find("-->").ok_or_else(malformed)?;
+    let start = body[..end].rfind("--").ok_or_else(malformed)?;
+    let source = body[..start].trim();
+    let spec = body[start + 2..end].trim();
+    let target = body[end + 3..].trim();
+    if source.is_empty() || target.is_empty() || spec.is_empty() {
+        return Err(malformed());
+    }
+    if body[end + 3..].contains("-->") {
+        return Err(format!("more than one arrow in `{}`", body));
+    }
+
+    let (name, strength) = match spec.split_once('(') {
+        Some((name, rest)) => {
+            let value = rest
+                .strip_suffix(')')
+                .ok_or_else(|| format!("missing `)` after strength in `{}`", spec))?;
+            let strength = value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|s| (0.0..=1.0).contains(s))
+                .ok_or_else(|| format!("strength must be a number between 0.0 and 1.0, found `{}`", value.trim()))?;
+            (name.trim(), Some(strength))
+        }
+        None => (spec, None),
+    };
+    let relation = RelationType::parse(name).ok_or_else(|| format!("unknown relation type `{}`", name))?;
+
+    Ok(Some(LinkLine {
+        line,
+        source: source.to_string(),
+        target: target.to_string(),
+        relation,
+        strength,
+        description,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(content: &str) -> Vec<String> {
+        parse(content).errors.into_iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_links() {
+        let parsed = parse(
+            "# 读书会\n\nnotes/a.md --Supports(0.8)--> notes/b.md # 实验数据支持\n  notes/b.md --矛盾--> notes/c#2.md\n\
+             notes/c.md --Cites-Methodology--> notes/d.md\n",
+        );
+        assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+        assert_eq!(
+            parsed.links[0],
+            LinkLine {
+                line: 3,
+                source: "notes/a.md".to_string(),
+                target: "notes/b.md".to_string(),
+                relation: RelationType::Supports,
+                strength: Some(0.8),
+                description: Some("实验数据支持".to_string()),
+            }
+        );
+        assert_eq!(parsed.links[1].line, 4);
+        assert_eq!(parsed.links[1].relation, RelationType::Contradicts);
+        assert_eq!(parsed.links[1].target, "notes/c#2.md");
+        assert_eq!((parsed.links[1].strength, &parsed.links[1].description), (None, &None));
+        assert_eq!(parsed.links[2].relation, RelationType::Custom("Cites-Methodology".to_string()));
+    }
+
+    #[test]
+    fn test_parse_malformed_arrows() {
+        assert_eq!(
+            errors("a.md -> b.md\na.md --Supports b.md\n--Supports--> b.md\na.md ---> b.md\na.md --X--> b.md --Y--> c.md"),
+            [
+                "line 1: expected `<source> --Relation--> <target>`, found `a.md -> b.md`",
+                "line 2: expected `<source> --Relation--> <target>`, found `a.md --Supports b.md`",
+                "line 3: expected `<source> --Relation--> <target>`, found `--Supports--> b.md`",
+                "line 4: expected `<source> --Relation--> <target>`, found `a.md ---> b.md`",
+                "line 5: more than one arrow in `a.md --X--> b.md --Y--> c.md`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_bad_strength_and_relation() {
+        let parsed = parse("a.md --Supports(1.5)--> b.md\na.md --Supports(high)--> b.md\na.md --Supports(0.5--> b.md\na.md --1st order--> b.md\nb.md --Refines--> c.md");
+        assert_eq!(
+            parsed.errors.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+            [
+                "line 1: strength must be a number between 0.0 and 1.0, found `1.5`",
+                "line 2: strength must be a number between 0.0 and 1.0, found `high`",
+                "line 3: missing `)` after strength in `Supports(0.5`",
+                "line 4: unknown relation type `1st order`",
+            ]
+        );
+        // 出错的行不影响其他行
+        assert_eq!(parsed.links.len(), 1);
+        assert_eq!(parsed.links[0].line, 5);
+    }
+}
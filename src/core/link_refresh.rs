@@ -0,0 +1,251 @@
+//! 链接刷新模块（`memexia link refresh`）
+//!
+//! 重新索引时发现笔记标题变化，会把旧标题记录在节点的 `memexia:previousTitle` 中
+//! （见 [`track_title_change`]）。之后其他笔记里仍写着旧标题的 Wiki 链接由
+//! [`rewrite_links`] 改写为新标题：
+//!
+//! - `[[202401011200 旧标题]]`：Zettel ID 仍能解析到该笔记，只替换 ID 之后的标题部分
+//! - `[[旧标题]]`：旧标题只属于一个笔记、且没有笔记正在使用该标题时整体替换
+//!
+//! 只改写 `[[...]]` 内 `|` 之前的目标文本，`|关系:强度:描述` 部分原样保留
+
+use crate::core::zettel;
+use crate::error::Result;
+use crate::storage::{GraphStorage, Node};
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// 一处链接改写
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkChange {
+    /// 原链接（含 `[[ ]]`）
+    pub old: String,
+    /// 改写后的链接
+    pub new: String,
+}
+
+/// 一个文件中的链接改写
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileRefresh {
+    /// 文件路径（相对仓库根目录）
+    pub path: PathBuf,
+    /// 改写列表（按出现顺序）
+    pub changes: Vec<LinkChange>,
+}
+
+/// 标题变化索引
+#[derive(Debug, Clone, Default)]
+pub struct TitleIndex {
+    /// 节点 ID → (当前标题, 上一个标题)
+    renamed: HashMap<String, (String, String)>,
+    /// 上一个标题 → 节点 ID（多个节点共用同一旧标题、或有笔记正在使用该标题时为 None）
+    by_previous: HashMap<String, Option<String>>,
+}
+
+impl TitleIndex {
+    /// 从图中收集标题发生过变化的节点（跳过已软删除的节点）
+    pub fn build(graph: &dyn GraphStorage) -> Result<Self> {
+        let mut index = TitleIndex::default();
+        for node in graph.get_all_nodes()? {
+            if node.is_deleted() {
+                continue;
+            }
+            if let Some(previous) = node.previous_title().filter(|p| *p != node.title) {
+                index
+                    .by_previous
+                    .entry(previous.to_string())
+                    .and_modify(|id| *id = None)
+                    .or_insert_with(|| Some(node.id.clone()));
+                index.renamed.insert(node.id.clone(), (node.title.clone(), previous.to_string()));
+            }
+        }
+
+        // 旧标题已被笔记沿用时不再指向改名的笔记（经标题索引查找）；
+        // 未解析链接生成的占位节点以链接文本为标题，不算作正在使用
+        for (previous, id) in index.by_previous.iter_mut() {
+            if graph
+                .find_by_title(previous, true)?
+                .iter()
+                .any(|node| node.content_hash().is_some())
+            {
+                *id = None;
+            }
+        }
+        Ok(index)
+    }
+
+    /// 没有标题变化时无需扫描文件
+    pub fn is_empty(&self) -> bool {
+        self.renamed.is_empty()
+    }
+
+    /// 改写单个链接目标，不需要改写时返回 None
+    fn rewrite_target(&self, target: &str, resolve: &dyn Fn(&str) -> String) -> Option<String> {
+        let resolved = resolve(target);
+        if let Some((title, previous)) = self.renamed.get(&resolved) {
+            if target == previous {
+                return Some(title.clone());
+            }
+            // Zettel 链接：ID + 分隔符 + 旧标题
+            if let Some(id) = zettel::extract_zettel_id(target) {
+                if let Some(rest) = target.strip_prefix(id).filter(|_| resolved == zettel::zettel_node_id(id)) {
+                    let separator = rest.chars().next()?;
+                    if &rest[separator.len_utf8()..] == previous {
+                        return Some(format!("{}{}{}", id, separator, title));
+                    }
+                }
+            }
+        }
+
+        // 按旧标题书写的链接：旧标题唯一且没有被其他笔记沿用
+        let id = self.by_previous.get(target)?.as_ref()?;
+        self.renamed.get(id).map(|(title, _)| title.clone())
+    }
+}
+
+/// 重新索引前记录标题变化
+///
+/// 已索引过的节点（带内容哈希）标题变化时，把旧标题写入新节点的 `previousTitle`
+///
+/// # Arguments
+///
+/// * `graph` - 图存储
+/// * `node` - 即将写入的节点
+pub fn track_title_change(graph: &dyn GraphStorage, node: &mut Node) -> Result<()> {
+    let Some(existing) = graph.get_node(&node.id)? else {
+        return Ok(());
+    };
+    if existing.content_hash().is_some() && !existing.title.is_empty() && existing.title != node.title {
+        node.set_previous_title(existing.title);
+    }
+    Ok(())
+}
+
+fn link_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\[\[([^\[\]]+)\]\]").unwrap())
+}
+
+/// 改写内容中引用旧标题的 Wiki 链接
+///
+/// # Arguments
+///
+/// * `content` - 笔记内容
+/// * `index` - 标题变化索引
+/// * `resolve` - 链接目标 → 节点 ID（与索引时的解析规则一致）
+///
+/// # Returns
+///
+/// 没有需要改写的链接时返回 None；否则返回改写后的内容和改写列表
+pub fn rewrite_links(
+    content: &str,
+    index: &TitleIndex,
+    resolve: &dyn Fn(&str) -> String,
+) -> Option<(String, Vec<LinkChange>)> {
+    let mut changes = Vec::new();
+    let updated = link_regex().replace_all(content, |caps: &regex::Captures| {
+        let inner = &caps[1];
+        let (target, suffix) = match inner.find('|') {
+            Some(pos) => inner.split_at(pos),
+            None => (inner, ""),
+        };
+        match index.rewrite_target(target, resolve) {
+            Some(new_target) if new_target != target => {
+                let new = format!("[[{}{}]]", new_target, suffix);
+                changes.push(LinkChange {
+                    old: caps[0].to_string(),
+                    new: new.clone(),
+                });
+                new
+            }
+            _ => caps[0].to_string(),
+        }
+    });
+    if changes.is_empty() {
+        None
+    } else {
+        Some((updated.into_owned(), changes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{ids, NodeType, Storage};
+    use tempfile::TempDir;
+
+    fn renamed(id: &str, title: &str, previous: &str) -> Node {
+        let mut node = Node::new(id, NodeType::Concept, title);
+        node.set_previous_title(previous);
+        node
+    }
+
+    fn resolve(target: &str) -> String {
+        zettel::resolve_link_target(target).unwrap_or_else(|| ids::file_id(target))
+    }
+
+    #[test]
+    fn test_rewrite_preserves_pipe_segment() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let graph = storage.graph();
+        graph.add_node(&renamed("urn:memexia:zettel:202401011200", "Free Will", "Freedom")).unwrap();
+        graph.add_node(&renamed("urn:memexia:file:b.md", "Determinism", "Fate")).unwrap();
+        // 占位节点的标题与旧标题相同也不影响改写
+        graph.add_node(&Node::new("urn:memexia:file:Fate", NodeType::Concept, "Fate")).unwrap();
+
+        let index = TitleIndex::build(graph).unwrap();
+        let content = "See [[202401011200 Freedom|Supports:0.8:a | b]] and [[Fate]].\n\
+                       Freedom and Fate outside links stay. [[Freedom of speech]] [[202401011200-Other]]";
+        let (updated, changes) = rewrite_links(content, &index, &resolve).unwrap();
+        assert_eq!(
+            updated,
+            "See [[202401011200 Free Will|Supports:0.8:a | b]] and [[Determinism]].\n\
+             Freedom and Fate outside links stay. [[Freedom of speech]] [[202401011200-Other]]"
+        );
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].old, "[[202401011200 Freedom|Supports:0.8:a | b]]");
+
+        // 已是新标题时没有改写
+        assert!(rewrite_links(&updated, &index, &resolve).is_none());
+    }
+
+    #[test]
+    fn test_ambiguous_or_reused_title_is_skipped() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let graph = storage.graph();
+        graph.add_node(&renamed("urn:memexia:file:a.md", "A2", "Shared")).unwrap();
+        graph.add_node(&renamed("urn:memexia:file:b.md", "B2", "Shared")).unwrap();
+        graph.add_node(&renamed("urn:memexia:file:c.md", "C2", "Reused")).unwrap();
+        let mut reused = Node::new("urn:memexia:file:d.md", NodeType::Concept, "Reused");
+        reused.set_content_hash("h");
+        graph.add_node(&reused).unwrap();
+
+        let index = TitleIndex::build(graph).unwrap();
+        assert!(rewrite_links("[[Shared]] [[Reused|RelatedTo]]", &index, &resolve).is_none());
+    }
+
+    #[test]
+    fn test_track_title_change() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let graph = storage.graph();
+
+        // 未索引过的占位节点不记录
+        graph.add_node(&Node::new("urn:memexia:file:a.md", NodeType::Concept, "a.md")).unwrap();
+        let mut node = Node::new("urn:memexia:file:a.md", NodeType::Concept, "Alpha");
+        track_title_change(graph, &mut node).unwrap();
+        assert_eq!(node.previous_title(), None);
+
+        node.set_content_hash("h1");
+        graph.add_node(&node).unwrap();
+        let mut node = Node::new("urn:memexia:file:a.md", NodeType::Concept, "Beta");
+        track_title_change(graph, &mut node).unwrap();
+        assert_eq!(node.previous_title(), Some("Alpha"));
+        graph.add_node(&node).unwrap();
+        assert_eq!(graph.get_node("urn:memexia:file:a.md").unwrap().unwrap().previous_title(), Some("Alpha"));
+    }
+}
@@ -0,0 +1,586 @@
+//! 笔记 lint 模块
+//!
+//! 与检查图谱的 [`crate::core::validate`] 不同，lint 直接检查 Markdown 源文件的写法和结构，
+//! 供 `memexia lint` 和编辑器集成使用。每条诊断带有文件、行号、规则名和说明
+//!
+//! ## 内置规则
+//!
+//! | 规则 | 默认级别 | 说明 |
+//! |------|----------|------|
+//! | `missing_title` | warn | frontmatter 中没有 `title` |
+//! | `title_mismatch` | warn | H1 与 frontmatter 标题不一致 |
+//! | `empty_link` | error | 目标为空的 wiki 链接（`[[ ]]`） |
+//! | `duplicate_tags` | warn | frontmatter 中重复的标签（不区分大小写） |
+//! | `unknown_relation` | warn | 既不是内置关系也不是合法自定义关系的名称（会被当作 RelatedTo） |
+//! | `unclosed_link` | warn | 没有对应 `]]` 的 `[[` |
+//! | `invalid_strength` | warn | 链接强度不是数字或超出 0–1 |
+//! | `invalid_frontmatter` | error | frontmatter 不是合法的 YAML 映射（整个 frontmatter 被忽略） |
+//! | `long_note` | warn | 正文字数超过阈值，建议拆分 |
+//! | `explicit_related_to` | warn | 配置了其他默认关系（`links.default_relation`）时显式写出 RelatedTo 的链接 |
+//!
+//! `unknown_relation`、`unclosed_link`、`invalid_strength` 和 `invalid_frontmatter`
+//! 直接使用解析器返回的 [`ParseWarning`]，与索引时记录的警告一致
+//!
+//! 规则级别和字数阈值通过配置覆盖：
+//!
+//! ```toml
+//! [lint]
+//! max_words = 3000
+//!
+//! [lint.rules]
+//! missing_title = "off"
+//! unknown_relation = "error"
+//! ```
+//!
+//! 代码块中的内容不参与链接相关的检查
+
+use crate::core::parser::{
+    count_words, extract_frontmatter, parse_markdown_with_default, Frontmatter, ParseWarning, ParseWarningKind,
+};
+use crate::core::validate::{RuleLevel, Severity};
+use crate::storage::RelationType;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/// 默认字数阈值
+pub const DEFAULT_MAX_WORDS: usize = 2000;
+
+/// lint 配置（`[lint]`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintConfig {
+    /// 规则名到级别的映射，未列出的规则使用默认级别
+    #[serde(default)]
+    pub rules: BTreeMap<String, RuleLevel>,
+    /// `long_note` 规则的字数阈值
+    #[serde(default = "default_max_words")]
+    pub max_words: usize,
+    /// 仓库的默认链接关系，取自 `links.default_relation`，由调用方填写
+    #[serde(skip)]
+    pub default_relation: RelationType,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            rules: BTreeMap::new(),
+            max_words: DEFAULT_MAX_WORDS,
+            default_relation: RelationType::RelatedTo,
+        }
+    }
+}
+
+fn default_max_words() -> usize {
+    DEFAULT_MAX_WORDS
+}
+
+/// 一条诊断
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    /// 文件路径（相对仓库根目录）
+    pub path: String,
+    /// 行号（从 1 开始）
+    pub line: usize,
+    /// 规则名
+    pub rule: String,
+    /// 说明
+    pub message: String,
+    /// 严重程度（由规则级别决定）
+    pub severity: Severity,
+}
+
+impl Diagnostic {
+    /// 创建诊断，路径、规则名和严重程度由 [`Linter`] 统一填写
+    pub fn at(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            path: String::new(),
+            line,
+            rule: String::new(),
+            message: message.into(),
+            severity: Severity::Warning,
+        }
+    }
+}
+
+/// 待检查的笔记
+pub struct LintNote<'a> {
+    /// 文件内容
+    pub content: &'a str,
+    /// 解析后的 frontmatter
+    pub frontmatter: Option<Frontmatter>,
+    /// 正文（不含 frontmatter）
+    pub body: String,
+    /// 正文第一行在文件中的行号
+    pub body_line: usize,
+    /// 解析警告
+    pub warnings: Vec<ParseWarning>,
+}
+
+impl<'a> LintNote<'a> {
+    /// 解析笔记
+    pub fn new(content: &'a str) -> Self {
+        Self::with_default_relation(content, &RelationType::RelatedTo)
+    }
+
+    /// 解析笔记，未写关系的链接使用指定的默认关系
+    pub fn with_default_relation(content: &'a str, default_relation: &RelationType) -> Self {
+        let (_, body) = extract_frontmatter(content);
+        let (doc, warnings) = parse_markdown_with_default(content, "", default_relation);
+        let body_line = content.lines().count() - body.lines().count() + 1;
+        Self {
+            content,
+            frontmatter: doc.frontmatter,
+            body,
+            body_line,
+            warnings,
+        }
+    }
+
+    /// frontmatter 中某个键所在的行号
+    pub fn key_line(&self, key: &str) -> Option<usize> {
+        self.frontmatter.as_ref()?;
+        let prefix = format!("{}:", key);
+        self.content
+            .lines()
+            .take(self.body_line)
+            .position(|line| line.starts_with(&prefix))
+            .map(|i| i + 1)
+    }
+
+    /// 正文中不在代码块内的行及其行号
+    pub fn body_lines(&self) -> Vec<(usize, &str)> {
+        let mut in_fence = false;
+        let mut lines = Vec::new();
+        for (i, line) in self.body.lines().enumerate() {
+            if line.trim_start().starts_with("```") {
+                in_fence = !in_fence;
+                continue;
+            }
+            if !in_fence {
+                lines.push((self.body_line + i, line));
+            }
+        }
+        lines
+    }
+}
+
+/// lint 规则
+pub trait LintRule: Send + Sync {
+    /// 规则名（配置键）
+    fn name(&self) -> &str;
+
+    /// 规则说明
+    fn description(&self) -> &str;
+
+    /// 未配置时的级别
+    fn default_level(&self) -> RuleLevel {
+        RuleLevel::Warn
+    }
+
+    /// 检查笔记，返回所有诊断
+    fn check(&self, note: &LintNote, config: &LintConfig) -> Vec<Diagnostic>;
+}
+
+/// lint 报告
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LintReport {
+    /// 检查的文件数
+    pub files: usize,
+    /// 诊断（按文件和行号排序）
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl LintReport {
+    /// 最严重的诊断级别，没有诊断时为 None
+    pub fn worst(&self) -> Option<Severity> {
+        self.diagnostics.iter().map(|d| d.severity).max()
+    }
+
+    /// 指定严重程度的诊断数
+    pub fn count(&self, severity: Severity) -> usize {
+        self.diagnostics.iter().filter(|d| d.severity == severity).count()
+    }
+}
+
+/// 规则集合
+pub struct Linter {
+    rules: Vec<Box<dyn LintRule>>,
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Self::with_builtin_rules()
+    }
+}
+
+impl Linter {
+    /// 创建不含规则的 linter
+    pub fn empty() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// 创建包含所有内置规则的 linter
+    pub fn with_builtin_rules() -> Self {
+        let mut linter = Self::empty();
+        linter.register(Box::new(MissingTitle));
+        linter.register(Box::new(TitleMismatch));
+        linter.register(Box::new(EmptyLink));
+        linter.register(Box::new(DuplicateTags));
+        linter.register(Box::new(ParseWarningRule(ParseWarningKind::UnknownRelation)));
+        linter.register(Box::new(ParseWarningRule(ParseWarningKind::UnclosedLink)));
+        linter.register(Box::new(ParseWarningRule(ParseWarningKind::InvalidStrength)));
+        linter.register(Box::new(ParseWarningRule(ParseWarningKind::InvalidFrontmatter)));
+        linter.register(Box::new(LongNote));
+        linter.register(Box::new(ExplicitRelatedTo));
+        linter
+    }
+
+    /// 注册规则（同名规则会被替换）
+    pub fn register(&mut self, rule: Box<dyn LintRule>) {
+        self.rules.retain(|r| r.name() != rule.name());
+        self.rules.push(rule);
+    }
+
+    /// 检查单个文件
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 用于诊断的文件路径
+    /// * `content` - 文件内容
+    /// * `config` - lint 配置
+    ///
+    /// # Returns
+    ///
+    /// 按行号排序的诊断
+    pub fn lint(&self, path: &str, content: &str, config: &LintConfig) -> Vec<Diagnostic> {
+        let note = LintNote::with_default_relation(content, &config.default_relation);
+        let mut diagnostics = Vec::new();
+        for rule in &self.rules {
+            let level = config.rules.get(rule.name()).copied().unwrap_or_else(|| rule.default_level());
+            let Some(severity) = level.severity() else {
+                continue;
+            };
+            for mut diagnostic in rule.check(&note, config) {
+                diagnostic.path = path.to_string();
+                diagnostic.rule = rule.name().to_string();
+                diagnostic.severity = severity;
+                diagnostics.push(diagnostic);
+            }
+        }
+        diagnostics.sort_by_key(|d| d.line);
+        diagnostics
+    }
+}
+
+/// frontmatter 中没有标题
+pub struct MissingTitle;
+
+impl LintRule for MissingTitle {
+    fn name(&self) -> &str {
+        "missing_title"
+    }
+
+    fn description(&self) -> &str {
+        "Notes should set a title in their frontmatter"
+    }
+
+    fn check(&self, note: &LintNote, _config: &LintConfig) -> Vec<Diagnostic> {
+        let has_title = note
+            .frontmatter
+            .as_ref()
+            .and_then(|fm| fm.title.as_deref())
+            .is_some_and(|title| !title.trim().is_empty());
+        if has_title {
+            return Vec::new();
+        }
+        vec![Diagnostic::at(1, "frontmatter has no title")]
+    }
+}
+
+/// H1 与 frontmatter 标题不一致
+pub struct TitleMismatch;
+
+impl LintRule for TitleMismatch {
+    fn name(&self) -> &str {
+        "title_mismatch"
+    }
+
+    fn description(&self) -> &str {
+        "The first heading should match the frontmatter title"
+    }
+
+    fn check(&self, note: &LintNote, _config: &LintConfig) -> Vec<Diagnostic> {
+        let Some(title) = note.frontmatter.as_ref().and_then(|fm| fm.title.as_deref()) else {
+            return Vec::new();
+        };
+        let heading = note
+            .body_lines()
+            .into_iter()
+            .find_map(|(line, text)| text.strip_prefix("# ").map(|h| (line, h.trim())));
+        match heading {
+            Some((line, heading)) if heading != title.trim() => vec![Diagnostic::at(
+                line,
+                format!("heading \"{}\" does not match title \"{}\"", heading, title.trim()),
+            )],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// 目标为空的 wiki 链接
+pub struct EmptyLink;
+
+impl LintRule for EmptyLink {
+    fn name(&self) -> &str {
+        "empty_link"
+    }
+
+    fn description(&self) -> &str {
+        "Wiki links must name a target"
+    }
+
+    fn default_level(&self) -> RuleLevel {
+        RuleLevel::Error
+    }
+
+    fn check(&self, note: &LintNote, _config: &LintConfig) -> Vec<Diagnostic> {
+        let re = Regex::new(r"\[\[\s*(?:\|[^\]]*)?\]\]").unwrap();
+        note.body_lines()
+            .into_iter()
+            .flat_map(|(line, text)| {
+                re.find_iter(text)
+                    .map(move |m| Diagnostic::at(line, format!("link {} has no target and is ignored", m.as_str())))
+            })
+            .collect()
+    }
+}
+
+/// frontmatter 中重复的标签
+pub struct DuplicateTags;
+
+impl LintRule for DuplicateTags {
+    fn name(&self) -> &str {
+        "duplicate_tags"
+    }
+
+    fn description(&self) -> &str {
+        "Tags should be listed only once"
+    }
+
+    fn check(&self, note: &LintNote, _config: &LintConfig) -> Vec<Diagnostic> {
+        let Some(frontmatter) = &note.frontmatter else {
+            return Vec::new();
+        };
+        let line = note.key_line("tags").unwrap_or(1);
+        let mut seen = HashSet::new();
+        let mut reported = HashSet::new();
+        let mut diagnostics = Vec::new();
+        for tag in &frontmatter.tags {
+            let key = tag.to_lowercase();
+            if !seen.insert(key.clone()) && reported.insert(key) {
+                diagnostics.push(Diagnostic::at(line, format!("tag {} is listed more than once", tag)));
+            }
+        }
+        diagnostics
+    }
+}
+
+/// 解析器报告的一类警告，每个 [`ParseWarningKind`] 对应一条规则
+pub struct ParseWarningRule(pub ParseWarningKind);
+
+impl LintRule for ParseWarningRule {
+    fn name(&self) -> &str {
+        self.0.as_str()
+    }
+
+    fn description(&self) -> &str {
+        match self.0 {
+            ParseWarningKind::UnclosedLink => "Every [[ should be closed by ]] on the same line",
+            ParseWarningKind::InvalidFrontmatter => "Frontmatter must be a valid YAML mapping",
+            ParseWarningKind::InvalidStrength => "Link strengths must be numbers between 0 and 1",
+            ParseWarningKind::UnknownRelation => "Link relations must be built-in or valid custom relation names",
+        }
+    }
+
+    fn default_level(&self) -> RuleLevel {
+        match self.0 {
+            ParseWarningKind::InvalidFrontmatter => RuleLevel::Error,
+            _ => RuleLevel::Warn,
+        }
+    }
+
+    fn check(&self, note: &LintNote, _config: &LintConfig) -> Vec<Diagnostic> {
+        note.warnings
+            .iter()
+            .filter(|warning| warning.kind == self.0)
+            .map(|warning| Diagnostic::at(warning.line, warning.message.clone()))
+            .collect()
+    }
+}
+
+/// 正文过长
+pub struct LongNote;
+
+impl LintRule for LongNote {
+    fn name(&self) -> &str {
+        "long_note"
+    }
+
+    fn description(&self) -> &str {
+        "Long notes should be split into smaller ones"
+    }
+
+    fn check(&self, note: &LintNote, config: &LintConfig) -> Vec<Diagnostic> {
+        let words = count_words(&note.body);
+        if words <= config.max_words {
+            return Vec::new();
+        }
+        vec![Diagnostic::at(
+            1,
+            format!("note has {} words (limit {}); consider splitting it", words, config.max_words),
+        )]
+    }
+}
+
+/// 默认关系不是 RelatedTo 时显式写出 RelatedTo 的链接
+pub struct ExplicitRelatedTo;
+
+impl LintRule for ExplicitRelatedTo {
+    fn name(&self) -> &str {
+        "explicit_related_to"
+    }
+
+    fn description(&self) -> &str {
+        "Explicit RelatedTo links differ from the configured default relation"
+    }
+
+    fn check(&self, note: &LintNote, config: &LintConfig) -> Vec<Diagnostic> {
+        if config.default_relation == RelationType::RelatedTo {
+            return Vec::new();
+        }
+        let re = Regex::new(r"\[\[([^]|]+)\|([^]|:]+)").unwrap();
+        let mut diagnostics = Vec::new();
+        for (line, text) in note.body_lines() {
+            for cap in re.captures_iter(text) {
+                if RelationType::builtin(&cap[2]) == Some(RelationType::RelatedTo) {
+                    diagnostics.push(Diagnostic::at(
+                        line,
+                        format!(
+                            "link to {} is explicitly RelatedTo while links.default_relation is {}",
+                            cap[1].trim(),
+                            config.default_relation
+                        ),
+                    ));
+                }
+            }
+        }
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint(content: &str) -> Vec<Diagnostic> {
+        Linter::with_builtin_rules().lint("note.md", content, &LintConfig::default())
+    }
+
+    fn rules(diagnostics: &[Diagnostic]) -> Vec<&str> {
+        diagnostics.iter().map(|d| d.rule.as_str()).collect()
+    }
+
+    #[test]
+    fn test_clean_note() {
+        assert!(lint("---\ntitle: Idea\ntags: [a, b]\n---\n\n# Idea\n\nSee [[other|Supports:0.8]].\n").is_empty());
+    }
+
+    #[test]
+    fn test_missing_title() {
+        let diagnostics = lint("# Idea\n\nBody.\n");
+        assert_eq!(rules(&diagnostics), ["missing_title"]);
+        assert_eq!(diagnostics[0].line, 1);
+        assert_eq!(rules(&lint("---\ntype: Concept\n---\n# Idea\n")), ["missing_title"]);
+    }
+
+    #[test]
+    fn test_title_mismatch() {
+        let diagnostics = lint("---\ntitle: Idea\n---\n\n# Other idea\n");
+        assert_eq!(rules(&diagnostics), ["title_mismatch"]);
+        assert_eq!(diagnostics[0].line, 5);
+        assert!(lint("---\ntitle: Idea\n---\n\nNo heading at all.\n").is_empty());
+    }
+
+    #[test]
+    fn test_empty_link() {
+        let diagnostics = lint("---\ntitle: T\n---\n# T\n\nOne [[ ]] and [[|Supports]].\n\n```\n[[ ]]\n```\n");
+        assert_eq!(rules(&diagnostics), ["empty_link", "empty_link"]);
+        assert!(diagnostics.iter().all(|d| d.line == 6 && d.severity == Severity::Error));
+    }
+
+    #[test]
+    fn test_duplicate_tags() {
+        let diagnostics = lint("---\ntitle: T\ntags: [rust, Rust, go, rust]\n---\n# T\n");
+        assert_eq!(rules(&diagnostics), ["duplicate_tags"]);
+        assert_eq!(diagnostics[0].line, 3);
+        assert!(diagnostics[0].message.contains("Rust"));
+    }
+
+    #[test]
+    fn test_unknown_relation() {
+        let diagnostics = lint(
+            "---\ntitle: T\n---\n# T\n\n[[a|支持度:0.9]] [[b|derives_from]] [[c|leads to]] [[d|Cites-Methodology]]\n",
+        );
+        assert_eq!(rules(&diagnostics), ["unknown_relation", "unknown_relation"]);
+        assert!(diagnostics[0].message.contains("\"支持度\""));
+        assert!(diagnostics[1].message.contains("\"leads to\""));
+        assert!(diagnostics.iter().all(|d| d.line == 6));
+    }
+
+    #[test]
+    fn test_parser_warnings() {
+        let diagnostics = lint("---\ntitle: T\n---\n# T\n\n[[a|Supports:1.5]] and [[b\n\n```\n[[c\n```\n");
+        assert_eq!(rules(&diagnostics), ["unclosed_link", "invalid_strength"]);
+        assert!(diagnostics.iter().all(|d| d.line == 6));
+
+        let diagnostics = lint("---\ntitle: [T\n---\n# T\n");
+        let invalid: Vec<_> = diagnostics.iter().filter(|d| d.rule == "invalid_frontmatter").collect();
+        assert_eq!(invalid.len(), 1);
+        assert_eq!(invalid[0].severity, Severity::Error);
+        assert_eq!(invalid[0].line, 3);
+    }
+
+    #[test]
+    fn test_long_note_and_config() {
+        let content = format!("---\ntitle: T\n---\n# T\n\n{}\n", "word ".repeat(50));
+        let mut config = LintConfig {
+            max_words: 20,
+            ..LintConfig::default()
+        };
+        let linter = Linter::with_builtin_rules();
+        assert_eq!(rules(&linter.lint("n.md", &content, &config)), ["long_note"]);
+
+        config.rules.insert("long_note".to_string(), RuleLevel::Off);
+        assert!(linter.lint("n.md", &content, &config).is_empty());
+
+        config.rules.insert("missing_title".to_string(), RuleLevel::Error);
+        let diagnostics = linter.lint("n.md", "no frontmatter\n", &config);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].path, "n.md");
+    }
+
+    #[test]
+    fn test_explicit_related_to() {
+        let content = "---\ntitle: T\n---\n# T\n\n[[a|RelatedTo]] [[b|相关:0.5]] [[c]] [[d|Supports]]\n";
+        assert!(lint(content).is_empty());
+
+        let config = LintConfig {
+            default_relation: RelationType::References,
+            ..LintConfig::default()
+        };
+        let diagnostics = Linter::with_builtin_rules().lint("n.md", content, &config);
+        assert_eq!(rules(&diagnostics), ["explicit_related_to", "explicit_related_to"]);
+        assert!(diagnostics[0].message.contains("link to a") && diagnostics[0].message.ends_with("References"));
+        assert!(diagnostics.iter().all(|d| d.line == 6));
+    }
+}
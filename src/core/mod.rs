@@ -4,13 +4,64 @@ pub mod parser;
 pub mod watcher;
 pub mod indexer;
 pub mod watch_config;
+pub mod config;
+pub mod hooks;
+pub mod zettel;
+pub mod note;
+pub mod stats;
+pub mod review;
+pub mod suggestions;
+pub mod suggest;
+pub mod time_travel;
+pub mod decay;
+pub mod validate;
+pub mod lint;
+pub mod similarity;
+pub mod similarity_import;
+pub mod duplicates;
+pub mod file_log;
+pub mod folder_defaults;
+pub mod contradictions;
+pub mod publish;
+pub mod corpus;
+pub mod auto_snapshot;
+pub mod encrypted;
+pub mod trash;
+pub mod undo;
+pub mod paths;
+pub mod doctor;
+pub mod watch_journal;
+pub mod registry;
+pub mod rename;
+pub mod retag;
+pub mod archive;
+pub mod search;
+pub mod retention;
+pub mod graph_stats;
+pub mod link_refresh;
+pub mod render;
+pub mod writer;
+pub mod split;
+pub mod capture;
+pub mod query_templates;
+pub mod language;
+pub mod staging;
+pub mod derivation_tree;
+pub mod subgraph;
+pub mod graph_template;
+pub mod link_batch;
+pub mod layout;
+pub mod dashboard;
+pub mod feed;
+pub mod summary;
+pub mod synthetic;
 
 // 重新导出 repository 模块中的公共 API
 pub use repository::Repository;
 
 // 重新导出 parser 模块中的公共 API
 pub use parser::{
-    parse_markdown, quick_parse, ParsedDoc, WikiLink, Frontmatter,
+    parse_file, parse_markdown, quick_parse, ParsedDoc, ParseWarning, ParseWarningKind, WikiLink, Frontmatter,
     frontmatter::{parse_frontmatter, extract_frontmatter, has_frontmatter},
     wiki_link::{parse_wiki_links, remove_wiki_links, replace_wiki_links_with_text},
 };
@@ -19,7 +70,10 @@ pub use parser::{
 pub use watcher::{FileWatcher, FileWatcherConfig, FileEvent, run_watcher};
 
 // 重新导出 indexer 模块中的公共 API
-pub use indexer::{Indexer, IndexResult, IndexSummary};
+pub use indexer::{Indexer, IndexResult, IndexSummary, SkipReason};
 
 // 重新导出 watch_config 模块中的公共 API
 pub use watch_config::WatchConfig;
+
+// 重新导出 config 模块中的公共 API
+pub use config::{RepoConfig, CacheConfig, ChangesConfig, DerivationRecording, IdsConfig, IndexConfig, HooksConfig, PathsConfig, ServeConfig, ServeScope, ServeToken, WatchDaemonConfig};
@@ -0,0 +1,295 @@
+//! 笔记创建模块
+//!
+//! 为 `memexia new` 生成带 frontmatter 脚手架的新笔记文件
+//!
+//! ## 模板
+//!
+//! 用户模板位于 `.memexia/templates/<name>.md`，支持以下占位符：
+//!
+//! - `{{title}}` - 笔记标题
+//! - `{{date}}` - 创建日期（`YYYY-MM-DD`）
+//!
+//! 独占一行的 `{{backlinks}}`、`{{related}}` 和 `{{children}}` 由图谱展开，
+//! 见 [`crate::core::graph_template`]
+//!
+//! 目录默认的 frontmatter 和模板见 [`crate::core::folder_defaults`]
+
+use crate::core::folder_defaults::FolderDefaults;
+use crate::core::parser::Frontmatter;
+use crate::core::zettel;
+use crate::storage::NodeType;
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDateTime;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 新笔记的默认目录（相对仓库根目录）
+pub const NOTES_DIR: &str = "notes";
+
+/// 用户模板目录（相对仓库根目录）
+pub const TEMPLATES_DIR: &str = ".memexia/templates";
+
+/// 新笔记选项
+#[derive(Debug, Clone)]
+pub struct NoteOptions {
+    /// 笔记标题
+    pub title: String,
+    /// 节点类型，为 None 时使用目录默认值（没有则为 Concept）
+    pub node_type: Option<NodeType>,
+    /// 标签列表
+    pub tags: Vec<String>,
+    /// 模板名称（对应 `.memexia/templates/<name>.md`），为 None 时使用目录默认模板
+    pub template: Option<String>,
+    /// 目标目录（相对仓库根目录），为 None 时使用 [`NOTES_DIR`]
+    pub dir: Option<String>,
+}
+
+impl NoteOptions {
+    /// 创建默认选项（目录默认类型，无标签，无模板）
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            node_type: None,
+            tags: Vec::new(),
+            template: None,
+            dir: None,
+        }
+    }
+}
+
+/// 创建新笔记
+///
+/// 启用 Zettel ID 时文件名为 `<ID> <标题>.md`，否则为 slug 化的标题，
+/// 与已有文件冲突时追加 `-2`、`-3` 等后缀。目标路径匹配目录默认值时，
+/// 未在选项中指定的类型和模板取默认值，默认标签与选项中的标签合并
+///
+/// # Arguments
+///
+/// * `root` - 仓库根目录
+/// * `options` - 笔记选项
+/// * `zettel_ids` - 是否生成 Zettel ID 前缀
+/// * `now` - 当前本地时间（用于生成 ID 和 `{{date}}`）
+///
+/// # Returns
+///
+/// 新文件的路径
+pub fn create_note(root: &Path, options: &NoteOptions, zettel_ids: bool, now: NaiveDateTime) -> Result<PathBuf> {
+    let title = options.title.trim();
+    if title.is_empty() {
+        bail!("Note title cannot be empty");
+    }
+
+    let relative_dir = options.dir.as_deref().unwrap_or(NOTES_DIR);
+    let dir = root.join(relative_dir);
+    let path = if zettel_ids {
+        let existing = zettel::collect_zettel_ids(root);
+        let id = zettel::generate_zettel_id(now, &existing);
+        dir.join(format!("{} {}.md", id, sanitize_file_name(title)))
+    } else {
+        unique_path(&dir, &slugify(title))
+    };
+
+    let defaults = FolderDefaults::load(root)?;
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let relative = format!("{}/{}", relative_dir.trim_end_matches('/'), file_name);
+    let folder = defaults.lookup(relative.trim_start_matches("./")).cloned().unwrap_or_default();
+
+    // 先加载模板，避免模板缺失时留下空文件
+    let body = match options.template.as_ref().or(folder.template.as_ref()) {
+        Some(name) => render_template(&load_template(root, name)?, title, now),
+        None => format!("# {}\n\n", title),
+    };
+    fs::create_dir_all(&dir)?;
+
+    let mut tags = options.tags.clone();
+    for tag in folder.tags() {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    let frontmatter = Frontmatter {
+        title: Some(title.to_string()),
+        node_type: options.node_type.clone().or_else(|| folder.node_type()).unwrap_or_default(),
+        tags,
+        summary: None,
+        aliases: Vec::new(),
+        created: None,
+        lang: None,
+        noindex: false,
+    };
+    let content = format!("---\n{}{}---\n\n{}", frontmatter.to_yaml(), folder.extra_yaml(), body);
+    fs::write(&path, content).with_context(|| format!("Failed to write {:?}", path))?;
+
+    Ok(path)
+}
+
+/// 将标题转换为文件名 slug
+///
+/// 小写化，字母数字（含中文等 Unicode 字符）保留，其余字符折叠为单个 `-`
+pub fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars().flat_map(|c| c.to_lowercase()) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    let slug = slug.trim_end_matches('-').to_string();
+    if slug.is_empty() {
+        "untitled".to_string()
+    } else {
+        slug
+    }
+}
+
+/// 替换模板中的占位符
+pub fn render_template(template: &str, title: &str, now: NaiveDateTime) -> String {
+    template
+        .replace("{{title}}", title)
+        .replace("{{date}}", &now.format("%Y-%m-%d").to_string())
+}
+
+/// 读取用户模板
+fn load_template(root: &Path, name: &str) -> Result<String> {
+    let path = root.join(TEMPLATES_DIR).join(format!("{}.md", name));
+    if !path.exists() {
+        bail!("Template not found: {:?}", path);
+    }
+    fs::read_to_string(&path).with_context(|| format!("Failed to read template {:?}", path))
+}
+
+/// 生成不与已有文件冲突的路径
+fn unique_path(dir: &Path, slug: &str) -> PathBuf {
+    let mut path = dir.join(format!("{}.md", slug));
+    let mut suffix = 1;
+    while path.exists() {
+        suffix += 1;
+        path = dir.join(format!("{}-{}.md", slug, suffix));
+    }
+    path
+}
+
+/// 将标题转换为合法文件名（替换路径分隔符等保留字符）
+fn sanitize_file_name(title: &str) -> String {
+    title
+        .chars()
+        .map(|c| if matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') { '-' } else { c })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parse_markdown;
+    use chrono::NaiveDate;
+    use tempfile::TempDir;
+
+    fn now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 3, 15)
+            .unwrap()
+            .and_hms_opt(10, 30, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Free Will"), "free-will");
+        assert_eq!(slugify("  What is   *mind*? "), "what-is-mind");
+        assert_eq!(slugify("自由 意志"), "自由-意志");
+        assert_eq!(slugify("???"), "untitled");
+    }
+
+    #[test]
+    fn test_create_zettel_note() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let options = NoteOptions::new("Free Will");
+
+        let first = create_note(root, &options, true, now()).unwrap();
+        let second = create_note(root, &options, true, now()).unwrap();
+
+        assert_eq!(first.file_name().unwrap(), "202403151030 Free Will.md");
+        assert_eq!(second.file_name().unwrap(), "202403151031 Free Will.md");
+
+        let content = fs::read_to_string(&first).unwrap();
+        let doc = parse_markdown(&content, "notes/202403151030 Free Will.md").0;
+        assert_eq!(doc.get_title(), "Free Will");
+    }
+
+    #[test]
+    fn test_create_note_slug_collisions() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let options = NoteOptions::new("Free Will");
+
+        let first = create_note(root, &options, false, now()).unwrap();
+        let second = create_note(root, &options, false, now()).unwrap();
+        let third = create_note(root, &options, false, now()).unwrap();
+
+        assert_eq!(first.file_name().unwrap(), "free-will.md");
+        assert_eq!(second.file_name().unwrap(), "free-will-2.md");
+        assert_eq!(third.file_name().unwrap(), "free-will-3.md");
+    }
+
+    #[test]
+    fn test_create_note_type_and_tags() {
+        let temp_dir = TempDir::new().unwrap();
+        let options = NoteOptions {
+            node_type: Some(NodeType::Question),
+            tags: vec!["哲学".to_string(), "mind".to_string()],
+            ..NoteOptions::new("Is the mind physical?")
+        };
+
+        let path = create_note(temp_dir.path(), &options, false, now()).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        let doc = parse_markdown(&content, "notes/is-the-mind-physical.md").0;
+
+        assert_eq!(doc.get_node_type(), NodeType::Question);
+        assert!(doc.get_all_tags().contains(&"哲学".to_string()));
+        assert!(content.contains("# Is the mind physical?"));
+    }
+
+    #[test]
+    fn test_create_note_from_template() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join(TEMPLATES_DIR)).unwrap();
+        fs::write(
+            root.join(TEMPLATES_DIR).join("daily.md"),
+            "# {{title}}\n\nDate: {{date}}\n\n## Notes\n",
+        )
+        .unwrap();
+
+        let options = NoteOptions {
+            template: Some("daily".to_string()),
+            ..NoteOptions::new("Standup")
+        };
+        let path = create_note(root, &options, false, now()).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+
+        assert!(content.starts_with("---\ntitle: \"Standup\"\n"));
+        assert!(content.contains("# Standup\n\nDate: 2024-03-15\n\n## Notes\n"));
+
+        let missing = NoteOptions {
+            template: Some("missing".to_string()),
+            ..NoteOptions::new("Other")
+        };
+        assert!(create_note(root, &missing, false, now()).is_err());
+        assert!(!root.join(NOTES_DIR).join("other.md").exists());
+    }
+
+    #[test]
+    fn test_create_note_in_configured_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let options = NoteOptions {
+            dir: Some("content/inbox".to_string()),
+            ..NoteOptions::new("Inbox Item")
+        };
+
+        let path = create_note(temp_dir.path(), &options, false, now()).unwrap();
+        assert_eq!(path, temp_dir.path().join("content/inbox/inbox-item.md"));
+        assert!(!temp_dir.path().join(NOTES_DIR).exists());
+    }
+}
@@ -9,14 +9,27 @@
 //! - 提取 `#tags` 标签
 //! - 提取标题（从 frontmatter 或 H1）
 //! - 生成纯文本内容（去链接）
+//!
+//! `.org` 文件由 [`org`] 子模块解析为相同的 [`ParsedDoc`] 结构，见 [`parse_file`]
+//!
+//! 可以容忍的写法问题不会导致解析失败，而是作为 [`ParseWarning`] 与文档一起返回
 
 pub mod frontmatter;
+pub mod org;
+pub mod warning;
 pub mod wiki_link;
 
-pub use frontmatter::{parse_frontmatter, extract_frontmatter, has_frontmatter, Frontmatter};
-pub use wiki_link::{parse_wiki_links, remove_wiki_links, replace_wiki_links_with_text, WikiLink};
-
-use crate::storage::{Node, NodeType};
+pub use frontmatter::{
+    parse_frontmatter, try_parse_frontmatter, extract_frontmatter, has_frontmatter, Frontmatter, FrontmatterError,
+};
+pub use warning::{ParseWarning, ParseWarningKind};
+pub use wiki_link::{
+    link_warnings, link_warnings_with_default, parse_wiki_links, parse_wiki_links_with_default, remove_wiki_links,
+    replace_wiki_links_with_text, WikiLink,
+};
+
+use crate::core::{graph_template, language};
+use crate::storage::{ids, Node, NodeType, RelationType, WORD_COUNT_KEY};
 use std::collections::HashSet;
 
 /// 解析后的文档结构
@@ -36,6 +49,10 @@ pub struct ParsedDoc {
     pub title: Option<String>,
     /// 文件名（用于生成节点 ID）
     pub file_name: String,
+    /// 正文字数（不含 frontmatter）
+    pub word_count: usize,
+    /// 是否排除在索引之外（来自 frontmatter 的 `memexia: noindex` / `index: false`）
+    pub noindex: bool,
 }
 
 impl Default for ParsedDoc {
@@ -47,6 +64,8 @@ impl Default for ParsedDoc {
             content: String::new(),
             title: None,
             file_name: String::new(),
+            word_count: 0,
+            noindex: false,
         }
     }
 }
@@ -105,7 +124,22 @@ impl ParsedDoc {
 
     /// 转换为 Node
     pub fn to_node(&self) -> Node {
-        let node_id = Self::file_name_to_id(&self.file_name);
+        self.build_node(Self::file_name_to_id(&self.file_name))
+    }
+
+    /// 转换为 Node（Zettelkasten ID 模式）
+    ///
+    /// 文件名以时间戳 ID 开头时使用 `urn:memexia:zettel:<ID>` 作为节点 ID，
+    /// 否则回退到基于文件路径的 ID
+    pub fn to_zettel_node(&self) -> Node {
+        let node_id = super::zettel::extract_zettel_id(&self.file_name)
+            .map(super::zettel::zettel_node_id)
+            .unwrap_or_else(|| Self::file_name_to_id(&self.file_name));
+        self.build_node(node_id)
+    }
+
+    /// 以指定 ID 构建 Node
+    fn build_node(&self, node_id: String) -> Node {
         let mut node = Node::new(
             &node_id,
             self.get_node_type(),
@@ -126,6 +160,9 @@ impl ParsedDoc {
         // 设置摘要（如果有）
         if let Some(ref fm) = self.frontmatter {
             if let Some(ref summary) = fm.summary {
+                if !summary.trim().is_empty() {
+                    node.set_summary(summary.trim());
+                }
                 if node.content.is_none() || node.content.as_ref().map(|c| c.len()).unwrap_or(0) > summary.len() {
                     // 如果摘要比完整内容短，使用摘要
                     if summary.len() < 200 {
@@ -135,34 +172,32 @@ impl ParsedDoc {
             }
         }
 
+        // 设置创建时间（frontmatter 中的 created/date）
+        if let Some(created) = self.frontmatter.as_ref().and_then(|fm| fm.created) {
+            node.created_at = created;
+            node.updated_at = node.updated_at.max(created);
+        }
+
+        // 记录字数
+        node.metadata.insert(WORD_COUNT_KEY.to_string(), self.word_count.into());
+
+        // 记录正文语言（frontmatter `lang` 优先）
+        let language = self
+            .frontmatter
+            .as_ref()
+            .and_then(|fm| fm.lang.clone())
+            .or_else(|| language::detect(&self.content).map(str::to_string));
+        if let Some(language) = language {
+            node.set_language(language);
+        }
+
         node
     }
 
     /// 将文件路径转换为 URN 格式 ID（带 URL 编码）
     fn file_name_to_id(file_name: &str) -> String {
-        let path_str = file_name.replace('\\', "/");
-        let encoded = encode_iri_component(&path_str);
-        format!("urn:memexia:file:{}", encoded)
-    }
-}
-
-/// 对 IRI 路径组件进行 percent 编码
-fn encode_iri_component(s: &str) -> String {
-    let mut result = String::new();
-    for c in s.chars() {
-        // 只对 ASCII 字母数字和安全的符号不编码
-        if c.is_ascii_alphanumeric() || "-_.~!$&'()*+,;=:@/".contains(c) {
-            result.push(c);
-        } else {
-            let mut buf = [0u8; 4];
-            let encoded = c.encode_utf8(&mut buf);
-            for byte in encoded.as_bytes() {
-                result.push('%');
-                result.push_str(&format!("{:02X}", byte));
-            }
-        }
+        ids::file_id(file_name)
     }
-    result
 }
 
 /// 解析 Markdown 内容
@@ -174,21 +209,45 @@ fn encode_iri_component(s: &str) -> String {
 ///
 /// # Returns
 ///
-/// 解析后的 ParsedDoc
-pub fn parse_markdown(content: &str, file_name: &str) -> ParsedDoc {
+/// 解析后的 ParsedDoc 和解析警告（行号相对整个文件）
+pub fn parse_markdown(content: &str, file_name: &str) -> (ParsedDoc, Vec<ParseWarning>) {
+    parse_markdown_with_default(content, file_name, &RelationType::RelatedTo)
+}
+
+/// 解析 Markdown 内容，未写关系的链接使用指定的默认关系（配置 `links.default_relation`）
+pub fn parse_markdown_with_default(
+    content: &str,
+    file_name: &str,
+    default_relation: &RelationType,
+) -> (ParsedDoc, Vec<ParseWarning>) {
     let mut doc = ParsedDoc::new(file_name);
+    let mut warnings = Vec::new();
 
     // 1. 提取并解析 frontmatter
     let (fm_yaml, remaining) = extract_frontmatter(content);
-    if let Some(yaml_str) = fm_yaml {
-        doc.frontmatter = parse_frontmatter(&format!("---\n{}---", yaml_str));
+    if fm_yaml.is_some() {
+        match try_parse_frontmatter(content) {
+            Ok(frontmatter) => doc.frontmatter = frontmatter,
+            Err(e) => warnings.push(ParseWarning::new(
+                e.line,
+                ParseWarningKind::InvalidFrontmatter,
+                format!("invalid frontmatter YAML: {}; the frontmatter is ignored", e.message),
+            )),
+        }
+        doc.noindex = doc.frontmatter.as_ref().is_some_and(|fm| fm.noindex);
     }
 
-    // 2. 解析 wiki 链接
-    doc.wiki_links = parse_wiki_links(&remaining);
+    // 2. 解析 wiki 链接（remaining 是 content 的后缀，据此换算正文行号；跳过图谱模板生成块）
+    let linked = graph_template::mask_generated(&remaining);
+    doc.wiki_links = parse_wiki_links_with_default(&linked, default_relation);
+    let body_offset = content[..content.len() - remaining.len()].matches('\n').count();
+    warnings.extend(link_warnings_with_default(&linked, default_relation).into_iter().map(|mut warning| {
+        warning.line += body_offset;
+        warning
+    }));
 
     // 3. 提取 #tag 标签
-    doc.tags = extract_tags(&remaining);
+    doc.tags = extract_tags(&linked);
 
     // 4. 提取标题（如果 frontmatter 没有）
     if doc.frontmatter.is_none() || doc.frontmatter.as_ref().unwrap().title.is_none() {
@@ -198,19 +257,90 @@ pub fn parse_markdown(content: &str, file_name: &str) -> ParsedDoc {
     // 5. 设置纯内容（移除 wiki 链接，保留其他内容）
     doc.content = remove_wiki_links(&remaining);
 
-    doc
+    // 6. 统计正文字数
+    doc.word_count = count_words(&remaining);
+
+    (doc, warnings)
+}
+
+/// 按扩展名解析文件内容
+///
+/// `.org` 文件使用 Org-mode 解析器，其他文件按 Markdown 解析
+///
+/// # Arguments
+///
+/// * `content` - 文件内容
+/// * `file_name` - 文件名（用于判断格式和生成节点 ID）
+///
+/// Org-mode 解析器目前不产生警告
+pub fn parse_file(content: &str, file_name: &str) -> (ParsedDoc, Vec<ParseWarning>) {
+    parse_file_with_default(content, file_name, &RelationType::RelatedTo)
+}
+
+/// 按扩展名解析文件内容，未写关系的链接使用指定的默认关系
+///
+/// Org 链接没有关系写法，全部使用默认关系
+pub fn parse_file_with_default(
+    content: &str,
+    file_name: &str,
+    default_relation: &RelationType,
+) -> (ParsedDoc, Vec<ParseWarning>) {
+    if file_name.ends_with(".org") {
+        let mut doc = org::parse_org(content, file_name);
+        for link in &mut doc.wiki_links {
+            link.relation = default_relation.clone();
+        }
+        (doc, Vec::new())
+    } else {
+        parse_markdown_with_default(content, file_name, default_relation)
+    }
+}
+
+/// 统计字数
+///
+/// 连续的字母数字（可含内部撇号）计为一个词，中日韩字符每个字计为一个词
+pub fn count_words(text: &str) -> usize {
+    let mut count = 0;
+    let mut in_word = false;
+
+    for c in text.chars() {
+        if is_cjk(c) {
+            count += 1;
+            in_word = false;
+        } else if c.is_alphanumeric() || c == '_' || (in_word && c == '\'') {
+            if !in_word {
+                count += 1;
+                in_word = true;
+            }
+        } else {
+            in_word = false;
+        }
+    }
+
+    count
+}
+
+/// 是否为中日韩文字
+pub(crate) fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'     // 平假名、片假名
+        | '\u{3400}'..='\u{4DBF}'   // CJK 扩展 A
+        | '\u{4E00}'..='\u{9FFF}'   // CJK 统一汉字
+        | '\u{AC00}'..='\u{D7AF}'   // 谚文音节
+        | '\u{F900}'..='\u{FAFF}'   // CJK 兼容汉字
+    )
 }
 
 /// 从内容中提取标签
 ///
-/// 匹配 `#tag` 格式的标签
+/// 匹配 `#tag` 格式的标签，层级标签以 `/` 分隔（`#topic/sub`）
 fn extract_tags(content: &str) -> Vec<String> {
     let mut tags = Vec::new();
-    let re = regex::Regex::new(r"(?m)(?:^|\s)#(\w[\w-]*)").unwrap();
+    let re = regex::Regex::new(r"(?m)(?:^|\s)#(\w[\w/-]*)").unwrap();
 
     for cap in re.captures_iter(content) {
         if let Some(tag) = cap.get(1) {
-            let tag_str = tag.as_str().to_string();
+            let tag_str = tag.as_str().trim_end_matches('/').to_string();
             if !tags.contains(&tag_str) {
                 tags.push(tag_str);
             }
@@ -236,8 +366,9 @@ fn extract_title(content: &str) -> Option<String> {
 pub fn quick_parse(content: &str) -> (Option<Frontmatter>, Vec<WikiLink>, Vec<String>) {
     let (fm_yaml, remaining) = extract_frontmatter(content);
     let frontmatter = fm_yaml.and_then(|yaml| parse_frontmatter(&format!("---\n{}---", &yaml)));
-    let links = parse_wiki_links(&remaining);
-    let tags = extract_tags(&remaining);
+    let linked = graph_template::mask_generated(&remaining);
+    let links = parse_wiki_links(&linked);
+    let tags = extract_tags(&linked);
 
     (frontmatter, links, tags)
 }
@@ -266,7 +397,7 @@ summary: 关于自由意志的讨论
 
 #标签1 #标签2"#;
 
-        let doc = parse_markdown(content, "notes/free_will.md");
+        let doc = parse_markdown(content, "notes/free_will.md").0;
 
         assert_eq!(doc.file_name, "notes/free_will.md");
         assert!(doc.frontmatter.is_some());
@@ -281,7 +412,7 @@ summary: 关于自由意志的讨论
 
 内容 [[目标|RelatedTo]] #tag1"##;
 
-        let doc = parse_markdown(content, "test.md");
+        let doc = parse_markdown(content, "test.md").0;
 
         assert!(doc.frontmatter.is_none());
         assert_eq!(doc.title, Some("标题".to_string()));
@@ -299,7 +430,7 @@ tags: [test]
 
 测试内容 [[目标|Supports]]"#;
 
-        let doc = parse_markdown(content, "test.md");
+        let doc = parse_markdown(content, "test.md").0;
         let node = doc.to_node();
 
         assert_eq!(node.id, "urn:memexia:file:test.md");
@@ -308,6 +439,20 @@ tags: [test]
         assert!(node.tags.contains(&"test".to_string()));
     }
 
+    #[test]
+    fn test_parsed_doc_to_zettel_node() {
+        let doc = parse_markdown("# Free Will", "notes/202403151030 Free Will.md").0;
+        assert_eq!(doc.to_zettel_node().id, "urn:memexia:zettel:202403151030");
+
+        // 重命名标题部分不改变身份
+        let renamed = parse_markdown("# Free Will", "notes/202403151030 Libertarian Free Will.md").0;
+        assert_eq!(renamed.to_zettel_node().id, "urn:memexia:zettel:202403151030");
+
+        // 无 ID 前缀时回退到文件 ID
+        let plain = parse_markdown("# Plain", "notes/plain.md").0;
+        assert_eq!(plain.to_zettel_node().id, "urn:memexia:file:notes/plain.md");
+    }
+
     #[test]
     fn test_get_all_tags() {
         let content = r#"---
@@ -318,7 +463,7 @@ tags: [fm-tag]
 
 内容 #content-tag #another"#;
 
-        let doc = parse_markdown(content, "test.md");
+        let doc = parse_markdown(content, "test.md").0;
         let all_tags = doc.get_all_tags();
 
         assert!(all_tags.contains(&"fm-tag".to_string()));
@@ -353,4 +498,42 @@ type: Evidence
         assert!(tags.contains(&"tag2".to_string()));
         assert!(tags.contains(&"tag3".to_string()));
     }
+
+    #[test]
+    fn test_count_words() {
+        assert_eq!(count_words("One two, three!"), 3);
+        assert_eq!(count_words("don't stop"), 2);
+        assert_eq!(count_words("# Title\n\n[[Free Will]] matters"), 4);
+        assert_eq!(count_words("自由意志 is real"), 6);
+        assert_eq!(count_words(""), 0);
+
+        let doc = parse_markdown("---\ntitle: T\n---\nbody words here", "a.md").0;
+        assert_eq!(doc.word_count, 3);
+        assert_eq!(doc.to_node().word_count(), Some(3));
+    }
+
+    #[test]
+    fn test_parse_markdown_warnings() {
+        let content = "---\ntitle: T\n---\n\n# T\n\n[[a|bogus relation]]\n[[b|Supports:2]] [[c\n";
+        let (doc, warnings) = parse_markdown(content, "t.md");
+        assert_eq!(doc.wiki_links.len(), 2);
+        let kinds: Vec<_> = warnings.iter().map(|w| (w.line, w.kind)).collect();
+        assert_eq!(
+            kinds,
+            [
+                (7, ParseWarningKind::UnknownRelation),
+                (8, ParseWarningKind::UnclosedLink),
+                (8, ParseWarningKind::InvalidStrength),
+            ]
+        );
+
+        let (doc, warnings) = parse_markdown("---\ntitle: T\ntags: [a\n---\n\n[[x]]\n", "t.md");
+        assert!(doc.frontmatter.is_none());
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, ParseWarningKind::InvalidFrontmatter);
+        assert_eq!(warnings[0].line, 4);
+
+        assert!(parse_markdown("# Clean\n\n[[a|Supports:0.5]]\n", "t.md").1.is_empty());
+        assert!(parse_file("* Org\n[[a\n", "t.org").1.is_empty());
+    }
 }
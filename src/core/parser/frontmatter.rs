@@ -10,10 +10,20 @@
 //! type: Concept
 //! tags: [哲学, 心灵]
 //! summary: 简短描述
+//! aliases: [自由意志问题]
+//! created: 2024-03-15
+//! lang: zh
 //! ---
 //! ```
+//!
+//! `lang` 指定正文语言，覆盖索引时的自动检测（见 [`crate::core::language`]）
+//!
+//! `created`（或 `date`）支持 RFC 3339、`YYYY-MM-DD HH:MM[:SS]` 和 `YYYY-MM-DD` 格式
+//!
+//! `memexia: noindex` 或 `index: false` 把笔记排除在图和搜索之外（文件仍由 Git 管理）
 
 use crate::storage::{Node, NodeType};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use yaml_rust2::{Yaml, YamlLoader};
 
 /// Frontmatter 结构
@@ -29,6 +39,14 @@ pub struct Frontmatter {
     pub tags: Vec<String>,
     /// 简短描述
     pub summary: Option<String>,
+    /// 别名列表
+    pub aliases: Vec<String>,
+    /// 创建时间
+    pub created: Option<DateTime<Utc>>,
+    /// 正文语言（`lang`）
+    pub lang: Option<String>,
+    /// 是否排除在索引之外（`memexia: noindex` 或 `index: false`）
+    pub noindex: bool,
 }
 
 impl Frontmatter {
@@ -44,6 +62,10 @@ impl Frontmatter {
             node_type: node.node_type.clone(),
             tags: node.tags.clone(),
             summary: node.content.clone(),
+            aliases: Vec::new(),
+            created: Some(node.created_at),
+            lang: None,
+            noindex: false,
         }
     }
 
@@ -72,10 +94,32 @@ impl Frontmatter {
             yaml.push_str(&format!("summary: \"{}\"\n", escape_yaml_string(summary)));
         }
 
+        if !self.aliases.is_empty() {
+            let aliases: Vec<String> = self.aliases.iter().map(|a| format!("\"{}\"", escape_yaml_string(a))).collect();
+            yaml.push_str(&format!("aliases: [{}]\n", aliases.join(", ")));
+        }
+
+        if let Some(lang) = &self.lang {
+            yaml.push_str(&format!("lang: {}\n", lang));
+        }
+
+        if let Some(created) = &self.created {
+            yaml.push_str(&format!("created: {}\n", created.to_rfc3339()));
+        }
+
         yaml
     }
 }
 
+/// frontmatter 存在但无法解析
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrontmatterError {
+    /// 出错的行号（从 1 开始，相对传入的内容）
+    pub line: usize,
+    /// 说明
+    pub message: String,
+}
+
 /// 解析 YAML frontmatter
 ///
 /// # Arguments
@@ -84,26 +128,49 @@ impl Frontmatter {
 ///
 /// # Returns
 ///
-/// 解析后的 Frontmatter，如果 frontmatter 不存在或解析失败返回 None
+/// 解析后的 Frontmatter，如果 frontmatter 不存在或解析失败返回 None；
+/// 需要区分两者时使用 [`try_parse_frontmatter`]
 pub fn parse_frontmatter(content: &str) -> Option<Frontmatter> {
+    try_parse_frontmatter(content).ok().flatten()
+}
+
+/// 解析 YAML frontmatter，区分"没有 frontmatter"和"frontmatter 不是合法 YAML"
+///
+/// # Returns
+///
+/// 没有 frontmatter（或 frontmatter 为空）时返回 `Ok(None)`，
+/// YAML 语法错误或顶层不是映射时返回带行号的 [`FrontmatterError`]
+pub fn try_parse_frontmatter(content: &str) -> std::result::Result<Option<Frontmatter>, FrontmatterError> {
     // 检查是否有 frontmatter
     let start = content.trim_start();
     if !start.starts_with("---") {
-        return None;
+        return Ok(None);
     }
 
     // 找到结束 ---
     let end_marker = match start[4..].find("---") {
         Some(pos) => pos + 4,
-        None => return None,
+        None => return Ok(None),
     };
 
     let yaml_str = &start[4..end_marker];
+    // YAML 第一行在内容中的行号（跳过前导空行和开头的 ---）
+    let first_line = content[..content.len() - start.len()].matches('\n').count() + 2;
 
     // 解析 YAML
-    let docs = YamlLoader::load_from_str(yaml_str).ok()?;
-    let doc = docs.first()?;
-    let hash = doc.as_hash()?;
+    let docs = YamlLoader::load_from_str(yaml_str).map_err(|e| FrontmatterError {
+        line: first_line + e.marker().line().saturating_sub(1),
+        message: e.info().to_string(),
+    })?;
+    let Some(doc) = docs.first() else {
+        return Ok(None);
+    };
+    let Some(hash) = doc.as_hash() else {
+        return Err(FrontmatterError {
+            line: first_line,
+            message: "frontmatter is not a key-value mapping".to_string(),
+        });
+    };
     let mut frontmatter = Frontmatter::new();
 
     // 解析各字段
@@ -129,7 +196,53 @@ pub fn parse_frontmatter(content: &str) -> Option<Frontmatter> {
         frontmatter.summary = summary.as_str().map(|s| s.to_string());
     }
 
-    Some(frontmatter)
+    // `aliases` 可以是列表或单个字符串
+    match hash.get(&Yaml::String("aliases".to_string())) {
+        Some(Yaml::Array(aliases)) => {
+            frontmatter.aliases = aliases.iter().filter_map(|a| a.as_str()).map(str::to_string).collect();
+        }
+        Some(Yaml::String(alias)) => frontmatter.aliases = vec![alias.clone()],
+        _ => {}
+    }
+
+    let created = hash
+        .get(&Yaml::String("created".to_string()))
+        .or_else(|| hash.get(&Yaml::String("date".to_string())));
+    if let Some(created) = created.and_then(|c| c.as_str()) {
+        frontmatter.created = parse_datetime(created);
+    }
+
+    if let Some(lang) = hash.get(&Yaml::String("lang".to_string())).and_then(|v| v.as_str()) {
+        frontmatter.lang = crate::core::language::normalize(lang);
+    }
+
+    let memexia_noindex = hash
+        .get(&Yaml::String("memexia".to_string()))
+        .and_then(|v| v.as_str())
+        .is_some_and(|v| v.trim().eq_ignore_ascii_case("noindex"));
+    let index_false = hash.get(&Yaml::String("index".to_string())).and_then(|v| v.as_bool()) == Some(false);
+    frontmatter.noindex = memexia_noindex || index_false;
+
+    Ok(Some(frontmatter))
+}
+
+/// 解析 frontmatter 中的日期时间
+///
+/// 不带时区的时间按 UTC 处理
+pub fn parse_datetime(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S"] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(s, format) {
+            return Some(dt.and_utc());
+        }
+    }
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc())
 }
 
 /// 解析节点类型字符串
@@ -147,7 +260,7 @@ fn parse_node_type(s: &str) -> NodeType {
 }
 
 /// 转义 YAML 字符串中的特殊字符
-fn escape_yaml_string(s: &str) -> String {
+pub(crate) fn escape_yaml_string(s: &str) -> String {
     let mut result = String::with_capacity(s.len());
     for c in s.chars() {
         match c {
@@ -280,4 +393,47 @@ summary: 描述
         assert!(yaml.contains("type: Question"));
         assert!(yaml.contains("tags: [tag1, tag2]"));
     }
+
+    #[test]
+    fn test_parse_created_date() {
+        let fm = parse_frontmatter("---\ntitle: A\ncreated: 2023-05-01\n---\n").unwrap();
+        assert_eq!(fm.created.unwrap().to_rfc3339(), "2023-05-01T00:00:00+00:00");
+
+        let fm = parse_frontmatter("---\ndate: 2023-05-01 14:30\n---\n").unwrap();
+        assert_eq!(fm.created.unwrap().to_rfc3339(), "2023-05-01T14:30:00+00:00");
+
+        let fm = parse_frontmatter("---\ncreated: \"2023-05-01T08:00:00+08:00\"\n---\n").unwrap();
+        assert_eq!(fm.created.unwrap().to_rfc3339(), "2023-05-01T00:00:00+00:00");
+
+        let fm = parse_frontmatter("---\ncreated: someday\n---\n").unwrap();
+        assert!(fm.created.is_none());
+
+        // to_yaml 输出可被重新解析
+        let node = Node::new("urn:x", NodeType::Concept, "x");
+        let yaml = format!("---\n{}---\n", Frontmatter::from_node(&node).to_yaml());
+        assert!(parse_frontmatter(&yaml).unwrap().created.is_some());
+    }
+
+    #[test]
+    fn test_parse_noindex() {
+        assert!(parse_frontmatter("---\nmemexia: noindex\n---\n").unwrap().noindex);
+        assert!(parse_frontmatter("---\nindex: false\n---\n").unwrap().noindex);
+        assert!(!parse_frontmatter("---\nindex: true\n---\n").unwrap().noindex);
+        assert!(!parse_frontmatter("---\ntitle: A\n---\n").unwrap().noindex);
+    }
+
+    #[test]
+    fn test_try_parse_frontmatter_errors() {
+        assert!(matches!(try_parse_frontmatter("# No frontmatter\n"), Ok(None)));
+        assert!(matches!(try_parse_frontmatter("---\n---\n"), Ok(None)));
+
+        let err = try_parse_frontmatter("---\ntitle: A\ntags: [a, b\nsummary: x\n---\n").unwrap_err();
+        assert_eq!(err.line, 4);
+
+        let err = try_parse_frontmatter("\n---\njust text\n---\n").unwrap_err();
+        assert_eq!(err.line, 3);
+        assert!(err.message.contains("mapping"));
+
+        assert_eq!(parse_frontmatter("---\ntags: [a, b\n---\n").map(|fm| fm.title), None);
+    }
 }
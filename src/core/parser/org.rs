@@ -0,0 +1,269 @@
+//! Org-mode 解析模块
+//!
+//! 将 Emacs Org 文件解析为与 Markdown 相同的 [`ParsedDoc`]，下游的节点、边、搜索和历史无需区分格式
+//!
+//! ## 支持的语法
+//!
+//! ```org
+//! #+TITLE: 标题
+//! #+FILETAGS: :tag1:tag2:
+//!
+//! * 一级标题                     :tag3:
+//! 正文，链接到 [[file:other.org][另一篇]] 或 [[id:6f1c2d3e-...]]
+//! ```
+//!
+//! - `[[file:路径]]` 链接的目标按写法保留（与 Markdown 的 `[[目标]]` 一致）
+//! - `[[id:...]]` 链接的目标为 `urn:memexia:org:<ID>`
+//! - 两类链接的关系均为 RelatedTo（经 [`super::parse_file_with_default`] 解析时为配置的默认关系）；
+//!   其他链接类型和不认识的语法直接忽略
+
+use super::{count_words, ParsedDoc, WikiLink};
+use crate::storage::RelationType;
+use regex::Regex;
+
+/// Org ID 链接目标的 URN 前缀
+pub const ORG_ID_PREFIX: &str = "urn:memexia:org:";
+
+/// Org 标题行
+#[derive(Debug, Clone, PartialEq)]
+pub struct Headline {
+    /// 层级（星号个数）
+    pub level: usize,
+    /// 标题文本（不含 TODO 关键字和标签）
+    pub title: String,
+    /// 标题行上的标签
+    pub tags: Vec<String>,
+}
+
+/// 解析 Org 内容
+///
+/// # Arguments
+///
+/// * `content` - Org 文件内容
+/// * `file_name` - 文件名（用于生成节点 ID）
+///
+/// # Returns
+///
+/// 解析后的 ParsedDoc（frontmatter 始终为 None）
+pub fn parse_org(content: &str, file_name: &str) -> ParsedDoc {
+    let mut doc = ParsedDoc::new(file_name);
+
+    // 1. 文件级关键字
+    doc.title = keyword(content, "TITLE").filter(|t| !t.is_empty());
+    if let Some(filetags) = keyword(content, "FILETAGS") {
+        push_tags(&mut doc.tags, split_tags(&filetags));
+    }
+
+    // 2. 标题行：标签并入文档标签，无 #+TITLE 时使用第一个一级标题
+    let headlines = parse_headlines(content);
+    for headline in &headlines {
+        push_tags(&mut doc.tags, headline.tags.iter().cloned());
+    }
+    if doc.title.is_none() {
+        doc.title = headlines.iter().find(|h| h.level == 1).map(|h| h.title.clone());
+    }
+
+    // 3. 链接
+    doc.wiki_links = parse_org_links(content);
+
+    // 4. 纯内容和字数
+    doc.content = plain_text(content);
+    doc.word_count = count_words(&doc.content);
+
+    doc
+}
+
+/// 解析所有标题行
+pub fn parse_headlines(content: &str) -> Vec<Headline> {
+    let re = Regex::new(r"(?m)^(\*+)\s+(.*?)\s*$").unwrap();
+    let tags_re = Regex::new(r"\s+(:[^\s:]+(?::[^\s:]+)*:)$").unwrap();
+
+    re.captures_iter(content)
+        .map(|cap| {
+            let mut text = cap[2].to_string();
+            let mut tags = Vec::new();
+            if let Some(m) = tags_re.captures(&text).and_then(|c| c.get(1)) {
+                tags = split_tags(m.as_str()).collect();
+                text.truncate(text.len() - m.as_str().len());
+            }
+            Headline {
+                level: cap[1].len(),
+                title: strip_todo(text.trim()).to_string(),
+                tags,
+            }
+        })
+        .collect()
+}
+
+/// 解析 `file:` 和 `id:` 链接
+pub fn parse_org_links(content: &str) -> Vec<WikiLink> {
+    link_regex()
+        .captures_iter(content)
+        .filter_map(|cap| link_target(&cap[1]))
+        .map(|target| WikiLink::new(target, RelationType::RelatedTo, 1.0, String::new()))
+        .collect()
+}
+
+/// 读取 `#+KEY: value` 关键字（大小写不敏感，取第一个）
+fn keyword(content: &str, key: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let rest = line.trim_start().strip_prefix("#+")?;
+        let (name, value) = rest.split_once(':')?;
+        name.eq_ignore_ascii_case(key).then(|| value.trim().to_string())
+    })
+}
+
+/// 拆分 `:a:b:` 或 `a b` 形式的标签
+fn split_tags(tags: &str) -> impl Iterator<Item = String> + '_ {
+    tags.split(|c: char| c == ':' || c.is_whitespace())
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+}
+
+fn push_tags(tags: &mut Vec<String>, new: impl Iterator<Item = String>) {
+    for tag in new {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+}
+
+/// 去掉标题开头的 TODO 关键字
+fn strip_todo(title: &str) -> &str {
+    for keyword in ["TODO ", "DONE "] {
+        if let Some(rest) = title.strip_prefix(keyword) {
+            return rest.trim_start();
+        }
+    }
+    title
+}
+
+fn link_regex() -> Regex {
+    Regex::new(r"\[\[([^\]]+)\](?:\[([^\]]*)\])?\]").unwrap()
+}
+
+/// 链接目标：`file:` 去掉搜索选项，`id:` 转为 URN，其他类型返回 None
+fn link_target(link: &str) -> Option<String> {
+    if let Some(path) = link.strip_prefix("file:") {
+        let path = path.split("::").next().unwrap_or(path);
+        let path = path.strip_prefix("./").unwrap_or(path);
+        return (!path.is_empty()).then(|| path.to_string());
+    }
+    if let Some(id) = link.strip_prefix("id:") {
+        let id = id.trim();
+        return (!id.is_empty()).then(|| format!("{}{}", ORG_ID_PREFIX, id));
+    }
+    None
+}
+
+/// 纯文本内容：去掉关键字行和属性抽屉，链接替换为描述文本，标题行去掉星号和标签
+fn plain_text(content: &str) -> String {
+    let links = link_regex();
+    let mut out = String::new();
+    let mut in_drawer = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if in_drawer {
+            in_drawer = !trimmed.eq_ignore_ascii_case(":END:");
+            continue;
+        }
+        if trimmed.eq_ignore_ascii_case(":PROPERTIES:") {
+            in_drawer = true;
+            continue;
+        }
+        if trimmed.starts_with("#+") {
+            continue;
+        }
+
+        let headline = line.starts_with('*').then(|| parse_headlines(line)).and_then(|h| h.into_iter().next());
+        let line = match headline {
+            Some(headline) => headline.title,
+            None => line.to_string(),
+        };
+        let line = links.replace_all(&line, |cap: &regex::Captures| {
+            cap.get(2).map_or("", |m| m.as_str()).to_string()
+        });
+        out.push_str(&line);
+        out.push('\n');
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = "\
+#+TITLE: Free Will
+#+FILETAGS: :philosophy:ethics:
+#+STARTUP: overview
+
+* Overview                                          :draft:
+:PROPERTIES:
+:ID:       6f1c2d3e-aaaa-bbbb-cccc-000000000001
+:END:
+Compatibilism is discussed in [[file:./compatibilism.org::*History][this note]].
+
+** TODO Read more                                 :reading:ethics:
+See also [[id:1a2b3c4d-0000-1111-2222-333333333333]] and [[https://example.com][the web]].
+
+* Open questions
+#+BEGIN_QUOTE
+Some unknown #+construct with [[nonsense]]
+#+END_QUOTE
+";
+
+    #[test]
+    fn test_parse_org_fixture() {
+        let doc = parse_org(FIXTURE, "philosophy/free-will.org");
+
+        assert_eq!(doc.get_title(), "Free Will");
+        assert!(doc.frontmatter.is_none());
+        assert_eq!(doc.tags, vec!["philosophy", "ethics", "draft", "reading"]);
+
+        let targets: Vec<_> = doc.wiki_links.iter().map(|l| l.target.as_str()).collect();
+        assert_eq!(
+            targets,
+            vec!["compatibilism.org", "urn:memexia:org:1a2b3c4d-0000-1111-2222-333333333333"]
+        );
+        assert!(doc.wiki_links.iter().all(|l| l.relation == RelationType::RelatedTo));
+
+        // 链接替换为描述，关键字和属性抽屉不计入正文
+        assert!(doc.content.contains("discussed in this note."));
+        assert!(doc.content.contains("See also  and the web."));
+        assert!(!doc.content.contains("#+TITLE"));
+        assert!(!doc.content.contains(":ID:"));
+        assert!(doc.word_count > 0);
+
+        let node = doc.to_node();
+        assert_eq!(node.id, "urn:memexia:file:philosophy/free-will.org");
+        assert_eq!(node.title, "Free Will");
+    }
+
+    #[test]
+    fn test_parse_headlines() {
+        let headlines = parse_headlines(FIXTURE);
+        let top: Vec<_> = headlines.iter().filter(|h| h.level == 1).map(|h| h.title.as_str()).collect();
+        assert_eq!(top, vec!["Overview", "Open questions"]);
+        assert_eq!(
+            headlines[1],
+            Headline {
+                level: 2,
+                title: "Read more".to_string(),
+                tags: vec!["reading".to_string(), "ethics".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_title_falls_back_to_headline() {
+        let doc = parse_org("Intro text\n* First Heading\n* Second\n", "notes.org");
+        assert_eq!(doc.get_title(), "First Heading");
+
+        let doc = parse_org("just text, [[unclosed\n", "plain.org");
+        assert_eq!(doc.get_title(), "plain");
+        assert!(doc.wiki_links.is_empty());
+    }
+}
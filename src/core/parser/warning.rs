@@ -0,0 +1,68 @@
+//! 解析警告模块
+//!
+//! 解析器遇到可以容忍但很可能是笔误的写法时（未闭合的链接、无效的 frontmatter 等）
+//! 不会失败，而是在 [`ParsedDoc`](super::ParsedDoc) 之外返回警告，
+//! 由索引器记录、由 `memexia lint` 展示
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// 解析警告类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParseWarningKind {
+    /// 没有对应 `]]` 的 `[[`
+    UnclosedLink,
+    /// frontmatter 存在但不是合法的 YAML 映射
+    InvalidFrontmatter,
+    /// 链接强度不是数字或超出 0–1
+    InvalidStrength,
+    /// 既不是内置关系也不是合法自定义关系的名称
+    UnknownRelation,
+}
+
+impl ParseWarningKind {
+    /// 类别名（snake_case，同时用作 lint 规则名）
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ParseWarningKind::UnclosedLink => "unclosed_link",
+            ParseWarningKind::InvalidFrontmatter => "invalid_frontmatter",
+            ParseWarningKind::InvalidStrength => "invalid_strength",
+            ParseWarningKind::UnknownRelation => "unknown_relation",
+        }
+    }
+}
+
+impl fmt::Display for ParseWarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 解析警告
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParseWarning {
+    /// 行号（从 1 开始，相对整个文件）
+    pub line: usize,
+    /// 类别
+    pub kind: ParseWarningKind,
+    /// 说明
+    pub message: String,
+}
+
+impl ParseWarning {
+    /// 创建警告
+    pub fn new(line: usize, kind: ParseWarningKind, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
@@ -5,14 +5,15 @@
 //! ## 链接格式
 //!
 //! ```markdown
-//! [[目标]]                                    # 默认 RelatedTo
-//! [[目标|关系]]                               # 指定关系类型
+//! [[目标]]                                    # 默认关系（RelatedTo，可由 `links.default_relation` 配置）
+//! [[目标|关系]]                               # 指定关系类型（内置或自定义，如 Cites-Methodology）
 //! [[目标|关系:0.85]]                          # 指定关系 + 强度
 //! [[目标|关系:0.85:描述]]                     # 完整格式
 //! ```
 
 use regex::Regex;
-use crate::storage::{Edge, RelationType};
+use super::warning::{ParseWarning, ParseWarningKind};
+use crate::storage::{ids, Edge, RelationType};
 
 /// Wiki 链接结构
 ///
@@ -53,10 +54,10 @@ impl WikiLink {
 
     /// 转换为 Edge
     pub fn to_edge(&self, from: &str) -> Edge {
-        let encoded_target = encode_iri_component(&self.target);
-        let target_urn = format!("urn:memexia:file:{}", encoded_target);
+        let encoded_target = ids::encode_path(&self.target);
+        let target_urn = ids::file_id(&self.target);
         let edge_id = format!("urn:memexia:edge:{}-{}", from, encoded_target);
-        let mut edge = Edge::new(&edge_id, from, &target_urn, self.relation);
+        let mut edge = Edge::new(&edge_id, from, &target_urn, self.relation.clone());
         if self.strength != 1.0 {
             edge.update_strength(self.strength);
         }
@@ -65,28 +66,23 @@ impl WikiLink {
         }
         edge
     }
-}
 
-/// 对 IRI 路径组件进行 percent 编码
-fn encode_iri_component(s: &str) -> String {
-    let mut result = String::new();
-    for c in s.chars() {
-        // 只对 ASCII 字母数字和安全的符号不编码
-        if c.is_ascii_alphanumeric() || "-_.~!$&'()*+,;=:@/".contains(c) {
-            result.push(c);
-        } else {
-            let mut buf = [0u8; 4];
-            let encoded = c.encode_utf8(&mut buf);
-            for byte in encoded.as_bytes() {
-                result.push('%');
-                result.push_str(&format!("{:02X}", byte));
-            }
+    /// 转换为指向指定目标节点的 Edge
+    ///
+    /// 用于目标 ID 已被解析（如 Zettel ID）的情况；强度等字段在构建时校验
+    pub fn to_edge_with_target(&self, from: &str, target_urn: &str) -> crate::error::Result<Edge> {
+        let target_key = target_urn.strip_prefix("urn:memexia:file:").unwrap_or(target_urn);
+        let mut builder = Edge::builder(from, target_urn, self.relation.clone())
+            .id(format!("urn:memexia:edge:{}-{}", from, target_key))
+            .strength(self.strength);
+        if !self.description.is_empty() {
+            builder = builder.description(&self.description);
         }
+        builder.build()
     }
-    result
 }
 
-/// 解析 wiki 链接
+/// 解析 wiki 链接，未写关系的链接使用 RelatedTo
 ///
 /// # Arguments
 ///
@@ -96,6 +92,16 @@ fn encode_iri_component(s: &str) -> String {
 ///
 /// 解析出的 WikiLink 列表
 pub fn parse_wiki_links(content: &str) -> Vec<WikiLink> {
+    parse_wiki_links_with_default(content, &RelationType::RelatedTo)
+}
+
+/// 解析 wiki 链接，未写关系或关系名无法识别的链接使用指定的默认关系
+///
+/// # Arguments
+///
+/// * `content` - Markdown 内容
+/// * `default_relation` - 默认关系（配置 `links.default_relation`）
+pub fn parse_wiki_links_with_default(content: &str, default_relation: &RelationType) -> Vec<WikiLink> {
     let mut links = Vec::new();
 
     // 正则表达式匹配 [[目标|关系:strength:描述]] 或 [[目标|关系]]
@@ -104,7 +110,7 @@ pub fn parse_wiki_links(content: &str) -> Vec<WikiLink> {
 
     for cap in re.captures_iter(content) {
         if let Some(link_str) = cap.get(1) {
-            if let Some(link) = parse_link_str(link_str.as_str()) {
+            if let Some(link) = parse_link_str(link_str.as_str(), default_relation) {
                 links.push(link);
             }
         }
@@ -118,11 +124,12 @@ pub fn parse_wiki_links(content: &str) -> Vec<WikiLink> {
 /// # Arguments
 ///
 /// * `link_str` - 链接字符串，如 "目标" 或 "目标|关系:0.8:描述"
+/// * `default_relation` - 未写关系或关系名无法识别时使用的关系
 ///
 /// # Returns
 ///
 /// 解析后的 WikiLink，解析失败返回 None
-fn parse_link_str(link_str: &str) -> Option<WikiLink> {
+fn parse_link_str(link_str: &str, default_relation: &RelationType) -> Option<WikiLink> {
     let parts: Vec<&str> = link_str.split('|').collect();
 
     let target = parts[0].trim().to_string();
@@ -131,7 +138,7 @@ fn parse_link_str(link_str: &str) -> Option<WikiLink> {
     }
 
     // 默认值
-    let mut relation = RelationType::RelatedTo;
+    let mut relation = default_relation.clone();
     let mut strength = 1.0;
     let mut description = String::new();
 
@@ -143,7 +150,7 @@ fn parse_link_str(link_str: &str) -> Option<WikiLink> {
         if let Some(colon_pos) = after_pipe.find(':') {
             // 格式: 关系:strength:描述
             let rel_str = &after_pipe[..colon_pos];
-            relation = parse_relation(rel_str);
+            relation = parse_relation(rel_str, default_relation);
 
             let after_colon = &after_pipe[colon_pos + 1..];
 
@@ -162,7 +169,7 @@ fn parse_link_str(link_str: &str) -> Option<WikiLink> {
             }
         } else {
             // 格式: 关系
-            relation = parse_relation(after_pipe);
+            relation = parse_relation(after_pipe, default_relation);
         }
     }
 
@@ -170,24 +177,112 @@ fn parse_link_str(link_str: &str) -> Option<WikiLink> {
 }
 
 /// 解析关系类型字符串
-fn parse_relation(s: &str) -> RelationType {
-    match s.to_lowercase().replace('_', "").as_str() {
-        "contains" => RelationType::Contains,
-        "partof" => RelationType::PartOf,
-        "instanceof" => RelationType::InstanceOf,
-        "derivesfrom" => RelationType::DerivesFrom,
-        "leadsto" => RelationType::LeadsTo,
-        "supports" => RelationType::Supports,
-        "contradicts" => RelationType::Contradicts,
-        "refines" => RelationType::Refines,
-        "references" => RelationType::References,
-        "relatedto" => RelationType::RelatedTo,
-        "analogousto" => RelationType::AnalogousTo,
-        "precedes" => RelationType::Precedes,
-        "follows" => RelationType::Follows,
-        "simultaneous" => RelationType::Simultaneous,
-        _ => RelationType::RelatedTo, // 默认
+///
+/// 不是内置关系的合法名称作为自定义关系保留，其余无法识别的名称当作默认关系
+fn parse_relation(s: &str, default_relation: &RelationType) -> RelationType {
+    RelationType::parse(s).unwrap_or_else(|| default_relation.clone())
+}
+
+/// 检查链接写法，返回解析时被容忍的问题
+///
+/// 检测未闭合的 `[[`、无法识别的关系名和无效的强度；
+/// 逐行检查（跨行的链接视为未闭合），代码块中的内容不参与检查
+///
+/// # Arguments
+///
+/// * `content` - Markdown 正文
+///
+/// # Returns
+///
+/// 警告列表，行号相对传入的内容
+pub fn link_warnings(content: &str) -> Vec<ParseWarning> {
+    link_warnings_with_default(content, &RelationType::RelatedTo)
+}
+
+/// 检查链接写法，无法识别的关系名在警告中说明会被当作指定的默认关系
+pub fn link_warnings_with_default(content: &str, default_relation: &RelationType) -> Vec<ParseWarning> {
+    let link_re = Regex::new(r"\[\[([^]\|]+(?:\|[^]\|]+)?)\]\]").unwrap();
+    let closed_re = Regex::new(r"\[\[[^\]]*\]\]").unwrap();
+    let mut warnings = Vec::new();
+    let mut in_fence = false;
+
+    for (i, text) in content.lines().enumerate() {
+        if text.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let line = i + 1;
+
+        if closed_re.replace_all(text, "").contains("[[") {
+            warnings.push(ParseWarning::new(
+                line,
+                ParseWarningKind::UnclosedLink,
+                "unclosed \"[[\" (missing \"]]\"); the text is not parsed as a link",
+            ));
+        }
+
+        for cap in link_re.captures_iter(text) {
+            for (kind, message) in link_issues(&cap[1], default_relation) {
+                warnings.push(ParseWarning::new(line, kind, message));
+            }
+        }
     }
+
+    warnings
+}
+
+/// 单个链接字符串中的问题，与 [`parse_link_str`] 的容错规则对应
+fn link_issues(link_str: &str, default_relation: &RelationType) -> Vec<(ParseWarningKind, String)> {
+    let mut issues = Vec::new();
+    let parts: Vec<&str> = link_str.split('|').collect();
+    let target = parts[0].trim();
+    if target.is_empty() || parts.len() < 2 || parts[1].is_empty() {
+        return issues;
+    }
+
+    let after_pipe = parts[1];
+    let (rel_str, after_colon) = match after_pipe.find(':') {
+        Some(colon_pos) => (&after_pipe[..colon_pos], Some(&after_pipe[colon_pos + 1..])),
+        None => (after_pipe, None),
+    };
+
+    let relation = rel_str.trim();
+    if !relation.is_empty() && RelationType::parse(rel_str).is_none() {
+        issues.push((
+            ParseWarningKind::UnknownRelation,
+            format!(
+                "unknown relation \"{}\" in link to {}; it is treated as {}",
+                relation, target, default_relation
+            ),
+        ));
+    }
+
+    if let Some(after_colon) = after_colon {
+        let strength = after_colon.split(':').next().unwrap_or_default();
+        if !strength.is_empty() {
+            match strength.parse::<f64>() {
+                Ok(s) if (0.0..=1.0).contains(&s) => {}
+                Ok(s) => issues.push((
+                    ParseWarningKind::InvalidStrength,
+                    format!(
+                        "strength {} in link to {} is outside 0–1; it is clamped to {}",
+                        strength,
+                        target,
+                        s.clamp(0.0, 1.0)
+                    ),
+                )),
+                Err(_) => issues.push((
+                    ParseWarningKind::InvalidStrength,
+                    format!("strength \"{}\" in link to {} is not a number; 1.0 is used", strength, target),
+                )),
+            }
+        }
+    }
+
+    issues
 }
 
 /// 从内容中移除所有 wiki 链接标记
@@ -223,7 +318,7 @@ mod tests {
 
     #[test]
     fn test_parse_link_basic() {
-        let link = parse_link_str("目标").unwrap();
+        let link = parse_link_str("目标", &RelationType::RelatedTo).unwrap();
         assert_eq!(link.target, "目标");
         assert_eq!(link.relation, RelationType::RelatedTo);
         assert_eq!(link.strength, 1.0);
@@ -232,14 +327,14 @@ mod tests {
 
     #[test]
     fn test_parse_link_with_relation() {
-        let link = parse_link_str("目标|Contradicts").unwrap();
+        let link = parse_link_str("目标|Contradicts", &RelationType::RelatedTo).unwrap();
         assert_eq!(link.target, "目标");
         assert_eq!(link.relation, RelationType::Contradicts);
     }
 
     #[test]
     fn test_parse_link_with_strength() {
-        let link = parse_link_str("目标|Supports:0.85").unwrap();
+        let link = parse_link_str("目标|Supports:0.85", &RelationType::RelatedTo).unwrap();
         assert_eq!(link.target, "目标");
         assert_eq!(link.relation, RelationType::Supports);
         assert_eq!(link.strength, 0.85);
@@ -247,7 +342,7 @@ mod tests {
 
     #[test]
     fn test_parse_link_full() {
-        let link = parse_link_str("目标|Refines:0.9:更精确的描述").unwrap();
+        let link = parse_link_str("目标|Refines:0.9:更精确的描述", &RelationType::RelatedTo).unwrap();
         assert_eq!(link.target, "目标");
         assert_eq!(link.relation, RelationType::Refines);
         assert_eq!(link.strength, 0.9);
@@ -256,7 +351,7 @@ mod tests {
 
     #[test]
     fn test_parse_link_with_underscore() {
-        let link = parse_link_str("目标|Part_Of").unwrap();
+        let link = parse_link_str("目标|Part_Of", &RelationType::RelatedTo).unwrap();
         assert_eq!(link.relation, RelationType::PartOf);
     }
 
@@ -305,23 +400,72 @@ mod tests {
 
     #[test]
     fn test_parse_relation_case_insensitive() {
-        assert_eq!(parse_relation("contradicts"), RelationType::Contradicts);
-        assert_eq!(parse_relation("CONTRADICTS"), RelationType::Contradicts);
-        assert_eq!(parse_relation("Contradicts"), RelationType::Contradicts);
+        assert_eq!(parse_relation("contradicts", &RelationType::RelatedTo), RelationType::Contradicts);
+        assert_eq!(parse_relation("CONTRADICTS", &RelationType::RelatedTo), RelationType::Contradicts);
+        assert_eq!(parse_relation("Contradicts", &RelationType::RelatedTo), RelationType::Contradicts);
     }
 
     #[test]
     fn test_parse_relation_unknown() {
-        let link = parse_link_str("目标|UnknownRelation").unwrap();
+        let link = parse_link_str("目标|未知关系", &RelationType::RelatedTo).unwrap();
         assert_eq!(link.relation, RelationType::RelatedTo); // 默认值
     }
 
+    #[test]
+    fn test_configured_default_relation() {
+        let links = parse_wiki_links_with_default(
+            "[[a]] [[b|未知关系:0.5]] [[c|RelatedTo]] [[d|Supports]]",
+            &RelationType::References,
+        );
+        let relations: Vec<_> = links.iter().map(|l| l.relation.clone()).collect();
+        assert_eq!(
+            relations,
+            [RelationType::References, RelationType::References, RelationType::RelatedTo, RelationType::Supports]
+        );
+        let warnings = link_warnings_with_default("[[b|未知关系]]", &RelationType::References);
+        assert!(warnings[0].message.ends_with("it is treated as References"));
+    }
+
+    #[test]
+    fn test_parse_relation_custom() {
+        let link = parse_link_str("目标|Refutes-Experimentally:0.7", &RelationType::RelatedTo).unwrap();
+        assert_eq!(link.relation, RelationType::Custom("Refutes-Experimentally".to_string()));
+        assert_eq!(link.strength, 0.7);
+    }
+
     #[test]
     fn test_strength_clamping() {
-        let link1 = parse_link_str("目标|Supports:1.5").unwrap();
+        let link1 = parse_link_str("目标|Supports:1.5", &RelationType::RelatedTo).unwrap();
         assert_eq!(link1.strength, 1.0); // 超过1.0 被钳制
 
-        let link2 = parse_link_str("目标|Supports:-0.5").unwrap();
+        let link2 = parse_link_str("目标|Supports:-0.5", &RelationType::RelatedTo).unwrap();
         assert_eq!(link2.strength, 0.0); // 低于0.0 被钳制
     }
+
+    fn warning_kinds(content: &str) -> Vec<(usize, ParseWarningKind)> {
+        link_warnings(content).into_iter().map(|w| (w.line, w.kind)).collect()
+    }
+
+    #[test]
+    fn test_link_warnings_unclosed() {
+        assert_eq!(warning_kinds("ok [[a]]\nbroken [[b and\n[[c]] [[d|Supports]]"), [(2, ParseWarningKind::UnclosedLink)]);
+        assert!(warning_kinds("```\n[[ -f x\n```\n").is_empty());
+    }
+
+    #[test]
+    fn test_link_warnings_relation_and_strength() {
+        let warnings = link_warnings("[[a|Supports:0.5]]\n\n[[b|leads to]] [[c|Cites-Methodology]]\n[[d|Supports:1.5]] [[e|Supports:high:why]]");
+        let kinds: Vec<_> = warnings.iter().map(|w| (w.line, w.kind)).collect();
+        assert_eq!(
+            kinds,
+            [
+                (3, ParseWarningKind::UnknownRelation),
+                (4, ParseWarningKind::InvalidStrength),
+                (4, ParseWarningKind::InvalidStrength),
+            ]
+        );
+        assert_eq!(warnings[0].message, "unknown relation \"leads to\" in link to b; it is treated as RelatedTo");
+        assert!(warnings[1].message.contains("clamped to 1"));
+        assert!(warnings[2].message.contains("\"high\""));
+    }
 }
@@ -0,0 +1,319 @@
+//! 路径规范化模块
+//!
+//! 路径在系统中有多种形式：规范化的绝对路径、带 `\\?\` 前缀的 Windows verbatim 路径、
+//! 反斜杠或正斜杠分隔的相对路径。本模块统一把它们转换为 [`RepoRelativePath`]：
+//!
+//! - 分隔符一律为 `/`
+//! - 保留大小写（Windows 下与仓库根目录比较时忽略大小写）
+//! - 去除 `\\?\` 前缀，按词法消解 `.` 和 `..`
+//! - 相对路径相对仓库根目录解析
+//!
+//! 节点 ID、暂存区和 CLI 的文件/链接命令都经由 [`normalize_input`]，
+//! 因此无论用户如何输入路径，同一文件的节点 ID 都逐字节相同
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 相对仓库根目录的规范化路径
+///
+/// 位于仓库之外的路径保留为规范化的绝对路径，见 [`is_external`](Self::is_external)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RepoRelativePath(String);
+
+impl RepoRelativePath {
+    /// 路径字符串（`/` 分隔）
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// 转换为字符串
+    pub fn into_string(self) -> String {
+        self.0
+    }
+
+    /// 路径是否位于仓库之外
+    pub fn is_external(&self) -> bool {
+        is_absolute_str(&self.0)
+    }
+
+    /// 拼接到仓库根目录得到文件系统路径
+    pub fn to_path(&self, repo_root: &Path) -> PathBuf {
+        if self.is_external() {
+            PathBuf::from(&self.0)
+        } else {
+            repo_root.join(&self.0)
+        }
+    }
+}
+
+impl fmt::Display for RepoRelativePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for RepoRelativePath {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// 规范化用户或文件系统给出的路径
+///
+/// 路径存在时先通过文件系统规范化（解析符号链接），不存在时按词法处理
+///
+/// # Arguments
+///
+/// * `path` - 绝对路径，或相对仓库根目录的路径
+/// * `repo_root` - 仓库根目录
+///
+/// # Returns
+///
+/// 相对仓库根目录的路径
+pub fn normalize_input(path: &Path, repo_root: &Path) -> RepoRelativePath {
+    let canonical_root = fs::canonicalize(repo_root).unwrap_or_else(|_| repo_root.to_path_buf());
+    let resolved = fs::canonicalize(repo_root.join(path)).unwrap_or_else(|_| path.to_path_buf());
+    let input = resolved.to_string_lossy();
+
+    let relative = normalize_str(&input, &canonical_root.to_string_lossy());
+    if relative.is_external() {
+        // 文件已不存在时 input 未经规范化，再与原始根目录比较一次
+        let fallback = normalize_str(&input, &repo_root.to_string_lossy());
+        if !fallback.is_external() {
+            return fallback;
+        }
+    }
+    relative
+}
+
+/// 纯字符串形式的 [`normalize_input`]（不访问文件系统）
+///
+/// # Arguments
+///
+/// * `input` - 绝对路径，或相对 `root` 的路径
+/// * `root` - 仓库根目录
+pub fn normalize_str(input: &str, root: &str) -> RepoRelativePath {
+    let root = clean(root);
+    let input = to_slash(&strip_verbatim_str(input));
+    let absolute = if is_absolute_str(&input) {
+        clean(&input)
+    } else {
+        clean(&format!("{}/{}", root, input))
+    };
+
+    match strip_root(&absolute, &root) {
+        Some(relative) => RepoRelativePath(relative),
+        None => RepoRelativePath(absolute),
+    }
+}
+
+/// 用于输出的路径形式：去除 Windows verbatim 前缀
+pub fn display(path: &Path) -> String {
+    strip_verbatim_str(&path.to_string_lossy()).to_string()
+}
+
+/// 去除 Windows verbatim 前缀（`\\?\C:\x` → `C:\x`，`\\?\UNC\server\share` → `\\server\share`）
+pub fn strip_verbatim(path: &Path) -> PathBuf {
+    let lossy = path.to_string_lossy();
+    let stripped = strip_verbatim_str(&lossy);
+    if stripped.len() == lossy.len() {
+        path.to_path_buf()
+    } else {
+        PathBuf::from(stripped.as_ref())
+    }
+}
+
+/// 字符串形式的 [`strip_verbatim`]
+fn strip_verbatim_str(s: &str) -> std::borrow::Cow<'_, str> {
+    if let Some(unc) = s.strip_prefix("\\\\?\\UNC\\") {
+        std::borrow::Cow::Owned(format!("\\\\{}", unc))
+    } else if let Some(rest) = s.strip_prefix("\\\\?\\") {
+        std::borrow::Cow::Borrowed(rest)
+    } else {
+        std::borrow::Cow::Borrowed(s)
+    }
+}
+
+/// 反斜杠转换为正斜杠
+fn to_slash(s: &str) -> String {
+    s.replace('\\', "/")
+}
+
+/// 是否带 Windows 盘符（`C:`）
+fn has_drive(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':'
+}
+
+/// `/` 分隔的路径是否为绝对路径（`/x`、`//server/share`、`C:/x`）
+fn is_absolute_str(s: &str) -> bool {
+    s.starts_with('/') || (has_drive(s) && s[2..].starts_with('/'))
+}
+
+/// 拆出路径前缀（盘符、UNC 主机和共享名，或根 `/`）和其余部分
+fn split_prefix(s: &str) -> (String, &str) {
+    if has_drive(s) {
+        let rest = &s[2..];
+        if let Some(rest) = rest.strip_prefix('/') {
+            return (format!("{}/", &s[..2]), rest);
+        }
+        return (s[..2].to_string(), rest);
+    }
+    if let Some(unc) = s.strip_prefix("//") {
+        let mut parts = unc.splitn(3, '/');
+        let server = parts.next().unwrap_or_default();
+        let share = parts.next().unwrap_or_default();
+        let rest = parts.next().unwrap_or_default();
+        return (format!("//{}/{}/", server, share), rest);
+    }
+    if let Some(rest) = s.strip_prefix('/') {
+        return ("/".to_string(), rest);
+    }
+    (String::new(), s)
+}
+
+/// 按词法规范化 `/` 分隔的路径：合并重复分隔符，消解 `.` 和 `..`
+fn clean(s: &str) -> String {
+    let s = to_slash(strip_verbatim_str(s).as_ref());
+    let (prefix, rest) = split_prefix(&s);
+    let rooted = prefix.ends_with('/');
+
+    let mut parts: Vec<&str> = Vec::new();
+    for part in rest.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => match parts.last() {
+                Some(&last) if last != ".." => {
+                    parts.pop();
+                }
+                // 绝对路径不能越过根
+                _ if rooted => {}
+                _ => parts.push(".."),
+            },
+            other => parts.push(other),
+        }
+    }
+
+    let joined = parts.join("/");
+    if prefix.is_empty() && joined.is_empty() {
+        ".".to_string()
+    } else {
+        format!("{}{}", prefix, joined)
+    }
+}
+
+/// 去除根目录前缀，路径不在根目录下时返回 None
+///
+/// 带盘符或 UNC 前缀的 Windows 路径比较时忽略大小写，返回值保留输入的大小写
+fn strip_root(path: &str, root: &str) -> Option<String> {
+    let root = root.trim_end_matches('/');
+    let windows = has_drive(root) || root.starts_with("//");
+    let head = path.get(..root.len())?;
+    let matches = if windows { head.eq_ignore_ascii_case(root) } else { head == root };
+    if !matches {
+        return None;
+    }
+
+    let rest = &path[root.len()..];
+    if rest.is_empty() {
+        return Some(".".to_string());
+    }
+    if root.is_empty() || root.ends_with(':') {
+        return Some(rest.trim_start_matches('/').to_string());
+    }
+    rest.strip_prefix('/').map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Indexer;
+    use crate::storage::Storage;
+    use tempfile::TempDir;
+
+    fn rel(input: &str, root: &str) -> String {
+        normalize_str(input, root).into_string()
+    }
+
+    #[test]
+    fn test_windows_backslashes_and_mixed_separators() {
+        assert_eq!(rel("C:\\Notes\\ideas\\a.md", "C:\\Notes"), "ideas/a.md");
+        assert_eq!(rel("C:/Notes\\ideas/a.md", "C:\\Notes\\"), "ideas/a.md");
+        assert_eq!(rel("ideas\\sub/a.md", "C:\\Notes"), "ideas/sub/a.md");
+        assert_eq!(rel("C:\\\\Notes\\\\a.md", "C:\\Notes"), "a.md");
+    }
+
+    #[test]
+    fn test_windows_case_preserved_but_root_matched_case_insensitively() {
+        assert_eq!(rel("c:\\notes\\Ideas\\A.md", "C:\\Notes"), "Ideas/A.md");
+        assert!(normalize_str("D:\\Notes\\a.md", "C:\\Notes").is_external());
+        // Unix 路径区分大小写
+        assert!(normalize_str("/home/U/notes/a.md", "/home/u/notes").is_external());
+    }
+
+    #[test]
+    fn test_verbatim_prefix() {
+        assert_eq!(rel("\\\\?\\C:\\Notes\\ideas\\a.md", "C:\\Notes"), "ideas/a.md");
+        assert_eq!(rel("C:\\Notes\\a.md", "\\\\?\\C:\\Notes"), "a.md");
+        assert_eq!(rel("\\\\?\\UNC\\server\\share\\notes\\a.md", "\\\\server\\share\\notes"), "a.md");
+        assert_eq!(display(Path::new("\\\\?\\C:\\Notes\\a.md")), "C:\\Notes\\a.md");
+        assert_eq!(strip_verbatim(Path::new("\\\\?\\C:\\Notes")), PathBuf::from("C:\\Notes"));
+    }
+
+    #[test]
+    fn test_dot_components() {
+        assert_eq!(rel("ideas\\..\\b.md", "C:\\Notes"), "b.md");
+        assert_eq!(rel("C:\\Notes\\x\\.\\..\\y\\a.md", "C:\\Notes"), "y/a.md");
+        assert_eq!(rel("./ideas/../ideas/a.md", "/home/u/notes"), "ideas/a.md");
+        assert_eq!(rel("/home/u/notes", "/home/u/notes/"), ".");
+
+        // 越出仓库的路径保留为绝对路径
+        let outside = normalize_str("../x.md", "/home/u/notes");
+        assert!(outside.is_external());
+        assert_eq!(outside.as_str(), "/home/u/x.md");
+        assert_eq!(rel("/../../etc/x.md", "/home/u/notes"), "/etc/x.md");
+        // 前缀相同但不是同一目录
+        assert!(normalize_str("/home/u/notes2/a.md", "/home/u/notes").is_external());
+    }
+
+    #[test]
+    fn test_normalize_input_on_filesystem() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(root.join("sub/a.md"), "# A").unwrap();
+
+        for input in [
+            root.join("sub/a.md"),
+            PathBuf::from("sub/a.md"),
+            PathBuf::from("./sub/a.md"),
+            PathBuf::from("sub/../sub/a.md"),
+            PathBuf::from("sub\\a.md"),
+        ] {
+            assert_eq!(normalize_input(&input, root).as_str(), "sub/a.md", "{:?}", input);
+        }
+        // 不存在的文件按词法处理
+        assert_eq!(normalize_input(&root.join("sub/missing.md"), root).as_str(), "sub/missing.md");
+        assert_eq!(normalize_input(Path::new("sub/a.md"), root).to_path(root), root.join("sub/a.md"));
+    }
+
+    #[test]
+    fn test_node_ids_identical_across_input_forms() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(root.join("sub/a b.md"), "# A").unwrap();
+
+        let indexer = Indexer::new(Storage::init(root).unwrap());
+        let expected = "urn:memexia:file:sub/a%20b.md";
+        for input in [
+            root.join("sub/a b.md"),
+            root.join("sub/./a b.md"),
+            PathBuf::from("sub/a b.md"),
+            PathBuf::from("sub\\a b.md"),
+        ] {
+            assert_eq!(indexer.path_to_id(&input), expected, "{:?}", input);
+        }
+    }
+}
@@ -0,0 +1,392 @@
+//! 静态站点发布模块
+//!
+//! 为 `memexia publish <out-dir>` 生成只读的 HTML 站点：
+//!
+//! ```text
+//! <out-dir>/
+//! ├── index.html          # 全部笔记列表
+//! ├── graph.json          # 节点和边，供前端图谱视图使用
+//! ├── notes/<path>.html   # 每篇笔记一页（保持原目录结构）
+//! └── tags/<tag>.html     # 每个标签一页
+//! ```
+//!
+//! Wiki 链接改写为页面间的相对链接，链接目标未发布（占位节点或被排除）时保留为纯文本；
+//! 每页末尾附带由入边生成的反向链接列表。带 `publish.exclude_tags` 中任一标签的笔记不会出现在站点中
+//!
+//! 页面文件名保留原始（可能是中文）路径，链接中的每段路径都做 percent 编码
+
+use crate::core::parser::wiki_link::parse_wiki_links;
+use crate::error::Result;
+use crate::storage::{GraphStorage, Node};
+use pulldown_cmark::{html, Options, Parser};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+
+/// 发布配置（`[publish]`）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PublishConfig {
+    /// 带这些标签的笔记不发布
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_tags: Vec<String>,
+    /// 站点发布后的根 URL（如 `https://notes.example.com`），供 `memexia feed` 链接到笔记页面
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+}
+
+impl PublishConfig {
+    /// 笔记是否被排除
+    pub fn excludes(&self, node: &Node) -> bool {
+        node.tags.iter().any(|tag| self.exclude_tags.contains(tag))
+    }
+
+    /// 站点首页的 URL；未配置 `base_url` 时为 None
+    pub fn site_url(&self) -> Option<String> {
+        self.base_url.as_ref().map(|base| format!("{}/", base.trim_end_matches('/')))
+    }
+
+    /// 笔记页面的 URL；未配置 `base_url` 时为 None
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 相对仓库根目录的笔记路径（以 `/` 分隔）
+    pub fn page_url(&self, path: &str) -> Option<String> {
+        self.site_url().map(|site| format!("{}{}", site, encode_path(&page_path(path))))
+    }
+}
+
+/// 待发布的笔记
+#[derive(Debug, Clone)]
+pub struct SourceNote {
+    /// 相对仓库根目录的文件路径（以 `/` 分隔）
+    pub path: String,
+    /// 对应的图节点
+    pub node: Node,
+    /// 去掉 frontmatter 后的 Markdown 正文
+    pub body: String,
+}
+
+/// 发布结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PublishSummary {
+    /// 生成的笔记页数
+    pub pages: usize,
+    /// 生成的标签页数
+    pub tags: usize,
+    /// 被排除的笔记数
+    pub excluded: usize,
+}
+
+/// 生成静态站点
+///
+/// # Arguments
+///
+/// * `graph` - 图存储（用于反向链接和 `graph.json` 中的边）
+/// * `notes` - 待发布的笔记
+/// * `config` - 发布配置
+/// * `resolve` - 将 wiki 链接目标解析为节点 ID（与索引时的规则一致）
+/// * `out` - 输出目录（不存在时创建，已有文件会被覆盖）
+pub fn publish(
+    graph: &dyn GraphStorage,
+    notes: &[SourceNote],
+    config: &PublishConfig,
+    resolve: &dyn Fn(&str) -> String,
+    out: &Path,
+) -> Result<PublishSummary> {
+    let (published, excluded): (Vec<&SourceNote>, Vec<&SourceNote>) =
+        notes.iter().partition(|note| !config.excludes(&note.node));
+
+    // 节点 ID -> (页面路径, 笔记)
+    let pages: HashMap<&str, (String, &SourceNote)> = published
+        .iter()
+        .map(|note| (note.node.id.as_str(), (page_path(&note.path), *note)))
+        .collect();
+
+    let mut tags: BTreeMap<&str, Vec<&SourceNote>> = BTreeMap::new();
+    for note in &published {
+        for tag in &note.node.tags {
+            tags.entry(tag.as_str()).or_default().push(note);
+        }
+    }
+
+    for note in &published {
+        let (page, _) = &pages[note.node.id.as_str()];
+        let html = render_note(graph, note, page, &pages, resolve)?;
+        write_file(out, page, &html)?;
+    }
+
+    for (tag, notes) in &tags {
+        let page = tag_page_path(tag);
+        let items: Vec<_> = notes
+            .iter()
+            .map(|note| (relative_href(&page, &pages[note.node.id.as_str()].0), note.node.title.as_str()))
+            .collect();
+        let body = format!("<h1>#{}</h1>\n{}", escape_html(tag), link_list(&items));
+        write_file(out, &page, &layout(tag, &page, &body))?;
+    }
+
+    write_file(out, "index.html", &render_index(&published, &pages, &tags))?;
+    write_file(out, "graph.json", &serde_json::to_string_pretty(&graph_json(graph, &pages)?)?)?;
+
+    Ok(PublishSummary {
+        pages: published.len(),
+        tags: tags.len(),
+        excluded: excluded.len(),
+    })
+}
+
+/// 渲染单篇笔记页面
+fn render_note(
+    graph: &dyn GraphStorage,
+    note: &SourceNote,
+    page: &str,
+    pages: &HashMap<&str, (String, &SourceNote)>,
+    resolve: &dyn Fn(&str) -> String,
+) -> Result<String> {
+    let re = Regex::new(r"\[\[[^\]]+\]\]").unwrap();
+    let markdown = re.replace_all(&note.body, |caps: &regex::Captures| {
+        let Some(link) = parse_wiki_links(&caps[0]).into_iter().next() else {
+            return caps[0].to_string();
+        };
+        let text = link.target.strip_suffix(".md").unwrap_or(&link.target);
+        match pages.get(resolve(&link.target).as_str()) {
+            Some((target, _)) => format!("[{}](<{}>)", escape_markdown(text), relative_href(page, target)),
+            None => escape_markdown(text),
+        }
+    });
+
+    let mut body = String::new();
+    html::push_html(&mut body, Parser::new_ext(&markdown, Options::all()));
+
+    let mut out = String::new();
+    if !note.node.tags.is_empty() {
+        let tags: Vec<_> = note
+            .node
+            .tags
+            .iter()
+            .map(|tag| format!("<a href=\"{}\">#{}</a>", relative_href(page, &tag_page_path(tag)), escape_html(tag)))
+            .collect();
+        out.push_str(&format!("<p class=\"tags\">{}</p>\n", tags.join(" ")));
+    }
+    out.push_str(&format!("<article>\n{}</article>\n", body));
+
+    // 反向链接（同一来源只列一次）
+    let mut sources = BTreeSet::new();
+    for edge in graph.get_edges_by_target(&note.node.id)? {
+        if edge.from != note.node.id {
+            sources.insert(edge.from);
+        }
+    }
+    let backlinks: Vec<_> = sources
+        .iter()
+        .filter_map(|id| pages.get(id.as_str()))
+        .map(|(target, source)| (relative_href(page, target), source.node.title.as_str()))
+        .collect();
+    if !backlinks.is_empty() {
+        out.push_str(&format!(
+            "<section class=\"backlinks\">\n<h2>Backlinks</h2>\n{}</section>\n",
+            link_list(&backlinks)
+        ));
+    }
+
+    Ok(layout(&note.node.title, page, &out))
+}
+
+/// 渲染首页
+fn render_index(
+    published: &[&SourceNote],
+    pages: &HashMap<&str, (String, &SourceNote)>,
+    tags: &BTreeMap<&str, Vec<&SourceNote>>,
+) -> String {
+    let mut notes: Vec<_> = published
+        .iter()
+        .map(|note| (pages[note.node.id.as_str()].0.clone(), note.node.title.as_str()))
+        .collect();
+    notes.sort_by(|a, b| a.1.cmp(b.1).then_with(|| a.0.cmp(&b.0)));
+    let notes: Vec<_> = notes.iter().map(|(page, title)| (relative_href("index.html", page), *title)).collect();
+
+    let mut body = format!("<h1>Notes ({})</h1>\n{}", notes.len(), link_list(&notes));
+    if !tags.is_empty() {
+        let tags: Vec<_> = tags
+            .iter()
+            .map(|(tag, notes)| {
+                format!(
+                    "<a href=\"{}\">#{}</a> ({})",
+                    relative_href("index.html", &tag_page_path(tag)),
+                    escape_html(tag),
+                    notes.len()
+                )
+            })
+            .collect();
+        body.push_str(&format!("<h2>Tags</h2>\n<p class=\"tags\">{}</p>\n", tags.join(" ")));
+    }
+    layout("Index", "index.html", &body)
+}
+
+/// 只包含已发布节点的图数据
+fn graph_json(graph: &dyn GraphStorage, pages: &HashMap<&str, (String, &SourceNote)>) -> Result<serde_json::Value> {
+    let mut nodes: Vec<_> = pages
+        .values()
+        .map(|(page, note)| {
+            json!({
+                "id": note.node.id,
+                "title": note.node.title,
+                "type": note.node.node_type.to_string(),
+                "tags": note.node.tags,
+                "url": encode_path(page),
+            })
+        })
+        .collect();
+    nodes.sort_by(|a, b| a["id"].as_str().cmp(&b["id"].as_str()));
+
+    let mut edges: Vec<_> = graph
+        .get_all_edges()?
+        .into_iter()
+        .filter(|edge| pages.contains_key(edge.from.as_str()) && pages.contains_key(edge.to.as_str()))
+        .map(|edge| {
+            json!({
+                "source": edge.from,
+                "target": edge.to,
+                "relation": edge.relation.to_string(),
+                "strength": edge.weight(),
+            })
+        })
+        .collect();
+    edges.sort_by(|a, b| (a["source"].as_str(), a["target"].as_str()).cmp(&(b["source"].as_str(), b["target"].as_str())));
+
+    Ok(json!({ "nodes": nodes, "edges": edges }))
+}
+
+/// 笔记文件路径对应的页面路径：`a/b.md` -> `notes/a/b.html`
+fn page_path(path: &str) -> String {
+    let stem = path.strip_suffix(".md").unwrap_or(path);
+    format!("notes/{}.html", stem)
+}
+
+/// 标签页路径（标签中的路径分隔符等替换为 `-`）
+fn tag_page_path(tag: &str) -> String {
+    let name: String = tag
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect();
+    format!("tags/{}.html", name)
+}
+
+/// 从页面 `from` 指向页面 `to` 的相对链接（各段 percent 编码）
+fn relative_href(from: &str, to: &str) -> String {
+    let from_dirs: Vec<&str> = from.split('/').collect();
+    let from_dirs = &from_dirs[..from_dirs.len() - 1];
+    let to_parts: Vec<&str> = to.split('/').collect();
+
+    let common = from_dirs
+        .iter()
+        .zip(&to_parts[..to_parts.len() - 1])
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut parts = vec![".."; from_dirs.len() - common];
+    parts.extend(&to_parts[common..]);
+    encode_path(&parts.join("/"))
+}
+
+/// 对路径各段做 percent 编码（保留 `/`）
+fn encode_path(path: &str) -> String {
+    let mut result = String::new();
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~/".contains(&byte) {
+            result.push(byte as char);
+        } else {
+            result.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    result
+}
+
+/// 链接列表
+fn link_list(items: &[(String, &str)]) -> String {
+    let mut out = String::from("<ul>\n");
+    for (href, title) in items {
+        out.push_str(&format!("<li><a href=\"{}\">{}</a></li>\n", href, escape_html(title)));
+    }
+    out.push_str("</ul>\n");
+    out
+}
+
+/// 页面骨架
+fn layout(title: &str, page: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n\
+         <nav><a href=\"{}\">Index</a></nav>\n{}</body>\n</html>\n",
+        escape_html(title),
+        relative_href(page, "index.html"),
+        body
+    )
+}
+
+fn write_file(out: &Path, page: &str, content: &str) -> Result<()> {
+    let path = out.join(page);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)?;
+    Ok(())
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// 转义链接文本中的 Markdown 特殊字符
+fn escape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\[]*_`<>".contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_href() {
+        assert_eq!(relative_href("notes/a.html", "notes/b.html"), "b.html");
+        assert_eq!(relative_href("notes/x/a.html", "notes/b.html"), "../b.html");
+        assert_eq!(relative_href("notes/a.html", "tags/rust.html"), "../tags/rust.html");
+        assert_eq!(relative_href("index.html", "notes/x/a.html"), "notes/x/a.html");
+        assert_eq!(relative_href("notes/a.html", "index.html"), "../index.html");
+        assert_eq!(relative_href("notes/a.html", "notes/自由 意志.html"), "%E8%87%AA%E7%94%B1%20%E6%84%8F%E5%BF%97.html");
+    }
+
+    #[test]
+    fn test_page_paths() {
+        assert_eq!(page_path("dir/note.md"), "notes/dir/note.html");
+        let config = PublishConfig { base_url: Some("https://notes.example.com/".to_string()), ..Default::default() };
+        assert_eq!(config.page_url("dir/自由.md").unwrap(), "https://notes.example.com/notes/dir/%E8%87%AA%E7%94%B1.html");
+        assert_eq!(PublishConfig::default().page_url("a.md"), None);
+        assert_eq!(tag_page_path("area/rust"), "tags/area-rust.html");
+        assert_eq!(tag_page_path("哲学"), "tags/哲学.html");
+    }
+
+    #[test]
+    fn test_excludes() {
+        let config = PublishConfig {
+            exclude_tags: vec!["private".to_string()],
+            ..Default::default()
+        };
+        let mut node = Node::new("urn:memexia:file:a.md", crate::storage::NodeType::Concept, "A");
+        assert!(!config.excludes(&node));
+        node.add_tag("private");
+        assert!(config.excludes(&node));
+    }
+}
@@ -0,0 +1,391 @@
+//! 查询模板模块（`memexia graph query --template`）
+//!
+//! 常见问题的 SPARQL 查询保存为 `.memexia/queries/<name>.rq`，`init` 时安装内置模板，
+//! 用户可以添加自己的文件。模板开头的注释是头部：
+//!
+//! ```text
+//! # Notes carrying a tag
+//! # @param tag literal
+//! # @param limit integer 50
+//! PREFIX memexia: <memexia:>
+//! SELECT ?node WHERE { ?node memexia:tag {{tag}} } LIMIT {{limit}}
+//! ```
+//!
+//! - 第一行普通注释是说明
+//! - `@param <名称> <类型> [默认值]` 声明参数，正文中以 `{{名称}}` 引用
+//!
+//! 参数类型决定替换时的转义方式：
+//!
+//! | 类型 | 替换为 |
+//! |------|--------|
+//! | `literal` | 字符串字面量，转义引号、反斜杠和换行 |
+//! | `iri` | `<...>`；不含 `:` 的值视为笔记路径，转换为文件节点 ID |
+//! | `type` | 节点类型 IRI（如 `Question` → `<memexia:Question>`） |
+//! | `integer` | 整数 |
+//!
+//! 仓库中的同名文件覆盖内置模板；仓库缺少模板目录时仍可使用内置模板
+
+use crate::error::{MemexiaError, Result};
+use crate::storage::ids;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// 模板目录（相对仓库根目录）
+pub const QUERIES_DIR: &str = ".memexia/queries";
+
+/// 内置模板：(名称, 内容)
+pub const BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    (
+        "backlinks-of",
+        "\
+# Notes linking to a node, with the relation they use
+# @param node iri
+PREFIX memexia: <memexia:>
+SELECT ?source ?title ?relation WHERE {
+  ?source ?relation {{node}} .
+  ?source memexia:title ?title .
+}
+ORDER BY ?title
+",
+    ),
+    (
+        "notes-tagged",
+        "\
+# Notes carrying a tag
+# @param tag literal
+PREFIX memexia: <memexia:>
+SELECT ?node ?title WHERE {
+  ?node memexia:tag {{tag}} .
+  ?node memexia:title ?title .
+  FILTER NOT EXISTS { ?node memexia:deletedAt ?deleted }
+}
+ORDER BY ?title
+",
+    ),
+    (
+        "edges-between-types",
+        "\
+# Links from notes of one type to notes of another
+# @param from type
+# @param to type
+PREFIX memexia: <memexia:>
+PREFIX rdf: <rdf:>
+SELECT ?source ?relation ?target WHERE {
+  ?source rdf:type {{from}} .
+  ?target rdf:type {{to}} .
+  ?source ?relation ?target .
+}
+",
+    ),
+    (
+        "recently-updated",
+        "\
+# Most recently updated notes
+# @param limit integer 20
+PREFIX memexia: <memexia:>
+SELECT ?node ?title ?updated WHERE {
+  ?node memexia:title ?title .
+  ?node memexia:updatedAt ?updated .
+  FILTER NOT EXISTS { ?node memexia:deletedAt ?deleted }
+}
+ORDER BY DESC(?updated)
+LIMIT {{limit}}
+",
+    ),
+];
+
+/// 参数类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    /// 字符串字面量
+    Literal,
+    /// IRI（节点 ID 或笔记路径）
+    Iri,
+    /// 节点类型
+    Type,
+    /// 整数
+    Integer,
+}
+
+impl std::fmt::Display for ParamKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ParamKind::Literal => "literal",
+            ParamKind::Iri => "iri",
+            ParamKind::Type => "type",
+            ParamKind::Integer => "integer",
+        };
+        f.write_str(name)
+    }
+}
+
+/// 模板参数声明
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateParam {
+    /// 参数名
+    pub name: String,
+    /// 参数类型
+    pub kind: ParamKind,
+    /// 默认值；为 None 时必须提供
+    pub default: Option<String>,
+}
+
+/// 查询模板
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryTemplate {
+    /// 模板名称（文件名去掉 `.rq`）
+    pub name: String,
+    /// 说明
+    pub description: String,
+    /// 参数声明
+    pub params: Vec<TemplateParam>,
+    /// 模板全文
+    pub body: String,
+}
+
+impl QueryTemplate {
+    /// 解析模板，校验参数声明与正文中的占位符一致
+    pub fn parse(name: &str, text: &str) -> Result<Self> {
+        let invalid = |message: String| MemexiaError::Validation(format!("Query template '{}': {}", name, message));
+        let mut description = String::new();
+        let mut params: Vec<TemplateParam> = Vec::new();
+        for line in text.lines() {
+            let Some(comment) = line.trim().strip_prefix('#') else {
+                break;
+            };
+            let comment = comment.trim();
+            let Some(declaration) = comment.strip_prefix("@param") else {
+                if description.is_empty() {
+                    description = comment.to_string();
+                }
+                continue;
+            };
+            let mut parts = declaration.split_whitespace();
+            let (Some(param), Some(kind)) = (parts.next(), parts.next()) else {
+                return Err(invalid(format!("expected '@param <name> <kind> [default]', got '{}'", comment)));
+            };
+            let kind = match kind {
+                "literal" => ParamKind::Literal,
+                "iri" => ParamKind::Iri,
+                "type" => ParamKind::Type,
+                "integer" => ParamKind::Integer,
+                other => return Err(invalid(format!("unknown parameter kind '{}'", other))),
+            };
+            let default = parts.collect::<Vec<_>>().join(" ");
+            params.push(TemplateParam {
+                name: param.to_string(),
+                kind,
+                default: (!default.is_empty()).then_some(default),
+            });
+        }
+
+        for placeholder in placeholders(text) {
+            if !params.iter().any(|param| param.name == placeholder) {
+                return Err(invalid(format!("placeholder {{{{{}}}}} is not declared with @param", placeholder)));
+            }
+        }
+        Ok(Self {
+            name: name.to_string(),
+            description,
+            params,
+            body: text.to_string(),
+        })
+    }
+
+    /// 代入参数，生成可执行的查询
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - 参数名到原始值的映射；未声明的参数和缺少的必需参数都是错误
+    pub fn render(&self, values: &BTreeMap<String, String>) -> Result<String> {
+        if let Some(unknown) = values.keys().find(|key| !self.params.iter().any(|param| &param.name == *key)) {
+            return Err(MemexiaError::Validation(format!(
+                "Query template '{}' has no parameter '{}'",
+                self.name, unknown
+            )));
+        }
+        let mut query = self.body.clone();
+        for param in &self.params {
+            let Some(value) = values.get(&param.name).or(param.default.as_ref()) else {
+                return Err(MemexiaError::Validation(format!(
+                    "Query template '{}' requires --param {}=<{}>",
+                    self.name, param.name, param.kind
+                )));
+            };
+            query = query.replace(&format!("{{{{{}}}}}", param.name), &escape(param, value)?);
+        }
+        Ok(query)
+    }
+}
+
+/// 正文中的 `{{name}}` 占位符（忽略注释行）
+fn placeholders(text: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    for line in text.lines().filter(|line| !line.trim_start().starts_with('#')) {
+        let mut rest = line;
+        while let Some(start) = rest.find("{{") {
+            let Some(end) = rest[start + 2..].find("}}") else {
+                break;
+            };
+            names.push(rest[start + 2..start + 2 + end].trim());
+            rest = &rest[start + 2 + end + 2..];
+        }
+    }
+    names
+}
+
+/// 按参数类型把原始值转成 SPARQL 项
+fn escape(param: &TemplateParam, value: &str) -> Result<String> {
+    let invalid = |reason: &str| {
+        MemexiaError::Validation(format!("Invalid value for parameter '{}' ({}): {}", param.name, reason, value))
+    };
+    match param.kind {
+        ParamKind::Literal => {
+            let mut literal = String::with_capacity(value.len() + 2);
+            literal.push('"');
+            for c in value.chars() {
+                match c {
+                    '"' => literal.push_str("\\\""),
+                    '\\' => literal.push_str("\\\\"),
+                    '\n' => literal.push_str("\\n"),
+                    '\r' => literal.push_str("\\r"),
+                    '\t' => literal.push_str("\\t"),
+                    c => literal.push(c),
+                }
+            }
+            literal.push('"');
+            Ok(literal)
+        }
+        ParamKind::Iri => {
+            let iri = if value.contains(':') { value.to_string() } else { ids::file_id(value) };
+            if iri.is_empty() || iri.chars().any(|c| c.is_whitespace() || c.is_control() || "<>\"{}|^`\\".contains(c)) {
+                return Err(invalid("not a valid IRI"));
+            }
+            Ok(format!("<{}>", iri))
+        }
+        ParamKind::Type => {
+            let name = match value.to_lowercase().as_str() {
+                "concept" => "Concept",
+                "question" => "Question",
+                "evidence" => "Evidence",
+                "resource" => "Resource",
+                "person" => "Person",
+                "event" => "Event",
+                "meta" => "Meta",
+                _ => return Err(invalid("unknown node type")),
+            };
+            Ok(format!("<memexia:{}>", name))
+        }
+        ParamKind::Integer => value
+            .trim()
+            .parse::<i64>()
+            .map(|n| n.to_string())
+            .map_err(|_| invalid("not an integer")),
+    }
+}
+
+/// 把内置模板写入仓库的模板目录（已存在的文件不覆盖）
+pub fn install(root: &Path) -> Result<()> {
+    let dir = root.join(QUERIES_DIR);
+    fs::create_dir_all(&dir)?;
+    for (name, text) in BUILTIN_TEMPLATES {
+        let path = dir.join(format!("{}.rq", name));
+        if !path.exists() {
+            fs::write(path, text)?;
+        }
+    }
+    Ok(())
+}
+
+/// 加载模板：仓库中的文件优先，其次是内置模板
+pub fn load(root: &Path, name: &str) -> Result<QueryTemplate> {
+    let path = root.join(QUERIES_DIR).join(format!("{}.rq", name));
+    if path.is_file() {
+        return QueryTemplate::parse(name, &fs::read_to_string(path)?);
+    }
+    match BUILTIN_TEMPLATES.iter().find(|(builtin, _)| *builtin == name) {
+        Some((_, text)) => QueryTemplate::parse(name, text),
+        None => Err(MemexiaError::Validation(format!(
+            "Unknown query template '{}'; see --list-templates",
+            name
+        ))),
+    }
+}
+
+/// 列出可用模板（按名称排序）
+pub fn list(root: &Path) -> Result<Vec<QueryTemplate>> {
+    let mut names: Vec<String> = BUILTIN_TEMPLATES.iter().map(|(name, _)| name.to_string()).collect();
+    if let Ok(entries) = fs::read_dir(root.join(QUERIES_DIR)) {
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "rq") {
+                if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    names.push(stem.to_string());
+                }
+            }
+        }
+    }
+    names.sort();
+    names.dedup();
+    names.iter().map(|name| load(root, name)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn values(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_builtin_templates_parse() {
+        for (name, text) in BUILTIN_TEMPLATES {
+            let template = QueryTemplate::parse(name, text).unwrap();
+            assert!(!template.description.is_empty(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_render_escapes_parameters() {
+        let template = load(Path::new("/nonexistent"), "notes-tagged").unwrap();
+        let query = template.render(&values(&[("tag", "say \"hi\"\\")])).unwrap();
+        assert!(query.contains("?node memexia:tag \"say \\\"hi\\\"\\\\\" ."), "{}", query);
+        assert!(template.render(&values(&[])).is_err());
+        assert!(template.render(&values(&[("tag", "x"), ("other", "y")])).is_err());
+
+        let template = load(Path::new("/nonexistent"), "backlinks-of").unwrap();
+        let query = template.render(&values(&[("node", "notes/a.md")])).unwrap();
+        assert!(query.contains(&format!("<{}>", ids::file_id("notes/a.md"))));
+        assert!(template.render(&values(&[("node", "urn:x> } DROP ALL {")])).is_err());
+
+        let template = load(Path::new("/nonexistent"), "recently-updated").unwrap();
+        assert!(template.render(&values(&[])).unwrap().contains("LIMIT 20"));
+        assert!(template.render(&values(&[("limit", "5; DROP")])).is_err());
+
+        let template = load(Path::new("/nonexistent"), "edges-between-types").unwrap();
+        let query = template.render(&values(&[("from", "question"), ("to", "Evidence")])).unwrap();
+        assert!(query.contains("rdf:type <memexia:Question>") && query.contains("rdf:type <memexia:Evidence>"));
+    }
+
+    #[test]
+    fn test_list_includes_user_templates() {
+        let temp = TempDir::new().unwrap();
+        install(temp.path()).unwrap();
+        fs::write(
+            temp.path().join(QUERIES_DIR).join("by-title.rq"),
+            "# Notes with a title\n# @param title literal\nSELECT ?n WHERE { ?n <memexia:title> {{title}} }\n",
+        )
+        .unwrap();
+
+        let templates = list(temp.path()).unwrap();
+        let names: Vec<&str> = templates.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["backlinks-of", "by-title", "edges-between-types", "notes-tagged", "recently-updated"]);
+        assert_eq!(templates[1].params[0].kind, ParamKind::Literal);
+
+        let err = QueryTemplate::parse("bad", "# @param a literal\nSELECT {{b}}").unwrap_err();
+        assert!(err.to_string().contains("{{b}}"));
+    }
+}
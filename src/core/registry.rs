@@ -0,0 +1,269 @@
+//! 仓库注册表模块（`memexia repos`）
+//!
+//! 用户全局配置 `<config_dir>/memexia/config.toml` 中的 `[[repos]]` 表记录用过的仓库：
+//!
+//! ```toml
+//! [[repos]]
+//! name = "work"
+//! path = "/home/me/work-notes"
+//! last_used = "2024-03-15T10:30:00Z"
+//! ```
+//!
+//! `init` 和成功打开仓库的命令自动登记（命令行入口通过 [`enable`] 开启，库调用默认不写全局配置），
+//! `--repo <name>` 按名称解析路径。路径已不存在的条目保留并在列表中标记
+
+use crate::error::{MemexiaError, Result};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// 已登记的仓库
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegisteredRepo {
+    /// 名称（`--repo` 使用）
+    pub name: String,
+    /// 仓库根目录
+    pub path: PathBuf,
+    /// 最近一次打开的时间
+    pub last_used: DateTime<Utc>,
+}
+
+impl RegisteredRepo {
+    /// 路径是否仍是 Memexia 仓库
+    pub fn exists(&self) -> bool {
+        self.path.join(".memexia").is_dir()
+    }
+}
+
+/// 仓库注册表（用户全局配置）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Registry {
+    /// 已登记的仓库
+    #[serde(default)]
+    pub repos: Vec<RegisteredRepo>,
+}
+
+impl Registry {
+    /// 读取注册表，文件不存在时返回空表
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)?;
+        Ok(toml::from_str(&content).with_context(|| format!("Failed to parse {:?}", path))?)
+    }
+
+    /// 写入注册表
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = toml::to_string_pretty(self).context("Failed to serialize repository registry")?;
+        fs::write(path, content).with_context(|| format!("Failed to write {:?}", path))?;
+        Ok(())
+    }
+
+    /// 按名称查找
+    pub fn get(&self, name: &str) -> Option<&RegisteredRepo> {
+        self.repos.iter().find(|r| r.name == name)
+    }
+
+    /// 按路径查找
+    pub fn find_path(&self, root: &Path) -> Option<&RegisteredRepo> {
+        self.repos.iter().find(|r| r.path == root)
+    }
+
+    /// 按名称解析仓库路径
+    ///
+    /// # Returns
+    ///
+    /// 仓库根目录；名称未登记或路径已不存在时返回错误
+    pub fn resolve(&self, name: &str) -> Result<PathBuf> {
+        let repo = self
+            .get(name)
+            .ok_or_else(|| MemexiaError::Validation(format!("No registered repository named '{}'", name)))?;
+        if !repo.exists() {
+            return Err(MemexiaError::RepositoryNotFound { path: repo.path.clone() });
+        }
+        Ok(repo.path.clone())
+    }
+
+    /// 记录一次使用：已登记时更新时间，否则以目录名（冲突时追加 `-2`、`-3`）登记
+    pub fn touch(&mut self, root: &Path, now: DateTime<Utc>) -> &RegisteredRepo {
+        if let Some(index) = self.repos.iter().position(|r| r.path == root) {
+            self.repos[index].last_used = now;
+            return &self.repos[index];
+        }
+        let name = self.unique_name(&default_name(root));
+        self.push(name, root, now)
+    }
+
+    /// 显式登记仓库
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - 名称；为 None 时使用目录名
+    /// * `root` - 仓库根目录
+    /// * `now` - 当前时间
+    pub fn add(&mut self, name: Option<&str>, root: &Path, now: DateTime<Utc>) -> Result<&RegisteredRepo> {
+        let name = name.map(str::to_string).unwrap_or_else(|| default_name(root));
+        validate_name(&name)?;
+        if let Some(existing) = self.get(&name) {
+            if existing.path != root {
+                return Err(MemexiaError::Validation(format!(
+                    "Repository name '{}' is already used by {}",
+                    name,
+                    existing.path.display()
+                )));
+            }
+        }
+        // 同一路径只保留一个条目
+        self.repos.retain(|r| r.path != root);
+        Ok(self.push(name, root, now))
+    }
+
+    /// 移除登记（不删除仓库本身）
+    pub fn remove(&mut self, name: &str) -> Result<RegisteredRepo> {
+        let index = self.position(name)?;
+        Ok(self.repos.remove(index))
+    }
+
+    /// 重命名
+    pub fn rename(&mut self, old: &str, new: &str) -> Result<()> {
+        validate_name(new)?;
+        let index = self.position(old)?;
+        if old != new && self.get(new).is_some() {
+            return Err(MemexiaError::Validation(format!("Repository name '{}' is already registered", new)));
+        }
+        self.repos[index].name = new.to_string();
+        Ok(())
+    }
+
+    fn position(&self, name: &str) -> Result<usize> {
+        self.repos
+            .iter()
+            .position(|r| r.name == name)
+            .ok_or_else(|| MemexiaError::Validation(format!("No registered repository named '{}'", name)))
+    }
+
+    fn unique_name(&self, base: &str) -> String {
+        let mut name = base.to_string();
+        let mut n = 2;
+        while self.get(&name).is_some() {
+            name = format!("{}-{}", base, n);
+            n += 1;
+        }
+        name
+    }
+
+    fn push(&mut self, name: String, root: &Path, now: DateTime<Utc>) -> &RegisteredRepo {
+        self.repos.push(RegisteredRepo {
+            name,
+            path: root.to_path_buf(),
+            last_used: now,
+        });
+        self.repos.last().unwrap()
+    }
+}
+
+/// 目录名作为默认名称
+fn default_name(root: &Path) -> String {
+    root.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| "repo".to_string())
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.trim().is_empty() {
+        return Err(MemexiaError::Validation("Repository name cannot be empty".to_string()));
+    }
+    Ok(())
+}
+
+/// 默认的全局配置路径（`<config_dir>/memexia/config.toml`）
+pub fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("memexia").join("config.toml"))
+}
+
+/// 当前启用的注册表路径
+static ACTIVE_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// 启用自动登记，之后打开的仓库写入指定的注册表
+pub fn enable(path: PathBuf) {
+    *ACTIVE_PATH.write().unwrap_or_else(|e| e.into_inner()) = Some(path);
+}
+
+/// 当前启用的注册表路径（未启用时为 None）
+pub fn active_path() -> Option<PathBuf> {
+    ACTIVE_PATH.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// 记录仓库被打开（未启用注册表时不做任何事）
+pub fn record_use(root: &Path) -> Result<()> {
+    let Some(path) = active_path() else {
+        return Ok(());
+    };
+    let mut registry = Registry::load(&path)?;
+    registry.touch(root, Utc::now());
+    registry.save(&path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_touch_registers_with_unique_names() {
+        let mut registry = Registry::default();
+        let now = Utc::now();
+
+        assert_eq!(registry.touch(Path::new("/home/u/notes"), now).name, "notes");
+        assert_eq!(registry.touch(Path::new("/work/notes"), now).name, "notes-2");
+        let later = now + chrono::Duration::hours(1);
+        assert_eq!(registry.touch(Path::new("/home/u/notes"), later).last_used, later);
+        assert_eq!(registry.repos.len(), 2);
+    }
+
+    #[test]
+    fn test_add_remove_rename() {
+        let mut registry = Registry::default();
+        let now = Utc::now();
+
+        registry.add(Some("work"), Path::new("/work"), now).unwrap();
+        assert!(registry.add(Some("work"), Path::new("/elsewhere"), now).is_err());
+        registry.add(Some("job"), Path::new("/work"), now).unwrap();
+        assert_eq!(registry.repos.len(), 1);
+
+        registry.add(None, Path::new("/home/u/personal"), now).unwrap();
+        assert!(registry.rename("personal", "job").is_err());
+        registry.rename("personal", "life").unwrap();
+        assert!(registry.get("life").is_some());
+
+        assert_eq!(registry.remove("job").unwrap().path, Path::new("/work"));
+        assert!(registry.remove("job").is_err());
+    }
+
+    #[test]
+    fn test_resolve_flags_missing_paths() {
+        let temp = TempDir::new().unwrap();
+        let repo = temp.path().join("vault");
+        fs::create_dir_all(repo.join(".memexia")).unwrap();
+        let config = temp.path().join("config/config.toml");
+
+        let mut registry = Registry::default();
+        registry.add(Some("vault"), &repo, Utc::now()).unwrap();
+        registry.add(Some("gone"), &temp.path().join("gone"), Utc::now()).unwrap();
+        registry.save(&config).unwrap();
+
+        let registry = Registry::load(&config).unwrap();
+        assert_eq!(registry.resolve("vault").unwrap(), repo);
+        assert!(!registry.get("gone").unwrap().exists());
+        assert!(matches!(registry.resolve("gone"), Err(MemexiaError::RepositoryNotFound { .. })));
+        assert!(matches!(registry.resolve("missing"), Err(MemexiaError::Validation(_))));
+    }
+}
@@ -0,0 +1,133 @@
+//! 节点重命名模块
+//!
+//! 文件在索引之间被移动或重命名（`git mv`、文件管理器）时，旧路径的节点会变成缺失文件，
+//! 新路径则是一个没有节点的新文件。[`crate::core::Indexer::index_all`] 借助
+//! `memexia:contentHash` 和 Git 的重命名检测把两者配对，再由 [`rename_node`]
+//! 把旧节点的身份迁移到新 ID：创建时间、元数据以及两个方向的边（含注释）都会保留
+
+use crate::error::Result;
+use crate::storage::{Edge, GraphStorage, DELETED_AT_KEY};
+use serde::{Deserialize, Serialize};
+
+/// 一次检测到的节点重命名
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeRename {
+    /// 旧节点 ID
+    pub old_id: String,
+    /// 新节点 ID
+    pub new_id: String,
+}
+
+/// 把节点迁移到新 ID
+///
+/// 新节点已存在（通常刚由新文件索引生成）时保留其内容，只继承旧节点的创建时间和
+/// 新节点缺少的元数据；不存在时整体复制旧节点。软删除标记不继承
+///
+/// # Arguments
+///
+/// * `graph` - 图存储
+/// * `old` - 旧节点 ID
+/// * `new` - 新节点 ID
+///
+/// # Returns
+///
+/// 改写的边数
+pub fn rename_node(graph: &dyn GraphStorage, old: &str, new: &str) -> Result<usize> {
+    // 边和节点的迁移作为一个事务，中途失败不会留下半迁移的节点
+    graph.transaction(|graph| {
+        let mut edges = 0;
+        // 包括墓碑状态的边：旧节点已被软删除时它们随新节点重新可见
+        for edge in graph.list_edges_filtered(true)? {
+            if edge.from != old && edge.to != old {
+                continue;
+            }
+            let mut rewritten = edge.clone();
+            if edge.from == old {
+                rewritten.from = new.to_string();
+            }
+            if edge.to == old {
+                rewritten.to = new.to_string();
+            }
+            rewritten.id = Edge::make_id(&rewritten.from, &rewritten.to);
+            graph.delete_edge(&edge.id)?;
+            graph.add_edge(&rewritten)?;
+            edges += 1;
+        }
+
+        if let Some(mut previous) = graph.get_node(old)? {
+            previous.metadata.remove(DELETED_AT_KEY);
+            let node = match graph.get_node(new)? {
+                Some(mut current) => {
+                    current.created_at = previous.created_at;
+                    for (key, value) in previous.metadata {
+                        current.metadata.entry(key).or_insert(value);
+                    }
+                    current
+                }
+                None => {
+                    previous.id = new.to_string();
+                    previous
+                }
+            };
+            // update_node 先清除已有属性，避免重复的创建时间三元组
+            graph.update_node(&node)?;
+            graph.delete_node(old)?;
+        }
+        Ok(edges)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{Node, NodeType, RelationType, Storage};
+    use chrono::{Duration, Utc};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rename_node_moves_edges_and_keeps_created_at() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let graph = storage.graph();
+
+        let created = Utc::now() - Duration::days(30);
+        let mut old = Node::new("urn:memexia:file:old.md", NodeType::Concept, "Old");
+        old.created_at = created;
+        old.set_deleted_at(Utc::now());
+        graph.add_node(&old).unwrap();
+        graph.add_node(&Node::new("urn:memexia:file:other.md", NodeType::Concept, "Other")).unwrap();
+        graph.add_node(&Node::new("urn:memexia:file:new.md", NodeType::Concept, "New")).unwrap();
+
+        let mut incoming = Edge::new(
+            Edge::make_id("urn:memexia:file:other.md", "urn:memexia:file:old.md"),
+            "urn:memexia:file:other.md",
+            "urn:memexia:file:old.md",
+            RelationType::Supports,
+        );
+        incoming.annotate("keep me", Utc::now());
+        graph.add_edge(&incoming).unwrap();
+        graph
+            .add_edge(&Edge::new(
+                Edge::make_id("urn:memexia:file:old.md", "urn:memexia:file:other.md"),
+                "urn:memexia:file:old.md",
+                "urn:memexia:file:other.md",
+                RelationType::RelatedTo,
+            ))
+            .unwrap();
+
+        assert_eq!(rename_node(graph, "urn:memexia:file:old.md", "urn:memexia:file:new.md").unwrap(), 2);
+
+        assert!(!graph.node_exists("urn:memexia:file:old.md").unwrap());
+        let node = graph.get_node("urn:memexia:file:new.md").unwrap().unwrap();
+        assert_eq!(node.title, "New");
+        assert_eq!(node.created_at.timestamp(), created.timestamp());
+        assert!(!node.is_deleted());
+
+        let backlinks = graph.get_edges_by_target("urn:memexia:file:new.md").unwrap();
+        assert_eq!(backlinks.len(), 1);
+        assert_eq!(backlinks[0].relation, RelationType::Supports);
+        assert_eq!(backlinks[0].latest_annotation().unwrap().text, "keep me");
+        assert_eq!(graph.get_edges_by_source("urn:memexia:file:new.md").unwrap().len(), 1);
+        assert!(graph.get_edges_by_target("urn:memexia:file:old.md").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,236 @@
+//! 笔记 HTML 渲染模块
+//!
+//! 为 `memexia serve` 的 `GET /api/nodes/{id}/html` 把笔记正文渲染为 HTML，
+//! GUI 无需自带 Markdown 渲染器即可得到与 Memexia 解析一致的预览：
+//!
+//! - wiki 链接渲染为 `<a data-node-id="...">`，目标按索引器的规则解析；
+//!   目标没有对应的笔记时渲染为 `<a class="broken" data-target="...">`
+//! - `#tag` 渲染为 `<a data-tag="...">`
+//! - 标题带锚点 `id`，同时返回目录
+//!
+//! `[render] sanitize_html = true`（默认）时，源文件中的原始 HTML 按普通文本转义输出
+
+use crate::core::note::slugify;
+use crate::core::publish::escape_html;
+use pulldown_cmark::{html, CowStr, Event, LinkType, Options, Parser, Tag, TagEnd};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// 渲染配置（`[render]`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderConfig {
+    /// 是否转义源文件中的原始 HTML
+    #[serde(default = "default_sanitize_html")]
+    pub sanitize_html: bool,
+}
+
+impl Default for RenderConfig {
+    fn default() -> Self {
+        Self {
+            sanitize_html: default_sanitize_html(),
+        }
+    }
+}
+
+fn default_sanitize_html() -> bool {
+    true
+}
+
+/// 目录项
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TocEntry {
+    /// 标题级别（1–6）
+    pub level: u8,
+    /// 标题文本
+    pub text: String,
+    /// 标题锚点（HTML `id`）
+    pub anchor: String,
+}
+
+/// 渲染结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RenderedNote {
+    /// 正文 HTML
+    pub html: String,
+    /// 目录（按出现顺序）
+    pub toc: Vec<TocEntry>,
+}
+
+/// 渲染笔记正文
+///
+/// # Arguments
+///
+/// * `body` - 去掉 frontmatter 的 Markdown 正文
+/// * `resolve` - 将 wiki 链接目标解析为节点 ID，目标没有对应笔记时返回 None
+/// * `config` - 渲染配置
+pub fn render_markdown(body: &str, resolve: &dyn Fn(&str) -> Option<String>, config: &RenderConfig) -> RenderedNote {
+    let tag_re = Regex::new(r"(^|\s)#(\w[\w/-]*)").unwrap();
+    let mut events: Vec<Event> = Vec::new();
+    let mut toc = Vec::new();
+    let mut anchors = HashSet::new();
+    // 当前标题的 Start 事件位置和已收集的文本
+    let mut heading: Option<(usize, String)> = None;
+    let mut in_wiki_link = false;
+    let mut in_code_block = false;
+
+    for event in Parser::new_ext(body, Options::all()) {
+        match event {
+            Event::Start(Tag::Link { link_type: LinkType::WikiLink { .. }, dest_url, .. }) => {
+                let target = dest_url.trim();
+                let text = target.strip_suffix(".md").unwrap_or(target);
+                let open = match resolve(target) {
+                    Some(id) => format!("<a data-node-id=\"{}\">", escape_html(&id)),
+                    None => format!("<a class=\"broken\" data-target=\"{}\">", escape_html(target)),
+                };
+                if let Some((_, heading_text)) = &mut heading {
+                    heading_text.push_str(text);
+                }
+                events.push(Event::InlineHtml(open.into()));
+                events.push(Event::Text(text.to_string().into()));
+                in_wiki_link = true;
+            }
+            Event::End(TagEnd::Link) if in_wiki_link => {
+                events.push(Event::InlineHtml("</a>".into()));
+                in_wiki_link = false;
+            }
+            // 显示文本（`|` 之后的关系和强度）不输出
+            _ if in_wiki_link => {}
+            Event::Start(Tag::Heading { .. }) => {
+                heading = Some((events.len(), String::new()));
+                events.push(event);
+            }
+            Event::End(TagEnd::Heading(level)) => {
+                if let Some((start, text)) = heading.take() {
+                    if let Event::Start(Tag::Heading { id, .. }) = &mut events[start] {
+                        let anchor = id.as_deref().map(str::to_string).unwrap_or_else(|| unique_anchor(&text, &anchors));
+                        anchors.insert(anchor.clone());
+                        *id = Some(anchor.clone().into());
+                        toc.push(TocEntry {
+                            level: level as u8,
+                            text: text.trim().to_string(),
+                            anchor,
+                        });
+                    }
+                }
+                events.push(Event::End(TagEnd::Heading(level)));
+            }
+            Event::Start(Tag::CodeBlock(_)) => {
+                in_code_block = true;
+                events.push(event);
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                in_code_block = false;
+                events.push(event);
+            }
+            Event::Text(text) if !in_code_block => {
+                if let Some((_, heading_text)) = &mut heading {
+                    heading_text.push_str(&text);
+                }
+                push_text_with_tags(&mut events, &tag_re, &text);
+            }
+            Event::Code(code) => {
+                if let Some((_, heading_text)) = &mut heading {
+                    heading_text.push_str(&code);
+                }
+                events.push(Event::Code(code));
+            }
+            Event::Html(raw) | Event::InlineHtml(raw) if config.sanitize_html => {
+                events.push(Event::Text(raw));
+            }
+            event => events.push(event),
+        }
+    }
+
+    let mut out = String::new();
+    html::push_html(&mut out, events.into_iter());
+    RenderedNote { html: out, toc }
+}
+
+/// 把文本中的 `#tag` 替换为标签链接
+fn push_text_with_tags<'a>(events: &mut Vec<Event<'a>>, tag_re: &Regex, text: &str) {
+    let mut last = 0;
+    for cap in tag_re.captures_iter(text) {
+        let tag = cap.get(2).unwrap();
+        let hash = tag.start() - 1;
+        if hash > last {
+            events.push(Event::Text(CowStr::from(text[last..hash].to_string())));
+        }
+        let name = tag.as_str().trim_end_matches('/');
+        let end = hash + 1 + name.len();
+        events.push(Event::InlineHtml(
+            format!("<a data-tag=\"{}\">#{}</a>", escape_html(name), escape_html(name)).into(),
+        ));
+        last = end;
+    }
+    if last < text.len() {
+        events.push(Event::Text(CowStr::from(text[last..].to_string())));
+    }
+}
+
+/// 由标题文本生成不重复的锚点
+fn unique_anchor(text: &str, used: &HashSet<String>) -> String {
+    let base = slugify(text);
+    if !used.contains(&base) {
+        return base;
+    }
+    (1..)
+        .map(|i| format!("{}-{}", base, i))
+        .find(|anchor| !used.contains(anchor))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(body: &str) -> RenderedNote {
+        let resolve = |target: &str| (target == "Free Will").then(|| "urn:memexia:file:Free%20Will".to_string());
+        render_markdown(body, &resolve, &RenderConfig::default())
+    }
+
+    #[test]
+    fn test_render_wiki_links() {
+        let rendered = render("See [[Free Will|Supports:0.8]] and [[Missing]].\n\n```\n[[Free Will]]\n```\n");
+        assert!(rendered.html.contains("<a data-node-id=\"urn:memexia:file:Free%20Will\">Free Will</a>"));
+        assert!(rendered.html.contains("<a class=\"broken\" data-target=\"Missing\">Missing</a>"));
+        assert!(!rendered.html.contains("Supports"));
+        assert!(rendered.html.contains("<code>[[Free Will]]\n</code>"));
+    }
+
+    #[test]
+    fn test_render_tags() {
+        let rendered = render("Thinking about #mind/free-will today. Not a tag: a#b\n");
+        assert!(rendered.html.contains("<a data-tag=\"mind/free-will\">#mind/free-will</a> today"));
+        assert!(!rendered.html.contains("data-tag=\"b\""));
+    }
+
+    #[test]
+    fn test_render_toc() {
+        let rendered = render("# Free Will\n\n## Views\n\n## Views\n\n### `code` and [[Free Will]]\n");
+        let toc: Vec<_> = rendered.toc.iter().map(|e| (e.level, e.text.as_str(), e.anchor.as_str())).collect();
+        assert_eq!(
+            toc,
+            [
+                (1, "Free Will", "free-will"),
+                (2, "Views", "views"),
+                (2, "Views", "views-1"),
+                (3, "code and Free Will", "code-and-free-will"),
+            ]
+        );
+        assert!(rendered.html.contains("<h2 id=\"views-1\">Views</h2>"));
+    }
+
+    #[test]
+    fn test_render_sanitize_html() {
+        let body = "<script>alert(1)</script>\n\nText <b>bold</b>\n";
+        let resolve = |_: &str| None;
+        let sanitized = render_markdown(body, &resolve, &RenderConfig::default());
+        assert!(!sanitized.html.contains("<script>"));
+        assert!(sanitized.html.contains("&lt;b&gt;bold&lt;/b&gt;"));
+
+        let raw = render_markdown(body, &resolve, &RenderConfig { sanitize_html: false });
+        assert!(raw.html.contains("<script>"));
+        assert!(raw.html.contains("<b>bold</b>"));
+    }
+}
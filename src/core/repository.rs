@@ -1,106 +1,298 @@
 //! 仓库管理模块
 //!
 //! 管理 Memexia 仓库的生命周期
+//!
+//! 打开仓库只确定根目录；图存储和版本控制在首次使用时才打开（[`Repository::storage`]、
+//! [`Repository::vcs`]），只读取暂存区、Git 或配置文件的命令不必承担 RocksDB 的打开延迟
 
-use anyhow::{Context, Result};
+use anyhow::Context;
 use std::path::{Path, PathBuf};
 use std::fs;
-use std::io::Write;
-use crate::storage::{Storage, Node, NodeType};
-use crate::core::{object, parser};
-use crate::vcs::{Vcs, CommitInfo};
+use std::sync::mpsc::Receiver;
+use std::sync::OnceLock;
+use std::time::Instant;
+use crate::storage::observer::CHANGE_LOG_PATH;
+use crate::storage::{ids, ChangeLog, DanglingRepair, DanglingStrategy, Edge, EdgeEdit, EdgeFilter, Storage, Node, NodeType, RelationType};
+use crate::core::{archive, capture, contradictions, corpus, dashboard, derivation_tree, doctor, feed, duplicates, file_log, encrypted, graph_template, hooks, link_batch, link_refresh, note, registry, retag, lint, object, parser, paths, publish, query_templates, render, review, search, split, staging, stats, summary, time_travel, trash, undo, writer, zettel, DerivationRecording, Indexer, RepoConfig};
+use crate::core::folder_defaults::FolderDefaults;
+use crate::core::validate::{ValidationReport, Validator};
+use crate::vcs::{Vcs, CommitInfo, CommitLink, RetentionReport};
+use crate::vcs::graph_history::GraphDelta;
+use crate::error::{MemexiaError, Result};
+use crate::events::{EventBus, RepoEvent};
+
+/// 提交消息模板路径（相对仓库根目录）
+pub const COMMIT_TEMPLATE_PATH: &str = ".memexia/config/commit-template.md";
 
 /// Memexia 仓库
 pub struct Repository {
     /// 仓库根路径
     root: PathBuf,
-    /// 存储后端
-    storage: Storage,
-    /// 版本控制
-    vcs: Vcs,
+    /// 是否以只读模式打开
+    read_only: bool,
+    /// 是否开启 dry-run（存储打开前开启时在打开后应用）
+    dry_run: bool,
+    /// 是否开启节点读缓存（同上）
+    node_cache: bool,
+    /// 仓库事件总线，存储打开后交给存储
+    events: EventBus,
+    /// 存储后端（首次使用时打开）
+    storage: OnceLock<Storage>,
+    /// 版本控制（首次使用时打开）
+    vcs: OnceLock<Vcs>,
+    /// 图验证规则（提交前钩子和 `validate` 使用）
+    validator: Validator,
+}
+
+/// 提交时一个暂存文件解析后待写入的内容
+struct StagedNote {
+    /// 仓库相对路径
+    path: String,
+    /// 文件内容（写入对象库）
+    content: Vec<u8>,
+    /// 要写入的节点；过大或二进制的文件为 None
+    node: Option<Node>,
+    /// 链接目标（节点 ID, 标题），不存在时补建占位节点
+    targets: Vec<(String, String)>,
+    /// 链接对应的出边
+    edges: Vec<crate::storage::Edge>,
+}
+
+/// 写入一个暂存文件的节点、占位目标和边（提交的第二阶段，在图事务中调用）
+fn apply_staged_note(
+    graph: &dyn crate::storage::GraphStorage,
+    node: &mut Node,
+    targets: &[(String, String)],
+    edges: &[crate::storage::Edge],
+) -> Result<()> {
+    link_refresh::track_title_change(graph, node)?;
+    graph.add_node(node)?;
+    for (target_id, title) in targets {
+        if !graph.node_exists(target_id)? {
+            graph.add_node(&Node::new(target_id, NodeType::Concept, title))?;
+        }
+    }
+    for edge in edges {
+        graph.add_edge(edge)?;
+    }
+    Ok(())
 }
 
 impl Repository {
     /// 初始化新仓库
     pub fn init(path: &Path) -> Result<Self> {
-        let root = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        Self::init_with_notes(path, &[])
+    }
 
-        #[cfg(windows)]
-        let root = {
-            let p = root.to_string_lossy();
-            if let Some(without_prefix) = p.strip_prefix("\\\\?\\") {
-                PathBuf::from(without_prefix)
-            } else {
-                root
-            }
-        };
+    /// 初始化新仓库，并把笔记目录写入 `paths.notes` 配置
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 仓库路径
+    /// * `notes` - 笔记目录（相对仓库根目录，`.` 表示根目录）；为空时使用默认的 `notes/`
+    pub fn init_with_notes(path: &Path, notes: &[String]) -> Result<Self> {
+        let root = paths::strip_verbatim(&fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()));
 
         if root.join(".memexia").exists() {
-            anyhow::bail!("Repository already exists at {:?}", root);
+            return Err(MemexiaError::AlreadyInitialized { path: root });
+        }
+
+        let mut dirs = Vec::with_capacity(notes.len());
+        for dir in notes {
+            let rel = paths::normalize_input(&root.join(dir), &root);
+            if rel.is_external() {
+                return Err(MemexiaError::Validation(format!("Notes directory is outside the repository: {}", dir)));
+            }
+            dirs.push(rel.into_string());
         }
 
         // 初始化存储
-        let storage = Storage::init(&root)?;
+        let storage = Storage::init_with_notes(&root, &dirs)?;
+        query_templates::install(&root)?;
+        if !dirs.is_empty() {
+            let value = toml::Value::Array(dirs.into_iter().map(toml::Value::String).collect());
+            RepoConfig::set_value(&root, "paths.notes", value)?;
+        }
 
         // 初始化 VCS（同时初始化 Git 仓库）
         let vcs = Vcs::init(&root)?;
+        Self::register(&root);
 
-        Ok(Self {
-            root,
-            storage,
-            vcs,
-        })
+        let repo = Self::new(root, false);
+        let mut storage = storage;
+        storage.use_events(repo.events.clone());
+        let _ = repo.storage.set(storage);
+        let _ = repo.vcs.set(vcs);
+        Ok(repo)
     }
 
     /// 打开已有仓库
+    ///
+    /// 只查找仓库根目录；图存储和版本控制在首次使用时打开，
+    /// 存储被占用或损坏等错误由 [`storage`](Self::storage) 返回
     pub fn open(path: &Path) -> Result<Self> {
-        let mut current = Some(fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()));
+        let root = Self::find_root(path)?;
+        Self::register(&root);
+        Ok(Self::new(root, false))
+    }
 
-        #[cfg(windows)]
-        {
-            if let Some(ref mut p) = current {
-                let p_str = p.to_string_lossy();
-                if let Some(without_prefix) = p_str.strip_prefix("\\\\?\\") {
-                    *p = PathBuf::from(without_prefix);
-                }
-            }
+    /// 以只读模式打开已有仓库（`--read-only`）
+    ///
+    /// 不写入仓库中的任何文件：查询、搜索、导出、统计和日志正常工作，
+    /// 修改仓库的操作返回 [`MemexiaError::ReadOnlyRepository`]，见 [`Storage::open_read_only`]
+    pub fn open_read_only(path: &Path) -> Result<Self> {
+        let root = Self::find_root(path)?;
+        Self::register(&root);
+        Ok(Self::new(root, true))
+    }
+
+    fn new(root: PathBuf, read_only: bool) -> Self {
+        Self {
+            root,
+            read_only,
+            dry_run: false,
+            node_cache: false,
+            events: EventBus::new(),
+            storage: OnceLock::new(),
+            vcs: OnceLock::new(),
+            validator: Validator::with_builtin_rules(),
         }
+    }
+
+    /// 从 `path` 向上查找包含 `.memexia` 目录的仓库根路径（不打开存储）
+    pub fn find_root(path: &Path) -> Result<PathBuf> {
+        let mut current = Some(paths::strip_verbatim(
+            &fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()),
+        ));
 
-        // 向上查找 .memexia 目录
-        let mut found_root: Option<PathBuf> = None;
         while let Some(path) = current {
             if path.join(".memexia").exists() {
-                found_root = Some(path);
-                break;
+                return Ok(path);
             }
             current = path.parent().map(|p| p.to_path_buf());
         }
 
-        let root = match found_root {
-            Some(r) => r,
-            None => anyhow::bail!(
-                "Not a Memexia repository (or any of the parent directories): .memexia"
-            ),
+        Err(MemexiaError::RepositoryNotFound {
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// 登记到用户全局的仓库注册表（失败不影响命令本身）
+    fn register(root: &Path) {
+        if let Err(e) = registry::record_use(root) {
+            tracing::warn!("Failed to update repository registry: {}", e);
+        }
+    }
+
+    /// 按 `[changes]` 配置注册变更日志观察者（失败时只记录日志）
+    fn attach_change_log(root: &Path, storage: &Storage) {
+        let config = match RepoConfig::load(root) {
+            Ok(config) if config.changes.log => config.changes,
+            Ok(_) => return,
+            Err(e) => {
+                tracing::warn!("Failed to load config for the change log: {}", e);
+                return;
+            }
+        };
+        match ChangeLog::open(&root.join(CHANGE_LOG_PATH), config.max_log_bytes) {
+            Ok(log) => storage.register_observer(std::sync::Arc::new(log)),
+            Err(e) => tracing::warn!("Failed to open {}: {}", CHANGE_LOG_PATH, e),
+        }
+    }
+
+    /// 获取存储后端，首次调用时打开图存储
+    ///
+    /// 打开失败（存储被占用、没有写权限、仓库损坏）时返回错误，下次调用会重试
+    pub fn storage(&self) -> Result<&Storage> {
+        if let Some(storage) = self.storage.get() {
+            return Ok(storage);
+        }
+        let start = Instant::now();
+        let mut storage = if self.read_only {
+            Storage::open_read_only(&self.root)?
+        } else {
+            Storage::open(&self.root)?
         };
+        if self.node_cache {
+            storage.enable_node_cache(Self::node_cache_capacity(&self.root));
+        }
+        if self.dry_run {
+            storage.enable_dry_run();
+        }
+        storage.use_events(self.events.clone());
+        if !self.read_only {
+            Self::attach_change_log(&self.root, &storage);
+        }
+        tracing::debug!(elapsed_ms = start.elapsed().as_millis() as u64, "opened graph store");
+        Ok(self.storage.get_or_init(|| storage))
+    }
 
-        let storage = Storage::open(&root)?;
-        let vcs = Vcs::open(&root)?;
+    /// 为读取密集的命令开启节点读缓存，容量见 `[cache] nodes`，见 [`Storage::enable_node_cache`]
+    pub fn enable_node_cache(&mut self) {
+        self.node_cache = true;
+        if let Some(storage) = self.storage.get_mut() {
+            storage.enable_node_cache(Self::node_cache_capacity(&self.root));
+        }
+    }
 
-        Ok(Self {
-            root,
-            storage,
-            vcs,
-        })
+    fn node_cache_capacity(root: &Path) -> usize {
+        match RepoConfig::load(root) {
+            Ok(config) => config.cache.nodes,
+            Err(e) => {
+                tracing::warn!("Failed to load config for the node cache: {}", e);
+                0
+            }
+        }
+    }
+
+    /// 开启 dry-run：之后的图写操作只记录不执行，见 [`Storage::enable_dry_run`]
+    pub fn enable_dry_run(&mut self) {
+        self.dry_run = true;
+        if let Some(storage) = self.storage.get_mut() {
+            storage.enable_dry_run();
+        }
+    }
+
+    /// 是否以只读模式打开
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// 是否处于 dry-run 模式（不必打开存储）
+    fn is_dry_run(&self) -> bool {
+        self.dry_run && !self.read_only
     }
 
-    /// 获取存储后端
-    pub fn storage(&self) -> &Storage {
-        &self.storage
+    /// 只读模式下拒绝修改仓库的操作
+    fn ensure_writable(&self) -> Result<()> {
+        if self.is_read_only() {
+            return Err(MemexiaError::ReadOnlyRepository { path: self.root.clone() });
+        }
+        Ok(())
+    }
+
+    /// 订阅仓库事件，见 [`crate::events`]
+    ///
+    /// 同一仓库的所有操作（包括使用 [`storage`](Self::storage) 克隆创建的索引器）都会发出事件
+    pub fn subscribe(&self) -> Receiver<RepoEvent> {
+        self.events.subscribe()
+    }
+
+    /// 获取版本控制，首次调用时打开 Git 仓库和图历史
+    pub fn vcs(&self) -> Result<&Vcs> {
+        if let Some(vcs) = self.vcs.get() {
+            return Ok(vcs);
+        }
+        let start = Instant::now();
+        let vcs = Vcs::open(&self.root)?;
+        tracing::debug!(elapsed_ms = start.elapsed().as_millis() as u64, "opened version control");
+        Ok(self.vcs.get_or_init(|| vcs))
     }
 
-    /// 获取版本控制
-    pub fn vcs(&self) -> &Vcs {
-        &self.vcs
+    /// 可变的版本控制（提交、修改提交），首次调用时打开
+    fn vcs_mut(&mut self) -> Result<&mut Vcs> {
+        self.vcs()?;
+        Ok(self.vcs.get_mut().expect("version control was opened above"))
     }
 
     /// 获取仓库根路径
@@ -109,129 +301,602 @@ impl Repository {
     }
 
     /// 添加文件到暂存区
+    ///
+    /// 记录每个文件的路径、当前内容哈希和暂存时间；读写暂存区期间持有仓库锁，
+    /// 见 [`staging`]
     pub fn add(&self, files: &[PathBuf]) -> Result<()> {
-        let index_path = self.root.join(".memexia/index");
-        let mut index = if index_path.exists() {
-            let content = fs::read_to_string(&index_path)?;
-            content.lines().map(|s| s.to_string()).collect::<Vec<_>>()
+        self.ensure_writable()?;
+        let mut staged = Vec::with_capacity(files.len());
+        for file in files {
+            let abs_path = fs::canonicalize(file).context("File not found")?;
+            let rel_path = paths::normalize_input(&abs_path, &self.root);
+            if rel_path.is_external() {
+                return Err(anyhow::anyhow!("File is outside repository: {}", paths::display(&abs_path)).into());
+            }
+            let hash = object::hash_content(&fs::read(&abs_path)?);
+            staged.push((rel_path.into_string(), hash));
+        }
+
+        let now = chrono::Utc::now();
+        staging::StagingIndex::update(&self.root, |index| {
+            for (path, hash) in &staged {
+                index.stage(path, hash, now);
+            }
+        })
+    }
+
+    /// 自动暂存内容已变化的笔记（`commit --all`）
+    ///
+    /// 已跟踪（曾提交过）且内容哈希变化的笔记同时加入 `.memexia/index` 和 Git 索引；
+    /// `include_untracked` 时还包括符合监听白名单的新笔记。已在暂存区中的文件不重复计入
+    ///
+    /// # Returns
+    ///
+    /// 本次自动暂存的文件（相对仓库根目录）
+    pub fn stage_changes(&self, include_untracked: bool) -> Result<Vec<PathBuf>> {
+        self.ensure_writable()?;
+        let config = RepoConfig::load(&self.root)?;
+        let mut indexer = Indexer::new(self.storage()?.clone());
+        indexer.set_extensions(config.index.extensions.clone());
+
+        let staged = staging::StagingIndex::load(&self.root)?;
+
+        let changed: Vec<PathBuf> = self
+            .vcs()?
+            .worktree_changes(include_untracked)?
+            .into_iter()
+            .filter(|rel| indexer.is_indexable(rel) && !staged.contains(&rel.to_string_lossy()))
+            .collect();
+        if changed.is_empty() {
+            return Ok(changed);
+        }
+
+        let absolute: Vec<PathBuf> = changed.iter().map(|rel| self.root.join(rel)).collect();
+        self.add(&absolute)?;
+        self.vcs()?.stage(&absolute)?;
+        Ok(changed)
+    }
+
+    /// 按仓库配置索引目录（`memexia reindex`）
+    ///
+    /// 检测重命名时，Git 报告的重命名作为线索与内容哈希一起配对新旧文件，
+    /// 被重命名笔记的节点历史随节点迁移到新 ID
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 要索引的目录
+    /// * `full` - 是否先逐个删除已有文件节点再全量重建（此时不检测重命名）；
+    ///   不在 dry-run 时应改用清空图存储的 [`rebuild_index`](Self::rebuild_index)
+    /// * `detect_renames` - 是否检测重命名
+    ///
+    /// # Returns
+    ///
+    /// 索引汇总结果
+    pub fn reindex(&self, path: &Path, full: bool, detect_renames: bool) -> Result<crate::core::IndexSummary> {
+        self.ensure_writable()?;
+        let config = RepoConfig::load(&self.root)?;
+        let mut indexer = self.reindexer(&config)?;
+        indexer.set_rename_detection(detect_renames && !full);
+        if detect_renames && !full {
+            let pathspecs: Vec<String> = config.index.extensions.iter().map(|ext| format!("*.{}", ext)).collect();
+            indexer.set_rename_hints(self.vcs()?.worktree_renames(&pathspecs)?);
+        }
+
+        let summary = if full {
+            indexer.reindex_all(path)?
         } else {
-            Vec::new()
+            indexer.index_all(path)?
         };
 
-        for file in files {
-            let abs_path = fs::canonicalize(file).context("File not found")?;
-            let rel_path = pathdiff::diff_paths(&abs_path, &self.root)
-                .context("File is outside repository")?;
+        if !self.is_dry_run() {
+            for rename in &summary.renamed {
+                self.vcs()?.graph_history.rename_node_history(&rename.old_id, &rename.new_id)?;
+            }
+            if config.history.derivations == DerivationRecording::Index {
+                self.record_indexed_derivations()?;
+            }
+        }
+        Ok(summary)
+    }
+
+    /// 为图中全部 `DerivesFrom` 链接记录推导关系并关联 HEAD（`history.derivations = "index"`）；
+    /// 还没有提交时不记录
+    fn record_indexed_derivations(&self) -> Result<usize> {
+        let Some(head) = self.vcs()?.head_info()? else {
+            return Ok(0);
+        };
+        let filter = EdgeFilter { relation: Some(RelationType::DerivesFrom), ..Default::default() };
+        let edges = self.storage()?.graph().query_edges(filter)?;
+        self.record_link_derivations(&edges, &head.oid)
+    }
+
+    /// 按仓库配置创建 `reindex` 使用的索引器
+    fn reindexer(&self, config: &RepoConfig) -> Result<Indexer> {
+        let mut indexer = Indexer::new(self.storage()?.clone());
+        indexer.set_zettel_ids(config.ids.zettel);
+        indexer.set_extensions(config.index.extensions.clone());
+        indexer.set_decrypt_cmd(config.index.decrypt_cmd.clone());
+        indexer.set_size_limits(config.index.max_note_bytes, config.index.content_excerpt_bytes);
+        indexer.set_default_relation(config.links.default_relation.clone());
+        indexer.set_auto_summary(config.index.auto_summary);
+        indexer.set_note_roots(config.paths.walk_dirs());
+        indexer.set_folder_defaults(FolderDefaults::load(&self.root)?);
+        Ok(indexer)
+    }
+
+    /// 清空图存储后全量重建索引（`memexia reindex --full`），见 [`Storage::reset`]
+    ///
+    /// 旧图先移到一旁，全部文件索引成功后才删除；重建出错或任一文件索引失败时恢复旧图，
+    /// 不会留下空图。dry-run 时不能移走图存储，改为 [`reindex`](Self::reindex) 的逐个删除
+    /// 图存储无法打开（[`MemexiaError::StoreCorrupt`]）时先用 [`recover_store`](Self::recover_store) 换成空存储
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 要索引的目录
+    ///
+    /// # Returns
+    ///
+    /// 索引汇总结果
+    pub fn rebuild_index(&mut self, path: &Path) -> Result<crate::core::IndexSummary> {
+        self.ensure_writable()?;
+        if self.is_dry_run() {
+            return self.reindex(path, true, false);
+        }
+        if let Err(MemexiaError::StoreCorrupt { .. }) = self.storage().map(|_| ()) {
+            self.recover_store()?;
+        }
+        self.storage()?;
+        let backup = self.storage.get_mut().expect("storage was opened above").reset()?;
+
+        // 索引器持有存储的克隆，恢复前必须释放
+        let result = RepoConfig::load(&self.root).map_err(MemexiaError::from).and_then(|config| {
+            let indexer = self.reindexer(&config)?;
+            let summary = indexer.index_all(path)?;
+            if let Some((file, error)) = summary.errors.first() {
+                return Err(MemexiaError::Other(anyhow::anyhow!(
+                    "Full reindex failed for {} file(s), the previous graph was restored; first error: {}: {}",
+                    summary.errors.len(),
+                    file,
+                    error
+                )));
+            }
+            self.storage()?.graph().rebuild_title_index()?;
+            if config.history.derivations == DerivationRecording::Index {
+                self.record_indexed_derivations()?;
+            }
+            Ok(summary)
+        });
 
-            let path_str = rel_path.to_string_lossy().to_string();
-            if !index.contains(&path_str) {
-                index.push(path_str);
+        let storage = self.storage.get_mut().expect("storage was opened above");
+        match result {
+            Ok(summary) => {
+                if let Err(e) = backup.discard() {
+                    tracing::warn!("Failed to remove the graph backup: {}", e);
+                }
+                Ok(summary)
+            }
+            Err(err) => {
+                let backup_path = backup.path().to_path_buf();
+                storage.restore(backup).map_err(|restore_err| {
+                    MemexiaError::Other(anyhow::anyhow!(
+                        "{}; restoring the previous graph from {} also failed: {}",
+                        err,
+                        paths::display(&backup_path),
+                        restore_err
+                    ))
+                })?;
+                Err(err)
             }
         }
+    }
 
-        let mut file = fs::File::create(index_path)?;
-        for line in index {
-            writeln!(file, "{}", line)?;
+    /// 把无法打开的图存储换成空存储，之后可用 [`rebuild_index`](Self::rebuild_index) 从文件重建
+    ///
+    /// 已打开的存储不受影响（直接返回 None）；见 [`Storage::recover`]
+    ///
+    /// # Returns
+    ///
+    /// 旧存储被移到的路径
+    pub fn recover_store(&mut self) -> Result<Option<PathBuf>> {
+        self.ensure_writable()?;
+        if self.storage.get().is_some() {
+            return Ok(None);
         }
+        let moved = Storage::recover(&self.root)?;
+        self.storage()?;
+        Ok(moved)
+    }
 
-        Ok(())
+    /// 创建新笔记
+    ///
+    /// 按选项生成笔记文件，加入暂存区并立即索引到图谱
+    ///
+    /// # Returns
+    ///
+    /// 新文件路径和对应的节点 ID
+    pub fn create_note(&self, options: &note::NoteOptions) -> Result<(PathBuf, String)> {
+        self.ensure_writable()?;
+        let config = RepoConfig::load(&self.root)?;
+        let now = chrono::Local::now().naive_local();
+
+        let options = note::NoteOptions {
+            dir: options.dir.clone().or_else(|| Some(config.paths.new_note_dir())),
+            ..options.clone()
+        };
+        let path = note::create_note(&self.root, &options, config.ids.zettel, now)?;
+
+        let mut indexer = Indexer::new(self.storage()?.clone());
+        indexer.set_zettel_ids(config.ids.zettel);
+        indexer.set_extensions(config.index.extensions.clone());
+        indexer.set_size_limits(config.index.max_note_bytes, config.index.content_excerpt_bytes);
+        indexer.set_default_relation(config.links.default_relation.clone());
+        indexer.set_auto_summary(config.index.auto_summary);
+        // 模板中的图谱占位符在创建时展开
+        self.render_graph_blocks(&path, &indexer.path_to_id(&path))?;
+        self.add(std::slice::from_ref(&path))?;
+        indexer.index_file(&path)?;
+
+        let node_id = indexer.path_to_id(&path);
+        Ok((path, node_id))
     }
 
     /// 查看暂存区状态
+    ///
+    /// 暂存后内容又有变化的文件标注 `(modified since add)`，提交时使用的是文件的当前内容
     pub fn status(&self) -> Result<String> {
-        let index_path = self.root.join(".memexia/index");
-        if !index_path.exists() {
+        let index = staging::StagingIndex::load(&self.root)?;
+        if index.is_empty() {
             return Ok("No changes staged.".to_string());
         }
 
-        let content = fs::read_to_string(index_path)?;
-        if content.is_empty() {
-            return Ok("No changes staged.".to_string());
+        let mut output = String::from("Staged files:\n");
+        for entry in index.entries() {
+            let current = fs::read(self.root.join(&entry.path)).ok().map(|content| object::hash_content(&content));
+            let modified = entry.hash.is_some() && current != entry.hash;
+            output.push_str(&entry.path);
+            if modified {
+                output.push_str(" (modified since add)");
+            }
+            output.push('\n');
         }
-
-        Ok(format!("Staged files:\n{}", content))
+        Ok(output)
     }
 
     /// 提交变更
     ///
-    /// 流程：
-    /// 1. 读取暂存区文件列表
-    /// 2. 处理每个文件，更新图数据库
-    /// 3. 调用 VCS 创建 Git 提交
-    /// 4. 记录图历史
+    /// 分两个阶段：
+    /// 1. 读取并解析全部暂存文件，得到要写入的节点、边和对象，此阶段不修改任何内容
+    /// 2. 在一个图事务中写入节点和边、执行提交前验证、写入对象并创建 Git 提交（同时记录图快照），
+    ///    成功后才清空暂存区
+    ///
+    /// Git 提交创建之前的任何失败都会撤销图谱写入、保留暂存区；
+    /// 某个文件无法读取或解析时返回 [`MemexiaError::StagedFile`] 并指明该文件
     pub fn commit(&mut self, message: &str) -> Result<String> {
-        let index_path = self.root.join(".memexia/index");
-        if !index_path.exists() {
-            anyhow::bail!("Nothing to commit");
+        self.ensure_writable()?;
+        let staged = staging::StagingIndex::load(&self.root)?;
+        if staged.is_empty() {
+            return Err(MemexiaError::NothingToCommit);
         }
 
-        let content = fs::read_to_string(&index_path)?;
-        if content.is_empty() {
-            anyhow::bail!("Nothing to commit");
-        }
+        let index: Vec<String> = staged.entries().iter().map(|entry| entry.path.clone()).collect();
 
-        let index: Vec<String> = content.lines().map(|s| s.to_string()).collect();
+        let _span = tracing::info_span!("commit", root = %self.root.display(), files = index.len()).entered();
 
-        // 收集要提交的文件路径
-        let mut files: Vec<PathBuf> = Vec::new();
+        let config = RepoConfig::load(&self.root)?;
 
+        // 第一阶段：解析全部暂存文件
+        let folder_defaults = FolderDefaults::load(&self.root)?;
+        let mut notes: Vec<StagedNote> = Vec::new();
         for path_str in &index {
             let path = self.root.join(path_str);
             if !path.exists() {
                 continue;
             }
+            let note = self
+                .prepare_staged_file(&path, path_str, &config, &folder_defaults)
+                .map_err(|e| MemexiaError::StagedFile { file: path_str.clone(), source: Box::new(e) })?;
+            notes.push(note);
+        }
+
+        let files: Vec<PathBuf> = notes.iter().map(|note| self.root.join(&note.path)).collect();
+        let affected_nodes: Vec<String> =
+            notes.iter().filter_map(|note| note.node.as_ref().map(|node| node.id.clone())).collect();
+
+        // 记录提交前的图快照，用于计算变更统计
+        let previous_graph = self.last_commit()?.and_then(|c| c.graph_hash);
+
+        // 第二阶段：在图事务中写入并提交，事务内的失败撤销全部图谱写入
+        let storage = self.storage()?.clone();
+        let events = storage.events();
+        // 事务中还要读取 root 和 validator，只可变借用 vcs 字段
+        self.vcs()?;
+        let vcs = self.vcs.get_mut().expect("version control was opened above");
+        let commit_hash = storage.graph().transaction(|graph| {
+            let mut edges_before = Vec::new();
+            for note in &mut notes {
+                let Some(node) = note.node.as_mut() else {
+                    continue;
+                };
+                let _write_span = tracing::info_span!("storage_write", file = %note.path).entered();
+                let before = events.outgoing_edges(graph, &node.id)?;
+                apply_staged_note(graph, node, &note.targets, &note.edges)
+                    .map_err(|e| MemexiaError::StagedFile { file: note.path.clone(), source: Box::new(e) })?;
+                edges_before.push(before);
+            }
+
+            // 提交前验证，存在错误级别违规时中止
+            hooks::run_pre_commit(&config, graph, &self.validator, events)?;
+
+            for note in &notes {
+                object::write_object(&self.root, &note.content)?;
+            }
+
+            // 解析和验证都通过后才发出索引事件，被拒绝的提交不产生事件
+            let indexed = notes.iter().filter_map(|note| note.node.as_ref().map(|node| (node, &note.path)));
+            for ((node, path), before) in indexed.zip(&edges_before) {
+                events.emit(RepoEvent::node_indexed(node, path));
+                events.emit_edge_changes(graph, &node.id, before)?;
+            }
+            vcs.commit(message, &files, &affected_nodes, &storage)
+        })?;
+
+        let introduced = self.record_link_provenance(&affected_nodes, &commit_hash)?;
+        if config.history.derivations == DerivationRecording::Commit {
+            self.record_link_derivations(&introduced, &commit_hash)?;
+        }
+
+        println!("Committed: {}", commit_hash);
+
+        // 清空已提交的暂存条目，提交期间新暂存的文件保留
+        staging::StagingIndex::update(&self.root, |index| index.remove_committed(staged.entries()))?;
+
+        self.notify_post_commit(&commit_hash, message, &index, previous_graph.as_deref());
+        self.enforce_retention();
+
+        Ok(commit_hash)
+    }
 
-            files.push(path.clone());
+    /// 读取并解析一个暂存文件（提交的第一阶段，不修改图谱）
+    ///
+    /// 加密笔记按 `index.decrypt_cmd` 解密后解析，未配置时只生成占位节点；
+    /// 过大或二进制的文件照常提交，但不写入图谱
+    fn prepare_staged_file(
+        &self,
+        path: &Path,
+        path_str: &str,
+        config: &RepoConfig,
+        folder_defaults: &FolderDefaults,
+    ) -> Result<StagedNote> {
+        let content = fs::read(path)?;
+        let hash = object::hash_content(&content);
+        let mut note = StagedNote {
+            path: path_str.to_string(),
+            content,
+            node: None,
+            targets: Vec::new(),
+            edges: Vec::new(),
+        };
 
-            let file_content = fs::read(&path)?;
-            let _hash = object::write_object(&self.root, &file_content)?;
+        let is_encrypted = encrypted::is_encrypted(path);
+        // 加密笔记的密文不做二进制检查
+        let probe: &[u8] = if is_encrypted { &[] } else { &note.content };
+        if let Some(reason) =
+            crate::core::indexer::check_note(note.content.len() as u64, probe, config.index.max_note_bytes)
+        {
+            tracing::warn!(file = %path_str, "not indexed: {}", reason);
+            return Ok(note);
+        }
 
-            let content_str = String::from_utf8_lossy(&file_content);
-            let parsed = parser::parse_markdown(&content_str, path_str);
+        let text = match (&config.index.decrypt_cmd, is_encrypted) {
+            (None, true) => {
+                let mut indexer = Indexer::new(self.storage()?.clone());
+                indexer.set_zettel_ids(config.ids.zettel);
+                let mut node = encrypted::stub_node(&indexer.path_to_id(path), path_str);
+                node.set_content_hash(hash);
+                note.node = Some(node);
+                return Ok(note);
+            }
+            (Some(cmd), true) => encrypted::decrypt(cmd, path)?,
+            _ => String::from_utf8_lossy(&note.content).into_owned(),
+        };
+        let parsed = tracing::info_span!("parse", file = %path_str)
+            .in_scope(|| parser::parse_file_with_default(&text, path_str, &config.links.default_relation).0);
 
-            // Create node in graph
-            let node = parsed.to_node();
-            self.storage.graph().add_node(&node)?;
+        let zettel_ids = config.ids.zettel;
+        let mut node = if zettel_ids {
+            parsed.to_zettel_node()
+        } else {
+            parsed.to_node()
+        };
+        if config.index.auto_summary {
+            summary::apply(&mut node, &text, path_str);
+        }
+        if is_encrypted {
+            encrypted::mark_encrypted(&mut node);
+        }
+        if parsed.frontmatter.is_none() {
+            if let Some(node_type) = folder_defaults.node_type_for(path_str) {
+                node.node_type = node_type;
+            }
+        }
+        node.truncate_content(config.index.content_excerpt_bytes, path_str);
+        node.set_content_hash(hash);
 
-            // Create edges for links
-            for link in &parsed.wiki_links {
-                let target_id = format!("urn:memexia:file:{}", link.target.replace(" ", "_"));
+        for link in &parsed.wiki_links {
+            // 已是 URN 的目标（如 Org ID 链接）直接使用，Zettel 模式下解析 ID 前缀
+            let resolved_target = if link.target.starts_with("urn:memexia:") {
+                Some(link.target.clone())
+            } else if zettel_ids {
+                zettel::resolve_link_target(&link.target)
+            } else {
+                None
+            };
+            let edge = match &resolved_target {
+                Some(target) => link.to_edge_with_target(&node.id, target)?,
+                None => link.to_edge(&node.id),
+            };
+            note.targets.push((edge.to.clone(), link.target.clone()));
+            note.edges.push(edge);
+        }
+        note.node = Some(node);
+        Ok(note)
+    }
 
-                if !self.storage.graph().node_exists(&target_id)? {
-                    let target_node = Node::new(&target_id, NodeType::Concept, &link.target);
-                    self.storage.graph().add_node(&target_node)?;
+    /// 为提交的笔记中还没有来源记录的链接写入首次提交（`memexia:introducedInCommit`）
+    ///
+    /// 已有记录的链接保持不变，只有新出现的链接记为本次提交
+    ///
+    /// # Returns
+    ///
+    /// 本次提交新引入的链接
+    fn record_link_provenance(&self, nodes: &[String], commit_hash: &str) -> Result<Vec<Edge>> {
+        self.storage()?.graph().transaction(|graph| {
+            let mut introduced = Vec::new();
+            for node in nodes {
+                for mut edge in graph.get_edges_by_source(node)? {
+                    if edge.introduced_in_commit.is_none() && !edge.is_suggestion() {
+                        edge.introduced_in_commit = Some(commit_hash.to_string());
+                        graph.add_edge(&edge)?;
+                        introduced.push(edge);
+                    }
                 }
+            }
+            Ok(introduced)
+        })
+    }
+
+    /// 为 `DerivesFrom` 链接记录推导关系（链接所在笔记为子节点，目标为父节点），
+    /// 见 [`DerivationRecording`]；已记录过的节点对被跳过
+    ///
+    /// # Returns
+    ///
+    /// 新增的推导记录数
+    fn record_link_derivations<'a>(
+        &self,
+        edges: impl IntoIterator<Item = &'a Edge>,
+        commit_hash: &str,
+    ) -> Result<usize> {
+        let pairs: Vec<(String, String)> = edges
+            .into_iter()
+            .filter(|edge| edge.relation == RelationType::DerivesFrom && !edge.is_suggestion())
+            .map(|edge| (edge.from.clone(), edge.to.clone()))
+            .collect();
+        if pairs.is_empty() {
+            return Ok(0);
+        }
+        self.vcs()?.graph_history.record_new_derivations(&pairs, commit_hash)
+    }
 
-                let edge = link.to_edge(&node.id);
-                self.storage.graph().add_edge(&edge)?;
+    /// 把首次提交记为 `from` 的链接改记为 `to`（提交被修改后哈希改变）
+    fn move_link_provenance(&self, from: &str, to: &str) -> Result<()> {
+        let graph = self.storage()?.graph();
+        let moved: Vec<crate::storage::Edge> = graph
+            .list_edges()?
+            .into_iter()
+            .filter(|edge| edge.introduced_in_commit.as_deref() == Some(from))
+            .collect();
+        // 已记录的来源不会被覆盖，先删除再以新提交写回
+        graph.transaction(|graph| {
+            for mut edge in moved {
+                graph.delete_edge(&edge.id)?;
+                edge.introduced_in_commit = Some(to.to_string());
+                graph.add_edge(&edge)?;
             }
+            Ok(())
+        })
+    }
+
+    /// 按 `history.retention` 清理提交快照（`memexia gc` 和每次提交后执行）
+    ///
+    /// HEAD 和各分支末端提交的快照始终保留；dry-run 时只计算不修改
+    ///
+    /// # Returns
+    ///
+    /// 被清理的关联和删除的快照
+    pub fn apply_retention(&self) -> Result<RetentionReport> {
+        let dry_run = self.is_dry_run();
+        if !dry_run {
+            self.ensure_writable()?;
         }
+        let config = RepoConfig::load(&self.root)?;
+        self.vcs()?.apply_retention(&config.history.retention, dry_run)
+    }
 
-        // 调用 VCS 提交
-        let commit_hash = self.vcs.commit(message, &files, &self.storage)?;
+    /// 快照后执行保留策略，失败不影响已完成的提交
+    fn enforce_retention(&self) {
+        if let Err(e) = self.apply_retention() {
+            tracing::warn!("Failed to apply snapshot retention policy: {:#}", e);
+        }
+    }
 
-        println!("Committed: {}", commit_hash);
+    /// 触发提交钩子
+    ///
+    /// 钩子失败不影响提交结果
+    fn notify_post_commit(&self, commit_hash: &str, message: &str, files: &[String], previous_graph: Option<&str>) {
+        let config = match RepoConfig::load(&self.root) {
+            Ok(config) => config,
+            Err(e) => {
+                tracing::warn!("Failed to load config for hooks: {:#}", e);
+                return;
+            }
+        };
+        if config.hooks.post_commit_url.is_none() && config.hooks.post_commit_cmd.is_none() {
+            return;
+        }
 
-        // Clear index
-        fs::File::create(index_path)?;
+        let history = match self.vcs() {
+            Ok(vcs) => &vcs.graph_history,
+            Err(e) => {
+                tracing::warn!("Failed to open version control for hooks: {:#}", e);
+                return;
+            }
+        };
+        let graph_delta = match history.get_commit_graph_hash(commit_hash) {
+            Ok(Some(new_hash)) => match previous_graph {
+                Some(old_hash) => history
+                    .diff(old_hash, &new_hash)
+                    .map(|delta| {
+                        let (added, removed) = delta.stats();
+                        hooks::GraphDeltaStats { added, removed }
+                    })
+                    .unwrap_or_default(),
+                None => history
+                    .get_snapshot(&new_hash)
+                    .map(|s| hooks::GraphDeltaStats {
+                        added: s.nquads.lines().filter(|l| !l.trim().is_empty()).count(),
+                        removed: 0,
+                    })
+                    .unwrap_or_default(),
+            },
+            _ => hooks::GraphDeltaStats::default(),
+        };
 
-        Ok(commit_hash)
+        let payload = hooks::CommitPayload::new(commit_hash, message, files.to_vec(), graph_delta);
+        hooks::run_post_commit(&config.hooks, &payload);
     }
 
     /// 修改最后一次提交
     pub fn amend(&mut self, message: &str) -> Result<()> {
-        self.vcs.amend(message, &self.storage)?;
+        self.ensure_writable()?;
+        let previous = self.vcs()?.resolve_revision("HEAD").ok();
+        let storage = self.storage()?.clone();
+        self.vcs_mut()?.amend(message, &storage)?;
+        // 修改后的提交哈希不同，把记在旧提交上的链接来源转到新提交
+        if let (Some(previous), Ok(amended)) = (previous, self.vcs()?.resolve_revision("HEAD")) {
+            self.move_link_provenance(&previous.to_string(), &amended.to_string())?;
+        }
         println!("Commit amended successfully");
+        self.enforce_retention();
         Ok(())
     }
 
     /// 查看提交历史
     pub fn log(&self, limit: usize) -> Result<Vec<CommitInfo>> {
-        let mut commits = self.vcs.log(limit)?;
+        let mut commits = self.vcs()?.log(limit)?;
 
         // 补充图快照哈希信息
         for commit in &mut commits {
-            if let Ok(Some(graph_hash)) = self.vcs.graph_history.get_commit_graph_hash(&commit.oid) {
+            if let Ok(Some(graph_hash)) = self.vcs()?.graph_history.get_commit_graph_hash(&commit.oid) {
                 commit.graph_hash = Some(graph_hash);
             }
         }
@@ -239,12 +904,72 @@ impl Repository {
         Ok(commits)
     }
 
+    /// 查看涉及指定笔记的提交历史
+    ///
+    /// 按提交消息中的 `Affected-Nodes` / `Affects` trailer 过滤
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 笔记文件路径
+    /// * `limit` - 最多返回的提交数
+    pub fn log_for_node(&self, path: &Path, limit: usize) -> Result<Vec<CommitInfo>> {
+        let node_id = self.node_id_for_path(path)?;
+        let commits = self.log(usize::MAX)?;
+        Ok(commits
+            .into_iter()
+            .filter(|c| c.touches_node(&node_id))
+            .take(limit)
+            .collect())
+    }
+
+    /// 单篇笔记的变更日志（`memexia file log`）
+    ///
+    /// 合并修改过文件的 Git 提交（跟随重命名）、节点快照和推导记录
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 文件路径
+    ///
+    /// # Returns
+    ///
+    /// 从新到旧排列的日志
+    pub fn file_log(&self, path: &Path) -> Result<Vec<file_log::FileLogEntry>> {
+        let node_id = self.node_id_for_path(path)?;
+        let absolute = fs::canonicalize(path).unwrap_or_else(|_| self.root.join(path));
+        let relative = paths::normalize_input(&absolute, &self.root);
+        if relative.is_external() {
+            return Err(MemexiaError::Validation(format!("File is outside repository: {}", paths::display(&absolute))));
+        }
+
+        let revisions = self.vcs()?.file_history(Path::new(relative.as_str()))?;
+        let history = &self.vcs()?.graph_history;
+        Ok(file_log::build_file_log(
+            &revisions,
+            &history.get_node_history(&node_id)?,
+            &history.get_derivations(&node_id)?,
+            &history.get_derived_nodes(&node_id)?,
+        ))
+    }
+
+    /// 读取提交消息模板 `.memexia/config/commit-template.md`
+    ///
+    /// # Returns
+    ///
+    /// 模板不存在时返回 None
+    pub fn commit_template(&self) -> Result<Option<String>> {
+        let path = self.root.join(COMMIT_TEMPLATE_PATH);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(fs::read_to_string(path)?))
+    }
+
     /// 查看最后一次提交
     pub fn last_commit(&self) -> Result<Option<CommitInfo>> {
-        let mut commit = self.vcs.head_info()?;
+        let mut commit = self.vcs()?.head_info()?;
 
         if let Some(ref mut c) = commit {
-            if let Ok(Some(graph_hash)) = self.vcs.graph_history.get_commit_graph_hash(&c.oid) {
+            if let Ok(Some(graph_hash)) = self.vcs()?.graph_history.get_commit_graph_hash(&c.oid) {
                 c.graph_hash = Some(graph_hash);
             }
         }
@@ -254,7 +979,7 @@ impl Repository {
 
     /// SPARQL 查询
     pub fn query_graph(&self, _query: &str) -> Result<Vec<String>> {
-        let nodes = self.storage.graph().list_nodes()?;
+        let nodes = self.storage()?.graph().list_nodes()?;
         let mut rows = Vec::new();
 
         for node in nodes {
@@ -266,14 +991,1395 @@ impl Repository {
 
     /// 导出图为 N-Quads
     pub fn export_nquads(&self) -> Result<String> {
-        self.storage.graph().export_nquads()
+        self.storage()?.graph().export_nquads()
     }
 
     /// 获取图历史
     pub fn graph_history(&self, limit: usize) -> Result<Vec<(String, String)>> {
-        let entries = self.vcs.graph_history.get_history(limit)?;
+        let entries = self.vcs()?.graph_history.get_history(limit)?;
         Ok(entries.iter()
             .map(|e| (e.commit_hash.clone(), e.graph_hash.clone()))
             .collect())
     }
+
+    /// 加载指定日期或提交时的图状态（不影响当前存储）
+    ///
+    /// 修订表达式先解析为完整提交 OID，再按 OID 查找图历史
+    pub fn graph_at(&self, at: &time_travel::At) -> Result<time_travel::HistoricalGraph> {
+        let at = match at {
+            time_travel::At::Commit(rev) if !rev.starts_with(crate::vcs::AUTO_SNAPSHOT_PREFIX) => {
+                time_travel::At::Commit(self.vcs()?.resolve_revision(rev)?.to_string())
+            }
+            other => other.clone(),
+        };
+        time_travel::graph_at(&self.vcs()?.graph_history, &at)
+    }
+
+    /// 按配置运行图验证规则
+    pub fn validate(&self) -> Result<ValidationReport> {
+        let config = RepoConfig::load(&self.root)?;
+        self.validator.run(self.storage()?.graph(), &config.validate)
+    }
+
+    /// 获取验证器（可注册自定义规则）
+    pub fn validator_mut(&mut self) -> &mut Validator {
+        &mut self.validator
+    }
+
+    /// 按配置检查 Markdown 源文件（`memexia lint`）
+    ///
+    /// # Arguments
+    ///
+    /// * `paths` - 要检查的文件或目录；为空时检查笔记目录下所有 Markdown 笔记
+    ///
+    /// # Returns
+    ///
+    /// 按文件和行号排序的诊断
+    pub fn lint(&self, paths: &[PathBuf]) -> Result<lint::LintReport> {
+        let config = RepoConfig::load(&self.root)?;
+        let mut indexer = Indexer::new(self.storage()?.clone());
+        indexer.set_note_roots(config.paths.walk_dirs());
+
+        let is_markdown = |path: &Path| path.extension().and_then(|e| e.to_str()) == Some("md");
+        let mut files = Vec::new();
+        let roots = if paths.is_empty() { vec![self.root.clone()] } else { paths.to_vec() };
+        for root in roots {
+            if root.is_file() {
+                // 显式指定的文件总是检查
+                files.push(root);
+                continue;
+            }
+            if !root.exists() {
+                return Err(anyhow::anyhow!("File not found: {}", paths::display(&root)).into());
+            }
+            let entries = walkdir::WalkDir::new(&root).into_iter().filter_map(|e| e.ok());
+            for entry in entries {
+                let path = entry.path();
+                // 笔记目录只限制默认的全仓库检查
+                if path.is_file()
+                    && is_markdown(path)
+                    && indexer.config().is_allowed(path)
+                    && (!paths.is_empty() || indexer.in_note_roots(path))
+                {
+                    files.push(path.to_path_buf());
+                }
+            }
+        }
+
+        let linter = lint::Linter::with_builtin_rules();
+        let mut lint_config = config.lint.clone();
+        lint_config.default_relation = config.links.default_relation.clone();
+        let mut report = lint::LintReport::default();
+        for file in &files {
+            let content = fs::read_to_string(file).with_context(|| format!("Failed to read {}", paths::display(file)))?;
+            let absolute = fs::canonicalize(file).unwrap_or_else(|_| file.clone());
+            let relative = paths::normalize_input(&absolute, &self.root);
+            report.diagnostics.extend(linter.lint(relative.as_str(), &content, &lint_config));
+        }
+        report.files = files.len();
+        report.diagnostics.sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+        Ok(report)
+    }
+
+    /// 计算仓库统计（笔记数、字数、标签数和按月增长），不含已归档的笔记
+    pub fn stats(&self) -> Result<stats::RepoStats> {
+        self.stats_filtered(false)
+    }
+
+    /// 计算仓库统计，可选包含已归档的笔记
+    pub fn stats_filtered(&self, include_archived: bool) -> Result<stats::RepoStats> {
+        let mut nodes = self.storage()?.graph().get_all_nodes()?;
+        nodes.retain(|n| include_archived || !n.is_archived());
+        let commits = if self.vcs()?.head_info()?.is_some() {
+            self.vcs()?.log(usize::MAX)?
+        } else {
+            Vec::new()
+        };
+        Ok(stats::RepoStats::compute(&nodes, &commits))
+    }
+
+    /// 按提交顺序统计图快照的规模变化（`memexia stats --history`）
+    ///
+    /// 见 [`snapshot_stats`](crate::vcs::snapshot_stats)
+    pub fn stats_history(&self) -> Result<Vec<crate::vcs::snapshot_stats::StatsPoint>> {
+        self.vcs()?.graph_history.stats_history()
+    }
+
+    /// 查找疑似重复的笔记对
+    ///
+    /// # Arguments
+    ///
+    /// * `content_threshold` - 正文相似度阈值；为 None 时只比较标题
+    pub fn find_duplicates(&self, content_threshold: Option<f64>) -> Result<Vec<duplicates::DuplicatePair>> {
+        duplicates::find_duplicates(self.storage()?.graph(), content_threshold)
+    }
+
+    /// 汇总矛盾簇（`memexia report contradictions`）
+    ///
+    /// # Arguments
+    ///
+    /// * `min_strength` - 矛盾边的最低强度
+    pub fn find_contradictions(&self, min_strength: f64) -> Result<Vec<contradictions::ContradictionCluster>> {
+        contradictions::find_contradictions(self.storage()?.graph(), min_strength)
+    }
+
+    /// 将已索引的笔记发布为静态 HTML 站点
+    ///
+    /// # Arguments
+    ///
+    /// * `out` - 输出目录（位于仓库内时不会被当作笔记扫描）
+    ///
+    /// # Returns
+    ///
+    /// 生成的页数、标签页数和被排除的笔记数
+    pub fn publish(&self, out: &Path) -> Result<publish::PublishSummary> {
+        self.publish_filtered(out, false)
+    }
+
+    /// 发布静态站点，可选包含已归档的笔记（[`Repository::publish`] 默认排除）
+    pub fn publish_filtered(&self, out: &Path, include_archived: bool) -> Result<publish::PublishSummary> {
+        let config = RepoConfig::load(&self.root)?;
+        let out = Self::output_dir(out)?;
+        let indexer = self.note_indexer(&config)?;
+
+        let notes: Vec<publish::SourceNote> = self
+            .indexed_notes(&indexer, &out)?
+            .into_iter()
+            .filter(|note| include_archived || !note.node.is_archived())
+            .map(|note| {
+                let (_, body) = parser::frontmatter::extract_frontmatter(&note.content);
+                publish::SourceNote { path: note.path, node: note.node, body }
+            })
+            .collect();
+
+        publish::publish(self.storage()?.graph(), &notes, &config.publish, &|target| indexer.get_target_id(target), &out)
+    }
+
+    /// 节点的出链（`memexia serve` 的 `GET /api/nodes/{id}/links`），含首次提交等元数据
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - 节点 ID，也可以是 wiki 链接目标（如 `notes/a.md`）
+    pub fn node_links(&self, id: &str) -> Result<Vec<crate::storage::Edge>> {
+        let config = RepoConfig::load(&self.root)?;
+        let id = self.note_indexer(&config)?.get_target_id(id);
+        let graph = self.storage()?.graph();
+        graph.require_node(&id)?;
+        graph.get_edges_by_source(&id)
+    }
+
+    /// 把笔记渲染为 HTML（`memexia serve` 的 `GET /api/nodes/{id}/html`）
+    ///
+    /// 读取节点对应的源文件并去掉 frontmatter；wiki 链接按索引器的规则解析，
+    /// 目标没有对应的已索引笔记时渲染为断开的链接
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - 节点 ID，也可以是 wiki 链接目标（如 `notes/a.md`）
+    pub fn render_node_html(&self, id: &str) -> Result<render::RenderedNote> {
+        let config = RepoConfig::load(&self.root)?;
+        let indexer = self.note_indexer(&config)?;
+        let graph = self.storage()?.graph();
+
+        let id = indexer.get_target_id(id);
+        let node = graph.require_node(&id)?;
+        let path = node
+            .content_hash()
+            .and_then(|_| self.note_source_path(&indexer, &id))
+            .ok_or_else(|| MemexiaError::Validation(format!("Node '{}' is not backed by a note file", id)))?;
+        let content = if encrypted::is_encrypted(&path) {
+            match &config.index.decrypt_cmd {
+                Some(cmd) => encrypted::decrypt(cmd, &path)?,
+                None => {
+                    return Err(MemexiaError::Validation(format!(
+                        "Node '{}' is encrypted and no index.decrypt_cmd is configured",
+                        id
+                    )))
+                }
+            }
+        } else {
+            fs::read_to_string(&path)?
+        };
+        let (_, body) = parser::frontmatter::extract_frontmatter(&content);
+
+        let resolve = |target: &str| {
+            let target_id = indexer.get_target_id(target);
+            match graph.get_node(&target_id) {
+                Ok(Some(target)) if target.content_hash().is_some() => Some(target_id),
+                _ => None,
+            }
+        };
+        Ok(render::render_markdown(&body, &resolve, &config.render))
+    }
+
+    /// 节点对应的笔记源文件；文件节点直接由 ID 得到路径，其他 ID（如 Zettel ID）遍历笔记目录查找
+    fn note_source_path(&self, indexer: &Indexer, id: &str) -> Option<PathBuf> {
+        if id.starts_with("urn:memexia:file:") {
+            let path = self.root.join(ids::display_file_id(id));
+            return path.is_file().then_some(path);
+        }
+        walkdir::WalkDir::new(&self.root)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .map(|e| e.into_path())
+            .find(|path| {
+                path.is_file()
+                    && indexer.config().is_allowed(path)
+                    && indexer.in_note_roots(path)
+                    && indexer.path_to_id(path) == id
+            })
+    }
+
+    /// 导出纯文本语料（`memexia export --format corpus`）
+    ///
+    /// # Arguments
+    ///
+    /// * `out` - 输出目录
+    /// * `chunk` - 分块选项；`None` 时每篇笔记一个文件
+    pub fn export_corpus(&self, out: &Path, chunk: Option<corpus::ChunkOptions>) -> Result<corpus::CorpusSummary> {
+        let config = RepoConfig::load(&self.root)?;
+        let out = Self::output_dir(out)?;
+        let notes = self.indexed_notes(&self.note_indexer(&config)?, &out)?;
+        corpus::export_corpus(&notes, chunk, &out)
+    }
+
+    /// 输出目录的绝对路径（相对路径基于当前目录）
+    fn output_dir(out: &Path) -> Result<PathBuf> {
+        Ok(if out.is_absolute() { out.to_path_buf() } else { std::env::current_dir()?.join(out) })
+    }
+
+    /// 按仓库配置创建用于遍历笔记的索引器
+    fn note_indexer(&self, config: &RepoConfig) -> Result<Indexer> {
+        let mut indexer = Indexer::new(self.storage()?.clone());
+        indexer.set_zettel_ids(config.ids.zettel);
+        indexer.set_extensions(config.index.extensions.clone());
+        indexer.set_note_roots(config.paths.walk_dirs());
+        Ok(indexer)
+    }
+
+    /// 已索引的 Markdown 笔记（按路径排序），跳过输出目录 `skip`
+    fn indexed_notes(&self, indexer: &Indexer, skip: &Path) -> Result<Vec<corpus::CorpusNote>> {
+        let graph = self.storage()?.graph();
+        let skip = fs::canonicalize(skip).unwrap_or_else(|_| skip.to_path_buf());
+
+        let mut notes = Vec::new();
+        let entries = walkdir::WalkDir::new(&self.root)
+            .into_iter()
+            .filter_entry(|e| e.path() != skip)
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_file());
+        for entry in entries {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("md")
+                || !indexer.config().is_allowed(path)
+                || !indexer.in_note_roots(path)
+            {
+                continue;
+            }
+
+            // 只包含已索引的笔记
+            let Some(node) = graph.get_node(&indexer.path_to_id(path))? else {
+                continue;
+            };
+            let rel_path = pathdiff::diff_paths(path, &self.root)
+                .unwrap_or_else(|| path.to_path_buf())
+                .to_string_lossy()
+                .replace('\\', "/");
+            notes.push(corpus::CorpusNote { path: rel_path, node, content: fs::read_to_string(path)? });
+        }
+        notes.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(notes)
+    }
+
+    /// 批量改名或删除标签（`memexia tag`）
+    ///
+    /// # Arguments
+    ///
+    /// * `op` - 改标签操作
+    /// * `write_files` - 是否同时改写笔记源文件并暂存；dry-run 时只统计不写入
+    ///
+    /// # Returns
+    ///
+    /// 受影响的节点和改写的文件
+    pub fn retag(&self, op: &retag::TagOp, write_files: bool) -> Result<retag::RetagSummary> {
+        self.ensure_writable()?;
+        let nodes = retag::retag_graph(self.storage()?.graph(), op)?;
+        let mut summary = retag::RetagSummary { nodes, files: Vec::new() };
+        if !write_files {
+            return Ok(summary);
+        }
+
+        let config = RepoConfig::load(&self.root)?;
+        let mut indexer = Indexer::new(self.storage()?.clone());
+        indexer.set_extensions(config.index.extensions.clone());
+        indexer.set_note_roots(config.paths.walk_dirs());
+
+        let mut rewritten = Vec::new();
+        let entries = walkdir::WalkDir::new(&self.root)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_file());
+        for entry in entries {
+            let path = entry.path();
+            // 加密笔记的正文不可直接改写
+            if !indexer.is_indexable(path) || encrypted::is_encrypted(path) || !indexer.in_note_roots(path) {
+                continue;
+            }
+            let Ok(content) = fs::read_to_string(path) else {
+                continue;
+            };
+            let Some(updated) = retag::rewrite_tags(&content, op) else {
+                continue;
+            };
+            if !self.is_dry_run() {
+                fs::write(path, updated)?;
+            }
+            rewritten.push(path.to_path_buf());
+        }
+        rewritten.sort();
+
+        if !rewritten.is_empty() && !self.is_dry_run() {
+            self.add(&rewritten)?;
+            self.vcs()?.stage(&rewritten)?;
+        }
+        summary.files = rewritten
+            .iter()
+            .map(|path| PathBuf::from(paths::normalize_input(path, &self.root).into_string()))
+            .collect();
+        Ok(summary)
+    }
+
+    /// 把标签升级为概念笔记（`memexia tag promote`）
+    ///
+    /// 以标签为标题新建笔记，从每个带该标签的节点向新节点添加一条边并记录推导；
+    /// 边和去标签在同一个图事务中完成
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - 要升级的标签
+    /// * `node_type` - 新笔记的类型，为 None 时使用目录默认值
+    /// * `relation` - 从带标签节点指向新节点的关系
+    /// * `consume` - 是否从这些节点上去掉该标签
+    /// * `write_files` - 去掉标签时是否同时改写笔记源文件并暂存
+    pub fn promote_tag(
+        &self,
+        tag: &str,
+        node_type: Option<NodeType>,
+        relation: RelationType,
+        consume: bool,
+        write_files: bool,
+    ) -> Result<retag::TagPromotion> {
+        self.ensure_writable()?;
+        retag::validate_tag(tag)?;
+        let graph = self.storage()?.graph();
+        let tagged: Vec<Node> = graph
+            .get_all_nodes()?
+            .into_iter()
+            .filter(|node| !node.is_deleted() && node.tags.iter().any(|t| t == tag))
+            .collect();
+        if tagged.is_empty() {
+            return Err(MemexiaError::Validation(format!("No notes carry tag: {}", tag)));
+        }
+
+        let options = note::NoteOptions { node_type, ..note::NoteOptions::new(tag) };
+        let (path, node_id) = self.create_note(&options)?;
+        let mut tagged: Vec<Node> = tagged.into_iter().filter(|node| node.id != node_id).collect();
+        tagged.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let op = retag::TagOp::remove(tag, false)?;
+        let (edges, untagged) = graph.transaction(|graph| {
+            let (mut edges, mut untagged) = (0, 0);
+            for node in &tagged {
+                graph.add_edge(&Edge::builder(&node.id, &node_id, relation.clone()).build()?)?;
+                edges += 1;
+                if consume {
+                    if let Some(tags) = op.apply_all(&node.tags) {
+                        let mut node = node.clone();
+                        node.tags = tags;
+                        graph.update_node(&node)?;
+                        untagged += 1;
+                    }
+                }
+            }
+            Ok((edges, untagged))
+        })?;
+
+        let head = self.vcs()?.head_info()?.map(|commit| commit.oid).unwrap_or_default();
+        let history = &self.vcs()?.graph_history;
+        for node in &tagged {
+            history.record_derivation(&node_id, &node.id, &head)?;
+        }
+
+        let files = if consume && write_files { self.retag(&op, true)?.files } else { Vec::new() };
+        Ok(retag::TagPromotion {
+            path: PathBuf::from(paths::normalize_input(&path, &self.root).into_string()),
+            node_id,
+            derivations: tagged.len(),
+            tagged: tagged.into_iter().map(|node| node.id).collect(),
+            edges,
+            untagged,
+            files,
+        })
+    }
+
+    /// 把引用旧标题的 Wiki 链接改写为笔记的当前标题
+    ///
+    /// # Arguments
+    ///
+    /// * `write_files` - 是否写回文件并暂存；为 false 时只报告将要改写的链接
+    ///
+    /// # Returns
+    ///
+    /// 按路径排序的改写列表
+    pub fn refresh_links(&self, write_files: bool) -> Result<Vec<link_refresh::FileRefresh>> {
+        if write_files {
+            self.ensure_writable()?;
+        }
+        let index = link_refresh::TitleIndex::build(self.storage()?.graph())?;
+        if index.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let config = RepoConfig::load(&self.root)?;
+        let indexer = self.note_indexer(&config)?;
+        let resolve = |target: &str| indexer.get_target_id(target);
+
+        let mut refreshed = Vec::new();
+        let mut rewritten = Vec::new();
+        let entries = walkdir::WalkDir::new(&self.root)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_file());
+        for entry in entries {
+            let path = entry.path();
+            // 加密笔记的正文不可直接改写
+            if !indexer.is_indexable(path) || encrypted::is_encrypted(path) || !indexer.in_note_roots(path) {
+                continue;
+            }
+            let Ok(content) = fs::read_to_string(path) else {
+                continue;
+            };
+            let Some((updated, changes)) = link_refresh::rewrite_links(&content, &index, &resolve) else {
+                continue;
+            };
+            if write_files && !self.is_dry_run() {
+                fs::write(path, updated)?;
+                rewritten.push(path.to_path_buf());
+            }
+            refreshed.push(link_refresh::FileRefresh {
+                path: PathBuf::from(paths::normalize_input(path, &self.root).into_string()),
+                changes,
+            });
+        }
+        refreshed.sort_by(|a, b| a.path.cmp(&b.path));
+
+        if !rewritten.is_empty() {
+            self.add(&rewritten)?;
+            self.vcs()?.stage(&rewritten)?;
+        }
+        Ok(refreshed)
+    }
+
+    /// 立即创建自动快照（图自上次自动快照后有变化时）
+    ///
+    /// # Returns
+    ///
+    /// 新记录的 `auto:<时间>` 关联；图未变化时返回 None
+    pub fn snapshot_now(&self) -> Result<Option<CommitLink>> {
+        self.ensure_writable()?;
+        let keep = RepoConfig::load(&self.root)?.watch.auto_snapshot_keep;
+        self.vcs()?.graph_history.auto_snapshot(self.storage()?, keep)
+    }
+
+    /// 校验图快照（`memexia snapshot verify`、`memexia doctor`），见 [`snapshot_verify`](crate::vcs::snapshot_verify)
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - 只校验该快照（完整哈希或唯一前缀）；None 时校验全部快照并检查提交关联
+    /// * `repair_meta` - 重新生成缺失或过期的 `.meta`（dry-run 时只报告）
+    pub fn verify_snapshots(&self, hash: Option<&str>, repair_meta: bool) -> Result<crate::vcs::snapshot_verify::VerifyReport> {
+        let repair_meta = repair_meta && !self.is_dry_run();
+        if repair_meta {
+            self.ensure_writable()?;
+        }
+        self.vcs()?.graph_history.verify_snapshots(hash, repair_meta)
+    }
+
+    /// 以节点为根构建推导树（`memexia derivation tree`），见 [`derivation_tree`]
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - 节点 ID（`urn:memexia:` 开头）或笔记路径
+    /// * `direction` - 展开方向
+    /// * `depth` - 最大展开层数（None 时使用 `history.traversal.max_depth`）
+    ///
+    /// 树中节点总数受 `history.traversal.max_nodes` 限制，触及限制和发现的环见返回的遍历情况
+    pub fn derivation_tree(
+        &self,
+        node: &str,
+        direction: derivation_tree::Direction,
+        depth: Option<usize>,
+    ) -> Result<crate::vcs::Traversal<derivation_tree::TreeNode>> {
+        let node_id = if node.starts_with("urn:memexia:") {
+            node.to_string()
+        } else {
+            self.node_id_for_path(Path::new(node))?
+        };
+        let config = RepoConfig::load(&self.root)?;
+        derivation_tree::build(
+            &self.vcs()?.graph_history,
+            self.storage()?.graph(),
+            &node_id,
+            direction,
+            depth,
+            &config.history.traversal,
+        )
+    }
+
+    /// 把节点历史、推导记录和提交关联导出为 tar 包（`memexia history export`）
+    ///
+    /// 见 [`history_bundle`](crate::vcs::history_bundle)
+    pub fn export_history(&self, file: &Path) -> Result<crate::vcs::history_bundle::BundleManifest> {
+        self.vcs()?.export_history(file)
+    }
+
+    /// 导入历史包（`memexia history import`）
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - [`export_history`](Self::export_history) 导出的 tar 包
+    /// * `merge` - 与已有历史合并；为 false 且本地已有历史时返回 `Validation` 错误
+    pub fn import_history(&self, file: &Path, merge: bool) -> Result<crate::vcs::history_bundle::ImportReport> {
+        self.ensure_writable()?;
+        self.vcs()?.import_history(file, merge)
+    }
+
+    /// 把节点移入回收站（`DELETE /api/daemon/nodes/{id}`），笔记文件保持不变
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - 节点 ID、笔记文件路径（相对仓库根目录或绝对路径）或 wiki 链接目标
+    ///
+    /// # Returns
+    ///
+    /// 被删除的节点 ID；节点已在回收站中时返回错误
+    pub fn trash_node(&self, target: &str) -> Result<String> {
+        self.ensure_writable()?;
+        let config = RepoConfig::load(&self.root)?;
+        let indexer = self.note_indexer(&config)?;
+        let graph = self.storage()?.graph();
+
+        let path = self.root.join(target);
+        let id = if path.is_file() { indexer.path_to_id(&path) } else { indexer.get_target_id(target) };
+        graph.require_node(&id)?;
+        if !trash::soft_delete(graph, &id, chrono::Utc::now())? {
+            return Err(MemexiaError::Validation(format!("Node '{}' is already in the trash", id)));
+        }
+        Ok(id)
+    }
+
+    /// 从回收站恢复文件对应的节点
+    ///
+    /// 清除软删除标记；文件已被删除且 HEAD 中有该文件时同时从 Git 检出
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 文件路径（相对仓库根目录或绝对路径）
+    pub fn restore_node(&self, path: &Path) -> Result<trash::RestoreOutcome> {
+        self.ensure_writable()?;
+        let path = if path.is_absolute() { path.to_path_buf() } else { self.root.join(path) };
+        let config = RepoConfig::load(&self.root)?;
+        let mut indexer = Indexer::new(self.storage()?.clone());
+        indexer.set_zettel_ids(config.ids.zettel);
+
+        let node_id = indexer.path_to_id(&path);
+        trash::restore(self.storage()?.graph(), &node_id)?;
+
+        let relative = paths::normalize_input(&path, &self.root);
+        let file_restored = self.vcs()?.restore_file(Path::new(relative.as_str()))?;
+        Ok(trash::RestoreOutcome { node_id, file_restored })
+    }
+
+    /// 修改文件对应的节点，并把改动写回笔记的 frontmatter（`memexia node set`）
+    ///
+    /// 写回规则见 [`writer`]；文件在上次索引后被修改过时以文件为准：
+    /// 放弃这次修改并按文件重新索引，返回 [`writer::WriteBack::Conflict`]
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 笔记文件路径（相对仓库根目录或绝对路径）
+    /// * `edit` - 修改内容
+    ///
+    /// # Returns
+    ///
+    /// 节点 ID 和写回结果；节点没有对应的笔记文件（如占位节点）时只修改图，写回结果为 None
+    pub fn edit_node(&self, path: &Path, edit: &writer::NodeEdit) -> Result<(String, Option<writer::WriteBack>)> {
+        self.ensure_writable()?;
+        let path = if path.is_absolute() { path.to_path_buf() } else { self.root.join(path) };
+        let config = RepoConfig::load(&self.root)?;
+        let indexer = self.note_indexer(&config)?;
+        let graph = self.storage()?.graph();
+
+        let mut node = graph.require_node(&indexer.path_to_id(&path))?;
+        if encrypted::is_encrypted(&path) {
+            return Err(MemexiaError::Validation(format!(
+                "Node '{}' is encrypted; edit the note file instead",
+                node.id
+            )));
+        }
+        edit.apply(&mut node)?;
+
+        if node.content_hash().is_none() || !path.is_file() || self.is_dry_run() {
+            graph.update_node(&node)?;
+            return Ok((node.id, None));
+        }
+        let outcome = writer::update_frontmatter(&path, &mut node)?;
+        match outcome {
+            writer::WriteBack::Written => {
+                graph.update_node(&node)?;
+                self.add(std::slice::from_ref(&path))?;
+                self.vcs()?.stage(std::slice::from_ref(&path))?;
+            }
+            writer::WriteBack::Unchanged => graph.update_node(&node)?,
+            writer::WriteBack::Conflict => {
+                indexer.index_file(&path)?;
+            }
+        }
+        Ok((node.id, Some(outcome)))
+    }
+
+    /// 修改节点的标题、类型和标签（`memexia node set`、`PATCH /api/daemon/nodes/{id}`）
+    ///
+    /// 没有笔记文件的节点（生成节点、占位节点、URL 资源节点）只修改图，保留创建时间；
+    /// 文件节点经 [`edit_node`](Self::edit_node) 写回 frontmatter。
+    /// 标题决定文件节点在编辑器中的显示，修改文件节点的标题要求 `write_file`
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - 节点 ID、笔记文件路径（相对仓库根目录或绝对路径）或 wiki 链接目标
+    /// * `edit` - 修改内容
+    /// * `write_file` - 是否允许修改文件节点的标题
+    pub fn set_node(&self, target: &str, edit: &writer::NodeEdit, write_file: bool) -> Result<writer::NodeSet> {
+        self.ensure_writable()?;
+        let config = RepoConfig::load(&self.root)?;
+        let indexer = self.note_indexer(&config)?;
+        let graph = self.storage()?.graph();
+
+        let path = self.root.join(target);
+        let id = if path.is_file() { indexer.path_to_id(&path) } else { indexer.get_target_id(target) };
+        let before = graph.require_node(&id)?;
+        let source = before.content_hash().and_then(|_| self.note_source_path(&indexer, &id));
+
+        let Some(source) = source else {
+            let mut node = before.clone();
+            edit.apply(&mut node)?;
+            graph.update_node(&node)?;
+            let changes = writer::diff(&before, &node);
+            return Ok(writer::NodeSet { id, changes, write_back: None });
+        };
+        if edit.title.is_some() && !write_file {
+            return Err(MemexiaError::Validation(format!(
+                "Node '{}' is backed by {}; changing its title requires --write-file",
+                id,
+                paths::normalize_input(&source, &self.root).as_str()
+            )));
+        }
+        let (id, write_back) = self.edit_node(&source, edit)?;
+        let after = graph.require_node(&id)?;
+        Ok(writer::NodeSet {
+            changes: writer::diff(&before, &after),
+            id,
+            write_back,
+        })
+    }
+
+    /// 回收站中的节点
+    pub fn deleted_nodes(&self) -> Result<Vec<Node>> {
+        trash::deleted_nodes(self.storage()?.graph())
+    }
+
+    /// 永久删除回收站中的节点
+    ///
+    /// # Arguments
+    ///
+    /// * `older_than` - 只删除至少在该时长之前被删除的节点；None 表示全部
+    ///
+    /// # Returns
+    ///
+    /// 被永久删除的节点 ID
+    pub fn purge_deleted(&self, older_than: Option<chrono::Duration>) -> Result<Vec<String>> {
+        self.ensure_writable()?;
+        let graph = self.storage()?.graph();
+        let now = chrono::Utc::now();
+
+        let mut journal = undo::UndoJournal::new("gc --purge-deleted");
+        let candidates = trash::purge_candidates(graph, older_than, now)?;
+        let edges = graph.list_edges_filtered(true)?;
+        for node in &candidates {
+            journal.capture_node(graph, &node.id)?;
+            for edge in edges.iter().filter(|e| e.from == node.id) {
+                journal.capture_edge(graph, &edge.id)?;
+            }
+        }
+
+        let purged = trash::purge_deleted(graph, older_than, now)?;
+        self.record_undo(&journal)?;
+        Ok(purged)
+    }
+
+    /// 保存撤销日志，供 `memexia undo` 使用
+    ///
+    /// dry-run 或日志为空时不写入
+    pub fn record_undo(&self, journal: &undo::UndoJournal) -> Result<()> {
+        self.ensure_writable()?;
+        if self.is_dry_run() || journal.is_empty() {
+            return Ok(());
+        }
+        journal.save(&self.root)
+    }
+
+    /// 清除撤销日志（用于无法撤销的操作，避免之后误用过期的日志）
+    ///
+    /// # Returns
+    ///
+    /// 是否清除了已有的日志
+    pub fn discard_undo(&self) -> Result<bool> {
+        self.ensure_writable()?;
+        if self.is_dry_run() {
+            return Ok(false);
+        }
+        undo::clear(&self.root)
+    }
+
+    /// 查找未编码的旧版文件节点 ID（`memexia doctor`）
+    pub fn legacy_ids(&self) -> Result<Vec<doctor::LegacyId>> {
+        doctor::find_legacy_ids(self.storage()?.graph())
+    }
+
+    /// 把旧版文件节点 ID 改写为规范的编码形式（`memexia doctor --fix`）
+    pub fn migrate_legacy_ids(&self, legacy: &[doctor::LegacyId]) -> Result<doctor::MigrationSummary> {
+        self.ensure_writable()?;
+        doctor::migrate_legacy_ids(self.storage()?.graph(), legacy)
+    }
+
+    /// 汇总仓库概览和健康分（`memexia dashboard`），见 [`dashboard`]
+    pub fn dashboard(&self) -> Result<dashboard::Dashboard> {
+        let config = RepoConfig::load(&self.root)?;
+        let graph = self.storage()?.graph();
+        let nodes = graph.get_all_nodes()?;
+        let edges = graph.get_all_edges()?;
+        let dangling = graph.find_dangling_edges()?.len();
+        let last_commit = self.vcs()?.head_info()?;
+        Ok(dashboard::Dashboard::compute(
+            &nodes,
+            &edges,
+            dangling,
+            last_commit.as_ref(),
+            chrono::Utc::now(),
+            &config.dashboard,
+        ))
+    }
+
+    /// 按提交生成变更订阅源（`memexia feed`），见 [`feed`]
+    ///
+    /// 每个提交与上一个提交的图快照比较；没有图快照或快照已被清理的提交只有标题，没有正文内容
+    ///
+    /// # Arguments
+    ///
+    /// * `since` - 只包含该时间及之后的提交
+    pub fn feed(&self, since: Option<chrono::DateTime<chrono::Utc>>) -> Result<feed::Feed> {
+        let config = RepoConfig::load(&self.root)?;
+        let history = &self.vcs()?.graph_history;
+        let commits = self.log(usize::MAX)?;
+        let snapshot = |commit: Option<&CommitInfo>| {
+            commit.and_then(|c| c.graph_hash.as_deref()).and_then(|hash| history.get_snapshot(hash).ok())
+        };
+
+        let mut entries = Vec::new();
+        let mut current = snapshot(commits.first());
+        for (i, commit) in commits.iter().enumerate() {
+            let updated = commit.time().unwrap_or_default();
+            if since.is_some_and(|since| updated < since) {
+                break;
+            }
+            let parent = snapshot(commits.get(i + 1));
+            let (delta, titles) = match &current {
+                Some(new) => (GraphDelta::between(parent.as_ref(), new).summarize(), new.titles()),
+                None => Default::default(),
+            };
+            entries.push(feed::FeedEntry::new(commit, updated, &delta, &titles, &config.publish));
+            current = parent;
+        }
+
+        let name = self.root.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        Ok(feed::Feed {
+            title: format!("{} changes", name).trim().to_string(),
+            link: config.publish.site_url(),
+            entries,
+        })
+    }
+
+    /// 端点节点不存在的边（`memexia doctor`）
+    pub fn dangling_links(&self) -> Result<Vec<Edge>> {
+        self.storage()?.graph().find_dangling_edges()
+    }
+
+    /// 修复端点节点不存在的边（`memexia doctor --fix-dangling`）
+    pub fn repair_dangling_links(&self, strategy: DanglingStrategy) -> Result<DanglingRepair> {
+        if strategy != DanglingStrategy::ReportOnly {
+            self.ensure_writable()?;
+        }
+        self.storage()?.graph().repair_dangling(strategy)
+    }
+
+    /// 互为逆关系的冗余边对（`memexia doctor`）
+    pub fn redundant_inverse_links(&self) -> Result<Vec<(Edge, Edge)>> {
+        doctor::find_redundant_inverses(self.storage()?.graph())
+    }
+
+    /// 标题索引与节点标题不一致的节点 ID（`memexia doctor`）
+    pub fn title_index_mismatches(&self) -> Result<Vec<String>> {
+        self.storage()?.graph().title_index_mismatches()
+    }
+
+    /// 按节点标题重建标题索引（`memexia doctor --fix`），返回修正的节点数
+    pub fn rebuild_title_index(&self) -> Result<usize> {
+        self.ensure_writable()?;
+        self.storage()?.graph().rebuild_title_index()
+    }
+
+    /// 为已有的边追加注释，并记录撤销日志
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - 源文件路径
+    /// * `target` - 目标文件路径
+    /// * `text` - 注释正文
+    ///
+    /// # Returns
+    ///
+    /// 追加注释后的边；两个文件之间没有边时返回错误
+    pub fn annotate_link(&self, source: &Path, target: &Path, text: &str) -> Result<crate::storage::Edge> {
+        self.ensure_writable()?;
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(MemexiaError::Validation("Annotation text cannot be empty".to_string()));
+        }
+        let mut edge = self.link_between(source, target)?;
+
+        // 注释与已有注释合并写入，撤销时先删除整条边再写回原状态
+        let mut journal = undo::UndoJournal::new("link annotate");
+        journal.capture_edge(self.storage()?.graph(), &edge.id)?;
+        journal.remove_edges.push(edge.id.clone());
+
+        edge.annotate(text, chrono::Utc::now());
+        self.storage()?.graph().add_edge(&edge)?;
+        self.record_undo(&journal)?;
+        Ok(edge)
+    }
+
+    /// 修改两个文件之间链接的强度、描述或关系（`memexia link set-strength` 等）
+    ///
+    /// 见 [`edit_edge`](Self::edit_edge)
+    pub fn edit_link(&self, source: &Path, target: &Path, edit: &EdgeEdit) -> Result<Edge> {
+        let edge = self.link_between(source, target)?;
+        self.edit_edge(&edge.id, edit)
+    }
+
+    /// 按 ID 原地修改边（`PATCH /api/edges/{id}`）
+    ///
+    /// 注释、首次提交和创建时间保持不变，修改前的值追加到边的修改记录（[`Edge::history`]）；
+    /// 修改只作用于图谱，不改写笔记中的 wiki 链接
+    ///
+    /// # Returns
+    ///
+    /// 修改后的边；边不存在、没有任何修改或强度不在 0.0 - 1.0 范围内时返回错误
+    pub fn edit_edge(&self, id: &str, edit: &EdgeEdit) -> Result<Edge> {
+        self.ensure_writable()?;
+        if edit.is_empty() {
+            return Err(MemexiaError::Validation("Nothing to change".to_string()));
+        }
+        let graph = self.storage()?.graph();
+        let mut edge = graph.get_edge(id)?.ok_or_else(|| MemexiaError::EdgeNotFound { id: id.to_string() })?;
+        let before = edge.relation.clone();
+        edit.apply(&mut edge, chrono::Utc::now())?;
+
+        // 撤销时先删除整条边再写回原状态
+        let mut journal = undo::UndoJournal::new("link set");
+        journal.capture_edge(graph, &edge.id)?;
+        journal.remove_edges.push(edge.id.clone());
+
+        graph.transaction(|graph| {
+            // 关系是独立的三元组，改变关系时先删除旧的
+            if edge.relation != before {
+                graph.delete_edge(&edge.id)?;
+            }
+            graph.add_edge(&edge)
+        })?;
+        self.record_undo(&journal)?;
+        Ok(edge)
+    }
+
+    /// 两个文件之间的边
+    ///
+    /// # Returns
+    ///
+    /// 边（含注释）；不存在时返回错误
+    pub fn link_between(&self, source: &Path, target: &Path) -> Result<crate::storage::Edge> {
+        self.ensure_writable()?;
+        let edge_id = crate::storage::Edge::make_id(&self.node_id_for_path(source)?, &self.node_id_for_path(target)?);
+        self.storage()?.graph().get_edge(&edge_id)?.ok_or_else(|| {
+            MemexiaError::Validation(format!("No link from {} to {}", source.display(), target.display()))
+        })
+    }
+
+    /// 撤销最近一次记录了撤销日志的操作
+    ///
+    /// # Returns
+    ///
+    /// 被撤销的日志；没有可撤销的操作时返回错误
+    pub fn undo(&self) -> Result<undo::UndoJournal> {
+        self.ensure_writable()?;
+        undo::undo_last(self.storage()?.graph(), &self.root)
+    }
+
+    /// 随机选择一篇满足过滤条件的笔记
+    pub fn random_note(&self, filter: &review::NoteFilter) -> Result<Option<review::ReviewItem>> {
+        review::random_note(self.storage()?.graph(), filter, &mut rand::rng())
+    }
+
+    /// 获取回顾队列（最久未修改的笔记在前，跳过 `interval_days` 天内回顾过的笔记）
+    pub fn review_queue(&self, limit: usize, interval_days: i64) -> Result<Vec<review::ReviewItem>> {
+        review::review_queue(
+            self.storage()?.graph(),
+            limit,
+            chrono::Duration::days(interval_days),
+            chrono::Utc::now(),
+        )
+    }
+
+    /// 将文件标记为已回顾
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 笔记文件路径（相对当前目录或绝对路径）
+    ///
+    /// # Returns
+    ///
+    /// 更新后的节点
+    pub fn mark_reviewed(&self, path: &Path) -> Result<Node> {
+        self.ensure_writable()?;
+        let node_id = self.node_id_for_path(path)?;
+        review::mark_reviewed(self.storage()?.graph(), &node_id, chrono::Utc::now())
+    }
+
+    /// 归档笔记（`memexia archive`）
+    ///
+    /// 把笔记移到归档目录（`paths.archive`）下的相同相对路径，节点身份、历史和边随之迁移到新 ID，
+    /// 然后加上 `archived` 标签和归档时间并暂存移动
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 笔记文件路径（相对当前目录或绝对路径）
+    pub fn archive(&self, path: &Path) -> Result<archive::ArchiveOutcome> {
+        self.move_note(path, true)
+    }
+
+    /// 取消归档（`memexia unarchive`）：移回原位置并清除归档标记
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 归档目录中的笔记文件路径（相对当前目录或绝对路径）
+    pub fn unarchive(&self, path: &Path) -> Result<archive::ArchiveOutcome> {
+        self.move_note(path, false)
+    }
+
+    fn move_note(&self, path: &Path, archiving: bool) -> Result<archive::ArchiveOutcome> {
+        self.ensure_writable()?;
+        let config = RepoConfig::load(&self.root)?;
+        let source = fs::canonicalize(path).with_context(|| format!("File not found: {}", paths::display(path)))?;
+        let relative = paths::normalize_input(&source, &self.root);
+        if relative.is_external() {
+            return Err(anyhow::anyhow!("File is outside repository: {}", paths::display(&source)).into());
+        }
+        let from = relative.into_string();
+        let dir = config.paths.archive_dir();
+        let to = if archiving {
+            archive::archived_path(&from, &dir)?
+        } else {
+            archive::unarchived_path(&from, &dir)?
+        };
+
+        let graph = self.storage()?.graph();
+        let indexer = self.note_indexer(&config)?;
+        let old_id = indexer.path_to_id(&source);
+        if !graph.node_exists(&old_id)? {
+            return Err(MemexiaError::NodeNotFound { id: old_id });
+        }
+        let target = self.root.join(&to);
+        if target.exists() {
+            return Err(MemexiaError::Validation(format!("Destination already exists: {}", to)));
+        }
+        let new_id = indexer.path_to_id(&target);
+        if self.is_dry_run() {
+            return Ok(archive::ArchiveOutcome { from, to, node_id: new_id, edges: 0 });
+        }
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(&source, &target).with_context(|| format!("Failed to move {} to {}", from, to))?;
+        indexer.index_file(&target)?;
+
+        let mut edges = 0;
+        if new_id != old_id {
+            edges = crate::core::rename::rename_node(graph, &old_id, &new_id)?;
+            self.vcs()?.graph_history.rename_node_history(&old_id, &new_id)?;
+        }
+        if archiving {
+            archive::mark_archived(graph, &new_id, chrono::Utc::now())?;
+        } else {
+            archive::clear_archived(graph, &new_id)?;
+        }
+
+        self.add(std::slice::from_ref(&target))?;
+        self.vcs()?.stage(&[self.root.join(&from), target])?;
+        Ok(archive::ArchiveOutcome { from, to, node_id: new_id, edges })
+    }
+
+    /// 向笔记追加或前置一段文字（`memexia append`），然后重新索引并暂存
+    ///
+    /// 插入规则见 [`capture`]
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 笔记文件路径（相对当前目录或绝对路径）
+    /// * `text` - 要插入的文字
+    /// * `insert` - 插入位置
+    ///
+    /// # Returns
+    ///
+    /// 笔记的节点 ID
+    pub fn append_note(&self, path: &Path, text: &str, insert: &capture::Insert) -> Result<String> {
+        self.ensure_writable()?;
+        let config = RepoConfig::load(&self.root)?;
+        let source = fs::canonicalize(path).with_context(|| format!("File not found: {}", paths::display(path)))?;
+        if paths::normalize_input(&source, &self.root).is_external() {
+            return Err(anyhow::anyhow!("File is outside repository: {}", paths::display(&source)).into());
+        }
+        if encrypted::is_encrypted(&source) {
+            return Err(MemexiaError::Validation(format!(
+                "Cannot append to encrypted note: {}",
+                paths::display(&source)
+            )));
+        }
+
+        let content = fs::read_to_string(&source)?;
+        let updated = capture::insert_text(&content, text, insert)?;
+        let indexer = self.note_indexer(&config)?;
+        if self.is_dry_run() {
+            return Ok(indexer.path_to_id(&source));
+        }
+
+        fs::write(&source, updated)?;
+        indexer.index_file(&source)?;
+        let files = [source];
+        self.add(&files)?;
+        self.vcs()?.stage(&files)?;
+        Ok(indexer.path_to_id(&files[0]))
+    }
+
+    /// 把笔记中的一个 H2/H3 小节拆分为新笔记（`memexia file split`）
+    ///
+    /// 新笔记与原笔记在同一目录，原笔记中的小节替换为指向新笔记的链接（规则见 [`split`]）。
+    /// 小节中的链接改由新节点发出，原节点不再保留只在小节中出现的出边；
+    /// 新节点记录为由原节点推导而来（关联当前 HEAD），两个文件都被暂存
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 笔记文件路径（相对当前目录或绝对路径）
+    /// * `heading` - 小节标题（精确匹配）
+    /// * `node_type` - 新笔记的类型，默认 Concept
+    /// * `embed` - 以 `![[...]]` 嵌入代替 `Contains` 链接
+    pub fn split_note(
+        &self,
+        path: &Path,
+        heading: &str,
+        node_type: Option<NodeType>,
+        embed: bool,
+    ) -> Result<split::SplitOutcome> {
+        self.ensure_writable()?;
+        let config = RepoConfig::load(&self.root)?;
+        let source = fs::canonicalize(path).with_context(|| format!("File not found: {}", paths::display(path)))?;
+        let relative = paths::normalize_input(&source, &self.root);
+        if relative.is_external() {
+            return Err(anyhow::anyhow!("File is outside repository: {}", paths::display(&source)).into());
+        }
+        let from = relative.into_string();
+        if encrypted::is_encrypted(&source) {
+            return Err(MemexiaError::Validation(format!("Cannot split encrypted note: {}", from)));
+        }
+
+        let graph = self.storage()?.graph();
+        let indexer = self.note_indexer(&config)?;
+        let original_id = indexer.path_to_id(&source);
+        graph.require_node(&original_id)?;
+
+        let content = fs::read_to_string(&source)?;
+        let section = split::find_section(&content, heading)?;
+        let dir = from.rsplit_once('/').map(|(dir, _)| format!("{}/", dir)).unwrap_or_default();
+        let to = format!("{}{}.md", dir, note::slugify(&section.title));
+        let target = self.root.join(&to);
+        if target.exists() {
+            return Err(MemexiaError::Validation(format!("Destination already exists: {}", to)));
+        }
+        let node_id = indexer.path_to_id(&target);
+        let outcome = split::SplitOutcome { path: to.clone(), node_id, original_id };
+        if self.is_dry_run() {
+            return Ok(outcome);
+        }
+
+        let split = split::split_content(&content, &section, &to, &node_type.unwrap_or_default(), embed);
+        fs::write(&target, &split.extracted)?;
+        fs::write(&source, &split.original)?;
+
+        // 只在小节中出现的链接改由新节点发出
+        let link_targets = |content: &str, name: &str| -> Vec<String> {
+            parser::parse_file(content, name)
+                .0
+                .wiki_links
+                .iter()
+                .map(|link| indexer.get_target_id(&link.target))
+                .collect()
+        };
+        let remaining = link_targets(&split.original, &from);
+        let moved = link_targets(&split.extracted, &to);
+        for edge in graph.get_edges_by_source(&outcome.original_id)? {
+            if moved.contains(&edge.to) && !remaining.contains(&edge.to) {
+                graph.delete_edge(&edge.id)?;
+                self.storage()?.events().emit(RepoEvent::edge_removed(&edge));
+            }
+        }
+        indexer.index_file(&target)?;
+        indexer.index_file(&source)?;
+
+        let head = self.vcs()?.head_info()?.map(|commit| commit.oid).unwrap_or_default();
+        self.vcs()?
+            .graph_history
+            .record_derivation(&outcome.node_id, &outcome.original_id, &head)?;
+
+        let files = [source, target];
+        self.add(&files)?;
+        self.vcs()?.stage(&files)?;
+        Ok(outcome)
+    }
+
+    /// 展开笔记中的图谱占位符并重新生成已有的生成块（`memexia file render-template`）
+    ///
+    /// 见 [`graph_template`]；dry-run 时只检查不写入
+    ///
+    /// # Returns
+    ///
+    /// 文件内容是否有变化
+    pub fn render_graph_template(&self, path: &Path) -> Result<bool> {
+        self.ensure_writable()?;
+        let config = RepoConfig::load(&self.root)?;
+        let source = fs::canonicalize(path).with_context(|| format!("File not found: {}", paths::display(path)))?;
+        let relative = paths::normalize_input(&source, &self.root);
+        if relative.is_external() {
+            return Err(anyhow::anyhow!("File is outside repository: {}", paths::display(&source)).into());
+        }
+        if encrypted::is_encrypted(&source) {
+            return Err(MemexiaError::Validation(format!(
+                "Cannot render encrypted note: {}",
+                relative.into_string()
+            )));
+        }
+
+        let node_id = self.note_indexer(&config)?.path_to_id(&source);
+        self.render_graph_blocks(&source, &node_id)
+    }
+
+    /// 渲染笔记中的图谱占位符，内容有变化时写回（dry-run 时不写）
+    fn render_graph_blocks(&self, path: &Path, node_id: &str) -> Result<bool> {
+        let content = fs::read_to_string(path)?;
+        let file_name = path.to_string_lossy();
+        let tags = parser::parse_file(&content, &file_name).0.get_all_tags();
+        let rendered = graph_template::render(self.storage()?.graph(), node_id, &tags, &content)?;
+        if rendered == content {
+            return Ok(false);
+        }
+        if !self.is_dry_run() {
+            fs::write(path, rendered)?;
+        }
+        Ok(true)
+    }
+
+    /// 搜索已索引的笔记（`memexia search`）
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - 查询
+    /// * `include_archived` - 是否包含已归档的笔记
+    pub fn search(&self, query: &str, include_archived: bool, lang: Option<&str>) -> Result<Vec<search::SearchHit>> {
+        search::search(self.storage()?.graph(), &self.root, query, include_archived, lang)
+    }
+
+    /// 在两个文件对应的节点之间手动创建链接（`memexia link create`）
+    ///
+    /// 节点不存在时创建 Resource 类型的节点；操作记入撤销日志
+    pub fn create_link(&self, source: &Path, target: &Path, relation: RelationType) -> Result<Edge> {
+        self.ensure_writable()?;
+        let graph = self.storage()?.graph();
+        let source_id = self.node_id_for_path(source)?;
+        let target_id = self.node_id_for_path(target)?;
+
+        let edge = Edge::builder(&source_id, &target_id, relation).build()?;
+        let mut journal = undo::UndoJournal::new("link create");
+        journal.capture_node(graph, &source_id)?;
+        journal.capture_node(graph, &target_id)?;
+        journal.capture_edge(graph, &edge.id)?;
+
+        self.add_link_endpoints(graph, [(&source_id, source), (&target_id, target)])?;
+        graph.add_edge(&edge)?;
+        self.record_undo(&journal)?;
+        Ok(edge)
+    }
+
+    /// 批量创建链接（`memexia link apply`），整批记入一条撤销日志
+    ///
+    /// 路径相对仓库根目录，节点不存在时与 [`create_link`](Self::create_link) 一样创建 Resource 节点
+    ///
+    /// # Arguments
+    ///
+    /// * `links` - 解析后的链接，见 [`link_batch::parse`]
+    /// * `strict` - 为 true 时在一个事务中写入，任何一行失败都撤销整批并返回该行的错误；
+    ///   否则跳过失败的行，错误带行号记入结果
+    pub fn apply_links(&self, links: &[link_batch::LinkLine], strict: bool) -> Result<link_batch::ApplyReport> {
+        self.ensure_writable()?;
+        let graph = self.storage()?.graph();
+        let mut journal = undo::UndoJournal::new("link apply");
+        let mut report = link_batch::ApplyReport::default();
+
+        if strict {
+            report.applied = graph.transaction(|graph| {
+                links
+                    .iter()
+                    .map(|link| {
+                        self.apply_link(graph, &mut journal, link)
+                            .map_err(|e| MemexiaError::Validation(format!("line {}: {}", link.line, e)))
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })?;
+        } else {
+            for link in links {
+                match self.apply_link(graph, &mut journal, link) {
+                    Ok(edge) => report.applied.push(edge),
+                    Err(e) => report.errors.push(link_batch::LineError { line: link.line, message: e.to_string() }),
+                }
+            }
+        }
+
+        self.record_undo(&journal)?;
+        Ok(report)
+    }
+
+    /// 写入批量链接中的一行
+    fn apply_link(
+        &self,
+        graph: &dyn crate::storage::GraphStorage,
+        journal: &mut undo::UndoJournal,
+        link: &link_batch::LinkLine,
+    ) -> Result<Edge> {
+        let resolve = |path: &str| -> Result<PathBuf> {
+            let path = self.root.join(path);
+            if paths::normalize_input(&path, &self.root).is_external() {
+                return Err(MemexiaError::Validation(format!("Path is outside the repository: {}", paths::display(&path))));
+            }
+            Ok(path)
+        };
+        let (source, target) = (resolve(&link.source)?, resolve(&link.target)?);
+        let (source_id, target_id) = (self.node_id_for_path(&source)?, self.node_id_for_path(&target)?);
+
+        let mut builder = Edge::builder(&source_id, &target_id, link.relation.clone());
+        if let Some(strength) = link.strength {
+            builder = builder.strength(strength);
+        }
+        if let Some(description) = &link.description {
+            builder = builder.description(description);
+        }
+        let edge = builder.build()?;
+
+        journal.capture_node(graph, &source_id)?;
+        journal.capture_node(graph, &target_id)?;
+        journal.capture_edge(graph, &edge.id)?;
+        self.add_link_endpoints(graph, [(&source_id, &source), (&target_id, &target)])?;
+        graph.add_edge(&edge)?;
+        Ok(edge)
+    }
+
+    /// 为手动链接补建不存在的端点节点（Resource 类型，标题为相对路径）
+    fn add_link_endpoints(&self, graph: &dyn crate::storage::GraphStorage, endpoints: [(&String, &Path); 2]) -> Result<()> {
+        for (id, path) in endpoints {
+            if !graph.node_exists(id)? {
+                let title = paths::normalize_input(path, &self.root).into_string();
+                graph.add_node(&Node::builder(id, NodeType::Resource, title.as_str()).build()?)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 删除两个文件对应节点之间的链接（`memexia link delete`），操作记入撤销日志
+    pub fn delete_link(&self, source: &Path, target: &Path) -> Result<()> {
+        self.ensure_writable()?;
+        let graph = self.storage()?.graph();
+        let edge_id = Edge::make_id(&self.node_id_for_path(source)?, &self.node_id_for_path(target)?);
+
+        let mut journal = undo::UndoJournal::new("link delete");
+        journal.capture_edge(graph, &edge_id)?;
+        graph.remove_edge(&edge_id)?;
+        self.record_undo(&journal)
+    }
+
+    /// 将笔记文件路径转换为节点 ID（遵循 Zettel ID 配置）
+    ///
+    /// 已存在的相对路径相对当前目录解析，否则相对仓库根目录；
+    /// 同一文件的不同输入形式得到逐字节相同的 ID，见 [`paths::normalize_input`]
+    pub fn node_id_for_path(&self, path: &Path) -> Result<String> {
+        let config = RepoConfig::load(&self.root)?;
+        let path = fs::canonicalize(path).unwrap_or_else(|_| self.root.join(path));
+
+        let mut indexer = Indexer::new(self.storage()?.clone());
+        indexer.set_zettel_ids(config.ids.zettel);
+        indexer.set_extensions(config.index.extensions.clone());
+        Ok(indexer.path_to_id(&path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::store_opens_on_this_thread;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_open_is_lazy() {
+        let temp = TempDir::new().unwrap();
+        let repo = Repository::init(temp.path()).unwrap();
+        fs::write(temp.path().join("a.md"), "# A\n").unwrap();
+        repo.add(&[temp.path().join("a.md")]).unwrap();
+        drop(repo);
+
+        let opens = store_opens_on_this_thread();
+        let repo = Repository::open(temp.path()).unwrap();
+        // status 只读取暂存区，Git 查询也不打开图存储
+        assert!(repo.status().unwrap().contains("a.md"));
+        assert!(repo.last_commit().unwrap().is_none());
+        assert_eq!(store_opens_on_this_thread(), opens);
+
+        // 首次读取图谱时打开一次，之后复用
+        repo.stats().unwrap();
+        repo.stats().unwrap();
+        assert_eq!(store_opens_on_this_thread(), opens + 1);
+    }
 }
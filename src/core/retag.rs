@@ -0,0 +1,392 @@
+//! 批量改标签模块（`memexia tag rename` / `memexia tag remove` / `memexia tag promote`）
+//!
+//! 图中的改写直接更新节点的 `memexia:tag`；需要同步源文件时由 [`rewrite_tags`]
+//! 改写 Markdown：
+//!
+//! - frontmatter 中的 `tags`，支持 `tags: [a, b]`、`tags: a` 和逐行 `- a` 三种写法
+//! - 正文中的 `#tag`，只匹配完整的标签（`#old` 不会改写 `#older` 或 `#old-x`），
+//!   紧跟的标点不属于标签
+//!
+//! 层级标签以 `/` 分隔；递归模式下 `old/child` 随 `old` 一起改名或删除
+
+use crate::error::{MemexiaError, Result};
+use crate::storage::GraphStorage;
+use regex::{Captures, Regex};
+use std::path::PathBuf;
+
+/// 一次改标签操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagOp {
+    /// 要改写的标签
+    pub from: String,
+    /// 新标签；为 None 时删除
+    pub to: Option<String>,
+    /// 是否同时改写层级子标签（`from/...`）
+    pub recursive: bool,
+}
+
+impl TagOp {
+    /// 改名操作
+    pub fn rename(from: &str, to: &str, recursive: bool) -> Result<Self> {
+        validate_tag(from)?;
+        validate_tag(to)?;
+        Ok(Self {
+            from: from.to_string(),
+            to: Some(to.to_string()),
+            recursive,
+        })
+    }
+
+    /// 删除操作
+    pub fn remove(tag: &str, recursive: bool) -> Result<Self> {
+        validate_tag(tag)?;
+        Ok(Self {
+            from: tag.to_string(),
+            to: None,
+            recursive,
+        })
+    }
+
+    /// 对单个标签应用操作
+    ///
+    /// # Returns
+    ///
+    /// 标签不受影响时返回 None；受影响时返回改写结果（删除为 `Some(None)`）
+    pub fn apply(&self, tag: &str) -> Option<Option<String>> {
+        if tag == self.from {
+            return Some(self.to.clone());
+        }
+        if !self.recursive {
+            return None;
+        }
+        let child = tag.strip_prefix(&self.from)?.strip_prefix('/')?;
+        Some(self.to.as_ref().map(|to| format!("{}/{}", to, child)))
+    }
+
+    /// 对标签列表应用操作，保持顺序并去重
+    ///
+    /// # Returns
+    ///
+    /// 列表没有变化时返回 None
+    pub fn apply_all(&self, tags: &[String]) -> Option<Vec<String>> {
+        let mut changed = false;
+        let mut result: Vec<String> = Vec::with_capacity(tags.len());
+        for tag in tags {
+            let mapped = match self.apply(tag) {
+                Some(mapped) => {
+                    changed = true;
+                    mapped
+                }
+                None => Some(tag.clone()),
+            };
+            if let Some(mapped) = mapped {
+                if !result.contains(&mapped) {
+                    result.push(mapped);
+                }
+            }
+        }
+        changed.then_some(result)
+    }
+}
+
+/// 改标签结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RetagSummary {
+    /// 受影响的节点 ID
+    pub nodes: Vec<String>,
+    /// 改写过的文件（相对仓库根目录）
+    pub files: Vec<PathBuf>,
+}
+
+/// 标签升级为笔记的结果（`memexia tag promote`）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagPromotion {
+    /// 新笔记路径（相对仓库根目录）
+    pub path: PathBuf,
+    /// 新节点 ID
+    pub node_id: String,
+    /// 带该标签的节点 ID（按 ID 排序），每个节点有一条指向新节点的边和一条推导记录
+    pub tagged: Vec<String>,
+    /// 新增的边数
+    pub edges: usize,
+    /// 记录的推导数
+    pub derivations: usize,
+    /// 去掉了标签的节点数（`--consume`）
+    pub untagged: usize,
+    /// 改写过的文件（相对仓库根目录，`--consume --write-files`）
+    pub files: Vec<PathBuf>,
+}
+
+/// 校验标签名
+pub(crate) fn validate_tag(tag: &str) -> Result<()> {
+    let valid = !tag.is_empty()
+        && !tag.starts_with(['/', '-'])
+        && !tag.ends_with('/')
+        && tag.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '/'));
+    if valid {
+        Ok(())
+    } else {
+        Err(MemexiaError::Validation(format!("Invalid tag: {:?}", tag)))
+    }
+}
+
+/// 在图中改写标签
+///
+/// # Arguments
+///
+/// * `graph` - 图存储
+/// * `op` - 改标签操作
+///
+/// # Returns
+///
+/// 受影响的节点 ID（按 ID 排序）
+pub fn retag_graph(graph: &dyn GraphStorage, op: &TagOp) -> Result<Vec<String>> {
+    let mut affected = Vec::new();
+    for mut node in graph.get_all_nodes()? {
+        if let Some(tags) = op.apply_all(&node.tags) {
+            node.tags = tags;
+            graph.update_node(&node)?;
+            affected.push(node.id);
+        }
+    }
+    affected.sort();
+    Ok(affected)
+}
+
+/// 改写 Markdown 中的标签
+///
+/// # Returns
+///
+/// 内容没有变化时返回 None
+pub fn rewrite_tags(content: &str, op: &TagOp) -> Option<String> {
+    let (frontmatter, body) = split_frontmatter(content);
+    let new_frontmatter = rewrite_frontmatter(frontmatter, op);
+    let new_body = rewrite_inline(body, op);
+    if new_frontmatter.is_none() && new_body.is_none() {
+        return None;
+    }
+    Some(format!(
+        "{}{}",
+        new_frontmatter.as_deref().unwrap_or(frontmatter),
+        new_body.as_deref().unwrap_or(body)
+    ))
+}
+
+/// 拆出开头的 frontmatter 块（含两条 `---` 分隔行）
+pub(crate) fn split_frontmatter(content: &str) -> (&str, &str) {
+    let Some(rest) = content.strip_prefix("---\n").or_else(|| content.strip_prefix("---\r\n")) else {
+        return ("", content);
+    };
+    let mut offset = content.len() - rest.len();
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        if line.trim_end() == "---" {
+            return content.split_at(offset);
+        }
+    }
+    ("", content)
+}
+
+fn rewrite_frontmatter(frontmatter: &str, op: &TagOp) -> Option<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut changed = false;
+    let mut in_block_list = false;
+    for line in frontmatter.split_inclusive('\n') {
+        let (text, ending) = match line.strip_suffix("\r\n") {
+            Some(text) => (text, "\r\n"),
+            None => line.strip_suffix('\n').map_or((line, ""), |text| (text, "\n")),
+        };
+
+        if in_block_list {
+            let trimmed = text.trim_start();
+            if let Some(item) = trimmed.strip_prefix("- ") {
+                let indent = &text[..text.len() - trimmed.len()];
+                match op.apply(unquote(item.trim())) {
+                    Some(Some(new)) => {
+                        lines.push(format!("{}- {}{}", indent, requote(item.trim(), &new), ending));
+                        changed = true;
+                    }
+                    Some(None) => changed = true,
+                    None => lines.push(line.to_string()),
+                }
+                continue;
+            }
+            in_block_list = false;
+        }
+
+        let Some(value) = text.strip_prefix("tags:") else {
+            lines.push(line.to_string());
+            continue;
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            in_block_list = true;
+            lines.push(line.to_string());
+        } else if let Some(inner) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            let items: Vec<&str> = inner.split(',').map(str::trim).filter(|item| !item.is_empty()).collect();
+            let mut seen: Vec<String> = Vec::new();
+            let mut rendered: Vec<String> = Vec::new();
+            let mut list_changed = false;
+            for item in items {
+                let (tag, text) = match op.apply(unquote(item)) {
+                    Some(Some(new)) => (new.clone(), requote(item, &new)),
+                    Some(None) => {
+                        list_changed = true;
+                        continue;
+                    }
+                    None => (unquote(item).to_string(), item.to_string()),
+                };
+                list_changed |= text != item;
+                // 改名后与已有标签重复时只保留第一个
+                if !seen.contains(&tag) {
+                    seen.push(tag);
+                    rendered.push(text);
+                } else {
+                    list_changed = true;
+                }
+            }
+            if list_changed {
+                lines.push(format!("tags: [{}]{}", rendered.join(", "), ending));
+                changed = true;
+            } else {
+                lines.push(line.to_string());
+            }
+        } else {
+            match op.apply(unquote(value)) {
+                Some(Some(new)) => {
+                    lines.push(format!("tags: {}{}", requote(value, &new), ending));
+                    changed = true;
+                }
+                Some(None) => {
+                    lines.push(format!("tags: []{}", ending));
+                    changed = true;
+                }
+                None => lines.push(line.to_string()),
+            }
+        }
+    }
+    changed.then(|| lines.concat())
+}
+
+/// 去掉 YAML 标量两侧的引号
+pub(crate) fn unquote(item: &str) -> &str {
+    item.strip_prefix('"')
+        .and_then(|i| i.strip_suffix('"'))
+        .or_else(|| item.strip_prefix('\'').and_then(|i| i.strip_suffix('\'')))
+        .unwrap_or(item)
+}
+
+/// 按原条目的引号风格写出新标签
+fn requote(original: &str, tag: &str) -> String {
+    if original.starts_with('"') {
+        format!("\"{}\"", tag)
+    } else if original.starts_with('\'') {
+        format!("'{}'", tag)
+    } else {
+        tag.to_string()
+    }
+}
+
+fn rewrite_inline(body: &str, op: &TagOp) -> Option<String> {
+    // 与解析器一致：标签前是行首或空白；这里额外允许 `/` 以覆盖层级标签
+    let re = Regex::new(r"(?m)(^|[^\S\n])#(\w[\w/-]*)").unwrap();
+    let mut changed = false;
+    let result = re.replace_all(body, |cap: &Captures| {
+        let tag = cap[2].trim_end_matches('/');
+        let trailing = &cap[2][tag.len()..];
+        match op.apply(tag) {
+            Some(Some(new)) => {
+                changed = true;
+                format!("{}#{}{}", &cap[1], new, trailing)
+            }
+            // 删除时连同前面的空白一起去掉
+            Some(None) => {
+                changed = true;
+                trailing.to_string()
+            }
+            None => cap[0].to_string(),
+        }
+    });
+    changed.then(|| result.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{Node, NodeType, Storage};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_apply() {
+        let op = TagOp::rename("old", "new", false).unwrap();
+        assert_eq!(op.apply("old"), Some(Some("new".to_string())));
+        assert_eq!(op.apply("old/child"), None);
+        assert_eq!(op.apply("older"), None);
+
+        let op = TagOp::rename("old", "new", true).unwrap();
+        assert_eq!(op.apply("old/child/leaf"), Some(Some("new/child/leaf".to_string())));
+        assert_eq!(op.apply("older/child"), None);
+
+        let op = TagOp::remove("old", true).unwrap();
+        assert_eq!(op.apply("old/child"), Some(None));
+        assert_eq!(
+            op.apply_all(&["a".to_string(), "old".to_string(), "old/x".to_string()]),
+            Some(vec!["a".to_string()])
+        );
+
+        assert!(TagOp::rename("old", "bad tag", false).is_err());
+        assert!(TagOp::remove("", false).is_err());
+    }
+
+    #[test]
+    fn test_retag_graph() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        let graph = storage.graph();
+        graph
+            .add_node(&Node::builder("urn:memexia:file:a.md", NodeType::Concept, "A").tags(["old", "keep"]).build().unwrap())
+            .unwrap();
+        graph
+            .add_node(&Node::builder("urn:memexia:file:b.md", NodeType::Concept, "B").tags(["new", "old"]).build().unwrap())
+            .unwrap();
+        graph
+            .add_node(&Node::builder("urn:memexia:file:c.md", NodeType::Concept, "C").tags(["older"]).build().unwrap())
+            .unwrap();
+
+        let affected = retag_graph(graph, &TagOp::rename("old", "new", false).unwrap()).unwrap();
+        assert_eq!(affected, ["urn:memexia:file:a.md", "urn:memexia:file:b.md"]);
+        let tags = |id: &str| {
+            let mut tags = graph.get_node(id).unwrap().unwrap().tags;
+            tags.sort();
+            tags
+        };
+        assert_eq!(tags("urn:memexia:file:a.md"), ["keep", "new"]);
+        assert_eq!(tags("urn:memexia:file:b.md"), ["new"]);
+        assert_eq!(tags("urn:memexia:file:c.md"), ["older"]);
+    }
+
+    #[test]
+    fn test_rewrite_tags() {
+        let op = TagOp::rename("old", "new", false).unwrap();
+        let content = "---\ntitle: T\ntags: [\"old\", keep]\n---\n\n# T\n\nSee #old, not #older or #old-x.\n#old\n";
+        assert_eq!(
+            rewrite_tags(content, &op).unwrap(),
+            "---\ntitle: T\ntags: [\"new\", keep]\n---\n\n# T\n\nSee #new, not #older or #old-x.\n#new\n"
+        );
+        assert_eq!(rewrite_tags("# T\n\nnothing #older here\n", &op), None);
+
+        let block = "---\ntags:\n  - keep\n  - old/child\ntitle: T\n---\nbody #old/child.\n";
+        let recursive = TagOp::rename("old", "new", true).unwrap();
+        assert_eq!(
+            rewrite_tags(block, &recursive).unwrap(),
+            "---\ntags:\n  - keep\n  - new/child\ntitle: T\n---\nbody #new/child.\n"
+        );
+        assert_eq!(rewrite_tags(block, &op), None);
+
+        let remove = TagOp::remove("old", false).unwrap();
+        assert_eq!(
+            rewrite_tags("---\ntags: old\n---\nText #old. More\n", &remove).unwrap(),
+            "---\ntags: []\n---\nText. More\n"
+        );
+    }
+}
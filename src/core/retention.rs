@@ -0,0 +1,307 @@
+//! 图快照保留策略模块
+//!
+//! 每次提交都会存储一份完整的图快照，不加限制时快照会占满磁盘。
+//! `history.retention` 配置决定保留哪些提交的快照：
+//!
+//! ```toml
+//! [history]
+//! retention = "keep_all"                 # 默认：全部保留
+//! # retention = { keep_last = 100 }       # 只保留最近 100 个提交的快照
+//! # retention = { daily = 30, weekly = 52, monthly = "all" }
+//! ```
+//!
+//! 分级策略与常见备份工具一致：按日 / 周 / 月分桶，从新到旧为每个桶保留最新的一个快照，
+//! 每级最多保留指定个数的桶（`"all"` 表示不限）。一个快照满足任意一级即被保留
+//!
+//! 策略在每次快照后和 `memexia gc` 时执行，见
+//! [`GraphHistory::apply_retention`](crate::vcs::GraphHistory::apply_retention)；
+//! HEAD 和各分支末端提交的快照永远不会被清理
+
+use crate::vcs::CommitLink;
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+
+/// 分级策略中一级的保留个数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawTier", into = "RawTier")]
+pub enum Tier {
+    /// 最多保留的桶数
+    Count(usize),
+    /// 不限
+    All,
+}
+
+impl Tier {
+    fn limit(self) -> usize {
+        match self {
+            Tier::Count(count) => count,
+            Tier::All => usize::MAX,
+        }
+    }
+}
+
+/// 快照保留策略
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(try_from = "RawPolicy", into = "RawPolicy")]
+pub enum RetentionPolicy {
+    /// 保留所有快照
+    #[default]
+    KeepAll,
+    /// 只保留最近 N 个提交的快照
+    KeepLast(usize),
+    /// 按日 / 周 / 月分级保留（未设置的级别不保留）
+    Tiered {
+        /// 按日
+        daily: Tier,
+        /// 按周（ISO 周）
+        weekly: Tier,
+        /// 按月
+        monthly: Tier,
+    },
+}
+
+impl fmt::Display for RetentionPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tier = |tier: &Tier| match tier {
+            Tier::Count(count) => count.to_string(),
+            Tier::All => "all".to_string(),
+        };
+        match self {
+            RetentionPolicy::KeepAll => write!(f, "keep_all"),
+            RetentionPolicy::KeepLast(count) => write!(f, "keep_last: {}", count),
+            RetentionPolicy::Tiered { daily, weekly, monthly } => write!(
+                f,
+                "daily: {}, weekly: {}, monthly: {}",
+                tier(daily),
+                tier(weekly),
+                tier(monthly)
+            ),
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// 按策略选出要保留的关联
+    ///
+    /// # Arguments
+    ///
+    /// * `links` - 提交关联（按记录顺序，从旧到新）
+    ///
+    /// # Returns
+    ///
+    /// 与 `links` 一一对应的保留标记。分级策略按记录时间分桶，没有记录时间的关联不会被选中
+    pub fn retained(&self, links: &[CommitLink]) -> Vec<bool> {
+        match self {
+            RetentionPolicy::KeepAll => vec![true; links.len()],
+            RetentionPolicy::KeepLast(count) => {
+                let first = links.len().saturating_sub(*count);
+                (0..links.len()).map(|index| index >= first).collect()
+            }
+            RetentionPolicy::Tiered { daily, weekly, monthly } => {
+                let mut keep = vec![false; links.len()];
+                let tiers: [(Tier, BucketFn); 3] = [
+                    (*daily, |at| (at.year(), at.ordinal(), 0)),
+                    (*weekly, |at| (at.iso_week().year(), at.iso_week().week(), 1)),
+                    (*monthly, |at| (at.year(), at.month(), 2)),
+                ];
+                for (tier, bucket) in tiers {
+                    let mut seen = HashSet::new();
+                    // 从新到旧，每个桶保留最新的一个
+                    for (index, link) in links.iter().enumerate().rev() {
+                        let Some(at) = link.recorded_at else {
+                            continue;
+                        };
+                        if seen.len() >= tier.limit() {
+                            break;
+                        }
+                        if seen.insert(bucket(&at)) {
+                            keep[index] = true;
+                        }
+                    }
+                }
+                keep
+            }
+        }
+    }
+}
+
+/// 记录时间所在的桶（年、日 / 周 / 月序号、级别）
+type BucketFn = fn(&DateTime<Utc>) -> (i32, u32, u32);
+
+/// 配置文件中的策略形式：`"keep_all"` 或表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum RawPolicy {
+    Name(String),
+    Table(RawTable),
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawTable {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    keep_last: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    daily: Option<Tier>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    weekly: Option<Tier>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    monthly: Option<Tier>,
+}
+
+impl TryFrom<RawPolicy> for RetentionPolicy {
+    type Error = String;
+
+    fn try_from(raw: RawPolicy) -> Result<Self, Self::Error> {
+        let table = match raw {
+            RawPolicy::Name(name) if name == "keep_all" => return Ok(RetentionPolicy::KeepAll),
+            RawPolicy::Name(name) => {
+                return Err(format!(
+                    "unknown retention policy '{}' (expected \"keep_all\", {{ keep_last = N }} or daily/weekly/monthly tiers)",
+                    name
+                ))
+            }
+            RawPolicy::Table(table) => table,
+        };
+
+        let tiered = table.daily.is_some() || table.weekly.is_some() || table.monthly.is_some();
+        match table.keep_last {
+            Some(_) if tiered => Err("keep_last cannot be combined with daily/weekly/monthly tiers".to_string()),
+            Some(0) => Err("keep_last must be at least 1".to_string()),
+            Some(count) => Ok(RetentionPolicy::KeepLast(count)),
+            None if !tiered => Err("retention table needs keep_last or at least one of daily, weekly, monthly".to_string()),
+            None => Ok(RetentionPolicy::Tiered {
+                daily: table.daily.unwrap_or(Tier::Count(0)),
+                weekly: table.weekly.unwrap_or(Tier::Count(0)),
+                monthly: table.monthly.unwrap_or(Tier::Count(0)),
+            }),
+        }
+    }
+}
+
+impl From<RetentionPolicy> for RawPolicy {
+    fn from(policy: RetentionPolicy) -> Self {
+        let tier = |tier: Tier| (tier != Tier::Count(0)).then_some(tier);
+        match policy {
+            RetentionPolicy::KeepAll => RawPolicy::Name("keep_all".to_string()),
+            RetentionPolicy::KeepLast(count) => RawPolicy::Table(RawTable {
+                keep_last: Some(count),
+                ..RawTable::default()
+            }),
+            RetentionPolicy::Tiered { daily, weekly, monthly } => RawPolicy::Table(RawTable {
+                keep_last: None,
+                daily: tier(daily),
+                weekly: tier(weekly),
+                monthly: tier(monthly),
+            }),
+        }
+    }
+}
+
+/// 配置文件中的级别形式：整数或 `"all"`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum RawTier {
+    Count(usize),
+    Name(String),
+}
+
+impl TryFrom<RawTier> for Tier {
+    type Error = String;
+
+    fn try_from(raw: RawTier) -> Result<Self, Self::Error> {
+        match raw {
+            RawTier::Count(count) => Ok(Tier::Count(count)),
+            RawTier::Name(name) if name == "all" => Ok(Tier::All),
+            RawTier::Name(name) => Err(format!("invalid retention count '{}' (expected a number or \"all\")", name)),
+        }
+    }
+}
+
+impl From<Tier> for RawTier {
+    fn from(tier: Tier) -> Self {
+        match tier {
+            Tier::Count(count) => RawTier::Count(count),
+            Tier::All => RawTier::Name("all".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+
+    #[derive(Deserialize)]
+    struct Wrapper {
+        retention: RetentionPolicy,
+    }
+
+    fn parse(value: &str) -> Result<RetentionPolicy, toml::de::Error> {
+        toml::from_str::<Wrapper>(&format!("retention = {}", value)).map(|w| w.retention)
+    }
+
+    #[test]
+    fn test_parse_policies() {
+        assert_eq!(parse("\"keep_all\"").unwrap(), RetentionPolicy::KeepAll);
+        assert_eq!(parse("{ keep_last = 100 }").unwrap(), RetentionPolicy::KeepLast(100));
+        assert_eq!(
+            parse("{ daily = 30, weekly = 52, monthly = \"all\" }").unwrap(),
+            RetentionPolicy::Tiered {
+                daily: Tier::Count(30),
+                weekly: Tier::Count(52),
+                monthly: Tier::All
+            }
+        );
+        assert!(parse("\"keep_some\"").is_err());
+        assert!(parse("{ keep_last = 0 }").is_err());
+        assert!(parse("{ keep_last = 3, daily = 1 }").is_err());
+        assert!(parse("{ daily = \"many\" }").is_err());
+        assert!(parse("{}").is_err());
+
+        // 序列化后可以读回
+        let policy = parse("{ weekly = 4, monthly = \"all\" }").unwrap();
+        let value = toml::Value::try_from(&policy).unwrap();
+        assert_eq!(parse(&value.to_string()).unwrap(), policy);
+    }
+
+    fn links(times: &[chrono::DateTime<Utc>]) -> Vec<CommitLink> {
+        times
+            .iter()
+            .enumerate()
+            .map(|(index, at)| CommitLink {
+                commit_hash: format!("c{}", index),
+                graph_hash: format!("g{}", index),
+                recorded_at: Some(*at),
+                pruned: false,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_keep_last() {
+        let start = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let links = links(&(0..5).map(|i| start + Duration::hours(i)).collect::<Vec<_>>());
+        assert_eq!(RetentionPolicy::KeepLast(2).retained(&links), [false, false, false, true, true]);
+        assert_eq!(RetentionPolicy::KeepLast(10).retained(&links), [true; 5]);
+        assert_eq!(RetentionPolicy::KeepAll.retained(&links), [true; 5]);
+    }
+
+    #[test]
+    fn test_tiered_keeps_newest_per_bucket() {
+        let day = |d: u32, h: u32| Utc.with_ymd_and_hms(2026, 3, d, h, 0, 0).unwrap();
+        // 3 月 2 日（周一）两次、3 日、9 日（下周一）、4 月 1 日
+        let links = links(&[day(2, 9), day(2, 18), day(3, 12), day(9, 8), Utc.with_ymd_and_hms(2026, 4, 1, 0, 0, 0).unwrap()]);
+
+        let daily = RetentionPolicy::Tiered { daily: Tier::Count(2), weekly: Tier::Count(0), monthly: Tier::Count(0) };
+        assert_eq!(daily.retained(&links), [false, false, false, true, true]);
+
+        let weekly = RetentionPolicy::Tiered { daily: Tier::Count(0), weekly: Tier::All, monthly: Tier::Count(0) };
+        assert_eq!(weekly.retained(&links), [false, false, true, true, true]);
+
+        let monthly = RetentionPolicy::Tiered { daily: Tier::Count(1), weekly: Tier::Count(0), monthly: Tier::All };
+        assert_eq!(monthly.retained(&links), [false, false, false, true, true]);
+    }
+}
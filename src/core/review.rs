@@ -0,0 +1,295 @@
+//! 回顾模块
+//!
+//! 为 `memexia random` 和 `memexia review` 选择需要重新阅读的笔记：
+//!
+//! - [`random_note`] - 按类型/标签过滤后随机选择一篇笔记
+//! - [`review_queue`] - 按 `updated_at` 升序排列的回顾队列，最近回顾过的笔记会被跳过
+//! - [`mark_reviewed`] - 在节点上记录 `memexia:lastReviewed` 时间
+//!
+//! 只统计已索引的笔记（带字数的节点），链接产生的占位节点不参与回顾
+
+use crate::error::Result;
+use crate::storage::{GraphStorage, Node, NodeType};
+use chrono::{DateTime, Duration, Utc};
+use rand::seq::IndexedRandom;
+use rand::Rng;
+
+/// 默认回顾间隔（天）：该时间内回顾过的笔记不再进入队列
+pub const DEFAULT_REVIEW_INTERVAL_DAYS: i64 = 7;
+
+/// 笔记过滤条件
+#[derive(Debug, Clone, Default)]
+pub struct NoteFilter {
+    /// 只选择该类型的节点
+    pub node_type: Option<NodeType>,
+    /// 只选择带该标签的节点
+    pub tag: Option<String>,
+}
+
+impl NoteFilter {
+    /// 节点是否满足过滤条件
+    pub fn matches(&self, node: &Node) -> bool {
+        self.node_type.as_ref().is_none_or(|t| &node.node_type == t)
+            && self.tag.as_ref().is_none_or(|tag| node.tags.iter().any(|t| t == tag))
+    }
+}
+
+/// 回顾条目
+#[derive(Debug, Clone)]
+pub struct ReviewItem {
+    /// 笔记节点
+    pub node: Node,
+    /// 笔记文件相对路径（Zettel ID 节点为 None）
+    pub path: Option<String>,
+    /// 反向链接数
+    pub backlinks: usize,
+}
+
+impl ReviewItem {
+    /// 从节点创建条目，并查询反向链接数
+    fn new(graph: &dyn GraphStorage, node: Node) -> Result<Self> {
+        let backlinks = graph.get_edges_by_target(&node.id)?.len();
+        let path = node_path(&node.id);
+        Ok(Self { node, path, backlinks })
+    }
+
+    /// 笔记摘要：正文中第一行非标题文本
+    pub fn summary(&self) -> Option<&str> {
+        self.node
+            .content
+            .as_deref()?
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'))
+    }
+}
+
+/// 随机选择一篇笔记
+///
+/// # Arguments
+///
+/// * `graph` - 图存储
+/// * `filter` - 过滤条件
+/// * `rng` - 随机数生成器
+///
+/// # Returns
+///
+/// 没有满足条件的笔记时返回 None
+pub fn random_note<R: Rng + ?Sized>(
+    graph: &dyn GraphStorage,
+    filter: &NoteFilter,
+    rng: &mut R,
+) -> Result<Option<ReviewItem>> {
+    let candidates: Vec<Node> = notes(graph)?.into_iter().filter(|n| filter.matches(n)).collect();
+
+    match candidates.choose(rng) {
+        Some(node) => Ok(Some(ReviewItem::new(graph, node.clone())?)),
+        None => Ok(None),
+    }
+}
+
+/// 生成回顾队列
+///
+/// 按 `updated_at` 升序（最久未修改的在前）排列，跳过 `interval` 内回顾过的笔记
+///
+/// # Arguments
+///
+/// * `graph` - 图存储
+/// * `limit` - 最多返回的条目数
+/// * `interval` - 回顾间隔
+/// * `now` - 当前时间
+pub fn review_queue(
+    graph: &dyn GraphStorage,
+    limit: usize,
+    interval: Duration,
+    now: DateTime<Utc>,
+) -> Result<Vec<ReviewItem>> {
+    let mut candidates: Vec<Node> = notes(graph)?
+        .into_iter()
+        .filter(|n| n.last_reviewed().is_none_or(|at| now - at >= interval))
+        .collect();
+    candidates.sort_by(|a, b| a.updated_at.cmp(&b.updated_at).then_with(|| a.id.cmp(&b.id)));
+
+    candidates
+        .into_iter()
+        .take(limit)
+        .map(|node| ReviewItem::new(graph, node))
+        .collect()
+}
+
+/// 记录笔记已回顾
+///
+/// # Arguments
+///
+/// * `graph` - 图存储
+/// * `id` - 节点 ID
+/// * `now` - 回顾时间
+///
+/// # Returns
+///
+/// 更新后的节点
+pub fn mark_reviewed(graph: &dyn GraphStorage, id: &str, now: DateTime<Utc>) -> Result<Node> {
+    let mut node = graph.require_node(id)?;
+    node.set_last_reviewed(now);
+    graph.update_node(&node)?;
+    Ok(node)
+}
+
+/// 从文件节点 ID 还原相对路径（解码 percent 编码）
+pub fn node_path(id: &str) -> Option<String> {
+    let encoded = id.strip_prefix("urn:memexia:file:")?;
+
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut rest = encoded.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        let decoded = (b == b'%')
+            .then(|| tail.get(..2))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match decoded {
+            Some(byte) => {
+                bytes.push(byte);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(b);
+                rest = tail;
+            }
+        }
+    }
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// 获取所有已索引的笔记
+fn notes(graph: &dyn GraphStorage) -> Result<Vec<Node>> {
+    Ok(graph
+        .get_all_nodes()?
+        .into_iter()
+        .filter(|n| n.word_count().is_some())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Indexer;
+    use crate::storage::Storage;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// 创建带三篇笔记的仓库：old 被 new 链接，mid 带标签
+    fn fixture() -> (TempDir, Storage) {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let storage = Storage::init(root).unwrap();
+        let indexer = Indexer::new(storage.clone());
+
+        let notes = [
+            ("old.md", "---\ncreated: 2022-01-01\ntype: Question\n---\n# Old\n\nWhy does it rot?"),
+            ("mid.md", "---\ncreated: 2023-01-01\ntags: [mind]\n---\nMiddle note"),
+            ("new.md", "---\ncreated: 2024-01-01\n---\nSee [[old.md]] again"),
+        ];
+        for (name, content) in notes {
+            let path = root.join(name);
+            fs::write(&path, content).unwrap();
+            indexer.index_file(&path).unwrap();
+        }
+
+        // 统一修改时间，使顺序只取决于 created
+        for node in storage.graph().get_all_nodes().unwrap() {
+            let mut node = node;
+            node.updated_at = node.created_at;
+            storage.graph().update_node(&node).unwrap();
+        }
+
+        (temp_dir, storage)
+    }
+
+    fn ids(items: &[ReviewItem]) -> Vec<&str> {
+        items.iter().map(|i| i.path.as_deref().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_review_queue_orders_by_updated_at() {
+        let (_temp, storage) = fixture();
+        let queue = review_queue(storage.graph(), 10, Duration::days(7), Utc::now()).unwrap();
+
+        // 占位节点不进入队列
+        assert_eq!(ids(&queue), vec!["old.md", "mid.md", "new.md"]);
+        assert_eq!(queue[0].summary(), Some("Why does it rot?"));
+        assert_eq!(queue[0].backlinks, 1);
+
+        let queue = review_queue(storage.graph(), 2, Duration::days(7), Utc::now()).unwrap();
+        assert_eq!(ids(&queue), vec!["old.md", "mid.md"]);
+    }
+
+    #[test]
+    fn test_mark_reviewed_skips_recent() {
+        let (_temp, storage) = fixture();
+        let now = Utc::now();
+
+        let node = mark_reviewed(storage.graph(), "urn:memexia:file:old.md", now).unwrap();
+        assert!(node.last_reviewed().is_some());
+
+        // 回顾时间被持久化，且不影响其他字段
+        let stored = storage.graph().get_node("urn:memexia:file:old.md").unwrap().unwrap();
+        assert_eq!(stored.last_reviewed().unwrap().timestamp(), now.timestamp());
+        assert_eq!(stored.updated_at, node.updated_at);
+        assert_eq!(stored.word_count(), Some(5));
+
+        let queue = review_queue(storage.graph(), 10, Duration::days(7), now).unwrap();
+        assert_eq!(ids(&queue), vec!["mid.md", "new.md"]);
+
+        // 超过回顾间隔后重新进入队列
+        let later = now + Duration::days(8);
+        let queue = review_queue(storage.graph(), 10, Duration::days(7), later).unwrap();
+        assert_eq!(ids(&queue), vec!["old.md", "mid.md", "new.md"]);
+
+        assert!(mark_reviewed(storage.graph(), "urn:memexia:file:missing.md", now).is_err());
+    }
+
+    #[test]
+    fn test_last_reviewed_survives_reindex() {
+        let (temp, storage) = fixture();
+        mark_reviewed(storage.graph(), "urn:memexia:file:mid.md", Utc::now()).unwrap();
+
+        Indexer::new(storage.clone()).index_file(&temp.path().join("mid.md")).unwrap();
+        let node = storage.graph().get_node("urn:memexia:file:mid.md").unwrap().unwrap();
+        assert!(node.last_reviewed().is_some());
+    }
+
+    #[test]
+    fn test_random_note_filters() {
+        let (_temp, storage) = fixture();
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let filter = NoteFilter { node_type: Some(NodeType::Question), tag: None };
+        let item = random_note(storage.graph(), &filter, &mut rng).unwrap().unwrap();
+        assert_eq!(item.path.as_deref(), Some("old.md"));
+
+        let filter = NoteFilter { node_type: None, tag: Some("mind".to_string()) };
+        let item = random_note(storage.graph(), &filter, &mut rng).unwrap().unwrap();
+        assert_eq!(item.node.title, "mid");
+
+        let filter = NoteFilter { node_type: Some(NodeType::Person), tag: None };
+        assert!(random_note(storage.graph(), &filter, &mut rng).unwrap().is_none());
+
+        for _ in 0..10 {
+            let item = random_note(storage.graph(), &NoteFilter::default(), &mut rng).unwrap().unwrap();
+            assert!(item.node.word_count().is_some());
+        }
+    }
+
+    #[test]
+    fn test_node_path() {
+        assert_eq!(node_path("urn:memexia:file:notes/a.md").as_deref(), Some("notes/a.md"));
+        assert_eq!(
+            node_path("urn:memexia:file:%E8%87%AA%E7%94%B1%20will.md").as_deref(),
+            Some("自由 will.md")
+        );
+        assert_eq!(node_path("urn:memexia:file:100%").as_deref(), Some("100%"));
+        assert!(node_path("urn:memexia:zettel:202401011200").is_none());
+    }
+}
@@ -0,0 +1,134 @@
+//! 笔记搜索模块
+//!
+//! `memexia search <query>` 在已索引笔记的标题、标签和正文中按不区分大小写的子串匹配查找，
+//! 标题命中排在标签和正文命中之前。已软删除的节点不参与搜索，
+//! 已归档的笔记默认排除（见 [`archive`](super::archive)）
+//!
+//! 子串匹配不需要分词，中文等不以空格分词的语言同样适用；`--lang` 只保留指定语言的笔记
+//! （见 [`language`](super::language)）
+//!
+//! 超长笔记在节点上只保存正文摘录（见 [`Node::excerpt_of`]），正文匹配改为读取笔记文件的完整内容
+
+use crate::core::language;
+use crate::error::Result;
+use crate::storage::{GraphStorage, Node};
+use std::fs;
+use std::path::Path;
+
+/// 标题命中的得分
+const TITLE_SCORE: usize = 10;
+/// 标签命中的得分
+const TAG_SCORE: usize = 5;
+
+/// 一条搜索结果
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    /// 命中的节点
+    pub node: Node,
+    /// 得分（越高越相关）
+    pub score: usize,
+}
+
+/// 搜索已索引的笔记
+///
+/// # Arguments
+///
+/// * `graph` - 图存储
+/// * `root` - 仓库根目录（读取只保存了摘录的笔记的完整正文）
+/// * `query` - 查询（按空白切分为多个词，所有词都必须命中）
+/// * `include_archived` - 是否包含已归档的笔记
+/// * `lang` - 只保留该语言的笔记（`zh` 匹配 `zh-cn`）
+///
+/// # Returns
+///
+/// 按得分降序、标题升序排列的结果；查询为空时没有结果
+pub fn search(
+    graph: &dyn GraphStorage,
+    root: &Path,
+    query: &str,
+    include_archived: bool,
+    lang: Option<&str>,
+) -> Result<Vec<SearchHit>> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut hits: Vec<SearchHit> = graph
+        .get_all_nodes()?
+        .into_iter()
+        .filter(|n| n.word_count().is_some() && !n.is_deleted() && (include_archived || !n.is_archived()))
+        .filter(|n| lang.is_none_or(|lang| n.language().is_some_and(|language| language::matches(language, lang))))
+        .filter_map(|node| score(&node, root, &terms).map(|score| SearchHit { node, score }))
+        .collect();
+    hits.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.node.title.cmp(&b.node.title)));
+    Ok(hits)
+}
+
+/// 节点对所有查询词的得分；有词未命中时为 `None`
+fn score(node: &Node, root: &Path, terms: &[String]) -> Option<usize> {
+    let title = node.title.to_lowercase();
+    let tags: Vec<String> = node.tags.iter().map(|t| t.to_lowercase()).collect();
+    let full_text = node.excerpt_of().and_then(|path| fs::read_to_string(root.join(path)).ok());
+    let content = full_text.as_deref().or(node.content.as_deref()).unwrap_or_default().to_lowercase();
+
+    let mut total = 0;
+    for term in terms {
+        let mut term_score = 0;
+        if title.contains(term.as_str()) {
+            term_score += TITLE_SCORE;
+        }
+        if tags.iter().any(|t| t.contains(term.as_str())) {
+            term_score += TAG_SCORE;
+        }
+        term_score += content.matches(term.as_str()).count();
+        if term_score == 0 {
+            return None;
+        }
+        total += term_score;
+    }
+    Some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{NodeType, Storage, WORD_COUNT_KEY};
+    use tempfile::TempDir;
+
+    fn note(id: &str, title: &str, content: &str) -> Node {
+        let mut node = Node::builder(id, NodeType::Concept, title).content(content).build().unwrap();
+        node.metadata.insert(WORD_COUNT_KEY.to_string(), content.split_whitespace().count().into());
+        node
+    }
+
+    #[test]
+    fn test_search_ranks_and_filters() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let graph = storage.graph();
+        graph.add_node(&note("urn:memexia:file:a.md", "Free will", "Is the will free?")).unwrap();
+        graph.add_node(&note("urn:memexia:file:b.md", "Determinism", "Opposes free will.")).unwrap();
+        let mut archived = note("urn:memexia:file:archive/c.md", "Old will", "Free will draft");
+        archived.set_archived_at(chrono::Utc::now());
+        graph.add_node(&archived).unwrap();
+        // 未索引的占位节点不参与搜索
+        graph.add_node(&Node::new("urn:memexia:file:d.md", NodeType::Concept, "Will")).unwrap();
+
+        let titles: Vec<String> = search(graph, temp.path(), "FREE will", false, None).unwrap().into_iter().map(|h| h.node.title).collect();
+        assert_eq!(titles, ["Free will", "Determinism"]);
+
+        let titles: Vec<String> = search(graph, temp.path(), "will", true, None).unwrap().into_iter().map(|h| h.node.title).collect();
+        assert_eq!(titles, ["Free will", "Old will", "Determinism"]);
+
+        assert!(search(graph, temp.path(), "free nothing", false, None).unwrap().is_empty());
+        assert!(search(graph, temp.path(), "   ", false, None).unwrap().is_empty());
+
+        let mut chinese = note("urn:memexia:file:e.md", "自由意志", "free will 自由意志的讨论");
+        chinese.set_language("zh");
+        graph.add_node(&chinese).unwrap();
+        let titles: Vec<String> = search(graph, temp.path(), "free", false, Some("zh")).unwrap().into_iter().map(|h| h.node.title).collect();
+        assert_eq!(titles, ["自由意志"]);
+        assert!(search(graph, temp.path(), "free", false, Some("en")).unwrap().is_empty());
+    }
+}
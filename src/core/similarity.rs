@@ -0,0 +1,128 @@
+//! 文本相似度模块
+//!
+//! 不依赖外部服务的轻量相似度计算，用于重复笔记检测：
+//!
+//! - [`normalize_title`] - 标题归一化（忽略大小写、标点和多余空白）
+//! - [`shingles`] - 按词切分后的 k-shingle 集合
+//! - [`jaccard`] - 两个 shingle 集合的 Jaccard 系数
+//! - [`content_similarity`] - 去掉 wiki 链接后的正文相似度
+//!
+//! 分词规则与字数统计一致：中日韩文字每字一词，其他按字母数字连续段切分
+
+use crate::core::parser::is_cjk;
+use crate::core::parser::wiki_link::remove_wiki_links;
+use std::collections::HashSet;
+
+/// 默认 shingle 长度（词数）
+pub const DEFAULT_SHINGLE_SIZE: usize = 3;
+
+/// 归一化标题
+///
+/// 转为小写，只保留字母数字，单词之间以单个空格分隔
+pub fn normalize_title(title: &str) -> String {
+    title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 切分为小写词序列（中日韩文字每字一词）
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+
+    for c in text.chars() {
+        if is_cjk(c) {
+            if !current.is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+            tokens.push(c.to_string());
+        } else if c.is_alphanumeric() {
+            current.extend(c.to_lowercase());
+        } else if !current.is_empty() {
+            tokens.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// 生成 k-shingle 集合
+///
+/// 词数不足 `k` 时整段作为一个 shingle；空文本返回空集合
+pub fn shingles(text: &str, k: usize) -> HashSet<String> {
+    let tokens = tokenize(text);
+    if tokens.is_empty() {
+        return HashSet::new();
+    }
+    if tokens.len() <= k {
+        return HashSet::from([tokens.join(" ")]);
+    }
+    tokens.windows(k.max(1)).map(|w| w.join(" ")).collect()
+}
+
+/// Jaccard 系数 `|A ∩ B| / |A ∪ B|`，两者都为空时为 0
+pub fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// 正文相似度：去掉 wiki 链接后按 3-shingle 计算 Jaccard 系数
+pub fn content_similarity(a: &str, b: &str) -> f64 {
+    jaccard(&content_shingles(a), &content_shingles(b))
+}
+
+/// 去掉 wiki 链接后的 shingle 集合（批量比较时预先计算）
+pub fn content_shingles(text: &str) -> HashSet<String> {
+    shingles(&remove_wiki_links(text), DEFAULT_SHINGLE_SIZE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_title() {
+        assert_eq!(normalize_title("Free Will"), "free will");
+        assert_eq!(normalize_title("  free-will!! "), "free will");
+        assert_eq!(normalize_title("Free, Will?"), "free will");
+        assert_eq!(normalize_title("自由意志（草稿）"), "自由意志 草稿");
+        assert_ne!(normalize_title("Free Willpower"), normalize_title("Free Will"));
+    }
+
+    #[test]
+    fn test_tokenize_and_shingles() {
+        assert_eq!(tokenize("Hello, World! 自由"), vec!["hello", "world", "自", "由"]);
+
+        let s = shingles("a b c d", 3);
+        assert_eq!(s, HashSet::from(["a b c".to_string(), "b c d".to_string()]));
+        assert_eq!(shingles("short text", 3), HashSet::from(["short text".to_string()]));
+        assert!(shingles("  ...  ", 3).is_empty());
+    }
+
+    #[test]
+    fn test_jaccard_synthetic_texts() {
+        let base = "the quick brown fox jumps over the lazy dog near the river bank";
+        assert_eq!(content_similarity(base, base), 1.0);
+
+        // 大小写、标点和 wiki 链接不影响
+        let same = "The quick brown fox jumps over the lazy dog, near the river bank. [[fox]]";
+        assert_eq!(content_similarity(base, same), 1.0);
+
+        // 末尾改动一个词：12 个 shingle 中 1 个不同
+        let edited = "the quick brown fox jumps over the lazy dog near the river shore";
+        let score = content_similarity(base, edited);
+        assert!((score - 10.0 / 12.0).abs() < 1e-9, "score = {}", score);
+
+        let unrelated = "completely different words about memory palaces and recall";
+        assert_eq!(content_similarity(base, unrelated), 0.0);
+        assert_eq!(content_similarity("", ""), 0.0);
+    }
+}
@@ -0,0 +1,273 @@
+//! 相似度导入模块
+//!
+//! 把外部工具（如基于 `export --format corpus` 语料的向量检索）算出的相似度写回图谱，
+//! 作为 `AnalogousTo` 建议边进入 [`suggestions`](super::suggestions) 的接受 / 拒绝流程
+//!
+//! 输入为 CSV，每行 `node_id_a,node_id_b,score`（首行不是数值分数时视为表头）：
+//!
+//! - 分数不低于阈值的节点对写入为 `EdgeSource::AI` 边，置信度即分数
+//! - `a,b` 与 `b,a` 视为同一对，取较高的分数
+//! - 已有显式边连接的节点对被跳过；已有的 `AnalogousTo` 建议边只更新置信度
+//! - 不存在的节点 ID 被记录在结果中，不中断导入
+
+use crate::error::{MemexiaError, Result};
+use crate::storage::csv::parse_records;
+use crate::storage::{Edge, EdgeDirection, EdgeSource, GraphStorage, RelationType};
+use std::collections::BTreeMap;
+
+/// 默认导入阈值
+pub const DEFAULT_THRESHOLD: f64 = 0.75;
+
+/// 一行相似度
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreRow {
+    /// CSV 中的行号（从 1 开始）
+    pub line: usize,
+    /// 节点 A
+    pub a: String,
+    /// 节点 B
+    pub b: String,
+    /// 相似度 (0.0 - 1.0)
+    pub score: f64,
+}
+
+/// 解析相似度 CSV
+///
+/// # Returns
+///
+/// 按出现顺序的行；列数不对或分数不是 0.0 - 1.0 之间的数值时返回带行号的错误
+pub fn parse_scores(content: &str) -> Result<Vec<ScoreRow>> {
+    let mut rows = Vec::new();
+    for (index, (line, fields)) in parse_records(content)?.into_iter().enumerate() {
+        if fields.len() != 3 {
+            return Err(MemexiaError::Validation(format!(
+                "row {}: expected 3 columns (node_id_a,node_id_b,score), got {}",
+                line,
+                fields.len()
+            )));
+        }
+        let score = match fields[2].trim().parse::<f64>() {
+            Ok(score) => score,
+            // 首行不是分数时视为表头
+            Err(_) if index == 0 => continue,
+            Err(_) => {
+                return Err(MemexiaError::Validation(format!(
+                    "row {}, column 'score': invalid number '{}'",
+                    line, fields[2]
+                )))
+            }
+        };
+        if !(0.0..=1.0).contains(&score) {
+            return Err(MemexiaError::Validation(format!(
+                "row {}, column 'score': {} is not between 0.0 and 1.0",
+                line, score
+            )));
+        }
+        rows.push(ScoreRow {
+            line,
+            a: fields[0].trim().to_string(),
+            b: fields[1].trim().to_string(),
+            score,
+        });
+    }
+    Ok(rows)
+}
+
+/// 导入计划
+#[derive(Debug, Clone, Default)]
+pub struct SimilarityPlan {
+    /// 要写入的建议边（新建或更新置信度）
+    pub edges: Vec<Edge>,
+    /// 其中更新已有建议边的数量
+    pub updated: usize,
+    /// 低于阈值的节点对数
+    pub below_threshold: usize,
+    /// 已有显式边而跳过的节点对数
+    pub already_linked: usize,
+    /// 重复（含对称）的行数
+    pub duplicates: usize,
+    /// 不存在的节点 ID 及其所在行号
+    pub unknown: Vec<(usize, String)>,
+}
+
+/// 计算要写入的建议边（不修改图谱）
+///
+/// # Arguments
+///
+/// * `graph` - 图存储
+/// * `rows` - 相似度行
+/// * `threshold` - 最低分数（含）
+pub fn plan(graph: &dyn GraphStorage, rows: &[ScoreRow], threshold: f64) -> Result<SimilarityPlan> {
+    let mut result = SimilarityPlan::default();
+
+    // 以无序节点对为键去重，保留分数最高的行
+    let mut pairs: BTreeMap<(String, String), &ScoreRow> = BTreeMap::new();
+    for row in rows {
+        if row.a == row.b {
+            continue;
+        }
+        let key = if row.a <= row.b {
+            (row.a.clone(), row.b.clone())
+        } else {
+            (row.b.clone(), row.a.clone())
+        };
+        match pairs.get(&key) {
+            Some(existing) => {
+                result.duplicates += 1;
+                if row.score > existing.score {
+                    pairs.insert(key, row);
+                }
+            }
+            None => {
+                pairs.insert(key, row);
+            }
+        }
+    }
+
+    for ((a, b), row) in pairs {
+        if row.score < threshold {
+            result.below_threshold += 1;
+            continue;
+        }
+
+        let mut known = true;
+        for id in [&row.a, &row.b] {
+            if !graph.node_exists(id)? {
+                result.unknown.push((row.line, id.clone()));
+                known = false;
+            }
+        }
+        if !known {
+            continue;
+        }
+
+        let existing: Vec<Edge> = graph
+            .get_edges_for_node(&a, EdgeDirection::Both)?
+            .into_iter()
+            .filter(|e| (e.from == a && e.to == b) || (e.from == b && e.to == a))
+            .collect();
+        if existing.iter().any(|e| e.source == EdgeSource::Explicit) {
+            result.already_linked += 1;
+            continue;
+        }
+
+        if let Some(edge) = existing
+            .iter()
+            .find(|e| e.source == EdgeSource::AI && e.relation == RelationType::AnalogousTo)
+        {
+            let mut edge = edge.clone();
+            edge.confidence = row.score;
+            result.edges.push(edge);
+            result.updated += 1;
+            continue;
+        }
+
+        // 每个方向只能有一条边：优先使用 CSV 中的方向，被其他建议占用时改用反方向
+        let free = [(&row.a, &row.b), (&row.b, &row.a)]
+            .into_iter()
+            .find(|(from, to)| !existing.iter().any(|e| &e.from == *from && &e.to == *to));
+        let Some((from, to)) = free else {
+            result.already_linked += 1;
+            continue;
+        };
+        let edge = Edge::builder(from.as_str(), to.as_str(), RelationType::AnalogousTo)
+            .source(EdgeSource::AI)
+            .confidence(row.score)
+            .description(format!("similarity score {}", row.score))
+            .build()?;
+        result.edges.push(edge);
+    }
+
+    Ok(result)
+}
+
+/// 写入计划中的建议边
+pub fn apply(graph: &dyn GraphStorage, plan: &SimilarityPlan) -> Result<()> {
+    for edge in &plan.edges {
+        graph.add_edge(edge)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::suggestions;
+    use crate::storage::{Node, NodeType, Storage};
+    use tempfile::TempDir;
+
+    const CSV: &str = "\
+node_id_a,node_id_b,score
+urn:memexia:file:a.md,urn:memexia:file:b.md,0.91
+urn:memexia:file:b.md,urn:memexia:file:a.md,0.88
+urn:memexia:file:a.md,urn:memexia:file:c.md,0.40
+urn:memexia:file:c.md,urn:memexia:file:ghost.md,0.95
+urn:memexia:file:b.md,urn:memexia:file:d.md,0.80
+urn:memexia:file:c.md,urn:memexia:file:d.md,0.85
+";
+
+    #[test]
+    fn test_parse_scores() {
+        let rows = parse_scores(CSV).unwrap();
+        assert_eq!(rows.len(), 6);
+        assert_eq!(rows[0].line, 2);
+        assert_eq!(rows[0].score, 0.91);
+
+        // 没有表头
+        assert_eq!(parse_scores("a,b,0.5\n").unwrap().len(), 1);
+        let err = parse_scores("a,b,0.5\na,b,high\n").unwrap_err();
+        assert!(err.to_string().contains("row 2, column 'score'"));
+        assert!(parse_scores("a,b,1.5\n").is_err());
+        assert!(parse_scores("a,b\n").is_err());
+    }
+
+    #[test]
+    fn test_import_scores() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let graph = storage.graph();
+        for name in ["a", "b", "c", "d"] {
+            let id = format!("urn:memexia:file:{}.md", name);
+            graph.add_node(&Node::new(id.as_str(), NodeType::Concept, name)).unwrap();
+        }
+        // c-d 已有显式边
+        graph
+            .add_edge(&Edge::builder("urn:memexia:file:d.md", "urn:memexia:file:c.md", RelationType::Supports)
+                .build()
+                .unwrap())
+            .unwrap();
+
+        let plan = plan(graph, &parse_scores(CSV).unwrap(), 0.75).unwrap();
+        assert_eq!(plan.duplicates, 1);
+        assert_eq!(plan.below_threshold, 1);
+        assert_eq!(plan.already_linked, 1);
+        assert_eq!(plan.unknown, [(5, "urn:memexia:file:ghost.md".to_string())]);
+        apply(graph, &plan).unwrap();
+
+        let suggested: Vec<(String, String, f64)> = suggestions::list(graph)
+            .unwrap()
+            .into_iter()
+            .map(|e| {
+                assert_eq!(e.relation, RelationType::AnalogousTo);
+                (e.from, e.to, e.confidence)
+            })
+            .collect();
+        assert_eq!(
+            suggested,
+            [
+                ("urn:memexia:file:a.md".to_string(), "urn:memexia:file:b.md".to_string(), 0.91),
+                ("urn:memexia:file:b.md".to_string(), "urn:memexia:file:d.md".to_string(), 0.80),
+            ]
+        );
+
+        // 再次导入只更新置信度，不产生重复
+        let rows = parse_scores("urn:memexia:file:b.md,urn:memexia:file:a.md,0.95\n").unwrap();
+        let plan = super::plan(graph, &rows, 0.75).unwrap();
+        assert_eq!(plan.updated, 1);
+        apply(graph, &plan).unwrap();
+        let edges = suggestions::list(graph).unwrap();
+        assert_eq!(edges.len(), 2);
+        assert_eq!(edges[0].from, "urn:memexia:file:a.md");
+        assert_eq!(edges[0].confidence, 0.95);
+    }
+}
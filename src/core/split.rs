@@ -0,0 +1,202 @@
+//! 拆分笔记模块（`memexia file split`）
+//!
+//! 把过长笔记中的一个 H2/H3 小节提取为新笔记：
+//!
+//! - 小节从标题行开始，到下一个同级或更高级标题为止（代码块中的 `#` 行不算标题）
+//! - 新笔记以标题命名（[`slugify`](super::note::slugify)），放在原笔记所在目录，
+//!   小节标题成为新笔记的 H1，下级标题保持原级别
+//! - 原笔记中的小节替换为 `[[新笔记|Contains]]`，或 `--embed` 时的 `![[新笔记]]`
+//!
+//! 标题按文本精确匹配；找不到时错误信息给出最接近的标题
+
+use crate::core::parser::frontmatter::escape_yaml_string;
+use crate::error::{MemexiaError, Result};
+use crate::storage::NodeType;
+
+/// 笔记中的一个小节
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    /// 标题级别（2 或 3）
+    pub level: usize,
+    /// 标题文本
+    pub title: String,
+    /// 标题行起始字节偏移
+    pub start: usize,
+    /// 小节结束字节偏移（下一个同级或更高级标题的起点，或文件末尾）
+    pub end: usize,
+}
+
+/// 一次拆分
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitOutcome {
+    /// 新笔记路径（相对仓库根目录）
+    pub path: String,
+    /// 新笔记的节点 ID
+    pub node_id: String,
+    /// 原笔记的节点 ID
+    pub original_id: String,
+}
+
+/// 笔记中的所有 ATX 标题：(级别, 文本, 行起始偏移)
+pub(crate) fn headings(content: &str) -> Vec<(usize, String, usize)> {
+    let mut result = Vec::new();
+    let mut in_code = false;
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let trimmed = line.trim_end();
+        if trimmed.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        let level = trimmed.chars().take_while(|c| *c == '#').count();
+        if !(1..=6).contains(&level) {
+            continue;
+        }
+        let Some(text) = trimmed[level..].strip_prefix(' ') else {
+            continue;
+        };
+        // 去掉可选的结尾 `#`
+        let text = text.trim_end_matches('#').trim();
+        result.push((level, text.to_string(), start));
+    }
+    result
+}
+
+/// 查找要拆分的 H2/H3 小节
+///
+/// # Returns
+///
+/// 找到的小节；找不到时返回带最接近标题提示的 `Validation` 错误
+pub fn find_section(content: &str, heading: &str) -> Result<Section> {
+    let headings = headings(content);
+    let heading = heading.trim();
+    let found = headings
+        .iter()
+        .position(|(level, text, _)| matches!(level, 2 | 3) && text == heading);
+    let Some(index) = found else {
+        let suggestion = headings
+            .iter()
+            .filter(|(level, _, _)| matches!(level, 2 | 3))
+            .map(|(_, text, _)| (edit_distance(&text.to_lowercase(), &heading.to_lowercase()), text))
+            .filter(|(distance, text)| *distance <= text.chars().count().max(heading.chars().count()) / 2)
+            .min_by_key(|(distance, _)| *distance);
+        let message = match suggestion {
+            Some((_, text)) => format!("No H2/H3 heading \"{}\"; did you mean \"{}\"?", heading, text),
+            None => format!("No H2/H3 heading \"{}\"", heading),
+        };
+        return Err(MemexiaError::Validation(message));
+    };
+
+    let (level, title, start) = headings[index].clone();
+    let end = headings[index + 1..]
+        .iter()
+        .find(|(next_level, _, _)| *next_level <= level)
+        .map_or(content.len(), |(_, _, offset)| *offset);
+    Ok(Section { level, title, start, end })
+}
+
+/// 拆分结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitContent {
+    /// 替换小节后的原笔记
+    pub original: String,
+    /// 新笔记（含 frontmatter）
+    pub extracted: String,
+}
+
+/// 拆分笔记内容
+///
+/// # Arguments
+///
+/// * `content` - 原笔记内容
+/// * `section` - 要提取的小节
+/// * `target` - 新笔记的链接目标（相对仓库根目录的路径）
+/// * `node_type` - 新笔记的类型
+/// * `embed` - 是否以 `![[...]]` 嵌入代替 `Contains` 链接
+pub fn split_content(content: &str, section: &Section, target: &str, node_type: &NodeType, embed: bool) -> SplitContent {
+    let text = &content[section.start..section.end];
+    // 标题行之后的正文
+    let body = text.split_once('\n').map_or("", |(_, rest)| rest);
+
+    let link = if embed {
+        format!("![[{}]]", target)
+    } else {
+        format!("[[{}|Contains]]", target)
+    };
+    // 小节之后还有内容时保留一个空行分隔
+    let separator = if section.end < content.len() { "\n\n" } else { "\n" };
+    let original = format!("{}{}{}{}", &content[..section.start], link, separator, &content[section.end..]);
+
+    let extracted = format!(
+        "---\ntitle: \"{}\"\ntype: {}\n---\n\n# {}\n{}",
+        escape_yaml_string(&section.title),
+        node_type,
+        section.title,
+        body
+    );
+    let extracted = format!("{}\n", extracted.trim_end());
+    SplitContent { original, extracted }
+}
+
+/// 编辑距离（按字符计）
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb { previous } else { 1 + previous.min(row[j]).min(current) };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "# Mind\n\nIntro.\n\n## Free Will\n\nSee [[determinism.md]].\n\n```\n## not a heading\n```\n\n### Compatibilism\n\nDetails.\n\n## Consciousness\n\nQualia.\n";
+
+    #[test]
+    fn test_find_section() {
+        let section = find_section(NOTE, "Free Will").unwrap();
+        assert_eq!(section.level, 2);
+        assert!(NOTE[section.start..].starts_with("## Free Will\n"));
+        assert!(NOTE[section.end..].starts_with("## Consciousness\n"));
+
+        // H3 小节在下一个 H2 处结束
+        let section = find_section(NOTE, "Compatibilism").unwrap();
+        assert_eq!(&NOTE[section.start..section.end], "### Compatibilism\n\nDetails.\n\n");
+
+        let err = find_section(NOTE, "free wil").unwrap_err().to_string();
+        assert!(err.contains("did you mean \"Free Will\""), "{}", err);
+        assert!(!find_section(NOTE, "Mind").unwrap_err().to_string().contains("did you mean"));
+        assert!(find_section(NOTE, "not a heading").is_err());
+    }
+
+    #[test]
+    fn test_split_content() {
+        let section = find_section(NOTE, "Free Will").unwrap();
+        let split = split_content(NOTE, &section, "notes/free-will.md", &NodeType::Question, false);
+        assert_eq!(
+            split.original,
+            "# Mind\n\nIntro.\n\n[[notes/free-will.md|Contains]]\n\n## Consciousness\n\nQualia.\n"
+        );
+        assert_eq!(
+            split.extracted,
+            "---\ntitle: \"Free Will\"\ntype: Question\n---\n\n# Free Will\n\nSee [[determinism.md]].\n\n```\n## not a heading\n```\n\n### Compatibilism\n\nDetails.\n"
+        );
+
+        let last = find_section(NOTE, "Consciousness").unwrap();
+        let split = split_content(NOTE, &last, "consciousness.md", &NodeType::Concept, true);
+        assert!(split.original.ends_with("Details.\n\n![[consciousness.md]]\n"));
+    }
+}
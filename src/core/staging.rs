@@ -0,0 +1,284 @@
+//! 暂存区模块
+//!
+//! `.memexia/index` 记录 `memexia add` 暂存的文件，每行一个 JSON 条目：
+//!
+//! ```text
+//! {"path":"notes/a.md","hash":"<sha256>","added":"2026-01-02T03:04:05Z"}
+//! ```
+//!
+//! - `path`：相对仓库根目录的路径
+//! - `hash`：暂存时的内容哈希，`status` 据此标出暂存后又修改过的文件
+//! - `added`：暂存时间
+//!
+//! 旧版本每行只有一个路径，读取时按没有哈希和时间的条目处理，下次写入时转换为新格式
+//!
+//! 读-改-写期间持有仓库锁（[`RepoLock`]），写入先写临时文件再原子重命名：
+//! 并发的 `memexia add`（如编辑器插件触发）不会丢失条目，也不会读到写了一半的文件
+
+use crate::error::{MemexiaError, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// 暂存区文件（相对仓库根目录）
+pub const INDEX_FILE: &str = ".memexia/index";
+
+/// 仓库锁文件（相对仓库根目录）
+pub const LOCK_FILE: &str = ".memexia/lock";
+
+/// 等待仓库锁的最长时间
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 重试获取锁的间隔
+const LOCK_RETRY: Duration = Duration::from_millis(10);
+
+/// 超过此时间未释放的锁视为崩溃进程遗留，直接移除
+const STALE_LOCK: Duration = Duration::from_secs(60);
+
+/// 暂存区中的一个文件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StagedEntry {
+    /// 相对仓库根目录的路径（`/` 分隔）
+    pub path: String,
+    /// 暂存时的内容哈希（旧格式条目没有）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    /// 暂存时间（旧格式条目没有）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added: Option<DateTime<Utc>>,
+}
+
+/// 暂存区
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StagingIndex {
+    entries: Vec<StagedEntry>,
+}
+
+impl StagingIndex {
+    /// 读取仓库的暂存区，文件不存在时为空
+    pub fn load(root: &Path) -> Result<Self> {
+        match fs::read_to_string(root.join(INDEX_FILE)) {
+            Ok(content) => Self::parse(&content),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// 解析暂存区内容，兼容每行一个路径的旧格式
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut entries = Vec::new();
+        for line in content.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let entry = if line.starts_with('{') {
+                serde_json::from_str(line)?
+            } else {
+                StagedEntry { path: line.to_string(), hash: None, added: None }
+            };
+            entries.push(entry);
+        }
+        Ok(Self { entries })
+    }
+
+    /// 写入暂存区（先写临时文件再重命名）
+    ///
+    /// 调用方应持有 [`RepoLock`]，见 [`update`](Self::update)
+    pub fn save(&self, root: &Path) -> Result<()> {
+        let mut content = String::new();
+        for entry in &self.entries {
+            content.push_str(&serde_json::to_string(entry)?);
+            content.push('\n');
+        }
+        let path = root.join(INDEX_FILE);
+        let tmp = path.with_extension(format!("tmp-{}", std::process::id()));
+        fs::write(&tmp, content)?;
+        if let Err(e) = fs::rename(&tmp, &path) {
+            let _ = fs::remove_file(&tmp);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    /// 在仓库锁保护下读取、修改并写回暂存区
+    ///
+    /// # Returns
+    ///
+    /// 修改函数的返回值
+    pub fn update<T>(root: &Path, f: impl FnOnce(&mut Self) -> T) -> Result<T> {
+        let _lock = RepoLock::acquire(root)?;
+        let mut index = Self::load(root)?;
+        let result = f(&mut index);
+        index.save(root)?;
+        Ok(result)
+    }
+
+    /// 所有条目（按暂存顺序）
+    pub fn entries(&self) -> &[StagedEntry] {
+        &self.entries
+    }
+
+    /// 暂存区是否为空
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 是否已暂存该路径
+    pub fn contains(&self, path: &str) -> bool {
+        self.entries.iter().any(|entry| entry.path == path)
+    }
+
+    /// 暂存文件；已暂存时更新哈希和时间，位置不变
+    pub fn stage(&mut self, path: &str, hash: &str, now: DateTime<Utc>) {
+        let entry = StagedEntry { path: path.to_string(), hash: Some(hash.to_string()), added: Some(now) };
+        match self.entries.iter_mut().find(|existing| existing.path == path) {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    /// 移除已提交的条目
+    ///
+    /// 只移除路径和哈希都与提交时一致的条目；提交期间重新暂存（内容已变）的文件保留
+    pub fn remove_committed(&mut self, committed: &[StagedEntry]) {
+        self.entries.retain(|entry| {
+            !committed
+                .iter()
+                .any(|done| done.path == entry.path && done.hash == entry.hash)
+        });
+    }
+}
+
+/// 仓库锁
+///
+/// 以独占创建 `.memexia/lock` 实现，进程内的多个线程与多个进程之间同样互斥；
+/// 释放时删除锁文件
+#[derive(Debug)]
+pub struct RepoLock {
+    path: PathBuf,
+}
+
+impl RepoLock {
+    /// 获取仓库锁，最多等待 10 秒
+    ///
+    /// 超过 60 秒未释放的锁视为崩溃进程遗留并被移除；等待超时返回
+    /// [`MemexiaError::RepositoryLocked`]
+    pub fn acquire(root: &Path) -> Result<Self> {
+        let path = root.join(LOCK_FILE);
+        let deadline = Instant::now() + LOCK_TIMEOUT;
+        loop {
+            match fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Ok(Self { path }),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e.into()),
+            }
+            if is_stale(&path) {
+                let _ = fs::remove_file(&path);
+                continue;
+            }
+            if Instant::now() >= deadline {
+                return Err(MemexiaError::RepositoryLocked { path });
+            }
+            std::thread::sleep(LOCK_RETRY);
+        }
+    }
+}
+
+impl Drop for RepoLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// 锁文件是否已遗留过久
+fn is_stale(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age > STALE_LOCK)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn repo_dir() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join(".memexia")).unwrap();
+        temp
+    }
+
+    #[test]
+    fn test_load_legacy_format() {
+        let temp = repo_dir();
+        let root = temp.path();
+        fs::write(root.join(INDEX_FILE), "notes/a.md\n\nb.md\n").unwrap();
+
+        let index = StagingIndex::load(root).unwrap();
+        let paths: Vec<&str> = index.entries().iter().map(|entry| entry.path.as_str()).collect();
+        assert_eq!(paths, ["notes/a.md", "b.md"]);
+        assert!(index.entries().iter().all(|entry| entry.hash.is_none() && entry.added.is_none()));
+
+        // 写回后转换为 JSON 行，再次读取结果不变
+        let now = Utc::now();
+        StagingIndex::update(root, |index| index.stage("b.md", "abc", now)).unwrap();
+        let content = fs::read_to_string(root.join(INDEX_FILE)).unwrap();
+        assert!(content.lines().all(|line| line.starts_with('{')), "{}", content);
+        let index = StagingIndex::load(root).unwrap();
+        assert_eq!(index.entries().len(), 2);
+        assert_eq!(index.entries()[1].hash.as_deref(), Some("abc"));
+        assert!(!root.join(LOCK_FILE).exists());
+    }
+
+    #[test]
+    fn test_concurrent_updates_keep_all_entries() {
+        let temp = repo_dir();
+        let root = temp.path();
+
+        std::thread::scope(|scope| {
+            for thread in 0..2 {
+                scope.spawn(move || {
+                    for i in 0..25 {
+                        let path = format!("t{}/{}.md", thread, i);
+                        StagingIndex::update(root, |index| index.stage(&path, "hash", Utc::now())).unwrap();
+                    }
+                });
+            }
+        });
+
+        let index = StagingIndex::load(root).unwrap();
+        assert_eq!(index.entries().len(), 50);
+        assert!(index.contains("t0/24.md") && index.contains("t1/0.md"));
+    }
+
+    #[test]
+    fn test_remove_committed_keeps_restaged_files() {
+        let now = Utc::now();
+        let mut index = StagingIndex::default();
+        index.stage("a.md", "1", now);
+        index.stage("b.md", "1", now);
+        let committed = index.entries().to_vec();
+        // 提交期间 b.md 被修改并重新暂存
+        index.stage("b.md", "2", now);
+        index.stage("c.md", "1", now);
+
+        index.remove_committed(&committed);
+        let paths: Vec<&str> = index.entries().iter().map(|entry| entry.path.as_str()).collect();
+        assert_eq!(paths, ["b.md", "c.md"]);
+    }
+
+    #[test]
+    fn test_stale_lock_is_removed() {
+        let temp = repo_dir();
+        let root = temp.path();
+        let lock = fs::File::create(root.join(LOCK_FILE)).unwrap();
+        lock.set_modified(SystemTime::now() - STALE_LOCK * 2).unwrap();
+
+        let acquired = RepoLock::acquire(root).unwrap();
+        assert!(root.join(LOCK_FILE).exists());
+        drop(acquired);
+        assert!(!root.join(LOCK_FILE).exists());
+    }
+}
@@ -0,0 +1,229 @@
+//! 仓库统计模块
+//!
+//! 为 `memexia stats` 计算笔记库级别的统计数据：
+//!
+//! - 已索引笔记数、总字数和平均字数（来自节点的 `memexia:wordCount` 属性）
+//! - 标签数
+//! - 按语言统计的笔记数（来自节点的 `memexia:language` 属性，没有语言的笔记不计入）
+//! - 每月新建笔记数（来自节点 `created_at`，可由 frontmatter `created` 指定）
+//! - 每月提交数（来自 Git 日志）
+
+use crate::storage::Node;
+use crate::vcs::CommitInfo;
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+
+/// 火花线字符（由低到高）
+const SPARK_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// 仓库统计
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RepoStats {
+    /// 已索引的笔记数（不含占位节点）
+    pub notes: usize,
+    /// 总字数
+    pub total_words: u64,
+    /// 平均每篇笔记字数
+    pub average_words: f64,
+    /// 不同标签数
+    pub tags: usize,
+    /// 各语言的笔记数（按语言代码排序）
+    pub languages: BTreeMap<String, usize>,
+    /// 每月新建笔记数（按月份连续，空缺月份为 0）
+    pub notes_per_month: Vec<MonthCount>,
+    /// 每月提交数（按月份连续，空缺月份为 0）
+    pub commits_per_month: Vec<MonthCount>,
+}
+
+/// 单月计数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MonthCount {
+    /// 月份（`YYYY-MM`）
+    pub month: String,
+    /// 计数
+    pub count: usize,
+}
+
+impl RepoStats {
+    /// 计算统计数据
+    ///
+    /// # Arguments
+    ///
+    /// * `nodes` - 图谱中的所有节点（没有字数的占位节点不计入笔记）
+    /// * `commits` - 提交历史
+    pub fn compute(nodes: &[Node], commits: &[CommitInfo]) -> Self {
+        let notes: Vec<&Node> = nodes.iter().filter(|n| n.word_count().is_some()).collect();
+
+        let total_words: u64 = notes.iter().filter_map(|n| n.word_count()).sum();
+        let average_words = if notes.is_empty() {
+            0.0
+        } else {
+            total_words as f64 / notes.len() as f64
+        };
+
+        let tags: HashSet<&str> = notes.iter().flat_map(|n| n.tags.iter().map(String::as_str)).collect();
+        let mut languages = BTreeMap::new();
+        for language in notes.iter().filter_map(|n| n.language()) {
+            *languages.entry(language.to_string()).or_default() += 1;
+        }
+
+        let note_months = notes.iter().map(|n| n.created_at.date_naive());
+        let commit_months = commits.iter().filter_map(|c| {
+            NaiveDate::parse_from_str(c.timestamp.get(..10)?, "%Y-%m-%d").ok()
+        });
+
+        Self {
+            notes: notes.len(),
+            total_words,
+            average_words,
+            tags: tags.len(),
+            languages,
+            notes_per_month: month_histogram(note_months),
+            commits_per_month: month_histogram(commit_months),
+        }
+    }
+}
+
+/// 按月份统计日期
+///
+/// 结果从最早月份连续到最晚月份，中间没有数据的月份计为 0
+pub fn month_histogram(dates: impl IntoIterator<Item = NaiveDate>) -> Vec<MonthCount> {
+    let mut counts: BTreeMap<(i32, u32), usize> = BTreeMap::new();
+    for date in dates {
+        *counts.entry((date.year(), date.month())).or_default() += 1;
+    }
+
+    let (Some(&first), Some(&last)) = (counts.keys().next(), counts.keys().next_back()) else {
+        return Vec::new();
+    };
+
+    let mut result = Vec::new();
+    let (mut year, mut month) = first;
+    while (year, month) <= last {
+        result.push(MonthCount {
+            month: format!("{:04}-{:02}", year, month),
+            count: counts.get(&(year, month)).copied().unwrap_or(0),
+        });
+        if month == 12 {
+            year += 1;
+            month = 1;
+        } else {
+            month += 1;
+        }
+    }
+    result
+}
+
+/// 生成火花线
+///
+/// 每个值对应一个字符，按最大值等比缩放；0 显示为最低的 `▁`
+pub fn sparkline(values: &[usize]) -> String {
+    let max = values.iter().copied().max().unwrap_or(0);
+    values
+        .iter()
+        .map(|&v| {
+            let level = (v * (SPARK_CHARS.len() - 1)).checked_div(max).unwrap_or(0);
+            SPARK_CHARS[level]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Indexer;
+    use crate::storage::Storage;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn commit(timestamp: &str) -> CommitInfo {
+        CommitInfo {
+            oid: "0".repeat(40),
+            message: "msg".to_string(),
+            trailers: Vec::new(),
+            graph_hash: None,
+            author: "A <a@example.com>".to_string(),
+            timestamp: timestamp.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_stats_over_fixture() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let storage = Storage::init(root).unwrap();
+
+        // 正文字数：3 + 5 + 4（中文每字计一词）
+        fs::write(
+            root.join("notes/a.md"),
+            "---\ncreated: 2024-01-10\ntags: [mind]\n---\nOne two three",
+        )
+        .unwrap();
+        fs::write(
+            root.join("notes/b.md"),
+            "---\ncreated: 2024-03-02\ntags: [mind, ethics]\n---\nfour five six [[Seven]] eight",
+        )
+        .unwrap();
+        fs::write(root.join("notes/c.md"), "---\ndate: 2024-03-20\n---\n自由意志").unwrap();
+
+        let indexer = Indexer::new(storage.clone());
+        let summary = indexer.index_all(root).unwrap();
+        assert_eq!(summary.files_indexed, 3);
+
+        let nodes = storage.graph().get_all_nodes().unwrap();
+        let commits = vec![commit("2024-02-01 10:00:00"), commit("2024-02-15 09:00:00")];
+        let stats = RepoStats::compute(&nodes, &commits);
+
+        // 占位节点 Seven 不计入笔记
+        assert_eq!(stats.notes, 3);
+        assert_eq!(stats.total_words, 12);
+        assert_eq!(stats.average_words, 4.0);
+        assert_eq!(stats.tags, 2);
+
+        let notes: Vec<(&str, usize)> = stats
+            .notes_per_month
+            .iter()
+            .map(|m| (m.month.as_str(), m.count))
+            .collect();
+        assert_eq!(notes, vec![("2024-01", 1), ("2024-02", 0), ("2024-03", 2)]);
+
+        assert_eq!(stats.commits_per_month, vec![MonthCount { month: "2024-02".to_string(), count: 2 }]);
+    }
+
+    #[test]
+    fn test_word_count_updated_on_reindex() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let storage = Storage::init(root).unwrap();
+        let indexer = Indexer::new(storage.clone());
+        let path = root.join("notes/a.md");
+
+        fs::write(&path, "one two").unwrap();
+        indexer.index_file(&path).unwrap();
+        fs::write(&path, "one two three four").unwrap();
+        indexer.index_file(&path).unwrap();
+
+        let node = storage.graph().get_node("urn:memexia:file:notes/a.md").unwrap().unwrap();
+        assert_eq!(node.word_count(), Some(4));
+    }
+
+    #[test]
+    fn test_month_histogram_spans_years() {
+        let dates = [
+            NaiveDate::from_ymd_opt(2023, 11, 5).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        ];
+        let months: Vec<String> = month_histogram(dates).into_iter().map(|m| m.month).collect();
+        assert_eq!(months, vec!["2023-11", "2023-12", "2024-01"]);
+        assert!(month_histogram([]).is_empty());
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[0, 1, 7]), "▁▂█");
+        assert_eq!(sparkline(&[2, 2]), "██");
+        assert_eq!(sparkline(&[0, 0]), "▁▁");
+        assert_eq!(sparkline(&[]), "");
+    }
+}
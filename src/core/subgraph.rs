@@ -0,0 +1,147 @@
+//! 子图选择模块（`memexia export --tag` / `--query`）
+//!
+//! 先确定节点集合：带指定标签的节点，或用户 SPARQL SELECT 查询中 `?node` 绑定的节点；
+//! 再取两端都在集合内的边。启用边界时，一端在集合外的边也保留，集合外的端点
+//! 以只有类型和标题的存根节点表示（不含正文、标签和元数据）
+//!
+//! 已软删除的节点和墓碑状态的边不参与选择
+
+use crate::error::{MemexiaError, Result};
+use crate::storage::{Edge, GraphStorage, Node};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// 查询中标识节点的变量
+pub const NODE_VARIABLE: &str = "?node";
+
+/// 选出的子图
+#[derive(Debug, Clone, Default)]
+pub struct Subgraph {
+    /// 集合内的节点（按 ID 排序）
+    pub nodes: Vec<Node>,
+    /// 边界存根：集合外、与集合内节点相连的节点（按 ID 排序）
+    pub stubs: Vec<Node>,
+    /// 保留的边
+    pub edges: Vec<Edge>,
+}
+
+impl Subgraph {
+    /// 集合内节点和存根节点（按 ID 排序）
+    pub fn all_nodes(&self) -> Vec<&Node> {
+        let mut nodes: Vec<&Node> = self.nodes.iter().chain(&self.stubs).collect();
+        nodes.sort_by(|a, b| a.id.cmp(&b.id));
+        nodes
+    }
+
+    /// 是否为存根节点
+    pub fn is_stub(&self, id: &str) -> bool {
+        self.stubs.iter().any(|stub| stub.id == id)
+    }
+}
+
+/// 带指定标签的节点 ID
+pub fn select_by_tag(graph: &dyn GraphStorage, tag: &str) -> Result<BTreeSet<String>> {
+    Ok(graph
+        .list_nodes()?
+        .into_iter()
+        .filter(|node| node.tags.iter().any(|t| t == tag))
+        .map(|node| node.id)
+        .collect())
+}
+
+/// SPARQL SELECT 查询中 `?node` 绑定的节点 ID
+///
+/// 查询没有投影 `?node` 时返回 `Validation` 错误；绑定到不存在的节点的值被忽略
+pub fn select_by_query(graph: &dyn GraphStorage, sparql: &str) -> Result<BTreeSet<String>> {
+    let result = graph.query(sparql)?;
+    if !result.variables.iter().any(|v| v == NODE_VARIABLE) {
+        return Err(MemexiaError::Validation(format!(
+            "The query must select {} (e.g. SELECT ?node WHERE {{ ... }})",
+            NODE_VARIABLE
+        )));
+    }
+    Ok(result
+        .bindings
+        .into_iter()
+        .filter_map(|mut row| row.remove(NODE_VARIABLE))
+        .collect())
+}
+
+/// 按节点集合取子图
+///
+/// # Arguments
+///
+/// * `graph` - 图存储
+/// * `ids` - 节点集合
+/// * `include_boundary` - 是否保留连到集合外节点的边（外部端点作为存根）
+pub fn extract(graph: &dyn GraphStorage, ids: &BTreeSet<String>, include_boundary: bool) -> Result<Subgraph> {
+    let all: BTreeMap<String, Node> = graph.list_nodes()?.into_iter().map(|node| (node.id.clone(), node)).collect();
+
+    let mut subgraph = Subgraph {
+        nodes: ids.iter().filter_map(|id| all.get(id).cloned()).collect(),
+        ..Default::default()
+    };
+    let mut stub_ids = BTreeSet::new();
+    for edge in graph.list_edges()? {
+        let (from_in, to_in) = (ids.contains(&edge.from), ids.contains(&edge.to));
+        if from_in && to_in {
+            subgraph.edges.push(edge);
+        } else if include_boundary && (from_in || to_in) {
+            let outside = if from_in { &edge.to } else { &edge.from };
+            if all.contains_key(outside) {
+                stub_ids.insert(outside.clone());
+                subgraph.edges.push(edge);
+            }
+        }
+    }
+    subgraph.stubs = stub_ids.iter().map(|id| stub(&all[id])).collect();
+    Ok(subgraph)
+}
+
+/// 集合外节点的存根：只保留 ID、类型、标题和时间
+fn stub(node: &Node) -> Node {
+    let mut stub = Node::new(&node.id, node.node_type.clone(), &node.title);
+    stub.created_at = node.created_at;
+    stub.updated_at = node.updated_at;
+    stub
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{NodeType, RelationType, Storage};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_extract_interior_and_boundary() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let graph = storage.graph();
+        for (id, tags) in [("a", vec!["哲学"]), ("b", vec!["哲学", "x"]), ("c", vec![])] {
+            let node = Node::builder(format!("urn:memexia:file:{}.md", id), NodeType::Concept, id)
+                .content("body")
+                .tags(tags)
+                .build()
+                .unwrap();
+            graph.add_node(&node).unwrap();
+        }
+        let edge = |from: &str, to: &str| {
+            let (from, to) = (format!("urn:memexia:file:{}.md", from), format!("urn:memexia:file:{}.md", to));
+            Edge::new(Edge::make_id(&from, &to), from, to, RelationType::Supports)
+        };
+        graph.add_edge(&edge("a", "b")).unwrap();
+        graph.add_edge(&edge("b", "c")).unwrap();
+
+        let ids = select_by_tag(graph, "哲学").unwrap();
+        let inner = extract(graph, &ids, false).unwrap();
+        assert_eq!(inner.nodes.len(), 2);
+        assert!(inner.stubs.is_empty());
+        assert_eq!(inner.edges.len(), 1);
+
+        let outer = extract(graph, &ids, true).unwrap();
+        assert_eq!(outer.edges.len(), 2);
+        assert_eq!(outer.stubs.len(), 1);
+        assert!(outer.is_stub("urn:memexia:file:c.md"));
+        assert!(outer.stubs[0].content.is_none() && outer.stubs[0].tags.is_empty());
+        assert_eq!(outer.all_nodes().len(), 3);
+    }
+}
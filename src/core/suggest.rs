@@ -0,0 +1,340 @@
+//! 链接推荐模块
+//!
+//! 不依赖 LLM，基于启发式规则为尚未连接的笔记推荐关系：
+//!
+//! - 共享标签：两篇笔记至少共享 `min_shared_tags` 个标签
+//! - 标题提及：一篇笔记的正文按词边界完整出现另一篇笔记的标题
+//!
+//! 推荐结果可以通过 [`apply`] 写入为 `EdgeSource::AI` 边，
+//! 再用 [`suggestions`](super::suggestions) 模块接受或拒绝
+
+use crate::core::parser::is_cjk;
+use crate::error::Result;
+use crate::storage::{Edge, EdgeSource, GraphStorage, Node, RelationType};
+use std::collections::{BTreeMap, HashSet};
+
+/// 默认最少共享标签数
+pub const DEFAULT_MIN_SHARED_TAGS: usize = 2;
+
+/// 参与标题提及检测的最短标题长度（字符数），过短的标题容易误匹配
+const MIN_TITLE_CHARS: usize = 3;
+
+/// 标题提及的置信度
+const TITLE_MENTION_CONFIDENCE: f64 = 0.7;
+
+/// 推荐原因
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProposalReason {
+    /// 共享的标签
+    SharedTags(Vec<String>),
+    /// 源笔记正文提及目标笔记标题
+    TitleMention,
+}
+
+impl std::fmt::Display for ProposalReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProposalReason::SharedTags(tags) => write!(f, "shared tags: {}", tags.join(", ")),
+            ProposalReason::TitleMention => write!(f, "title mention"),
+        }
+    }
+}
+
+/// 推荐的边
+#[derive(Debug, Clone, PartialEq)]
+pub struct Proposal {
+    /// 源节点 ID
+    pub from: String,
+    /// 目标节点 ID
+    pub to: String,
+    /// 启发式置信度 (0.0 - 1.0)
+    pub confidence: f64,
+    /// 推荐原因
+    pub reasons: Vec<ProposalReason>,
+}
+
+impl Proposal {
+    /// 转换为 AI 来源的 `RelatedTo` 边，原因写入描述
+    pub fn to_edge(&self) -> Result<Edge> {
+        let description = self.reasons.iter().map(|r| r.to_string()).collect::<Vec<_>>().join("; ");
+        Edge::builder(&self.from, &self.to, RelationType::RelatedTo)
+            .source(EdgeSource::AI)
+            .confidence(self.confidence)
+            .description(description)
+            .build()
+    }
+}
+
+/// 计算推荐链接
+///
+/// 只考虑已索引且未归档的笔记（带字数的节点），已经有任意方向连接的笔记对会被跳过
+///
+/// # Arguments
+///
+/// * `graph` - 图存储
+/// * `min_shared_tags` - 按共享标签推荐时的最少标签数
+///
+/// # Returns
+///
+/// 按置信度降序排列的推荐
+pub fn propose(graph: &dyn GraphStorage, min_shared_tags: usize) -> Result<Vec<Proposal>> {
+    propose_filtered(graph, min_shared_tags, false)
+}
+
+/// 计算推荐链接，可选包含已归档的笔记
+///
+/// # Arguments
+///
+/// * `graph` - 图存储
+/// * `min_shared_tags` - 按共享标签推荐时的最少标签数
+/// * `include_archived` - 是否包含已归档的笔记（[`propose`] 默认排除）
+pub fn propose_filtered(graph: &dyn GraphStorage, min_shared_tags: usize, include_archived: bool) -> Result<Vec<Proposal>> {
+    let mut notes: Vec<Node> = graph
+        .get_all_nodes()?
+        .into_iter()
+        .filter(|n| n.word_count().is_some() && (include_archived || !n.is_archived()))
+        .collect();
+    notes.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let connected: HashSet<(String, String)> = graph
+        .get_all_edges()?
+        .into_iter()
+        .map(|e| pair_key(&e.from, &e.to))
+        .collect();
+
+    // 以无序节点对为键合并多种原因
+    let mut proposals: BTreeMap<(String, String), Proposal> = BTreeMap::new();
+    let mut add = |from: &Node, to: &Node, confidence: f64, reason: ProposalReason| {
+        let key = pair_key(&from.id, &to.id);
+        if connected.contains(&key) {
+            return;
+        }
+        proposals
+            .entry(key)
+            .and_modify(|p| {
+                // 多个独立证据按 noisy-or 合并
+                p.confidence = 1.0 - (1.0 - p.confidence) * (1.0 - confidence);
+                p.reasons.push(reason.clone());
+            })
+            .or_insert_with(|| Proposal {
+                from: from.id.clone(),
+                to: to.id.clone(),
+                confidence,
+                reasons: vec![reason.clone()],
+            });
+    };
+
+    for (i, a) in notes.iter().enumerate() {
+        for b in &notes[i + 1..] {
+            let mut shared: Vec<String> = a.tags.iter().filter(|t| b.tags.contains(t)).cloned().collect();
+            shared.sort();
+            if min_shared_tags > 0 && shared.len() >= min_shared_tags {
+                add(a, b, shared_tags_confidence(shared.len()), ProposalReason::SharedTags(shared));
+            }
+        }
+    }
+
+    for source in &notes {
+        let Some(content) = &source.content else {
+            continue;
+        };
+        for target in &notes {
+            if target.id != source.id && mentions_title(content, &target.title) {
+                add(source, target, TITLE_MENTION_CONFIDENCE, ProposalReason::TitleMention);
+            }
+        }
+    }
+
+    let mut proposals: Vec<Proposal> = proposals.into_values().collect();
+    for proposal in &mut proposals {
+        proposal.confidence = (proposal.confidence * 100.0).round() / 100.0;
+    }
+    proposals.sort_by(|a, b| {
+        b.confidence
+            .total_cmp(&a.confidence)
+            .then_with(|| (&a.from, &a.to).cmp(&(&b.from, &b.to)))
+    });
+    Ok(proposals)
+}
+
+/// 将推荐写入为 AI 来源的边
+///
+/// # Returns
+///
+/// 写入的边
+pub fn apply(graph: &dyn GraphStorage, proposals: &[Proposal]) -> Result<Vec<Edge>> {
+    let mut edges = Vec::new();
+    for proposal in proposals {
+        let edge = proposal.to_edge()?;
+        graph.add_edge(&edge)?;
+        edges.push(edge);
+    }
+    Ok(edges)
+}
+
+/// 共享标签推荐的置信度：2 个标签 0.6，每多一个加 0.1，最高 0.9
+fn shared_tags_confidence(shared: usize) -> f64 {
+    (0.4 + 0.1 * shared as f64).min(0.9)
+}
+
+/// 无序节点对键
+fn pair_key(a: &str, b: &str) -> (String, String) {
+    if a <= b {
+        (a.to_string(), b.to_string())
+    } else {
+        (b.to_string(), a.to_string())
+    }
+}
+
+/// 正文是否按词边界提及标题（不区分大小写）
+///
+/// 标题首尾为字母数字时要求相邻字符不是字母数字；
+/// 中日韩文字没有词边界，直接按子串匹配
+pub fn mentions_title(content: &str, title: &str) -> bool {
+    let title = title.trim();
+    if title.chars().count() < MIN_TITLE_CHARS {
+        return false;
+    }
+
+    let content = content.to_lowercase();
+    let title = title.to_lowercase();
+    let needs_left = title.chars().next().is_some_and(is_word_char);
+    let needs_right = title.chars().next_back().is_some_and(is_word_char);
+
+    content.match_indices(&title).any(|(start, matched)| {
+        let before = content[..start].chars().next_back();
+        let after = content[start + matched.len()..].chars().next();
+        (!needs_left || !before.is_some_and(is_word_char))
+            && (!needs_right || !after.is_some_and(is_word_char))
+    })
+}
+
+/// 参与词边界判断的字符（中日韩文字除外的字母数字）
+fn is_word_char(c: char) -> bool {
+    (c.is_alphanumeric() || c == '_') && !is_cjk(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Indexer;
+    use crate::storage::Storage;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// 恰好产生两条推荐的笔记库：
+    ///
+    /// - free-will / agency 共享两个标签
+    /// - essay 提及 Determinism 标题
+    ///
+    /// 其余笔记覆盖不应推荐的情况
+    fn fixture() -> (TempDir, Storage) {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let storage = Storage::init(root).unwrap();
+        let indexer = Indexer::new(storage.clone());
+
+        let notes = [
+            ("free-will.md", "---\ntitle: Free Will\ntags: [mind, ethics, choice]\n---\nBody"),
+            ("agency.md", "---\ntitle: Agency\ntags: [mind, ethics, action]\n---\nBody"),
+            // 只共享一个标签，不推荐
+            ("habits.md", "---\ntitle: Habits\ntags: [mind]\n---\nBody"),
+            ("determinism.md", "---\ntitle: Determinism\n---\nBody"),
+            // 提及 Determinism；Free Willpower 不是 Free Will 的完整词；AI 标题过短
+            (
+                "essay.md",
+                "---\ntitle: Essay\n---\nHard determinism, AI, and Free Willpower as a concept.",
+            ),
+            ("ai.md", "---\ntitle: AI\n---\nBody"),
+            // 已有链接的笔记对不重复推荐
+            ("linked.md", "---\ntitle: Linked\ntags: [ethics, action]\n---\nSee [[agency.md]]"),
+        ];
+        for (name, content) in notes {
+            let path = root.join(name);
+            fs::write(&path, content).unwrap();
+            indexer.index_file(&path).unwrap();
+        }
+
+        (temp_dir, storage)
+    }
+
+    fn pairs(proposals: &[Proposal]) -> Vec<(&str, &str)> {
+        proposals.iter().map(|p| (p.from.as_str(), p.to.as_str())).collect()
+    }
+
+    #[test]
+    fn test_propose_fixture() {
+        let (_temp, storage) = fixture();
+        let proposals = propose(storage.graph(), DEFAULT_MIN_SHARED_TAGS).unwrap();
+
+        assert_eq!(
+            pairs(&proposals),
+            vec![
+                ("urn:memexia:file:essay.md", "urn:memexia:file:determinism.md"),
+                ("urn:memexia:file:agency.md", "urn:memexia:file:free-will.md"),
+            ]
+        );
+        assert_eq!(proposals[0].reasons, vec![ProposalReason::TitleMention]);
+        assert_eq!(proposals[1].confidence, 0.6);
+        assert_eq!(
+            proposals[1].reasons,
+            vec![ProposalReason::SharedTags(vec!["ethics".to_string(), "mind".to_string()])]
+        );
+
+        // 提高共享标签阈值后只剩标题提及
+        let proposals = propose(storage.graph(), 3).unwrap();
+        assert_eq!(
+            pairs(&proposals),
+            vec![("urn:memexia:file:essay.md", "urn:memexia:file:determinism.md")]
+        );
+    }
+
+    #[test]
+    fn test_apply_is_idempotent() {
+        let (_temp, storage) = fixture();
+        let graph = storage.graph();
+
+        let proposals = propose(graph, DEFAULT_MIN_SHARED_TAGS).unwrap();
+        let edges = apply(graph, &proposals).unwrap();
+        assert_eq!(edges.len(), 2);
+
+        let edge = graph.require_edge(&Edge::make_id(&proposals[0].from, &proposals[0].to)).unwrap();
+        assert_eq!(edge.source, EdgeSource::AI);
+        assert_eq!(edge.confidence, TITLE_MENTION_CONFIDENCE);
+
+        // 再次运行时已连接的笔记对被跳过
+        let again = propose(graph, DEFAULT_MIN_SHARED_TAGS).unwrap();
+        assert!(again.is_empty());
+        assert!(apply(graph, &again).unwrap().is_empty());
+        assert_eq!(crate::core::suggestions::list(graph).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_mentions_title_word_boundaries() {
+        assert!(mentions_title("I read about free will today", "Free Will"));
+        assert!(mentions_title("(Free Will)", "Free Will"));
+        assert!(!mentions_title("Free Willpower", "Free Will"));
+        assert!(!mentions_title("unfree will", "Free Will"));
+        assert!(!mentions_title("AI is everywhere", "AI"));
+        // 中日韩标题没有词边界
+        assert!(mentions_title("我们讨论自由意志问题", "自由意志"));
+    }
+
+    #[test]
+    fn test_noisy_or_merge() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let storage = Storage::init(root).unwrap();
+        let indexer = Indexer::new(storage.clone());
+
+        fs::write(root.join("a.md"), "---\ntitle: Alpha\ntags: [x, y]\n---\nMentions Bravo here").unwrap();
+        fs::write(root.join("b.md"), "---\ntitle: Bravo\ntags: [x, y]\n---\nBody").unwrap();
+        indexer.index_all(root).unwrap();
+
+        let proposals = propose(storage.graph(), 2).unwrap();
+        assert_eq!(proposals.len(), 1);
+        assert_eq!(proposals[0].reasons.len(), 2);
+        // 1 - (1 - 0.6) * (1 - 0.7)
+        assert_eq!(proposals[0].confidence, 0.88);
+    }
+}
@@ -0,0 +1,214 @@
+//! 建议链接模块
+//!
+//! 管理机器建议的边（来源为 AI 或推导得出）的生命周期：
+//!
+//! - [`list`] - 按置信度降序列出所有建议
+//! - [`accept`] - 接受建议，来源改为显式
+//! - [`reject`] - 拒绝建议，删除该边
+//! - [`accept_all`] - 批量接受置信度不低于阈值的建议
+
+use crate::error::{MemexiaError, Result};
+use crate::storage::{Edge, EdgeFilter, EdgeSource, GraphStorage};
+
+/// 默认批量接受的最低置信度
+pub const DEFAULT_MIN_CONFIDENCE: f64 = 0.8;
+
+/// 列出所有建议的边
+///
+/// # Returns
+///
+/// 来源为 AI 或推导得出的边，按置信度降序、ID 升序排列
+pub fn list(graph: &dyn GraphStorage) -> Result<Vec<Edge>> {
+    let mut edges = Vec::new();
+    for source in [EdgeSource::AI, EdgeSource::Derived] {
+        edges.extend(graph.query_edges(EdgeFilter {
+            source: Some(source),
+            ..Default::default()
+        })?);
+    }
+
+    edges.sort_by(|a, b| {
+        b.confidence
+            .total_cmp(&a.confidence)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    Ok(edges)
+}
+
+/// 接受建议，将来源改为显式
+///
+/// # Arguments
+///
+/// * `graph` - 图存储
+/// * `id` - 边 ID
+///
+/// # Returns
+///
+/// 更新后的边；边不存在或已经是显式边时返回错误
+pub fn accept(graph: &dyn GraphStorage, id: &str) -> Result<Edge> {
+    let mut edge = require_suggestion(graph, id)?;
+    edge.source = EdgeSource::Explicit;
+    graph.add_edge(&edge)?;
+    Ok(edge)
+}
+
+/// 拒绝建议，从图中删除该边
+///
+/// # Returns
+///
+/// 被删除的边；边不存在或已经是显式边时返回错误
+pub fn reject(graph: &dyn GraphStorage, id: &str) -> Result<Edge> {
+    let edge = require_suggestion(graph, id)?;
+    graph.delete_edge(&edge.id)?;
+    Ok(edge)
+}
+
+/// 批量接受置信度不低于 `min_confidence` 的建议
+///
+/// # Returns
+///
+/// 被接受的边
+pub fn accept_all(graph: &dyn GraphStorage, min_confidence: f64) -> Result<Vec<Edge>> {
+    let mut accepted = Vec::new();
+    for mut edge in list(graph)? {
+        if edge.confidence >= min_confidence {
+            edge.source = EdgeSource::Explicit;
+            graph.add_edge(&edge)?;
+            accepted.push(edge);
+        }
+    }
+    Ok(accepted)
+}
+
+/// 获取建议边，不存在或不是建议时返回错误
+fn require_suggestion(graph: &dyn GraphStorage, id: &str) -> Result<Edge> {
+    let edge = graph.require_edge(id)?;
+    if !edge.is_suggestion() {
+        return Err(MemexiaError::Validation(format!("Edge is not a suggestion: {}", id)));
+    }
+    Ok(edge)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{Node, NodeType, RelationType, Storage};
+    use tempfile::TempDir;
+
+    const A: &str = "urn:memexia:file:free-will.md";
+    const B: &str = "urn:memexia:file:determinism.md";
+    const C: &str = "urn:memexia:file:ethics.md";
+
+    /// 一条显式边和三条建议边（置信度 0.9 / 0.5 / 0.85）
+    fn fixture() -> (TempDir, Storage) {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        let graph = storage.graph();
+
+        for id in [A, B, C] {
+            graph.add_node(&Node::new(id, NodeType::Concept, id)).unwrap();
+        }
+
+        let edges = [
+            Edge::builder(A, B, RelationType::Contradicts).build().unwrap(),
+            Edge::builder(B, A, RelationType::RelatedTo)
+                .source(EdgeSource::AI)
+                .confidence(0.9)
+                .build()
+                .unwrap(),
+            Edge::builder(A, C, RelationType::Supports)
+                .source(EdgeSource::AI)
+                .confidence(0.5)
+                .strength(0.4)
+                .description("weak hint")
+                .build()
+                .unwrap(),
+            Edge::builder(C, B, RelationType::LeadsTo)
+                .source(EdgeSource::Derived)
+                .confidence(0.85)
+                .build()
+                .unwrap(),
+        ];
+        for edge in &edges {
+            graph.add_edge(edge).unwrap();
+        }
+
+        (temp_dir, storage)
+    }
+
+    fn ids(edges: &[Edge]) -> Vec<String> {
+        edges.iter().map(|e| e.id.clone()).collect()
+    }
+
+    #[test]
+    fn test_edge_metadata_persisted() {
+        let (_temp, storage) = fixture();
+        let edge = storage.graph().require_edge(&Edge::make_id(A, C)).unwrap();
+
+        assert_eq!(edge.source, EdgeSource::AI);
+        assert_eq!(edge.confidence, 0.5);
+        assert_eq!(edge.strength, 0.4);
+        assert_eq!(edge.description.as_deref(), Some("weak hint"));
+
+        // 元数据不会被当作节点或边
+        assert_eq!(storage.graph().get_all_nodes().unwrap().len(), 3);
+        assert_eq!(storage.graph().get_all_edges().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_query_edges_by_source() {
+        let (_temp, storage) = fixture();
+        let graph = storage.graph();
+
+        let ai = graph
+            .query_edges(EdgeFilter { source: Some(EdgeSource::AI), ..Default::default() })
+            .unwrap();
+        assert_eq!(ai.len(), 2);
+
+        let explicit_from_a = graph
+            .query_edges(EdgeFilter {
+                from: Some(A.to_string()),
+                source: Some(EdgeSource::Explicit),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(ids(&explicit_from_a), vec![Edge::make_id(A, B)]);
+    }
+
+    #[test]
+    fn test_list_sorted_by_confidence() {
+        let (_temp, storage) = fixture();
+        let edges = list(storage.graph()).unwrap();
+        assert_eq!(ids(&edges), vec![Edge::make_id(B, A), Edge::make_id(C, B), Edge::make_id(A, C)]);
+    }
+
+    #[test]
+    fn test_accept_and_reject() {
+        let (_temp, storage) = fixture();
+        let graph = storage.graph();
+
+        let accepted = accept(graph, &Edge::make_id(B, A)).unwrap();
+        assert_eq!(accepted.source, EdgeSource::Explicit);
+        assert_eq!(graph.require_edge(&Edge::make_id(B, A)).unwrap().source, EdgeSource::Explicit);
+
+        reject(graph, &Edge::make_id(A, C)).unwrap();
+        assert!(graph.get_edge(&Edge::make_id(A, C)).unwrap().is_none());
+
+        assert_eq!(ids(&list(graph).unwrap()), vec![Edge::make_id(C, B)]);
+        assert_eq!(graph.get_all_edges().unwrap().len(), 3);
+
+        // 显式边和不存在的边都不能接受或拒绝
+        assert!(matches!(accept(graph, &Edge::make_id(A, B)), Err(MemexiaError::Validation(_))));
+        assert!(matches!(reject(graph, &Edge::make_id(A, C)), Err(MemexiaError::EdgeNotFound { .. })));
+    }
+
+    #[test]
+    fn test_accept_all_min_confidence() {
+        let (_temp, storage) = fixture();
+        let graph = storage.graph();
+
+        let accepted = accept_all(graph, DEFAULT_MIN_CONFIDENCE).unwrap();
+        assert_eq!(ids(&accepted), vec![Edge::make_id(B, A), Edge::make_id(C, B)]);
+        assert_eq!(ids(&list(graph).unwrap()), vec![Edge::make_id(A, C)]);
+    }
+}
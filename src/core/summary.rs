@@ -0,0 +1,137 @@
+//! 自动摘要模块
+//!
+//! 开启 `index.auto_summary` 后，索引时为没有 frontmatter `summary:` 的笔记提取摘要：
+//!
+//! - 取正文中第一个不是标题、代码块的段落（只由 `#tag` 组成的段落也跳过）
+//! - wiki 链接替换为显示文本（目标去掉 `.md`，与 [`crate::core::render`] 一致）
+//! - 空白合并为单个空格，超过 [`MAX_SUMMARY_CHARS`] 个字符时在词边界截断并加省略号
+//!
+//! 提取的摘要带 `summarySource: auto` 标记（见 [`Node::set_auto_summary`]），
+//! 每次重新索引时按当前正文重新提取；作者写的摘要总是优先
+
+use crate::core::parser::extract_frontmatter;
+use crate::storage::Node;
+use pulldown_cmark::{Event, LinkType, Options, Parser, Tag, TagEnd};
+
+/// 自动摘要的最大字符数
+pub const MAX_SUMMARY_CHARS: usize = 280;
+
+/// 从 Markdown 正文提取摘要
+///
+/// # Arguments
+///
+/// * `body` - 去掉 frontmatter 的 Markdown 正文
+///
+/// # Returns
+///
+/// 第一个可用段落的文本；正文没有这样的段落时返回 None
+pub fn extract(body: &str) -> Option<String> {
+    let mut paragraph: Option<String> = None;
+    let mut in_wiki_link = false;
+
+    for event in Parser::new_ext(body, Options::all()) {
+        match event {
+            Event::Start(Tag::Paragraph) => paragraph = Some(String::new()),
+            Event::End(TagEnd::Paragraph) => {
+                if let Some(text) = paragraph.take().map(|text| collapse_whitespace(&text)) {
+                    if !text.is_empty() && !text.split(' ').all(|word| word.starts_with('#')) {
+                        return Some(truncate(&text, MAX_SUMMARY_CHARS));
+                    }
+                }
+            }
+            _ => {
+                let Some(text) = paragraph.as_mut() else { continue };
+                match event {
+                    Event::Start(Tag::Link { link_type: LinkType::WikiLink { .. }, dest_url, .. }) => {
+                        let target = dest_url.trim();
+                        text.push_str(target.strip_suffix(".md").unwrap_or(target));
+                        in_wiki_link = true;
+                    }
+                    Event::End(TagEnd::Link) if in_wiki_link => in_wiki_link = false,
+                    // `|` 之后的关系和强度不输出
+                    _ if in_wiki_link => {}
+                    Event::Text(s) | Event::Code(s) | Event::InlineMath(s) => text.push_str(&s),
+                    Event::SoftBreak | Event::HardBreak => text.push(' '),
+                    _ => {}
+                }
+            }
+        }
+    }
+    None
+}
+
+/// 为没有摘要的节点设置自动摘要
+///
+/// 只处理 Markdown 笔记；节点已有作者写的摘要时不做修改
+///
+/// # Arguments
+///
+/// * `node` - 由笔记解析出的节点
+/// * `content` - 笔记文件的完整内容（含 frontmatter）
+/// * `file_name` - 笔记相对路径，用于判断格式
+pub fn apply(node: &mut Node, content: &str, file_name: &str) {
+    if node.summary().is_some() || file_name.ends_with(".org") {
+        return;
+    }
+    if let Some(summary) = extract(&extract_frontmatter(content).1) {
+        node.set_auto_summary(summary);
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 截断到不超过 `max_chars` 个字符（含省略号），尽量在空白处截断
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let end = text.char_indices().nth(max_chars - 1).map(|(i, _)| i).unwrap_or(text.len());
+    let cut = &text[..end];
+    let cut = match cut.rfind(' ') {
+        Some(space) if space > end / 2 => &cut[..space],
+        _ => cut,
+    };
+    format!("{}…", cut.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::NodeType;
+
+    #[test]
+    fn test_extract_skips_headings_and_code_blocks() {
+        let body = "# Free Will\n\n```rust\nfn main() {}\n```\n\n#philosophy #mind\n\n\
+                    Free will is the capacity to choose,\nsee [[Determinism|Contradicts:0.8]] and [[notes/agency.md]].\n\n\
+                    Second paragraph.\n";
+        assert_eq!(
+            extract(body).as_deref(),
+            Some("Free will is the capacity to choose, see Determinism and notes/agency.")
+        );
+        assert_eq!(extract("# Only a heading\n\n    indented code\n"), None);
+    }
+
+    #[test]
+    fn test_extract_truncates_long_paragraph() {
+        let body = "word ".repeat(100);
+        let summary = extract(&body).unwrap();
+        assert!(summary.chars().count() <= MAX_SUMMARY_CHARS);
+        assert!(summary.ends_with("word…"));
+    }
+
+    #[test]
+    fn test_apply_keeps_authored_summary() {
+        let mut node = Node::new("urn:memexia:file:a.md", NodeType::Concept, "A".to_string());
+        node.set_summary("Written by hand");
+        apply(&mut node, "First paragraph.\n", "a.md");
+        assert_eq!(node.summary(), Some("Written by hand"));
+        assert!(!node.is_auto_summary());
+
+        let mut node = Node::new("urn:memexia:file:a.md", NodeType::Concept, "A".to_string());
+        apply(&mut node, "---\ntitle: A\n---\n# A\n\nFirst paragraph.\n", "a.md");
+        assert_eq!(node.summary(), Some("First paragraph."));
+        assert!(node.is_auto_summary());
+    }
+}
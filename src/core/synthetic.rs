@@ -0,0 +1,280 @@
+//! 合成笔记库模块（`memexia debug generate-vault`）
+//!
+//! 为性能工作和测试按种子确定性地生成大型笔记库：
+//!
+//! - 笔记写入 `<dir>/notes/note-00001.md` …，带 frontmatter（标题、类型、标签）和一段正文
+//! - wiki 链接按偏好依附（preferential attachment）选择目标：已被链接得越多的笔记越容易被链接，
+//!   得到与真实笔记库相近的长尾入度分布；链接目标相对 `<dir>`，在 `<dir>` 中初始化仓库即可索引
+//! - 标签取自 `tag-01` … `tag-NN`，笔记数不少于标签数时每个标签至少出现一次
+//! - `broken_fraction` 比例的笔记被破坏：一半把最后一条链接换成指向不存在笔记的悬空链接，
+//!   另一半写入无法解析的 frontmatter
+//!
+//! 同一种子和参数总是生成逐字节相同的文件（随机数使用 [`StdRng`]）
+
+use crate::error::{MemexiaError, Result};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 笔记所在的子目录
+pub const NOTES_DIR: &str = "notes";
+
+/// 标题和正文的词表
+const WORDS: [&str; 32] = [
+    "memory", "graph", "lattice", "signal", "river", "theory", "method", "evidence", "question", "pattern",
+    "archive", "concept", "network", "origin", "mirror", "vector", "harbor", "thread", "garden", "engine",
+    "summit", "canvas", "field", "orbit", "prism", "ledger", "beacon", "circuit", "meadow", "atlas", "cipher",
+    "echo",
+];
+
+/// 正文链接使用的关系（`None` 为不写关系）
+const RELATIONS: [Option<&str>; 5] = [None, Some("Supports"), Some("Refines"), Some("References"), Some("Contradicts")];
+
+/// frontmatter 中的节点类型
+const NODE_TYPES: [&str; 4] = ["Concept", "Question", "Evidence", "Resource"];
+
+/// 生成参数
+#[derive(Debug, Clone, PartialEq)]
+pub struct VaultSpec {
+    /// 笔记数
+    pub notes: usize,
+    /// 每篇笔记的出链数（笔记数不足时为其余全部笔记）
+    pub links_per_note: usize,
+    /// 标签词表大小
+    pub tags: usize,
+    /// 随机数种子
+    pub seed: u64,
+    /// 被破坏的笔记比例（0–1）
+    pub broken_fraction: f64,
+}
+
+impl Default for VaultSpec {
+    fn default() -> Self {
+        Self {
+            notes: 1000,
+            links_per_note: 4,
+            tags: 50,
+            seed: 42,
+            broken_fraction: 0.0,
+        }
+    }
+}
+
+/// 生成结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GeneratedVault {
+    /// 生成的文件（相对 `<dir>`，按路径排序）
+    pub files: Vec<PathBuf>,
+    /// 链接总数（含悬空链接）
+    pub links: usize,
+    /// 用到的不同标签数
+    pub tags: usize,
+    /// 悬空链接数
+    pub dangling_links: usize,
+    /// frontmatter 无法解析的笔记数
+    pub malformed_frontmatter: usize,
+}
+
+/// 第 `index` 篇笔记相对 `<dir>` 的路径（从 0 开始）
+pub fn note_path(index: usize) -> String {
+    format!("{}/note-{:05}.md", NOTES_DIR, index + 1)
+}
+
+/// 生成笔记库
+///
+/// # Arguments
+///
+/// * `dir` - 目标目录；`<dir>/notes` 必须不存在或为空
+/// * `spec` - 生成参数
+pub fn generate_vault(dir: &Path, spec: &VaultSpec) -> Result<GeneratedVault> {
+    if !(0.0..=1.0).contains(&spec.broken_fraction) {
+        return Err(MemexiaError::Validation(format!(
+            "Broken fraction must be between 0 and 1, got {}",
+            spec.broken_fraction
+        )));
+    }
+    if spec.tags == 0 {
+        return Err(MemexiaError::Validation("At least one tag is required".to_string()));
+    }
+    let notes_dir = dir.join(NOTES_DIR);
+    if notes_dir.exists() && fs::read_dir(&notes_dir)?.next().is_some() {
+        return Err(MemexiaError::Validation(format!("{} is not empty", notes_dir.display())));
+    }
+    fs::create_dir_all(&notes_dir)?;
+
+    let mut rng = StdRng::seed_from_u64(spec.seed);
+    let targets = attach_links(&mut rng, spec.notes, spec.links_per_note);
+
+    // 被破坏的笔记：偶数位置的换悬空链接，奇数位置的写坏 frontmatter
+    let mut order: Vec<usize> = (0..spec.notes).collect();
+    order.shuffle(&mut rng);
+    let broken_count = (spec.notes as f64 * spec.broken_fraction).round() as usize;
+    let mut dangling = BTreeSet::new();
+    let mut malformed = BTreeSet::new();
+    for (i, &note) in order[..broken_count].iter().enumerate() {
+        if i % 2 == 0 && !targets[note].is_empty() {
+            dangling.insert(note);
+        } else {
+            malformed.insert(note);
+        }
+    }
+
+    let mut result = GeneratedVault::default();
+    let mut used_tags = BTreeSet::new();
+    for (index, links) in targets.iter().enumerate() {
+        let title = format!("{} {} {}", capitalize(pick(&mut rng)), pick(&mut rng), index + 1);
+        let node_type = NODE_TYPES[rng.random_range(0..NODE_TYPES.len())];
+        let mut tags = BTreeSet::from([index % spec.tags]);
+        for _ in 0..rng.random_range(0..3) {
+            tags.insert(rng.random_range(0..spec.tags));
+        }
+        let tags: Vec<String> = tags.into_iter().map(|tag| format!("tag-{:02}", tag + 1)).collect();
+
+        let mut content = if malformed.contains(&index) {
+            format!("---\ntitle: [{}\ntags: {}\n---\n", title, tags.join(", "))
+        } else {
+            used_tags.extend(tags.iter().cloned());
+            format!("---\ntitle: \"{}\"\ntype: {}\ntags: [{}]\n---\n", title, node_type, tags.join(", "))
+        };
+        content.push_str(&format!("# {}\n\n", title));
+        let words: Vec<&str> = (0..rng.random_range(8..24)).map(|_| pick(&mut rng)).collect();
+        content.push_str(&format!("{}.\n\n", capitalize(&words.join(" "))));
+
+        for (i, &target) in links.iter().enumerate() {
+            let target = if dangling.contains(&index) && i + 1 == links.len() {
+                result.dangling_links += 1;
+                format!("missing/missing-{:05}.md", index + 1)
+            } else {
+                note_path(target)
+            };
+            match RELATIONS[rng.random_range(0..RELATIONS.len())] {
+                Some(relation) => content.push_str(&format!("- [[{}|{}]]\n", target, relation)),
+                None => content.push_str(&format!("- [[{}]]\n", target)),
+            }
+        }
+        result.links += links.len();
+
+        let path = note_path(index);
+        fs::write(dir.join(&path), content)?;
+        result.files.push(PathBuf::from(path));
+    }
+    result.tags = used_tags.len();
+    result.malformed_frontmatter = malformed.len();
+    Ok(result)
+}
+
+/// 按偏好依附为每篇笔记选择不重复的链接目标
+///
+/// 候选池中每篇笔记出现一次，每被链接一次再多出现一次，均匀抽取即按 (入度 + 1) 加权；
+/// 开头几篇笔记的前驱不足时，从其余笔记中均匀补足
+fn attach_links(rng: &mut StdRng, notes: usize, links_per_note: usize) -> Vec<Vec<usize>> {
+    let per_note = links_per_note.min(notes.saturating_sub(1));
+    let mut pool: Vec<usize> = Vec::with_capacity(notes * (per_note + 1));
+    let mut targets = Vec::with_capacity(notes);
+    for index in 0..notes {
+        let mut chosen: Vec<usize> = Vec::with_capacity(per_note);
+        if index >= per_note {
+            while chosen.len() < per_note {
+                let target = pool[rng.random_range(0..pool.len())];
+                if !chosen.contains(&target) {
+                    chosen.push(target);
+                }
+            }
+        } else {
+            let mut others: Vec<usize> = (0..notes).filter(|&other| other != index).collect();
+            others.shuffle(rng);
+            chosen.extend_from_slice(&others[..per_note]);
+        }
+        pool.extend_from_slice(&chosen);
+        pool.push(index);
+        targets.push(chosen);
+    }
+    targets
+}
+
+fn pick(rng: &mut StdRng) -> &'static str {
+    WORDS[rng.random_range(0..WORDS.len())]
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::object::hash_content;
+    use crate::core::parser::parse_markdown;
+    use tempfile::TempDir;
+
+    fn hashes(dir: &Path, vault: &GeneratedVault) -> Vec<String> {
+        vault.files.iter().map(|file| hash_content(&fs::read(dir.join(file)).unwrap())).collect()
+    }
+
+    #[test]
+    fn test_generate_is_deterministic() {
+        let spec = VaultSpec { notes: 120, links_per_note: 3, tags: 10, seed: 7, broken_fraction: 0.1 };
+        let (a, b) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+        let first = generate_vault(a.path(), &spec).unwrap();
+        let second = generate_vault(b.path(), &spec).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(hashes(a.path(), &first), hashes(b.path(), &second));
+
+        let c = TempDir::new().unwrap();
+        let other = generate_vault(c.path(), &VaultSpec { seed: 8, ..spec.clone() }).unwrap();
+        assert_ne!(hashes(a.path(), &first), hashes(c.path(), &other));
+
+        // 目录非空时拒绝覆盖
+        assert!(matches!(generate_vault(a.path(), &spec), Err(MemexiaError::Validation(_))));
+    }
+
+    #[test]
+    fn test_generate_honors_counts() {
+        let spec = VaultSpec { notes: 200, links_per_note: 4, tags: 25, seed: 42, broken_fraction: 0.0 };
+        let dir = TempDir::new().unwrap();
+        let vault = generate_vault(dir.path(), &spec).unwrap();
+        assert_eq!(vault.files.len(), 200);
+        assert_eq!((vault.links, vault.tags, vault.dangling_links, vault.malformed_frontmatter), (800, 25, 0, 0));
+
+        let mut tags = BTreeSet::new();
+        let mut in_degree = vec![0; spec.notes];
+        for file in &vault.files {
+            let content = fs::read_to_string(dir.path().join(file)).unwrap();
+            let (doc, warnings) = parse_markdown(&content, &file.to_string_lossy());
+            assert!(warnings.is_empty(), "{:?}", warnings);
+            assert_eq!(doc.wiki_links.len(), 4);
+            tags.extend(doc.get_all_tags());
+            for link in &doc.wiki_links {
+                let index = (0..spec.notes).position(|i| note_path(i) == link.target).unwrap();
+                in_degree[index] += 1;
+            }
+        }
+        assert_eq!(tags.len(), 25);
+        // 偏好依附：入度分布有明显的长尾
+        let max = *in_degree.iter().max().unwrap();
+        assert!(max >= 20, "max in-degree {}", max);
+    }
+
+    #[test]
+    fn test_generate_broken_notes() {
+        let spec = VaultSpec { notes: 40, links_per_note: 2, tags: 5, seed: 1, broken_fraction: 0.5 };
+        let dir = TempDir::new().unwrap();
+        let vault = generate_vault(dir.path(), &spec).unwrap();
+        assert_eq!((vault.dangling_links, vault.malformed_frontmatter), (10, 10));
+
+        let warnings: usize = vault
+            .files
+            .iter()
+            .map(|file| parse_markdown(&fs::read_to_string(dir.path().join(file)).unwrap(), "x.md").1.len())
+            .sum();
+        assert_eq!(warnings, 10);
+        assert!(generate_vault(dir.path(), &VaultSpec { broken_fraction: 1.5, ..spec }).is_err());
+    }
+}
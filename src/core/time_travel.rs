@@ -0,0 +1,184 @@
+//! 时间旅行查询模块
+//!
+//! 按日期或提交查看图谱的历史状态：
+//!
+//! - [`At`] - 解析 `--at <date|commit>` 参数
+//! - [`graph_at`] - 定位历史快照并加载为临时内存图
+//!
+//! 历史快照加载到独立的内存存储中，不影响当前仓库的存储；
+//! 同一进程内按快照哈希缓存，多次查询同一快照不会重复导入
+
+use crate::error::{MemexiaError, Result};
+use crate::storage::{GraphStorage, OxigraphStorage};
+use crate::vcs::{CommitLink, GraphHistory, AUTO_SNAPSHOT_PREFIX};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+/// 提交哈希前缀的最短长度
+const MIN_COMMIT_PREFIX: usize = 4;
+
+/// 历史时间点
+#[derive(Debug, Clone, PartialEq)]
+pub enum At {
+    /// 时间点（日期解析为当天结束时刻）
+    Time(DateTime<Utc>),
+    /// 修订表达式（提交哈希或其前缀、`HEAD~N`、分支名）或自动快照键 `auto:<时间>`
+    Commit(String),
+}
+
+impl At {
+    /// 解析 `--at` 参数
+    ///
+    /// 依次尝试 RFC 3339 时间、`YYYY-MM-DD` 日期、自动快照键、十六进制提交哈希前缀和修订表达式；
+    /// 修订表达式在加载时由 [`crate::vcs::Vcs::resolve_revision`] 解析
+    pub fn parse(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+            return Ok(At::Time(time.with_timezone(&Utc)));
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            let end_of_day = NaiveTime::from_hms_opt(23, 59, 59).unwrap_or_default();
+            return Ok(At::Time(date.and_time(end_of_day).and_utc()));
+        }
+        if s.starts_with(AUTO_SNAPSHOT_PREFIX) {
+            return Ok(At::Commit(s.to_string()));
+        }
+        if s.len() >= MIN_COMMIT_PREFIX && s.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Ok(At::Commit(s.to_ascii_lowercase()));
+        }
+        if !s.is_empty() && !s.starts_with('-') && !s.contains(char::is_whitespace) {
+            return Ok(At::Commit(s.to_string()));
+        }
+        Err(MemexiaError::Validation(format!(
+            "Invalid --at value '{}': expected YYYY-MM-DD, RFC 3339 time, revision (hash, HEAD~N, branch) or auto:<time>",
+            s
+        )))
+    }
+}
+
+impl std::fmt::Display for At {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            At::Time(time) => write!(f, "{}", time.format("%Y-%m-%d %H:%M:%S UTC")),
+            At::Commit(hash) => write!(f, "{}", hash),
+        }
+    }
+}
+
+/// 历史图视图
+#[derive(Debug, Clone)]
+pub struct HistoricalGraph {
+    /// 对应的提交关联
+    pub link: CommitLink,
+    /// 快照加载出的内存图
+    pub graph: OxigraphStorage,
+}
+
+impl HistoricalGraph {
+    /// 图存储
+    pub fn graph(&self) -> &dyn GraphStorage {
+        &self.graph
+    }
+}
+
+/// 定位并加载指定时间点的图状态
+///
+/// # Arguments
+///
+/// * `history` - 图历史
+/// * `at` - 时间点或提交
+///
+/// # Returns
+///
+/// 历史图视图；没有匹配的提交时返回 `Validation` 错误
+pub fn graph_at(history: &GraphHistory, at: &At) -> Result<HistoricalGraph> {
+    let link = match at {
+        At::Time(time) => history.resolve_at(*time)?,
+        At::Commit(prefix) => history.resolve_commit(prefix)?,
+    }
+    .ok_or_else(|| MemexiaError::Validation(format!("No graph snapshot found at {}", at)))?;
+
+    let graph = load_snapshot(history, &link.graph_hash)?;
+    Ok(HistoricalGraph { link, graph })
+}
+
+/// 加载快照为内存图（进程内按哈希缓存）
+///
+/// 快照按内容寻址，同一哈希的内容总是相同，因此缓存可跨仓库共享
+pub fn load_snapshot(history: &GraphHistory, hash: &str) -> Result<OxigraphStorage> {
+    static CACHE: OnceLock<Mutex<HashMap<String, OxigraphStorage>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(Default::default);
+
+    if let Some(graph) = cache.lock().unwrap_or_else(|e| e.into_inner()).get(hash) {
+        return Ok(graph.clone());
+    }
+
+    let snapshot = history.get_snapshot(hash)?;
+    let graph = tracing::info_span!("snapshot_import", hash = %hash)
+        .in_scope(|| OxigraphStorage::from_nquads(&snapshot.nquads))?;
+    cache
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(hash.to_string(), graph.clone());
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{Node, NodeType, Storage};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_at() {
+        assert_eq!(
+            At::parse("2024-03-01").unwrap(),
+            At::Time("2024-03-01T23:59:59Z".parse().unwrap())
+        );
+        assert_eq!(
+            At::parse("2024-03-01T08:00:00+08:00").unwrap(),
+            At::Time("2024-03-01T00:00:00Z".parse().unwrap())
+        );
+        assert_eq!(At::parse("AbC123").unwrap(), At::Commit("abc123".to_string()));
+        assert_eq!(
+            At::parse("auto:20240301T080000").unwrap(),
+            At::Commit("auto:20240301T080000".to_string())
+        );
+        assert_eq!(At::parse("HEAD~2").unwrap(), At::Commit("HEAD~2".to_string()));
+        assert_eq!(At::parse("main").unwrap(), At::Commit("main".to_string()));
+        assert!(matches!(At::parse("last march"), Err(MemexiaError::Validation(_))));
+        assert!(matches!(At::parse("--all"), Err(MemexiaError::Validation(_))));
+    }
+
+    #[test]
+    fn test_load_snapshot_is_isolated_and_cached() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let history = GraphHistory::init(temp.path()).unwrap();
+
+        storage
+            .graph()
+            .add_node(&Node::new("urn:memexia:file:a.md", NodeType::Concept, "A"))
+            .unwrap();
+        let hash = history.snapshot(&storage).unwrap();
+
+        let graph = load_snapshot(&history, &hash).unwrap();
+        assert!(graph.node_exists("urn:memexia:file:a.md").unwrap());
+
+        // 修改历史图不影响当前存储
+        graph
+            .add_node(&Node::new("urn:memexia:file:scratch.md", NodeType::Concept, "Scratch"))
+            .unwrap();
+        assert!(!storage.graph().node_exists("urn:memexia:file:scratch.md").unwrap());
+
+        // 再次加载返回缓存中的同一个图
+        let again = load_snapshot(&history, &hash).unwrap();
+        assert!(again.node_exists("urn:memexia:file:scratch.md").unwrap());
+
+        assert!(matches!(
+            graph_at(&history, &At::Commit("abcd".to_string())),
+            Err(MemexiaError::Validation(_))
+        ));
+    }
+}
@@ -0,0 +1,187 @@
+//! 回收站（软删除）模块
+//!
+//! 文件被删除时不立即移除节点，而是记录 `memexia:deletedAt`：
+//!
+//! - 软删除的节点不出现在 `list_nodes`、`list_edges` 和统计中；任一端点被软删除的边处于墓碑状态，
+//!   仍然保存，节点恢复后重新可见
+//! - `memexia node restore <path>` 清除标记，并在文件缺失时从 Git 检出
+//! - `memexia gc --purge-deleted --older-than 30d` 真正删除回收站中超过期限的节点及其出边
+
+use crate::error::{MemexiaError, Result};
+use crate::storage::{GraphStorage, Node};
+use chrono::{DateTime, Duration, Utc};
+
+/// 节点恢复结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestoreOutcome {
+    /// 恢复的节点 ID
+    pub node_id: String,
+    /// 是否从 Git 检出了文件
+    pub file_restored: bool,
+}
+
+/// 软删除节点
+///
+/// # Arguments
+///
+/// * `graph` - 图存储
+/// * `id` - 节点 ID
+/// * `now` - 删除时间
+///
+/// # Returns
+///
+/// 节点存在且此前未被删除时返回 true
+pub fn soft_delete(graph: &dyn GraphStorage, id: &str, now: DateTime<Utc>) -> Result<bool> {
+    let Some(mut node) = graph.get_node(id)? else {
+        return Ok(false);
+    };
+    if node.is_deleted() {
+        return Ok(false);
+    }
+    node.set_deleted_at(now);
+    graph.update_node(&node)?;
+    Ok(true)
+}
+
+/// 从回收站恢复节点
+///
+/// # Returns
+///
+/// 恢复后的节点；节点不存在或未被删除时返回错误
+pub fn restore(graph: &dyn GraphStorage, id: &str) -> Result<Node> {
+    let mut node = graph.require_node(id)?;
+    if !node.is_deleted() {
+        return Err(MemexiaError::Validation(format!("Node '{}' is not in the trash", id)));
+    }
+    node.clear_deleted();
+    graph.update_node(&node)?;
+    Ok(node)
+}
+
+/// 回收站中的节点（按删除时间排序）
+pub fn deleted_nodes(graph: &dyn GraphStorage) -> Result<Vec<Node>> {
+    let mut nodes: Vec<Node> = graph
+        .list_nodes_filtered(true)?
+        .into_iter()
+        .filter(Node::is_deleted)
+        .collect();
+    nodes.sort_by_key(|n| n.deleted_at());
+    Ok(nodes)
+}
+
+/// 回收站中满足期限、会被 [`purge_deleted`] 永久删除的节点
+///
+/// # Arguments
+///
+/// * `graph` - 图存储
+/// * `older_than` - 只选择至少在该时长之前被删除的节点；None 表示全部
+/// * `now` - 当前时间
+pub fn purge_candidates(graph: &dyn GraphStorage, older_than: Option<Duration>, now: DateTime<Utc>) -> Result<Vec<Node>> {
+    let cutoff = now - older_than.unwrap_or_else(Duration::zero);
+    Ok(deleted_nodes(graph)?
+        .into_iter()
+        .filter(|node| node.deleted_at().is_none_or(|at| at <= cutoff))
+        .collect())
+}
+
+/// 永久删除回收站中的节点
+///
+/// # Arguments
+///
+/// * `graph` - 图存储
+/// * `older_than` - 只删除至少在该时长之前被删除的节点；None 表示全部
+/// * `now` - 当前时间
+///
+/// # Returns
+///
+/// 被永久删除的节点 ID
+pub fn purge_deleted(graph: &dyn GraphStorage, older_than: Option<Duration>, now: DateTime<Utc>) -> Result<Vec<String>> {
+    let edges = graph.list_edges_filtered(true)?;
+
+    let mut purged = Vec::new();
+    for node in purge_candidates(graph, older_than, now)? {
+        for edge in edges.iter().filter(|e| e.from == node.id) {
+            graph.delete_edge(&edge.id)?;
+        }
+        graph.delete_node(&node.id)?;
+        purged.push(node.id);
+    }
+    Ok(purged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{Edge, NodeType, RelationType, Storage};
+    use tempfile::TempDir;
+
+    fn fixture() -> (TempDir, Storage) {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let graph = storage.graph();
+        for name in ["a", "b", "c"] {
+            let id = format!("urn:memexia:file:{}.md", name);
+            graph.add_node(&Node::new(&id, NodeType::Concept, name)).unwrap();
+        }
+        for (from, to) in [("a", "b"), ("c", "a")] {
+            let from = format!("urn:memexia:file:{}.md", from);
+            let to = format!("urn:memexia:file:{}.md", to);
+            graph
+                .add_edge(&Edge::new(Edge::make_id(&from, &to), &from, &to, RelationType::RelatedTo))
+                .unwrap();
+        }
+        (temp, storage)
+    }
+
+    #[test]
+    fn test_soft_delete_hides_node_and_edges() {
+        let (_temp, storage) = fixture();
+        let graph = storage.graph();
+        let now = Utc::now();
+
+        assert!(soft_delete(graph, "urn:memexia:file:a.md", now).unwrap());
+        assert!(!soft_delete(graph, "urn:memexia:file:a.md", now).unwrap());
+        assert!(!soft_delete(graph, "urn:memexia:file:missing.md", now).unwrap());
+
+        assert_eq!(graph.list_nodes().unwrap().len(), 2);
+        assert!(graph.list_edges().unwrap().is_empty());
+        assert_eq!(graph.get_stats().unwrap().node_count, 2);
+        assert_eq!(graph.list_nodes_filtered(true).unwrap().len(), 3);
+        assert_eq!(graph.list_edges_filtered(true).unwrap().len(), 2);
+
+        let node = graph.require_node("urn:memexia:file:a.md").unwrap();
+        assert_eq!(node.deleted_at().unwrap().timestamp(), now.timestamp());
+        assert_eq!(deleted_nodes(graph).unwrap().len(), 1);
+
+        restore(graph, "urn:memexia:file:a.md").unwrap();
+        assert_eq!(graph.list_nodes().unwrap().len(), 3);
+        assert_eq!(graph.list_edges().unwrap().len(), 2);
+        assert!(matches!(
+            restore(graph, "urn:memexia:file:a.md"),
+            Err(MemexiaError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_purge_respects_age() {
+        let (_temp, storage) = fixture();
+        let graph = storage.graph();
+        let now = Utc::now();
+
+        soft_delete(graph, "urn:memexia:file:a.md", now - Duration::days(40)).unwrap();
+        soft_delete(graph, "urn:memexia:file:b.md", now - Duration::days(1)).unwrap();
+
+        let purged = purge_deleted(graph, Some(Duration::days(30)), now).unwrap();
+        assert_eq!(purged, vec!["urn:memexia:file:a.md".to_string()]);
+        assert!(!graph.node_exists("urn:memexia:file:a.md").unwrap());
+        assert!(graph.node_exists("urn:memexia:file:b.md").unwrap());
+        assert!(graph
+            .list_edges_filtered(true)
+            .unwrap()
+            .iter()
+            .all(|e| e.from != "urn:memexia:file:a.md"));
+
+        assert_eq!(purge_deleted(graph, None, now).unwrap().len(), 1);
+        assert!(deleted_nodes(graph).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,239 @@
+//! 撤销日志模块
+//!
+//! 在提交之外修改图的操作（`link create` / `link delete`、建议的接受与拒绝、`gc --purge-deleted`）
+//! 执行前记录受影响节点和边的原始状态，写入 `.memexia/undo/last.json`：
+//!
+//! - 操作前存在的节点和边按完整序列化保存，撤销时原样写回
+//! - 操作前不存在的节点和边只记录 ID，撤销时删除
+//!
+//! `memexia undo` 应用一次逆操作后清除日志。无法低成本撤销的操作（`reindex --full`、`import`）
+//! 不写日志，而是清除旧日志并提示无法撤销
+
+use crate::error::{MemexiaError, Result};
+use crate::storage::{Edge, GraphStorage, Node};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 撤销日志目录（相对仓库根目录）
+pub const UNDO_DIR: &str = ".memexia/undo";
+
+/// 最近一次操作的日志文件名
+const LAST_FILE: &str = "last.json";
+
+/// 最近一次可撤销操作的逆操作
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UndoJournal {
+    /// 操作名称（如 `link delete`）
+    pub operation: String,
+    /// 记录时间
+    pub created_at: DateTime<Utc>,
+    /// 撤销时删除的边（操作新增的边）
+    #[serde(default)]
+    pub remove_edges: Vec<String>,
+    /// 撤销时删除的节点（操作新增的节点）
+    #[serde(default)]
+    pub remove_nodes: Vec<String>,
+    /// 撤销时写回的节点（操作前的状态）
+    #[serde(default)]
+    pub restore_nodes: Vec<Node>,
+    /// 撤销时写回的边（操作前的状态）
+    #[serde(default)]
+    pub restore_edges: Vec<Edge>,
+}
+
+impl UndoJournal {
+    /// 为指定操作创建空日志
+    pub fn new(operation: &str) -> Self {
+        Self {
+            operation: operation.to_string(),
+            created_at: Utc::now(),
+            remove_edges: Vec::new(),
+            remove_nodes: Vec::new(),
+            restore_nodes: Vec::new(),
+            restore_edges: Vec::new(),
+        }
+    }
+
+    /// 记录节点在修改前的状态（同一节点只记录第一次）
+    ///
+    /// # Arguments
+    ///
+    /// * `graph` - 图存储
+    /// * `id` - 即将被修改、创建或删除的节点 ID
+    pub fn capture_node(&mut self, graph: &dyn GraphStorage, id: &str) -> Result<()> {
+        if self.remove_nodes.iter().any(|n| n == id) || self.restore_nodes.iter().any(|n| n.id == id) {
+            return Ok(());
+        }
+        match graph.get_node(id)? {
+            Some(node) => self.restore_nodes.push(node),
+            None => self.remove_nodes.push(id.to_string()),
+        }
+        Ok(())
+    }
+
+    /// 记录边在修改前的状态（同一条边只记录第一次）
+    ///
+    /// # Arguments
+    ///
+    /// * `graph` - 图存储
+    /// * `id` - 即将被修改、创建或删除的边 ID
+    pub fn capture_edge(&mut self, graph: &dyn GraphStorage, id: &str) -> Result<()> {
+        if self.remove_edges.iter().any(|e| e == id) || self.restore_edges.iter().any(|e| e.id == id) {
+            return Ok(());
+        }
+        match graph.get_edge(id)? {
+            Some(edge) => self.restore_edges.push(edge),
+            None => self.remove_edges.push(id.to_string()),
+        }
+        Ok(())
+    }
+
+    /// 是否没有记录任何逆操作
+    pub fn is_empty(&self) -> bool {
+        self.remove_edges.is_empty()
+            && self.remove_nodes.is_empty()
+            && self.restore_nodes.is_empty()
+            && self.restore_edges.is_empty()
+    }
+
+    /// 在图上应用逆操作
+    ///
+    /// 先删除新增的边和节点，再写回节点，最后写回边（保证边的端点已存在）
+    pub fn apply(&self, graph: &dyn GraphStorage) -> Result<()> {
+        for id in &self.remove_edges {
+            graph.delete_edge(id)?;
+        }
+        for id in &self.remove_nodes {
+            graph.delete_node(id)?;
+        }
+        for node in &self.restore_nodes {
+            graph.add_node(node)?;
+        }
+        for edge in &self.restore_edges {
+            graph.add_edge(edge)?;
+        }
+        Ok(())
+    }
+
+    /// 保存为仓库的最近一次撤销日志（覆盖之前的日志）
+    pub fn save(&self, root: &Path) -> Result<()> {
+        let path = journal_path(root);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// 读取仓库的最近一次撤销日志
+    pub fn load(root: &Path) -> Result<Option<Self>> {
+        let path = journal_path(root);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+}
+
+/// 撤销日志文件路径
+pub fn journal_path(root: &Path) -> PathBuf {
+    root.join(UNDO_DIR).join(LAST_FILE)
+}
+
+/// 清除撤销日志
+///
+/// # Returns
+///
+/// 日志存在并被删除时返回 true
+pub fn clear(root: &Path) -> Result<bool> {
+    let path = journal_path(root);
+    if !path.exists() {
+        return Ok(false);
+    }
+    fs::remove_file(path)?;
+    Ok(true)
+}
+
+/// 撤销最近一次操作：应用逆操作后清除日志，保证只生效一次
+///
+/// # Returns
+///
+/// 被撤销的日志；没有可撤销的操作时返回错误
+pub fn undo_last(graph: &dyn GraphStorage, root: &Path) -> Result<UndoJournal> {
+    let journal = UndoJournal::load(root)?
+        .ok_or_else(|| MemexiaError::Validation("Nothing to undo".to_string()))?;
+    journal.apply(graph)?;
+    clear(root)?;
+    Ok(journal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{NodeType, RelationType, Storage};
+    use tempfile::TempDir;
+
+    fn id(name: &str) -> String {
+        format!("urn:memexia:file:{}.md", name)
+    }
+
+    #[test]
+    fn test_undo_link_delete_restores_metadata() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let graph = storage.graph();
+        for name in ["a", "b"] {
+            graph.add_node(&Node::new(id(name), NodeType::Concept, name)).unwrap();
+        }
+        let edge = Edge::builder(id("a"), id("b"), RelationType::Supports)
+            .strength(0.3)
+            .description("evidence")
+            .build()
+            .unwrap();
+        graph.add_edge(&edge).unwrap();
+
+        let mut journal = UndoJournal::new("link delete");
+        journal.capture_edge(graph, &edge.id).unwrap();
+        graph.remove_edge(&edge.id).unwrap();
+        journal.save(temp.path()).unwrap();
+
+        let undone = undo_last(graph, temp.path()).unwrap();
+        assert_eq!(undone.operation, "link delete");
+        let restored = graph.require_edge(&edge.id).unwrap();
+        assert_eq!(restored.strength, 0.3);
+        assert_eq!(restored.description.as_deref(), Some("evidence"));
+
+        // 只生效一次
+        assert!(!journal_path(temp.path()).exists());
+        assert!(matches!(undo_last(graph, temp.path()), Err(MemexiaError::Validation(_))));
+    }
+
+    #[test]
+    fn test_undo_removes_created_items() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let graph = storage.graph();
+        graph.add_node(&Node::new(id("a"), NodeType::Concept, "A")).unwrap();
+
+        let edge_id = Edge::make_id(&id("a"), &id("b"));
+        let mut journal = UndoJournal::new("link create");
+        journal.capture_node(graph, &id("a")).unwrap();
+        journal.capture_node(graph, &id("b")).unwrap();
+        journal.capture_edge(graph, &edge_id).unwrap();
+        assert_eq!(journal.remove_nodes, vec![id("b")]);
+        assert_eq!(journal.restore_nodes.len(), 1);
+
+        graph.add_node(&Node::new(id("b"), NodeType::Resource, "b")).unwrap();
+        graph
+            .add_edge(&Edge::new(edge_id.clone(), id("a"), id("b"), RelationType::RelatedTo))
+            .unwrap();
+
+        journal.apply(graph).unwrap();
+        assert!(graph.node_exists(&id("a")).unwrap());
+        assert!(!graph.node_exists(&id("b")).unwrap());
+        assert!(!graph.edge_exists(&edge_id).unwrap());
+    }
+}
@@ -0,0 +1,532 @@
+//! 图验证模块
+//!
+//! 按可配置的规则检查图谱不变量，供 `memexia validate` 和提交前钩子使用
+//!
+//! ## 内置规则
+//!
+//! | 规则 | 默认级别 | 说明 |
+//! |------|----------|------|
+//! | `require_tags` | off | 每篇笔记至少有一个标签 |
+//! | `evidence_supports` | warn | Evidence 节点至少有一条 Supports 出边 |
+//! | `question_contains_question` | warn | Question 之间不应使用 Contains 关系 |
+//! | `no_self_loops` | error | 边的源和目标不能是同一节点 |
+//! | `dangling_edges` | error | 边的端点必须存在 |
+//!
+//! 规则级别通过配置覆盖：
+//!
+//! ```toml
+//! [validate.rules]
+//! require_tags = "warn"
+//! no_self_loops = "off"
+//! ```
+//!
+//! 库用户可以实现 [`Rule`] 并通过 [`Validator::register`] 注册自定义规则
+
+use crate::error::Result;
+use crate::storage::{GraphStorage, NodeType, RelationType};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+/// 违规严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// 警告
+    Warning,
+    /// 错误
+    Error,
+}
+
+impl Severity {
+    /// 解析严重程度（`warn` / `warning` / `error`）
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "warn" | "warning" => Some(Severity::Warning),
+            "error" => Some(Severity::Error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// 规则级别（配置值 `off` / `warn` / `error`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleLevel {
+    /// 关闭
+    Off,
+    /// 违规报告为警告
+    Warn,
+    /// 违规报告为错误
+    Error,
+}
+
+impl RuleLevel {
+    /// 对应的违规严重程度，关闭时为 None
+    pub fn severity(self) -> Option<Severity> {
+        match self {
+            RuleLevel::Off => None,
+            RuleLevel::Warn => Some(Severity::Warning),
+            RuleLevel::Error => Some(Severity::Error),
+        }
+    }
+}
+
+/// 验证配置（`[validate]`）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ValidateConfig {
+    /// 规则名到级别的映射，未列出的规则使用默认级别
+    #[serde(default)]
+    pub rules: BTreeMap<String, RuleLevel>,
+}
+
+/// 违规对象
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "id", rename_all = "lowercase")]
+pub enum Target {
+    /// 节点 ID
+    Node(String),
+    /// 边 ID
+    Edge(String),
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::Node(id) | Target::Edge(id) => write!(f, "{}", id),
+        }
+    }
+}
+
+/// 违规
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Violation {
+    /// 违规的节点或边
+    pub target: Target,
+    /// 说明
+    pub message: String,
+    /// 严重程度（由规则级别决定）
+    pub severity: Severity,
+}
+
+impl Violation {
+    /// 节点违规
+    pub fn node(id: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            target: Target::Node(id.into()),
+            message: message.into(),
+            severity: Severity::Warning,
+        }
+    }
+
+    /// 边违规
+    pub fn edge(id: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            target: Target::Edge(id.into()),
+            message: message.into(),
+            severity: Severity::Warning,
+        }
+    }
+}
+
+/// 验证规则
+pub trait Rule: Send + Sync {
+    /// 规则名（配置键）
+    fn name(&self) -> &str;
+
+    /// 规则说明
+    fn description(&self) -> &str;
+
+    /// 未配置时的级别
+    fn default_level(&self) -> RuleLevel {
+        RuleLevel::Warn
+    }
+
+    /// 检查图谱，返回所有违规
+    ///
+    /// 违规的严重程度由 [`Validator`] 按规则级别统一设置
+    fn check(&self, graph: &dyn GraphStorage) -> Result<Vec<Violation>>;
+}
+
+/// 单条规则的检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleReport {
+    /// 规则名
+    pub rule: String,
+    /// 规则说明
+    pub description: String,
+    /// 违规
+    pub violations: Vec<Violation>,
+}
+
+/// 验证报告
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ValidationReport {
+    /// 有违规的规则（按注册顺序）
+    pub rules: Vec<RuleReport>,
+}
+
+impl ValidationReport {
+    /// 最严重的违规级别，没有违规时为 None
+    pub fn worst(&self) -> Option<Severity> {
+        self.violations().map(|v| v.severity).max()
+    }
+
+    /// 所有违规
+    pub fn violations(&self) -> impl Iterator<Item = &Violation> {
+        self.rules.iter().flat_map(|r| r.violations.iter())
+    }
+
+    /// 指定严重程度的违规数
+    pub fn count(&self, severity: Severity) -> usize {
+        self.violations().filter(|v| v.severity == severity).count()
+    }
+
+    /// 只保留不低于 `min` 的违规
+    pub fn filter(mut self, min: Severity) -> Self {
+        for rule in &mut self.rules {
+            rule.violations.retain(|v| v.severity >= min);
+        }
+        self.rules.retain(|r| !r.violations.is_empty());
+        self
+    }
+}
+
+/// 规则集合
+pub struct Validator {
+    rules: Vec<Box<dyn Rule>>,
+}
+
+impl Default for Validator {
+    fn default() -> Self {
+        Self::with_builtin_rules()
+    }
+}
+
+impl Validator {
+    /// 创建不含规则的验证器
+    pub fn empty() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// 创建包含所有内置规则的验证器
+    pub fn with_builtin_rules() -> Self {
+        let mut validator = Self::empty();
+        validator.register(Box::new(RequireTags));
+        validator.register(Box::new(EvidenceSupports));
+        validator.register(Box::new(QuestionContainsQuestion));
+        validator.register(Box::new(NoSelfLoops));
+        validator.register(Box::new(DanglingEdges));
+        validator
+    }
+
+    /// 注册规则（同名规则会被替换）
+    pub fn register(&mut self, rule: Box<dyn Rule>) {
+        self.rules.retain(|r| r.name() != rule.name());
+        self.rules.push(rule);
+    }
+
+    /// 已注册的规则
+    pub fn rules(&self) -> impl Iterator<Item = &dyn Rule> {
+        self.rules.iter().map(|r| r.as_ref())
+    }
+
+    /// 运行所有启用的规则
+    ///
+    /// # Arguments
+    ///
+    /// * `graph` - 图存储
+    /// * `config` - 规则级别配置
+    pub fn run(&self, graph: &dyn GraphStorage, config: &ValidateConfig) -> Result<ValidationReport> {
+        let mut report = ValidationReport::default();
+
+        for rule in &self.rules {
+            let level = config.rules.get(rule.name()).copied().unwrap_or_else(|| rule.default_level());
+            let Some(severity) = level.severity() else {
+                continue;
+            };
+
+            let mut violations = tracing::debug_span!("rule", name = rule.name()).in_scope(|| rule.check(graph))?;
+            if violations.is_empty() {
+                continue;
+            }
+            for violation in &mut violations {
+                violation.severity = severity;
+            }
+            report.rules.push(RuleReport {
+                rule: rule.name().to_string(),
+                description: rule.description().to_string(),
+                violations,
+            });
+        }
+
+        Ok(report)
+    }
+}
+
+/// 每篇笔记至少有一个标签
+pub struct RequireTags;
+
+impl Rule for RequireTags {
+    fn name(&self) -> &str {
+        "require_tags"
+    }
+
+    fn description(&self) -> &str {
+        "Every note needs at least one tag"
+    }
+
+    fn default_level(&self) -> RuleLevel {
+        RuleLevel::Off
+    }
+
+    fn check(&self, graph: &dyn GraphStorage) -> Result<Vec<Violation>> {
+        Ok(graph
+            .get_all_nodes()?
+            .into_iter()
+            .filter(|n| n.word_count().is_some() && n.tags.is_empty())
+            .map(|n| Violation::node(n.id, "note has no tags"))
+            .collect())
+    }
+}
+
+/// Evidence 节点至少有一条 Supports 出边
+pub struct EvidenceSupports;
+
+impl Rule for EvidenceSupports {
+    fn name(&self) -> &str {
+        "evidence_supports"
+    }
+
+    fn description(&self) -> &str {
+        "Evidence nodes must support at least one claim"
+    }
+
+    fn check(&self, graph: &dyn GraphStorage) -> Result<Vec<Violation>> {
+        let mut violations = Vec::new();
+        for node in graph.get_all_nodes()? {
+            if node.node_type != NodeType::Evidence {
+                continue;
+            }
+            let supports = graph
+                .get_edges_by_source(&node.id)?
+                .iter()
+                .any(|e| e.relation == RelationType::Supports);
+            if !supports {
+                violations.push(Violation::node(node.id, "Evidence node has no Supports edge"));
+            }
+        }
+        Ok(violations)
+    }
+}
+
+/// Question 之间不应使用 Contains 关系
+pub struct QuestionContainsQuestion;
+
+impl Rule for QuestionContainsQuestion {
+    fn name(&self) -> &str {
+        "question_contains_question"
+    }
+
+    fn description(&self) -> &str {
+        "Questions should not contain other questions"
+    }
+
+    fn check(&self, graph: &dyn GraphStorage) -> Result<Vec<Violation>> {
+        let questions: HashSet<String> = graph
+            .get_all_nodes()?
+            .into_iter()
+            .filter(|n| n.node_type == NodeType::Question)
+            .map(|n| n.id)
+            .collect();
+
+        Ok(graph
+            .get_all_edges()?
+            .into_iter()
+            .filter(|e| e.relation == RelationType::Contains && questions.contains(&e.from) && questions.contains(&e.to))
+            .map(|e| Violation::edge(e.id, "Question contains another Question"))
+            .collect())
+    }
+}
+
+/// 边的源和目标不能是同一节点
+pub struct NoSelfLoops;
+
+impl Rule for NoSelfLoops {
+    fn name(&self) -> &str {
+        "no_self_loops"
+    }
+
+    fn description(&self) -> &str {
+        "Edges must not point back to their source"
+    }
+
+    fn default_level(&self) -> RuleLevel {
+        RuleLevel::Error
+    }
+
+    fn check(&self, graph: &dyn GraphStorage) -> Result<Vec<Violation>> {
+        Ok(graph
+            .get_all_edges()?
+            .into_iter()
+            .filter(|e| e.from == e.to)
+            .map(|e| Violation::edge(e.id, "edge links a node to itself"))
+            .collect())
+    }
+}
+
+/// 边的端点必须存在
+pub struct DanglingEdges;
+
+impl Rule for DanglingEdges {
+    fn name(&self) -> &str {
+        "dangling_edges"
+    }
+
+    fn description(&self) -> &str {
+        "Edge endpoints must exist"
+    }
+
+    fn default_level(&self) -> RuleLevel {
+        RuleLevel::Error
+    }
+
+    fn check(&self, graph: &dyn GraphStorage) -> Result<Vec<Violation>> {
+        let mut violations = Vec::new();
+        for edge in graph.get_all_edges()? {
+            for endpoint in [&edge.from, &edge.to] {
+                if !graph.node_exists(endpoint)? {
+                    violations.push(Violation::edge(&edge.id, format!("missing node {}", endpoint)));
+                }
+            }
+        }
+        Ok(violations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Indexer;
+    use crate::storage::{Edge, Node, Storage};
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn storage() -> (TempDir, Storage) {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        (temp_dir, storage)
+    }
+
+    fn config(rules: &[(&str, RuleLevel)]) -> ValidateConfig {
+        ValidateConfig {
+            rules: rules.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_require_tags() {
+        let (temp, storage) = storage();
+        let indexer = Indexer::new(storage.clone());
+        fs::write(temp.path().join("tagged.md"), "---\ntags: [mind]\n---\nBody [[placeholder]]").unwrap();
+        indexer.index_file(&temp.path().join("tagged.md")).unwrap();
+
+        // 默认关闭；开启后通过（占位节点不算笔记）
+        let validator = Validator::with_builtin_rules();
+        assert!(validator.run(storage.graph(), &ValidateConfig::default()).unwrap().worst().is_none());
+        let enabled = config(&[("require_tags", RuleLevel::Warn)]);
+        assert!(validator.run(storage.graph(), &enabled).unwrap().worst().is_none());
+
+        fs::write(temp.path().join("bare.md"), "No tags here").unwrap();
+        indexer.index_file(&temp.path().join("bare.md")).unwrap();
+
+        let report = validator.run(storage.graph(), &enabled).unwrap();
+        assert_eq!(report.worst(), Some(Severity::Warning));
+        assert_eq!(report.rules.len(), 1);
+        assert_eq!(report.rules[0].rule, "require_tags");
+        assert_eq!(report.rules[0].violations[0].target, Target::Node("urn:memexia:file:bare.md".to_string()));
+
+        // 提升为错误后按最低级别过滤
+        let report = validator.run(storage.graph(), &config(&[("require_tags", RuleLevel::Error)])).unwrap();
+        assert_eq!(report.worst(), Some(Severity::Error));
+        assert_eq!(report.filter(Severity::Error).count(Severity::Error), 1);
+    }
+
+    #[test]
+    fn test_evidence_supports() {
+        let (_temp, storage) = storage();
+        let graph = storage.graph();
+        let evidence = "urn:memexia:file:data.md";
+        let claim = "urn:memexia:file:claim.md";
+        graph.add_node(&Node::new(evidence, NodeType::Evidence, "Data")).unwrap();
+        graph.add_node(&Node::new(claim, NodeType::Concept, "Claim")).unwrap();
+
+        let validator = Validator::with_builtin_rules();
+        let report = validator.run(graph, &ValidateConfig::default()).unwrap();
+        assert_eq!(report.rules.len(), 1);
+        assert_eq!(report.rules[0].rule, "evidence_supports");
+        assert_eq!(report.worst(), Some(Severity::Warning));
+
+        // 与 Supports 以外的关系连接仍然违规
+        graph.add_edge(&Edge::builder(evidence, claim, RelationType::RelatedTo).build().unwrap()).unwrap();
+        assert_eq!(validator.run(graph, &ValidateConfig::default()).unwrap().count(Severity::Warning), 1);
+
+        graph.add_edge(&Edge::builder(evidence, claim, RelationType::Supports).build().unwrap()).unwrap();
+        assert!(validator.run(graph, &ValidateConfig::default()).unwrap().worst().is_none());
+
+        // 关闭规则
+        graph.add_node(&Node::new("urn:memexia:file:more.md", NodeType::Evidence, "More")).unwrap();
+        let off = config(&[("evidence_supports", RuleLevel::Off)]);
+        assert!(validator.run(graph, &off).unwrap().worst().is_none());
+    }
+
+    /// 自定义规则：标题不能为空
+    struct NonEmptyTitle;
+
+    impl Rule for NonEmptyTitle {
+        fn name(&self) -> &str {
+            "non_empty_title"
+        }
+
+        fn description(&self) -> &str {
+            "Nodes need a title"
+        }
+
+        fn default_level(&self) -> RuleLevel {
+            RuleLevel::Error
+        }
+
+        fn check(&self, graph: &dyn GraphStorage) -> Result<Vec<Violation>> {
+            Ok(graph
+                .get_all_nodes()?
+                .into_iter()
+                .filter(|n| n.title.is_empty())
+                .map(|n| Violation::node(n.id, "empty title"))
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_register_custom_rule() {
+        let (_temp, storage) = storage();
+        storage
+            .graph()
+            .add_node(&Node::new("urn:memexia:file:x.md", NodeType::Concept, ""))
+            .unwrap();
+
+        let mut validator = Validator::empty();
+        validator.register(Box::new(NonEmptyTitle));
+        let report = validator.run(storage.graph(), &ValidateConfig::default()).unwrap();
+        assert_eq!(report.worst(), Some(Severity::Error));
+        assert_eq!(validator.rules().count(), 1);
+    }
+}
@@ -36,10 +36,10 @@ pub struct WatchConfig {
 impl WatchConfig {
     /// 创建新的默认配置
     ///
-    /// 默认允许所有 .md 文件，排除 .git 和临时文件
+    /// 默认允许所有 .md 文件（含 `.md.age` / `.md.gpg` 加密笔记），排除 .git 和临时文件
     pub fn new() -> Self {
         Self {
-            whitelist: vec!["*.md".to_string()],
+            whitelist: vec!["*.md".to_string(), "*.md.age".to_string(), "*.md.gpg".to_string()],
             blacklist: vec![
                 ".git/**/*".to_string(),
                 ".memexia/**/*".to_string(),
@@ -0,0 +1,225 @@
+//! 文件监听日志模块
+//!
+//! `memexia watch` 在 `.memexia/watch/journal.json` 中记录处理进度：
+//!
+//! - 启动时记录会话开始时间
+//! - 每处理完一批文件事件，把游标推进到本批开始前的时间
+//!
+//! 守护进程崩溃、被杀掉或机器休眠期间发生的文件变更不会产生事件。
+//! 下次启动时 [`catch_up`] 扫描修改时间晚于游标的文件，并借助 `memexia:contentHash`
+//! 跳过内容未变化的文件；游标之后被删除的文件对应的节点同样被软删除
+
+use crate::core::indexer::{IndexResult, IndexSummary, Indexer};
+use crate::core::watcher::FileEvent;
+use crate::error::Result;
+use crate::storage::ids;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use walkdir::WalkDir;
+
+/// 监听日志目录（相对仓库根目录）
+pub const WATCH_DIR: &str = ".memexia/watch";
+
+/// 日志文件名
+const JOURNAL_FILE: &str = "journal.json";
+
+/// 比较修改时间时向前放宽的秒数
+///
+/// 游标记录的是一批事件开始处理前的时间，去抖中尚未送达的事件可能早于游标；
+/// 放宽的窗口内的文件由内容哈希判断是否真的变化
+const CURSOR_SLACK_SECS: i64 = 5;
+
+/// 文件监听进度
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WatchJournal {
+    /// 最近一次会话的开始时间
+    #[serde(default)]
+    pub session_started: Option<DateTime<Utc>>,
+    /// 游标：此时间之前的变更已全部处理
+    #[serde(default)]
+    pub last_processed: Option<DateTime<Utc>>,
+}
+
+impl WatchJournal {
+    /// 读取仓库的监听日志，不存在时返回空日志
+    pub fn load(root: &Path) -> Result<Self> {
+        let path = journal_path(root);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// 写入监听日志
+    pub fn save(&self, root: &Path) -> Result<()> {
+        let path = journal_path(root);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // 先写临时文件再重命名，崩溃时不会留下截断的日志
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    /// 开始新会话
+    ///
+    /// # Returns
+    ///
+    /// 新会话的日志与上次会话的游标（首次监听时为 `None`）
+    pub fn start_session(root: &Path, now: DateTime<Utc>) -> Result<(Self, Option<DateTime<Utc>>)> {
+        let mut journal = Self::load(root)?;
+        let cursor = journal.last_processed;
+        journal.session_started = Some(now);
+        journal.save(root)?;
+        Ok((journal, cursor))
+    }
+
+    /// 推进游标并写入日志
+    ///
+    /// # Arguments
+    ///
+    /// * `processed_until` - 此时间之前的变更已处理完
+    pub fn checkpoint(&mut self, root: &Path, processed_until: DateTime<Utc>) -> Result<()> {
+        // 游标单调递增
+        if self.last_processed.is_some_and(|at| at >= processed_until) {
+            return Ok(());
+        }
+        self.last_processed = Some(processed_until);
+        self.save(root)
+    }
+}
+
+/// 监听日志文件路径
+pub fn journal_path(root: &Path) -> PathBuf {
+    root.join(WATCH_DIR).join(JOURNAL_FILE)
+}
+
+/// 补扫停机期间错过的变更
+///
+/// # Arguments
+///
+/// * `indexer` - 索引器
+/// * `root` - 仓库根目录
+/// * `since` - 上次会话的游标；为 `None` 时检查所有文件（内容未变化的仍被跳过）
+///
+/// # Returns
+///
+/// 索引汇总结果
+pub fn catch_up(indexer: &Indexer, root: &Path, since: Option<DateTime<Utc>>) -> Result<IndexSummary> {
+    let start = Instant::now();
+    let mut summary = IndexSummary::default();
+    let threshold = since.map(|at| at - chrono::Duration::seconds(CURSOR_SLACK_SECS));
+
+    for entry in WalkDir::new(root).into_iter().filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if !path.is_file() || !indexer.is_indexable(path) || !indexer.in_note_roots(path) {
+            continue;
+        }
+        if let Some(threshold) = threshold {
+            let modified = entry.metadata().ok().and_then(|m| m.modified().ok()).map(DateTime::<Utc>::from);
+            // 游标之前修改的文件只在图中缺少对应节点时才重新索引
+            if modified.is_some_and(|at| at < threshold) && indexer.storage().graph().node_exists(&indexer.path_to_id(path))? {
+                summary.add(IndexResult::Skipped);
+                continue;
+            }
+        }
+        match indexer.index_changed(path) {
+            Ok(result) => summary.add(result),
+            Err(e) => summary.errors.push((path.to_string_lossy().into_owned(), e.to_string())),
+        }
+    }
+
+    // 有内容哈希的文件节点来自文件索引，文件已不存在说明停机期间被删除
+    for node in indexer.storage().graph().list_nodes()? {
+        if node.content_hash().is_none() || !node.id.starts_with(ids::FILE_PREFIX) {
+            continue;
+        }
+        let path = root.join(ids::display_file_id(&node.id));
+        if path.exists() {
+            continue;
+        }
+        let event = FileEvent::Deleted(path.to_string_lossy().into_owned());
+        match indexer.handle_event(&event) {
+            Ok(result) => summary.add(result),
+            Err(e) => summary.errors.push((event.path().to_string(), e.to_string())),
+        }
+    }
+
+    summary.elapsed = start.elapsed();
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Storage;
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, Indexer) {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        (temp, Indexer::new(storage))
+    }
+
+    #[test]
+    fn test_checkpoint_is_monotonic() {
+        let temp = TempDir::new().unwrap();
+        let now = Utc::now();
+
+        let (mut journal, cursor) = WatchJournal::start_session(temp.path(), now).unwrap();
+        assert!(cursor.is_none());
+        journal.checkpoint(temp.path(), now).unwrap();
+        journal.checkpoint(temp.path(), now - chrono::Duration::seconds(10)).unwrap();
+
+        let loaded = WatchJournal::load(temp.path()).unwrap();
+        assert_eq!(loaded.last_processed, Some(now));
+        assert_eq!(loaded.session_started, Some(now));
+    }
+
+    #[test]
+    fn test_catch_up_indexes_changes_missed_while_stopped() {
+        let (temp, indexer) = setup();
+        let root = temp.path();
+        fs::write(root.join("stable.md"), "# Stable\n").unwrap();
+        fs::write(root.join("edited.md"), "# Before\n").unwrap();
+        fs::write(root.join("removed.md"), "# Removed\n").unwrap();
+
+        // 第一次会话：正常索引后记录游标，随后进程“崩溃”
+        let (mut journal, _) = WatchJournal::start_session(root, Utc::now()).unwrap();
+        indexer.index_all(root).unwrap();
+        journal.checkpoint(root, Utc::now()).unwrap();
+
+        // 停机期间的变更
+        fs::write(root.join("edited.md"), "# After\n\n[[Stable]]\n").unwrap();
+        fs::write(root.join("created.md"), "# Created\n").unwrap();
+        fs::remove_file(root.join("removed.md")).unwrap();
+
+        // 第二次会话：补扫
+        let (_, cursor) = WatchJournal::start_session(root, Utc::now()).unwrap();
+        assert!(cursor.is_some());
+        let summary = catch_up(&indexer, root, cursor).unwrap();
+
+        assert_eq!(summary.files_indexed, 2);
+        assert_eq!(summary.files_skipped, 1);
+        assert_eq!(summary.files_deleted, 1);
+        assert!(!summary.has_errors());
+
+        let graph = indexer.storage().graph();
+        let edited = graph.get_node(&indexer.path_to_id(&root.join("edited.md"))).unwrap().unwrap();
+        assert_eq!(edited.content_hash(), Some(crate::core::object::hash_content(b"# After\n\n[[Stable]]\n").as_str()));
+        assert!(graph.node_exists(&indexer.path_to_id(&root.join("created.md"))).unwrap());
+        let removed = graph.get_node(&indexer.path_to_id(&root.join("removed.md"))).unwrap().unwrap();
+        assert!(removed.is_deleted());
+
+        // 再次补扫没有变化
+        let again = catch_up(&indexer, root, cursor).unwrap();
+        assert_eq!(again.files_indexed, 0);
+        assert_eq!(again.files_deleted, 0);
+    }
+}
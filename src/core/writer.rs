@@ -0,0 +1,480 @@
+//! 节点元数据写回模块（`memexia node set`）
+//!
+//! 修改文件节点时由 [`update_frontmatter`] 把改动写回笔记的 frontmatter，文件始终是可信来源：
+//!
+//! - 只改写受管理的键 `title`、`type`、`tags`、`summary`、`aliases`，且只改写与文件当前
+//!   解析结果不同的键；其他键、键的顺序和正文字节保持不变
+//! - `tags` 只写入 frontmatter 中的部分，正文里的 `#tag` 不改动；列表保持原有的
+//!   `[a, b]` 或逐行 `- a` 写法，保留下来的条目保持原有引号
+//! - 先写同目录下的临时文件再重命名，写入是原子的
+//! - 写入后节点的 `memexia:contentHash` 更新为新内容的哈希；调用方保存节点后，
+//!   索引器把写回后的文件视为未变化（[`Indexer::is_unchanged`](super::Indexer::is_unchanged)），
+//!   监听器收到这次修改事件时不会再索引一遍
+//!
+//! 冲突规则：文件在上次同步后被修改过（内容哈希与节点记录不一致）时以文件为准，
+//! 不写回并返回 [`WriteBack::Conflict`]
+
+use crate::core::object;
+use crate::core::parser::frontmatter::escape_yaml_string;
+use crate::core::parser::{parse_markdown, try_parse_frontmatter};
+use crate::core::retag;
+use crate::error::{MemexiaError, Result};
+use crate::storage::{ids, Node, NodeType};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+/// 要写回的别名列表（节点元数据键，只在写回时使用，不保存到图中）
+pub const ALIASES_KEY: &str = "aliases";
+
+/// 写回结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WriteBack {
+    /// 已改写文件
+    Written,
+    /// frontmatter 已与节点一致，文件未改动
+    Unchanged,
+    /// 文件在上次同步后被修改过，以文件为准，未写回
+    Conflict,
+}
+
+/// 对节点的一次修改
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NodeEdit {
+    /// 新标题
+    pub title: Option<String>,
+    /// 新类型
+    pub node_type: Option<NodeType>,
+    /// 要添加的标签
+    pub add_tags: Vec<String>,
+    /// 要移除的标签
+    pub remove_tags: Vec<String>,
+    /// 新摘要
+    pub summary: Option<String>,
+    /// 新别名列表
+    pub aliases: Option<Vec<String>>,
+}
+
+impl NodeEdit {
+    /// 是否没有任何修改
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 把修改应用到节点
+    ///
+    /// 摘要记录为作者写的摘要（[`Node::set_summary`]），别名记录在 [`ALIASES_KEY`] 元数据中，供 [`update_frontmatter`] 写回
+    pub fn apply(&self, node: &mut Node) -> Result<()> {
+        for tag in &self.add_tags {
+            retag::validate_tag(tag)?;
+        }
+        if let Some(title) = &self.title {
+            if title.trim().is_empty() {
+                return Err(MemexiaError::Validation("Title cannot be empty".to_string()));
+            }
+            node.title = title.trim().to_string();
+        }
+        if let Some(node_type) = &self.node_type {
+            node.node_type = node_type.clone();
+        }
+        for tag in &self.remove_tags {
+            node.remove_tag(tag);
+        }
+        for tag in &self.add_tags {
+            node.add_tag(tag);
+        }
+        if let Some(summary) = &self.summary {
+            node.set_summary(summary.clone());
+        }
+        if let Some(aliases) = &self.aliases {
+            node.metadata.insert(ALIASES_KEY.to_string(), aliases.clone().into());
+        }
+        node.updated_at = chrono::Utc::now();
+        Ok(())
+    }
+}
+
+/// 一个字段修改前后的值
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldChange {
+    /// 字段名（`title`、`type`、`tags`）
+    pub field: &'static str,
+    /// 修改前的值
+    pub before: String,
+    /// 修改后的值
+    pub after: String,
+}
+
+/// 修改节点的结果（`memexia node set`）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NodeSet {
+    /// 节点 ID
+    pub id: String,
+    /// 实际改变的字段
+    pub changes: Vec<FieldChange>,
+    /// 写回结果；只修改了图时为 None
+    pub write_back: Option<WriteBack>,
+}
+
+/// 比较节点修改前后的标题、类型和标签（标签不计顺序）
+pub fn diff(before: &Node, after: &Node) -> Vec<FieldChange> {
+    let tags = |node: &Node| {
+        let mut tags = node.tags.clone();
+        tags.sort();
+        tags.join(", ")
+    };
+    let fields = [
+        ("title", before.title.clone(), after.title.clone()),
+        ("type", before.node_type.to_string(), after.node_type.to_string()),
+        ("tags", tags(before), tags(after)),
+    ];
+    fields
+        .into_iter()
+        .filter(|(_, before, after)| before != after)
+        .map(|(field, before, after)| FieldChange { field, before, after })
+        .collect()
+}
+
+/// 把节点的受管理字段写回笔记文件的 frontmatter
+///
+/// # Arguments
+///
+/// * `path` - 笔记文件路径
+/// * `node` - 由该文件索引得到的节点；写入后更新其内容哈希，由调用方保存
+pub fn update_frontmatter(path: &Path, node: &mut Node) -> Result<WriteBack> {
+    let bytes = fs::read(path)?;
+    let Some(synced) = node.content_hash() else {
+        return Err(MemexiaError::Validation(format!(
+            "Node '{}' was not indexed from {}",
+            node.id,
+            path.display()
+        )));
+    };
+    if object::hash_content(&bytes) != synced {
+        tracing::warn!(
+            file = %path.display(),
+            node = %node.id,
+            "File changed since it was last indexed; keeping the file and skipping the write-back"
+        );
+        return Ok(WriteBack::Conflict);
+    }
+    let content = String::from_utf8(bytes)
+        .map_err(|_| MemexiaError::Validation(format!("{} is not valid UTF-8", path.display())))?;
+
+    let Some(updated) = rewrite(&content, node, path)? else {
+        return Ok(WriteBack::Unchanged);
+    };
+    write_atomically(path, &updated)?;
+    node.set_content_hash(object::hash_content(updated.as_bytes()));
+    Ok(WriteBack::Written)
+}
+
+/// 计算写回后的文件内容；没有需要改写的键时返回 None
+fn rewrite(content: &str, node: &Node, path: &Path) -> Result<Option<String>> {
+    let invalid = |message: String| MemexiaError::Validation(format!("Cannot update {}: {}", path.display(), message));
+    let frontmatter = try_parse_frontmatter(content)
+        .map_err(|e| invalid(format!("invalid frontmatter at line {}: {}", e.line, e.message)))?;
+    let (block, body) = retag::split_frontmatter(content);
+    if block.is_empty() && frontmatter.is_some() {
+        return Err(invalid("frontmatter does not start at the first line".to_string()));
+    }
+
+    // 与索引器使用相同的文件名，标题回退到文件名时比较结果一致
+    let file_name = if node.id.starts_with("urn:memexia:file:") {
+        ids::display_file_id(&node.id)
+    } else {
+        path.file_name().unwrap_or_default().to_string_lossy().into_owned()
+    };
+    let (doc, _) = parse_markdown(content, &file_name);
+    let frontmatter = frontmatter.unwrap_or_default();
+
+    let mut lines = FrontmatterLines::new(block);
+    if node.title != doc.get_title() {
+        lines.set_scalar("title", &yaml_scalar(&node.title));
+    }
+    if node.node_type != doc.get_node_type() {
+        lines.set_scalar("type", &node.node_type.to_string());
+    }
+
+    // 正文中已有的 #tag 不需要写入 frontmatter
+    let tags: Vec<String> = node
+        .tags
+        .iter()
+        .filter(|tag| frontmatter.tags.contains(tag) || !doc.tags.contains(tag))
+        .cloned()
+        .collect();
+    if !same_items(&tags, &frontmatter.tags) {
+        lines.set_list("tags", &ordered(&frontmatter.tags, &tags));
+    }
+
+    // 自动提取的摘要只存在于图中，不写入 frontmatter
+    if let Some(summary) = node.summary().filter(|_| !node.is_auto_summary()) {
+        if frontmatter.summary.as_deref() != Some(summary) {
+            lines.set_scalar("summary", &yaml_scalar(summary));
+        }
+    }
+    if let Some(aliases) = node.metadata.get(ALIASES_KEY).and_then(|v| v.as_array()) {
+        let aliases: Vec<String> = aliases.iter().filter_map(|a| a.as_str()).map(str::to_string).collect();
+        if aliases != frontmatter.aliases {
+            lines.set_list("aliases", &aliases);
+        }
+    }
+
+    if !lines.changed {
+        return Ok(None);
+    }
+    Ok(Some(format!("{}{}", lines.render(), body)))
+}
+
+/// 两个列表是否包含相同的条目（不计顺序）
+fn same_items(a: &[String], b: &[String]) -> bool {
+    a.len() == b.len() && a.iter().all(|item| b.contains(item))
+}
+
+/// 保持原有条目的顺序，新条目追加在后
+fn ordered(existing: &[String], wanted: &[String]) -> Vec<String> {
+    let mut result: Vec<String> = existing.iter().filter(|item| wanted.contains(item)).cloned().collect();
+    for item in wanted {
+        if !result.contains(item) {
+            result.push(item.clone());
+        }
+    }
+    result
+}
+
+/// 按需加引号的 YAML 标量
+fn yaml_scalar(value: &str) -> String {
+    let plain = value.chars().next().is_some_and(char::is_alphanumeric)
+        && value.trim_end() == value
+        && value.chars().all(|c| c.is_alphanumeric() || matches!(c, ' ' | '_' | '-' | '/' | '.'))
+        && value.parse::<f64>().is_err()
+        && !matches!(
+            value.to_ascii_lowercase().as_str(),
+            "true" | "false" | "yes" | "no" | "on" | "off" | "null"
+        );
+    if plain {
+        value.to_string()
+    } else {
+        format!("\"{}\"", escape_yaml_string(value))
+    }
+}
+
+/// 按行编辑的 frontmatter 块
+struct FrontmatterLines {
+    /// 两条 `---` 之间的行（不含换行符）
+    lines: Vec<String>,
+    /// 换行符（沿用原文件的 `\n` 或 `\r\n`）
+    ending: &'static str,
+    changed: bool,
+}
+
+impl FrontmatterLines {
+    /// 由 [`retag::split_frontmatter`] 拆出的块创建；块为空时写回会新建 frontmatter
+    fn new(block: &str) -> Self {
+        let ending = if block.contains("\r\n") { "\r\n" } else { "\n" };
+        let mut lines: Vec<String> = block.lines().map(str::to_string).collect();
+        // 去掉两条 `---`
+        if lines.len() >= 2 {
+            lines.pop();
+            lines.remove(0);
+        }
+        Self {
+            lines,
+            ending,
+            changed: false,
+        }
+    }
+
+    /// 顶层键所在行及其后续缩进行或列表项的范围
+    fn find(&self, key: &str) -> Option<(usize, usize)> {
+        let start = self
+            .lines
+            .iter()
+            .position(|line| line.strip_prefix(key).is_some_and(|rest| rest.starts_with(':')))?;
+        let end = self.lines[start + 1..]
+            .iter()
+            .position(|line| !(line.starts_with([' ', '\t']) || line.starts_with("- ") || line == "-"))
+            .map_or(self.lines.len(), |offset| start + 1 + offset);
+        Some((start, end))
+    }
+
+    /// 替换顶层键（不存在时追加在末尾）
+    fn replace(&mut self, key: &str, new_lines: Vec<String>) {
+        match self.find(key) {
+            Some((start, end)) => {
+                self.lines.splice(start..end, new_lines);
+            }
+            None => self.lines.extend(new_lines),
+        }
+        self.changed = true;
+    }
+
+    fn set_scalar(&mut self, key: &str, value: &str) {
+        self.replace(key, vec![format!("{}: {}", key, value)]);
+    }
+
+    /// 写入列表，沿用原有写法和已有条目的原文
+    fn set_list(&mut self, key: &str, items: &[String]) {
+        let existing: Vec<String> = match self.find(key) {
+            Some((start, end)) => self.lines[start..end].to_vec(),
+            None => Vec::new(),
+        };
+        // 原有条目：值 -> 原文
+        let mut originals: Vec<(String, String)> = Vec::new();
+        let mut block_indent = None;
+        if let Some((first, rest)) = existing.split_first() {
+            let value = first[key.len() + 1..].trim();
+            if let Some(inner) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+                originals.extend(inner.split(',').map(str::trim).map(|item| (retag::unquote(item).to_string(), item.to_string())));
+            } else if !value.is_empty() {
+                originals.push((retag::unquote(value).to_string(), value.to_string()));
+            }
+            for line in rest {
+                let trimmed = line.trim_start();
+                if let Some(item) = trimmed.strip_prefix("- ") {
+                    block_indent.get_or_insert_with(|| line[..line.len() - trimmed.len()].to_string());
+                    originals.push((retag::unquote(item.trim()).to_string(), item.trim().to_string()));
+                }
+            }
+        }
+        let render = |item: &String| {
+            originals
+                .iter()
+                .find(|(value, _)| value == item)
+                .map_or_else(|| yaml_scalar(item), |(_, original)| original.clone())
+        };
+
+        let new_lines = match block_indent {
+            Some(indent) if !items.is_empty() => std::iter::once(format!("{}:", key))
+                .chain(items.iter().map(|item| format!("{}- {}", indent, render(item))))
+                .collect(),
+            _ => vec![format!("{}: [{}]", key, items.iter().map(render).collect::<Vec<_>>().join(", "))],
+        };
+        self.replace(key, new_lines);
+    }
+
+    /// 输出完整的 frontmatter 块（含两条 `---`）
+    fn render(&self) -> String {
+        let mut out = format!("---{}", self.ending);
+        for line in &self.lines {
+            out.push_str(line);
+            out.push_str(self.ending);
+        }
+        out.push_str("---");
+        out.push_str(self.ending);
+        out
+    }
+}
+
+/// 先写同目录下的临时文件再重命名，避免留下写了一半的笔记
+fn write_atomically(path: &Path, content: &str) -> Result<()> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let tmp = path.with_file_name(format!(".{}.memexia-tmp", name));
+    fs::write(&tmp, content)?;
+    if let Err(e) = fs::rename(&tmp, path) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{FileEvent, IndexResult, Indexer};
+    use crate::storage::Storage;
+    use tempfile::TempDir;
+
+    const NOTE: &str = "---\r\ntitle: Free Will\r\nauthor: me # keep this comment\r\ntags:\r\n    - \"mind\"\r\n    - ethics\r\nextra:\r\n  nested: true\r\n---\r\n\r\n# Free Will\r\n\r\nBody with #inline tag and [[Other]].  \r\nTrailing spaces stay.\r\n";
+
+    /// 索引一篇笔记，返回存储、文件路径和节点
+    fn indexed(content: &str) -> (TempDir, Storage, std::path::PathBuf, Node) {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let path = temp.path().join("free-will.md");
+        fs::write(&path, content).unwrap();
+        let indexer = Indexer::new(storage.clone());
+        indexer.index_file(&path).unwrap();
+        let node = storage.graph().get_node(&indexer.path_to_id(&path)).unwrap().unwrap();
+        (temp, storage, path, node)
+    }
+
+    fn body(content: &str) -> &str {
+        retag::split_frontmatter(content).1
+    }
+
+    #[test]
+    fn test_round_trip_preserves_unknown_keys_and_body() {
+        let (_temp, _storage, path, mut node) = indexed(NOTE);
+        let edit = NodeEdit {
+            title: Some("Free Will: a survey".to_string()),
+            node_type: Some(NodeType::Question),
+            add_tags: vec!["philosophy".to_string()],
+            remove_tags: vec!["ethics".to_string()],
+            summary: Some("Do we choose?".to_string()),
+            aliases: Some(vec!["Volition".to_string()]),
+        };
+        edit.apply(&mut node).unwrap();
+        assert_eq!(update_frontmatter(&path, &mut node).unwrap(), WriteBack::Written);
+
+        let written = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            retag::split_frontmatter(&written).0,
+            "---\r\ntitle: \"Free Will: a survey\"\r\nauthor: me # keep this comment\r\ntags:\r\n    - \"mind\"\r\n    - philosophy\r\nextra:\r\n  nested: true\r\ntype: Question\r\nsummary: \"Do we choose?\"\r\naliases: [Volition]\r\n---\r\n"
+        );
+        assert_eq!(body(&written).as_bytes(), body(NOTE).as_bytes());
+
+        let (doc, warnings) = parse_markdown(&written, "free-will.md");
+        assert!(warnings.is_empty());
+        let frontmatter = doc.frontmatter.unwrap();
+        assert_eq!(frontmatter.title.as_deref(), Some("Free Will: a survey"));
+        assert_eq!(frontmatter.node_type, NodeType::Question);
+        assert_eq!(frontmatter.tags, ["mind", "philosophy"]);
+        assert_eq!(frontmatter.aliases, ["Volition"]);
+
+        // 再次写回时没有差异
+        assert_eq!(update_frontmatter(&path, &mut node).unwrap(), WriteBack::Unchanged);
+    }
+
+    #[test]
+    fn test_creates_frontmatter() {
+        let content = "# Note\n\nText #inline\n";
+        let (_temp, _storage, path, mut node) = indexed(content);
+        NodeEdit {
+            add_tags: vec!["inline".to_string(), "new".to_string()],
+            ..Default::default()
+        }
+        .apply(&mut node)
+        .unwrap();
+        assert_eq!(update_frontmatter(&path, &mut node).unwrap(), WriteBack::Written);
+        assert_eq!(fs::read_to_string(&path).unwrap(), format!("---\ntags: [new]\n---\n{}", content));
+    }
+
+    #[test]
+    fn test_conflict_keeps_file() {
+        let (_temp, _storage, path, mut node) = indexed(NOTE);
+        fs::write(&path, "# Edited elsewhere\n").unwrap();
+        node.title = "Other".to_string();
+        assert_eq!(update_frontmatter(&path, &mut node).unwrap(), WriteBack::Conflict);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# Edited elsewhere\n");
+    }
+
+    #[test]
+    fn test_write_back_is_not_reindexed() {
+        let (_temp, storage, path, mut node) = indexed(NOTE);
+        node.title = "Renamed".to_string();
+        assert_eq!(update_frontmatter(&path, &mut node).unwrap(), WriteBack::Written);
+        storage.graph().update_node(&node).unwrap();
+
+        let indexer = Indexer::new(storage.clone());
+        assert!(indexer.is_unchanged(&path).unwrap());
+        let event = FileEvent::Modified(path.to_string_lossy().into_owned());
+        assert_eq!(indexer.handle_event(&event).unwrap(), IndexResult::Skipped);
+
+        // 之后的外部修改照常索引
+        fs::write(&path, fs::read_to_string(&path).unwrap().replace("Renamed", "Again")).unwrap();
+        assert!(matches!(indexer.handle_event(&event).unwrap(), IndexResult::Indexed { .. }));
+        assert_eq!(storage.graph().get_node(&node.id).unwrap().unwrap().title, "Again");
+    }
+}
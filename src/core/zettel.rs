@@ -0,0 +1,157 @@
+//! Zettelkasten ID 模块
+//!
+//! 支持以时间戳 ID 开头的文件名（如 `202403151030 Free Will.md`）
+//!
+//! 启用 `ids.zettel = true` 后：
+//!
+//! - 节点 ID 取自文件名前缀：`urn:memexia:zettel:202403151030`，重命名标题部分不改变身份
+//! - `[[202403151030]]` 或任何以该 ID 开头的链接目标都解析到同一节点
+
+use chrono::{Duration, NaiveDateTime};
+use std::collections::HashSet;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Zettel 节点 ID 前缀
+pub const ZETTEL_ID_PREFIX: &str = "urn:memexia:zettel:";
+
+/// 生成 ID 使用的时间格式（精确到分钟）
+pub const ZETTEL_ID_FORMAT: &str = "%Y%m%d%H%M";
+
+/// 时间戳 ID 的最小/最大位数（`YYYYMMDDHHMM` / `YYYYMMDDHHMMSS`）
+const MIN_ID_LEN: usize = 12;
+const MAX_ID_LEN: usize = 14;
+
+/// 从文件名或链接目标中提取 Zettel ID
+///
+/// 只检查最后一个路径组件；ID 必须是 12-14 位数字，其后为结尾、空白、`-`、`_` 或 `.`
+///
+/// # Arguments
+///
+/// * `name` - 文件路径或链接目标
+///
+/// # Returns
+///
+/// 提取出的 ID，不符合格式时返回 None
+pub fn extract_zettel_id(name: &str) -> Option<&str> {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    let digits = base.chars().take_while(|c| c.is_ascii_digit()).count();
+
+    if !(MIN_ID_LEN..=MAX_ID_LEN).contains(&digits) {
+        return None;
+    }
+
+    match base[digits..].chars().next() {
+        None => Some(&base[..digits]),
+        Some(c) if c.is_whitespace() || matches!(c, '-' | '_' | '.') => Some(&base[..digits]),
+        _ => None,
+    }
+}
+
+/// 将 Zettel ID 转换为节点 ID
+pub fn zettel_node_id(id: &str) -> String {
+    format!("{}{}", ZETTEL_ID_PREFIX, id)
+}
+
+/// 解析链接目标为 Zettel 节点 ID
+///
+/// 目标以 Zettel ID 开头时返回对应节点 ID，否则返回 None
+pub fn resolve_link_target(target: &str) -> Option<String> {
+    extract_zettel_id(target).map(zettel_node_id)
+}
+
+/// 生成新的 Zettel ID
+///
+/// 以当前时间（分钟精度）为基础，与已有 ID 冲突时顺延一分钟
+///
+/// # Arguments
+///
+/// * `now` - 当前本地时间
+/// * `existing` - 已被占用的 ID 集合
+pub fn generate_zettel_id(now: NaiveDateTime, existing: &HashSet<String>) -> String {
+    let mut time = now;
+    loop {
+        let id = time.format(ZETTEL_ID_FORMAT).to_string();
+        if !existing.contains(&id) {
+            return id;
+        }
+        time += Duration::minutes(1);
+    }
+}
+
+/// 收集仓库中已使用的 Zettel ID
+///
+/// 跳过隐藏目录（如 `.memexia`、`.git`）
+pub fn collect_zettel_ids(root: &Path) -> HashSet<String> {
+    WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| extract_zettel_id(&e.file_name().to_string_lossy()).map(|id| id.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_extract_zettel_id() {
+        assert_eq!(extract_zettel_id("202403151030 Free Will.md"), Some("202403151030"));
+        assert_eq!(extract_zettel_id("notes/20240315103059-free-will.md"), Some("20240315103059"));
+        assert_eq!(extract_zettel_id("202403151030"), Some("202403151030"));
+        assert_eq!(extract_zettel_id("202403151030.md"), Some("202403151030"));
+
+        // 位数不对或后面紧跟字母
+        assert_eq!(extract_zettel_id("2024 Free Will.md"), None);
+        assert_eq!(extract_zettel_id("202403151030abc.md"), None);
+        assert_eq!(extract_zettel_id("Free Will.md"), None);
+        assert_eq!(extract_zettel_id("2024031510301234.md"), None);
+    }
+
+    #[test]
+    fn test_resolve_link_target() {
+        assert_eq!(
+            resolve_link_target("202403151030"),
+            Some("urn:memexia:zettel:202403151030".to_string())
+        );
+        assert_eq!(
+            resolve_link_target("202403151030 Renamed Title"),
+            Some("urn:memexia:zettel:202403151030".to_string())
+        );
+        assert_eq!(resolve_link_target("决定论"), None);
+    }
+
+    #[test]
+    fn test_generate_zettel_id_avoids_collisions() {
+        let now = NaiveDate::from_ymd_opt(2024, 3, 15)
+            .unwrap()
+            .and_hms_opt(10, 30, 42)
+            .unwrap();
+
+        let mut existing = HashSet::new();
+        assert_eq!(generate_zettel_id(now, &existing), "202403151030");
+
+        existing.insert("202403151030".to_string());
+        existing.insert("202403151031".to_string());
+        assert_eq!(generate_zettel_id(now, &existing), "202403151032");
+    }
+
+    #[test]
+    fn test_collect_zettel_ids() {
+        let temp_dir = TempDir::new().unwrap();
+        let notes = temp_dir.path().join("notes");
+        std::fs::create_dir_all(&notes).unwrap();
+        std::fs::create_dir_all(temp_dir.path().join(".memexia")).unwrap();
+        std::fs::write(notes.join("202403151030 A.md"), "").unwrap();
+        std::fs::write(notes.join("plain.md"), "").unwrap();
+        std::fs::write(temp_dir.path().join(".memexia/202401010000 hidden.md"), "").unwrap();
+
+        let ids = collect_zettel_ids(temp_dir.path());
+        assert_eq!(ids.len(), 1);
+        assert!(ids.contains("202403151030"));
+    }
+}
@@ -0,0 +1,255 @@
+//! 错误类型模块
+//!
+//! 定义库边界上的结构化错误类型 [`MemexiaError`]，便于调用方（如 GUI）按错误种类匹配处理；
+//! CLI 二进制内部仍使用 `anyhow`
+
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Memexia 库的结果类型
+pub type Result<T, E = MemexiaError> = std::result::Result<T, E>;
+
+/// Memexia 结构化错误
+#[derive(Debug, Error)]
+pub enum MemexiaError {
+    /// 指定路径（及其父目录）中不存在仓库
+    #[error("Not a Memexia repository (or any of the parent directories): {}", path.display())]
+    RepositoryNotFound {
+        /// 查找的起始路径
+        path: PathBuf,
+    },
+
+    /// 仓库已存在
+    #[error("Repository already exists at {}", path.display())]
+    AlreadyInitialized {
+        /// 仓库根路径
+        path: PathBuf,
+    },
+
+    /// 仓库结构损坏（缺少必要文件等）
+    #[error("Corrupt Memexia repository at {}: {reason}", path.display())]
+    CorruptRepository {
+        /// 仓库根路径
+        path: PathBuf,
+        /// 损坏原因
+        reason: String,
+    },
+
+    /// 仓库以只读模式打开，拒绝修改
+    #[error("Repository at {} is opened read-only; this operation would modify it", path.display())]
+    ReadOnlyRepository {
+        /// 仓库根路径
+        path: PathBuf,
+    },
+
+    /// 仓库锁被其他进程持有，等待超时
+    #[error("Repository is locked by another process ({}); retry later, or remove the lock file if no other memexia command is running", path.display())]
+    RepositoryLocked {
+        /// 锁文件路径
+        path: PathBuf,
+    },
+
+    /// 图存储被其他进程（通常是 `memexia serve`）打开，RocksDB 只允许一个进程写入
+    #[error("The graph store of the repository at {} is in use by another process (such as `memexia serve` or another memexia command); wait for it to finish or stop the server", path.display())]
+    StoreBusy {
+        /// 仓库根路径
+        path: PathBuf,
+    },
+
+    /// 图存储无法打开（RocksDB 文件损坏或缺失）
+    ///
+    /// 只在首次使用图存储时返回：不需要图的命令（`status`、`log`、`config`、`doctor`）仍可运行，
+    /// `memexia reindex --full` 会把损坏的存储移开并从笔记文件重建，见 [`Repository::recover_store`](crate::core::Repository::recover_store)
+    #[error("The graph store of the repository at {} cannot be opened ({reason}); rebuild it from your notes with `memexia reindex --full`, or replace .memexia/graph with a backup copy", path.display())]
+    StoreCorrupt {
+        /// 仓库根路径
+        path: PathBuf,
+        /// 打开失败的原因
+        reason: String,
+    },
+
+    /// 没有仓库的写权限（如只读挂载）
+    #[error("Cannot write to the repository at {} (permission denied); retry with --read-only to open it without writing", path.display())]
+    WriteAccessDenied {
+        /// 仓库根路径
+        path: PathBuf,
+    },
+
+    /// 节点不存在
+    #[error("Node not found: {id}")]
+    NodeNotFound {
+        /// 节点 ID
+        id: String,
+    },
+
+    /// 边不存在
+    #[error("Edge not found: {id}")]
+    EdgeNotFound {
+        /// 边 ID
+        id: String,
+    },
+
+    /// 图快照不存在
+    #[error("Snapshot not found: {hash}")]
+    SnapshotNotFound {
+        /// 快照哈希
+        hash: String,
+    },
+
+    /// 图快照已被保留策略清理
+    #[error("Snapshot {hash} was pruned by the retention policy")]
+    SnapshotPruned {
+        /// 快照哈希
+        hash: String,
+    },
+
+    /// 暂存区为空
+    #[error("Nothing to commit")]
+    NothingToCommit,
+
+    /// 操作被中止（超时或调用方取消）
+    #[error("Operation cancelled")]
+    Cancelled,
+
+    /// 文件解析失败
+    #[error("Failed to parse {file}: {reason}")]
+    ParseError {
+        /// 文件路径
+        file: String,
+        /// 失败原因
+        reason: String,
+    },
+
+    /// 暂存文件无法提交（读取、解析或写入失败），整个提交已中止
+    #[error("Cannot commit {file}: {source}")]
+    StagedFile {
+        /// 文件路径（相对仓库根目录）
+        file: String,
+        /// 失败原因
+        source: Box<MemexiaError>,
+    },
+
+    /// 输入校验失败
+    #[error("{0}")]
+    Validation(String),
+
+    /// 图存储错误
+    #[error("Storage error: {0}")]
+    StorageError(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+    /// Git 错误
+    #[error("Git error: {0}")]
+    GitError(#[from] git2::Error),
+
+    /// IO 错误
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// JSON 序列化错误
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    /// 其他错误（用于尚未迁移到结构化错误的内部代码）
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+/// 为 Oxigraph 的错误类型实现到 `StorageError` 的转换
+macro_rules! impl_storage_error {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl From<$ty> for MemexiaError {
+                fn from(err: $ty) -> Self {
+                    MemexiaError::StorageError(Box::new(err))
+                }
+            }
+        )*
+    };
+}
+
+impl_storage_error!(
+    oxigraph::store::StorageError,
+    oxigraph::store::SerializerError,
+    oxigraph::store::LoaderError,
+    oxigraph::model::IriParseError,
+    oxigraph::sparql::QueryEvaluationError,
+);
+
+impl MemexiaError {
+    /// 错误是否由存储的锁文件被其他进程持有引起
+    ///
+    /// RocksDB 通过错误消息报告锁冲突（如 `While lock file: ...: Resource temporarily unavailable`），
+    /// 只能按消息识别
+    pub fn is_lock_conflict(&self) -> bool {
+        match self {
+            MemexiaError::StoreBusy { .. } => true,
+            MemexiaError::StorageError(err) => {
+                let message = err.to_string().to_lowercase();
+                message.contains("lock file") || message.contains("lock hold")
+            }
+            _ => false,
+        }
+    }
+
+    /// 错误是否由权限不足或只读文件系统引起
+    ///
+    /// 沿错误链查找 [`std::io::Error`]，用于在打开仓库失败时提示 `--read-only`
+    pub fn is_permission_denied(&self) -> bool {
+        let mut current: Option<&(dyn std::error::Error + 'static)> = Some(self);
+        while let Some(err) = current {
+            if let Some(io) = err.downcast_ref::<std::io::Error>() {
+                if matches!(
+                    io.kind(),
+                    std::io::ErrorKind::PermissionDenied | std::io::ErrorKind::ReadOnlyFilesystem
+                ) {
+                    return true;
+                }
+            }
+            current = match err.downcast_ref::<MemexiaError>() {
+                // `#[error(transparent)]` 的 source 会跳过被包装的错误本身
+                Some(MemexiaError::Io(io)) => Some(io),
+                Some(MemexiaError::Other(other)) => Some(other.as_ref()),
+                _ => err.source(),
+            };
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_messages() {
+        let err = MemexiaError::NodeNotFound {
+            id: "urn:memexia:file:a.md".to_string(),
+        };
+        assert_eq!(err.to_string(), "Node not found: urn:memexia:file:a.md");
+
+        let err = MemexiaError::SnapshotNotFound { hash: "abc".to_string() };
+        assert_eq!(err.to_string(), "Snapshot not found: abc");
+    }
+
+    #[test]
+    fn test_anyhow_roundtrip_preserves_variant() {
+        let err: anyhow::Error = MemexiaError::NothingToCommit.into();
+        assert!(matches!(
+            err.downcast_ref::<MemexiaError>(),
+            Some(MemexiaError::NothingToCommit)
+        ));
+    }
+
+    #[test]
+    fn test_is_permission_denied() {
+        let denied = || std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        assert!(MemexiaError::Io(denied()).is_permission_denied());
+        assert!(MemexiaError::StorageError(Box::new(denied())).is_permission_denied());
+        let err = anyhow::Error::from(denied()).context("Failed to write .memexia/schema_version");
+        assert!(MemexiaError::Other(err).is_permission_denied());
+
+        let missing = std::io::Error::from(std::io::ErrorKind::NotFound);
+        assert!(!MemexiaError::Io(missing).is_permission_denied());
+        assert!(!MemexiaError::NothingToCommit.is_permission_denied());
+    }
+}
@@ -0,0 +1,237 @@
+//! 仓库事件模块
+//!
+//! 把 Memexia 嵌入其他应用（如桌面 GUI）时，通过 [`Repository::subscribe`](crate::core::Repository::subscribe)
+//! 订阅仓库事件，而不是解析命令行输出：
+//!
+//! | 事件 | 发出位置 |
+//! |------|----------|
+//! | [`RepoEvent::NodeIndexed`] | 索引器索引文件、`commit` 处理暂存文件 |
+//! | [`RepoEvent::NodeDeleted`] | 文件删除（移入回收站）、`noindex` 排除、重命名取代旧节点 |
+//! | [`RepoEvent::EdgeAdded`] / [`RepoEvent::EdgeRemoved`] | 索引时节点出边的增减 |
+//! | [`RepoEvent::SnapshotTaken`] | 图历史存储快照（提交时和自动快照） |
+//! | [`RepoEvent::CommitCreated`] | Git 提交和修改提交 |
+//! | [`RepoEvent::ValidationFailed`] | 提交前验证发现错误 |
+//!
+//! 事件总线保存在 [`Storage`](crate::storage::Storage) 中，克隆的存储共享同一总线，
+//! 因此仓库内部各处（索引器、VCS、图历史）都能发出事件。没有订阅者时发出事件不做任何事，
+//! 需要额外查询才能构造的事件先检查 [`EventBus::is_active`]
+//!
+//! 每个订阅者拿到独立的 [`Receiver`]，接收端被丢弃后在下一次发出事件时自动退订
+//!
+//! `memexia serve` 目前没有 WebSocket 推送通道；今后的推送应基于本总线实现，
+//! 并通过 [`crate::metrics`] 的 `websocket_connected`/`websocket_disconnected` 计数客户端。
+//! 需要逐条存储写入（而不是按操作汇总）的消费者改用
+//! [`StorageObserver`](crate::storage::StorageObserver)
+
+use crate::error::Result;
+use crate::storage::{Edge, GraphStorage, Node};
+use serde::Serialize;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+/// 仓库事件
+///
+/// 事件携带更新界面所需的 ID 和标题，收到后无需再查询图
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum RepoEvent {
+    /// 节点被（重新）索引
+    NodeIndexed {
+        /// 节点 ID
+        id: String,
+        /// 标题
+        title: String,
+        /// 节点类型
+        node_type: String,
+        /// 标签
+        tags: Vec<String>,
+        /// 源文件（相对仓库根目录）
+        path: String,
+    },
+    /// 节点被删除、移入回收站或排除出索引，与它相连的边随之失效
+    NodeDeleted {
+        /// 节点 ID
+        id: String,
+        /// 标题
+        title: String,
+    },
+    /// 新增边
+    EdgeAdded {
+        /// 边 ID
+        id: String,
+        /// 源节点 ID
+        from: String,
+        /// 目标节点 ID
+        to: String,
+        /// 关系类型
+        relation: String,
+        /// 关系强度
+        strength: f64,
+    },
+    /// 删除边
+    EdgeRemoved {
+        /// 边 ID
+        id: String,
+        /// 源节点 ID
+        from: String,
+        /// 目标节点 ID
+        to: String,
+        /// 关系类型
+        relation: String,
+    },
+    /// 创建了 Git 提交
+    CommitCreated {
+        /// 提交 OID
+        oid: String,
+        /// 提交消息（不含 Memexia 追加的 trailer）
+        message: String,
+    },
+    /// 存储了图快照
+    SnapshotTaken {
+        /// 快照哈希
+        hash: String,
+    },
+    /// 提交前验证发现错误级别的违规，提交被中止
+    ValidationFailed {
+        /// 违规说明（`目标: 说明`）
+        errors: Vec<String>,
+    },
+}
+
+impl RepoEvent {
+    /// 节点索引事件
+    pub fn node_indexed(node: &Node, path: &str) -> Self {
+        RepoEvent::NodeIndexed {
+            id: node.id.clone(),
+            title: node.title.clone(),
+            node_type: node.node_type.to_string(),
+            tags: node.tags.clone(),
+            path: path.to_string(),
+        }
+    }
+
+    /// 节点删除事件
+    pub fn node_deleted(node: &Node) -> Self {
+        RepoEvent::NodeDeleted {
+            id: node.id.clone(),
+            title: node.title.clone(),
+        }
+    }
+
+    /// 新增边事件
+    pub fn edge_added(edge: &Edge) -> Self {
+        RepoEvent::EdgeAdded {
+            id: edge.id.clone(),
+            from: edge.from.clone(),
+            to: edge.to.clone(),
+            relation: edge.relation.to_string(),
+            strength: edge.strength,
+        }
+    }
+
+    /// 删除边事件
+    pub fn edge_removed(edge: &Edge) -> Self {
+        RepoEvent::EdgeRemoved {
+            id: edge.id.clone(),
+            from: edge.from.clone(),
+            to: edge.to.clone(),
+            relation: edge.relation.to_string(),
+        }
+    }
+}
+
+/// 事件总线
+///
+/// 克隆得到的总线共享同一组订阅者
+#[derive(Debug, Clone, Default)]
+pub struct EventBus {
+    subscribers: Arc<Mutex<Vec<Sender<RepoEvent>>>>,
+}
+
+impl EventBus {
+    /// 创建没有订阅者的总线
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 订阅之后发出的所有事件
+    pub fn subscribe(&self) -> Receiver<RepoEvent> {
+        let (tx, rx) = mpsc::channel();
+        self.lock().push(tx);
+        rx
+    }
+
+    /// 是否有订阅者
+    pub fn is_active(&self) -> bool {
+        !self.lock().is_empty()
+    }
+
+    /// 向所有订阅者发出事件，同时移除已断开的订阅者
+    pub fn emit(&self, event: RepoEvent) {
+        let mut subscribers = self.lock();
+        if subscribers.is_empty() {
+            return;
+        }
+        subscribers.retain(|tx| tx.send(event.clone()).is_ok());
+    }
+
+    /// 节点当前的出边；没有订阅者时不查询，返回空列表
+    ///
+    /// 与写入后的出边一起传给 [`emit_edge_changes`](Self::emit_edge_changes)
+    pub fn outgoing_edges(&self, graph: &dyn GraphStorage, id: &str) -> Result<Vec<Edge>> {
+        if !self.is_active() {
+            return Ok(Vec::new());
+        }
+        graph.get_edges_by_source(id)
+    }
+
+    /// 比较节点写入前后的出边，发出新增和删除边事件
+    ///
+    /// # Arguments
+    ///
+    /// * `graph` - 图存储
+    /// * `id` - 节点 ID
+    /// * `before` - 写入前由 [`outgoing_edges`](Self::outgoing_edges) 取得的出边
+    pub fn emit_edge_changes(&self, graph: &dyn GraphStorage, id: &str, before: &[Edge]) -> Result<()> {
+        if !self.is_active() {
+            return Ok(());
+        }
+        let after = graph.get_edges_by_source(id)?;
+        let same = |a: &Edge, b: &Edge| a.to == b.to && a.relation == b.relation;
+        for edge in before.iter().filter(|edge| !after.iter().any(|e| same(e, edge))) {
+            self.emit(RepoEvent::edge_removed(edge));
+        }
+        for edge in after.iter().filter(|edge| !before.iter().any(|e| same(e, edge))) {
+            self.emit(RepoEvent::edge_added(edge));
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Sender<RepoEvent>>> {
+        // 发送不会 panic，锁不会中毒；万一中毒也继续使用内部数据
+        self.subscribers.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emit_without_subscribers_is_noop() {
+        let bus = EventBus::new();
+        assert!(!bus.is_active());
+        bus.emit(RepoEvent::SnapshotTaken { hash: "h".to_string() });
+
+        let rx = bus.clone().subscribe();
+        assert!(bus.is_active());
+        bus.emit(RepoEvent::SnapshotTaken { hash: "h".to_string() });
+        assert_eq!(rx.try_recv().unwrap(), RepoEvent::SnapshotTaken { hash: "h".to_string() });
+        assert!(rx.try_recv().is_err());
+
+        // 接收端丢弃后自动退订
+        drop(rx);
+        bus.emit(RepoEvent::SnapshotTaken { hash: "h".to_string() });
+        assert!(!bus.is_active());
+    }
+}
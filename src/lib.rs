@@ -1,10 +1,16 @@
 pub mod cli;
 pub mod core;
+pub mod error;
+pub mod events;
+pub mod metrics;
 pub mod storage;
+pub mod telemetry;
 pub mod vcs;
 
+pub use error::{MemexiaError, Result};
+
 pub fn init() -> anyhow::Result<()> {
     // Initialize global state if needed (e.g. logging)
-    tracing_subscriber::fmt::init();
+    telemetry::init(&telemetry::TraceOptions::default())?;
     Ok(())
 }
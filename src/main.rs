@@ -1,22 +1,44 @@
 use clap::Parser;
-use memexia::cli::{Cli, Commands, commands};
-
-fn main() -> anyhow::Result<()> {
-    // 初始化应用
-    memexia::init()?;
+use memexia::cli::{Cli, Commands, commands, exit, i18n, output, timefmt};
+use memexia::core::registry;
+use memexia::telemetry::{self, TraceOptions};
+use std::process::ExitCode;
+use std::time::Instant;
 
+fn main() -> ExitCode {
     // 解析命令行参数
     let cli = Cli::parse();
+    let verbose = cli.verbose;
+    output::set_pager_enabled(!cli.no_pager);
+    timefmt::set_style(timefmt::TimeStyle { utc: cli.utc, iso: cli.iso });
+    commands::set_read_only(cli.read_only);
+    commands::set_no_daemon(cli.no_daemon);
+    i18n::set_lang(i18n::detect(cli.lang.as_deref()));
+
+    // 初始化追踪（-v 输出 debug 日志和 span 耗时）
+    let options = TraceOptions {
+        verbose,
+        trace_json: cli.trace_json.clone(),
+    };
+    let timings = match telemetry::init(&options) {
+        Ok(timings) => timings,
+        Err(e) => return ExitCode::from(exit::report(&e, verbose).code()),
+    };
 
-    // 设置日志级别
-    if cli.verbose {
-        unsafe {
-            std::env::set_var("RUST_LOG", "debug");
-        }
+    // 打开的仓库登记到用户全局注册表；-C / --repo 切换工作目录
+    if let Some(path) = registry::default_path() {
+        registry::enable(path);
+    }
+    if let Err(e) = commands::enter_directory(cli.chdir.as_deref(), cli.repo.as_deref()) {
+        return ExitCode::from(exit::report(&e, verbose).code());
     }
 
+    let name = cli.command.name();
+    let root = std::env::current_dir().unwrap_or_default();
+    let start = Instant::now();
+
     // 执行命令
-    match cli.command {
+    let result = tracing::info_span!("command", name, root = %root.display()).in_scope(|| match cli.command {
         Commands::Init(args) => commands::init_repository(args),
         Commands::Add(args) => commands::add_files(args),
         Commands::Status(args) => commands::status(args),
@@ -28,8 +50,51 @@ fn main() -> anyhow::Result<()> {
         Commands::Sync(args) => commands::sync(args),
         Commands::Serve(args) => commands::serve(args),
         Commands::Config(args) => commands::config(args),
+        Commands::New(args) => commands::new_note(args),
+        Commands::Append(args) => commands::append(args),
         Commands::Reindex(args) => commands::reindex(args),
         Commands::File(args) => commands::file_operations(args),
         Commands::Link(args) => commands::link_operations(args),
+        Commands::Tag(args) => commands::tag_operations(args),
+        Commands::Archive(args) => commands::archive(args),
+        Commands::Unarchive(args) => commands::unarchive(args),
+        Commands::Export(args) => commands::export(args),
+        Commands::Import(args) => commands::import(args),
+        Commands::ImportSimilarity(args) => commands::import_similarity(args),
+        Commands::Stats(args) => commands::stats(args),
+        Commands::Dashboard(args) => commands::dashboard(args),
+        Commands::Feed(args) => commands::feed(args),
+        Commands::Random(args) => commands::random(args),
+        Commands::Review(args) => commands::review(args),
+        Commands::Validate(args) => commands::validate(args),
+        Commands::Lint(args) => commands::lint(args),
+        Commands::Report(args) => commands::report(args),
+        Commands::Publish(args) => commands::publish(args),
+        Commands::Watch(args) => commands::watch(args),
+        Commands::Snapshot(args) => commands::snapshot(args),
+        Commands::Node(args) => commands::node(args),
+        Commands::History(args) => commands::history(args),
+        Commands::Derivation(args) => commands::derivation(args),
+        Commands::Gc(args) => commands::gc(args),
+        Commands::Undo(args) => commands::undo(args),
+        Commands::Doctor(args) => commands::doctor(args),
+        Commands::Migrate(args) => commands::migrate(args),
+        Commands::Repos(args) => commands::repos(args),
+        #[cfg(feature = "debug-tools")]
+        Commands::Debug(args) => commands::debug(args),
+    });
+
+    // 命令结束后输出各阶段耗时摘要
+    tracing::debug!(
+        command = name,
+        elapsed_ms = start.elapsed().as_millis() as u64,
+        phases = %timings,
+        "command finished"
+    );
+
+    // 按错误分类返回稳定的退出码
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => ExitCode::from(exit::report(&e, verbose).code()),
     }
 }
@@ -0,0 +1,244 @@
+//! 运行时指标模块
+//!
+//! 进程内的计数器和仪表，全部基于原子变量，记录开销可以忽略；
+//! `memexia serve` 的 `/metrics` 以 Prometheus 文本格式输出（[`render`]）
+//!
+//! | 指标 | 类型 | 标签 | 说明 |
+//! |------|------|------|------|
+//! | `memexia_http_requests_total` | counter | `route`、`status` | API 请求数 |
+//! | `memexia_sparql_queries_total` | counter | | SPARQL 查询数 |
+//! | `memexia_sparql_query_duration_seconds` | histogram | | SPARQL 查询耗时 |
+//! | `memexia_index_events_processed_total` | counter | `kind` | 处理的文件事件（按索引结果分类，失败为 `error`） |
+//! | `memexia_node_cache_lookups_total` | counter | `result` | 节点读缓存的查找（`hit` / `miss`），见 [`crate::storage::CachedStorage`] |
+//! | `memexia_graph_nodes` / `memexia_graph_edges` | gauge | | 最近一次统计的图规模 |
+//! | `memexia_websocket_clients` | gauge | | 当前连接的 WebSocket 客户端数 |
+//! | `memexia_last_index_error_timestamp_seconds` | gauge | | 最近一次索引失败的 Unix 时间，从未失败时为 0 |
+//!
+//! 指标只在当前进程内累计，进程重启后归零
+
+use crate::core::IndexResult;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// HTTP 路由标签
+pub const ROUTES: [&str; 8] = [
+    "/api/nodes/{id}/html",
+    "/api/nodes/{id}/links",
+    "/api/titles/{title}",
+    "/api/layout",
+    "/api/daemon",
+    "/api/edges/{id}",
+    "/metrics",
+    "other",
+];
+
+/// 单独计数的 HTTP 状态码，其余状态码计入最后一项（500）
+pub const STATUSES: [u16; 11] = [200, 400, 403, 404, 405, 409, 413, 422, 429, 503, 500];
+
+/// 索引事件类别标签
+pub const INDEX_KINDS: [&str; 5] = ["indexed", "skipped", "excluded", "deleted", "error"];
+
+/// SPARQL 耗时直方图的桶上界（秒）
+pub const DURATION_BUCKETS: [f64; 8] = [0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0];
+
+static HTTP_REQUESTS: [[AtomicU64; STATUSES.len()]; ROUTES.len()] =
+    [const { [const { AtomicU64::new(0) }; STATUSES.len()] }; ROUTES.len()];
+static SPARQL_QUERIES: AtomicU64 = AtomicU64::new(0);
+/// 各桶的非累计计数，最后一项为超过最大桶上界的查询
+static SPARQL_BUCKETS: [AtomicU64; DURATION_BUCKETS.len() + 1] =
+    [const { AtomicU64::new(0) }; DURATION_BUCKETS.len() + 1];
+static SPARQL_DURATION_MICROS: AtomicU64 = AtomicU64::new(0);
+static INDEX_EVENTS: [AtomicU64; INDEX_KINDS.len()] = [const { AtomicU64::new(0) }; INDEX_KINDS.len()];
+static GRAPH_NODES: AtomicU64 = AtomicU64::new(0);
+static GRAPH_EDGES: AtomicU64 = AtomicU64::new(0);
+static WEBSOCKET_CLIENTS: AtomicI64 = AtomicI64::new(0);
+static NODE_CACHE_HITS: AtomicU64 = AtomicU64::new(0);
+static NODE_CACHE_MISSES: AtomicU64 = AtomicU64::new(0);
+static LAST_INDEX_ERROR: AtomicI64 = AtomicI64::new(0);
+
+/// 记录一次 HTTP 请求
+///
+/// # Arguments
+///
+/// * `route` - [`ROUTES`] 中的路由标签，未知路由计入 `other`
+/// * `status` - 响应状态码
+pub fn record_http_request(route: &str, status: u16) {
+    let route = ROUTES.iter().position(|r| *r == route).unwrap_or(ROUTES.len() - 1);
+    let status = STATUSES.iter().position(|s| *s == status).unwrap_or(STATUSES.len() - 1);
+    HTTP_REQUESTS[route][status].fetch_add(1, Ordering::Relaxed);
+}
+
+/// 记录一次 SPARQL 查询
+pub fn record_sparql_query(duration: Duration) {
+    let seconds = duration.as_secs_f64();
+    let bucket = DURATION_BUCKETS
+        .iter()
+        .position(|bound| seconds <= *bound)
+        .unwrap_or(DURATION_BUCKETS.len());
+    SPARQL_QUERIES.fetch_add(1, Ordering::Relaxed);
+    SPARQL_BUCKETS[bucket].fetch_add(1, Ordering::Relaxed);
+    SPARQL_DURATION_MICROS.fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+}
+
+/// SPARQL 查询计时器，离开作用域时记录耗时（查询失败同样计入）
+pub struct SparqlTimer(Instant);
+
+impl SparqlTimer {
+    /// 开始计时
+    pub fn start() -> Self {
+        Self(Instant::now())
+    }
+}
+
+impl Drop for SparqlTimer {
+    fn drop(&mut self) {
+        record_sparql_query(self.0.elapsed());
+    }
+}
+
+/// 记录一次文件事件的索引结果，失败时同时更新最近一次索引失败时间
+pub fn record_index_event<T>(result: &Result<IndexResult, T>) {
+    let kind = match result {
+        Ok(IndexResult::Indexed { .. } | IndexResult::Promoted { .. }) => 0,
+        Ok(IndexResult::Skipped | IndexResult::Rejected { .. }) => 1,
+        Ok(IndexResult::Excluded(_)) => 2,
+        Ok(IndexResult::Deleted(_)) => 3,
+        Err(_) => {
+            record_index_error();
+            4
+        }
+    };
+    INDEX_EVENTS[kind].fetch_add(1, Ordering::Relaxed);
+}
+
+/// 记录一次索引失败的时间
+pub fn record_index_error() {
+    LAST_INDEX_ERROR.store(chrono::Utc::now().timestamp(), Ordering::Relaxed);
+}
+
+/// 记录一次节点读缓存查找，见 [`crate::storage::CachedStorage`]
+pub fn record_node_cache(hit: bool) {
+    let counter = if hit { &NODE_CACHE_HITS } else { &NODE_CACHE_MISSES };
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+/// 更新图规模仪表
+pub fn set_graph_size(nodes: usize, edges: usize) {
+    GRAPH_NODES.store(nodes as u64, Ordering::Relaxed);
+    GRAPH_EDGES.store(edges as u64, Ordering::Relaxed);
+}
+
+/// WebSocket 客户端连接
+pub fn websocket_connected() {
+    WEBSOCKET_CLIENTS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// WebSocket 客户端断开
+pub fn websocket_disconnected() {
+    WEBSOCKET_CLIENTS.fetch_sub(1, Ordering::Relaxed);
+}
+
+/// 以 Prometheus 文本格式（0.0.4）输出所有指标
+pub fn render() -> String {
+    let mut out = String::new();
+
+    header(&mut out, "memexia_http_requests_total", "counter", "API requests by route and status");
+    for (route, counters) in ROUTES.iter().zip(&HTTP_REQUESTS) {
+        for (status, counter) in STATUSES.iter().zip(counters) {
+            let value = counter.load(Ordering::Relaxed);
+            if value > 0 {
+                let _ = writeln!(out, "memexia_http_requests_total{{route=\"{}\",status=\"{}\"}} {}", route, status, value);
+            }
+        }
+    }
+
+    header(&mut out, "memexia_sparql_queries_total", "counter", "SPARQL queries executed");
+    let _ = writeln!(out, "memexia_sparql_queries_total {}", SPARQL_QUERIES.load(Ordering::Relaxed));
+
+    header(&mut out, "memexia_sparql_query_duration_seconds", "histogram", "SPARQL query duration");
+    let mut cumulative = 0;
+    for (bound, bucket) in DURATION_BUCKETS.iter().zip(&SPARQL_BUCKETS) {
+        cumulative += bucket.load(Ordering::Relaxed);
+        let _ = writeln!(out, "memexia_sparql_query_duration_seconds_bucket{{le=\"{}\"}} {}", bound, cumulative);
+    }
+    cumulative += SPARQL_BUCKETS[DURATION_BUCKETS.len()].load(Ordering::Relaxed);
+    let _ = writeln!(out, "memexia_sparql_query_duration_seconds_bucket{{le=\"+Inf\"}} {}", cumulative);
+    let seconds = SPARQL_DURATION_MICROS.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+    let _ = writeln!(out, "memexia_sparql_query_duration_seconds_sum {}", seconds);
+    let _ = writeln!(out, "memexia_sparql_query_duration_seconds_count {}", cumulative);
+
+    header(&mut out, "memexia_index_events_processed_total", "counter", "File events processed by the indexer");
+    for (kind, counter) in INDEX_KINDS.iter().zip(&INDEX_EVENTS) {
+        let _ = writeln!(out, "memexia_index_events_processed_total{{kind=\"{}\"}} {}", kind, counter.load(Ordering::Relaxed));
+    }
+
+    header(&mut out, "memexia_node_cache_lookups_total", "counter", "Node cache lookups by result");
+    let _ = writeln!(out, "memexia_node_cache_lookups_total{{result=\"hit\"}} {}", NODE_CACHE_HITS.load(Ordering::Relaxed));
+    let _ = writeln!(out, "memexia_node_cache_lookups_total{{result=\"miss\"}} {}", NODE_CACHE_MISSES.load(Ordering::Relaxed));
+
+    gauge(&mut out, "memexia_graph_nodes", "Nodes in the graph", GRAPH_NODES.load(Ordering::Relaxed) as i64);
+    gauge(&mut out, "memexia_graph_edges", "Edges in the graph", GRAPH_EDGES.load(Ordering::Relaxed) as i64);
+    gauge(&mut out, "memexia_websocket_clients", "Connected WebSocket clients", WEBSOCKET_CLIENTS.load(Ordering::Relaxed));
+    gauge(
+        &mut out,
+        "memexia_last_index_error_timestamp_seconds",
+        "Unix time of the last indexing failure (0 if none)",
+        LAST_INDEX_ERROR.load(Ordering::Relaxed),
+    );
+
+    out
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn gauge(out: &mut String, name: &str, help: &str, value: i64) {
+    header(out, name, "gauge", help);
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 读取不带标签或带完整标签串的样本值
+    fn sample(text: &str, series: &str) -> f64 {
+        text.lines()
+            .find_map(|line| line.strip_prefix(series)?.strip_prefix(' '))
+            .map(|value| value.parse().unwrap())
+            .unwrap_or(0.0)
+    }
+
+    #[test]
+    fn test_render_counters_and_histogram() {
+        let before = render();
+        record_http_request("/metrics", 200);
+        record_http_request("/nope", 418);
+        record_sparql_query(Duration::from_millis(3));
+        record_sparql_query(Duration::from_secs(10));
+        record_index_event::<()>(&Ok(IndexResult::Skipped));
+        record_index_event(&Err(()));
+        record_node_cache(true);
+        record_node_cache(false);
+        let after = render();
+
+        let delta = |series: &str| sample(&after, series) - sample(&before, series);
+        assert!(delta("memexia_http_requests_total{route=\"/metrics\",status=\"200\"}") >= 1.0);
+        assert!(delta("memexia_http_requests_total{route=\"other\",status=\"500\"}") >= 1.0);
+        assert!(delta("memexia_sparql_queries_total") >= 2.0);
+        assert!(delta("memexia_sparql_query_duration_seconds_bucket{le=\"0.005\"}") >= 1.0);
+        assert!(delta("memexia_sparql_query_duration_seconds_bucket{le=\"+Inf\"}") >= 2.0);
+        assert!(delta("memexia_index_events_processed_total{kind=\"skipped\"}") >= 1.0);
+        assert!(delta("memexia_index_events_processed_total{kind=\"error\"}") >= 1.0);
+        assert!(delta("memexia_node_cache_lookups_total{result=\"hit\"}") >= 1.0);
+        assert!(delta("memexia_node_cache_lookups_total{result=\"miss\"}") >= 1.0);
+        assert!(sample(&after, "memexia_last_index_error_timestamp_seconds") > 0.0);
+        assert!(
+            sample(&after, "memexia_sparql_query_duration_seconds_bucket{le=\"5\"}")
+                < sample(&after, "memexia_sparql_query_duration_seconds_bucket{le=\"+Inf\"}")
+        );
+    }
+}
@@ -0,0 +1,530 @@
+//! 节点读缓存模块
+//!
+//! 渲染反向链接、寻路、`graph stats --detail` 和导出在一次命令中会反复读取同一批节点，
+//! 每次 [`GraphStorage::get_node`] 都要对 RocksDB 做多次模式扫描。[`CachedStorage`]
+//! 包装任意 [`GraphStorage`]，用容量固定的 LRU 缓存 `get_node` 和 `node_exists` 的结果，
+//! 其余操作直接转发
+//!
+//! 失效规则：
+//!
+//! - 经过包装的写入：使涉及的节点失效（边的写入使两个端点失效，节点删除清空整个缓存）
+//! - 绕过包装的写入（事务句柄、其他克隆的存储、监听进程）：经 [`CachedStorage::invalidator`]
+//!   注册为存储观察者后按同样规则失效；未注册时不应在有其他写入方的场景使用缓存
+//! - 事务结束后（无论成功与否）清空整个缓存
+//!
+//! 缓存未命中时先记下失效代数，读到结果后代数未变才写入缓存，
+//! 读取期间发生的写入不会留下过期的条目
+//!
+//! 由 [`Storage::enable_node_cache`](super::Storage::enable_node_cache) 为读取密集的命令开启，
+//! 容量见 `[cache] nodes` 配置；命中和未命中计入 [`crate::metrics`]
+
+use super::graph::{EdgeDirection, GraphStats, GraphStorage, QueryResult};
+use super::{Edge, EdgeFilter, Node, StorageObserver};
+use crate::error::Result;
+use crate::metrics;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// 默认缓存的节点数
+pub const DEFAULT_NODE_CACHE_CAPACITY: usize = 10_000;
+
+/// 一个节点的缓存结果
+#[derive(Debug, Clone, Default)]
+struct Entry {
+    /// `get_node` 的结果
+    node: Option<Option<Node>>,
+    /// `node_exists` 的结果
+    exists: Option<bool>,
+    /// 最近一次访问的序号
+    used: u64,
+}
+
+/// 缓存命中统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// 命中次数
+    pub hits: u64,
+    /// 未命中次数
+    pub misses: u64,
+}
+
+/// LRU 缓存本体
+#[derive(Debug, Default)]
+struct NodeCache {
+    capacity: usize,
+    entries: HashMap<String, Entry>,
+    /// 访问序号 → 节点 ID，最小的最久未使用
+    order: BTreeMap<u64, String>,
+    /// 访问序号计数
+    clock: u64,
+    /// 失效代数，每次失效加一
+    generation: u64,
+    stats: CacheStats,
+}
+
+impl NodeCache {
+    /// 查找并刷新访问顺序
+    fn lookup<T>(&mut self, id: &str, read: impl Fn(&Entry) -> Option<T>) -> Option<T> {
+        let entry = self.entries.get_mut(id)?;
+        let value = read(entry)?;
+        self.clock += 1;
+        self.order.remove(&entry.used);
+        entry.used = self.clock;
+        self.order.insert(self.clock, id.to_string());
+        Some(value)
+    }
+
+    /// 写入结果；`generation` 与当前代数不同时说明读取期间发生过写入，丢弃结果
+    fn store(&mut self, id: &str, generation: u64, update: impl FnOnce(&mut Entry)) {
+        if generation != self.generation || self.capacity == 0 {
+            return;
+        }
+        self.clock += 1;
+        let entry = self.entries.entry(id.to_string()).or_default();
+        self.order.remove(&entry.used);
+        entry.used = self.clock;
+        update(entry);
+        self.order.insert(self.clock, id.to_string());
+
+        while self.entries.len() > self.capacity {
+            let Some((_, oldest)) = self.order.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+    }
+
+    fn invalidate(&mut self, id: &str) {
+        self.generation += 1;
+        if let Some(entry) = self.entries.remove(id) {
+            self.order.remove(&entry.used);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.generation += 1;
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    fn record(&mut self, hit: bool) {
+        if hit {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+        }
+        metrics::record_node_cache(hit);
+    }
+}
+
+/// 共享的缓存句柄
+type SharedCache = Arc<Mutex<NodeCache>>;
+
+fn lock(cache: &SharedCache) -> MutexGuard<'_, NodeCache> {
+    cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// 带节点读缓存的图存储包装
+///
+/// 克隆共享同一个缓存
+#[derive(Clone)]
+pub struct CachedStorage {
+    /// 被包装的存储
+    inner: Arc<dyn GraphStorage>,
+    /// 缓存
+    cache: SharedCache,
+}
+
+impl std::fmt::Debug for CachedStorage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cache = lock(&self.cache);
+        f.debug_struct("CachedStorage")
+            .field("capacity", &cache.capacity)
+            .field("entries", &cache.entries.len())
+            .finish()
+    }
+}
+
+impl CachedStorage {
+    /// 包装存储
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - 被包装的存储
+    /// * `capacity` - 最多缓存的节点数，`0` 表示不缓存（只转发）
+    pub fn new(inner: Arc<dyn GraphStorage>, capacity: usize) -> Self {
+        Self {
+            inner,
+            cache: Arc::new(Mutex::new(NodeCache { capacity, ..Default::default() })),
+        }
+    }
+
+    /// 使缓存随底层存储的写入失效的观察者，注册到被包装的存储上
+    pub fn invalidator(&self) -> Arc<dyn StorageObserver> {
+        Arc::new(Invalidator { cache: self.cache.clone() })
+    }
+
+    /// 最多缓存的节点数
+    pub fn capacity(&self) -> usize {
+        lock(&self.cache).capacity
+    }
+
+    /// 命中统计
+    pub fn stats(&self) -> CacheStats {
+        lock(&self.cache).stats
+    }
+
+    /// 已缓存的节点数
+    pub fn len(&self) -> usize {
+        lock(&self.cache).entries.len()
+    }
+
+    /// 缓存是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 使一个节点的缓存失效
+    pub fn invalidate(&self, id: &str) {
+        lock(&self.cache).invalidate(id);
+    }
+
+    /// 清空缓存
+    pub fn clear(&self) {
+        lock(&self.cache).clear();
+    }
+
+    fn invalidate_endpoints(&self, edge_id: &str) {
+        match Edge::parse_id(edge_id) {
+            Some((from, to)) => {
+                let mut cache = lock(&self.cache);
+                cache.invalidate(from);
+                cache.invalidate(to);
+            }
+            None => self.clear(),
+        }
+    }
+}
+
+/// 按存储变更使缓存失效的观察者
+struct Invalidator {
+    cache: SharedCache,
+}
+
+impl StorageObserver for Invalidator {
+    fn on_node_added(&self, node: &Node) -> Result<()> {
+        lock(&self.cache).invalidate(&node.id);
+        Ok(())
+    }
+
+    fn on_node_updated(&self, node: &Node) -> Result<()> {
+        lock(&self.cache).invalidate(&node.id);
+        Ok(())
+    }
+
+    /// 节点删除可能连带删除其他节点的边，清空整个缓存
+    fn on_node_deleted(&self, _node: &Node) -> Result<()> {
+        lock(&self.cache).clear();
+        Ok(())
+    }
+
+    fn on_edge_added(&self, edge: &Edge) -> Result<()> {
+        let mut cache = lock(&self.cache);
+        cache.invalidate(&edge.from);
+        cache.invalidate(&edge.to);
+        Ok(())
+    }
+
+    fn on_edge_deleted(&self, edge: &Edge) -> Result<()> {
+        self.on_edge_added(edge)
+    }
+}
+
+impl GraphStorage for CachedStorage {
+    fn add_node(&self, node: &Node) -> Result<()> {
+        let result = self.inner.add_node(node);
+        self.invalidate(&node.id);
+        result
+    }
+
+    fn get_node(&self, id: &str) -> Result<Option<Node>> {
+        let generation = {
+            let mut cache = lock(&self.cache);
+            if let Some(node) = cache.lookup(id, |entry| entry.node.clone()) {
+                cache.record(true);
+                return Ok(node);
+            }
+            cache.record(false);
+            cache.generation
+        };
+        let node = self.inner.get_node(id)?;
+        lock(&self.cache).store(id, generation, |entry| {
+            entry.exists = Some(node.is_some());
+            entry.node = Some(node.clone());
+        });
+        Ok(node)
+    }
+
+    fn update_node(&self, node: &Node) -> Result<()> {
+        let result = self.inner.update_node(node);
+        self.invalidate(&node.id);
+        result
+    }
+
+    fn delete_node(&self, id: &str) -> Result<()> {
+        let result = self.inner.delete_node(id);
+        self.clear();
+        result
+    }
+
+    fn list_nodes(&self) -> Result<Vec<Node>> {
+        self.inner.list_nodes()
+    }
+
+    fn list_nodes_filtered(&self, include_deleted: bool) -> Result<Vec<Node>> {
+        self.inner.list_nodes_filtered(include_deleted)
+    }
+
+    fn get_all_nodes(&self) -> Result<Vec<Node>> {
+        self.inner.get_all_nodes()
+    }
+
+    fn add_edge(&self, edge: &Edge) -> Result<()> {
+        let result = self.inner.add_edge(edge);
+        self.invalidate_endpoints(&Edge::make_id(&edge.from, &edge.to));
+        result
+    }
+
+    fn get_edge(&self, id: &str) -> Result<Option<Edge>> {
+        self.inner.get_edge(id)
+    }
+
+    fn get_edges_for_node(&self, node_id: &str, direction: EdgeDirection) -> Result<Vec<Edge>> {
+        self.inner.get_edges_for_node(node_id, direction)
+    }
+
+    fn get_edges_by_source(&self, source: &str) -> Result<Vec<Edge>> {
+        self.inner.get_edges_by_source(source)
+    }
+
+    fn get_edges_by_target(&self, target: &str) -> Result<Vec<Edge>> {
+        self.inner.get_edges_by_target(target)
+    }
+
+    fn query_edges(&self, filter: EdgeFilter) -> Result<Vec<Edge>> {
+        self.inner.query_edges(filter)
+    }
+
+    fn delete_edge(&self, id: &str) -> Result<()> {
+        let result = self.inner.delete_edge(id);
+        self.invalidate_endpoints(id);
+        result
+    }
+
+    fn remove_edge(&self, id: &str) -> Result<()> {
+        let result = self.inner.remove_edge(id);
+        self.invalidate_endpoints(id);
+        result
+    }
+
+    fn list_edges(&self) -> Result<Vec<Edge>> {
+        self.inner.list_edges()
+    }
+
+    fn list_edges_filtered(&self, include_deleted: bool) -> Result<Vec<Edge>> {
+        self.inner.list_edges_filtered(include_deleted)
+    }
+
+    fn get_all_edges(&self) -> Result<Vec<Edge>> {
+        self.inner.get_all_edges()
+    }
+
+    fn for_each_node(&self, visit: &mut dyn FnMut(Node) -> Result<()>) -> Result<()> {
+        self.inner.for_each_node(visit)
+    }
+
+    fn for_each_edge(&self, visit: &mut dyn FnMut(Edge) -> Result<()>) -> Result<()> {
+        self.inner.for_each_edge(visit)
+    }
+
+    fn query(&self, sparql: &str) -> Result<QueryResult> {
+        self.inner.query(sparql)
+    }
+
+    fn sparql_query(&self, sparql: &str) -> Result<Vec<String>> {
+        self.inner.sparql_query(sparql)
+    }
+
+    /// 已缓存 `get_node` 的结果时直接由其得出（节点存在当且仅当 `get_node` 返回节点）
+    fn node_exists(&self, id: &str) -> Result<bool> {
+        let generation = {
+            let mut cache = lock(&self.cache);
+            if let Some(exists) = cache.lookup(id, |entry| entry.exists) {
+                cache.record(true);
+                return Ok(exists);
+            }
+            cache.record(false);
+            cache.generation
+        };
+        let exists = self.inner.node_exists(id)?;
+        lock(&self.cache).store(id, generation, |entry| entry.exists = Some(exists));
+        Ok(exists)
+    }
+
+    fn edge_exists(&self, id: &str) -> Result<bool> {
+        self.inner.edge_exists(id)
+    }
+
+    fn node_ids(&self) -> Result<std::collections::HashSet<String>> {
+        self.inner.node_ids()
+    }
+
+    fn find_by_title(&self, title: &str, case_sensitive: bool) -> Result<Vec<Node>> {
+        self.inner.find_by_title(title, case_sensitive)
+    }
+
+    fn title_index_mismatches(&self) -> Result<Vec<String>> {
+        self.inner.title_index_mismatches()
+    }
+
+    fn rebuild_title_index(&self) -> Result<usize> {
+        let result = self.inner.rebuild_title_index();
+        self.clear();
+        result
+    }
+
+    fn get_stats(&self) -> Result<GraphStats> {
+        self.inner.get_stats()
+    }
+
+    fn find_path(&self, source: &str, target: &str) -> Result<Option<Vec<String>>> {
+        self.inner.find_path(source, target)
+    }
+
+    fn export_nquads(&self) -> Result<String> {
+        self.inner.export_nquads()
+    }
+
+    /// 事务中的读写直接作用于底层存储的事务句柄，结束后清空缓存
+    fn run_transaction(&self, f: &mut dyn FnMut(&dyn GraphStorage) -> Result<()>) -> Result<()> {
+        self.clear();
+        let result = self.inner.run_transaction(f);
+        self.clear();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{NodeType, RelationType, Storage};
+    use tempfile::TempDir;
+
+    fn cached(storage: &Storage, capacity: usize) -> CachedStorage {
+        CachedStorage::new(Arc::new(storage.graph_storage().clone()), capacity)
+    }
+
+    #[test]
+    fn test_repeated_gets_hit_the_cache() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        storage.graph().add_node(&Node::new("urn:memexia:file:a.md", NodeType::Concept, "A")).unwrap();
+        let cache = cached(&storage, 16);
+
+        assert_eq!(cache.get_node("urn:memexia:file:a.md").unwrap().unwrap().title, "A");
+        assert_eq!(cache.stats(), CacheStats { hits: 0, misses: 1 });
+        for _ in 0..3 {
+            assert_eq!(cache.get_node("urn:memexia:file:a.md").unwrap().unwrap().title, "A");
+        }
+        // node_exists 由缓存的 get_node 结果得出；不存在的节点同样缓存
+        assert!(cache.node_exists("urn:memexia:file:a.md").unwrap());
+        assert!(cache.get_node("urn:memexia:file:missing.md").unwrap().is_none());
+        assert!(!cache.node_exists("urn:memexia:file:missing.md").unwrap());
+        assert_eq!(cache.stats(), CacheStats { hits: 5, misses: 2 });
+    }
+
+    #[test]
+    fn test_writes_invalidate_entries() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let cache = cached(&storage, 16);
+        let a = "urn:memexia:file:a.md";
+
+        assert!(!cache.node_exists(a).unwrap());
+        cache.add_node(&Node::new(a, NodeType::Concept, "A")).unwrap();
+        assert!(cache.node_exists(a).unwrap());
+
+        let mut node = cache.get_node(a).unwrap().unwrap();
+        node.title = "A2".to_string();
+        cache.update_node(&node).unwrap();
+        assert_eq!(cache.get_node(a).unwrap().unwrap().title, "A2");
+
+        // 交替读写
+        for i in 0..5 {
+            node.title = format!("A{}", i);
+            cache.update_node(&node).unwrap();
+            assert_eq!(cache.get_node(a).unwrap().unwrap().title, format!("A{}", i));
+            assert_eq!(cache.get_node(a).unwrap().unwrap().title, format!("A{}", i));
+        }
+
+        // 事务中的写入在事务结束后可见
+        (&cache as &dyn GraphStorage)
+            .transaction(|graph| {
+                node.title = "in transaction".to_string();
+                graph.update_node(&node)
+            })
+            .unwrap();
+        assert_eq!(cache.get_node(a).unwrap().unwrap().title, "in transaction");
+
+        cache.delete_node(a).unwrap();
+        assert!(cache.get_node(a).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_writes_through_other_handles_invalidate_via_observer() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let cache = cached(&storage, 16);
+        storage.register_observer(cache.invalidator());
+        let (a, c) = ("urn:memexia:file:a.md", "urn:memexia:file:c.md");
+
+        assert!(cache.get_node(a).unwrap().is_none());
+        storage.graph().add_node(&Node::new(a, NodeType::Concept, "A")).unwrap();
+        assert_eq!(cache.get_node(a).unwrap().unwrap().title, "A");
+
+        storage.graph().add_node(&Node::new(a, NodeType::Concept, "A2")).unwrap();
+        assert_eq!(cache.get_node(a).unwrap().unwrap().title, "A2");
+
+        // 边的写入使端点失效（Oxigraph 中作为边的源出现的 IRI 即视为存在）
+        assert!(!cache.node_exists(c).unwrap());
+        storage.graph().add_edge(&Edge::new(Edge::make_id(c, a), c, a, RelationType::Supports)).unwrap();
+        assert!(cache.node_exists(c).unwrap());
+
+        storage.graph().delete_node(a).unwrap();
+        assert!(cache.get_node(a).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_capacity_evicts_least_recently_used() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let cache = cached(&storage, 2);
+        let ids = ["urn:memexia:file:a.md", "urn:memexia:file:b.md", "urn:memexia:file:c.md"];
+
+        cache.get_node(ids[0]).unwrap();
+        cache.get_node(ids[1]).unwrap();
+        cache.get_node(ids[0]).unwrap();
+        cache.get_node(ids[2]).unwrap();
+        assert_eq!(cache.len(), 2);
+        let before = cache.stats();
+        cache.get_node(ids[0]).unwrap();
+        cache.get_node(ids[1]).unwrap();
+        assert_eq!(cache.stats().hits - before.hits, 1);
+
+        let disabled = cached(&storage, 0);
+        disabled.get_node(ids[0]).unwrap();
+        disabled.get_node(ids[0]).unwrap();
+        assert!(disabled.is_empty());
+        assert_eq!(disabled.stats(), CacheStats { hits: 0, misses: 2 });
+    }
+}
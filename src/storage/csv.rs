@@ -0,0 +1,733 @@
+//! CSV 导入导出模块
+//!
+//! 以扁平表格形式导入导出节点和边，便于在电子表格中整理概念列表
+//!
+//! ## 文件格式
+//!
+//! - 节点: `id,type,title,tags,created_at`（多个标签以 `;` 分隔）
+//! - 边: `from,to,relation,strength,description`
+//! - SPARQL 查询结果: 表头为变量名（不含 `?`），字面量写为词法形式
+//!
+//! 字段遵循 RFC 4180 引号规则，标题中的逗号、引号和换行都会被正确转义
+
+use super::{Edge, GraphStorage, Node, NodeType, QueryResult, RelationType};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// 节点文件表头
+pub const NODE_HEADER: [&str; 5] = ["id", "type", "title", "tags", "created_at"];
+
+/// 边文件表头
+pub const EDGE_HEADER: [&str; 5] = ["from", "to", "relation", "strength", "description"];
+
+/// CSV 导入统计
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CsvImportSummary {
+    /// 导入的节点数
+    pub nodes_imported: usize,
+    /// 导入的边数
+    pub edges_imported: usize,
+    /// 自动创建的占位节点数
+    pub placeholders_created: usize,
+}
+
+/// 从 CSV 文件导入节点和边
+///
+/// 先导入节点文件，再导入边文件，边引用的节点可以来自节点文件或已有图谱
+pub fn import_csv(
+    storage: &dyn GraphStorage,
+    nodes_path: Option<&Path>,
+    edges_path: Option<&Path>,
+    create_placeholders: bool,
+) -> Result<CsvImportSummary> {
+    let mut summary = CsvImportSummary::default();
+
+    if let Some(path) = nodes_path {
+        summary.nodes_imported = import_nodes_csv(storage, path)?;
+    }
+
+    if let Some(path) = edges_path {
+        let (edges, placeholders) = import_edges_csv(storage, path, create_placeholders)?;
+        summary.edges_imported = edges;
+        summary.placeholders_created = placeholders;
+    }
+
+    Ok(summary)
+}
+
+/// 导出节点为 CSV
+pub fn export_nodes_csv(storage: &dyn GraphStorage, path: &Path) -> Result<usize> {
+    let file = fs::File::create(path).with_context(|| format!("Failed to create {:?}", path))?;
+    write_nodes_csv(&storage.list_nodes()?, file)
+}
+
+/// 导出边为 CSV
+pub fn export_edges_csv(storage: &dyn GraphStorage, path: &Path) -> Result<usize> {
+    let file = fs::File::create(path).with_context(|| format!("Failed to create {:?}", path))?;
+    write_edges_csv(&storage.list_edges()?, file)
+}
+
+/// 将节点写为 CSV
+pub fn write_nodes_csv<W: Write>(nodes: &[Node], mut writer: W) -> Result<usize> {
+    write_record(&mut writer, &NODE_HEADER)?;
+    for node in nodes {
+        let node_type = node.node_type.to_string();
+        let tags = node.tags.join(";");
+        let created_at = node.created_at.to_rfc3339();
+        write_record(
+            &mut writer,
+            &[&node.id, &node_type, &node.title, &tags, &created_at],
+        )?;
+    }
+    Ok(nodes.len())
+}
+
+/// 将边写为 CSV
+pub fn write_edges_csv<W: Write>(edges: &[Edge], mut writer: W) -> Result<usize> {
+    write_record(&mut writer, &EDGE_HEADER)?;
+    for edge in edges {
+        let relation = edge.relation.to_string();
+        let strength = edge.strength.to_string();
+        let description = edge.description.as_deref().unwrap_or("");
+        write_record(
+            &mut writer,
+            &[&edge.from, &edge.to, &relation, &strength, description],
+        )?;
+    }
+    Ok(edges.len())
+}
+
+/// 查询结果 CSV 选项
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryCsvOptions {
+    /// 写入 UTF-8 BOM（Excel 据此识别中文等非 ASCII 文本）
+    pub bom: bool,
+    /// 每个变量后追加 `<变量>_type` 列（`iri`、`literal` 或 `bnode`）
+    pub typed: bool,
+}
+
+/// 导出 SPARQL 查询结果为 CSV
+///
+/// # Returns
+///
+/// 写入的结果行数；结果为空时只写表头
+pub fn export_query_csv(result: &QueryResult, path: &Path, options: QueryCsvOptions) -> Result<usize> {
+    let file = fs::File::create(path).with_context(|| format!("Failed to create {:?}", path))?;
+    write_query_csv(result, std::io::BufWriter::new(file), options)
+}
+
+/// 将 SPARQL 查询结果写为 CSV
+pub fn write_query_csv<W: Write>(result: &QueryResult, mut writer: W, options: QueryCsvOptions) -> Result<usize> {
+    if options.bom {
+        writer.write_all("\u{feff}".as_bytes()).context("Failed to write CSV record")?;
+    }
+
+    let mut header = Vec::new();
+    for var in &result.variables {
+        let name = var.trim_start_matches('?');
+        header.push(name.to_string());
+        if options.typed {
+            header.push(format!("{}_type", name));
+        }
+    }
+    write_record(&mut writer, &header.iter().map(String::as_str).collect::<Vec<_>>())?;
+
+    for binding in &result.bindings {
+        let mut fields = Vec::new();
+        for var in &result.variables {
+            let (kind, value) = match binding.get(var.trim_start_matches('?')).or_else(|| binding.get(var)) {
+                Some(term) => split_term(term),
+                None => ("", String::new()),
+            };
+            fields.push(value);
+            if options.typed {
+                fields.push(kind.to_string());
+            }
+        }
+        write_record(&mut writer, &fields.iter().map(String::as_str).collect::<Vec<_>>())?;
+    }
+    writer.flush().context("Failed to write CSV record")?;
+    Ok(result.bindings.len())
+}
+
+/// 拆分查询结果中的项为 (类型, 值)
+///
+/// 字面量以 N-Triples 形式给出（`"值"`、`"值"@lang`、`"值"^^<类型>`），取出词法形式并还原转义；
+/// `_:` 开头的是空白节点，其余为 IRI
+fn split_term(term: &str) -> (&'static str, String) {
+    let Some(rest) = term.strip_prefix('"') else {
+        return if term.starts_with("_:") { ("bnode", term.to_string()) } else { ("iri", term.to_string()) };
+    };
+
+    let mut value = String::with_capacity(rest.len());
+    let mut chars = rest.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => break,
+            '\\' => match chars.next() {
+                Some('n') => value.push('\n'),
+                Some('r') => value.push('\r'),
+                Some('t') => value.push('\t'),
+                Some('b') => value.push('\u{8}'),
+                Some('f') => value.push('\u{c}'),
+                Some('u') => push_unicode_escape(&mut value, &mut chars, 4),
+                Some('U') => push_unicode_escape(&mut value, &mut chars, 8),
+                Some(other) => value.push(other),
+                None => value.push('\\'),
+            },
+            c => value.push(c),
+        }
+    }
+    ("literal", value)
+}
+
+fn push_unicode_escape(value: &mut String, chars: &mut std::str::Chars, digits: usize) {
+    let hex: String = chars.by_ref().take(digits).collect();
+    match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+        Some(c) => value.push(c),
+        None => value.push(char::REPLACEMENT_CHARACTER),
+    }
+}
+
+/// 从 CSV 导入节点
+///
+/// 所有行校验通过后才会写入存储，任何一行出错都会返回包含行号和列名的错误
+pub fn import_nodes_csv(storage: &dyn GraphStorage, path: &Path) -> Result<usize> {
+    let content = fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    let nodes = parse_nodes_csv(&content)
+        .with_context(|| format!("Invalid nodes file {:?}", path))?;
+
+    for node in &nodes {
+        storage.add_node(node)?;
+    }
+
+    Ok(nodes.len())
+}
+
+/// 从 CSV 导入边
+///
+/// # Arguments
+///
+/// * `storage` - 图存储
+/// * `path` - 边文件路径
+/// * `create_placeholders` - 引用的节点不存在时是否自动创建占位节点
+///
+/// # Returns
+///
+/// (导入的边数, 创建的占位节点数)
+pub fn import_edges_csv(
+    storage: &dyn GraphStorage,
+    path: &Path,
+    create_placeholders: bool,
+) -> Result<(usize, usize)> {
+    let content = fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    let rows = parse_edges_csv(&content)
+        .with_context(|| format!("Invalid edges file {:?}", path))?;
+
+    // 校验引用的节点
+    let mut errors = Vec::new();
+    let mut missing: Vec<String> = Vec::new();
+    let mut seen_missing = HashSet::new();
+    for (row, edge) in &rows {
+        for (column, id) in [("from", &edge.from), ("to", &edge.to)] {
+            if seen_missing.contains(id.as_str()) {
+                continue;
+            }
+            if !storage.node_exists(id)? {
+                if create_placeholders {
+                    seen_missing.insert(id.clone());
+                    missing.push(id.clone());
+                } else {
+                    errors.push(format!(
+                        "row {}, column '{}': node '{}' does not exist",
+                        row, column, id
+                    ));
+                }
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        bail!(
+            "Invalid edges file {:?}:\n  {}\n(use --create-placeholders to create missing nodes)",
+            path,
+            errors.join("\n  ")
+        );
+    }
+
+    for id in &missing {
+        let title = id.rsplit(':').next().unwrap_or(id);
+        storage.add_node(&Node::new(id.as_str(), NodeType::Concept, title))?;
+    }
+
+    for (_, edge) in &rows {
+        storage.add_edge(edge)?;
+    }
+
+    Ok((rows.len(), missing.len()))
+}
+
+/// 解析节点 CSV 内容
+pub fn parse_nodes_csv(content: &str) -> Result<Vec<Node>> {
+    let records = parse_records(content)?;
+    let columns = header_columns(&records, &NODE_HEADER)?;
+
+    let mut nodes = Vec::new();
+    let mut errors = Vec::new();
+
+    for (row, fields) in records.iter().skip(1) {
+        let field = |name: &str| {
+            columns
+                .iter()
+                .position(|c| c == name)
+                .and_then(|i| fields.get(i))
+                .map(|s| s.trim())
+                .unwrap_or("")
+        };
+
+        let id = field("id");
+        if id.is_empty() {
+            errors.push(format!("row {}, column 'id': missing node id", row));
+            continue;
+        }
+
+        let node_type = match parse_node_type(field("type")) {
+            Some(t) => t,
+            None => {
+                errors.push(format!(
+                    "row {}, column 'type': unknown node type '{}'",
+                    row,
+                    field("type")
+                ));
+                continue;
+            }
+        };
+
+        let title = if field("title").is_empty() {
+            id.rsplit(':').next().unwrap_or(id)
+        } else {
+            field("title")
+        };
+
+        let mut builder = Node::builder(id, node_type, title)
+            .tags(field("tags").split(';').map(str::trim).filter(|t| !t.is_empty()));
+
+        let created_at = field("created_at");
+        if !created_at.is_empty() {
+            match DateTime::parse_from_rfc3339(created_at) {
+                Ok(dt) => {
+                    builder = builder.created_at(dt.with_timezone(&Utc));
+                }
+                Err(_) => {
+                    errors.push(format!(
+                        "row {}, column 'created_at': invalid RFC 3339 timestamp '{}'",
+                        row, created_at
+                    ));
+                    continue;
+                }
+            }
+        }
+
+        match builder.build() {
+            Ok(node) => nodes.push(node),
+            Err(e) => errors.push(format!("row {}: {}", row, e)),
+        }
+    }
+
+    if !errors.is_empty() {
+        bail!("{}", errors.join("\n"));
+    }
+
+    Ok(nodes)
+}
+
+/// 解析边 CSV 内容
+///
+/// 返回 (行号, 边) 列表
+pub fn parse_edges_csv(content: &str) -> Result<Vec<(usize, Edge)>> {
+    let records = parse_records(content)?;
+    let columns = header_columns(&records, &EDGE_HEADER)?;
+
+    let mut edges = Vec::new();
+    let mut errors = Vec::new();
+
+    for (row, fields) in records.iter().skip(1) {
+        let field = |name: &str| {
+            columns
+                .iter()
+                .position(|c| c == name)
+                .and_then(|i| fields.get(i))
+                .map(|s| s.as_str())
+                .unwrap_or("")
+        };
+
+        let from = field("from").trim();
+        let to = field("to").trim();
+        let mut row_errors = Vec::new();
+
+        if from.is_empty() {
+            row_errors.push(format!("row {}, column 'from': missing source node id", row));
+        }
+        if to.is_empty() {
+            row_errors.push(format!("row {}, column 'to': missing target node id", row));
+        }
+
+        let relation_str = field("relation").trim();
+        let relation = if relation_str.is_empty() {
+            Some(Default::default())
+        } else {
+            RelationType::parse(relation_str)
+        };
+        if relation.is_none() {
+            row_errors.push(format!(
+                "row {}, column 'relation': unknown relation type '{}'",
+                row, relation_str
+            ));
+        }
+
+        let strength_str = field("strength").trim();
+        let strength = if strength_str.is_empty() {
+            Some(1.0)
+        } else {
+            strength_str.parse::<f64>().ok().filter(|s| (0.0..=1.0).contains(s))
+        };
+        if strength.is_none() {
+            row_errors.push(format!(
+                "row {}, column 'strength': expected a number between 0 and 1, got '{}'",
+                row, strength_str
+            ));
+        }
+
+        if !row_errors.is_empty() {
+            errors.extend(row_errors);
+            continue;
+        }
+
+        let mut builder = Edge::builder(from, to, relation.unwrap_or_default()).strength(strength.unwrap_or(1.0));
+        let description = field("description");
+        if !description.is_empty() {
+            builder = builder.description(description);
+        }
+
+        match builder.build() {
+            Ok(edge) => edges.push((*row, edge)),
+            Err(e) => errors.push(format!("row {}: {}", row, e)),
+        }
+    }
+
+    if !errors.is_empty() {
+        bail!("{}", errors.join("\n"));
+    }
+
+    Ok(edges)
+}
+
+/// 解析节点类型（严格匹配，未知类型返回 None）
+fn parse_node_type(s: &str) -> Option<NodeType> {
+    match s.to_lowercase().as_str() {
+        "" | "concept" => Some(NodeType::Concept),
+        "question" => Some(NodeType::Question),
+        "evidence" => Some(NodeType::Evidence),
+        "resource" => Some(NodeType::Resource),
+        "person" => Some(NodeType::Person),
+        "event" => Some(NodeType::Event),
+        "meta" => Some(NodeType::Meta),
+        _ => None,
+    }
+}
+
+/// 校验表头并返回列名
+///
+/// 列可以任意排序，但必须包含全部必需列
+fn header_columns(records: &[(usize, Vec<String>)], expected: &[&str]) -> Result<Vec<String>> {
+    let (_, header) = match records.first() {
+        Some(h) => h,
+        None => bail!("row 1: missing header (expected {})", expected.join(",")),
+    };
+
+    let columns: Vec<String> = header.iter().map(|c| c.trim().to_lowercase()).collect();
+    for name in expected {
+        if !columns.iter().any(|c| c == name) {
+            bail!("row 1, column '{}': missing required column", name);
+        }
+    }
+
+    Ok(columns)
+}
+
+/// 写入一条 CSV 记录
+fn write_record<W: Write>(writer: &mut W, fields: &[&str]) -> Result<()> {
+    let line = fields
+        .iter()
+        .map(|f| escape_field(f))
+        .collect::<Vec<_>>()
+        .join(",");
+    writeln!(writer, "{}", line).context("Failed to write CSV record")?;
+    Ok(())
+}
+
+/// 转义 CSV 字段
+///
+/// 包含逗号、引号或换行的字段用双引号包裹，内部引号加倍
+pub(crate) fn escape_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// 解析 CSV 内容为记录列表
+///
+/// 返回 (行号, 字段列表)，行号为记录起始的物理行（从 1 开始，表头为第 1 行）
+pub(crate) fn parse_records(content: &str) -> Result<Vec<(usize, Vec<String>)>> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_start = 1;
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            ',' => fields.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                fields.push(std::mem::take(&mut field));
+                if !(fields.len() == 1 && fields[0].is_empty()) {
+                    records.push((record_start, std::mem::take(&mut fields)));
+                }
+                fields.clear();
+                line += 1;
+                record_start = line;
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        bail!("row {}: unterminated quoted field", record_start);
+    }
+
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((record_start, fields));
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{RelationType, Storage};
+    use tempfile::TempDir;
+
+    fn query_result(variables: &[&str], rows: &[&[(&str, &str)]]) -> QueryResult {
+        let mut result = QueryResult::new();
+        result.variables = variables.iter().map(|v| v.to_string()).collect();
+        for row in rows {
+            result.add_binding(row.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect());
+        }
+        result
+    }
+
+    fn query_csv(result: &QueryResult, options: QueryCsvOptions) -> String {
+        let mut buf = Vec::new();
+        write_query_csv(result, &mut buf, options).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn test_query_csv_escapes_literals() {
+        let result = query_result(
+            &["?s", "?title"],
+            &[
+                &[("s", "urn:memexia:file:a.md"), ("title", "\"Free will, determinism\"")],
+                &[("s", "urn:memexia:file:b.md"), ("title", "\"The \\\"self\\\"\"@en")],
+                &[("s", "urn:memexia:file:c.md"), ("title", "\"line one\\nline two\"")],
+                &[("s", "urn:memexia:file:d.md")],
+            ],
+        );
+
+        let csv = query_csv(&result, QueryCsvOptions::default());
+        assert_eq!(
+            csv,
+            "s,title\n\
+             urn:memexia:file:a.md,\"Free will, determinism\"\n\
+             urn:memexia:file:b.md,\"The \"\"self\"\"\"\n\
+             urn:memexia:file:c.md,\"line one\nline two\"\n\
+             urn:memexia:file:d.md,\n"
+        );
+
+        // 写出的文件可被导入使用的解析器读回
+        let records = parse_records(&csv).unwrap();
+        assert_eq!(records[3].1, vec!["urn:memexia:file:c.md", "line one\nline two"]);
+    }
+
+    #[test]
+    fn test_query_csv_typed_and_bom() {
+        let result = query_result(
+            &["?node", "?count"],
+            &[
+                &[("node", "urn:memexia:file:%E4%B8%AD.md"), ("count", "\"3\"^^<http://www.w3.org/2001/XMLSchema#integer")],
+                &[("node", "_:b0"), ("count", "\"自由意志\"")],
+            ],
+        );
+
+        let csv = query_csv(&result, QueryCsvOptions { bom: true, typed: true });
+        assert!(csv.starts_with('\u{feff}'));
+        let lines: Vec<_> = csv.trim_start_matches('\u{feff}').lines().collect();
+        assert_eq!(lines[0], "node,node_type,count,count_type");
+        assert_eq!(lines[1], "urn:memexia:file:%E4%B8%AD.md,iri,3,literal");
+        assert_eq!(lines[2], "_:b0,bnode,自由意志,literal");
+    }
+
+    #[test]
+    fn test_query_csv_empty_result_writes_header() {
+        let result = query_result(&["?s", "?p"], &[]);
+        assert_eq!(query_csv(&result, QueryCsvOptions::default()), "s,p\n");
+        assert_eq!(
+            query_csv(&result, QueryCsvOptions { bom: false, typed: true }),
+            "s,s_type,p,p_type\n"
+        );
+    }
+
+    #[test]
+    fn test_escape_field() {
+        assert_eq!(escape_field("plain"), "plain");
+        assert_eq!(escape_field("a,b"), "\"a,b\"");
+        assert_eq!(escape_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(escape_field("line1\nline2"), "\"line1\nline2\"");
+    }
+
+    #[test]
+    fn test_parse_records_quoted() {
+        let content = "a,b\n\"x, y\",\"multi\nline\"\nz,\"\"\"q\"\"\"\n";
+        let records = parse_records(content).unwrap();
+
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[1].1, vec!["x, y", "multi\nline"]);
+        assert_eq!(records[2].0, 4);
+        assert_eq!(records[2].1, vec!["z", "\"q\""]);
+    }
+
+    #[test]
+    fn test_csv_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+
+        let mut node_a = Node::new("urn:memexia:file:a.md", NodeType::Concept, "Free will, revisited");
+        node_a.add_tag("哲学");
+        node_a.add_tag("mind");
+        let node_b = Node::new("urn:memexia:file:b.md", NodeType::Question, "Line one\nline \"two\"");
+        storage.graph().add_node(&node_a).unwrap();
+        storage.graph().add_node(&node_b).unwrap();
+
+        let edge = Edge::new(
+            "urn:memexia:edge:urn:memexia:file:a.md-urn:memexia:file:b.md",
+            "urn:memexia:file:a.md",
+            "urn:memexia:file:b.md",
+            RelationType::Contradicts,
+        );
+        storage.graph().add_edge(&edge).unwrap();
+
+        let nodes_path = temp_dir.path().join("nodes.csv");
+        let edges_path = temp_dir.path().join("edges.csv");
+        assert_eq!(export_nodes_csv(storage.graph(), &nodes_path).unwrap(), 2);
+        assert_eq!(export_edges_csv(storage.graph(), &edges_path).unwrap(), 1);
+
+        let temp_dir2 = TempDir::new().unwrap();
+        let storage2 = Storage::init(temp_dir2.path()).unwrap();
+        assert_eq!(import_nodes_csv(storage2.graph(), &nodes_path).unwrap(), 2);
+        assert_eq!(import_edges_csv(storage2.graph(), &edges_path, false).unwrap(), (1, 0));
+
+        let imported_a = storage2.graph().get_node("urn:memexia:file:a.md").unwrap().unwrap();
+        assert_eq!(imported_a.title, "Free will, revisited");
+        assert_eq!(imported_a.tags.len(), 2);
+        assert!(imported_a.tags.contains(&"哲学".to_string()));
+
+        let imported_b = storage2.graph().get_node("urn:memexia:file:b.md").unwrap().unwrap();
+        assert_eq!(imported_b.node_type, NodeType::Question);
+        assert_eq!(imported_b.title, "Line one\nline \"two\"");
+
+        let edges = storage2.graph().list_edges().unwrap();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].relation, RelationType::Contradicts);
+    }
+
+    #[test]
+    fn test_edges_csv_strength_and_description() {
+        let mut edge = Edge::new("e", "urn:memexia:file:a.md", "urn:memexia:file:b.md", RelationType::Supports);
+        edge.update_strength(0.75);
+        edge.update_description("partly, with caveats");
+
+        let mut buf = Vec::new();
+        write_edges_csv(&[edge], &mut buf).unwrap();
+        let parsed = parse_edges_csv(&String::from_utf8(buf).unwrap()).unwrap();
+
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].1.relation, RelationType::Supports);
+        assert_eq!(parsed[0].1.strength, 0.75);
+        assert_eq!(parsed[0].1.description.as_deref(), Some("partly, with caveats"));
+    }
+
+    #[test]
+    fn test_malformed_rows_report_row_and_column() {
+        let content = "from,to,relation,strength,description\n\
+                       urn:memexia:file:a.md,urn:memexia:file:b.md,Supports,0.5,\n\
+                       urn:memexia:file:a.md,urn:memexia:file:c.md,Bogus!,1,\n\
+                       urn:memexia:file:b.md,urn:memexia:file:c.md,RelatedTo,abc,\n";
+
+        let err = parse_edges_csv(content).unwrap_err().to_string();
+        assert!(err.contains("row 3, column 'relation': unknown relation type 'Bogus!'"));
+        assert!(err.contains("row 4, column 'strength'"));
+    }
+
+    #[test]
+    fn test_import_edges_missing_nodes() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        storage
+            .graph()
+            .add_node(&Node::new("urn:memexia:file:a.md", NodeType::Concept, "A"))
+            .unwrap();
+
+        let edges_path = temp_dir.path().join("edges.csv");
+        std::fs::write(
+            &edges_path,
+            "from,to,relation,strength,description\nurn:memexia:file:a.md,urn:memexia:file:missing.md,Supports,,\n",
+        )
+        .unwrap();
+
+        let err = import_edges_csv(storage.graph(), &edges_path, false).unwrap_err().to_string();
+        assert!(err.contains("row 2, column 'to'"));
+        assert!(storage.graph().list_edges().unwrap().is_empty());
+
+        let (edges, placeholders) = import_edges_csv(storage.graph(), &edges_path, true).unwrap();
+        assert_eq!((edges, placeholders), (1, 1));
+        assert!(storage.graph().node_exists("urn:memexia:file:missing.md").unwrap());
+    }
+}
@@ -0,0 +1,63 @@
+//! Graphviz DOT 导出的共用样式
+//!
+//! `memexia graph dot` 和 `memexia derivation tree --dot` 使用相同的节点样式：
+//! 方框、填充色按节点类型区分（概念浅蓝、问题浅黄、其他浅绿）
+
+use super::{Edge, Node, NodeType};
+use std::fmt::Write;
+
+/// 图的默认节点属性
+pub const NODE_DEFAULTS: &str = "node [shape=box, style=filled];";
+
+/// 节点类型对应的填充色
+pub fn fill_color(node_type: &NodeType) -> &'static str {
+    match node_type {
+        NodeType::Concept => "lightblue",
+        NodeType::Question => "lightyellow",
+        _ => "lightgreen",
+    }
+}
+
+/// 把节点和边渲染为 `digraph memexia_graph`
+///
+/// # Arguments
+///
+/// * `nodes` - 要输出的节点
+/// * `edges` - 要输出的边
+/// * `is_stub` - 节点是否为子图边界外的存根（以虚线框、白底表示）
+pub fn render_graph(nodes: &[&Node], edges: &[Edge], is_stub: impl Fn(&str) -> bool) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "digraph memexia_graph {{");
+    let _ = writeln!(out, "  {}", NODE_DEFAULTS);
+    for node in nodes {
+        if is_stub(&node.id) {
+            let _ = writeln!(out, "  {} [fillcolor=white, style=\"filled,dashed\"];", quote(&node.id));
+        } else {
+            let _ = writeln!(out, "  {} [fillcolor={}];", quote(&node.id), fill_color(&node.node_type));
+        }
+    }
+    let _ = writeln!(out);
+    for edge in edges {
+        let _ = writeln!(out, "  {} -> {} [label={}];", quote(&edge.from), quote(&edge.to), quote(&edge.relation.to_string()));
+    }
+    let _ = writeln!(out, "}}");
+    out
+}
+
+/// 转义为 DOT 双引号字符串（含两侧引号）
+pub fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_and_colors() {
+        assert_eq!(quote(r#"say "hi" \ bye"#), r#""say \"hi\" \\ bye""#);
+        assert_eq!(fill_color(&NodeType::Concept), "lightblue");
+        assert_eq!(fill_color(&NodeType::Question), "lightyellow");
+        assert_eq!(fill_color(&NodeType::Evidence), "lightgreen");
+    }
+}
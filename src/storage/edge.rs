@@ -2,57 +2,53 @@
 //!
 //! 定义 Memexia 知识图谱中节点之间的连接关系
 
+use crate::error::{MemexiaError, Result};
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
 
+/// 自定义关系类型在 RDF 谓词中的子命名空间（`memexia:rel/<名称>`）
+pub const CUSTOM_RELATION_NAMESPACE: &str = "rel/";
+
+/// 自定义关系名称的最大长度
+const MAX_CUSTOM_RELATION_LEN: usize = 64;
+
 /// 关系类型枚举
 ///
-/// 符合项目文档 3.3.1 预定义的关系类型
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash)]
+/// 符合项目文档 3.3.1 预定义的关系类型；领域相关的关系使用 [`RelationType::Custom`]。
+/// 内置关系序列化为变体名，自定义关系序列化为其名称
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum RelationType {
     /// 包含关系（整体-部分）
-    #[serde(rename = "Contains")]
     Contains,
     /// 部分关系（部分-整体）
-    #[serde(rename = "PartOf")]
     PartOf,
     /// 实例关系
-    #[serde(rename = "InstanceOf")]
     InstanceOf,
     /// 推导自（逻辑推导）
-    #[serde(rename = "DerivesFrom")]
     DerivesFrom,
     /// 导向（因果或时间顺序）
-    #[serde(rename = "LeadsTo")]
     LeadsTo,
     /// 支持（证据支持）
-    #[serde(rename = "Supports")]
     Supports,
     /// 矛盾（逻辑对立）
-    #[serde(rename = "Contradicts")]
     Contradicts,
     /// 精炼（更精确表述）
-    #[serde(rename = "Refines")]
     Refines,
     /// 引用（外部来源）
-    #[serde(rename = "References")]
     References,
     /// 相关（一般性关联）
-    #[serde(rename = "RelatedTo")]
     RelatedTo,
     /// 类比（相似但不同领域）
-    #[serde(rename = "AnalogousTo")]
     AnalogousTo,
     /// 先于
-    #[serde(rename = "Precedes")]
     Precedes,
     /// 后于
-    #[serde(rename = "Follows")]
     Follows,
     /// 同时
-    #[serde(rename = "Simultaneous")]
     Simultaneous,
+    /// 用户自定义关系（名称只含 ASCII 字母、数字、`-` 和 `_`，以字母开头）
+    Custom(String),
 }
 
 impl Default for RelationType {
@@ -78,12 +74,134 @@ impl fmt::Display for RelationType {
             RelationType::Precedes => write!(f, "Precedes"),
             RelationType::Follows => write!(f, "Follows"),
             RelationType::Simultaneous => write!(f, "Simultaneous"),
+            RelationType::Custom(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl Serialize for RelationType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for RelationType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        RelationType::parse(&name)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid relation type '{}'", name)))
+    }
+}
+
+impl RelationType {
+    /// 创建自定义关系类型
+    ///
+    /// # Returns
+    ///
+    /// 名称不合法（须以 ASCII 字母开头，只包含 ASCII 字母、数字、`-` 和 `_`，不超过 64 个字符）
+    /// 或与内置关系重名时返回 `Validation` 错误
+    pub fn custom(name: &str) -> Result<Self> {
+        let name = name.trim();
+        if RelationType::builtin(name).is_some() {
+            return Err(MemexiaError::Validation(format!(
+                "Custom relation '{}' conflicts with a built-in relation type",
+                name
+            )));
+        }
+        if !is_valid_custom_name(name) {
+            return Err(MemexiaError::Validation(format!(
+                "Invalid relation name '{}': use ASCII letters, digits, '-' and '_', starting with a letter",
+                name
+            )));
+        }
+        Ok(RelationType::Custom(name.to_string()))
+    }
+
+    /// 按名称识别内置关系类型
+    ///
+    /// 不区分大小写，忽略下划线，支持中文别名（如 "矛盾"）
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().replace('_', "").as_str() {
+            "矛盾" | "contradicts" => Some(RelationType::Contradicts),
+            "属于" | "partof" => Some(RelationType::PartOf),
+            "包含" | "contains" => Some(RelationType::Contains),
+            "实例" | "instanceof" => Some(RelationType::InstanceOf),
+            "推导" | "derivesfrom" => Some(RelationType::DerivesFrom),
+            "导向" | "leadsto" => Some(RelationType::LeadsTo),
+            "支持" | "supports" => Some(RelationType::Supports),
+            "精炼" | "refines" => Some(RelationType::Refines),
+            "引用" | "references" => Some(RelationType::References),
+            "相关" | "relatedto" => Some(RelationType::RelatedTo),
+            "类比" | "analogousto" => Some(RelationType::AnalogousTo),
+            "先于" | "precedes" => Some(RelationType::Precedes),
+            "后于" | "follows" => Some(RelationType::Follows),
+            "同时" | "simultaneous" => Some(RelationType::Simultaneous),
+            _ => None,
+        }
+    }
+
+    /// 按名称解析关系类型
+    ///
+    /// 先识别内置关系，其余合法名称作为自定义关系（保留原大小写）
+    ///
+    /// # Returns
+    ///
+    /// 名称既不是内置关系也不是合法的自定义关系名时返回 None
+    pub fn parse(name: &str) -> Option<Self> {
+        RelationType::builtin(name).or_else(|| RelationType::custom(name).ok())
+    }
+
+    /// 逆关系：`A Contains B` 等价于 `B PartOf A`
+    ///
+    /// 内置的逆关系对为 `Contains`/`PartOf`、`Precedes`/`Follows` 和 `DerivesFrom`/`LeadsTo`
+    /// （后者是近似的：结论推导自前提，前提导向结论）；其他关系（包括自定义关系）返回 None
+    pub fn inverse(&self) -> Option<RelationType> {
+        match self {
+            RelationType::Contains => Some(RelationType::PartOf),
+            RelationType::PartOf => Some(RelationType::Contains),
+            RelationType::Precedes => Some(RelationType::Follows),
+            RelationType::Follows => Some(RelationType::Precedes),
+            RelationType::DerivesFrom => Some(RelationType::LeadsTo),
+            RelationType::LeadsTo => Some(RelationType::DerivesFrom),
+            _ => None,
         }
     }
+
+    /// 是否为自定义关系
+    pub fn is_custom(&self) -> bool {
+        matches!(self, RelationType::Custom(_))
+    }
+
+    /// RDF 谓词名（不含 `memexia:` 前缀）
+    ///
+    /// 内置关系为小写变体名（如 `contradicts`），自定义关系为 `rel/<名称>`
+    pub fn predicate_name(&self) -> String {
+        match self {
+            RelationType::Custom(name) => format!("{}{}", CUSTOM_RELATION_NAMESPACE, name),
+            builtin => builtin.to_string().to_lowercase(),
+        }
+    }
+
+    /// 从 RDF 谓词名（不含 `memexia:` 前缀）解析关系类型，见 [`RelationType::predicate_name`]
+    ///
+    /// 只有 `rel/` 子命名空间中的谓词会被当作自定义关系，其他未知谓词返回 None
+    pub fn from_predicate_name(name: &str) -> Option<Self> {
+        match name.strip_prefix(CUSTOM_RELATION_NAMESPACE) {
+            Some(custom) => RelationType::custom(custom).ok(),
+            None => RelationType::builtin(name),
+        }
+    }
+}
+
+/// 自定义关系名是否合法：以 ASCII 字母开头，只包含 ASCII 字母、数字、`-` 和 `_`
+fn is_valid_custom_name(name: &str) -> bool {
+    name.len() <= MAX_CUSTOM_RELATION_LEN
+        && name.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
 }
 
 /// 关系来源类型
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EdgeSource {
     /// 显式创建，用户手动指定
     #[serde(rename = "explicit")]
@@ -102,6 +220,18 @@ impl Default for EdgeSource {
     }
 }
 
+impl EdgeSource {
+    /// 从存储的字符串解析（`explicit` / `ai` / `derived`）
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "explicit" => Some(EdgeSource::Explicit),
+            "ai" => Some(EdgeSource::AI),
+            "derived" => Some(EdgeSource::Derived),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for EdgeSource {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -152,6 +282,74 @@ pub struct Edge {
 
     /// 创建时间
     pub created_at: DateTime<Utc>,
+
+    /// 随时间衰减后的有效强度
+    ///
+    /// 由 `memexia graph decay` 计算，不覆盖用户设置的 `strength`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_strength: Option<f64>,
+
+    /// 附加在关系上的注释（按时间先后排列）
+    ///
+    /// 保存在以边 ID 为主语的元数据上，重新索引任一端点不会丢失
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<EdgeAnnotation>,
+
+    /// 首次提交这条链接的 Git 提交
+    ///
+    /// 由提交流程在边第一次随笔记提交时写入，之后重新索引和提交都不会覆盖
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub introduced_in_commit: Option<String>,
+
+    /// 最近一次修改强度、描述或关系的时间（`memexia link set-*`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+
+    /// 强度、描述和关系的修改记录（按时间先后排列），与注释一样在重新索引后保留
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<EdgeChange>,
+}
+
+/// 边的可修改字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EdgeField {
+    /// 关系强度
+    Strength,
+    /// 关系描述
+    Description,
+    /// 关系类型
+    Relation,
+}
+
+impl fmt::Display for EdgeField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EdgeField::Strength => write!(f, "strength"),
+            EdgeField::Description => write!(f, "description"),
+            EdgeField::Relation => write!(f, "relation"),
+        }
+    }
+}
+
+/// 边的一次修改：记录修改前的值
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EdgeChange {
+    /// 修改时间
+    pub changed_at: DateTime<Utc>,
+    /// 被修改的字段
+    pub field: EdgeField,
+    /// 修改前的值（描述为空时为 None）
+    pub previous: Option<String>,
+}
+
+/// 边注释
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EdgeAnnotation {
+    /// 添加时间
+    pub created_at: DateTime<Utc>,
+    /// 注释正文
+    pub text: String,
 }
 
 fn default_strength() -> f64 {
@@ -191,9 +389,97 @@ impl Edge {
             description: None,
             source: EdgeSource::Explicit,
             created_at: Utc::now(),
+            effective_strength: None,
+            annotations: Vec::new(),
+            introduced_in_commit: None,
+            updated_at: None,
+            history: Vec::new(),
         }
     }
 
+    /// 创建边构建器
+    ///
+    /// 未指定 ID 时自动生成 `urn:memexia:edge:{from}-{to}`
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - 源节点 ID
+    /// * `to` - 目标节点 ID
+    /// * `relation` - 关系类型
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use memexia::storage::{Edge, EdgeSource, RelationType};
+    ///
+    /// let edge = Edge::builder("urn:memexia:file:a.md", "urn:memexia:file:b.md", RelationType::Supports)
+    ///     .strength(0.8)
+    ///     .description("实验数据支持该结论")
+    ///     .source(EdgeSource::AI)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(edge.id, "urn:memexia:edge:urn:memexia:file:a.md-urn:memexia:file:b.md");
+    /// assert_eq!(edge.strength, 0.8);
+    ///
+    /// // 强度超出 0.0 - 1.0 范围时构建失败
+    /// assert!(Edge::builder("a", "b", RelationType::Supports).strength(1.5).build().is_err());
+    /// ```
+    pub fn builder(from: impl Into<String>, to: impl Into<String>, relation: RelationType) -> EdgeBuilder {
+        EdgeBuilder {
+            id: None,
+            from: from.into(),
+            to: to.into(),
+            relation,
+            strength: default_strength(),
+            confidence: default_confidence(),
+            description: None,
+            source: EdgeSource::Explicit,
+            created_at: None,
+        }
+    }
+
+    /// 生成边 ID：`urn:memexia:edge:{from}-{to}`
+    pub fn make_id(from: &str, to: &str) -> String {
+        format!("urn:memexia:edge:{}-{}", from, to)
+    }
+
+    /// 从边 ID 解析源和目标节点 ID
+    ///
+    /// 以 `-urn:` 作为分隔，节点 ID 中包含 `-` 时也能正确解析；
+    /// 目标不是 URN 时退回到第一个 `-`
+    ///
+    /// # Returns
+    ///
+    /// 格式不正确时返回 None
+    pub fn parse_id(id: &str) -> Option<(&str, &str)> {
+        let suffix = id.strip_prefix("urn:memexia:edge:")?;
+        let split = suffix.find("-urn:").or_else(|| suffix.find('-'))?;
+        let (from, to) = (&suffix[..split], &suffix[split + 1..]);
+        if from.is_empty() || to.is_empty() {
+            return None;
+        }
+        Some((from, to))
+    }
+
+    /// 从端点 `node` 的视角表述这条边：`--[关系]--> 另一端` 或 `<--[关系]-- 另一端`
+    ///
+    /// 入边的关系有逆关系时改写为出边（存储的 `X Contains 本节点` 表述为 `--[PartOf]--> X`）
+    pub fn describe_from(&self, node: &str) -> String {
+        if self.from == node {
+            return format!("--[{}]--> {}", self.relation, self.to);
+        }
+        match self.relation.inverse() {
+            Some(inverse) => format!("--[{}]--> {}", inverse, self.from),
+            None => format!("<--[{}]-- {}", self.relation, self.from),
+        }
+    }
+
+    /// 是否为机器建议的边（AI 推荐或推导得出）
+    pub fn is_suggestion(&self) -> bool {
+        self.source != EdgeSource::Explicit
+    }
+
     /// 创建显式链接边
     ///
     /// 解析 `[[目标|类型]]` 语法的链接
@@ -205,18 +491,23 @@ impl Edge {
     ///
     /// # Returns
     ///
-    /// 解析后的边，如果类型无法解析则使用默认关系
+    /// 解析后的边，如果类型无法解析则使用默认关系（RelatedTo）
     pub fn from_link(from_node_id: &str, link_text: &str) -> Self {
+        Self::from_link_with_default(from_node_id, link_text, &RelationType::RelatedTo)
+    }
+
+    /// 创建显式链接边，未写类型或类型无法解析时使用指定的默认关系（配置 `links.default_relation`）
+    pub fn from_link_with_default(from_node_id: &str, link_text: &str, default_relation: &RelationType) -> Self {
         let (target, relation_str, strength, description) = Self::parse_link_text(link_text);
 
-        let id = format!("urn:memexia:edge:{}-{}", from_node_id, target);
+        let id = format!("urn:memexia:edge:{}-{}", from_node_id, super::ids::encode_path(target));
 
-        let relation = Self::parse_relation(&relation_str).unwrap_or_default();
+        let relation = RelationType::parse(relation_str).unwrap_or_else(|| default_relation.clone());
 
         Self {
             id,
             from: from_node_id.to_string(),
-            to: format!("urn:memexia:file:{}", target),
+            to: super::ids::file_id(target),
             relation,
             strength,
             confidence: 1.0,
@@ -227,6 +518,11 @@ impl Edge {
             },
             source: EdgeSource::Explicit,
             created_at: Utc::now(),
+            effective_strength: None,
+            annotations: Vec::new(),
+            introduced_in_commit: None,
+            updated_at: None,
+            history: Vec::new(),
         }
     }
 
@@ -268,35 +564,9 @@ impl Edge {
         (target, relation_str, strength, description)
     }
 
-    /// 解析关系类型字符串
-    ///
-    /// # Arguments
-    ///
-    /// * `relation_str` - 关系类型字符串，如 "矛盾"、"Contradicts" 等
-    ///
-    /// # Returns
-    ///
-    /// 对应的 `RelationType`，无法解析时返回 `None`
-    fn parse_relation(relation_str: &str) -> Option<RelationType> {
-        let relation_lower = relation_str.to_lowercase();
-
-        match relation_lower.as_str() {
-            "矛盾" | "contradicts" => Some(RelationType::Contradicts),
-            "属于" | "partof" | "part_of" => Some(RelationType::PartOf),
-            "包含" | "contains" => Some(RelationType::Contains),
-            "实例" | "instanceof" | "instance_of" => Some(RelationType::InstanceOf),
-            "推导" | "derivesfrom" | "derives_from" => Some(RelationType::DerivesFrom),
-            "导向" | "leadsto" | "leads_to" => Some(RelationType::LeadsTo),
-            "支持" | "supports" => Some(RelationType::Supports),
-            "精炼" | "refines" => Some(RelationType::Refines),
-            "引用" | "references" => Some(RelationType::References),
-            "相关" | "relatedto" | "related_to" => Some(RelationType::RelatedTo),
-            "类比" | "analogousto" | "analogous_to" => Some(RelationType::AnalogousTo),
-            "先于" | "precedes" => Some(RelationType::Precedes),
-            "后于" | "follows" => Some(RelationType::Follows),
-            "同时" | "simultaneous" => Some(RelationType::Simultaneous),
-            _ => None,
-        }
+    /// 排序和加权寻路使用的强度：存在有效强度时优先使用
+    pub fn weight(&self) -> f64 {
+        self.effective_strength.unwrap_or(self.strength)
     }
 
     /// 更新关系强度
@@ -308,6 +578,189 @@ impl Edge {
     pub fn update_description(&mut self, description: impl Into<String>) {
         self.description = Some(description.into());
     }
+
+    /// 追加注释，保持按时间排序
+    pub fn annotate(&mut self, text: impl Into<String>, at: DateTime<Utc>) {
+        self.annotations.push(EdgeAnnotation { created_at: at, text: text.into() });
+        self.annotations.sort_by_key(|a| a.created_at);
+    }
+
+    /// 最近一条注释
+    pub fn latest_annotation(&self) -> Option<&EdgeAnnotation> {
+        self.annotations.last()
+    }
+
+    /// 修改强度，原值记入修改记录
+    ///
+    /// # Returns
+    ///
+    /// 强度不在 0.0 - 1.0 范围内时返回错误；与当前值相同时不做修改
+    pub fn set_strength(&mut self, strength: f64, at: DateTime<Utc>) -> Result<()> {
+        if !(0.0..=1.0).contains(&strength) {
+            return Err(MemexiaError::Validation(format!(
+                "Edge strength must be between 0.0 and 1.0, got {}",
+                strength
+            )));
+        }
+        if strength != self.strength {
+            self.record_change(EdgeField::Strength, Some(self.strength.to_string()), at);
+            self.strength = strength;
+        }
+        Ok(())
+    }
+
+    /// 修改描述（None 或空白为清除），原值记入修改记录
+    pub fn set_description(&mut self, description: Option<String>, at: DateTime<Utc>) {
+        let description = description.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+        if description != self.description {
+            let previous = std::mem::replace(&mut self.description, description);
+            self.record_change(EdgeField::Description, previous, at);
+        }
+    }
+
+    /// 修改关系类型，原值记入修改记录
+    pub fn set_relation(&mut self, relation: RelationType, at: DateTime<Utc>) {
+        if relation != self.relation {
+            let previous = std::mem::replace(&mut self.relation, relation);
+            self.record_change(EdgeField::Relation, Some(previous.to_string()), at);
+        }
+    }
+
+    fn record_change(&mut self, field: EdgeField, previous: Option<String>, at: DateTime<Utc>) {
+        self.history.push(EdgeChange { changed_at: at, field, previous });
+        self.history.sort_by_key(|change| change.changed_at);
+        self.updated_at = Some(at);
+    }
+}
+
+/// 对边的一次修改（`memexia link set-*`、`PATCH /api/edges/{id}`）
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EdgeEdit {
+    /// 新强度（0.0 - 1.0）
+    pub strength: Option<f64>,
+    /// 新描述，空字符串表示清除
+    pub description: Option<String>,
+    /// 新关系类型
+    pub relation: Option<RelationType>,
+}
+
+impl EdgeEdit {
+    /// 是否没有任何修改
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 把修改应用到边，每个改变的字段追加一条修改记录
+    ///
+    /// 强度不合法时返回错误，边保持不变
+    pub fn apply(&self, edge: &mut Edge, at: DateTime<Utc>) -> Result<()> {
+        if let Some(strength) = self.strength {
+            edge.set_strength(strength, at)?;
+        }
+        if let Some(description) = &self.description {
+            edge.set_description(Some(description.clone()), at);
+        }
+        if let Some(relation) = &self.relation {
+            edge.set_relation(relation.clone(), at);
+        }
+        Ok(())
+    }
+}
+
+/// 边构建器
+///
+/// 通过 [`Edge::builder`] 创建，在 [`build`](EdgeBuilder::build) 时校验强度和置信度范围
+#[derive(Debug, Clone)]
+pub struct EdgeBuilder {
+    id: Option<String>,
+    from: String,
+    to: String,
+    relation: RelationType,
+    strength: f64,
+    confidence: f64,
+    description: Option<String>,
+    source: EdgeSource,
+    created_at: Option<DateTime<Utc>>,
+}
+
+impl EdgeBuilder {
+    /// 指定边 ID（默认由源和目标节点 ID 派生）
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// 设置关系强度 (0.0 - 1.0)
+    pub fn strength(mut self, strength: f64) -> Self {
+        self.strength = strength;
+        self
+    }
+
+    /// 设置置信度 (0.0 - 1.0)
+    pub fn confidence(mut self, confidence: f64) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    /// 设置关系描述
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// 设置来源类型
+    pub fn source(mut self, source: EdgeSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// 设置创建时间
+    pub fn created_at(mut self, created_at: DateTime<Utc>) -> Self {
+        self.created_at = Some(created_at);
+        self
+    }
+
+    /// 构建边
+    ///
+    /// # Returns
+    ///
+    /// 端点为空，或强度/置信度不在 0.0 - 1.0 范围内时返回错误
+    pub fn build(self) -> Result<Edge> {
+        if self.from.trim().is_empty() || self.to.trim().is_empty() {
+            return Err(MemexiaError::Validation("Edge endpoints cannot be empty".to_string()));
+        }
+        if !(0.0..=1.0).contains(&self.strength) {
+            return Err(MemexiaError::Validation(format!(
+                "Edge strength must be between 0.0 and 1.0, got {}",
+                self.strength
+            )));
+        }
+        if !(0.0..=1.0).contains(&self.confidence) {
+            return Err(MemexiaError::Validation(format!(
+                "Edge confidence must be between 0.0 and 1.0, got {}",
+                self.confidence
+            )));
+        }
+
+        let id = self.id.unwrap_or_else(|| Edge::make_id(&self.from, &self.to));
+
+        Ok(Edge {
+            id,
+            from: self.from,
+            to: self.to,
+            relation: self.relation,
+            strength: self.strength,
+            confidence: self.confidence,
+            description: self.description,
+            source: self.source,
+            created_at: self.created_at.unwrap_or_else(Utc::now),
+            effective_strength: None,
+            annotations: Vec::new(),
+            introduced_in_commit: None,
+            updated_at: None,
+            history: Vec::new(),
+        })
+    }
 }
 
 /// 边的查询过滤器
@@ -321,6 +774,19 @@ pub struct EdgeFilter {
     pub relation: Option<RelationType>,
     /// 最小强度阈值
     pub min_strength: Option<f64>,
+    /// 来源类型（可选）
+    pub source: Option<EdgeSource>,
+}
+
+impl EdgeFilter {
+    /// 边是否满足所有过滤条件
+    pub fn matches(&self, edge: &Edge) -> bool {
+        self.from.as_ref().is_none_or(|from| &edge.from == from)
+            && self.to.as_ref().is_none_or(|to| &edge.to == to)
+            && self.relation.as_ref().is_none_or(|relation| &edge.relation == relation)
+            && self.min_strength.is_none_or(|min| edge.strength >= min)
+            && self.source.is_none_or(|source| edge.source == source)
+    }
 }
 
 #[cfg(test)]
@@ -348,16 +814,24 @@ mod tests {
         let edge = Edge::from_link("urn:memexia:file:notes/free_will.md", "决定论");
 
         assert_eq!(edge.from, "urn:memexia:file:notes/free_will.md");
-        assert_eq!(edge.to, "urn:memexia:file:决定论");
+        assert_eq!(edge.to, "urn:memexia:file:%E5%86%B3%E5%AE%9A%E8%AE%BA");
         assert_eq!(edge.relation, RelationType::RelatedTo);
         assert_eq!(edge.strength, 1.0);
     }
 
+    #[test]
+    fn test_edge_from_link_with_default() {
+        let edge = Edge::from_link_with_default("urn:memexia:file:a.md", "b.md", &RelationType::References);
+        assert_eq!(edge.relation, RelationType::References);
+        let edge = Edge::from_link_with_default("urn:memexia:file:a.md", "b.md|相关", &RelationType::References);
+        assert_eq!(edge.relation, RelationType::RelatedTo);
+    }
+
     #[test]
     fn test_edge_from_link_with_type() {
         let edge = Edge::from_link("urn:memexia:file:notes/free_will.md", "决定论|矛盾");
 
-        assert_eq!(edge.to, "urn:memexia:file:决定论");
+        assert_eq!(edge.to, "urn:memexia:file:%E5%86%B3%E5%AE%9A%E8%AE%BA");
         assert_eq!(edge.relation, RelationType::Contradicts);
     }
 
@@ -384,25 +858,89 @@ mod tests {
     #[test]
     fn test_edge_parse_relation_chinese() {
         assert_eq!(
-            Edge::parse_relation("矛盾"),
+            RelationType::parse("矛盾"),
             Some(RelationType::Contradicts)
         );
-        assert_eq!(Edge::parse_relation("属于"), Some(RelationType::PartOf));
-        assert_eq!(Edge::parse_relation("支持"), Some(RelationType::Supports));
+        assert_eq!(RelationType::parse("属于"), Some(RelationType::PartOf));
+        assert_eq!(RelationType::parse("支持"), Some(RelationType::Supports));
     }
 
     #[test]
     fn test_edge_parse_relation_english() {
         assert_eq!(
-            Edge::parse_relation("Contradicts"),
+            RelationType::parse("Contradicts"),
             Some(RelationType::Contradicts)
         );
-        assert_eq!(Edge::parse_relation("PartOf"), Some(RelationType::PartOf));
+        assert_eq!(RelationType::parse("PartOf"), Some(RelationType::PartOf));
+        assert_eq!(RelationType::parse("part_of"), Some(RelationType::PartOf));
     }
 
     #[test]
     fn test_edge_parse_relation_unknown() {
-        assert_eq!(Edge::parse_relation("unknown"), None);
+        assert_eq!(RelationType::builtin("unknown"), None);
+        assert_eq!(RelationType::parse("unknown"), Some(RelationType::Custom("unknown".to_string())));
+        assert_eq!(RelationType::parse("not a relation"), None);
+        assert_eq!(RelationType::parse("未知"), None);
+    }
+
+    #[test]
+    fn test_custom_relation() {
+        let relation = RelationType::custom("Refutes-Experimentally").unwrap();
+        assert!(relation.is_custom());
+        assert_eq!(relation.to_string(), "Refutes-Experimentally");
+        assert_eq!(relation.predicate_name(), "rel/Refutes-Experimentally");
+        assert_eq!(RelationType::from_predicate_name("rel/Refutes-Experimentally"), Some(relation.clone()));
+
+        assert!(RelationType::custom("Supports").is_err());
+        assert!(RelationType::custom("1st-order").is_err());
+        assert!(RelationType::custom("cites methodology").is_err());
+        // 谓词名中只有 rel/ 子命名空间才是自定义关系
+        assert_eq!(RelationType::from_predicate_name("strength"), None);
+        assert_eq!(RelationType::from_predicate_name("contradicts"), Some(RelationType::Contradicts));
+
+        let edge = Edge::from_link("urn:memexia:file:a.md", "b.md|Cites-Methodology:0.5");
+        assert_eq!(edge.relation, RelationType::Custom("Cites-Methodology".to_string()));
+    }
+
+    #[test]
+    fn test_relation_serde_keeps_builtin_names() {
+        assert_eq!(serde_json::to_string(&RelationType::PartOf).unwrap(), "\"PartOf\"");
+        assert_eq!(serde_json::from_str::<RelationType>("\"PartOf\"").unwrap(), RelationType::PartOf);
+
+        let custom = RelationType::Custom("Cites-Methodology".to_string());
+        let json = serde_json::to_string(&custom).unwrap();
+        assert_eq!(json, "\"Cites-Methodology\"");
+        assert_eq!(serde_json::from_str::<RelationType>(&json).unwrap(), custom);
+        assert!(serde_json::from_str::<RelationType>("\"not valid\"").is_err());
+    }
+
+    #[test]
+    fn test_edge_builder() {
+        let edge = Edge::builder("from", "to", RelationType::Refines)
+            .id("custom")
+            .strength(0.5)
+            .confidence(0.7)
+            .source(EdgeSource::Derived)
+            .build()
+            .unwrap();
+
+        assert_eq!(edge.id, "custom");
+        assert_eq!(edge.strength, 0.5);
+        assert_eq!(edge.confidence, 0.7);
+        assert_eq!(edge.source, EdgeSource::Derived);
+        assert!(edge.description.is_none());
+
+        let derived = Edge::builder("from", "to", RelationType::Refines).build().unwrap();
+        assert_eq!(derived.id, "urn:memexia:edge:from-to");
+    }
+
+    #[test]
+    fn test_edge_builder_validation() {
+        assert!(Edge::builder("a", "b", RelationType::Supports).strength(-0.1).build().is_err());
+        assert!(Edge::builder("a", "b", RelationType::Supports).strength(f64::NAN).build().is_err());
+        assert!(Edge::builder("a", "b", RelationType::Supports).confidence(1.01).build().is_err());
+        assert!(Edge::builder("", "b", RelationType::Supports).build().is_err());
+        assert!(Edge::builder("a", "b", RelationType::Supports).strength(0.0).build().is_ok());
     }
 
     #[test]
@@ -416,4 +954,73 @@ mod tests {
 
         assert_eq!(edge, deserialized);
     }
+
+    #[test]
+    fn test_annotations_kept_in_chronological_order() {
+        let mut edge = Edge::new("test", "from", "to", RelationType::Supports);
+        let now = Utc::now();
+        edge.annotate("second", now);
+        edge.annotate("first", now - chrono::Duration::minutes(5));
+
+        let texts: Vec<_> = edge.annotations.iter().map(|a| a.text.as_str()).collect();
+        assert_eq!(texts, ["first", "second"]);
+        assert_eq!(edge.latest_annotation().unwrap().text, "second");
+
+        let deserialized: Edge = serde_json::from_str(&serde_json::to_string(&edge).unwrap()).unwrap();
+        assert_eq!(edge, deserialized);
+    }
+
+    #[test]
+    fn test_edge_edit_records_previous_values() {
+        let mut edge = Edge::new("test", "from", "to", RelationType::Supports);
+        let now = Utc::now();
+        let edit = EdgeEdit {
+            strength: Some(0.4),
+            description: Some("weak".to_string()),
+            relation: Some(RelationType::Refines),
+        };
+        edit.apply(&mut edge, now).unwrap();
+        assert_eq!((edge.strength, edge.description.as_deref(), &edge.relation), (0.4, Some("weak"), &RelationType::Refines));
+        assert_eq!(edge.updated_at, Some(now));
+        let previous: Vec<_> = edge.history.iter().map(|c| (c.field, c.previous.as_deref())).collect();
+        assert_eq!(
+            previous,
+            [(EdgeField::Strength, Some("1")), (EdgeField::Description, None), (EdgeField::Relation, Some("Supports"))]
+        );
+
+        // 未改变的值不记录
+        EdgeEdit { strength: Some(0.4), ..Default::default() }.apply(&mut edge, now).unwrap();
+        assert_eq!(edge.history.len(), 3);
+
+        let err = EdgeEdit { strength: Some(1.5), ..Default::default() }.apply(&mut edge, now);
+        assert!(matches!(err, Err(MemexiaError::Validation(_))));
+        assert_eq!(edge.strength, 0.4);
+    }
+
+    #[test]
+    fn test_parse_edge_id() {
+        let from = "urn:memexia:file:free-will.md";
+        let to = "urn:memexia:file:self-control.md";
+        assert_eq!(Edge::parse_id(&Edge::make_id(from, to)), Some((from, to)));
+        assert_eq!(Edge::parse_id("urn:memexia:edge:a-b"), Some(("a", "b")));
+        assert_eq!(Edge::parse_id("urn:memexia:edge:ab"), None);
+        assert_eq!(Edge::parse_id("edge-1"), None);
+    }
+
+    #[test]
+    fn test_inverse_phrasing() {
+        assert_eq!(RelationType::Contains.inverse(), Some(RelationType::PartOf));
+        assert_eq!(RelationType::Follows.inverse(), Some(RelationType::Precedes));
+        assert_eq!(RelationType::Supports.inverse(), None);
+
+        // 反向链接从被查询节点的视角表述
+        let book = "urn:memexia:file:book.md";
+        let chapter = "urn:memexia:file:chapter.md";
+        let edge = Edge::builder(book, chapter, RelationType::Contains).build().unwrap();
+        assert_eq!(edge.describe_from(chapter), format!("--[PartOf]--> {}", book));
+        assert_eq!(edge.describe_from(book), format!("--[Contains]--> {}", chapter));
+
+        let edge = Edge::builder(book, chapter, RelationType::Supports).build().unwrap();
+        assert_eq!(edge.describe_from(chapter), format!("<--[Supports]-- {}", book));
+    }
 }
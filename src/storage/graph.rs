@@ -2,12 +2,15 @@
 //!
 //! 定义 `GraphStorage` trait，为不同图数据库实现提供统一接口
 
-use super::{Edge, Node};
-use anyhow::Result;
+use super::{ids, Edge, Node, NodeType, RelationType};
+use crate::error::{MemexiaError, Result};
+use std::collections::{BTreeSet, HashSet};
 
 /// SPARQL 查询结果
 #[derive(Debug, Clone, Default)]
 pub struct QueryResult {
+    /// 投影变量（按 SELECT 中的顺序，如 `?s`）
+    pub variables: Vec<String>,
     /// 绑定变量名到值的映射
     pub bindings: Vec<std::collections::HashMap<String, String>>,
 }
@@ -16,6 +19,7 @@ impl QueryResult {
     /// 创建新的查询结果
     pub fn new() -> Self {
         Self {
+            variables: Vec::new(),
             bindings: Vec::new(),
         }
     }
@@ -84,13 +88,24 @@ pub trait GraphStorage: Send + Sync {
     /// 操作结果
     fn delete_node(&self, id: &str) -> Result<()>;
 
-    /// 列出所有节点
+    /// 列出所有节点（不含已软删除的节点）
     ///
     /// # Returns
     ///
     /// 所有节点的列表
     fn list_nodes(&self) -> Result<Vec<Node>>;
 
+    /// 列出节点，可选包含已软删除的节点
+    ///
+    /// # Arguments
+    ///
+    /// * `include_deleted` - 是否包含回收站中的节点
+    ///
+    /// # Returns
+    ///
+    /// 节点列表
+    fn list_nodes_filtered(&self, include_deleted: bool) -> Result<Vec<Node>>;
+
     /// 获取所有节点
     ///
     /// # Returns
@@ -158,6 +173,37 @@ pub trait GraphStorage: Send + Sync {
     /// 匹配的边列表
     fn get_edges_by_target(&self, target: &str) -> Result<Vec<Edge>>;
 
+    /// 获取以指定关系与节点相连的节点，同时考虑逆关系
+    ///
+    /// 匹配节点以 `relation` 指向的边，以及以其逆关系（[`RelationType::inverse`]）
+    /// 指向节点的边：`get_related(书, Contains)` 同时找到 `书 Contains 章` 和 `章 PartOf 书`
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - 节点 ID
+    /// * `relation` - 从该节点看的关系
+    ///
+    /// # Returns
+    ///
+    /// 相连的节点 ID（去重并排序，不含节点自身）
+    fn get_related(&self, node: &str, relation: &RelationType) -> Result<Vec<String>> {
+        let mut related = BTreeSet::new();
+        for edge in self.get_edges_by_source(node)? {
+            if &edge.relation == relation {
+                related.insert(edge.to);
+            }
+        }
+        if let Some(inverse) = relation.inverse() {
+            for edge in self.get_edges_by_target(node)? {
+                if edge.relation == inverse {
+                    related.insert(edge.from);
+                }
+            }
+        }
+        related.remove(node);
+        Ok(related.into_iter().collect())
+    }
+
     /// 获取满足条件的边
     ///
     /// # Arguments
@@ -191,13 +237,26 @@ pub trait GraphStorage: Send + Sync {
     /// 操作结果
     fn remove_edge(&self, id: &str) -> Result<()>;
 
-    /// 列出所有边
+    /// 列出所有边（不含墓碑状态的边）
+    ///
+    /// 任一端点被软删除的边处于墓碑状态：仍然保存，恢复节点后重新可见
     ///
     /// # Returns
     ///
     /// 所有边的列表
     fn list_edges(&self) -> Result<Vec<Edge>>;
 
+    /// 列出边，可选包含墓碑状态的边
+    ///
+    /// # Arguments
+    ///
+    /// * `include_deleted` - 是否包含端点已软删除的边
+    ///
+    /// # Returns
+    ///
+    /// 边列表
+    fn list_edges_filtered(&self, include_deleted: bool) -> Result<Vec<Edge>>;
+
     /// 获取所有边
     ///
     /// # Returns
@@ -205,6 +264,107 @@ pub trait GraphStorage: Send + Sync {
     /// 所有边的列表
     fn get_all_edges(&self) -> Result<Vec<Edge>>;
 
+    /// 逐个访问节点（不含已软删除的节点）
+    ///
+    /// 默认实现基于 [`GraphStorage::list_nodes`]；后端可以覆盖以避免一次性加载所有节点，
+    /// 用于大型仓库的流式导出
+    ///
+    /// # Arguments
+    ///
+    /// * `visit` - 对每个节点调用，返回错误时停止遍历
+    fn for_each_node(&self, visit: &mut dyn FnMut(Node) -> Result<()>) -> Result<()> {
+        for node in self.list_nodes()? {
+            visit(node)?;
+        }
+        Ok(())
+    }
+
+    /// 逐个访问边（不含墓碑状态的边）
+    ///
+    /// 默认实现基于 [`GraphStorage::list_edges`]，语义同 [`GraphStorage::for_each_node`]
+    ///
+    /// # Arguments
+    ///
+    /// * `visit` - 对每条边调用，返回错误时停止遍历
+    fn for_each_edge(&self, visit: &mut dyn FnMut(Edge) -> Result<()>) -> Result<()> {
+        for edge in self.list_edges()? {
+            visit(edge)?;
+        }
+        Ok(())
+    }
+
+    /// 所有节点的 ID（含已软删除的节点）
+    ///
+    /// 默认实现基于 [`GraphStorage::list_nodes_filtered`]；只作为边端点出现、没有类型声明的
+    /// IRI 不是节点，后端需要时应覆盖此方法
+    fn node_ids(&self) -> Result<HashSet<String>> {
+        Ok(self.list_nodes_filtered(true)?.into_iter().map(|node| node.id).collect())
+    }
+
+    /// 查找端点节点不存在的边（导入、部分删除或旧版本遗留）
+    ///
+    /// 先收集所有节点 ID（含已软删除的节点，指向它们的边处于墓碑状态而非悬空），
+    /// 再一次过滤所有边，不逐条查询端点
+    ///
+    /// # Returns
+    ///
+    /// 悬空的边，按边 ID 排序
+    fn find_dangling_edges(&self) -> Result<Vec<Edge>> {
+        let nodes = self.node_ids()?;
+        let mut dangling: Vec<Edge> = self
+            .list_edges_filtered(true)?
+            .into_iter()
+            .filter(|edge| !nodes.contains(&edge.from) || !nodes.contains(&edge.to))
+            .collect();
+        dangling.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(dangling)
+    }
+
+    /// 修复悬空的边，在一个事务中完成
+    ///
+    /// # Arguments
+    ///
+    /// * `strategy` - 修复方式，见 [`DanglingStrategy`]
+    ///
+    /// # Returns
+    ///
+    /// 修复结果；[`DanglingStrategy::ReportOnly`] 只统计不写入
+    fn repair_dangling(&self, strategy: DanglingStrategy) -> Result<DanglingRepair> {
+        let nodes = self.node_ids()?;
+        let dangling = self.find_dangling_edges()?;
+        let mut repair = DanglingRepair { edges: dangling.len(), ..Default::default() };
+        if dangling.is_empty() || strategy == DanglingStrategy::ReportOnly {
+            return Ok(repair);
+        }
+
+        self.run_transaction(&mut |graph| {
+            repair.placeholders = 0;
+            repair.dropped = 0;
+            match strategy {
+                DanglingStrategy::CreatePlaceholders => {
+                    let missing: BTreeSet<&str> = dangling
+                        .iter()
+                        .flat_map(|edge| [edge.from.as_str(), edge.to.as_str()])
+                        .filter(|id| !nodes.contains(*id))
+                        .collect();
+                    for id in missing {
+                        graph.add_node(&Node::new(id, NodeType::Concept, placeholder_title(id)))?;
+                        repair.placeholders += 1;
+                    }
+                }
+                DanglingStrategy::Drop => {
+                    for edge in &dangling {
+                        graph.delete_edge(&edge.id)?;
+                        repair.dropped += 1;
+                    }
+                }
+                DanglingStrategy::ReportOnly => {}
+            }
+            Ok(())
+        })?;
+        Ok(repair)
+    }
+
     /// 执行 SPARQL 查询
     ///
     /// # Arguments
@@ -249,6 +409,34 @@ pub trait GraphStorage: Send + Sync {
     /// 是否存在
     fn edge_exists(&self, id: &str) -> Result<bool>;
 
+    /// 按完整标题查找节点（不含已软删除的节点）
+    ///
+    /// 使用随节点写入维护的标题索引，不扫描整个图；多个节点可以使用同一标题
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - 标题
+    /// * `case_sensitive` - 是否区分大小写；不区分时按 Unicode 小写比较
+    ///
+    /// # Returns
+    ///
+    /// 标题相同的节点（按 ID 排序）
+    fn find_by_title(&self, title: &str, case_sensitive: bool) -> Result<Vec<Node>>;
+
+    /// 检查标题索引与节点标题是否一致（`memexia doctor`）
+    ///
+    /// # Returns
+    ///
+    /// 索引缺失、过期或多余的节点 ID（已排序）
+    fn title_index_mismatches(&self) -> Result<Vec<String>>;
+
+    /// 按节点标题重建标题索引
+    ///
+    /// # Returns
+    ///
+    /// 修正的节点数
+    fn rebuild_title_index(&self) -> Result<usize>;
+
     /// 获取图统计信息
     ///
     /// # Returns
@@ -270,10 +458,82 @@ pub trait GraphStorage: Send + Sync {
 
     /// 导出图为 N-Quads 格式
     ///
+    /// 输出按规范顺序排列（见 [`crate::storage::nquads`]），相同的图总是得到相同的字节，
+    /// 图快照的哈希和差异依赖这一点
+    ///
     /// # Returns
     ///
     /// N-Quads 格式的字符串
     fn export_nquads(&self) -> Result<String>;
+
+    /// 在事务中执行一组写操作
+    ///
+    /// 对象安全的底层接口，通常通过 [`transaction`](trait.GraphStorage.html#method.transaction) 调用
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - 接收事务句柄的操作；句柄提供与本存储相同的节点和边方法
+    ///
+    /// # Returns
+    ///
+    /// `f` 的结果；`f` 返回错误时其间的所有写入都被撤销
+    fn run_transaction(&self, f: &mut dyn FnMut(&dyn GraphStorage) -> Result<()>) -> Result<()>;
+
+    /// 获取必须存在的节点
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - 节点 ID
+    ///
+    /// # Returns
+    ///
+    /// 节点不存在时返回 [`MemexiaError::NodeNotFound`]
+    fn require_node(&self, id: &str) -> Result<Node> {
+        self.get_node(id)?
+            .ok_or_else(|| MemexiaError::NodeNotFound { id: id.to_string() })
+    }
+
+    /// 获取必须存在的边
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - 边 ID
+    ///
+    /// # Returns
+    ///
+    /// 边不存在时返回 [`MemexiaError::EdgeNotFound`]
+    fn require_edge(&self, id: &str) -> Result<Edge> {
+        self.get_edge(id)?
+            .ok_or_else(|| MemexiaError::EdgeNotFound { id: id.to_string() })
+    }
+}
+
+impl dyn GraphStorage + '_ {
+    /// 在事务中执行多步操作（如节点迁移、重新索引），全部生效或全部撤销
+    ///
+    /// 事务内的读取能看到事务内已完成的写入；嵌套调用并入外层事务
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - 接收事务句柄的操作
+    ///
+    /// # Returns
+    ///
+    /// `f` 的返回值；`f` 返回错误时撤销其间的所有写入并返回该错误
+    pub fn transaction<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&dyn GraphStorage) -> Result<T>,
+    {
+        let mut f = Some(f);
+        let mut output = None;
+        self.run_transaction(&mut |graph| {
+            if let Some(f) = f.take() {
+                output = Some(f(graph)?);
+            }
+            Ok(())
+        })?;
+        output.ok_or_else(|| MemexiaError::Other(anyhow::anyhow!("transaction body did not run")))
+    }
 }
 
 /// 边的方向
@@ -288,6 +548,46 @@ pub enum EdgeDirection {
     Both,
 }
 
+/// 悬空边的修复方式（`memexia doctor --fix-dangling`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DanglingStrategy {
+    /// 为缺失的端点补建 Concept 占位节点，标题取自 IRI 末段
+    CreatePlaceholders,
+    /// 删除悬空的边
+    Drop,
+    /// 只报告不修改
+    ReportOnly,
+}
+
+impl DanglingStrategy {
+    /// 解析修复方式（`create-placeholders` / `drop` / `report-only`）
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "create-placeholders" => Some(DanglingStrategy::CreatePlaceholders),
+            "drop" => Some(DanglingStrategy::Drop),
+            "report-only" => Some(DanglingStrategy::ReportOnly),
+            _ => None,
+        }
+    }
+}
+
+/// 悬空边的修复结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DanglingRepair {
+    /// 发现的悬空边数
+    pub edges: usize,
+    /// 补建的占位节点数
+    pub placeholders: usize,
+    /// 删除的边数
+    pub dropped: usize,
+}
+
+/// 占位节点的标题：IRI 最后一个 `/`、`:` 或 `#` 之后的部分（解码后）
+fn placeholder_title(id: &str) -> String {
+    let tail = id.rsplit(['/', ':', '#']).find(|part| !part.is_empty()).unwrap_or(id);
+    ids::decode_component(tail)
+}
+
 /// 图统计信息
 #[derive(Debug, Clone, Default)]
 pub struct GraphStats {
@@ -317,6 +617,21 @@ impl GraphStats {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_dangling_strategy_parse() {
+        assert_eq!(DanglingStrategy::parse("create-placeholders"), Some(DanglingStrategy::CreatePlaceholders));
+        assert_eq!(DanglingStrategy::parse("DROP"), Some(DanglingStrategy::Drop));
+        assert_eq!(DanglingStrategy::parse("report-only"), Some(DanglingStrategy::ReportOnly));
+        assert_eq!(DanglingStrategy::parse("delete"), None);
+    }
+
+    #[test]
+    fn test_placeholder_title() {
+        assert_eq!(placeholder_title("urn:memexia:file:notes/%E8%87%AA%E7%94%B1.md"), "自由.md");
+        assert_eq!(placeholder_title("https://example.org/concepts/entropy/"), "entropy");
+        assert_eq!(placeholder_title("urn:memexia:tag:rust"), "rust");
+    }
+
     #[test]
     fn test_query_result_new() {
         let result = QueryResult::new();
@@ -0,0 +1,154 @@
+//! 节点 ID 编码模块
+//!
+//! 文件节点 ID 形如 `urn:memexia:file:<路径>`，路径部分按 IRI 组件做 percent 编码：
+//! ASCII 字母数字和 `-_.~!$&'()*+,;=:@/` 保持原样，其余字符（含空格、`%`、`#` 和非 ASCII 字符）
+//! 按 UTF-8 字节编码为 `%XX`。所有生成文件节点 ID 的代码都经由本模块，
+//! 同一文件名无论经哪条路径创建都得到相同的 ID
+
+/// 文件节点 ID 前缀
+pub const FILE_PREFIX: &str = "urn:memexia:file:";
+
+/// 不需要编码的 ASCII 符号
+const SAFE_SYMBOLS: &str = "-_.~!$&'()*+,;=:@/";
+
+fn is_safe(c: char) -> bool {
+    c.is_ascii_alphanumeric() || SAFE_SYMBOLS.contains(c)
+}
+
+fn push_encoded(result: &mut String, c: char) {
+    let mut buf = [0u8; 4];
+    for byte in c.encode_utf8(&mut buf).as_bytes() {
+        result.push_str(&format!("%{:02X}", byte));
+    }
+}
+
+/// 对 IRI 路径组件进行 percent 编码
+pub fn encode_component(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for c in s.chars() {
+        if is_safe(c) {
+            result.push(c);
+        } else {
+            push_encoded(&mut result, c);
+        }
+    }
+    result
+}
+
+/// 解码 percent 编码（用于显示），无效的转义序列原样保留
+pub fn decode_component(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(byte) = hex_byte(bytes, i + 1) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn hex_byte(bytes: &[u8], at: usize) -> Option<u8> {
+    let pair = bytes.get(at..at + 2)?;
+    let pair = std::str::from_utf8(pair).ok()?;
+    u8::from_str_radix(pair, 16).ok()
+}
+
+/// 路径的编码形式（反斜杠转换为 `/` 后编码）
+pub fn encode_path(path: &str) -> String {
+    encode_component(&path.replace('\\', "/"))
+}
+
+/// 文件路径对应的节点 ID
+///
+/// # Arguments
+///
+/// * `path` - 相对仓库根目录的路径或 wiki 链接目标（未编码）
+pub fn file_id(path: &str) -> String {
+    format!("{}{}", FILE_PREFIX, encode_path(path))
+}
+
+/// 文件节点 ID 的显示形式（去掉前缀并解码）；不是文件节点 ID 时原样返回
+pub fn display_file_id(id: &str) -> String {
+    match id.strip_prefix(FILE_PREFIX) {
+        Some(path) => decode_component(path),
+        None => id.to_string(),
+    }
+}
+
+/// 对可能部分编码的字符串补全编码
+///
+/// 已有的有效 `%XX` 转义视为已编码并保留，其余需要编码的字符（含孤立的 `%`）被编码
+pub fn canonical_component(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut result = String::with_capacity(s.len());
+    for (i, c) in s.char_indices() {
+        if is_safe(c) || (c == '%' && hex_byte(bytes, i + 1).is_some()) {
+            result.push(c);
+        } else {
+            push_encoded(&mut result, c);
+        }
+    }
+    result
+}
+
+/// 将可能未编码的旧版文件节点 ID 转换为规范形式，见 [`canonical_component`]
+///
+/// 不是文件节点 ID 时原样返回
+pub fn canonical_file_id(id: &str) -> String {
+    match id.strip_prefix(FILE_PREFIX) {
+        Some(path) => format!("{}{}", FILE_PREFIX, canonical_component(path)),
+        None => id.to_string(),
+    }
+}
+
+/// 是否为未编码的旧版文件节点 ID
+pub fn is_legacy_file_id(id: &str) -> bool {
+    canonical_file_id(id) != id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_and_decode_roundtrip() {
+        for name in ["笔记/自由意志.md", "a b.md", "100%.md", "C# notes.md", "plain-name_1.md"] {
+            let id = file_id(name);
+            assert!(id.is_ascii(), "{}", id);
+            assert_eq!(display_file_id(&id), name);
+        }
+        assert_eq!(file_id("a b.md"), "urn:memexia:file:a%20b.md");
+        assert_eq!(file_id("100%.md"), "urn:memexia:file:100%25.md");
+        assert_eq!(file_id("C#.md"), "urn:memexia:file:C%23.md");
+        assert_eq!(file_id("sub\\a.md"), "urn:memexia:file:sub/a.md");
+        assert_eq!(file_id("中.md"), "urn:memexia:file:%E4%B8%AD.md");
+    }
+
+    #[test]
+    fn test_decode_keeps_invalid_escapes() {
+        assert_eq!(decode_component("100%.md"), "100%.md");
+        assert_eq!(decode_component("a%2"), "a%2");
+        assert_eq!(decode_component("a%zzb"), "a%zzb");
+    }
+
+    #[test]
+    fn test_legacy_detection() {
+        assert!(is_legacy_file_id("urn:memexia:file:自由意志.md"));
+        assert!(is_legacy_file_id("urn:memexia:file:a b.md"));
+        assert!(is_legacy_file_id("urn:memexia:file:100%.md"));
+        assert!(!is_legacy_file_id(&file_id("自由意志.md")));
+        assert!(!is_legacy_file_id(&file_id("100%.md")));
+        assert!(!is_legacy_file_id("urn:memexia:zettel:202401011200"));
+
+        for name in ["自由意志.md", "a b.md", "100%.md", "C# notes.md"] {
+            assert_eq!(canonical_file_id(&format!("{}{}", FILE_PREFIX, name)), file_id(name));
+        }
+    }
+}
@@ -0,0 +1,317 @@
+//! Mermaid 图导出模块
+//!
+//! 将图谱（或以某节点为中心的子图）渲染为 Mermaid `graph TD` 代码块，
+//! 便于直接嵌入 Markdown 笔记或 GitHub README
+//!
+//! ## 输出示例
+//!
+//! ```text
+//! graph TD
+//!     a_md["自由意志"]:::concept
+//!     b_md["决定论"]:::question
+//!     a_md -->|Contradicts| b_md
+//!     classDef concept fill:#add8e6,stroke:#333
+//! ```
+
+use super::{Edge, EdgeDirection, GraphStorage, Node, NodeType};
+use anyhow::{bail, Result};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+
+/// 默认的整图导出节点上限
+pub const DEFAULT_MAX_NODES: usize = 200;
+
+/// Mermaid 导出选项
+#[derive(Debug, Clone)]
+pub struct MermaidOptions {
+    /// 中心节点 ID（为空时导出整个图谱）
+    pub center: Option<String>,
+    /// 以中心节点为起点的遍历深度
+    pub depth: usize,
+    /// 整图导出时允许的最大节点数
+    pub max_nodes: usize,
+}
+
+impl Default for MermaidOptions {
+    fn default() -> Self {
+        Self {
+            center: None,
+            depth: 1,
+            max_nodes: DEFAULT_MAX_NODES,
+        }
+    }
+}
+
+/// 按选项从存储中渲染 Mermaid 图
+///
+/// 未指定中心节点且节点数超过上限时返回错误并提示使用 `--center`
+pub fn export_mermaid(storage: &dyn GraphStorage, options: &MermaidOptions) -> Result<String> {
+    let (nodes, edges) = match &options.center {
+        Some(center) => collect_subgraph(storage, center, options.depth)?,
+        None => {
+            let nodes = storage.list_nodes()?;
+            if nodes.len() > options.max_nodes {
+                bail!(
+                    "Graph has {} nodes, which exceeds the Mermaid export cap of {}. \
+                     Hint: use --center <path> --depth N to export a subgraph, or raise --max-nodes",
+                    nodes.len(),
+                    options.max_nodes
+                );
+            }
+            (nodes, storage.list_edges()?)
+        }
+    };
+
+    Ok(render_mermaid(&nodes, &edges))
+}
+
+/// 收集以某节点为中心、指定深度内的子图（双向遍历）
+pub fn collect_subgraph(
+    storage: &dyn GraphStorage,
+    center: &str,
+    depth: usize,
+) -> Result<(Vec<Node>, Vec<Edge>)> {
+    if !storage.node_exists(center)? {
+        bail!("Node not found: {}", center);
+    }
+
+    let mut visited: HashSet<String> = HashSet::new();
+    let mut edges: BTreeMap<String, Edge> = BTreeMap::new();
+    let mut queue = VecDeque::new();
+
+    visited.insert(center.to_string());
+    queue.push_back((center.to_string(), 0));
+
+    while let Some((current, level)) = queue.pop_front() {
+        if level >= depth {
+            continue;
+        }
+
+        for edge in storage.get_edges_for_node(&current, EdgeDirection::Both)? {
+            let neighbor = if edge.from == current { &edge.to } else { &edge.from };
+            if visited.insert(neighbor.clone()) {
+                queue.push_back((neighbor.clone(), level + 1));
+            }
+            edges.insert(edge.id.clone(), edge);
+        }
+    }
+
+    let mut nodes = Vec::new();
+    for id in &visited {
+        match storage.get_node(id)? {
+            Some(node) => nodes.push(node),
+            None => nodes.push(Node::new(id.as_str(), NodeType::Concept, id.rsplit(':').next().unwrap_or(id))),
+        }
+    }
+
+    Ok((nodes, edges.into_values().collect()))
+}
+
+/// 将节点和边渲染为 Mermaid `graph TD` 文本
+///
+/// 节点按 ID 排序以保证输出稳定；边引用但不在节点列表中的端点会以 ID 作为标签补齐
+pub fn render_mermaid(nodes: &[Node], edges: &[Edge]) -> String {
+    let mut sorted: Vec<&Node> = nodes.iter().collect();
+    sorted.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let mut ids: HashMap<&str, String> = HashMap::new();
+    let mut used: HashSet<String> = HashSet::new();
+    let mut output = String::from("graph TD\n");
+
+    for node in &sorted {
+        let mermaid_id = unique_id(&node.id, &mut used);
+        writeln!(
+            output,
+            "    {}[\"{}\"]:::{}",
+            mermaid_id,
+            escape_label(&node.title),
+            class_name(&node.node_type)
+        )
+        .unwrap();
+        ids.insert(node.id.as_str(), mermaid_id);
+    }
+
+    // 补齐悬空端点
+    for edge in edges {
+        for endpoint in [&edge.from, &edge.to] {
+            if !ids.contains_key(endpoint.as_str()) {
+                let mermaid_id = unique_id(endpoint, &mut used);
+                let label = endpoint.rsplit(':').next().unwrap_or(endpoint);
+                writeln!(output, "    {}[\"{}\"]", mermaid_id, escape_label(label)).unwrap();
+                ids.insert(endpoint.as_str(), mermaid_id);
+            }
+        }
+    }
+
+    for edge in edges {
+        writeln!(
+            output,
+            "    {} -->|{}| {}",
+            ids[edge.from.as_str()],
+            edge.relation,
+            ids[edge.to.as_str()]
+        )
+        .unwrap();
+    }
+
+    let mut types: Vec<&NodeType> = sorted.iter().map(|n| &n.node_type).collect();
+    types.sort_by_key(|t| t.to_string());
+    types.dedup();
+    for node_type in types {
+        writeln!(output, "    classDef {} {}", class_name(node_type), class_style(node_type)).unwrap();
+    }
+
+    output
+}
+
+/// 生成合法且唯一的 Mermaid 节点标识符
+fn unique_id(node_id: &str, used: &mut HashSet<String>) -> String {
+    let base = sanitize_id(node_id);
+    let mut candidate = base.clone();
+    let mut suffix = 1;
+    while used.contains(&candidate) {
+        suffix += 1;
+        candidate = format!("{}_{}", base, suffix);
+    }
+    used.insert(candidate.clone());
+    candidate
+}
+
+/// 将节点 ID 转换为只含 ASCII 字母数字和下划线的标识符
+fn sanitize_id(node_id: &str) -> String {
+    let local = node_id
+        .strip_prefix("urn:memexia:file:")
+        .or_else(|| node_id.strip_prefix("urn:memexia:"))
+        .unwrap_or(node_id);
+
+    let mut id: String = local
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    id = id.trim_matches('_').to_string();
+
+    // Mermaid 保留字和空标识符需要加前缀
+    if id.is_empty() || id.starts_with(|c: char| c.is_ascii_digit()) || id == "end" || id == "graph" {
+        id = format!("n_{}", id);
+    }
+    id
+}
+
+/// 转义 Mermaid 标签文本
+fn escape_label(label: &str) -> String {
+    label
+        .replace('"', "#quot;")
+        .replace(['\r', '\n'], " ")
+}
+
+/// 节点类型对应的 classDef 名称
+fn class_name(node_type: &NodeType) -> String {
+    node_type.to_string().to_lowercase()
+}
+
+/// 节点类型对应的样式（与 DOT 导出的配色保持一致）
+fn class_style(node_type: &NodeType) -> &'static str {
+    match node_type {
+        NodeType::Concept => "fill:#add8e6,stroke:#333",
+        NodeType::Question => "fill:#ffffe0,stroke:#333",
+        NodeType::Evidence => "fill:#90ee90,stroke:#333",
+        NodeType::Resource => "fill:#f5deb3,stroke:#333",
+        NodeType::Person => "fill:#ffb6c1,stroke:#333",
+        NodeType::Event => "fill:#e6e6fa,stroke:#333",
+        NodeType::Meta => "fill:#d3d3d3,stroke:#333",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{RelationType, Storage};
+    use tempfile::TempDir;
+
+    fn star_storage(temp_dir: &TempDir) -> Storage {
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        let hub = Node::new("urn:memexia:file:hub.md", NodeType::Concept, "Hub \"core\"");
+        storage.graph().add_node(&hub).unwrap();
+
+        for (i, relation) in [RelationType::Supports, RelationType::Contradicts, RelationType::Refines]
+            .into_iter()
+            .enumerate()
+        {
+            let id = format!("urn:memexia:file:leaf{}.md", i);
+            let leaf = Node::new(id.as_str(), NodeType::Question, format!("Leaf\n{}", i));
+            storage.graph().add_node(&leaf).unwrap();
+            let edge_id = format!("urn:memexia:edge:urn:memexia:file:hub.md-{}", id);
+            let edge = Edge::new(&edge_id, "urn:memexia:file:hub.md", id.as_str(), relation);
+            storage.graph().add_edge(&edge).unwrap();
+        }
+
+        storage
+    }
+
+    #[test]
+    fn test_render_star_graph() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = star_storage(&temp_dir);
+
+        let output = export_mermaid(storage.graph(), &MermaidOptions::default()).unwrap();
+
+        assert!(output.starts_with("graph TD\n"));
+        assert!(output.contains("    hub_md[\"Hub #quot;core#quot;\"]:::concept\n"));
+        assert!(output.contains("    leaf0_md[\"Leaf 0\"]:::question\n"));
+        assert!(output.contains("    hub_md -->|Supports| leaf0_md\n"));
+        assert!(output.contains("    hub_md -->|Contradicts| leaf1_md\n"));
+        assert!(output.contains("    hub_md -->|Refines| leaf2_md\n"));
+        assert!(output.contains("    classDef concept "));
+        assert!(output.contains("    classDef question "));
+        assert_eq!(output.lines().filter(|l| l.contains("-->")).count(), 3);
+    }
+
+    #[test]
+    fn test_center_subgraph() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = star_storage(&temp_dir);
+
+        let options = MermaidOptions {
+            center: Some("urn:memexia:file:leaf1.md".to_string()),
+            depth: 1,
+            ..Default::default()
+        };
+        let output = export_mermaid(storage.graph(), &options).unwrap();
+
+        assert!(output.contains("hub_md -->|Contradicts| leaf1_md"));
+        assert!(!output.contains("leaf0_md"));
+    }
+
+    #[test]
+    fn test_node_cap() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = star_storage(&temp_dir);
+
+        let options = MermaidOptions {
+            max_nodes: 3,
+            ..Default::default()
+        };
+        let err = export_mermaid(storage.graph(), &options).unwrap_err().to_string();
+        assert!(err.contains("exceeds the Mermaid export cap of 3"));
+        assert!(err.contains("--center"));
+
+        // 指定中心节点时不受整图上限限制
+        let options = MermaidOptions {
+            center: Some("urn:memexia:file:hub.md".to_string()),
+            depth: 1,
+            max_nodes: 3,
+        };
+        assert!(export_mermaid(storage.graph(), &options).is_ok());
+    }
+
+    #[test]
+    fn test_sanitize_id() {
+        assert_eq!(sanitize_id("urn:memexia:file:notes/free will.md"), "notes_free_will_md");
+        assert_eq!(sanitize_id("urn:memexia:file:2024.md"), "n_2024_md");
+        assert_eq!(sanitize_id("urn:memexia:file:%E7%9B%AE"), "E7_9B_AE");
+
+        let mut used = HashSet::new();
+        assert_eq!(unique_id("urn:memexia:file:a-b", &mut used), "a_b");
+        assert_eq!(unique_id("urn:memexia:file:a_b", &mut used), "a_b_2");
+    }
+}
@@ -9,19 +9,37 @@
 //! - [`graph`](graph::GraphStorage) - 图存储抽象 trait
 //! - [`oxigraph`](oxigraph::OxigraphStorage) - Oxigraph 图数据库实现
 //! - [`nquads`](nquads) - N-Quads 序列化/反序列化
+//! - [`csv`](csv) - CSV 导入导出
+//! - [`mermaid`](mermaid) - Mermaid 图导出
+//! - [`observer`](observer::StorageObserver) - 写入后通知外部系统的观察者
+//! - [`cache`](cache::CachedStorage) - 读取密集的命令使用的节点读缓存
 
 pub mod node;
 pub mod edge;
 pub mod graph;
 pub mod oxigraph;
 pub mod nquads;
-
-pub use node::{Node, NodeType};
-pub use edge::{Edge, EdgeFilter, EdgeSource, RelationType};
-pub use graph::{GraphStorage, GraphStats, QueryResult, EdgeDirection};
+pub mod csv;
+pub mod ndjson;
+pub mod schema;
+pub mod mermaid;
+pub mod dot;
+pub mod write_guard;
+pub mod ids;
+pub mod observer;
+pub mod cache;
+
+pub use node::{Node, NodeBuilder, NodeType, ARCHIVED_AT_KEY, ARCHIVED_TAG, CONTENT_HASH_KEY, DELETED_AT_KEY, ENCRYPTED_KEY, EXCERPT_OF_KEY, EXCLUDED_KEY, LANGUAGE_KEY, LAST_REVIEWED_KEY, PREVIOUS_TITLE_KEY, SUMMARY_KEY, SUMMARY_SOURCE_KEY, WORD_COUNT_KEY};
+pub use edge::{Edge, EdgeAnnotation, EdgeBuilder, EdgeChange, EdgeEdit, EdgeField, EdgeFilter, EdgeSource, RelationType};
+pub use graph::{DanglingRepair, DanglingStrategy, GraphStorage, GraphStats, QueryResult, EdgeDirection};
 pub use oxigraph::OxigraphStorage;
+pub use write_guard::{Mutation, MutationKind, MutationSummary, WriteGuard};
+pub use observer::{ChangeLog, StorageChange, StorageObserver};
+pub use cache::{CacheStats, CachedStorage};
 
-use anyhow::{Context, Result};
+use crate::error::{MemexiaError, Result};
+use crate::events::EventBus;
+use anyhow::Context;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
@@ -52,6 +70,40 @@ impl Default for RepositoryMeta {
     }
 }
 
+#[cfg(test)]
+thread_local! {
+    /// 本线程打开图存储的次数（测试检查代理到服务的命令没有打开存储）
+    static STORE_OPENS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// 本线程打开图存储的次数
+#[cfg(test)]
+pub(crate) fn store_opens_on_this_thread() -> usize {
+    STORE_OPENS.with(|opens| opens.get())
+}
+
+/// 重置前的图存储备份（`.memexia/graph.bak.<时间戳>`），见 [`Storage::reset`]
+///
+/// 新图可用后调用 [`discard`](Self::discard) 删除，否则交给 [`Storage::restore`] 恢复
+#[derive(Debug)]
+#[must_use = "the backup must be discarded or restored"]
+pub struct GraphBackup {
+    path: PathBuf,
+}
+
+impl GraphBackup {
+    /// 备份目录
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 删除备份
+    pub fn discard(self) -> Result<()> {
+        std::fs::remove_dir_all(&self.path).with_context(|| format!("Failed to remove {:?}", self.path))?;
+        Ok(())
+    }
+}
+
 /// Memexia 存储管理器
 ///
 /// 提供存储层的统一入口，封装图存储和文件操作
@@ -61,6 +113,12 @@ pub struct Storage {
     root: PathBuf,
     /// Oxigraph 图数据库
     graph_storage: OxigraphStorage,
+    /// dry-run 时拦截写操作的包装
+    guard: Option<WriteGuard>,
+    /// 节点读缓存（读取密集的命令开启）
+    cache: Option<CachedStorage>,
+    /// 仓库事件总线（克隆的存储共享同一总线）
+    events: EventBus,
 }
 
 impl Storage {
@@ -74,28 +132,128 @@ impl Storage {
     ///
     /// `Storage` 实例
     pub fn open(root: &Path) -> Result<Self> {
+        let storage = Self::open_unmigrated(root)?;
+        schema::Migrator::default()
+            .migrate_to_latest(&storage.graph_storage, root)
+            .map_err(|err| Self::write_access_error(root, err))?;
+
+        Ok(storage)
+    }
+
+    /// 以只读模式打开已有仓库
+    ///
+    /// 不写入任何文件，也不执行结构迁移；通过 [`graph`](Self::graph) 的写操作返回
+    /// [`MemexiaError::ReadOnlyRepository`]。用于只读挂载或没有写权限的仓库
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - 仓库根目录路径
+    ///
+    /// # Returns
+    ///
+    /// `Storage` 实例
+    pub fn open_read_only(root: &Path) -> Result<Self> {
+        let graph_path = Self::check_layout(root)?;
+        #[cfg(test)]
+        STORE_OPENS.with(|opens| opens.set(opens.get() + 1));
+        let graph_storage = OxigraphStorage::open_read_only(&graph_path).map_err(|err| Self::open_error(root, err))?;
+
+        let version = schema::read_version(root)?;
+        let latest = schema::Migrator::default().latest();
+        if version < latest {
+            tracing::warn!(version, latest, "read-only repository uses an older schema; migrations were skipped");
+        }
+
+        let guard = WriteGuard::read_only(std::sync::Arc::new(graph_storage.clone()), root.to_path_buf());
+        Ok(Self {
+            root: root.to_path_buf(),
+            graph_storage,
+            guard: Some(guard),
+            cache: None,
+            events: EventBus::new(),
+        })
+    }
+
+    /// 打开已有仓库，但不执行结构迁移
+    ///
+    /// 供 `memexia migrate` 显式迁移到指定版本使用
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - 仓库根目录路径
+    ///
+    /// # Returns
+    ///
+    /// `Storage` 实例
+    pub fn open_unmigrated(root: &Path) -> Result<Self> {
+        let graph_path = Self::check_layout(root)?;
+        #[cfg(test)]
+        STORE_OPENS.with(|opens| opens.set(opens.get() + 1));
+        let graph_storage = OxigraphStorage::open(&graph_path)
+            .map_err(|err| Self::write_access_error(root, Self::open_error(root, err)))?;
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            graph_storage,
+            guard: None,
+            cache: None,
+            events: EventBus::new(),
+        })
+    }
+
+    /// 权限不足导致的错误转换为 [`MemexiaError::WriteAccessDenied`]，提示使用 `--read-only`；
+    /// 存储被其他进程占用时转换为 [`MemexiaError::StoreBusy`]
+    fn write_access_error(root: &Path, err: MemexiaError) -> MemexiaError {
+        if err.is_permission_denied() {
+            tracing::debug!("opening repository for writing failed: {}", err);
+            MemexiaError::WriteAccessDenied { path: root.to_path_buf() }
+        } else if err.is_lock_conflict() {
+            tracing::debug!("graph store is locked by another process: {}", err);
+            MemexiaError::StoreBusy { path: root.to_path_buf() }
+        } else {
+            err
+        }
+    }
+
+    /// 打开图存储时的其余存储错误（文件损坏、缺失）转换为 [`MemexiaError::StoreCorrupt`]；
+    /// 权限不足和锁冲突保持原样
+    fn open_error(root: &Path, err: MemexiaError) -> MemexiaError {
+        if err.is_permission_denied() || err.is_lock_conflict() {
+            return err;
+        }
+        match err {
+            MemexiaError::StorageError(err) => {
+                tracing::debug!("graph store cannot be opened: {}", err);
+                MemexiaError::StoreCorrupt { path: root.to_path_buf(), reason: err.to_string() }
+            }
+            other => other,
+        }
+    }
+
+    /// 检查仓库目录结构，返回图存储路径
+    fn check_layout(root: &Path) -> Result<PathBuf> {
         let memexia_path = root.join(".memexia");
         if !memexia_path.exists() {
-            anyhow::bail!("Not a valid Memexia repository: missing .memexia");
+            return Err(MemexiaError::RepositoryNotFound { path: root.to_path_buf() });
         }
 
         let meta_path = memexia_path.join("meta.json");
         if !meta_path.exists() {
-            anyhow::bail!("Not a valid Memexia repository: missing .memexia/meta.json");
+            return Err(MemexiaError::CorruptRepository {
+                path: root.to_path_buf(),
+                reason: "missing .memexia/meta.json".to_string(),
+            });
         }
 
         let graph_path = memexia_path.join("graph");
         if !graph_path.exists() {
-            anyhow::bail!("Not a valid Memexia repository: missing .memexia/graph");
+            return Err(MemexiaError::StoreCorrupt {
+                path: root.to_path_buf(),
+                reason: "missing .memexia/graph".to_string(),
+            });
         }
 
-        let graph_storage = OxigraphStorage::open(&graph_path)
-            .with_context(|| format!("Failed to open graph store at {:?}", graph_path))?;
-
-        Ok(Self {
-            root: root.to_path_buf(),
-            graph_storage,
-        })
+        Ok(graph_path)
     }
 
     /// 初始化新仓库
@@ -108,22 +266,40 @@ impl Storage {
     ///
     /// `Storage` 实例
     pub fn init(root: &Path) -> Result<Self> {
+        Self::init_with_notes(root, &[])
+    }
+
+    /// 初始化存储，并创建指定的笔记目录
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - 仓库根目录
+    /// * `notes` - 笔记目录（相对仓库根目录）；为空时创建 `notes/`
+    ///
+    /// # Returns
+    ///
+    /// `Storage` 实例
+    pub fn init_with_notes(root: &Path, notes: &[String]) -> Result<Self> {
         let memexia_dir = root.join(".memexia");
 
         // 检查是否已存在
         if memexia_dir.exists() {
-            anyhow::bail!("Memexia repository already exists at {:?}", root);
+            return Err(MemexiaError::AlreadyInitialized { path: root.to_path_buf() });
         }
 
         // 创建目录结构
         std::fs::create_dir_all(&memexia_dir)?;
         std::fs::create_dir_all(memexia_dir.join("objects"))?;
         std::fs::create_dir_all(memexia_dir.join("config"))?;
-        std::fs::create_dir_all(memexia_dir.join("index"))?;
         std::fs::create_dir_all(memexia_dir.join("graph"))?;
 
-        // 创建 notes 目录
-        std::fs::create_dir_all(root.join("notes"))?;
+        // 创建笔记目录
+        if notes.is_empty() {
+            std::fs::create_dir_all(root.join("notes"))?;
+        }
+        for dir in notes {
+            std::fs::create_dir_all(root.join(dir))?;
+        }
 
         // 创建 meta.json 元数据文件
         let meta = RepositoryMeta::default();
@@ -136,12 +312,15 @@ impl Storage {
         std::fs::write(&nq_path, "").with_context(|| format!("Failed to create {:?}", nq_path))?;
 
         // 初始化 Oxigraph 存储
-        let graph_storage = OxigraphStorage::create(&memexia_dir.join("graph"))
-            .with_context(|| format!("Failed to create OxigraphStorage"))?;
+        let graph_storage = OxigraphStorage::create(&memexia_dir.join("graph"))?;
+        schema::write_version(root, schema::Migrator::default().latest())?;
 
         Ok(Self {
             root: root.to_path_buf(),
             graph_storage,
+            guard: None,
+            cache: None,
+            events: EventBus::new(),
         })
     }
 
@@ -151,7 +330,11 @@ impl Storage {
     ///
     /// 实现了 `GraphStorage` trait 的引用
     pub fn graph(&self) -> &dyn GraphStorage {
-        &self.graph_storage
+        match (&self.guard, &self.cache) {
+            (Some(guard), _) => guard,
+            (None, Some(cache)) => cache,
+            (None, None) => &self.graph_storage,
+        }
     }
 
     /// 获取可变的图存储抽象
@@ -160,7 +343,92 @@ impl Storage {
     ///
     /// 实现了 `GraphStorage` trait 的可变引用
     pub fn graph_mut(&mut self) -> &mut dyn GraphStorage {
-        &mut self.graph_storage
+        match (&mut self.guard, &mut self.cache) {
+            (Some(guard), _) => guard,
+            (None, Some(cache)) => cache,
+            (None, None) => &mut self.graph_storage,
+        }
+    }
+
+    /// 开启节点读缓存：之后通过 [`graph`](Self::graph) 的 `get_node` 和 `node_exists` 经 LRU 缓存，
+    /// 见 [`cache`]
+    ///
+    /// 缓存注册为存储观察者，绕过缓存的写入（事务句柄、克隆的存储）同样使其失效。
+    /// 已开启或 `capacity` 为 0 时不做任何事
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - 最多缓存的节点数
+    pub fn enable_node_cache(&mut self, capacity: usize) {
+        if capacity == 0 || self.cache.is_some() {
+            return;
+        }
+        let cache = CachedStorage::new(std::sync::Arc::new(self.graph_storage.clone()), capacity);
+        self.graph_storage.observers().register(cache.invalidator());
+        if let Some(guard) = &self.guard {
+            self.guard = Some(guard.wrapping(std::sync::Arc::new(cache.clone())));
+        }
+        self.cache = Some(cache);
+    }
+
+    /// 节点读缓存（未开启时为 None）
+    pub fn node_cache(&self) -> Option<&CachedStorage> {
+        self.cache.as_ref()
+    }
+
+    /// 读操作使用的后端：开启缓存时为缓存，否则为底层存储
+    fn read_backend(&self) -> std::sync::Arc<dyn GraphStorage> {
+        match &self.cache {
+            Some(cache) => std::sync::Arc::new(cache.clone()),
+            None => std::sync::Arc::new(self.graph_storage.clone()),
+        }
+    }
+
+    /// 仓库事件总线，见 [`crate::events`]
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    /// 改用已有的事件总线（仓库先于存储创建总线，订阅者在存储打开前就已注册）
+    pub fn use_events(&mut self, events: EventBus) {
+        self.events = events;
+    }
+
+    /// 注册存储观察者：之后每次写入成功后同步通知，见 [`observer`]
+    ///
+    /// 克隆的存储共享同一组观察者
+    pub fn register_observer(&self, observer: std::sync::Arc<dyn StorageObserver>) {
+        self.graph_storage.observers().register(observer);
+    }
+
+    /// 底层 Oxigraph 存储（绕过 dry-run 写保护）
+    pub fn graph_storage(&self) -> &OxigraphStorage {
+        &self.graph_storage
+    }
+
+    /// 开启 dry-run：之后通过 [`graph`](Self::graph) 的写操作只记录不执行
+    ///
+    /// 已开启时保留现有的变更日志；之后克隆的 `Storage` 共享同一日志。
+    /// 只读模式下写操作仍然被拒绝
+    pub fn enable_dry_run(&mut self) {
+        if self.guard.is_none() {
+            self.guard = Some(WriteGuard::new(self.read_backend()));
+        }
+    }
+
+    /// 是否处于 dry-run 模式
+    pub fn is_dry_run(&self) -> bool {
+        self.guard.as_ref().is_some_and(|guard| !guard.is_read_only())
+    }
+
+    /// 是否以只读模式打开
+    pub fn is_read_only(&self) -> bool {
+        self.guard.as_ref().is_some_and(WriteGuard::is_read_only)
+    }
+
+    /// dry-run 期间记录的变更（未开启时为空）
+    pub fn mutations(&self) -> Vec<Mutation> {
+        self.guard.as_ref().map(WriteGuard::mutations).unwrap_or_default()
     }
 
     /// 获取仓库根路径
@@ -209,6 +477,110 @@ impl Storage {
         Ok(())
     }
 
+    /// 替换无法打开的图存储：把 `.memexia/graph` 移到 `.memexia/graph.corrupt.<时间戳>`，
+    /// 在原位置创建空存储
+    ///
+    /// 用于 [`MemexiaError::StoreCorrupt`] 之后从笔记文件重建图，不打开旧存储
+    ///
+    /// # Returns
+    ///
+    /// 移走的旧存储路径；`.memexia/graph` 不存在时为 None
+    pub fn recover(root: &Path) -> Result<Option<PathBuf>> {
+        let graph_path = root.join(".memexia/graph");
+        let moved = if graph_path.exists() {
+            let moved = root.join(format!(".memexia/graph.corrupt.{}", chrono::Utc::now().format("%Y%m%d%H%M%S%3f")));
+            std::fs::rename(&graph_path, &moved)
+                .with_context(|| format!("Failed to move {:?} to {:?}", graph_path, moved))?;
+            Some(moved)
+        } else {
+            None
+        };
+        std::fs::create_dir_all(&graph_path)?;
+        OxigraphStorage::create(&graph_path)?;
+        tracing::warn!(moved = ?moved, "replaced the corrupt graph store with an empty one");
+        Ok(moved)
+    }
+
+    /// 清空图存储：关闭当前存储，把 `.memexia/graph` 移到 `.memexia/graph.bak.<时间戳>`，
+    /// 在原位置创建空存储
+    ///
+    /// 与逐个删除节点不同，旧图中的所有四元组（包括不属于任何节点的）都不会留下。
+    /// 已注册的观察者和事件总线保留，节点读缓存清空。备份交给调用方：新图可用后
+    /// [`GraphBackup::discard`]，否则 [`restore`](Self::restore)。
+    /// 其他仍持有旧存储的克隆不会切换到新存储，调用前应先释放
+    ///
+    /// # Returns
+    ///
+    /// 旧图的备份；只读或 dry-run 模式下返回错误
+    pub fn reset(&mut self) -> Result<GraphBackup> {
+        if self.is_read_only() {
+            return Err(MemexiaError::ReadOnlyRepository { path: self.root.clone() });
+        }
+        if self.is_dry_run() {
+            return Err(MemexiaError::Validation("Cannot reset the graph store in dry-run mode".to_string()));
+        }
+
+        let graph_path = self.root.join(".memexia/graph");
+        let backup = self
+            .root
+            .join(format!(".memexia/graph.bak.{}", chrono::Utc::now().format("%Y%m%d%H%M%S%3f")));
+        let capacity = self.close_store()?;
+        if let Err(err) = std::fs::rename(&graph_path, &backup) {
+            self.reopen_store(&graph_path, capacity)?;
+            return Err(anyhow::Error::new(err)
+                .context(format!("Failed to move {:?} to {:?}", graph_path, backup))
+                .into());
+        }
+
+        let observers = self.graph_storage.observers().clone();
+        match OxigraphStorage::create(&graph_path) {
+            Ok(storage) => self.graph_storage = storage.with_observers(observers),
+            Err(err) => {
+                let _ = std::fs::remove_dir_all(&graph_path);
+                std::fs::rename(&backup, &graph_path)?;
+                self.reopen_store(&graph_path, capacity)?;
+                return Err(err);
+            }
+        }
+        if let Some(capacity) = capacity {
+            self.enable_node_cache(capacity);
+        }
+        tracing::info!(backup = %backup.display(), "graph store reset");
+        Ok(GraphBackup { path: backup })
+    }
+
+    /// 丢弃 [`reset`](Self::reset) 之后写入的图，恢复备份
+    pub fn restore(&mut self, backup: GraphBackup) -> Result<()> {
+        let graph_path = self.root.join(".memexia/graph");
+        let capacity = self.close_store()?;
+        if graph_path.exists() {
+            std::fs::remove_dir_all(&graph_path).with_context(|| format!("Failed to remove {:?}", graph_path))?;
+        }
+        std::fs::rename(&backup.path, &graph_path)
+            .with_context(|| format!("Failed to restore {:?} from {:?}", graph_path, backup.path))?;
+        self.reopen_store(&graph_path, capacity)?;
+        tracing::info!(backup = %backup.path.display(), "graph store restored");
+        Ok(())
+    }
+
+    /// 关闭图存储（换成空的内存存储，保留观察者），返回节点读缓存的容量
+    fn close_store(&mut self) -> Result<Option<usize>> {
+        let capacity = self.cache.take().map(|cache| cache.capacity());
+        let observers = self.graph_storage.observers().clone();
+        self.graph_storage = OxigraphStorage::in_memory()?.with_observers(observers);
+        Ok(capacity)
+    }
+
+    /// 重新打开 [`close_store`](Self::close_store) 关闭的图存储
+    fn reopen_store(&mut self, graph_path: &Path, capacity: Option<usize>) -> Result<()> {
+        let observers = self.graph_storage.observers().clone();
+        self.graph_storage = OxigraphStorage::open(graph_path)?.with_observers(observers);
+        if let Some(capacity) = capacity {
+            self.enable_node_cache(capacity);
+        }
+        Ok(())
+    }
+
     /// 导出图为 N-Quads 格式
     ///
     /// # Arguments
@@ -277,7 +649,24 @@ mod tests {
 
         // 尝试打开不存在的仓库
         let result = Storage::open(temp_dir.path());
-        assert!(result.is_err());
+        assert!(matches!(result, Err(MemexiaError::RepositoryNotFound { .. })));
+    }
+
+    #[test]
+    fn test_storage_require_missing_node() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+
+        let result = storage.graph().require_node("urn:memexia:file:missing.md");
+        assert!(matches!(
+            result,
+            Err(MemexiaError::NodeNotFound { ref id }) if id == "urn:memexia:file:missing.md"
+        ));
+        let edge_id = "urn:memexia:edge:urn:memexia:file:a.md-urn:memexia:file:b.md";
+        assert!(matches!(
+            storage.graph().require_edge(edge_id),
+            Err(MemexiaError::EdgeNotFound { .. })
+        ));
     }
 
     #[test]
@@ -338,6 +727,249 @@ mod tests {
 
         assert_eq!(edges.len(), 1);
         assert_eq!(edges[0].relation, RelationType::Contradicts);
+
+        // 更新节点属性不影响出边
+        let mut node1 = storage.graph().get_node("urn:memexia:file:a.md").unwrap().unwrap();
+        node1.title = "A2".to_string();
+        storage.graph().update_node(&node1).unwrap();
+        assert_eq!(storage.graph().require_node("urn:memexia:file:a.md").unwrap().title, "A2");
+        assert_eq!(storage.graph().get_edges_by_source("urn:memexia:file:a.md").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_storage_get_related() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        let graph = storage.graph();
+        let (book, ch1, ch2, essay) =
+            ("urn:memexia:file:book.md", "urn:memexia:file:ch1.md", "urn:memexia:file:ch2.md", "urn:memexia:file:essay.md");
+        for (from, to, relation) in [
+            (book, ch1, RelationType::Contains),
+            (ch2, book, RelationType::PartOf),
+            (essay, book, RelationType::Supports),
+        ] {
+            graph.add_edge(&Edge::builder(from, to, relation).build().unwrap()).unwrap();
+        }
+
+        // 出边的关系和入边的逆关系都匹配
+        assert_eq!(graph.get_related(book, &RelationType::Contains).unwrap(), [ch1, ch2]);
+        assert_eq!(graph.get_related(ch1, &RelationType::PartOf).unwrap(), [book]);
+        assert!(graph.get_related(ch2, &RelationType::Contains).unwrap().is_empty());
+        // 没有逆关系的关系只匹配出边
+        assert!(graph.get_related(book, &RelationType::Supports).unwrap().is_empty());
+        assert_eq!(graph.get_related(essay, &RelationType::Supports).unwrap(), [book]);
+    }
+
+    #[test]
+    fn test_storage_find_by_title() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        let graph = storage.graph();
+        let ids = |nodes: Vec<Node>| nodes.into_iter().map(|n| n.id).collect::<Vec<_>>();
+
+        graph.add_node(&Node::new("urn:memexia:file:b.md", NodeType::Concept, "Rust")).unwrap();
+        graph.add_node(&Node::new("urn:memexia:file:a.md", NodeType::Concept, "rust")).unwrap();
+        assert_eq!(ids(graph.find_by_title("Rust", true).unwrap()), ["urn:memexia:file:b.md"]);
+        // 同名节点都能查到
+        assert_eq!(
+            ids(graph.find_by_title("RUST", false).unwrap()),
+            ["urn:memexia:file:a.md", "urn:memexia:file:b.md"]
+        );
+
+        // 更新标题和删除节点时索引随之更新
+        let mut node = graph.require_node("urn:memexia:file:a.md").unwrap();
+        node.title = "Go".to_string();
+        graph.update_node(&node).unwrap();
+        graph.delete_node("urn:memexia:file:b.md").unwrap();
+        assert!(graph.find_by_title("rust", false).unwrap().is_empty());
+        assert_eq!(ids(graph.find_by_title("go", false).unwrap()), ["urn:memexia:file:a.md"]);
+        assert!(graph.title_index_mismatches().unwrap().is_empty());
+
+        // 事务回滚时索引一同恢复
+        let result = graph.transaction(|graph| {
+            graph.add_node(&Node::new("urn:memexia:file:c.md", NodeType::Concept, "Go"))?;
+            Err::<(), _>(MemexiaError::Validation("abort".to_string()))
+        });
+        assert!(result.is_err());
+        assert_eq!(graph.find_by_title("Go", true).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_storage_title_index_desync() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        let graph = storage.graph();
+        graph.add_node(&Node::new("urn:memexia:file:a.md", NodeType::Concept, "Alpha")).unwrap();
+        graph.add_node(&Node::new("urn:memexia:file:b.md", NodeType::Concept, "Beta")).unwrap();
+
+        // 模拟索引丢失一项
+        let nquads: String = graph
+            .export_nquads()
+            .unwrap()
+            .lines()
+            .filter(|line| !(line.contains("titleKey") && line.contains("\"alpha\"")))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        storage.graph_storage().replace_contents(&nquads).unwrap();
+        assert!(graph.find_by_title("alpha", false).unwrap().is_empty());
+        assert_eq!(graph.title_index_mismatches().unwrap(), ["urn:memexia:file:a.md"]);
+
+        assert_eq!(graph.rebuild_title_index().unwrap(), 1);
+        assert!(graph.title_index_mismatches().unwrap().is_empty());
+        assert_eq!(graph.find_by_title("alpha", false).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_storage_reset_and_restore() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut storage = Storage::init(temp_dir.path()).unwrap();
+        storage.graph().add_node(&Node::new("urn:memexia:file:a.md", NodeType::Concept, "A")).unwrap();
+
+        let backup = storage.reset().unwrap();
+        assert!(backup.path().exists());
+        assert!(storage.graph().get_all_nodes().unwrap().is_empty());
+        storage.graph().add_node(&Node::new("urn:memexia:file:b.md", NodeType::Concept, "B")).unwrap();
+
+        storage.restore(backup).unwrap();
+        let ids: Vec<String> = storage.graph().get_all_nodes().unwrap().into_iter().map(|n| n.id).collect();
+        assert_eq!(ids, ["urn:memexia:file:a.md"]);
+
+        let backup = storage.reset().unwrap();
+        let path = backup.path().to_path_buf();
+        backup.discard().unwrap();
+        assert!(!path.exists());
+        assert!(temp_dir.path().join(".memexia/graph").exists());
+        assert!(storage.graph().get_all_nodes().unwrap().is_empty());
+
+        storage.enable_dry_run();
+        assert!(storage.reset().is_err());
+    }
+
+    fn seed_transaction_graph(storage: &Storage) {
+        storage.graph().add_node(&Node::new("urn:memexia:file:a.md", NodeType::Concept, "A")).unwrap();
+        storage.graph().add_node(&Node::new("urn:memexia:file:b.md", NodeType::Concept, "B")).unwrap();
+        let (from, to) = ("urn:memexia:file:a.md", "urn:memexia:file:b.md");
+        storage
+            .graph()
+            .add_edge(&Edge::new(Edge::make_id(from, to), from, to, RelationType::Supports))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_storage_transaction_rolls_back_on_error() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        seed_transaction_graph(&storage);
+        let before = storage.graph().export_nquads().unwrap();
+
+        let result: Result<()> = storage.graph().transaction(|graph| {
+            let mut a = graph.require_node("urn:memexia:file:a.md")?;
+            a.title = "A2".to_string();
+            graph.update_node(&a)?;
+            graph.delete_node("urn:memexia:file:b.md")?;
+            graph.add_node(&Node::new("urn:memexia:file:c.md", NodeType::Concept, "C"))?;
+            // 事务内能读到已完成的写入
+            assert!(graph.node_exists("urn:memexia:file:c.md")?);
+            // 中途失败
+            graph.require_node("urn:memexia:file:missing.md")?;
+            Ok(())
+        });
+
+        assert!(matches!(result, Err(MemexiaError::NodeNotFound { .. })));
+        let mut before: Vec<&str> = before.lines().collect();
+        let after = storage.graph().export_nquads().unwrap();
+        let mut after: Vec<&str> = after.lines().collect();
+        before.sort_unstable();
+        after.sort_unstable();
+        assert_eq!(before, after);
+        assert_eq!(storage.graph().require_node("urn:memexia:file:a.md").unwrap().title, "A");
+        assert!(!storage.graph().node_exists("urn:memexia:file:c.md").unwrap());
+    }
+
+    #[test]
+    fn test_storage_transaction_applies_all() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        seed_transaction_graph(&storage);
+
+        let count = storage
+            .graph()
+            .transaction(|graph| {
+                graph.delete_node("urn:memexia:file:b.md")?;
+                graph.add_node(&Node::new("urn:memexia:file:c.md", NodeType::Concept, "C"))?;
+                // 嵌套事务并入外层
+                graph.transaction(|inner| inner.add_node(&Node::new("urn:memexia:file:d.md", NodeType::Concept, "D")))?;
+                Ok(graph.list_nodes()?.len())
+            })
+            .unwrap();
+
+        assert_eq!(count, 3);
+        let graph = storage.graph();
+        assert!(!graph.node_exists("urn:memexia:file:b.md").unwrap());
+        assert!(graph.node_exists("urn:memexia:file:c.md").unwrap());
+        assert!(graph.node_exists("urn:memexia:file:d.md").unwrap());
+    }
+
+    /// 两条悬空边：a → missing（目标缺失），ghost → b（源缺失）
+    fn seed_dangling_graph(storage: &Storage) {
+        let graph = storage.graph();
+        graph.add_node(&Node::new("urn:memexia:file:a.md", NodeType::Concept, "A")).unwrap();
+        graph.add_node(&Node::new("urn:memexia:file:b.md", NodeType::Concept, "B")).unwrap();
+        for (from, to) in [
+            ("urn:memexia:file:a.md", "urn:memexia:file:b.md"),
+            ("urn:memexia:file:a.md", "urn:memexia:file:notes/missing.md"),
+            ("urn:memexia:file:ghost.md", "urn:memexia:file:b.md"),
+        ] {
+            graph.add_edge(&Edge::new(Edge::make_id(from, to), from, to, RelationType::Supports)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_storage_repair_dangling() {
+        use crate::storage::{DanglingRepair, DanglingStrategy};
+
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        seed_dangling_graph(&storage);
+        let graph = storage.graph();
+
+        let dangling: Vec<_> = graph.find_dangling_edges().unwrap().into_iter().map(|e| (e.from, e.to)).collect();
+        assert_eq!(dangling.len(), 2);
+        assert!(dangling.contains(&("urn:memexia:file:ghost.md".to_string(), "urn:memexia:file:b.md".to_string())));
+
+        // 只报告：不修改
+        let report = graph.repair_dangling(DanglingStrategy::ReportOnly).unwrap();
+        assert_eq!(report, DanglingRepair { edges: 2, placeholders: 0, dropped: 0 });
+        assert_eq!(graph.find_dangling_edges().unwrap().len(), 2);
+
+        // 补建占位节点：标题取自 IRI 末段，边保留
+        let report = graph.repair_dangling(DanglingStrategy::CreatePlaceholders).unwrap();
+        assert_eq!(report, DanglingRepair { edges: 2, placeholders: 2, dropped: 0 });
+        assert!(graph.find_dangling_edges().unwrap().is_empty());
+        let missing = graph.require_node("urn:memexia:file:notes/missing.md").unwrap();
+        assert_eq!((missing.node_type, missing.title.as_str()), (NodeType::Concept, "missing.md"));
+        assert_eq!(graph.list_edges().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_storage_drop_dangling() {
+        use crate::storage::{DanglingRepair, DanglingStrategy};
+
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        seed_dangling_graph(&storage);
+        let graph = storage.graph();
+
+        let report = graph.repair_dangling(DanglingStrategy::Drop).unwrap();
+        assert_eq!(report, DanglingRepair { edges: 2, placeholders: 0, dropped: 2 });
+        assert!(graph.find_dangling_edges().unwrap().is_empty());
+        let edges = graph.list_edges().unwrap();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].to, "urn:memexia:file:b.md");
+        assert_eq!(graph.list_nodes().unwrap().len(), 2);
+
+        // 没有悬空边时什么都不做
+        assert_eq!(graph.repair_dangling(DanglingStrategy::Drop).unwrap(), DanglingRepair::default());
     }
 
     #[test]
@@ -0,0 +1,310 @@
+//! NDJSON 导入导出模块
+//!
+//! 以换行分隔的 JSON 流导出节点和边，供向量数据库、分析笔记本等下游数据管道使用
+//!
+//! ## 格式
+//!
+//! 每行一个对象，`kind` 区分节点和边，其余字段与 [`Node`] / [`Edge`] 的 JSON 形式相同：
+//!
+//! ```text
+//! {"kind":"node","id":"urn:memexia:file:a.md","node_type":"Concept","title":"A",...}
+//! {"kind":"edge","id":"urn:memexia:edge:...","from":"...","to":"...","relation":"Supports",...}
+//! ```
+//!
+//! 导出先写节点再写边；导出和导入都逐行处理，内存占用不随仓库大小增长
+
+use super::{Edge, GraphStorage, Node, NodeType};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
+
+/// 每写出多少行刷新一次输出
+pub const FLUSH_EVERY: usize = 1000;
+
+/// 一行记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Record {
+    /// 节点
+    Node(Node),
+    /// 边
+    Edge(Edge),
+}
+
+/// 导出选项
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NdjsonExportOptions {
+    /// 是否导出节点
+    pub nodes: bool,
+    /// 是否导出边
+    pub edges: bool,
+    /// 只导出在此时间（含）之后更新的实体
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl Default for NdjsonExportOptions {
+    fn default() -> Self {
+        Self {
+            nodes: true,
+            edges: true,
+            since: None,
+        }
+    }
+}
+
+/// 导出统计
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct NdjsonExportSummary {
+    /// 导出的节点数
+    pub nodes: usize,
+    /// 导出的边数
+    pub edges: usize,
+}
+
+/// NDJSON 导入统计
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct NdjsonImportSummary {
+    /// 导入的节点数
+    pub nodes_imported: usize,
+    /// 导入的边数
+    pub edges_imported: usize,
+    /// 自动创建的占位节点数
+    pub placeholders_created: usize,
+}
+
+/// 边的最后更新时间：创建时间、最近一次修改和最新注释时间中最晚的一个
+fn edge_updated_at(edge: &Edge) -> DateTime<Utc> {
+    edge.annotations
+        .iter()
+        .map(|a| a.created_at)
+        .chain(edge.updated_at)
+        .fold(edge.created_at, DateTime::max)
+}
+
+/// 将节点和边写为 NDJSON
+///
+/// # Arguments
+///
+/// * `storage` - 图存储
+/// * `writer` - 输出
+/// * `options` - 导出选项
+///
+/// # Returns
+///
+/// 导出的节点数和边数
+pub fn write_ndjson<W: Write>(
+    storage: &dyn GraphStorage,
+    mut writer: W,
+    options: NdjsonExportOptions,
+) -> Result<NdjsonExportSummary> {
+    let mut summary = NdjsonExportSummary::default();
+    let mut lines = 0;
+    let mut write_line = |record: &Record, writer: &mut W| -> crate::error::Result<()> {
+        serde_json::to_writer(&mut *writer, record)?;
+        writer.write_all(b"\n")?;
+        lines += 1;
+        if lines % FLUSH_EVERY == 0 {
+            writer.flush()?;
+        }
+        Ok(())
+    };
+
+    if options.nodes {
+        storage.for_each_node(&mut |node| {
+            if options.since.is_some_and(|since| node.updated_at < since) {
+                return Ok(());
+            }
+            summary.nodes += 1;
+            write_line(&Record::Node(node), &mut writer)
+        })?;
+    }
+    if options.edges {
+        storage.for_each_edge(&mut |edge| {
+            if options.since.is_some_and(|since| edge_updated_at(&edge) < since) {
+                return Ok(());
+            }
+            summary.edges += 1;
+            write_line(&Record::Edge(edge), &mut writer)
+        })?;
+    }
+
+    writer.flush()?;
+    Ok(summary)
+}
+
+/// 从 NDJSON 导入节点和边
+///
+/// 已存在的节点被更新，边按 ID 覆盖；空行被忽略。边引用的节点必须已存在
+/// （来自图谱或前面的行），否则按 `create_placeholders` 创建占位节点或报错
+///
+/// # Arguments
+///
+/// * `storage` - 图存储
+/// * `reader` - 输入
+/// * `create_placeholders` - 引用的节点不存在时是否自动创建占位节点
+///
+/// # Returns
+///
+/// 导入统计
+pub fn read_ndjson<R: BufRead>(
+    storage: &dyn GraphStorage,
+    reader: R,
+    create_placeholders: bool,
+) -> Result<NdjsonImportSummary> {
+    let mut summary = NdjsonImportSummary::default();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line_no = index + 1;
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: Record =
+            serde_json::from_str(&line).with_context(|| format!("line {}: invalid record", line_no))?;
+
+        match record {
+            Record::Node(node) => {
+                if storage.node_exists(&node.id)? {
+                    storage.update_node(&node)?;
+                } else {
+                    storage.add_node(&node)?;
+                }
+                summary.nodes_imported += 1;
+            }
+            Record::Edge(edge) => {
+                for (field, id) in [("from", &edge.from), ("to", &edge.to)] {
+                    if storage.node_exists(id)? {
+                        continue;
+                    }
+                    if !create_placeholders {
+                        bail!(
+                            "line {}, field '{}': node '{}' does not exist\n(use --create-placeholders to create missing nodes)",
+                            line_no,
+                            field,
+                            id
+                        );
+                    }
+                    let title = id.rsplit(':').next().unwrap_or(id);
+                    storage.add_node(&Node::new(id.as_str(), NodeType::Concept, title))?;
+                    summary.placeholders_created += 1;
+                }
+                storage.add_edge(&edge)?;
+                summary.edges_imported += 1;
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{RelationType, Storage};
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    fn node(id: &str, updated_at: DateTime<Utc>) -> Node {
+        let mut node = Node::builder(id, NodeType::Concept, id).tags(["t"]).build().unwrap();
+        node.created_at = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        node.updated_at = updated_at;
+        node
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let source_dir = TempDir::new().unwrap();
+        let source = Storage::init(source_dir.path()).unwrap();
+        let at = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        source.graph().add_node(&node("urn:memexia:file:a.md", at)).unwrap();
+        source.graph().add_node(&node("urn:memexia:file:b.md", at)).unwrap();
+        let edge = Edge::builder("urn:memexia:file:a.md", "urn:memexia:file:b.md", RelationType::Supports)
+            .strength(0.8)
+            .description("because")
+            .build()
+            .unwrap();
+        source.graph().add_edge(&edge).unwrap();
+
+        let mut out = Vec::new();
+        let summary = write_ndjson(source.graph(), &mut out, NdjsonExportOptions::default()).unwrap();
+        assert_eq!(summary, NdjsonExportSummary { nodes: 2, edges: 1 });
+        let text = String::from_utf8(out.clone()).unwrap();
+        let kinds: Vec<&str> = text
+            .lines()
+            .map(|l| if l.starts_with(r#"{"kind":"node""#) { "node" } else { "edge" })
+            .collect();
+        assert_eq!(kinds, ["node", "node", "edge"]);
+
+        let target_dir = TempDir::new().unwrap();
+        let target = Storage::init(target_dir.path()).unwrap();
+        let imported = read_ndjson(target.graph(), out.as_slice(), false).unwrap();
+        assert_eq!((imported.nodes_imported, imported.edges_imported), (2, 1));
+
+        let a = target.graph().get_node("urn:memexia:file:a.md").unwrap().unwrap();
+        assert_eq!(a.tags, ["t"]);
+        assert_eq!(a.updated_at, at);
+        let edges = target.graph().list_edges().unwrap();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].relation, RelationType::Supports);
+        assert_eq!(edges[0].strength, 0.8);
+        assert_eq!(edges[0].description.as_deref(), Some("because"));
+
+        // 再次导入同样的数据不会产生重复
+        read_ndjson(target.graph(), out.as_slice(), false).unwrap();
+        assert_eq!(target.graph().list_nodes().unwrap().len(), 2);
+        assert_eq!(target.graph().list_edges().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_since_and_kind_filters() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        let graph = storage.graph();
+        let old = Utc.with_ymd_and_hms(2024, 1, 10, 0, 0, 0).unwrap();
+        let new = Utc.with_ymd_and_hms(2024, 6, 10, 0, 0, 0).unwrap();
+        graph.add_node(&node("urn:memexia:file:old.md", old)).unwrap();
+        graph.add_node(&node("urn:memexia:file:new.md", new)).unwrap();
+        let mut edge = Edge::new("e", "urn:memexia:file:old.md", "urn:memexia:file:new.md", RelationType::RelatedTo);
+        edge.created_at = old;
+        graph.add_edge(&edge).unwrap();
+
+        let since = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap();
+        let mut out = Vec::new();
+        let options = NdjsonExportOptions {
+            since: Some(since),
+            ..NdjsonExportOptions::default()
+        };
+        assert_eq!(
+            write_ndjson(graph, &mut out, options).unwrap(),
+            NdjsonExportSummary { nodes: 1, edges: 0 }
+        );
+        assert!(String::from_utf8(out).unwrap().contains("urn:memexia:file:new.md"));
+
+        let options = NdjsonExportOptions {
+            nodes: false,
+            ..NdjsonExportOptions::default()
+        };
+        assert_eq!(
+            write_ndjson(graph, std::io::sink(), options).unwrap(),
+            NdjsonExportSummary { nodes: 0, edges: 1 }
+        );
+    }
+
+    #[test]
+    fn test_import_missing_endpoint() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::init(temp_dir.path()).unwrap();
+        let edge = Edge::new("e", "urn:memexia:file:x.md", "urn:memexia:file:y.md", RelationType::RelatedTo);
+        let line = format!("\n{}\n", serde_json::to_string(&Record::Edge(edge)).unwrap());
+
+        let err = read_ndjson(storage.graph(), line.as_bytes(), false).unwrap_err();
+        assert!(err.to_string().contains("line 2, field 'from'"));
+
+        let summary = read_ndjson(storage.graph(), line.as_bytes(), true).unwrap();
+        assert_eq!(summary.placeholders_created, 2);
+        assert_eq!(storage.graph().list_edges().unwrap().len(), 1);
+
+        assert!(read_ndjson(storage.graph(), "{\"kind\":\"bogus\"}\n".as_bytes(), true).is_err());
+    }
+}
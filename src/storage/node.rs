@@ -2,10 +2,54 @@
 //!
 //! 定义 Memexia 知识图谱中的节点类型和数据结构
 
+use crate::error::{MemexiaError, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// 字数元数据键（存储为 `memexia:wordCount` 属性）
+pub const WORD_COUNT_KEY: &str = "wordCount";
+
+/// 加密笔记标记元数据键（存储为 `memexia:encrypted` 属性）
+pub const ENCRYPTED_KEY: &str = "encrypted";
+
+/// 排除索引标记元数据键（存储为 `memexia:excluded` 属性，只出现在 `noindex` 笔记的占位节点上）
+pub const EXCLUDED_KEY: &str = "excluded";
+
+/// 上次回顾时间元数据键（存储为 `memexia:lastReviewed` 属性，RFC 3339 格式）
+pub const LAST_REVIEWED_KEY: &str = "lastReviewed";
+
+/// 软删除时间元数据键（存储为 `memexia:deletedAt` 属性，RFC 3339 格式）
+pub const DELETED_AT_KEY: &str = "deletedAt";
+
+/// 归档时间元数据键（存储为 `memexia:archivedAt` 属性，RFC 3339 格式）
+pub const ARCHIVED_AT_KEY: &str = "archivedAt";
+
+/// 归档笔记带有的标签
+pub const ARCHIVED_TAG: &str = "archived";
+
+/// 上一个标题元数据键（存储为 `memexia:previousTitle` 属性，重新索引发现标题变化时记录）
+pub const PREVIOUS_TITLE_KEY: &str = "previousTitle";
+
+/// 文件内容哈希元数据键（存储为 `memexia:contentHash` 属性，SHA-256 十六进制）
+pub const CONTENT_HASH_KEY: &str = "contentHash";
+
+/// 正文语言元数据键（存储为 `memexia:language` 属性，如 `zh`、`en`）
+pub const LANGUAGE_KEY: &str = "language";
+
+/// 正文摘录元数据键（存储为 `memexia:excerptOf` 属性，值为笔记相对路径）
+///
+/// 超长笔记只在节点上保存正文开头，完整正文仍在该文件中
+pub const EXCERPT_OF_KEY: &str = "excerptOf";
+
+/// 摘要元数据键（存储为 `memexia:summary` 属性）
+///
+/// 来自 frontmatter `summary:`，或开启 `index.auto_summary` 时从正文提取（见 [`crate::core::summary`]）
+pub const SUMMARY_KEY: &str = "summary";
+
+/// 摘要来源元数据键（存储为 `memexia:summarySource` 属性）；只有自动提取的摘要带有该键，值为 `auto`
+pub const SUMMARY_SOURCE_KEY: &str = "summarySource";
+
 /// 节点类型枚举
 ///
 /// 符合项目文档 3.2.1 定义的节点类型
@@ -115,6 +159,35 @@ impl Node {
         }
     }
 
+    /// 创建节点构建器
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - 节点 ID
+    /// * `node_type` - 节点类型
+    /// * `title` - 节点标题
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use memexia::storage::{Node, NodeType};
+    ///
+    /// let node = Node::builder("urn:memexia:file:free_will.md", NodeType::Concept, "自由意志")
+    ///     .content("自由意志是哲学中的重要概念")
+    ///     .tag("哲学")
+    ///     .metadata("source", "book")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(node.tags, vec!["哲学"]);
+    /// assert_eq!(node.metadata["source"], "book");
+    /// ```
+    pub fn builder(id: impl Into<String>, node_type: NodeType, title: impl Into<String>) -> NodeBuilder {
+        NodeBuilder {
+            node: Self::new(id, node_type, title),
+        }
+    }
+
     /// 从文件路径创建节点
     ///
     /// # Arguments
@@ -135,10 +208,9 @@ impl Node {
         Self::new(id, NodeType::Concept, title)
     }
 
-    /// 将文件路径转换为 URN 格式 ID
+    /// 将文件路径转换为 URN 格式 ID（带 URL 编码）
     fn file_path_to_id(file_path: &std::path::Path) -> String {
-        let path_str = file_path.to_string_lossy().replace('\\', "/");
-        format!("urn:memexia:file:{}", path_str)
+        super::ids::file_id(&file_path.to_string_lossy())
     }
 
     /// 更新节点内容
@@ -173,6 +245,236 @@ impl Node {
         self.tags.retain(|t| t != tag);
         self.updated_at = Utc::now();
     }
+
+    /// 获取正文字数
+    ///
+    /// 只有被索引的笔记才有字数，占位节点返回 None
+    pub fn word_count(&self) -> Option<u64> {
+        self.metadata.get(WORD_COUNT_KEY).and_then(|v| v.as_u64())
+    }
+
+    /// 是否来自加密笔记（不保存正文）
+    pub fn is_encrypted(&self) -> bool {
+        self.metadata.get(ENCRYPTED_KEY).and_then(|v| v.as_bool()).unwrap_or(false)
+    }
+
+    /// 是否为排除索引（`noindex`）笔记的占位节点
+    pub fn is_excluded(&self) -> bool {
+        self.metadata.get(EXCLUDED_KEY).and_then(|v| v.as_bool()).unwrap_or(false)
+    }
+
+    /// 标记为排除索引笔记的占位节点
+    pub fn set_excluded(&mut self) {
+        self.metadata.insert(EXCLUDED_KEY.to_string(), true.into());
+    }
+
+    /// 获取上次回顾时间
+    pub fn last_reviewed(&self) -> Option<DateTime<Utc>> {
+        let value = self.metadata.get(LAST_REVIEWED_KEY)?.as_str()?;
+        DateTime::parse_from_rfc3339(value).ok().map(|dt| dt.with_timezone(&Utc))
+    }
+
+    /// 是否为未解析链接生成的占位节点（没有正文，也没有内容哈希）
+    pub fn is_placeholder(&self) -> bool {
+        self.content.is_none() && self.content_hash().is_none()
+    }
+
+    /// 索引时文件内容的哈希（由文件索引得到的节点才有）
+    pub fn content_hash(&self) -> Option<&str> {
+        self.metadata.get(CONTENT_HASH_KEY).and_then(|v| v.as_str())
+    }
+
+    /// 记录文件内容哈希
+    pub fn set_content_hash(&mut self, hash: impl Into<String>) {
+        self.metadata.insert(CONTENT_HASH_KEY.to_string(), hash.into().into());
+    }
+
+    /// 记录回顾时间（不修改 `updated_at`）
+    pub fn set_last_reviewed(&mut self, at: DateTime<Utc>) {
+        self.metadata.insert(LAST_REVIEWED_KEY.to_string(), at.to_rfc3339().into());
+    }
+
+    /// 获取软删除时间
+    pub fn deleted_at(&self) -> Option<DateTime<Utc>> {
+        let value = self.metadata.get(DELETED_AT_KEY)?.as_str()?;
+        DateTime::parse_from_rfc3339(value).ok().map(|dt| dt.with_timezone(&Utc))
+    }
+
+    /// 是否已被软删除（位于回收站中）
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at().is_some()
+    }
+
+    /// 标记为软删除（不修改 `updated_at`）
+    pub fn set_deleted_at(&mut self, at: DateTime<Utc>) {
+        self.metadata.insert(DELETED_AT_KEY.to_string(), at.to_rfc3339().into());
+    }
+
+    /// 清除软删除标记
+    pub fn clear_deleted(&mut self) {
+        self.metadata.remove(DELETED_AT_KEY);
+    }
+
+    /// 获取归档时间
+    pub fn archived_at(&self) -> Option<DateTime<Utc>> {
+        let value = self.metadata.get(ARCHIVED_AT_KEY)?.as_str()?;
+        DateTime::parse_from_rfc3339(value).ok().map(|dt| dt.with_timezone(&Utc))
+    }
+
+    /// 是否已归档（默认不出现在搜索、统计、发布和链接推荐中）
+    pub fn is_archived(&self) -> bool {
+        self.metadata.contains_key(ARCHIVED_AT_KEY)
+    }
+
+    /// 标记为归档：加上 `archived` 标签并记录归档时间
+    pub fn set_archived_at(&mut self, at: DateTime<Utc>) {
+        self.add_tag(ARCHIVED_TAG);
+        self.metadata.insert(ARCHIVED_AT_KEY.to_string(), at.to_rfc3339().into());
+    }
+
+    /// 清除归档标记和 `archived` 标签
+    pub fn clear_archived(&mut self) {
+        self.remove_tag(ARCHIVED_TAG);
+        self.metadata.remove(ARCHIVED_AT_KEY);
+    }
+
+    /// 获取标题变化前的上一个标题
+    pub fn previous_title(&self) -> Option<&str> {
+        self.metadata.get(PREVIOUS_TITLE_KEY).and_then(|v| v.as_str())
+    }
+
+    /// 记录上一个标题
+    pub fn set_previous_title(&mut self, title: impl Into<String>) {
+        self.metadata.insert(PREVIOUS_TITLE_KEY.to_string(), title.into().into());
+    }
+
+    /// 正文语言（frontmatter `lang:` 或索引时检测，见 [`crate::core::language`]）
+    pub fn language(&self) -> Option<&str> {
+        self.metadata.get(LANGUAGE_KEY).and_then(|v| v.as_str())
+    }
+
+    /// 记录正文语言
+    pub fn set_language(&mut self, language: impl Into<String>) {
+        self.metadata.insert(LANGUAGE_KEY.to_string(), language.into().into());
+    }
+
+    /// 摘要（frontmatter `summary:` 或自动提取）
+    pub fn summary(&self) -> Option<&str> {
+        self.metadata.get(SUMMARY_KEY).and_then(|v| v.as_str())
+    }
+
+    /// 摘要是否为索引时自动提取的
+    pub fn is_auto_summary(&self) -> bool {
+        self.metadata.get(SUMMARY_SOURCE_KEY).and_then(|v| v.as_str()) == Some("auto")
+    }
+
+    /// 设置作者写的摘要（清除自动提取标记）
+    pub fn set_summary(&mut self, summary: impl Into<String>) {
+        self.metadata.insert(SUMMARY_KEY.to_string(), summary.into().into());
+        self.metadata.remove(SUMMARY_SOURCE_KEY);
+    }
+
+    /// 设置自动提取的摘要
+    pub fn set_auto_summary(&mut self, summary: impl Into<String>) {
+        self.metadata.insert(SUMMARY_KEY.to_string(), summary.into().into());
+        self.metadata.insert(SUMMARY_SOURCE_KEY.to_string(), "auto".into());
+    }
+
+    /// 正文只保存了开头部分时，完整正文所在的笔记文件（相对仓库根目录）
+    pub fn excerpt_of(&self) -> Option<&str> {
+        self.metadata.get(EXCERPT_OF_KEY).and_then(|v| v.as_str())
+    }
+
+    /// 把正文截断为不超过 `max_bytes` 字节的摘录（在字符边界截断），并记录完整正文所在的文件
+    ///
+    /// # Returns
+    ///
+    /// 是否发生了截断
+    pub fn truncate_content(&mut self, max_bytes: usize, path: &str) -> bool {
+        let Some(content) = self.content.as_mut().filter(|content| content.len() > max_bytes) else {
+            return false;
+        };
+        let mut end = max_bytes;
+        while !content.is_char_boundary(end) {
+            end -= 1;
+        }
+        content.truncate(end);
+        self.metadata.insert(EXCERPT_OF_KEY.to_string(), path.into());
+        true
+    }
+}
+
+/// 节点构建器
+///
+/// 通过 [`Node::builder`] 创建，在 [`build`](NodeBuilder::build) 时校验字段
+#[derive(Debug, Clone)]
+pub struct NodeBuilder {
+    node: Node,
+}
+
+impl NodeBuilder {
+    /// 设置 Markdown 内容
+    pub fn content(mut self, content: impl Into<String>) -> Self {
+        self.node.content = Some(content.into());
+        self
+    }
+
+    /// 添加标签（重复标签会被忽略）
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        let tag = tag.into();
+        if !self.node.tags.contains(&tag) {
+            self.node.tags.push(tag);
+        }
+        self
+    }
+
+    /// 批量添加标签
+    pub fn tags<I, S>(self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        tags.into_iter().fold(self, |builder, tag| builder.tag(tag))
+    }
+
+    /// 设置扩展元数据
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.node.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// 设置创建时间（同时作为最后修改时间）
+    pub fn created_at(mut self, created_at: DateTime<Utc>) -> Self {
+        self.node.created_at = created_at;
+        self.node.updated_at = created_at;
+        self
+    }
+
+    /// 设置最后修改时间
+    pub fn updated_at(mut self, updated_at: DateTime<Utc>) -> Self {
+        self.node.updated_at = updated_at;
+        self
+    }
+
+    /// 构建节点
+    ///
+    /// # Returns
+    ///
+    /// ID 为空或修改时间早于创建时间时返回错误
+    pub fn build(self) -> Result<Node> {
+        if self.node.id.trim().is_empty() {
+            return Err(MemexiaError::Validation("Node ID cannot be empty".to_string()));
+        }
+        if self.node.updated_at < self.node.created_at {
+            return Err(MemexiaError::Validation(format!(
+                "Node '{}' updated_at ({}) is earlier than created_at ({})",
+                self.node.id,
+                self.node.updated_at,
+                self.node.created_at
+            )));
+        }
+        Ok(self.node)
+    }
 }
 
 #[cfg(test)]
@@ -221,6 +523,36 @@ mod tests {
         assert_eq!(node.tags[0], "philosophy");
     }
 
+    #[test]
+    fn test_node_builder() {
+        let created = "2024-03-15T10:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        let node = Node::builder("test", NodeType::Evidence, "Test")
+            .content("body")
+            .tags(["a", "b", "a"])
+            .metadata("weight", 3)
+            .created_at(created)
+            .build()
+            .unwrap();
+
+        assert_eq!(node.content.as_deref(), Some("body"));
+        assert_eq!(node.tags, vec!["a", "b"]);
+        assert_eq!(node.metadata["weight"], 3);
+        assert_eq!(node.created_at, created);
+        assert_eq!(node.updated_at, created);
+    }
+
+    #[test]
+    fn test_node_builder_validation() {
+        assert!(Node::builder("  ", NodeType::Concept, "Empty").build().is_err());
+
+        let created = "2024-03-15T10:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        let result = Node::builder("test", NodeType::Concept, "Test")
+            .created_at(created)
+            .updated_at(created - chrono::Duration::days(1))
+            .build();
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_node_serde_roundtrip() {
         let mut node = Node::new("test-id", NodeType::Question, "Test Question");
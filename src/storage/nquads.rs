@@ -3,9 +3,16 @@
 //! 实现 RDF N-Quads 格式的导入导出功能
 //!
 //! N-Quads 格式规范: https://www.w3.org/TR/n-quads/
+//!
+//! ## 规范顺序
+//!
+//! 导出（[`export_nquads`] 和 [`GraphStorage::export_nquads`]）按规范顺序输出，
+//! 与存储的插入顺序无关：依次按主语、谓语、宾语的序列化形式（如 `<urn:...>`、`"literal"`）
+//! 逐字节排序，重复的三元组只输出一次。图快照（格式版本 2 起）即为这一顺序的导出结果
 
-use super::{Edge, GraphStorage, RelationType};
-use anyhow::{Context, Result};
+use super::{Edge, GraphStorage, Node, RelationType};
+use crate::error::{MemexiaError, Result};
+use anyhow::Context;
 use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::{BufRead, BufReader, Write};
@@ -30,16 +37,7 @@ impl<W: Write> NQuadsEncoder<W> {
         predicate: &str,
         object: &str,
     ) -> Result<()> {
-        let subject_iri = escape_iri(subject);
-        let predicate_iri = escape_iri(predicate);
-        let object_value = if object.starts_with("http://")
-            || object.starts_with("https://")
-            || object.starts_with("urn:")
-        {
-            escape_iri(object)
-        } else {
-            escape_string(object)
-        };
+        let (subject_iri, predicate_iri, object_value) = serialize_triple(subject, predicate, object);
 
         writeln!(
             self.writer,
@@ -52,6 +50,19 @@ impl<W: Write> NQuadsEncoder<W> {
     }
 }
 
+/// 三元组各项的序列化形式：(主语, 谓语, 宾语)
+fn serialize_triple(subject: &str, predicate: &str, object: &str) -> (String, String, String) {
+    let object_value = if object.starts_with("http://")
+        || object.starts_with("https://")
+        || object.starts_with("urn:")
+    {
+        escape_iri(object)
+    } else {
+        escape_string(object)
+    };
+    (escape_iri(subject), escape_iri(predicate), object_value)
+}
+
 /// N-Quads 解码器
 pub struct NQuadsDecoder<R: BufRead> {
     /// 读取器
@@ -69,8 +80,13 @@ impl<R: BufRead> NQuadsDecoder<R> {
         }
     }
 
+    /// 最近读取的行号（从 1 开始；解析失败时为出错的行）
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
     /// 读取下一个三元组
-    pub fn read_triple(&mut self) -> Result<Option<(String, String, String)>> {
+    pub fn read_triple(&mut self) -> anyhow::Result<Option<(String, String, String)>> {
         let mut line = String::new();
 
         // 跳过空行和注释
@@ -131,7 +147,7 @@ fn escape_string(s: &str) -> String {
 }
 
 /// 解析 N-Quads 行
-fn parse_nquads_line(line: &str) -> Option<(String, String, String)> {
+pub(crate) fn parse_nquads_line(line: &str) -> Option<(String, String, String)> {
     let line = line.trim_end_matches('.').trim();
 
     let mut parts = Vec::new();
@@ -228,14 +244,21 @@ fn unescape_string(s: &str) -> String {
     result
 }
 
-/// 导出存储为 N-Quads 格式
+/// 导出存储为 N-Quads 格式（规范顺序）
 pub fn export_nquads(storage: &dyn GraphStorage, path: &Path) -> Result<File> {
     let file = File::create(path).with_context(|| format!("Failed to create {:?}", path))?;
+    write_nquads(&storage.list_nodes()?, &storage.list_edges()?, &file)?;
+    Ok(file)
+}
 
-    let mut encoder = NQuadsEncoder::new(&file);
+/// 把给定的节点和边按规范顺序写为 N-Quads
+///
+/// 只导出部分图（如 `memexia export --tag`）时由调用方先筛选节点和边
+pub fn write_nquads<W: Write>(nodes: &[Node], edges: &[Edge], writer: W) -> Result<()> {
+    // 先收集全部三元组，排序后再写入
+    let mut encoder = CanonicalTriples::default();
 
     // 导出节点
-    let nodes = storage.list_nodes()?;
     for node in nodes {
         let node_type_iri = match node.node_type {
             super::NodeType::Concept => "memexia:Concept",
@@ -271,17 +294,15 @@ pub fn export_nquads(storage: &dyn GraphStorage, path: &Path) -> Result<File> {
     }
 
     // 导出边
-    let edges = storage.list_edges()?;
     for edge in edges {
-        // 使用 to_lowercase() 以匹配 parse_relation_type 的期望
-        let predicate = format!("memexia:{}", edge.relation.to_string().to_lowercase());
+        let predicate = format!("memexia:{}", edge.relation.predicate_name());
 
         let mut object = edge.to.clone();
         if edge.strength != 1.0 || edge.description.is_some() {
             object = format!(
                 "{}|{}:{}:{}",
                 edge.to,
-                edge.relation.to_string().to_lowercase(),
+                edge.relation.predicate_name(),
                 edge.strength,
                 edge.description.as_deref().unwrap_or("")
             );
@@ -290,7 +311,31 @@ pub fn export_nquads(storage: &dyn GraphStorage, path: &Path) -> Result<File> {
         encoder.write_triple(&edge.from, &predicate, &object)?;
     }
 
-    Ok(file)
+    let mut writer = std::io::BufWriter::new(writer);
+    for (subject, predicate, object) in encoder.into_sorted() {
+        writeln!(writer, "{} {} {} .", subject, predicate, object).context("Failed to write N-Quads triple")?;
+    }
+    writer.flush().context("Failed to write N-Quads triple")?;
+    Ok(())
+}
+
+/// 按规范顺序收集的三元组
+#[derive(Default)]
+struct CanonicalTriples {
+    triples: Vec<(String, String, String)>,
+}
+
+impl CanonicalTriples {
+    fn write_triple(&mut self, subject: &str, predicate: &str, object: &str) -> Result<()> {
+        self.triples.push(serialize_triple(subject, predicate, object));
+        Ok(())
+    }
+
+    fn into_sorted(mut self) -> Vec<(String, String, String)> {
+        self.triples.sort_unstable();
+        self.triples.dedup();
+        self.triples
+    }
 }
 
 /// 从 N-Quads 格式导入
@@ -307,7 +352,12 @@ pub fn import_nquads(storage: &dyn GraphStorage, path: &Path) -> Result<()> {
     let mut edge_triples: Vec<(String, String, String)> = Vec::new();
 
     // 解析所有三元组
-    while let Some((subject, predicate, object)) = decoder.read_triple()? {
+    while let Some((subject, predicate, object)) =
+        decoder.read_triple().map_err(|e| MemexiaError::ParseError {
+            file: path.display().to_string(),
+            reason: e.to_string(),
+        })?
+    {
         // 跳过非 memexia 命名空间的边
         if predicate.starts_with("memexia:") && parse_relation_type(predicate.strip_prefix("memexia:").unwrap()).is_some() {
             edge_triples.push((subject, predicate, object));
@@ -363,7 +413,11 @@ pub fn import_nquads(storage: &dyn GraphStorage, path: &Path) -> Result<()> {
         }
 
         // 创建并添加节点
-        let node = Node::new(subject, node_type, title);
+        let mut builder = Node::builder(subject, node_type, title).tags(tags);
+        if let Some(content) = content {
+            builder = builder.content(content);
+        }
+        let node = builder.build()?;
         storage.add_node(&node)?;
         added_nodes.insert(subject.clone());
     }
@@ -379,14 +433,13 @@ pub fn import_nquads(storage: &dyn GraphStorage, path: &Path) -> Result<()> {
             let edge_id = format!("urn:memexia:edge:{}-{}", subject, to);
 
             if !added_edges.contains(&edge_id) {
-                let mut edge = Edge::new(&edge_id, &subject, &to, relation);
-
-                if strength != 1.0 {
-                    edge.update_strength(strength);
-                }
+                let mut builder = Edge::builder(&subject, &to, relation)
+                    .id(&edge_id)
+                    .strength(strength.clamp(0.0, 1.0));
                 if !description.is_empty() {
-                    edge.update_description(description);
+                    builder = builder.description(description);
                 }
+                let edge = builder.build()?;
 
                 storage.add_edge(&edge)?;
                 added_edges.insert(edge_id);
@@ -397,25 +450,9 @@ pub fn import_nquads(storage: &dyn GraphStorage, path: &Path) -> Result<()> {
     Ok(())
 }
 
-/// 解析关系类型字符串
+/// 从谓词名（去掉 `memexia:` 前缀）解析关系类型，见 [`RelationType::from_predicate_name`]
 pub(super) fn parse_relation_type(s: &str) -> Option<RelationType> {
-    match s.to_lowercase().as_str() {
-        "contains" => Some(RelationType::Contains),
-        "partof" | "part_of" => Some(RelationType::PartOf),
-        "instanceof" | "instance_of" => Some(RelationType::InstanceOf),
-        "derivesfrom" | "derives_from" => Some(RelationType::DerivesFrom),
-        "leadsto" | "leads_to" => Some(RelationType::LeadsTo),
-        "supports" => Some(RelationType::Supports),
-        "contradicts" => Some(RelationType::Contradicts),
-        "refines" => Some(RelationType::Refines),
-        "references" => Some(RelationType::References),
-        "relatedto" | "related_to" => Some(RelationType::RelatedTo),
-        "analogousto" | "analogous_to" => Some(RelationType::AnalogousTo),
-        "precedes" => Some(RelationType::Precedes),
-        "follows" => Some(RelationType::Follows),
-        "simultaneous" => Some(RelationType::Simultaneous),
-        _ => None,
-    }
+    RelationType::from_predicate_name(s)
 }
 
 /// 解析链接对象字符串
@@ -535,4 +572,81 @@ mod tests {
         assert_eq!(edges.len(), 1);
         assert_eq!(edges[0].relation, RelationType::Contradicts);
     }
+
+    #[test]
+    fn test_nquads_export_is_canonical() {
+        use tempfile::TempDir;
+        use super::super::{Node, NodeType, Edge, RelationType};
+
+        let a = Node::new("urn:memexia:file:a.md", NodeType::Concept, "A");
+        let b = Node::new("urn:memexia:file:b.md", NodeType::Question, "B");
+        let edge = Edge::new("e1", &b.id, &a.id, RelationType::Supports);
+
+        let export = |nodes: [&Node; 2]| {
+            let temp_dir = TempDir::new().unwrap();
+            let storage = super::super::Storage::init(temp_dir.path()).unwrap();
+            for node in nodes {
+                storage.graph().add_node(node).unwrap();
+            }
+            storage.graph().add_edge(&edge).unwrap();
+            let nq_path = temp_dir.path().join("export.nq");
+            export_nquads(storage.graph(), &nq_path).unwrap();
+            std::fs::read(&nq_path).unwrap()
+        };
+        let first = export([&a, &b]);
+        assert_eq!(first, export([&b, &a]));
+
+        let text = String::from_utf8(first).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].starts_with("<urn:memexia:file:a.md>"));
+        assert!(lines.last().unwrap().starts_with("<urn:memexia:file:b.md>"));
+    }
+
+    #[test]
+    fn test_nquads_custom_relation_roundtrip() {
+        use tempfile::TempDir;
+        use super::super::{Node, NodeType, RelationType};
+        use crate::core::parser::wiki_link::parse_wiki_links;
+        use super::super::ids;
+
+        let temp_dir = TempDir::new().unwrap();
+        let storage = super::super::Storage::init(temp_dir.path()).unwrap();
+        let custom = RelationType::Custom("Refutes-Experimentally".to_string());
+
+        // wiki 链接 → 存储
+        let links = parse_wiki_links("[[b.md|Refutes-Experimentally:0.6]] [[c.md|Supports]]");
+        assert_eq!(links[0].relation, custom);
+        storage
+            .graph()
+            .add_node(&Node::new("urn:memexia:file:a.md", NodeType::Concept, "A"))
+            .unwrap();
+        for link in &links {
+            storage.graph().add_node(&Node::new(ids::file_id(&link.target), NodeType::Concept, &link.target)).unwrap();
+            storage.graph().add_edge(&link.to_edge("urn:memexia:file:a.md")).unwrap();
+        }
+        let edges = storage.graph().get_edges_by_source("urn:memexia:file:a.md").unwrap();
+        assert!(edges.iter().any(|e| e.relation == custom && e.strength == 0.6));
+
+        // N-Quads 导出 → 导入
+        let nq_path = temp_dir.path().join("export.nq");
+        export_nquads(storage.graph(), &nq_path).unwrap();
+        let content = std::fs::read_to_string(&nq_path).unwrap();
+        assert!(content.contains("memexia:rel/Refutes-Experimentally"));
+        assert!(content.contains("memexia:supports"));
+
+        let temp_dir2 = TempDir::new().unwrap();
+        let storage2 = super::super::Storage::init(temp_dir2.path()).unwrap();
+        import_nquads(storage2.graph(), &nq_path).unwrap();
+
+        let edges = storage2.graph().list_edges().unwrap();
+        assert_eq!(edges.len(), 2);
+        let imported = edges.iter().find(|e| e.relation.is_custom()).unwrap();
+        assert_eq!(imported.relation, custom);
+        assert_eq!(imported.strength, 0.6);
+
+        // 统计按关系计数
+        let stats = storage2.graph().get_stats().unwrap();
+        assert!(stats.relation_counts.contains(&(custom, 1)));
+        assert!(stats.relation_counts.contains(&(RelationType::Supports, 1)));
+    }
 }
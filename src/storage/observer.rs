@@ -0,0 +1,389 @@
+//! 存储观察者模块
+//!
+//! 把图的变更同步到外部系统（向量库、ElasticSearch 等）时，实现 [`StorageObserver`]
+//! 并通过 [`Storage::register_observer`](super::Storage::register_observer) 注册：
+//!
+//! - 每次写入成功后同步调用，参数为受影响的节点或边（写入后的状态；删除时为删除前的状态）
+//! - 事务（[`GraphStorage::transaction`](super::GraphStorage)）中的变更在事务成功后依次通知，
+//!   回滚的变更不通知
+//! - 观察者返回错误或 panic 时只记录日志，不影响已完成的写入和其他观察者
+//! - dry-run 和只读模式下写入不会执行，也不会通知；结构迁移和 `replace_contents` 的整体替换不通知
+//!
+//! 克隆的存储共享同一组观察者。内置的 [`ChangeLog`] 把变更以 NDJSON 追加到
+//! `.memexia/changes.log`，供外部程序跟踪（`tail -f`）；今后 `memexia serve` 的推送通道
+//! 可以注册观察者转发同样的变更
+
+use super::{Edge, Node};
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// 变更日志文件（相对仓库根目录）
+pub const CHANGE_LOG_PATH: &str = ".memexia/changes.log";
+
+/// 变更日志轮转的默认大小上限（字节）
+pub const DEFAULT_CHANGE_LOG_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// 存储观察者
+///
+/// 所有方法都有空的默认实现，只需实现关心的变更
+pub trait StorageObserver: Send + Sync {
+    /// 新增节点
+    fn on_node_added(&self, _node: &Node) -> Result<()> {
+        Ok(())
+    }
+
+    /// 已有节点被重新写入（重新索引、更新属性）
+    fn on_node_updated(&self, _node: &Node) -> Result<()> {
+        Ok(())
+    }
+
+    /// 删除节点
+    fn on_node_deleted(&self, _node: &Node) -> Result<()> {
+        Ok(())
+    }
+
+    /// 新增或重新写入边
+    fn on_edge_added(&self, _edge: &Edge) -> Result<()> {
+        Ok(())
+    }
+
+    /// 删除边
+    fn on_edge_deleted(&self, _edge: &Edge) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// 一次存储变更
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", content = "entity", rename_all = "snake_case")]
+pub enum StorageChange {
+    /// 新增节点
+    NodeAdded(Node),
+    /// 更新节点
+    NodeUpdated(Node),
+    /// 删除节点
+    NodeDeleted(Node),
+    /// 新增边
+    EdgeAdded(Edge),
+    /// 删除边
+    EdgeDeleted(Edge),
+}
+
+impl StorageChange {
+    /// 交给观察者对应的方法
+    fn dispatch(&self, observer: &dyn StorageObserver) -> Result<()> {
+        match self {
+            StorageChange::NodeAdded(node) => observer.on_node_added(node),
+            StorageChange::NodeUpdated(node) => observer.on_node_updated(node),
+            StorageChange::NodeDeleted(node) => observer.on_node_deleted(node),
+            StorageChange::EdgeAdded(edge) => observer.on_edge_added(edge),
+            StorageChange::EdgeDeleted(edge) => observer.on_edge_deleted(edge),
+        }
+    }
+}
+
+/// 已注册的观察者（克隆的集合共享同一组观察者）
+#[derive(Clone, Default)]
+pub struct Observers {
+    observers: Arc<RwLock<Vec<Arc<dyn StorageObserver>>>>,
+}
+
+impl std::fmt::Debug for Observers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Observers").field("count", &self.read().len()).finish()
+    }
+}
+
+impl Observers {
+    /// 注册观察者
+    pub fn register(&self, observer: Arc<dyn StorageObserver>) {
+        self.observers
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(observer);
+    }
+
+    /// 是否没有观察者（此时写入不构造变更）
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    /// 依次通知所有观察者；错误和 panic 只记录日志
+    pub fn notify(&self, change: &StorageChange) {
+        for observer in self.read().iter() {
+            match panic::catch_unwind(AssertUnwindSafe(|| change.dispatch(observer.as_ref()))) {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => tracing::warn!("Storage observer failed: {}", err),
+                Err(_) => tracing::error!("Storage observer panicked; the write was kept"),
+            }
+        }
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Vec<Arc<dyn StorageObserver>>> {
+        // 观察者在锁外不会 panic（通知时已捕获），万一中毒也继续使用内部数据
+        self.observers.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// 变更日志中的一条记录（一行 JSON）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeRecord {
+    /// 序号，从 1 开始递增，轮转后继续
+    pub seq: u64,
+    /// 记录时间
+    pub time: DateTime<Utc>,
+    /// 变更（`op` 和 `entity` 字段）
+    #[serde(flatten)]
+    pub change: StorageChange,
+}
+
+/// 把变更以 NDJSON 追加到日志文件的内置观察者
+///
+/// 写入前文件将超过大小上限时，把当前日志改名为 `<日志>.1`（覆盖上一份）再写入新文件；
+/// 序号保存在记录中，重新打开日志时从最后一条记录继续
+#[derive(Debug)]
+pub struct ChangeLog {
+    path: PathBuf,
+    max_bytes: u64,
+    /// 下一条记录的序号
+    next_seq: Mutex<u64>,
+}
+
+impl ChangeLog {
+    /// 打开日志，从已有记录中恢复序号
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 日志文件路径
+    /// * `max_bytes` - 轮转的大小上限
+    pub fn open(path: &Path, max_bytes: u64) -> Result<Self> {
+        let last = match last_seq(path)? {
+            Some(seq) => Some(seq),
+            None => last_seq(&Self::rotated_path(path))?,
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            max_bytes,
+            next_seq: Mutex::new(last.unwrap_or(0) + 1),
+        })
+    }
+
+    /// 轮转后的上一份日志
+    pub fn rotated_path(path: &Path) -> PathBuf {
+        let mut name = path.as_os_str().to_os_string();
+        name.push(".1");
+        PathBuf::from(name)
+    }
+
+    /// 追加一条记录
+    pub fn append(&self, change: &StorageChange) -> Result<()> {
+        let mut next_seq = self.next_seq.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let record = ChangeRecord {
+            seq: *next_seq,
+            time: Utc::now(),
+            change: change.clone(),
+        };
+        let line = format!("{}\n", serde_json::to_string(&record)?);
+
+        let size = fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if size > 0 && size + line.len() as u64 > self.max_bytes {
+            fs::rename(&self.path, Self::rotated_path(&self.path))?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        file.write_all(line.as_bytes())?;
+        *next_seq += 1;
+        Ok(())
+    }
+}
+
+impl StorageObserver for ChangeLog {
+    fn on_node_added(&self, node: &Node) -> Result<()> {
+        self.append(&StorageChange::NodeAdded(node.clone()))
+    }
+
+    fn on_node_updated(&self, node: &Node) -> Result<()> {
+        self.append(&StorageChange::NodeUpdated(node.clone()))
+    }
+
+    fn on_node_deleted(&self, node: &Node) -> Result<()> {
+        self.append(&StorageChange::NodeDeleted(node.clone()))
+    }
+
+    fn on_edge_added(&self, edge: &Edge) -> Result<()> {
+        self.append(&StorageChange::EdgeAdded(edge.clone()))
+    }
+
+    fn on_edge_deleted(&self, edge: &Edge) -> Result<()> {
+        self.append(&StorageChange::EdgeDeleted(edge.clone()))
+    }
+}
+
+/// 日志中最后一条可解析记录的序号；文件不存在时返回 None
+fn last_seq(path: &Path) -> Result<Option<u64>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let mut last = None;
+    for line in BufReader::new(file).lines() {
+        if let Ok(record) = serde_json::from_str::<serde_json::Value>(&line?) {
+            last = record["seq"].as_u64().or(last);
+        }
+    }
+    Ok(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Indexer;
+    use crate::storage::{NodeType, Storage};
+    use tempfile::TempDir;
+
+    fn read_records(path: &Path) -> Vec<ChangeRecord> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    /// 收集所有变更的观察者
+    #[derive(Default)]
+    struct Collect(Mutex<Vec<StorageChange>>);
+
+    impl Collect {
+        /// 取出已收集的变更，节点和边只保留 ID
+        fn take(&self) -> Vec<(String, String)> {
+            std::mem::take(&mut *self.0.lock().unwrap())
+                .into_iter()
+                .map(|change| {
+                    let value = serde_json::to_value(&change).unwrap();
+                    (value["op"].as_str().unwrap().to_string(), value["entity"]["id"].as_str().unwrap().to_string())
+                })
+                .collect()
+        }
+    }
+
+    impl StorageObserver for Collect {
+        fn on_node_added(&self, node: &Node) -> Result<()> {
+            self.0.lock().unwrap().push(StorageChange::NodeAdded(node.clone()));
+            Ok(())
+        }
+        fn on_node_updated(&self, node: &Node) -> Result<()> {
+            self.0.lock().unwrap().push(StorageChange::NodeUpdated(node.clone()));
+            Ok(())
+        }
+        fn on_node_deleted(&self, node: &Node) -> Result<()> {
+            self.0.lock().unwrap().push(StorageChange::NodeDeleted(node.clone()));
+            Ok(())
+        }
+        fn on_edge_added(&self, edge: &Edge) -> Result<()> {
+            self.0.lock().unwrap().push(StorageChange::EdgeAdded(edge.clone()));
+            Ok(())
+        }
+        fn on_edge_deleted(&self, edge: &Edge) -> Result<()> {
+            self.0.lock().unwrap().push(StorageChange::EdgeDeleted(edge.clone()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_index_file_event_stream() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let collect = Arc::new(Collect::default());
+        storage.register_observer(collect.clone());
+
+        let path = temp.path().join("a.md");
+        fs::write(&path, "# A\n\n[[b.md|Supports]]\n").unwrap();
+        Indexer::new(storage.clone()).index_file(&path).unwrap();
+        let edge_id = |to: &str| format!("urn:memexia:edge:urn:memexia:file:a.md-urn:memexia:file:{}", to);
+        assert_eq!(
+            collect.take(),
+            vec![
+                ("node_added".to_string(), "urn:memexia:file:a.md".to_string()),
+                ("node_added".to_string(), "urn:memexia:file:b.md".to_string()),
+                ("edge_added".to_string(), edge_id("b.md")),
+            ]
+        );
+
+        // 重新索引：已有节点报告为更新
+        fs::write(&path, "# A\n\n[[c.md]]\n").unwrap();
+        Indexer::new(storage.clone()).index_file(&path).unwrap();
+        assert_eq!(
+            collect.take(),
+            vec![
+                ("node_updated".to_string(), "urn:memexia:file:a.md".to_string()),
+                ("node_added".to_string(), "urn:memexia:file:c.md".to_string()),
+                ("edge_added".to_string(), edge_id("c.md")),
+            ]
+        );
+
+        // dry-run 中的写入不执行，也不通知
+        let mut dry = storage.clone();
+        dry.enable_dry_run();
+        dry.graph().delete_node("urn:memexia:file:a.md").unwrap();
+        assert!(collect.take().is_empty());
+    }
+
+    #[test]
+    fn test_change_log_rotation() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("changes.log");
+        let node = Node::new("urn:memexia:file:a.md", NodeType::Concept, "A");
+        let line_len = {
+            let log = ChangeLog::open(&temp.path().join("probe.log"), u64::MAX).unwrap();
+            log.on_node_added(&node).unwrap();
+            fs::metadata(temp.path().join("probe.log")).unwrap().len()
+        };
+
+        // 上限容纳两条记录：第三条写入前轮转
+        let log = ChangeLog::open(&path, line_len * 2 + 10).unwrap();
+        for _ in 0..3 {
+            log.on_node_updated(&node).unwrap();
+        }
+        let rotated = read_records(&ChangeLog::rotated_path(&path));
+        let current = read_records(&path);
+        assert_eq!(rotated.iter().map(|r| r.seq).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(current.iter().map(|r| r.seq).collect::<Vec<_>>(), [3]);
+        assert_eq!(current[0].change, StorageChange::NodeUpdated(node.clone()));
+
+        // 重新打开后序号继续
+        let log = ChangeLog::open(&path, line_len * 2 + 10).unwrap();
+        log.on_node_deleted(&node).unwrap();
+        assert_eq!(read_records(&path).last().unwrap().seq, 4);
+    }
+
+    #[test]
+    fn test_failing_observer_is_isolated() {
+        struct Failing;
+        impl StorageObserver for Failing {
+            fn on_node_added(&self, _node: &Node) -> Result<()> {
+                panic!("observer bug")
+            }
+            fn on_node_deleted(&self, _node: &Node) -> Result<()> {
+                Err(crate::error::MemexiaError::Validation("unreachable sink".to_string()))
+            }
+        }
+
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("changes.log");
+        let observers = Observers::default();
+        observers.register(Arc::new(Failing));
+        observers.register(Arc::new(ChangeLog::open(&path, u64::MAX).unwrap()));
+
+        let node = Node::new("urn:memexia:file:a.md", NodeType::Concept, "A");
+        observers.notify(&StorageChange::NodeAdded(node.clone()));
+        observers.notify(&StorageChange::NodeDeleted(node));
+        // 前一个观察者失败不影响后面的观察者
+        assert_eq!(read_records(&path).len(), 2);
+    }
+}
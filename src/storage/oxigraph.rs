@@ -2,22 +2,56 @@
 //!
 //! 基于 Oxigraph 库实现 `GraphStorage` trait
 //! 使用 Oxigraph 0.5.3 的 Store API
-
-use super::{Edge, EdgeDirection, GraphStats, Node, NodeType};
+//!
+//! 事务（[`GraphStorage::run_transaction`]）中的写入直接作用于存储并记入日志，
+//! 事务内的读取和 SPARQL 查询因此能看到已完成的写入；出错时在一个 Oxigraph
+//! 事务中按相反顺序撤销日志中的变更
+//!
+//! 标题索引：写入标题时同时写入小写的 `memexia:titleKey`，
+//! [`GraphStorage::find_by_title`] 借助 Oxigraph 按谓语和宾语的索引直接查找，
+//! 索引与标题一同写入、删除和回滚
+
+use super::{
+    Edge, EdgeAnnotation, EdgeChange, EdgeDirection, EdgeSource, GraphStats, Node, NodeType, RelationType, ARCHIVED_AT_KEY, CONTENT_HASH_KEY, DELETED_AT_KEY, ENCRYPTED_KEY, EXCERPT_OF_KEY, EXCLUDED_KEY, LANGUAGE_KEY, LAST_REVIEWED_KEY, SUMMARY_KEY, SUMMARY_SOURCE_KEY,
+    PREVIOUS_TITLE_KEY, WORD_COUNT_KEY,
+};
 use super::nquads::parse_relation_type;
+use super::observer::{Observers, StorageChange};
 use crate::storage::graph::GraphStorage;
 use crate::storage::graph::QueryResult;
 use crate::storage::EdgeFilter;
-use anyhow::{bail, Context, Result};
+use crate::error::{MemexiaError, Result};
+use oxigraph::io::RdfFormat;
 use oxigraph::model::{GraphName, Literal, NamedNode, Quad, Term, NamedOrBlankNode};
 use oxigraph::store::Store;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// 事务中的一次四元组变更
+#[derive(Debug, Clone)]
+enum QuadChange {
+    /// 插入了原本不存在的四元组
+    Inserted(Quad),
+    /// 移除了原本存在的四元组
+    Removed(Quad),
+}
+
+/// 节点 ID → 某个字面量属性的全部取值
+type LiteralValues = BTreeMap<String, BTreeSet<String>>;
 
 /// Oxigraph 存储实现
 #[derive(Clone)]
 pub struct OxigraphStorage {
     /// Oxigraph 存储实例
     store: Store,
+    /// 事务日志（仅事务句柄持有），记录实际发生的四元组变更用于回滚
+    journal: Option<Arc<Mutex<Vec<QuadChange>>>>,
+    /// 存储观察者（克隆的存储共享）
+    observers: Observers,
+    /// 事务中待通知观察者的变更（仅事务句柄持有），事务成功后发出
+    pending: Option<Arc<Mutex<Vec<StorageChange>>>>,
 }
 
 impl std::fmt::Debug for OxigraphStorage {
@@ -27,12 +61,46 @@ impl std::fmt::Debug for OxigraphStorage {
 }
 
 impl OxigraphStorage {
+    fn with_store(store: Store) -> Self {
+        Self {
+            store,
+            journal: None,
+            observers: Observers::default(),
+            pending: None,
+        }
+    }
+
+    /// 存储观察者，见 [`super::observer`]
+    pub fn observers(&self) -> &Observers {
+        &self.observers
+    }
+
+    /// 改用已有的观察者集合（重建存储后保留已注册的观察者）
+    pub fn with_observers(mut self, observers: Observers) -> Self {
+        self.observers = observers;
+        self
+    }
+
+    /// 通知观察者一次变更；事务中先暂存，事务成功后再通知
+    fn notify(&self, change: StorageChange) {
+        match &self.pending {
+            Some(pending) => pending.lock().unwrap_or_else(|e| e.into_inner()).push(change),
+            None => self.observers.notify(&change),
+        }
+    }
+
     /// 打开已有存储
     pub fn open(path: &Path) -> Result<Self> {
-        let store = Store::open(path)
-            .with_context(|| format!("Failed to open Oxigraph store at {:?}", path))?;
+        let store = Store::open(path)?;
+
+        Ok(Self::with_store(store))
+    }
+
+    /// 以只读模式打开已有存储
+    pub fn open_read_only(path: &Path) -> Result<Self> {
+        let store = Store::open_read_only(path)?;
 
-        Ok(Self { store })
+        Ok(Self::with_store(store))
     }
 
     /// 创建新存储
@@ -43,20 +111,382 @@ impl OxigraphStorage {
             }
         }
 
-        let store = Store::open(path)
-            .with_context(|| format!("Failed to create Oxigraph store at {:?}", path))?;
+        let store = Store::open(path)?;
 
-        Ok(Self { store })
+        Ok(Self::with_store(store))
+    }
+
+    /// 创建内存存储（不写入磁盘）
+    pub fn in_memory() -> Result<Self> {
+        let store = Store::new()?;
+
+        Ok(Self::with_store(store))
+    }
+
+    /// 从 N-Quads 文本创建内存存储
+    ///
+    /// 用于临时加载历史快照，不影响仓库中的存储
+    pub fn from_nquads(nquads: &str) -> Result<Self> {
+        let storage = Self::in_memory()?;
+        storage.store.load_from_slice(RdfFormat::NQuads, nquads)?;
+
+        Ok(storage)
+    }
+
+    /// 用 N-Quads 文本替换存储中的全部内容
+    ///
+    /// 用于提交结构迁移的结果
+    pub fn replace_contents(&self, nquads: &str) -> Result<()> {
+        self.store.clear()?;
+        self.store.load_from_slice(RdfFormat::NQuads, nquads)?;
+
+        Ok(())
     }
 
     /// 清理 IRI 字符串，去掉尖括号
     fn clean_iri(iri: &str) -> String {
         iri.trim_start_matches('<').trim_end_matches('>').to_string()
     }
+
+    /// 插入四元组（事务中记录实际发生的插入）
+    fn insert_quad(&self, quad: &Quad) -> Result<()> {
+        let Some(journal) = &self.journal else {
+            self.store.insert(quad)?;
+            return Ok(());
+        };
+        if !self.store.contains(quad)? {
+            self.store.insert(quad)?;
+            journal.lock().unwrap_or_else(|e| e.into_inner()).push(QuadChange::Inserted(quad.clone()));
+        }
+        Ok(())
+    }
+
+    /// 移除四元组（事务中记录实际发生的移除）
+    fn remove_quad(&self, quad: &Quad) -> Result<()> {
+        let Some(journal) = &self.journal else {
+            self.store.remove(quad)?;
+            return Ok(());
+        };
+        if self.store.contains(quad)? {
+            self.store.remove(quad)?;
+            journal.lock().unwrap_or_else(|e| e.into_inner()).push(QuadChange::Removed(quad.clone()));
+        }
+        Ok(())
+    }
+
+    /// 在一个 Oxigraph 事务中按相反顺序撤销日志中的变更
+    fn roll_back(&self, changes: &[QuadChange]) -> Result<()> {
+        let mut transaction = self.store.start_transaction()?;
+        for change in changes.iter().rev() {
+            match change {
+                QuadChange::Inserted(quad) => transaction.remove(quad),
+                QuadChange::Removed(quad) => transaction.insert(quad),
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// 设置单值属性（先移除旧值，重新索引时保持唯一）
+    fn replace_property(&self, subject: &NamedOrBlankNode, predicate: &str, object: Term) -> Result<()> {
+        let predicate = NamedNode::new(predicate)?;
+        let old_quads: Vec<Quad> = self
+            .store
+            .quads_for_pattern(Some(subject.into()), Some((&predicate).into()), None, None)
+            .collect::<std::result::Result<_, _>>()?;
+        for quad in &old_quads {
+            self.remove_quad(quad)?;
+        }
+        let quad = Quad::new(subject.clone(), predicate, object, GraphName::DefaultGraph);
+        self.insert_quad(&quad)?;
+        Ok(())
+    }
+
+    /// 移除单值属性
+    fn remove_property(&self, subject: &NamedOrBlankNode, predicate: &str) -> Result<()> {
+        let predicate = NamedNode::new(predicate)?;
+        let old_quads: Vec<Quad> = self
+            .store
+            .quads_for_pattern(Some(subject.into()), Some((&predicate).into()), None, None)
+            .collect::<std::result::Result<_, _>>()?;
+        for quad in &old_quads {
+            self.remove_quad(quad)?;
+        }
+        Ok(())
+    }
+
+    /// 标题索引的键：不区分大小写的查找按 Unicode 小写比较
+    fn title_key(title: &str) -> String {
+        title.to_lowercase()
+    }
+
+    /// 各节点某个字面量属性的全部取值
+    fn literal_values(&self, predicate: &str) -> Result<LiteralValues> {
+        let predicate = NamedNode::new(predicate)?;
+        let mut values = LiteralValues::new();
+        for quad in self.store.quads_for_pattern(None, Some((&predicate).into()), None, None) {
+            let quad = quad?;
+            if let Term::Literal(lit) = &quad.object {
+                values
+                    .entry(Self::clean_iri(&quad.subject.to_string()))
+                    .or_default()
+                    .insert(lit.value().to_string());
+            }
+        }
+        Ok(values)
+    }
+
+    /// 标题索引与标题不一致的节点 ID，以及各节点的标题
+    fn title_index_state(&self) -> Result<(Vec<String>, LiteralValues)> {
+        let titles = self.literal_values("memexia:title")?;
+        let keys = self.literal_values("memexia:titleKey")?;
+        let ids: BTreeSet<&String> = titles.keys().chain(keys.keys()).collect();
+        let mismatches = ids
+            .into_iter()
+            .filter(|id| {
+                let expected: BTreeSet<String> = titles
+                    .get(*id)
+                    .map(|titles| titles.iter().map(|title| Self::title_key(title)).collect())
+                    .unwrap_or_default();
+                keys.get(*id).cloned().unwrap_or_default() != expected
+            })
+            .cloned()
+            .collect();
+        Ok((mismatches, titles))
+    }
+
+    /// 已软删除的节点 ID 集合
+    fn deleted_ids(&self) -> Result<std::collections::HashSet<String>> {
+        let predicate = NamedNode::new("memexia:deletedAt")?;
+        let mut ids = std::collections::HashSet::new();
+        for quad in self.store.quads_for_pattern(None, Some((&predicate).into()), None, None) {
+            ids.insert(Self::clean_iri(&quad?.subject.to_string()));
+        }
+        Ok(ids)
+    }
+
+    /// 过滤掉墓碑状态的边（任一端点已软删除）
+    fn retain_live_edges(&self, edges: &mut Vec<Edge>) -> Result<()> {
+        let deleted = self.deleted_ids()?;
+        if !deleted.is_empty() {
+            edges.retain(|e| !deleted.contains(&e.from) && !deleted.contains(&e.to));
+        }
+        Ok(())
+    }
+
+    /// 遍历节点，只在内存中保留已访问的节点 ID
+    fn visit_nodes(&self, include_deleted: bool, visit: &mut dyn FnMut(Node) -> Result<()>) -> Result<()> {
+        let mut seen = std::collections::HashSet::new();
+
+        for quad in self.store.iter().flatten() {
+            // 使用 is_named_node() 方法检查是否是 NamedNode
+            if !quad.subject.is_named_node() {
+                continue;
+            }
+            // subject.to_string() 返回带尖括号的IRI，需要清理
+            let subj_str = Self::clean_iri(&quad.subject.to_string());
+            // 边元数据以边 ID 为主语，不是节点
+            if subj_str.starts_with("urn:memexia:")
+                && !subj_str.starts_with("urn:memexia:edge:")
+                && seen.insert(subj_str.clone())
+            {
+                if let Ok(Some(node)) = self.get_node(&subj_str) {
+                    if include_deleted || !node.is_deleted() {
+                        visit(node)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 遍历边，只在内存中保留已访问的边 ID 和已软删除的节点 ID
+    fn visit_edges(&self, include_deleted: bool, visit: &mut dyn FnMut(Edge) -> Result<()>) -> Result<()> {
+        let deleted = if include_deleted { Default::default() } else { self.deleted_ids()? };
+        let mut seen = std::collections::HashSet::new();
+
+        for quad in self.store.iter().flatten() {
+            let pred_str = Self::clean_iri(&quad.predicate.to_string());
+            if !pred_str.starts_with("memexia:") || !quad.subject.is_named_node() || !quad.object.is_named_node() {
+                continue;
+            }
+            // 去掉 "memexia:" 前缀，提取关系类型
+            let relation_str = pred_str.strip_prefix("memexia:").unwrap_or(&pred_str);
+            // subject 和 object 都需要清理
+            let s_str = Self::clean_iri(&quad.subject.to_string());
+            let o_str = Self::clean_iri(&quad.object.to_string());
+            // 任一端点已软删除的边处于墓碑状态
+            if deleted.contains(&s_str) || deleted.contains(&o_str) {
+                continue;
+            }
+            if seen.insert(Edge::make_id(&s_str, &o_str)) {
+                if let Some(relation) = parse_relation_type(relation_str) {
+                    visit(self.load_edge(&s_str, &o_str, relation))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 边元数据的主语：以边 ID 作为 IRI
+    fn edge_metadata_subject(from: &str, to: &str) -> Result<NamedOrBlankNode> {
+        Ok(NamedOrBlankNode::from(NamedNode::new(Edge::make_id(from, to))?))
+    }
+
+    /// 构造边并读取已持久化的元数据（强度、置信度、来源、描述、首次提交、注释和修改记录）
+    fn load_edge(&self, from: &str, to: &str, relation: RelationType) -> Edge {
+        let mut edge = Edge::new(Edge::make_id(from, to), from, to, relation);
+        let Ok(subject) = Self::edge_metadata_subject(from, to) else {
+            return edge;
+        };
+
+        for quad in self.store.quads_for_pattern(Some((&subject).into()), None, None, None).flatten() {
+            let Term::Literal(lit) = &quad.object else {
+                continue;
+            };
+            match Self::clean_iri(&quad.predicate.to_string()).as_str() {
+                "memexia:strength" => {
+                    if let Ok(strength) = lit.value().parse() {
+                        edge.strength = strength;
+                    }
+                }
+                "memexia:confidence" => {
+                    if let Ok(confidence) = lit.value().parse() {
+                        edge.confidence = confidence;
+                    }
+                }
+                "memexia:source" => {
+                    if let Some(source) = EdgeSource::parse(lit.value()) {
+                        edge.source = source;
+                    }
+                }
+                "memexia:description" => edge.description = Some(lit.value().to_string()),
+                "memexia:createdAt" => {
+                    if let Ok(created_at) = DateTime::parse_from_rfc3339(lit.value()) {
+                        edge.created_at = created_at.with_timezone(&Utc);
+                    }
+                }
+                "memexia:effectiveStrength" => edge.effective_strength = lit.value().parse().ok(),
+                "memexia:introducedInCommit" => edge.introduced_in_commit = Some(lit.value().to_string()),
+                "memexia:annotation" => {
+                    if let Ok(annotation) = serde_json::from_str::<EdgeAnnotation>(lit.value()) {
+                        edge.annotations.push(annotation);
+                    }
+                }
+                "memexia:updatedAt" => {
+                    if let Ok(updated_at) = DateTime::parse_from_rfc3339(lit.value()) {
+                        edge.updated_at = Some(updated_at.with_timezone(&Utc));
+                    }
+                }
+                "memexia:edgeChange" => {
+                    if let Ok(change) = serde_json::from_str::<EdgeChange>(lit.value()) {
+                        edge.history.push(change);
+                    }
+                }
+                _ => {}
+            }
+        }
+        edge.annotations.sort_by_key(|a| a.created_at);
+        edge.history.sort_by_key(|c| c.changed_at);
+        edge
+    }
+
+    /// 写入边元数据（只保存非默认值，先清除旧值）
+    ///
+    /// 创建时间总是保存；边已存在时保留较早的创建时间，重新索引不会让旧关系变“新”。
+    /// 注释和修改记录与已保存的合并，重新索引生成的边不带它们也不会清除；
+    /// 已记录的首次提交不会被覆盖
+    fn write_edge_metadata(&self, edge: &Edge) -> Result<()> {
+        let existing = self.load_edge(&edge.from, &edge.to, edge.relation.clone());
+        let mut annotations = existing.annotations.clone();
+        for annotation in &edge.annotations {
+            if !annotations.contains(annotation) {
+                annotations.push(annotation.clone());
+            }
+        }
+        annotations.sort_by_key(|a| a.created_at);
+        let mut history = existing.history.clone();
+        for change in &edge.history {
+            if !history.contains(change) {
+                history.push(change.clone());
+            }
+        }
+        history.sort_by_key(|c| c.changed_at);
+        let updated_at = existing.updated_at.max(edge.updated_at);
+        let created_at = if self.has_edge_metadata(&edge.from, &edge.to, "memexia:createdAt")? {
+            existing.created_at.min(edge.created_at)
+        } else {
+            edge.created_at
+        };
+        let introduced_in_commit = existing.introduced_in_commit.or_else(|| edge.introduced_in_commit.clone());
+        self.remove_edge_metadata(&edge.from, &edge.to)?;
+
+        let subject = Self::edge_metadata_subject(&edge.from, &edge.to)?;
+        let mut properties = Vec::new();
+        if edge.strength != 1.0 {
+            properties.push(("memexia:strength", Literal::from(edge.strength)));
+        }
+        if edge.confidence != 1.0 {
+            properties.push(("memexia:confidence", Literal::from(edge.confidence)));
+        }
+        if edge.source != EdgeSource::Explicit {
+            properties.push(("memexia:source", Literal::new_simple_literal(edge.source.to_string())));
+        }
+        if let Some(description) = &edge.description {
+            properties.push(("memexia:description", Literal::new_simple_literal(description)));
+        }
+        if let Some(effective) = edge.effective_strength {
+            properties.push(("memexia:effectiveStrength", Literal::from(effective)));
+        }
+        properties.push(("memexia:createdAt", Literal::new_simple_literal(created_at.to_rfc3339())));
+        if let Some(commit) = &introduced_in_commit {
+            properties.push(("memexia:introducedInCommit", Literal::new_simple_literal(commit)));
+        }
+        for annotation in &annotations {
+            properties.push(("memexia:annotation", Literal::new_simple_literal(serde_json::to_string(annotation)?)));
+        }
+        if let Some(updated_at) = updated_at {
+            properties.push(("memexia:updatedAt", Literal::new_simple_literal(updated_at.to_rfc3339())));
+        }
+        for change in &history {
+            properties.push(("memexia:edgeChange", Literal::new_simple_literal(serde_json::to_string(change)?)));
+        }
+
+        for (predicate, object) in properties {
+            let quad = Quad::new(subject.clone(), NamedNode::new(predicate)?, object, GraphName::DefaultGraph);
+            self.insert_quad(&quad)?;
+        }
+        Ok(())
+    }
+
+    /// 边是否已保存指定元数据
+    fn has_edge_metadata(&self, from: &str, to: &str, predicate: &str) -> Result<bool> {
+        let subject = Self::edge_metadata_subject(from, to)?;
+        let predicate = NamedNode::new(predicate)?;
+        Ok(self
+            .store
+            .quads_for_pattern(Some((&subject).into()), Some((&predicate).into()), None, None)
+            .next()
+            .is_some())
+    }
+
+    /// 移除边元数据
+    fn remove_edge_metadata(&self, from: &str, to: &str) -> Result<()> {
+        let subject = Self::edge_metadata_subject(from, to)?;
+        let quads: Vec<Quad> = self
+            .store
+            .quads_for_pattern(Some((&subject).into()), None, None, None)
+            .collect::<std::result::Result<_, _>>()?;
+        for quad in &quads {
+            self.remove_quad(quad)?;
+        }
+        Ok(())
+    }
 }
 
-impl GraphStorage for OxigraphStorage {
-    fn add_node(&self, node: &Node) -> Result<()> {
+impl OxigraphStorage {
+    /// 写入节点的全部属性（不通知观察者）
+    fn write_node(&self, node: &Node) -> Result<()> {
         let subject = NamedOrBlankNode::from(NamedNode::new(&node.id)?);
         let graph_name = GraphName::DefaultGraph;
 
@@ -73,22 +503,18 @@ impl GraphStorage for OxigraphStorage {
         };
 
         let quad = Quad::new(subject.clone(), type_pred, type_obj, graph_name.clone());
-        self.store.insert(&quad)?;
+        self.insert_quad(&quad)?;
 
-        // 添加标题
+        // 添加标题和标题索引（重新索引时替换旧标题）
         if !node.title.is_empty() {
-            let title_pred = NamedNode::new("memexia:title")?;
-            let title_obj = Term::from(Literal::new_simple_literal(&node.title));
-            let quad = Quad::new(subject.clone(), title_pred, title_obj, graph_name.clone());
-            self.store.insert(&quad)?;
+            self.replace_property(&subject, "memexia:title", Term::from(Literal::new_simple_literal(&node.title)))?;
+            let key = Self::title_key(&node.title);
+            self.replace_property(&subject, "memexia:titleKey", Term::from(Literal::new_simple_literal(key)))?;
         }
 
-        // 添加内容
+        // 添加内容（重新索引时替换旧内容，超长笔记缩短为摘录后旧的完整正文不会残留）
         if let Some(content) = &node.content {
-            let content_pred = NamedNode::new("memexia:content")?;
-            let content_obj = Term::from(Literal::new_simple_literal(content));
-            let quad = Quad::new(subject.clone(), content_pred, content_obj, graph_name.clone());
-            self.store.insert(&quad)?;
+            self.replace_property(&subject, "memexia:content", Term::from(Literal::new_simple_literal(content)))?;
         }
 
         // 添加标签
@@ -96,20 +522,112 @@ impl GraphStorage for OxigraphStorage {
             let tag_pred = NamedNode::new("memexia:tag")?;
             let tag_obj = Term::from(Literal::new_simple_literal(tag));
             let quad = Quad::new(subject.clone(), tag_pred, tag_obj, graph_name.clone());
-            self.store.insert(&quad)?;
+            self.insert_quad(&quad)?;
+        }
+
+        // 添加字数（替换旧值，重新索引时保持唯一）
+        if let Some(word_count) = node.word_count() {
+            self.replace_property(&subject, "memexia:wordCount", Term::from(Literal::from(word_count)))?;
+        }
+
+        // 添加内容哈希（替换旧值）
+        if let Some(hash) = node.content_hash() {
+            self.replace_property(&subject, "memexia:contentHash", Term::from(Literal::new_simple_literal(hash)))?;
+        }
+
+        // 正文语言：重新索引时替换，检测不到语言时清除
+        match node.language() {
+            Some(language) => {
+                self.replace_property(&subject, "memexia:language", Term::from(Literal::new_simple_literal(language)))?
+            }
+            None => self.remove_property(&subject, "memexia:language")?,
+        }
+
+        // 正文摘录标记：笔记缩短到上限以内后重新索引时清除
+        match node.excerpt_of() {
+            Some(path) => self.replace_property(&subject, "memexia:excerptOf", Term::from(Literal::new_simple_literal(path)))?,
+            None => self.remove_property(&subject, "memexia:excerptOf")?,
+        }
+
+        // 摘要及其来源：作者删掉摘要或正文不再有可提取的段落时清除
+        match node.summary() {
+            Some(summary) => {
+                self.replace_property(&subject, "memexia:summary", Term::from(Literal::new_simple_literal(summary)))?
+            }
+            None => self.remove_property(&subject, "memexia:summary")?,
+        }
+        if node.is_auto_summary() {
+            self.replace_property(&subject, "memexia:summarySource", Term::from(Literal::new_simple_literal("auto")))?;
+        } else {
+            self.remove_property(&subject, "memexia:summarySource")?;
+        }
+
+        // 添加加密标记
+        if node.is_encrypted() {
+            self.replace_property(&subject, "memexia:encrypted", Term::from(Literal::from(true)))?;
+        }
+
+        // 排除标记：重新写入普通节点时清除（笔记去掉了 noindex）
+        if node.is_excluded() {
+            self.replace_property(&subject, "memexia:excluded", Term::from(Literal::from(true)))?;
+        } else {
+            self.remove_property(&subject, "memexia:excluded")?;
+        }
+
+        // 软删除标记：重新写入不带标记的节点（如文件重新出现）时清除
+        match node.deleted_at() {
+            Some(deleted) => {
+                let deleted_obj = Term::from(Literal::new_simple_literal(deleted.to_rfc3339()));
+                self.replace_property(&subject, "memexia:deletedAt", deleted_obj)?;
+            }
+            None => self.remove_property(&subject, "memexia:deletedAt")?,
+        }
+
+        // 添加回顾时间（重新索引的节点不带该值，保留已有记录）
+        if let Some(reviewed) = node.last_reviewed() {
+            let reviewed_obj = Term::from(Literal::new_simple_literal(reviewed.to_rfc3339()));
+            self.replace_property(&subject, "memexia:lastReviewed", reviewed_obj)?;
+        }
+
+        // 添加归档时间（重新索引的节点不带该值，保留已有记录）
+        if let Some(archived) = node.archived_at() {
+            let archived_obj = Term::from(Literal::new_simple_literal(archived.to_rfc3339()));
+            self.replace_property(&subject, "memexia:archivedAt", archived_obj)?;
+        }
+
+        // 添加上一个标题（重新索引的节点不带该值，保留已有记录）
+        if let Some(previous) = node.previous_title() {
+            self.replace_property(&subject, "memexia:previousTitle", Term::from(Literal::new_simple_literal(previous)))?;
         }
 
         // 添加时间戳
         let created_pred = NamedNode::new("memexia:createdAt")?;
         let created_obj = Term::from(Literal::new_simple_literal(node.created_at.to_rfc3339()));
         let quad = Quad::new(subject.clone(), created_pred, created_obj, graph_name.clone());
-        self.store.insert(&quad)?;
+        self.insert_quad(&quad)?;
 
         let updated_pred = NamedNode::new("memexia:updatedAt")?;
         let updated_obj = Term::from(Literal::new_simple_literal(node.updated_at.to_rfc3339()));
         let quad = Quad::new(subject, updated_pred, updated_obj, graph_name);
-        self.store.insert(&quad)?;
+        self.insert_quad(&quad)?;
+
+        Ok(())
+    }
+
+}
 
+impl GraphStorage for OxigraphStorage {
+    fn add_node(&self, node: &Node) -> Result<()> {
+        if self.observers.is_empty() {
+            return self.write_node(node);
+        }
+        let existed = self.node_exists(&node.id)?;
+        self.write_node(node)?;
+        self.notify(if existed {
+            StorageChange::NodeUpdated(node.clone())
+        } else {
+            StorageChange::NodeAdded(node.clone())
+        });
         Ok(())
     }
 
@@ -137,7 +655,7 @@ impl GraphStorage for OxigraphStorage {
 
             if pred_str == "http://www.w3.org/1999/02/22-rdf-syntax-ns#type" || pred_str == "rdf:type" {
                 if let Term::NamedNode(obj_node) = &quad.object {
-                    let type_str = obj_node.to_string();
+                    let type_str = Self::clean_iri(&obj_node.to_string());
                     node.node_type = match type_str.as_str() {
                         "memexia:Concept" => NodeType::Concept,
                         "memexia:Question" => NodeType::Question,
@@ -169,6 +687,56 @@ impl GraphStorage for OxigraphStorage {
                         node.created_at = dt.with_timezone(&chrono::Utc);
                     }
                 }
+            } else if pred_str == "memexia:wordCount" {
+                if let Term::Literal(lit) = &quad.object {
+                    if let Ok(count) = lit.value().parse::<u64>() {
+                        node.metadata.insert(WORD_COUNT_KEY.to_string(), count.into());
+                    }
+                }
+            } else if pred_str == "memexia:excluded" {
+                if let Term::Literal(lit) = &quad.object {
+                    node.metadata.insert(EXCLUDED_KEY.to_string(), (lit.value() == "true").into());
+                }
+            } else if pred_str == "memexia:encrypted" {
+                if let Term::Literal(lit) = &quad.object {
+                    node.metadata.insert(ENCRYPTED_KEY.to_string(), (lit.value() == "true").into());
+                }
+            } else if pred_str == "memexia:language" {
+                if let Term::Literal(lit) = &quad.object {
+                    node.metadata.insert(LANGUAGE_KEY.to_string(), lit.value().into());
+                }
+            } else if pred_str == "memexia:summary" {
+                if let Term::Literal(lit) = &quad.object {
+                    node.metadata.insert(SUMMARY_KEY.to_string(), lit.value().into());
+                }
+            } else if pred_str == "memexia:summarySource" {
+                if let Term::Literal(lit) = &quad.object {
+                    node.metadata.insert(SUMMARY_SOURCE_KEY.to_string(), lit.value().into());
+                }
+            } else if pred_str == "memexia:excerptOf" {
+                if let Term::Literal(lit) = &quad.object {
+                    node.metadata.insert(EXCERPT_OF_KEY.to_string(), lit.value().into());
+                }
+            } else if pred_str == "memexia:contentHash" {
+                if let Term::Literal(lit) = &quad.object {
+                    node.metadata.insert(CONTENT_HASH_KEY.to_string(), lit.value().into());
+                }
+            } else if pred_str == "memexia:deletedAt" {
+                if let Term::Literal(lit) = &quad.object {
+                    node.metadata.insert(DELETED_AT_KEY.to_string(), lit.value().into());
+                }
+            } else if pred_str == "memexia:lastReviewed" {
+                if let Term::Literal(lit) = &quad.object {
+                    node.metadata.insert(LAST_REVIEWED_KEY.to_string(), lit.value().into());
+                }
+            } else if pred_str == "memexia:previousTitle" {
+                if let Term::Literal(lit) = &quad.object {
+                    node.metadata.insert(PREVIOUS_TITLE_KEY.to_string(), lit.value().into());
+                }
+            } else if pred_str == "memexia:archivedAt" {
+                if let Term::Literal(lit) = &quad.object {
+                    node.metadata.insert(ARCHIVED_AT_KEY.to_string(), lit.value().into());
+                }
             } else if pred_str == "memexia:updatedAt" {
                 if let Term::Literal(lit) = &quad.object {
                     if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(
@@ -184,13 +752,29 @@ impl GraphStorage for OxigraphStorage {
     }
 
     fn update_node(&self, node: &Node) -> Result<()> {
-        self.delete_node(&node.id)?;
-        self.add_node(node)?;
+        // 只替换节点属性（字面量和类型），保留以该节点为起点的边
+        let subject = NamedOrBlankNode::from(NamedNode::new(&node.id)?);
+        let type_pred = NamedNode::new("rdf:type")?;
+        let quads: Vec<Quad> = self
+            .store
+            .quads_for_pattern(Some((&subject).into()), None, None, None)
+            .collect::<std::result::Result<_, _>>()?;
+        for quad in quads {
+            if quad.object.is_literal() || quad.predicate == type_pred {
+                self.remove_quad(&quad)?;
+            }
+        }
+
+        self.write_node(node)?;
+        if !self.observers.is_empty() {
+            self.notify(StorageChange::NodeUpdated(node.clone()));
+        }
         Ok(())
     }
 
     fn delete_node(&self, id: &str) -> Result<()> {
         let subject = NamedOrBlankNode::from(NamedNode::new(id)?);
+        let deleted = if self.observers.is_empty() { None } else { self.get_node(id)? };
 
         let quads: Result<Vec<Quad>, _> = self
             .store
@@ -200,73 +784,58 @@ impl GraphStorage for OxigraphStorage {
         match quads {
             Ok(quads) => {
                 for quad in quads {
-                    self.store.remove(&quad)?;
+                    self.remove_quad(&quad)?;
                 }
             }
             Err(e) => {
-                bail!("Failed to query quads: {:?}", e);
+                return Err(e.into());
             }
         }
 
+        if let Some(node) = deleted {
+            self.notify(StorageChange::NodeDeleted(node));
+        }
         Ok(())
     }
 
     fn list_nodes(&self) -> Result<Vec<Node>> {
-        let mut nodes = Vec::new();
-        let mut seen = std::collections::HashSet::new();
-
-        for result in self.store.iter() {
-            match result {
-                Ok(quad) => {
-                    // 使用 is_named_node() 方法检查是否是 NamedNode
-                    if quad.subject.is_named_node() {
-                        // subject.to_string() 返回带尖括号的IRI，需要清理
-                        let subj_str = Self::clean_iri(&quad.subject.to_string());
-                        if subj_str.starts_with("urn:memexia:") && seen.insert(subj_str.clone()) {
-                            if let Ok(Some(node)) = self.get_node(&subj_str) {
-                                nodes.push(node);
-                            }
-                        }
-                    }
-                }
-                Err(_) => {}
-            }
-        }
+        self.list_nodes_filtered(false)
+    }
 
+    fn list_nodes_filtered(&self, include_deleted: bool) -> Result<Vec<Node>> {
+        let mut nodes = Vec::new();
+        self.visit_nodes(include_deleted, &mut |node| {
+            nodes.push(node);
+            Ok(())
+        })?;
         Ok(nodes)
     }
 
     fn add_edge(&self, edge: &Edge) -> Result<()> {
         let subject = NamedOrBlankNode::from(NamedNode::new(&edge.from)?);
-        // 使用 to_lowercase() 以匹配 parse_relation_type 的期望
-        let predicate_str = format!("memexia:{}", edge.relation.to_string().to_lowercase());
+        let predicate_str = format!("memexia:{}", edge.relation.predicate_name());
         let predicate = NamedNode::new(&predicate_str)?;
         let object = NamedNode::new(&edge.to)?;
         let object_term = Term::from(object);
         let graph_name = GraphName::DefaultGraph;
 
         let quad = Quad::new(subject, predicate, object_term, graph_name);
-        self.store.insert(&quad)?;
+        self.insert_quad(&quad)?;
 
+        self.write_edge_metadata(edge)?;
+
+        if !self.observers.is_empty() {
+            self.notify(StorageChange::EdgeAdded(self.load_edge(&edge.from, &edge.to, edge.relation.clone())));
+        }
         Ok(())
     }
 
     fn get_edge(&self, id: &str) -> Result<Option<Edge>> {
-        if !id.starts_with("urn:memexia:edge:") {
-            bail!("Invalid edge ID format");
-        }
-
-        let suffix = id.strip_prefix("urn:memexia:edge:").unwrap();
-        let parts: Vec<&str> = suffix.split('-').collect();
-        if parts.len() < 2 {
-            bail!("Invalid edge ID format");
-        }
-
-        let from = parts[0];
-        let to = parts[1..].join("-");
+        let (from, to) = Edge::parse_id(id)
+            .ok_or_else(|| MemexiaError::Validation(format!("Invalid edge ID format: {}", id)))?;
 
         let from_node = NamedOrBlankNode::from(NamedNode::new(from)?);
-        let to_node = NamedNode::new(&to)?;
+        let to_node = NamedNode::new(to)?;
         let to_term = Term::from(to_node);
 
         for result in self.store.quads_for_pattern(
@@ -281,7 +850,9 @@ impl GraphStorage for OxigraphStorage {
                     if pred_str.starts_with("memexia:") {
                         let relation_str = pred_str.strip_prefix("memexia:").unwrap_or(&pred_str);
                         if let Some(relation) = parse_relation_type(relation_str) {
-                            return Ok(Some(Edge::new(id, from, &to, relation)));
+                            let mut edge = self.load_edge(from, to, relation);
+                            edge.id = id.to_string();
+                            return Ok(Some(edge));
                         }
                     }
                 }
@@ -308,8 +879,7 @@ impl GraphStorage for OxigraphStorage {
                                 if let Term::NamedNode(obj_node) = &quad.object {
                                     let obj_str = Self::clean_iri(&obj_node.to_string());
                                     if let Some(relation) = parse_relation_type(relation_str) {
-                                        let edge_id = format!("urn:memexia:edge:{}-{}", node_id, obj_str);
-                                        edges.push(Edge::new(&edge_id, node_id, &obj_str, relation));
+                                        edges.push(self.load_edge(node_id, &obj_str, relation));
                                     }
                                 }
                             }
@@ -329,8 +899,7 @@ impl GraphStorage for OxigraphStorage {
                                     let relation_str = pred_str.strip_prefix("memexia:").unwrap_or(&pred_str);
                                     if let Some(relation) = parse_relation_type(relation_str) {
                                         let subj_str = Self::clean_iri(&quad.subject.to_string());
-                                        let edge_id = format!("urn:memexia:edge:{}-{}", subj_str, node_id);
-                                        edges.push(Edge::new(&edge_id, &subj_str, node_id, relation));
+                                        edges.push(self.load_edge(&subj_str, node_id, relation));
                                     }
                                 }
                             }
@@ -340,75 +909,48 @@ impl GraphStorage for OxigraphStorage {
                 }
             }
             EdgeDirection::Both => {
-                // Outgoing
-                for result in self.store.quads_for_pattern(Some((&node).into()), None, None, None) {
-                    match result {
-                        Ok(quad) => {
-                            let pred_str = Self::clean_iri(&quad.predicate.to_string());
-                            if pred_str.starts_with("memexia:") {
-                                let relation_str = pred_str.strip_prefix("memexia:").unwrap_or(&pred_str);
-                                if let Term::NamedNode(obj_node) = &quad.object {
-                                    let obj_str = Self::clean_iri(&obj_node.to_string());
-                                    if let Some(relation) = parse_relation_type(relation_str) {
-                                        let edge_id = format!("urn:memexia:edge:{}-{}", node_id, obj_str);
-                                        edges.push(Edge::new(&edge_id, node_id, &obj_str, relation));
-                                    }
-                                }
-                            }
-                        }
-                        Err(_) => {}
-                    }
-                }
-                // Incoming
-                let target = Term::from(NamedNode::new(node_id)?);
-                for result in self.store.quads_for_pattern(None, None, Some((&target).into()), None) {
-                    match result {
-                        Ok(quad) => {
-                            if quad.subject.is_named_node() {
-                                let pred_str = Self::clean_iri(&quad.predicate.to_string());
-                                if pred_str.starts_with("memexia:") {
-                                    let relation_str = pred_str.strip_prefix("memexia:").unwrap_or(&pred_str);
-                                    if let Some(relation) = parse_relation_type(relation_str) {
-                                        let subj_str = Self::clean_iri(&quad.subject.to_string());
-                                        let edge_id = format!("urn:memexia:edge:{}-{}", subj_str, node_id);
-                                        edges.push(Edge::new(&edge_id, &subj_str, node_id, relation));
-                                    }
-                                }
-                            }
-                        }
-                        Err(_) => {}
-                    }
-                }
+                edges = self.get_edges_for_node(node_id, EdgeDirection::Outgoing)?;
+                edges.extend(self.get_edges_for_node(node_id, EdgeDirection::Incoming)?);
             }
         }
 
+        self.retain_live_edges(&mut edges)?;
+
         Ok(edges)
     }
 
     fn query_edges(&self, filter: EdgeFilter) -> Result<Vec<Edge>> {
         let mut edges = Vec::new();
 
-        let subject = filter.from.as_ref().map(|s| {
-            NamedOrBlankNode::from(NamedNode::new(s).unwrap())
-        });
-        let object = filter.to.as_ref().map(|t| Term::from(NamedNode::new(t).unwrap()));
+        let subject = match &filter.from {
+            Some(s) => Some(NamedOrBlankNode::from(NamedNode::new(s)?)),
+            None => None,
+        };
+        let object = match &filter.to {
+            Some(t) => Some(Term::from(NamedNode::new(t)?)),
+            None => None,
+        };
 
         for result in self.store.quads_for_pattern(
-            subject.as_ref().map(|s| (s as &NamedOrBlankNode).into()),
+            subject.as_ref().map(|s| s.into()),
             None,
-            object.as_ref().map(|t| (t as &Term).into()),
+            object.as_ref().map(|t| t.into()),
             None,
         ) {
             match result {
                 Ok(quad) => {
                     let pred_str = Self::clean_iri(&quad.predicate.to_string());
-                    if pred_str.starts_with("memexia:") {
-                        if quad.subject.is_named_node() && quad.object.is_named_node() {
-                            if let Some(relation) = parse_relation_type(&pred_str) {
-                                let s_str = Self::clean_iri(&quad.subject.to_string());
-                                let o_str = Self::clean_iri(&quad.object.to_string());
-                                let edge_id = format!("urn:memexia:edge:{}-{}", s_str, o_str);
-                                edges.push(Edge::new(&edge_id, &s_str, &o_str, relation));
+                    if pred_str.starts_with("memexia:")
+                        && quad.subject.is_named_node()
+                        && quad.object.is_named_node()
+                    {
+                        let relation_str = pred_str.strip_prefix("memexia:").unwrap_or(&pred_str);
+                        if let Some(relation) = parse_relation_type(relation_str) {
+                            let s_str = Self::clean_iri(&quad.subject.to_string());
+                            let o_str = Self::clean_iri(&quad.object.to_string());
+                            let edge = self.load_edge(&s_str, &o_str, relation);
+                            if filter.matches(&edge) {
+                                edges.push(edge);
                             }
                         }
                     }
@@ -417,27 +959,20 @@ impl GraphStorage for OxigraphStorage {
             }
         }
 
+        self.retain_live_edges(&mut edges)?;
+
         Ok(edges)
     }
 
     fn delete_edge(&self, id: &str) -> Result<()> {
-        if !id.starts_with("urn:memexia:edge:") {
-            bail!("Invalid edge ID format");
-        }
-
-        let suffix = id.strip_prefix("urn:memexia:edge:").unwrap();
-        let parts: Vec<&str> = suffix.split('-').collect();
-        if parts.len() < 2 {
-            bail!("Invalid edge ID format");
-        }
-
-        let from = parts[0];
-        let to = parts[1..].join("-");
+        let (from, to) = Edge::parse_id(id)
+            .ok_or_else(|| MemexiaError::Validation(format!("Invalid edge ID format: {}", id)))?;
 
+        let deleted = if self.observers.is_empty() { None } else { self.get_edge(id)? };
         let from_node = NamedOrBlankNode::from(NamedNode::new(from)?);
-        let to_term = Term::from(NamedNode::new(&to)?);
+        let to_term = Term::from(NamedNode::new(to)?);
 
-        let quads: Result<Vec<Quad>, _> = self
+        let quads: Vec<Quad> = self
             .store
             .quads_for_pattern(
                 Some((&from_node).into()),
@@ -445,61 +980,42 @@ impl GraphStorage for OxigraphStorage {
                 Some((&to_term).into()),
                 None,
             )
-            .collect();
-
-        match quads {
-            Ok(quads) => {
-                for quad in quads {
-                    self.store.remove(&quad)?;
-                }
-            }
-            Err(e) => {
-                bail!("Failed to query quads: {:?}", e);
-            }
+            .collect::<std::result::Result<_, _>>()?;
+        for quad in quads {
+            self.remove_quad(&quad)?;
         }
 
+        self.remove_edge_metadata(from, to)?;
+
+        if let Some(edge) = deleted {
+            self.notify(StorageChange::EdgeDeleted(edge));
+        }
         Ok(())
     }
 
     fn list_edges(&self) -> Result<Vec<Edge>> {
-        let mut edges = Vec::new();
-        let mut seen = std::collections::HashSet::new();
-
-        for result in self.store.iter() {
-            match result {
-                Ok(quad) => {
-                    let pred_str = Self::clean_iri(&quad.predicate.to_string());
-                    if pred_str.starts_with("memexia:") {
-                        if quad.subject.is_named_node() && quad.object.is_named_node() {
-                            // 去掉 "memexia:" 前缀，提取关系类型
-                            let relation_str = pred_str.strip_prefix("memexia:").unwrap_or(&pred_str);
-                            // subject 和 object 都需要清理
-                            let s_str = Self::clean_iri(&quad.subject.to_string());
-                            let o_str = Self::clean_iri(&quad.object.to_string());
-                            let edge_id = format!("urn:memexia:edge:{}-{}", s_str, o_str);
-                            if seen.insert(edge_id.clone()) {
-                                if let Some(relation) = parse_relation_type(relation_str) {
-                                    edges.push(Edge::new(&edge_id, &s_str, &o_str, relation));
-                                }
-                            }
-                        }
-                    }
-                }
-                Err(_) => {}
-            }
-        }
+        self.list_edges_filtered(false)
+    }
 
+    fn list_edges_filtered(&self, include_deleted: bool) -> Result<Vec<Edge>> {
+        let mut edges = Vec::new();
+        self.visit_edges(include_deleted, &mut |edge| {
+            edges.push(edge);
+            Ok(())
+        })?;
         Ok(edges)
     }
 
     fn query(&self, sparql: &str) -> Result<QueryResult> {
         use oxigraph::sparql::{QueryResults, SparqlEvaluator};
 
+        let _timer = crate::metrics::SparqlTimer::start();
         let mut result = QueryResult::default();
 
         // 使用 SparqlEvaluator 执行 SPARQL 查询
         let evaluation = SparqlEvaluator::new()
-            .parse_query(sparql)?
+            .parse_query(sparql)
+            .map_err(|e| MemexiaError::Validation(format!("Invalid SPARQL query: {}", e)))?
             .on_store(&self.store)
             .execute()?;
 
@@ -509,6 +1025,7 @@ impl GraphStorage for OxigraphStorage {
                 // ASK 查询结果 - 没有绑定
             }
             QueryResults::Solutions(solutions) => {
+                result.variables = solutions.variables().iter().map(|v| v.to_string()).collect();
                 for solution in solutions {
                     match solution {
                         Ok(b) => {
@@ -528,13 +1045,25 @@ impl GraphStorage for OxigraphStorage {
             }
             QueryResults::Graph(_) => {
                 // CONSTRUCT/DESCRIBE 查询
-                bail!("Graph results not supported yet. Use SELECT queries instead.");
+                return Err(MemexiaError::Validation(
+                    "Graph results not supported yet. Use SELECT queries instead.".to_string(),
+                ));
             }
         }
 
         Ok(result)
     }
 
+    fn node_ids(&self) -> Result<std::collections::HashSet<String>> {
+        // 节点都带类型声明；只有边三元组的主语不算节点
+        let predicate = NamedNode::new("rdf:type")?;
+        let mut ids = std::collections::HashSet::new();
+        for quad in self.store.quads_for_pattern(None, Some((&predicate).into()), None, None) {
+            ids.insert(Self::clean_iri(&quad?.subject.to_string()));
+        }
+        Ok(ids)
+    }
+
     fn node_exists(&self, id: &str) -> Result<bool> {
         let subject = NamedOrBlankNode::from(NamedNode::new(id)?);
         Ok(self
@@ -549,6 +1078,47 @@ impl GraphStorage for OxigraphStorage {
         Ok(self.get_edge(id)?.is_some())
     }
 
+    fn find_by_title(&self, title: &str, case_sensitive: bool) -> Result<Vec<Node>> {
+        let (predicate, value) = if case_sensitive {
+            ("memexia:title", title.to_string())
+        } else {
+            ("memexia:titleKey", Self::title_key(title))
+        };
+        let predicate = NamedNode::new(predicate)?;
+        let object = Term::from(Literal::new_simple_literal(value));
+
+        let mut ids = BTreeSet::new();
+        for quad in self.store.quads_for_pattern(None, Some((&predicate).into()), Some((&object).into()), None) {
+            ids.insert(Self::clean_iri(&quad?.subject.to_string()));
+        }
+        let mut nodes = Vec::new();
+        for id in ids {
+            if let Some(node) = self.get_node(&id)?.filter(|node| !node.is_deleted()) {
+                nodes.push(node);
+            }
+        }
+        Ok(nodes)
+    }
+
+    fn title_index_mismatches(&self) -> Result<Vec<String>> {
+        Ok(self.title_index_state()?.0)
+    }
+
+    fn rebuild_title_index(&self) -> Result<usize> {
+        let (mismatches, titles) = self.title_index_state()?;
+        for id in &mismatches {
+            let subject = NamedOrBlankNode::from(NamedNode::new(id)?);
+            match titles.get(id).and_then(|titles| titles.iter().next()) {
+                Some(title) => {
+                    let key = Term::from(Literal::new_simple_literal(Self::title_key(title)));
+                    self.replace_property(&subject, "memexia:titleKey", key)?
+                }
+                None => self.remove_property(&subject, "memexia:titleKey")?,
+            }
+        }
+        Ok(mismatches.len())
+    }
+
     fn get_stats(&self) -> Result<GraphStats> {
         let nodes = self.list_nodes()?;
         let edges = self.list_edges()?;
@@ -560,7 +1130,7 @@ impl GraphStorage for OxigraphStorage {
 
         let mut relation_counts = std::collections::HashMap::new();
         for edge in &edges {
-            *relation_counts.entry(edge.relation).or_insert(0) += 1;
+            *relation_counts.entry(edge.relation.clone()).or_insert(0) += 1;
         }
 
         Ok(GraphStats {
@@ -579,6 +1149,14 @@ impl GraphStorage for OxigraphStorage {
         self.list_edges()
     }
 
+    fn for_each_node(&self, visit: &mut dyn FnMut(Node) -> Result<()>) -> Result<()> {
+        self.visit_nodes(false, visit)
+    }
+
+    fn for_each_edge(&self, visit: &mut dyn FnMut(Edge) -> Result<()>) -> Result<()> {
+        self.visit_edges(false, visit)
+    }
+
     fn get_edges_by_source(&self, source: &str) -> Result<Vec<Edge>> {
         self.get_edges_for_node(source, EdgeDirection::Outgoing)
     }
@@ -632,7 +1210,9 @@ impl GraphStorage for OxigraphStorage {
                 return Ok(Some(path));
             }
 
-            let edges = self.get_edges_by_source(&current)?;
+            // 先访问权重较高的边，等长路径中优先选择较强（较新）的关系
+            let mut edges = self.get_edges_by_source(&current)?;
+            edges.sort_by(|a, b| b.weight().total_cmp(&a.weight()).then_with(|| a.to.cmp(&b.to)));
             for edge in &edges {
                 if !visited.contains(&edge.to) {
                     visited.insert(edge.to.clone());
@@ -645,21 +1225,42 @@ impl GraphStorage for OxigraphStorage {
         Ok(None)
     }
 
+    fn run_transaction(&self, f: &mut dyn FnMut(&dyn GraphStorage) -> Result<()>) -> Result<()> {
+        // 已在事务中：并入外层事务，由外层统一撤销
+        if self.journal.is_some() {
+            return f(self);
+        }
+
+        let journal = Arc::new(Mutex::new(Vec::new()));
+        let pending = Arc::new(Mutex::new(Vec::new()));
+        let handle = Self {
+            store: self.store.clone(),
+            journal: Some(journal.clone()),
+            observers: self.observers.clone(),
+            pending: Some(pending.clone()),
+        };
+        let result = f(&handle);
+        if result.is_err() {
+            let changes = std::mem::take(&mut *journal.lock().unwrap_or_else(|e| e.into_inner()));
+            if let Err(err) = self.roll_back(&changes) {
+                tracing::error!("Failed to roll back graph transaction: {}", err);
+            }
+            return result;
+        }
+        for change in std::mem::take(&mut *pending.lock().unwrap_or_else(|e| e.into_inner())) {
+            self.observers.notify(&change);
+        }
+        result
+    }
+
     fn export_nquads(&self) -> Result<String> {
         use std::fmt::Write;
 
-        let mut output = String::new();
-
+        let mut triples = Vec::new();
         for result in self.store.iter() {
             match result {
                 Ok(quad) => {
-                    // 格式化: <subject> <predicate> <object> .
-                    let subject = quad.subject.to_string();
-                    let predicate = quad.predicate.to_string();
-                    let object = quad.object.to_string();
-
-                    // N-Quads 格式
-                    writeln!(output, "{} {} {} .", subject, predicate, object).unwrap();
+                    triples.push((quad.subject.to_string(), quad.predicate.to_string(), quad.object.to_string()));
                 }
                 Err(e) => {
                     tracing::warn!("Failed to read quad: {:?}", e);
@@ -667,6 +1268,16 @@ impl GraphStorage for OxigraphStorage {
             }
         }
 
+        // 存储的迭代顺序与插入顺序和平台有关，按规范顺序排序
+        triples.sort_unstable();
+        triples.dedup();
+
+        let mut output = String::new();
+        for (subject, predicate, object) in triples {
+            // N-Quads 格式: <subject> <predicate> <object> .
+            writeln!(output, "{} {} {} .", subject, predicate, object).unwrap();
+        }
+
         Ok(output)
     }
 }
@@ -0,0 +1,332 @@
+//! 存储结构版本与迁移模块
+//!
+//! 谓词词汇、边元数据模型和 ID 编码都会随版本演进。仓库的结构版本记录在
+//! `.memexia/schema_version` 中，[`Storage::open`](super::Storage::open) 每次打开仓库时检查：
+//! 版本较旧时按顺序执行已注册的迁移步骤
+//!
+//! - 没有版本文件的仓库视为版本 [`BASE_VERSION`]（引入版本记录之前创建）
+//! - 迁移在图的内存副本上执行，全部成功后才替换仓库中的图并写入新版本；
+//!   任何一步失败时仓库保持原样
+//! - 迁移步骤也会收到仓库根目录，文件系统上的改动无法回滚，应当可以重复执行
+//!
+//! | 版本 | 迁移 |
+//! |------|------|
+//! | 1 | 引入版本记录之前的仓库 |
+//! | 2 | 把未编码的旧版文件节点 ID 改写为规范形式 |
+//! | 3 | 为已有节点建立标题索引（`memexia:titleKey`） |
+
+use super::{GraphStorage, OxigraphStorage};
+use crate::error::{MemexiaError, Result};
+use anyhow::Context;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 版本文件名（位于 `.memexia` 中）
+pub const SCHEMA_VERSION_FILE: &str = "schema_version";
+
+/// 引入版本记录之前的仓库版本
+pub const BASE_VERSION: u32 = 1;
+
+/// 迁移函数：在图副本上执行，可以读写仓库根目录下的文件
+pub type MigrationFn = dyn Fn(&dyn GraphStorage, &Path) -> Result<()> + Send + Sync;
+
+/// 一个迁移步骤
+pub struct Migration {
+    /// 迁移完成后的版本
+    pub version: u32,
+    /// 说明
+    pub description: String,
+    run: Box<MigrationFn>,
+}
+
+impl Migration {
+    /// 创建迁移步骤
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - 迁移完成后的版本（必须大于 [`BASE_VERSION`]）
+    /// * `description` - 说明
+    /// * `run` - 迁移函数
+    pub fn new(
+        version: u32,
+        description: impl Into<String>,
+        run: impl Fn(&dyn GraphStorage, &Path) -> Result<()> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            version,
+            description: description.into(),
+            run: Box::new(run),
+        }
+    }
+}
+
+impl std::fmt::Debug for Migration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Migration")
+            .field("version", &self.version)
+            .field("description", &self.description)
+            .finish_non_exhaustive()
+    }
+}
+
+/// 已执行的迁移
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedMigration {
+    /// 迁移完成后的版本
+    pub version: u32,
+    /// 说明
+    pub description: String,
+}
+
+/// 迁移结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// 迁移前的版本
+    pub from: u32,
+    /// 迁移后的版本
+    pub to: u32,
+    /// 按顺序执行的迁移
+    pub applied: Vec<AppliedMigration>,
+}
+
+/// 迁移步骤集合
+#[derive(Debug)]
+pub struct Migrator {
+    migrations: Vec<Migration>,
+}
+
+impl Default for Migrator {
+    fn default() -> Self {
+        Self::with_builtin_migrations()
+    }
+}
+
+impl Migrator {
+    /// 创建不含迁移步骤的迁移器
+    pub fn empty() -> Self {
+        Self { migrations: Vec::new() }
+    }
+
+    /// 创建包含所有内置迁移的迁移器
+    pub fn with_builtin_migrations() -> Self {
+        let mut migrator = Self::empty();
+        migrator.register(Migration::new(2, "encode legacy node IDs", |graph, _root| {
+            let legacy = crate::core::doctor::find_legacy_ids(graph)?;
+            crate::core::doctor::migrate_legacy_ids(graph, &legacy)?;
+            Ok(())
+        }));
+        migrator.register(Migration::new(3, "build the title index", |graph, _root| {
+            graph.rebuild_title_index()?;
+            Ok(())
+        }));
+        migrator
+    }
+
+    /// 注册迁移步骤（同一版本的步骤会被替换）
+    pub fn register(&mut self, migration: Migration) {
+        self.migrations.retain(|m| m.version != migration.version);
+        self.migrations.push(migration);
+        self.migrations.sort_by_key(|m| m.version);
+    }
+
+    /// 已注册的迁移步骤（按版本排序）
+    pub fn migrations(&self) -> &[Migration] {
+        &self.migrations
+    }
+
+    /// 最新版本
+    pub fn latest(&self) -> u32 {
+        self.migrations.last().map_or(BASE_VERSION, |m| m.version.max(BASE_VERSION))
+    }
+
+    /// 从 `current` 升级到 `target` 需要执行的迁移
+    pub fn pending(&self, current: u32, target: u32) -> Vec<&Migration> {
+        self.migrations
+            .iter()
+            .filter(|m| m.version > current && m.version <= target)
+            .collect()
+    }
+
+    /// 把仓库迁移到最新版本
+    ///
+    /// # Arguments
+    ///
+    /// * `graph` - 仓库的图存储
+    /// * `root` - 仓库根目录
+    pub fn migrate_to_latest(&self, graph: &OxigraphStorage, root: &Path) -> Result<MigrationReport> {
+        self.migrate(graph, root, self.latest())
+    }
+
+    /// 把仓库迁移到指定版本
+    ///
+    /// # Arguments
+    ///
+    /// * `graph` - 仓库的图存储
+    /// * `root` - 仓库根目录
+    /// * `target` - 目标版本；不能低于当前版本，也不能高于 [`Migrator::latest`]
+    ///
+    /// # Returns
+    ///
+    /// 迁移结果；已是目标版本时 `applied` 为空
+    pub fn migrate(&self, graph: &OxigraphStorage, root: &Path, target: u32) -> Result<MigrationReport> {
+        let current = read_version(root)?;
+        let latest = self.latest();
+        if current > latest {
+            return Err(MemexiaError::Validation(format!(
+                "Repository schema version {} is newer than the latest supported version {}; upgrade memexia",
+                current, latest
+            )));
+        }
+        if target < current || target > latest {
+            return Err(MemexiaError::Validation(format!(
+                "Cannot migrate schema from version {} to {} (latest is {})",
+                current, target, latest
+            )));
+        }
+
+        let mut report = MigrationReport {
+            from: current,
+            to: current,
+            applied: Vec::new(),
+        };
+        let pending = self.pending(current, target);
+        if pending.is_empty() {
+            if current < target {
+                write_version(root, target)?;
+                report.to = target;
+            }
+            return Ok(report);
+        }
+
+        // 在内存副本上执行，全部成功后才替换仓库中的图
+        let original = graph.export_nquads()?;
+        let copy = OxigraphStorage::from_nquads(&original)?;
+        for migration in pending {
+            tracing::info!(version = migration.version, description = %migration.description, "running schema migration");
+            (migration.run)(&copy, root).with_context(|| {
+                format!(
+                    "Schema migration to version {} ({}) failed; the repository was left unchanged",
+                    migration.version, migration.description
+                )
+            })?;
+            report.applied.push(AppliedMigration {
+                version: migration.version,
+                description: migration.description.clone(),
+            });
+        }
+
+        if let Err(err) = graph.replace_contents(&copy.export_nquads()?) {
+            graph.replace_contents(&original)?;
+            return Err(err);
+        }
+        write_version(root, target)?;
+        report.to = target;
+        tracing::info!(from = report.from, to = report.to, "schema migrated");
+        Ok(report)
+    }
+}
+
+/// 版本文件路径
+pub fn version_path(root: &Path) -> PathBuf {
+    root.join(".memexia").join(SCHEMA_VERSION_FILE)
+}
+
+/// 读取仓库的结构版本，没有版本文件时为 [`BASE_VERSION`]
+pub fn read_version(root: &Path) -> Result<u32> {
+    let path = version_path(root);
+    if !path.exists() {
+        return Ok(BASE_VERSION);
+    }
+    let content = fs::read_to_string(&path).with_context(|| format!("Failed to read {:?}", path))?;
+    content.trim().parse().map_err(|_| MemexiaError::CorruptRepository {
+        path: root.to_path_buf(),
+        reason: format!("invalid schema version {:?} in {:?}", content.trim(), path),
+    })
+}
+
+/// 写入仓库的结构版本
+pub fn write_version(root: &Path, version: u32) -> Result<()> {
+    let path = version_path(root);
+    fs::write(&path, format!("{}\n", version)).with_context(|| format!("Failed to write {:?}", path))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{ids, Node, NodeType, Storage};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_init_records_latest_version() {
+        let temp = TempDir::new().unwrap();
+        Storage::init(temp.path()).unwrap();
+        assert_eq!(read_version(temp.path()).unwrap(), Migrator::default().latest());
+
+        // 没有版本文件的旧仓库
+        fs::remove_file(version_path(temp.path())).unwrap();
+        assert_eq!(read_version(temp.path()).unwrap(), BASE_VERSION);
+    }
+
+    #[test]
+    fn test_dummy_migration_runs_once() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let builtin = Migrator::default().latest();
+
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+        let mut migrator = Migrator::default();
+        migrator.register(Migration::new(builtin + 1, "add marker node", move |graph, _root| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            graph.add_node(&Node::new("urn:memexia:generated:marker", NodeType::Meta, "marker"))
+        }));
+
+        let report = migrator.migrate(storage.graph_storage(), temp.path(), migrator.latest()).unwrap();
+        assert_eq!((report.from, report.to), (builtin, builtin + 1));
+        assert_eq!(report.applied.len(), 1);
+        assert_eq!(read_version(temp.path()).unwrap(), builtin + 1);
+        assert!(storage.graph().node_exists("urn:memexia:generated:marker").unwrap());
+
+        let report = migrator.migrate(storage.graph_storage(), temp.path(), migrator.latest()).unwrap();
+        assert!(report.applied.is_empty());
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        // 默认迁移器不认识更新的版本
+        assert!(Migrator::default().migrate(storage.graph_storage(), temp.path(), builtin).is_err());
+    }
+
+    #[test]
+    fn test_failed_migration_leaves_store_untouched() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        storage.graph().add_node(&Node::new("urn:memexia:file:a.md", NodeType::Concept, "A")).unwrap();
+        let builtin = Migrator::default().latest();
+
+        let mut migrator = Migrator::default();
+        migrator.register(Migration::new(builtin + 1, "delete then fail", |graph, _root| {
+            graph.delete_node("urn:memexia:file:a.md")?;
+            Err(MemexiaError::Validation("boom".to_string()))
+        }));
+        let err = migrator.migrate(storage.graph_storage(), temp.path(), builtin + 1).unwrap_err();
+        assert!(format!("{:#}", anyhow::Error::from(err)).contains("left unchanged"));
+        assert!(storage.graph().node_exists("urn:memexia:file:a.md").unwrap());
+        assert_eq!(read_version(temp.path()).unwrap(), builtin);
+    }
+
+    #[test]
+    fn test_builtin_migration_encodes_legacy_ids() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let legacy = "urn:memexia:file:自由意志.md";
+        storage.graph().add_node(&Node::new(legacy, NodeType::Concept, "自由意志")).unwrap();
+        write_version(temp.path(), BASE_VERSION).unwrap();
+
+        let report = Migrator::default().migrate_to_latest(storage.graph_storage(), temp.path()).unwrap();
+        assert_eq!(report.applied[0].description, "encode legacy node IDs");
+        assert!(!storage.graph().node_exists(legacy).unwrap());
+        assert!(storage.graph().node_exists(&ids::file_id("自由意志.md")).unwrap());
+    }
+}
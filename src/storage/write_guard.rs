@@ -0,0 +1,379 @@
+//! 写保护模块（dry-run）
+//!
+//! [`WriteGuard`] 包装一个 [`GraphStorage`]：读操作直接转发，写操作（增删改节点和边）
+//! 只记录到变更日志而不执行。开启 dry-run 时由 [`Storage::enable_dry_run`](super::Storage::enable_dry_run)
+//! 注入，命令处理代码无需为 dry-run 单独分支
+//!
+//! 只读模式（[`Storage::open_read_only`](super::Storage::open_read_only)）使用同一包装，
+//! 写操作直接返回 [`MemexiaError::ReadOnlyRepository`]
+//!
+//! 注意：被拦截的写操作不会反映到后续读取中，读到的始终是底层存储的当前状态
+
+use super::graph::{EdgeDirection, GraphStats, GraphStorage, QueryResult};
+use super::{Edge, EdgeFilter, Node};
+use crate::error::{MemexiaError, Result};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// 变更类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum MutationKind {
+    /// 添加节点
+    AddNode,
+    /// 更新节点
+    UpdateNode,
+    /// 删除节点
+    DeleteNode,
+    /// 添加边
+    AddEdge,
+    /// 删除边
+    DeleteEdge,
+}
+
+/// 一次被拦截的写操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mutation {
+    /// 变更类型
+    pub kind: MutationKind,
+    /// 节点或边 ID
+    pub id: String,
+}
+
+/// 按类型汇总的变更
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MutationSummary {
+    /// 变更类型
+    pub kind: MutationKind,
+    /// 变更次数
+    pub count: usize,
+    /// 受影响 ID 的样本（按出现顺序，最多 `sample_size` 个）
+    pub sample: Vec<String>,
+}
+
+/// 按类型汇总变更日志
+///
+/// # Arguments
+///
+/// * `mutations` - 变更日志
+/// * `sample_size` - 每种类型保留的 ID 样本数
+pub fn summarize(mutations: &[Mutation], sample_size: usize) -> Vec<MutationSummary> {
+    let mut summaries: Vec<MutationSummary> = Vec::new();
+    for mutation in mutations {
+        let index = match summaries.iter().position(|s| s.kind == mutation.kind) {
+            Some(index) => index,
+            None => {
+                summaries.push(MutationSummary { kind: mutation.kind, count: 0, sample: Vec::new() });
+                summaries.len() - 1
+            }
+        };
+        let summary = &mut summaries[index];
+        summary.count += 1;
+        if summary.sample.len() < sample_size {
+            summary.sample.push(mutation.id.clone());
+        }
+    }
+    summaries.sort_by_key(|s| s.kind);
+    summaries
+}
+
+/// 记录而不执行写操作的图存储包装
+///
+/// 克隆共享同一个变更日志
+#[derive(Clone)]
+pub struct WriteGuard {
+    /// 被包装的存储
+    inner: Arc<dyn GraphStorage>,
+    /// 变更日志
+    log: Arc<Mutex<Vec<Mutation>>>,
+    /// 只读仓库的根路径：设置时拒绝写操作而不是记录
+    read_only: Option<PathBuf>,
+}
+
+impl std::fmt::Debug for WriteGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WriteGuard").field("mutations", &self.mutations().len()).finish()
+    }
+}
+
+impl WriteGuard {
+    /// 包装存储
+    pub fn new(inner: Arc<dyn GraphStorage>) -> Self {
+        Self {
+            inner,
+            log: Arc::new(Mutex::new(Vec::new())),
+            read_only: None,
+        }
+    }
+
+    /// 包装只读仓库的存储：写操作返回 [`MemexiaError::ReadOnlyRepository`]
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - 被包装的存储
+    /// * `root` - 仓库根路径（用于错误信息）
+    pub fn read_only(inner: Arc<dyn GraphStorage>, root: PathBuf) -> Self {
+        Self {
+            read_only: Some(root),
+            ..Self::new(inner)
+        }
+    }
+
+    /// 改为包装另一个存储，保留变更日志和只读设置（开启节点读缓存时使用）
+    pub(super) fn wrapping(&self, inner: Arc<dyn GraphStorage>) -> Self {
+        Self { inner, ..self.clone() }
+    }
+
+    /// 是否拒绝写操作（只读模式）
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.is_some()
+    }
+
+    /// 已记录的变更（按发生顺序）
+    pub fn mutations(&self) -> Vec<Mutation> {
+        self.log.lock().map(|log| log.clone()).unwrap_or_default()
+    }
+
+    fn record(&self, kind: MutationKind, id: &str) -> Result<()> {
+        if let Some(root) = &self.read_only {
+            return Err(MemexiaError::ReadOnlyRepository { path: root.clone() });
+        }
+        if let Ok(mut log) = self.log.lock() {
+            log.push(Mutation { kind, id: id.to_string() });
+        }
+        Ok(())
+    }
+}
+
+impl GraphStorage for WriteGuard {
+    fn add_node(&self, node: &Node) -> Result<()> {
+        self.record(MutationKind::AddNode, &node.id)
+    }
+
+    fn get_node(&self, id: &str) -> Result<Option<Node>> {
+        self.inner.get_node(id)
+    }
+
+    fn update_node(&self, node: &Node) -> Result<()> {
+        self.record(MutationKind::UpdateNode, &node.id)
+    }
+
+    fn delete_node(&self, id: &str) -> Result<()> {
+        self.record(MutationKind::DeleteNode, id)
+    }
+
+    fn list_nodes(&self) -> Result<Vec<Node>> {
+        self.inner.list_nodes()
+    }
+
+    fn list_nodes_filtered(&self, include_deleted: bool) -> Result<Vec<Node>> {
+        self.inner.list_nodes_filtered(include_deleted)
+    }
+
+    fn get_all_nodes(&self) -> Result<Vec<Node>> {
+        self.inner.get_all_nodes()
+    }
+
+    fn add_edge(&self, edge: &Edge) -> Result<()> {
+        self.record(MutationKind::AddEdge, &Edge::make_id(&edge.from, &edge.to))
+    }
+
+    fn get_edge(&self, id: &str) -> Result<Option<Edge>> {
+        self.inner.get_edge(id)
+    }
+
+    fn get_edges_for_node(&self, node_id: &str, direction: EdgeDirection) -> Result<Vec<Edge>> {
+        self.inner.get_edges_for_node(node_id, direction)
+    }
+
+    fn get_edges_by_source(&self, source: &str) -> Result<Vec<Edge>> {
+        self.inner.get_edges_by_source(source)
+    }
+
+    fn get_edges_by_target(&self, target: &str) -> Result<Vec<Edge>> {
+        self.inner.get_edges_by_target(target)
+    }
+
+    fn query_edges(&self, filter: EdgeFilter) -> Result<Vec<Edge>> {
+        self.inner.query_edges(filter)
+    }
+
+    fn delete_edge(&self, id: &str) -> Result<()> {
+        self.record(MutationKind::DeleteEdge, id)
+    }
+
+    fn remove_edge(&self, id: &str) -> Result<()> {
+        self.delete_edge(id)
+    }
+
+    fn list_edges(&self) -> Result<Vec<Edge>> {
+        self.inner.list_edges()
+    }
+
+    fn list_edges_filtered(&self, include_deleted: bool) -> Result<Vec<Edge>> {
+        self.inner.list_edges_filtered(include_deleted)
+    }
+
+    fn get_all_edges(&self) -> Result<Vec<Edge>> {
+        self.inner.get_all_edges()
+    }
+
+    fn for_each_node(&self, visit: &mut dyn FnMut(Node) -> Result<()>) -> Result<()> {
+        self.inner.for_each_node(visit)
+    }
+
+    fn for_each_edge(&self, visit: &mut dyn FnMut(Edge) -> Result<()>) -> Result<()> {
+        self.inner.for_each_edge(visit)
+    }
+
+    fn query(&self, sparql: &str) -> Result<QueryResult> {
+        self.inner.query(sparql)
+    }
+
+    fn sparql_query(&self, sparql: &str) -> Result<Vec<String>> {
+        self.inner.sparql_query(sparql)
+    }
+
+    fn node_exists(&self, id: &str) -> Result<bool> {
+        self.inner.node_exists(id)
+    }
+
+    fn edge_exists(&self, id: &str) -> Result<bool> {
+        self.inner.edge_exists(id)
+    }
+
+    fn find_by_title(&self, title: &str, case_sensitive: bool) -> Result<Vec<Node>> {
+        self.inner.find_by_title(title, case_sensitive)
+    }
+
+    fn title_index_mismatches(&self) -> Result<Vec<String>> {
+        self.inner.title_index_mismatches()
+    }
+
+    /// 每个需要修正的节点记为一次节点更新
+    fn rebuild_title_index(&self) -> Result<usize> {
+        let mismatches = self.inner.title_index_mismatches()?;
+        for id in &mismatches {
+            self.record(MutationKind::UpdateNode, id)?;
+        }
+        Ok(mismatches.len())
+    }
+
+    fn get_stats(&self) -> Result<GraphStats> {
+        self.inner.get_stats()
+    }
+
+    fn find_path(&self, source: &str, target: &str) -> Result<Option<Vec<String>>> {
+        self.inner.find_path(source, target)
+    }
+
+    fn export_nquads(&self) -> Result<String> {
+        self.inner.export_nquads()
+    }
+
+    /// 写操作本就不会执行；失败时丢弃事务中记录的变更，使预览与实际执行一致
+    fn run_transaction(&self, f: &mut dyn FnMut(&dyn GraphStorage) -> Result<()>) -> Result<()> {
+        let start = self.mutations().len();
+        let result = f(self);
+        if result.is_err() {
+            if let Ok(mut log) = self.log.lock() {
+                log.truncate(start);
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Indexer;
+    use crate::storage::{NodeType, RelationType, Storage};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_writes_are_recorded_not_applied() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let guard = WriteGuard::new(Arc::new(storage.graph_storage().clone()));
+
+        let node = Node::new("urn:memexia:file:a.md", NodeType::Concept, "A");
+        guard.add_node(&node).unwrap();
+        guard.update_node(&node).unwrap();
+        let (from, to) = ("urn:memexia:file:a.md", "urn:memexia:file:b.md");
+        guard.add_edge(&Edge::new(Edge::make_id(from, to), from, to, RelationType::Supports)).unwrap();
+        guard.remove_edge(&Edge::make_id(from, to)).unwrap();
+        guard.delete_node("urn:memexia:file:a.md").unwrap();
+
+        assert!(storage.graph().list_nodes().unwrap().is_empty());
+        assert!(!guard.node_exists("urn:memexia:file:a.md").unwrap());
+
+        let kinds: Vec<_> = guard.mutations().into_iter().map(|m| m.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                MutationKind::AddNode,
+                MutationKind::UpdateNode,
+                MutationKind::AddEdge,
+                MutationKind::DeleteEdge,
+                MutationKind::DeleteNode,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dry_run_reindex_leaves_store_untouched() {
+        let temp = TempDir::new().unwrap();
+        let mut storage = Storage::init(temp.path()).unwrap();
+
+        // 已有一个过期节点，磁盘上有两篇笔记
+        let stale = Node::new("urn:memexia:file:stale.md", NodeType::Concept, "Stale");
+        storage.graph().add_node(&stale).unwrap();
+        std::fs::write(temp.path().join("a.md"), "# A\n\n[[b.md]]").unwrap();
+        std::fs::write(temp.path().join("b.md"), "# B").unwrap();
+        let before = storage.graph().export_nquads().unwrap();
+
+        storage.enable_dry_run();
+        let indexer = Indexer::new(storage.clone());
+        let summary = indexer.reindex_all(temp.path()).unwrap();
+        assert_eq!(summary.files_indexed, 2);
+
+        // 底层存储未变化，变更日志记录了全部意图
+        // （写入不反映到读取，链接目标 b.md 的占位节点也会被记录一次）
+        assert_eq!(storage.graph_storage().export_nquads().unwrap(), before);
+        let summaries = summarize(&storage.mutations(), 1);
+        let counts: Vec<_> = summaries.iter().map(|s| (s.kind, s.count)).collect();
+        assert_eq!(
+            counts,
+            vec![(MutationKind::AddNode, 3), (MutationKind::DeleteNode, 1), (MutationKind::AddEdge, 1)]
+        );
+        assert_eq!(summaries[1].sample, vec!["urn:memexia:file:stale.md"]);
+        assert_eq!(summaries[0].sample.len(), 1);
+    }
+
+    #[test]
+    fn test_failed_transaction_discards_recorded_writes() {
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let guard = WriteGuard::new(Arc::new(storage.graph_storage().clone()));
+        let graph: &dyn GraphStorage = &guard;
+
+        graph.add_node(&Node::new("urn:memexia:file:a.md", NodeType::Concept, "A")).unwrap();
+        let result = graph.transaction(|tx| {
+            tx.add_node(&Node::new("urn:memexia:file:b.md", NodeType::Concept, "B"))?;
+            tx.require_node("urn:memexia:file:missing.md")
+        });
+        assert!(result.is_err());
+        assert_eq!(guard.mutations().len(), 1);
+    }
+
+    #[test]
+    fn test_summarize_caps_sample() {
+        let mutations: Vec<_> = (0..5)
+            .map(|i| Mutation { kind: MutationKind::DeleteEdge, id: format!("e{}", i) })
+            .collect();
+        let summaries = summarize(&mutations, 3);
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].count, 5);
+        assert_eq!(summaries[0].sample, vec!["e0", "e1", "e2"]);
+    }
+}
@@ -0,0 +1,326 @@
+//! 追踪与计时模块
+//!
+//! 基于 `tracing` span 记录命令及各耗时阶段的用时：
+//!
+//! - `command` - 每个 CLI 命令（字段：`name`、`root`）
+//! - `walkdir` - 遍历目录
+//! - `parse` - 解析单个 Markdown 文件
+//! - `storage_write` - 写入图存储
+//! - `snapshot` - 导出图快照
+//! - `git_commit` - 创建 Git 提交
+//!
+//! [`init`] 安装的订阅器包含三层：
+//!
+//! - fmt 层：普通日志，`-v` 时输出 debug 级别和 span 关闭时的耗时
+//! - [`TimingLayer`]：按 span 名称汇总耗时，供命令结束时输出摘要
+//! - [`JsonSpanLayer`]：`--trace-json <file>` 时把每个 span 写成一行 JSON
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+/// 追踪选项
+#[derive(Debug, Clone, Default)]
+pub struct TraceOptions {
+    /// 输出 debug 级别日志和 span 耗时
+    pub verbose: bool,
+    /// span JSON 输出文件
+    pub trace_json: Option<PathBuf>,
+}
+
+/// 安装全局追踪订阅器
+///
+/// # Arguments
+///
+/// * `options` - 追踪选项
+///
+/// # Returns
+///
+/// 按阶段汇总的耗时记录
+pub fn init(options: &TraceOptions) -> anyhow::Result<PhaseTimings> {
+    let timings = PhaseTimings::default();
+
+    let level = if options.verbose { LevelFilter::DEBUG } else { LevelFilter::INFO };
+    let span_events = if options.verbose { FmtSpan::CLOSE } else { FmtSpan::NONE };
+    let fmt_layer = tracing_subscriber::fmt::layer()
+        .with_span_events(span_events)
+        .with_filter(level);
+
+    let json_layer = match &options.trace_json {
+        Some(path) => Some(JsonSpanLayer::new(BufWriter::new(File::create(path)?))),
+        None => None,
+    };
+
+    tracing_subscriber::registry()
+        .with(fmt_layer)
+        .with(TimingLayer::new(timings.clone()))
+        .with(json_layer)
+        .try_init()?;
+
+    Ok(timings)
+}
+
+/// 单个阶段的累计耗时
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PhaseStat {
+    /// 累计耗时
+    pub elapsed: Duration,
+    /// span 次数
+    pub count: usize,
+}
+
+/// 按 span 名称汇总的耗时记录
+#[derive(Debug, Clone, Default)]
+pub struct PhaseTimings {
+    phases: Arc<Mutex<BTreeMap<String, PhaseStat>>>,
+}
+
+impl PhaseTimings {
+    /// 记录一次 span 耗时
+    pub fn record(&self, name: &str, elapsed: Duration) {
+        let mut phases = self.phases.lock().unwrap();
+        let stat = phases.entry(name.to_string()).or_default();
+        stat.elapsed += elapsed;
+        stat.count += 1;
+    }
+
+    /// 获取当前汇总（按名称排序）
+    pub fn snapshot(&self) -> Vec<(String, PhaseStat)> {
+        let phases = self.phases.lock().unwrap();
+        phases.iter().map(|(name, stat)| (name.clone(), *stat)).collect()
+    }
+
+    /// 获取指定阶段的汇总
+    pub fn get(&self, name: &str) -> Option<PhaseStat> {
+        self.phases.lock().unwrap().get(name).copied()
+    }
+}
+
+impl fmt::Display for PhaseTimings {
+    /// 格式化为 `parse=12ms(3) storage_write=4ms(3)` 形式
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = self
+            .snapshot()
+            .into_iter()
+            .filter(|(name, _)| name != "command")
+            .map(|(name, stat)| format!("{}={}ms({})", name, stat.elapsed.as_millis(), stat.count))
+            .collect();
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+/// span 开始时间（存放在 span 扩展中）
+struct SpanStart(Instant);
+
+/// 按名称汇总 span 耗时的层
+pub struct TimingLayer {
+    timings: PhaseTimings,
+}
+
+impl TimingLayer {
+    /// 创建层，耗时写入 `timings`
+    pub fn new(timings: PhaseTimings) -> Self {
+        Self { timings }
+    }
+}
+
+impl<S> Layer<S> for TimingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            let mut extensions = span.extensions_mut();
+            if extensions.get_mut::<SpanStart>().is_none() {
+                extensions.insert(SpanStart(Instant::now()));
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(&id) {
+            if let Some(start) = span.extensions().get::<SpanStart>() {
+                self.timings.record(span.name(), start.0.elapsed());
+            }
+        }
+    }
+}
+
+/// span 字段（存放在 span 扩展中）
+struct SpanFields(serde_json::Map<String, serde_json::Value>);
+
+/// 收集 span 字段的访问器
+struct JsonVisitor<'a>(&'a mut serde_json::Map<String, serde_json::Value>);
+
+impl Visit for JsonVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name().to_string(), format!("{:?}", value).into());
+    }
+}
+
+/// 将每个关闭的 span 写成一行 JSON 的层
+///
+/// 每行包含 `id`、`parent_id`、`name`、`target`、`fields` 和 `elapsed_ms`，
+/// 可通过 `id` / `parent_id` 还原调用层级
+pub struct JsonSpanLayer<W: Write> {
+    writer: Mutex<W>,
+}
+
+impl<W: Write> JsonSpanLayer<W> {
+    /// 创建写入 `writer` 的层
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+        }
+    }
+}
+
+impl<S, W> Layer<S> for JsonSpanLayer<W>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: Write + Send + 'static,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            let mut fields = serde_json::Map::new();
+            attrs.record(&mut JsonVisitor(&mut fields));
+
+            let mut extensions = span.extensions_mut();
+            extensions.insert(SpanFields(fields));
+            if extensions.get_mut::<SpanStart>().is_none() {
+                extensions.insert(SpanStart(Instant::now()));
+            }
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &tracing::span::Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(fields) = span.extensions_mut().get_mut::<SpanFields>() {
+                values.record(&mut JsonVisitor(&mut fields.0));
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+
+        let extensions = span.extensions();
+        let elapsed = extensions.get::<SpanStart>().map(|s| s.0.elapsed()).unwrap_or_default();
+        let fields = extensions.get::<SpanFields>().map(|f| f.0.clone()).unwrap_or_default();
+
+        let record = serde_json::json!({
+            "id": id.into_u64(),
+            "parent_id": span.parent().map(|p| p.id().into_u64()),
+            "name": span.name(),
+            "target": span.metadata().target(),
+            "fields": fields,
+            "elapsed_ms": elapsed.as_secs_f64() * 1000.0,
+        });
+
+        let mut writer = self.writer.lock().unwrap();
+        let _ = writeln!(writer, "{}", record);
+        let _ = writer.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Repository;
+    use tempfile::TempDir;
+
+    /// 可在测试中读取的共享缓冲区
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_commit_span_hierarchy() {
+        let temp = TempDir::new().unwrap();
+        let mut repo = Repository::init(temp.path()).unwrap();
+        let file = temp.path().join("idea.md");
+        std::fs::write(&file, "# Idea\n\n[[Other]]").unwrap();
+        repo.add(&[file]).unwrap();
+
+        let buffer = SharedBuffer::default();
+        let timings = PhaseTimings::default();
+        let subscriber = tracing_subscriber::registry()
+            .with(TimingLayer::new(timings.clone()))
+            .with(JsonSpanLayer::new(buffer.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            repo.commit("Add idea").unwrap();
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let spans: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        let find = |name: &str| spans.iter().find(|s| s["name"] == name).unwrap();
+        let commit = find("commit");
+        assert!(commit["parent_id"].is_null());
+
+        // 各阶段都是 commit span 的直接子 span
+        for phase in ["parse", "storage_write", "snapshot", "git_commit"] {
+            assert_eq!(find(phase)["parent_id"], commit["id"], "phase {}", phase);
+        }
+        assert_eq!(find("parse")["fields"]["file"], "idea.md");
+
+        assert_eq!(timings.get("commit").unwrap().count, 1);
+        assert_eq!(timings.get("parse").unwrap().count, 1);
+    }
+
+    #[test]
+    fn test_phase_timings_display() {
+        let timings = PhaseTimings::default();
+        timings.record("parse", Duration::from_millis(5));
+        timings.record("parse", Duration::from_millis(7));
+        timings.record("command", Duration::from_millis(30));
+        timings.record("git_commit", Duration::from_millis(3));
+
+        assert_eq!(timings.to_string(), "git_commit=3ms(1) parse=12ms(2)");
+        assert_eq!(timings.get("parse").unwrap().count, 2);
+    }
+}
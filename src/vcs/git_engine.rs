@@ -3,10 +3,10 @@
 //! 使用 libgit2 (git2 crate) 内嵌 Git 功能
 
 use std::path::{Path, PathBuf};
-use anyhow::{Result, Context, anyhow};
+use crate::error::{MemexiaError, Result};
 use git2::{Repository, Oid, Signature};
 use chrono::{DateTime, Utc, TimeZone};
-use crate::vcs::CommitInfo;
+use crate::vcs::{CommitInfo, FileRevision};
 
 /// Git 引擎
 pub struct GitEngine {
@@ -23,12 +23,10 @@ impl GitEngine {
 
         // 如果 .git 不存在，则初始化
         if !git_path.exists() {
-            Repository::init(root)
-                .with_context(|| format!("Failed to init git repo at {:?}", root))?;
+            Repository::init(root)?;
         }
 
-        let repo = Repository::open(root)
-            .with_context(|| format!("Failed to open git repo at {:?}", root))?;
+        let repo = Repository::open(root)?;
 
         Ok(Self {
             repo,
@@ -38,8 +36,7 @@ impl GitEngine {
 
     /// 打开已存在的 Git 仓库
     pub fn open(root: &Path) -> Result<Self> {
-        let repo = Repository::open(root)
-            .with_context(|| format!("Failed to open git repo at {:?}", root))?;
+        let repo = Repository::open(root)?;
 
         Ok(Self {
             repo,
@@ -54,7 +51,7 @@ impl GitEngine {
 
     /// 获取 Git 配置
     pub fn config(&self) -> Result<git2::Config> {
-        self.repo.config().map_err(|e| anyhow!("Failed to get git config: {}", e))
+        Ok(self.repo.config()?)
     }
 
     /// 暂存文件
@@ -78,6 +75,8 @@ impl GitEngine {
         }
 
         index.add_all(&paths, git2::IndexAddOption::DEFAULT, None)?;
+        // 与 `git add <path>` 一致：工作区中已不存在的文件从索引中移除（如被移动的笔记）
+        index.update_all(&paths, None)?;
         index.write()?;
 
         Ok(())
@@ -192,13 +191,12 @@ impl GitEngine {
 
             let timestamp = format_timestamp(commit.time());
 
-            commits.push(CommitInfo {
-                oid: oid.to_string(),
-                message: commit.message().unwrap_or("").to_string(),
-                graph_hash: None, // 需要从外部补充
-                author: commit.author().to_string(),
+            commits.push(CommitInfo::from_raw(
+                oid.to_string(),
+                commit.message().unwrap_or(""),
+                commit.author().to_string(),
                 timestamp,
-            });
+            ));
         }
 
         Ok(commits)
@@ -209,6 +207,66 @@ impl GitEngine {
         Some(self.repo.head().ok()?.peel_to_commit().ok()?.id())
     }
 
+    /// 解析修订表达式为提交 OID
+    ///
+    /// 支持完整或缩写的提交哈希（至少 4 位且唯一）、`HEAD`、`HEAD~N` 和分支名，
+    /// 语法与 `git rev-parse` 一致
+    ///
+    /// # Arguments
+    ///
+    /// * `rev` - 修订表达式
+    ///
+    /// # Returns
+    ///
+    /// 完整的提交 OID；表达式有歧义、无法识别或不指向提交时返回 `Validation` 错误
+    pub fn resolve_revision(&self, rev: &str) -> Result<Oid> {
+        let rev = rev.trim();
+        let object = self.repo.revparse_single(rev).map_err(|e| match e.code() {
+            git2::ErrorCode::Ambiguous => MemexiaError::Validation(format!(
+                "Ambiguous revision '{}': several objects match this prefix, use more characters",
+                rev
+            )),
+            git2::ErrorCode::NotFound | git2::ErrorCode::InvalidSpec | git2::ErrorCode::UnbornBranch => {
+                MemexiaError::Validation(format!("Unknown revision '{}'", rev))
+            }
+            _ => e.into(),
+        })?;
+        let commit = object
+            .peel_to_commit()
+            .map_err(|_| MemexiaError::Validation(format!("Revision '{}' does not point to a commit", rev)))?;
+        Ok(commit.id())
+    }
+
+    /// 仓库中是否存在该提交（完整的 40 位哈希）
+    pub fn commit_exists(&self, oid: &str) -> bool {
+        Oid::from_str(oid).is_ok_and(|oid| self.repo.find_commit(oid).is_ok())
+    }
+
+    /// HEAD 可达的根提交（最早的无父提交），还没有提交时为 None
+    ///
+    /// 克隆自同一仓库的副本根提交相同，可用作仓库标识
+    pub fn root_commit(&self) -> Result<Option<String>> {
+        if self.get_head_oid().is_none() {
+            return Ok(None);
+        }
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push_head()?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+        Ok(revwalk.next().transpose()?.map(|oid| oid.to_string()))
+    }
+
+    /// 所有本地和远程跟踪分支末端的提交
+    pub fn branch_tips(&self) -> Result<Vec<String>> {
+        let mut tips = Vec::new();
+        for branch in self.repo.branches(None)? {
+            let (branch, _) = branch?;
+            if let Some(oid) = branch.get().target() {
+                tips.push(oid.to_string());
+            }
+        }
+        Ok(tips)
+    }
+
     /// 获取当前 HEAD 的提交信息
     pub fn head_info(&self) -> Result<Option<CommitInfo>> {
         // 尝试获取 HEAD 引用，如果不存在（无提交）则返回 None
@@ -241,16 +299,14 @@ impl GitEngine {
 
         let oid = commit.id();
         let author = commit.author().to_string();
-        let message = commit.message().unwrap_or("").to_string();
         let timestamp = format_timestamp(commit.time());
 
-        Ok(Some(CommitInfo {
-            oid: oid.to_string(),
-            message,
-            graph_hash: None,
+        Ok(Some(CommitInfo::from_raw(
+            oid.to_string(),
+            commit.message().unwrap_or(""),
             author,
             timestamp,
-        }))
+        )))
     }
 
     /// 获取父提交 OID
@@ -285,6 +341,177 @@ impl GitEngine {
 
         Ok(output.trim().to_string())
     }
+
+    /// 工作区中内容已变化的已跟踪文件
+    ///
+    /// # Arguments
+    ///
+    /// * `include_untracked` - 是否同时返回未跟踪的新文件
+    ///
+    /// # Returns
+    ///
+    /// 相对于仓库根目录的路径（不含已删除的文件）
+    pub fn worktree_changes(&self, include_untracked: bool) -> Result<Vec<PathBuf>> {
+        let mut options = git2::StatusOptions::new();
+        options
+            .include_untracked(include_untracked)
+            .recurse_untracked_dirs(include_untracked)
+            .include_ignored(false);
+
+        let mut paths = Vec::new();
+        for entry in self.repo.statuses(Some(&mut options))?.iter() {
+            let status = entry.status();
+            let changed = status.intersects(git2::Status::WT_MODIFIED | git2::Status::WT_TYPECHANGE)
+                || (include_untracked && status.contains(git2::Status::WT_NEW));
+            if let (true, Some(path)) = (changed, entry.path()) {
+                paths.push(PathBuf::from(path));
+            }
+        }
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// 修改过指定文件的提交
+    ///
+    /// 沿首个父提交从 HEAD 向前遍历，比较每个提交与父提交中该路径的 blob；
+    /// 文件在某个提交中由重命名产生时，继续追踪重命名前的路径
+    ///
+    /// # Arguments
+    ///
+    /// * `relative` - 相对于仓库根目录的文件路径（HEAD 中的路径）
+    ///
+    /// # Returns
+    ///
+    /// 从新到旧的文件变更；尚无提交时为空
+    pub fn file_history(&self, relative: &Path) -> Result<Vec<FileRevision>> {
+        let Ok(head) = self.repo.head().and_then(|h| h.peel_to_commit()) else {
+            return Ok(Vec::new());
+        };
+
+        let mut revisions = Vec::new();
+        let mut path = relative.to_path_buf();
+        let mut next = Some(head);
+        while let Some(commit) = next {
+            let parent = commit.parent(0).ok();
+            let tree = commit.tree()?;
+            let parent_tree = parent.as_ref().map(|p| p.tree()).transpose()?;
+
+            let current = tree.get_path(&path).ok().map(|e| e.id());
+            let mut previous_path = path.clone();
+            let mut before = parent_tree.as_ref().and_then(|t| t.get_path(&path).ok()).map(|e| e.id());
+            let mut renamed_from = None;
+
+            // 父提交中没有该路径：检查是否由重命名产生
+            if current.is_some() && before.is_none() {
+                if let Some(parent_tree) = &parent_tree {
+                    if let Some(old) = self.renamed_source(parent_tree, &tree, &path)? {
+                        before = parent_tree.get_path(&old).ok().map(|e| e.id());
+                        previous_path = old.clone();
+                        renamed_from = Some(old);
+                    }
+                }
+            }
+
+            if current != before {
+                let read = |oid: Option<Oid>| -> Result<Option<Vec<u8>>> {
+                    oid.map(|id| Ok(self.repo.find_blob(id)?.content().to_vec())).transpose()
+                };
+                let (message, _) = crate::vcs::message::split_trailers(commit.message().unwrap_or(""));
+                revisions.push(FileRevision {
+                    oid: commit.id().to_string(),
+                    message,
+                    time: Utc.timestamp_opt(commit.time().seconds(), 0).single().unwrap_or_default(),
+                    path: path.clone(),
+                    renamed_from,
+                    content: read(current)?,
+                    previous: read(before)?,
+                });
+            }
+
+            path = previous_path;
+            next = parent;
+        }
+        Ok(revisions)
+    }
+
+    /// 两棵树之间重命名为 `path` 的源路径
+    fn renamed_source(&self, old: &git2::Tree, new: &git2::Tree, path: &Path) -> Result<Option<PathBuf>> {
+        let mut diff = self.repo.diff_tree_to_tree(Some(old), Some(new), None)?;
+        let mut find = git2::DiffFindOptions::new();
+        find.renames(true);
+        diff.find_similar(Some(&mut find))?;
+        Ok(diff
+            .deltas()
+            .filter(|delta| delta.status() == git2::Delta::Renamed && delta.new_file().path() == Some(path))
+            .find_map(|delta| delta.old_file().path().map(Path::to_path_buf)))
+    }
+
+    /// HEAD 与工作区之间被重命名的文件
+    ///
+    /// 包括 `git mv` 暂存的重命名和工作区中直接移动的文件（未跟踪的新文件参与相似度匹配）
+    ///
+    /// # Arguments
+    ///
+    /// * `pathspecs` - 只比较匹配的路径（如 `*.md`），避免与 `.memexia/objects` 中的副本配对；为空时比较所有文件
+    ///
+    /// # Returns
+    ///
+    /// （旧路径，新路径）列表，路径相对于仓库根目录；尚无提交时为空
+    pub fn worktree_renames(&self, pathspecs: &[String]) -> Result<Vec<(PathBuf, PathBuf)>> {
+        let Ok(tree) = self.repo.head().and_then(|h| h.peel_to_tree()) else {
+            return Ok(Vec::new());
+        };
+        let mut options = git2::DiffOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true);
+        for spec in pathspecs {
+            options.pathspec(spec);
+        }
+        let mut diff = self.repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut options))?;
+
+        let mut find = git2::DiffFindOptions::new();
+        find.renames(true).for_untracked(true);
+        diff.find_similar(Some(&mut find))?;
+
+        let mut renames = Vec::new();
+        for delta in diff.deltas() {
+            if delta.status() != git2::Delta::Renamed {
+                continue;
+            }
+            if let (Some(old), Some(new)) = (delta.old_file().path(), delta.new_file().path()) {
+                renames.push((old.to_path_buf(), new.to_path_buf()));
+            }
+        }
+        Ok(renames)
+    }
+
+    /// 从 HEAD 检出被删除的文件
+    ///
+    /// # Arguments
+    ///
+    /// * `relative` - 相对于仓库根目录的文件路径
+    ///
+    /// # Returns
+    ///
+    /// 文件已存在、尚无提交或 HEAD 中没有该文件时返回 false
+    pub fn restore_file(&self, relative: &Path) -> Result<bool> {
+        let target = self.path.join(relative);
+        if target.exists() {
+            return Ok(false);
+        }
+        let Ok(tree) = self.repo.head().and_then(|h| h.peel_to_tree()) else {
+            return Ok(false);
+        };
+        let Ok(entry) = tree.get_path(relative) else {
+            return Ok(false);
+        };
+
+        let blob = self.repo.find_blob(entry.id())?;
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&target, blob.content())?;
+        Ok(true)
+    }
 }
 
 /// 格式化 git2::Time 为字符串
@@ -2,15 +2,45 @@
 //!
 //! 记录图数据的变化历史，支持快照存储和差异计算
 //! 包含节点快照和推导链追踪功能
+//!
+//! ## 快照格式
+//!
+//! 快照文件是图的 N-Quads 导出，快照哈希为其内容哈希。格式版本记录在 `.meta` 中：
+//!
+//! - 版本 1（`.meta` 中没有 `format` 字段）：按存储迭代顺序导出，相同的图可能得到不同的哈希
+//! - 版本 2（[`SNAPSHOT_FORMAT`]）：按规范顺序导出（见 [`crate::storage::nquads`]），
+//!   相同的图总是得到相同的字节和哈希
+//!
+//! 两种版本都按行读取，差异计算不依赖行序，旧快照仍然可以读取和比较
 
 use std::path::{Path, PathBuf};
 use std::fs;
 use std::io::Write;
-use anyhow::{Result, Context};
+use crate::error::{MemexiaError, Result};
+use anyhow::Context;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use crate::storage::Storage;
 use crate::core::object::hash_content;
+use crate::core::retention::RetentionPolicy;
+use crate::events::RepoEvent;
+use crate::vcs::snapshot_stats::{self, SnapshotStats, StatsPoint};
+use crate::vcs::snapshot_verify::{self, SnapshotProblem, SnapshotStatus, VerifyReport};
+use crate::vcs::traversal::{DerivationIndex, Traversal, TraversalLimits};
+use crate::storage::{nquads, RelationType};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+/// `rdf:type` 的完整 IRI（旧快照可能使用）
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+
+/// 自动快照在提交关联中的键前缀（`auto:<时间>`）
+pub const AUTO_SNAPSHOT_PREFIX: &str = "auto:";
+
+/// 当前快照格式版本：按规范顺序导出
+pub const SNAPSHOT_FORMAT: u32 = 2;
+
+/// 被保留策略清理的提交关联在 `commit-links` 行尾的标记
+const PRUNED_MARKER: &str = " [pruned]";
 
 /// 将节点ID转换为安全的目录名
 /// 替换 Windows 不允许的字符（如冒号）为空格下划线
@@ -18,6 +48,28 @@ fn sanitize_node_id_for_path(node_id: &str) -> String {
     node_id.replace(':', "_")
 }
 
+/// 解析 `commit-links` 中的一行：`commit -> graph [@ 时间][ [pruned]]`
+fn parse_commit_link(line: &str) -> Option<CommitLink> {
+    let (line, pruned) = match line.strip_suffix(PRUNED_MARKER) {
+        Some(line) => (line, true),
+        None => (line, false),
+    };
+    let (c_hash, rest) = line.split_once(" -> ")?;
+    let (g_hash, recorded_at) = match rest.split_once(" @ ") {
+        Some((g_hash, at)) => (
+            g_hash.trim(),
+            DateTime::parse_from_rfc3339(at.trim()).ok().map(|t| t.with_timezone(&Utc)),
+        ),
+        None => (rest.trim(), None),
+    };
+    Some(CommitLink {
+        commit_hash: c_hash.trim().to_string(),
+        graph_hash: g_hash.to_string(),
+        recorded_at,
+        pruned,
+    })
+}
+
 /// 图历史追踪器
 pub struct GraphHistory {
     /// 历史目录
@@ -77,6 +129,11 @@ impl GraphHistory {
         })
     }
 
+    /// 历史目录（`.memexia/history`）
+    pub fn path(&self) -> &Path {
+        &self.root
+    }
+
     /// 创建图快照
     ///
     /// 将当前图数据导出为 N-Quads，计算哈希并存储
@@ -89,10 +146,207 @@ impl GraphHistory {
 
         // 存储快照
         self.store_snapshot(&hash, &nquads)?;
+        storage.events().emit(RepoEvent::SnapshotTaken { hash: hash.clone() });
 
         Ok(hash)
     }
 
+    /// 创建自动快照
+    ///
+    /// 图哈希与上一次自动快照相同时不做任何事；否则存储快照，以 `auto:<时间>` 为键记录到提交关联，
+    /// 并只保留最近 `keep` 个自动快照
+    ///
+    /// # Arguments
+    ///
+    /// * `storage` - 存储后端
+    /// * `keep` - 保留的自动快照个数
+    ///
+    /// # Returns
+    ///
+    /// 新记录的关联；图未变化时返回 None
+    pub fn auto_snapshot(&self, storage: &Storage, keep: usize) -> Result<Option<CommitLink>> {
+        let nquads = storage.graph().export_nquads()?;
+        let hash = hash_content(nquads.as_bytes());
+
+        if self.auto_snapshots()?.last().is_some_and(|link| link.graph_hash == hash) {
+            return Ok(None);
+        }
+
+        self.store_snapshot(&hash, &nquads)?;
+        storage.events().emit(RepoEvent::SnapshotTaken { hash: hash.clone() });
+        let now = Utc::now();
+        let key = format!("{}{}", AUTO_SNAPSHOT_PREFIX, now.format("%Y%m%dT%H%M%S%.3fZ"));
+        self.record_at(&key, &hash, now)?;
+        self.prune_auto_snapshots(keep)?;
+
+        Ok(Some(CommitLink {
+            commit_hash: key,
+            graph_hash: hash,
+            recorded_at: Some(now),
+            pruned: false,
+        }))
+    }
+
+    /// 所有自动快照关联（按记录顺序）
+    pub fn auto_snapshots(&self) -> Result<Vec<CommitLink>> {
+        Ok(self
+            .commit_links()?
+            .into_iter()
+            .filter(|link| link.commit_hash.starts_with(AUTO_SNAPSHOT_PREFIX))
+            .collect())
+    }
+
+    /// 只保留最近 `keep` 个自动快照
+    ///
+    /// 从提交关联中删除更早的自动快照记录；快照文件不再被任何关联引用时一并删除
+    ///
+    /// # Returns
+    ///
+    /// 删除的自动快照个数
+    pub fn prune_auto_snapshots(&self, keep: usize) -> Result<usize> {
+        let link_file = self.root.join("commit-links");
+        if !link_file.exists() {
+            return Ok(0);
+        }
+
+        let content = fs::read_to_string(&link_file)?;
+        let lines: Vec<&str> = content.lines().collect();
+        let auto_count = lines.iter().filter(|l| l.starts_with(AUTO_SNAPSHOT_PREFIX)).count();
+        let mut to_prune = auto_count.saturating_sub(keep);
+        if to_prune == 0 {
+            return Ok(0);
+        }
+        let pruned = to_prune;
+
+        let mut kept = Vec::with_capacity(lines.len());
+        let mut removed_hashes = Vec::new();
+        for line in lines {
+            if to_prune > 0 && line.starts_with(AUTO_SNAPSHOT_PREFIX) {
+                to_prune -= 1;
+                if let Some((_, rest)) = line.split_once(" -> ") {
+                    removed_hashes.push(rest.split(" @ ").next().unwrap_or(rest).trim().to_string());
+                }
+                continue;
+            }
+            kept.push(line);
+        }
+
+        let mut output = kept.join("\n");
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        fs::write(&link_file, &output)?;
+
+        for hash in removed_hashes {
+            let referenced = kept.iter().any(|l| {
+                !l.ends_with(PRUNED_MARKER) && l.split_once(" -> ").is_some_and(|(_, rest)| rest.starts_with(&hash))
+            });
+            if !referenced {
+                self.remove_snapshot(&hash);
+            }
+        }
+
+        Ok(pruned)
+    }
+
+    /// 按保留策略清理提交快照
+    ///
+    /// 自动快照由 `watch.auto_snapshot_keep` 单独管理，不参与策略。策略未保留的提交关联在
+    /// `commit-links` 中标记为已清理（此后读取其快照返回 [`MemexiaError::SnapshotPruned`]），
+    /// 不再被任何保留的关联引用的快照文件连同 `.meta` 一起删除
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - 保留策略
+    /// * `protected` - 无论策略如何都保留的提交（HEAD 和各分支末端）
+    /// * `dry_run` - 只计算不修改
+    ///
+    /// # Returns
+    ///
+    /// 被清理的关联和删除的快照
+    pub fn apply_retention(
+        &self,
+        policy: &RetentionPolicy,
+        protected: &[String],
+        dry_run: bool,
+    ) -> Result<RetentionReport> {
+        let links = self.commit_links()?;
+        let candidates: Vec<usize> = (0..links.len())
+            .filter(|&i| !links[i].pruned && !links[i].commit_hash.starts_with(AUTO_SNAPSHOT_PREFIX))
+            .collect();
+        let selected: Vec<CommitLink> = candidates.iter().map(|&i| links[i].clone()).collect();
+
+        let mut prune = vec![false; links.len()];
+        for (&index, keep) in candidates.iter().zip(policy.retained(&selected)) {
+            prune[index] = !keep && !protected.contains(&links[index].commit_hash);
+        }
+
+        let mut report = RetentionReport::default();
+        if !prune.contains(&true) {
+            return Ok(report);
+        }
+        let referenced: HashSet<&str> = (0..links.len())
+            .filter(|&i| !links[i].pruned && !prune[i])
+            .map(|i| links[i].graph_hash.as_str())
+            .collect();
+        for (link, _) in links.iter().zip(&prune).filter(|(_, prune)| **prune) {
+            report.pruned.push(link.clone());
+            if !referenced.contains(link.graph_hash.as_str()) && !report.removed_snapshots.contains(&link.graph_hash) {
+                report.removed_snapshots.push(link.graph_hash.clone());
+            }
+        }
+        if dry_run {
+            return Ok(report);
+        }
+
+        // 按与 commit_links 相同的顺序定位行，给被清理的关联加上标记
+        let link_file = self.root.join("commit-links");
+        let content = fs::read_to_string(&link_file)?;
+        let mut index = 0;
+        let mut output = String::with_capacity(content.len());
+        for line in content.lines() {
+            output.push_str(line);
+            if line.contains(" -> ") {
+                if prune[index] {
+                    output.push_str(PRUNED_MARKER);
+                }
+                index += 1;
+            }
+            output.push('\n');
+        }
+        fs::write(&link_file, output)?;
+
+        for hash in &report.removed_snapshots {
+            self.remove_snapshot(hash);
+        }
+        tracing::info!(links = report.pruned.len(), snapshots = report.removed_snapshots.len(), "retention applied");
+        Ok(report)
+    }
+
+    /// 删除快照文件及其元数据（哈希不合法时忽略）
+    fn remove_snapshot(&self, hash: &str) {
+        if hash.len() > 2 && hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            let (dir_name, file_name) = hash.split_at(2);
+            let snapshot_path = self.snapshots_dir.join(dir_name).join(file_name);
+            let _ = fs::remove_file(snapshot_path.with_extension("meta"));
+            let _ = fs::remove_file(snapshot_path.with_extension(snapshot_stats::CACHE_EXTENSION));
+            let _ = fs::remove_file(snapshot_path);
+        }
+    }
+
+    /// 快照是否已被保留策略清理
+    fn is_pruned(&self, hash: &str) -> Result<bool> {
+        let link_file = self.root.join("commit-links");
+        if !link_file.exists() {
+            return Ok(false);
+        }
+        Ok(fs::read_to_string(&link_file)?.lines().any(|line| {
+            line.strip_suffix(PRUNED_MARKER)
+                .and_then(|line| line.split_once(" -> "))
+                .is_some_and(|(_, rest)| rest.split(" @ ").next().unwrap_or(rest).trim() == hash)
+        }))
+    }
+
     /// 存储快照
     fn store_snapshot(&self, hash: &str, nquads: &str) -> Result<()> {
         let (dir_name, file_name) = hash.split_at(2);
@@ -107,6 +361,7 @@ impl GraphHistory {
             hash: hash.to_string(),
             size: nquads.len(),
             timestamp: Utc::now(),
+            format: SNAPSHOT_FORMAT,
         };
 
         let meta_path = snapshot_path.with_extension("meta");
@@ -121,11 +376,19 @@ impl GraphHistory {
 
     /// 获取快照
     pub fn get_snapshot(&self, hash: &str) -> Result<GraphSnapshot> {
+        // 快照哈希为十六进制字符串，其他输入不可能对应快照文件
+        if hash.len() < 3 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(MemexiaError::SnapshotNotFound { hash: hash.to_string() });
+        }
+
         let (dir_name, file_name) = hash.split_at(2);
         let snapshot_path = self.snapshots_dir.join(dir_name).join(file_name);
 
         if !snapshot_path.exists() {
-            anyhow::bail!("Snapshot not found: {}", hash);
+            if self.is_pruned(hash)? {
+                return Err(MemexiaError::SnapshotPruned { hash: hash.to_string() });
+            }
+            return Err(MemexiaError::SnapshotNotFound { hash: hash.to_string() });
         }
 
         let nquads = fs::read_to_string(&snapshot_path)?;
@@ -140,6 +403,7 @@ impl GraphHistory {
                 hash: hash.to_string(),
                 size: nquads.len(),
                 timestamp: Utc::now(),
+                format: 1,
             }
         };
 
@@ -147,13 +411,214 @@ impl GraphHistory {
             hash: meta.hash,
             nquads,
             timestamp: meta.timestamp,
+            format: meta.format,
         })
     }
 
+    /// 统计快照的节点数、边数和各类型节点数，见 [`snapshot_stats`]
+    ///
+    /// 优先读取快照旁的 `.stats.json` 缓存，没有缓存时流式读取快照并写入缓存
+    ///
+    /// # Returns
+    ///
+    /// 快照已被清理或不存在时返回 None
+    pub fn snapshot_stats(&self, hash: &str) -> Result<Option<SnapshotStats>> {
+        if !self.has_snapshot(hash) {
+            return Ok(None);
+        }
+        let (dir_name, file_name) = hash.split_at(2);
+        let snapshot_path = self.snapshots_dir.join(dir_name).join(file_name);
+        let cache_path = snapshot_path.with_extension(snapshot_stats::CACHE_EXTENSION);
+        if let Some(stats) = fs::read_to_string(&cache_path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+        {
+            return Ok(Some(stats));
+        }
+
+        let file = fs::File::open(&snapshot_path)?;
+        let stats = SnapshotStats::compute(std::io::BufReader::new(file))?;
+        // 缓存写入失败（如只读仓库）不影响结果
+        if let Err(e) = fs::write(&cache_path, serde_json::to_string(&stats)?) {
+            tracing::debug!(path = %cache_path.display(), error = %e, "failed to cache snapshot stats");
+        }
+        Ok(Some(stats))
+    }
+
+    /// 本地所有快照的哈希（按哈希排序）
+    pub fn list_snapshots(&self) -> Result<Vec<String>> {
+        let mut hashes = Vec::new();
+        if !self.snapshots_dir.exists() {
+            return Ok(hashes);
+        }
+        for dir in fs::read_dir(&self.snapshots_dir)? {
+            let dir = dir?;
+            let prefix = dir.file_name().to_string_lossy().to_string();
+            if !dir.file_type()?.is_dir() || prefix.len() != 2 || !prefix.chars().all(|c| c.is_ascii_hexdigit()) {
+                continue;
+            }
+            for file in fs::read_dir(dir.path())? {
+                let name = file?.file_name().to_string_lossy().to_string();
+                // `.meta` 和统计缓存带扩展名，快照文件没有
+                if !name.is_empty() && name.chars().all(|c| c.is_ascii_hexdigit()) {
+                    hashes.push(format!("{}{}", prefix, name));
+                }
+            }
+        }
+        hashes.sort();
+        Ok(hashes)
+    }
+
+    /// 校验快照，见 [`snapshot_verify`]
+    ///
+    /// # Arguments
+    ///
+    /// * `only` - 只校验该快照（完整哈希或唯一前缀）；None 时校验全部快照并检查提交关联
+    /// * `repair_meta` - 按内容重新生成缺失或过期的 `.meta`
+    pub fn verify_snapshots(&self, only: Option<&str>, repair_meta: bool) -> Result<VerifyReport> {
+        let all = self.list_snapshots()?;
+        let links = self.commit_links()?;
+        let hashes = match only {
+            None => all,
+            Some(prefix) => {
+                let matches: Vec<String> = all.into_iter().filter(|hash| hash.starts_with(prefix)).collect();
+                match matches.len() {
+                    1 => matches,
+                    0 if self.is_pruned(prefix)? => {
+                        return Err(MemexiaError::SnapshotPruned { hash: prefix.to_string() })
+                    }
+                    0 => return Err(MemexiaError::SnapshotNotFound { hash: prefix.to_string() }),
+                    _ => {
+                        return Err(MemexiaError::Validation(format!(
+                            "Snapshot prefix {} is ambiguous ({} matches)",
+                            prefix,
+                            matches.len()
+                        )))
+                    }
+                }
+            }
+        };
+
+        let mut report = VerifyReport::default();
+        for hash in hashes {
+            report.snapshots.push(self.verify_snapshot(&hash, &links, repair_meta)?);
+        }
+        if only.is_none() {
+            report.dangling_links = links
+                .into_iter()
+                .filter(|link| !link.pruned && !self.has_snapshot(&link.graph_hash))
+                .collect();
+        }
+        Ok(report)
+    }
+
+    /// 校验单个快照，必要时重新生成 `.meta`
+    fn verify_snapshot(&self, hash: &str, links: &[CommitLink], repair_meta: bool) -> Result<SnapshotStatus> {
+        let (dir_name, file_name) = hash.split_at(2);
+        let snapshot_path = self.snapshots_dir.join(dir_name).join(file_name);
+        let meta_path = snapshot_path.with_extension("meta");
+        let mut status = SnapshotStatus {
+            hash: hash.to_string(),
+            size: 0,
+            problems: Vec::new(),
+            repaired: None,
+        };
+
+        let content = match fs::read(&snapshot_path) {
+            Ok(content) => content,
+            Err(e) => {
+                status.problems.push(SnapshotProblem::Unreadable { message: e.to_string() });
+                return Ok(status);
+            }
+        };
+        status.size = content.len() as u64;
+
+        let actual = hash_content(&content);
+        let intact = actual == hash;
+        if !intact {
+            status.problems.push(SnapshotProblem::HashMismatch { actual });
+        }
+        if let Some((line, message)) = snapshot_verify::check_nquads(&content) {
+            status.problems.push(SnapshotProblem::InvalidNQuads { line, message });
+        }
+
+        let meta: Option<std::result::Result<SnapshotMetadata, String>> = match fs::read_to_string(&meta_path) {
+            Ok(json) => Some(serde_json::from_str(&json).map_err(|e| e.to_string())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => Some(Err(e.to_string())),
+        };
+        let meta_problem = match &meta {
+            None => Some(SnapshotProblem::MissingMeta),
+            Some(Err(reason)) => Some(SnapshotProblem::StaleMeta { reason: reason.clone() }),
+            Some(Ok(meta)) if meta.hash != hash => Some(SnapshotProblem::StaleMeta {
+                reason: format!("records hash {}", meta.hash),
+            }),
+            Some(Ok(meta)) if meta.size != content.len() => Some(SnapshotProblem::StaleMeta {
+                reason: format!("records size {} (actual {})", meta.size, content.len()),
+            }),
+            Some(Ok(_)) => None,
+        };
+        let Some(meta_problem) = meta_problem else {
+            return Ok(status);
+        };
+        // 内容已损坏时不能据此生成元数据
+        if !repair_meta || !intact {
+            status.problems.push(meta_problem);
+            return Ok(status);
+        }
+
+        let existing = meta.and_then(|meta| meta.ok());
+        let timestamp = existing
+            .as_ref()
+            .map(|meta| meta.timestamp)
+            .or_else(|| links.iter().filter(|l| l.graph_hash == hash).filter_map(|l| l.recorded_at).min())
+            .or_else(|| fs::metadata(&snapshot_path).and_then(|m| m.modified()).ok().map(DateTime::<Utc>::from))
+            .unwrap_or_else(Utc::now);
+        let format = match existing {
+            Some(meta) => meta.format,
+            None if snapshot_verify::is_canonical(&String::from_utf8_lossy(&content)) => SNAPSHOT_FORMAT,
+            None => legacy_snapshot_format(),
+        };
+        let meta = SnapshotMetadata {
+            hash: hash.to_string(),
+            size: content.len(),
+            timestamp,
+            format,
+        };
+        fs::write(&meta_path, serde_json::to_string(&meta)?)?;
+        status.repaired = Some(meta_problem);
+        Ok(status)
+    }
+
+    /// 按提交顺序（从旧到新）统计每个提交的图快照（`memexia stats --history`）
+    ///
+    /// 不含自动快照；快照已被清理的提交保留在时间线中，统计为 None
+    pub fn stats_history(&self) -> Result<Vec<StatsPoint>> {
+        let mut points = Vec::new();
+        for link in self.commit_links()? {
+            if link.commit_hash.starts_with(AUTO_SNAPSHOT_PREFIX) {
+                continue;
+            }
+            let stats = if link.pruned { None } else { self.snapshot_stats(&link.graph_hash)? };
+            points.push(StatsPoint {
+                commit: link.commit_hash,
+                graph_hash: link.graph_hash,
+                recorded_at: link.recorded_at,
+                stats,
+            });
+        }
+        Ok(points)
+    }
+
     /// 记录提交关联
     ///
-    /// 将 Git 提交哈希与图快照哈希关联
+    /// 将 Git 提交哈希与图快照哈希关联，并记录关联时间
     pub fn record(&self, commit_hash: &str, graph_hash: &str) -> Result<()> {
+        self.record_at(commit_hash, graph_hash, Utc::now())
+    }
+
+    /// 以指定时间记录提交关联
+    fn record_at(&self, commit_hash: &str, graph_hash: &str, at: DateTime<Utc>) -> Result<()> {
         let link_file = self.root.join("commit-links");
 
         // 以 append 模式写入
@@ -163,73 +628,148 @@ impl GraphHistory {
             .append(true)
             .open(&link_file)?;
 
-        writeln!(file, "{} -> {}", commit_hash, graph_hash)?;
+        writeln!(file, "{} -> {} @ {}", commit_hash, graph_hash, at.to_rfc3339())?;
 
         Ok(())
     }
 
-    /// 获取提交关联的图哈希
-    pub fn get_commit_graph_hash(&self, commit_hash: &str) -> Result<Option<String>> {
+    /// 读取所有提交关联（按记录顺序）
+    ///
+    /// 兼容没有记录时间的旧格式 `commit -> graph`，此时时间取快照元数据；
+    /// 被保留策略清理的关联带有 `pruned` 标记
+    pub fn commit_links(&self) -> Result<Vec<CommitLink>> {
         let link_file = self.root.join("commit-links");
 
         if !link_file.exists() {
-            return Ok(None);
+            return Ok(Vec::new());
         }
 
         let content = fs::read_to_string(&link_file)?;
+        let mut links = Vec::new();
 
-        for line in content.lines() {
-            if let Some((c_hash, g_hash)) = line.split_once(" -> ") {
-                if c_hash.trim() == commit_hash {
-                    return Ok(Some(g_hash.trim().to_string()));
-                }
+        for mut link in content.lines().filter_map(parse_commit_link) {
+            if link.recorded_at.is_none() {
+                link.recorded_at = self.get_snapshot(&link.graph_hash).ok().map(|s| s.timestamp);
             }
+            links.push(link);
         }
 
-        Ok(None)
+        Ok(links)
     }
 
-    /// 获取历史记录
-    pub fn get_history(&self, limit: usize) -> Result<Vec<HistoryEntry>> {
-        let link_file = self.root.join("commit-links");
+    /// 合并导入的提交关联（`memexia history import`）
+    ///
+    /// 已存在的关联（提交哈希和图哈希都相同）跳过；本地没有对应图快照的关联标记为已清理，
+    /// 时间旅行到这些提交时报告快照已被清理而不是不存在
+    ///
+    /// # Returns
+    ///
+    /// 新增的关联
+    pub fn merge_commit_links(&self, content: &str) -> Result<Vec<CommitLink>> {
+        let existing: HashSet<(String, String)> = self
+            .commit_links()?
+            .into_iter()
+            .map(|link| (link.commit_hash, link.graph_hash))
+            .collect();
+
+        let mut added: Vec<CommitLink> = Vec::new();
+        let mut lines = String::new();
+        for mut link in content.lines().filter_map(parse_commit_link) {
+            let key = (link.commit_hash.clone(), link.graph_hash.clone());
+            if existing.contains(&key) || added.iter().any(|a| (&a.commit_hash, &a.graph_hash) == (&key.0, &key.1)) {
+                continue;
+            }
+            link.pruned = link.pruned || !self.has_snapshot(&link.graph_hash);
+            lines.push_str(&format!("{} -> {}", link.commit_hash, link.graph_hash));
+            if let Some(at) = link.recorded_at {
+                lines.push_str(&format!(" @ {}", at.to_rfc3339()));
+            }
+            if link.pruned {
+                lines.push_str(PRUNED_MARKER);
+            }
+            lines.push('\n');
+            added.push(link);
+        }
 
-        if !link_file.exists() {
-            return Ok(Vec::new());
+        if !lines.is_empty() {
+            use std::fs::OpenOptions;
+            let mut file = OpenOptions::new().create(true).append(true).open(self.root.join("commit-links"))?;
+            file.write_all(lines.as_bytes())?;
         }
+        Ok(added)
+    }
 
-        let content = fs::read_to_string(&link_file)?;
-        let mut entries: Vec<HistoryEntry> = Vec::new();
+    /// 本地是否存有该图快照
+    fn has_snapshot(&self, hash: &str) -> bool {
+        hash.len() > 2
+            && hash.chars().all(|c| c.is_ascii_hexdigit())
+            && self.snapshots_dir.join(&hash[..2]).join(&hash[2..]).exists()
+    }
 
-        for line in content.lines().rev().take(limit) {
-            if let Some((c_hash, g_hash)) = line.split_once(" -> ") {
-                let snapshot = self.get_snapshot(g_hash.trim()).ok();
+    /// 获取提交关联的图哈希
+    pub fn get_commit_graph_hash(&self, commit_hash: &str) -> Result<Option<String>> {
+        Ok(self
+            .commit_links()?
+            .into_iter()
+            .find(|link| link.commit_hash == commit_hash)
+            .map(|link| link.graph_hash))
+    }
 
-                entries.push(HistoryEntry {
-                    commit_hash: c_hash.trim().to_string(),
-                    graph_hash: g_hash.trim().to_string(),
-                    snapshot,
-                });
-            }
+    /// 按提交哈希前缀查找提交关联
+    ///
+    /// 同一提交多次记录时取最后一次
+    pub fn resolve_commit(&self, prefix: &str) -> Result<Option<CommitLink>> {
+        if prefix.is_empty() {
+            return Ok(None);
         }
+        Ok(self
+            .commit_links()?
+            .into_iter()
+            .rev()
+            .find(|link| link.commit_hash.starts_with(prefix)))
+    }
+
+    /// 查找指定时间点的图状态
+    ///
+    /// # Arguments
+    ///
+    /// * `when` - 时间点
+    ///
+    /// # Returns
+    ///
+    /// 在 `when` 及之前记录的最后一个提交关联；该时间早于所有提交时返回 None
+    pub fn resolve_at(&self, when: DateTime<Utc>) -> Result<Option<CommitLink>> {
+        Ok(self
+            .commit_links()?
+            .into_iter()
+            .filter(|link| link.recorded_at.is_some_and(|at| at <= when))
+            .max_by_key(|link| link.recorded_at))
+    }
+
+    /// 获取历史记录
+    pub fn get_history(&self, limit: usize) -> Result<Vec<HistoryEntry>> {
+        let entries = self
+            .commit_links()?
+            .into_iter()
+            .rev()
+            .take(limit)
+            .map(|link| HistoryEntry {
+                snapshot: self.get_snapshot(&link.graph_hash).ok(),
+                commit_hash: link.commit_hash,
+                graph_hash: link.graph_hash,
+            })
+            .collect();
 
         Ok(entries)
     }
 
     /// 计算两个快照之间的差异
+    ///
+    /// 新增和删除的行各自排序，输出顺序稳定
     pub fn diff(&self, old_hash: &str, new_hash: &str) -> Result<GraphDelta> {
         let old_snapshot = self.get_snapshot(old_hash)?;
         let new_snapshot = self.get_snapshot(new_hash)?;
-
-        let old_lines: std::collections::HashSet<_> = old_snapshot.nquads.lines().collect();
-        let new_lines: std::collections::HashSet<_> = new_snapshot.nquads.lines().collect();
-
-        let added: Vec<_> = new_lines.difference(&old_lines).collect();
-        let removed: Vec<_> = old_lines.difference(&new_lines).collect();
-
-        Ok(GraphDelta {
-            added_lines: added.iter().map(|s| s.to_string()).collect(),
-            removed_lines: removed.iter().map(|s| s.to_string()).collect(),
-        })
+        Ok(GraphDelta::between(Some(&old_snapshot), &new_snapshot))
     }
 
     // ==================== 节点快照功能 ====================
@@ -303,6 +843,85 @@ impl GraphHistory {
         Ok(serde_json::from_str(&content)?)
     }
 
+    /// 合并导入的节点历史（`memexia history import`）
+    ///
+    /// # Arguments
+    ///
+    /// * `dir_name` - 节点历史目录名（节点 ID 中的 `:` 已替换为 `_`）
+    /// * `entries` - 导入的历史条目
+    /// * `snapshots` - 导入的快照内容（快照哈希 -> 内容）
+    ///
+    /// # Returns
+    ///
+    /// (新增条目数, 已存在而跳过的条目数)；按快照哈希去重
+    pub fn merge_node_history(
+        &self,
+        dir_name: &str,
+        entries: Vec<NodeHistoryEntry>,
+        snapshots: &std::collections::BTreeMap<String, Vec<u8>>,
+    ) -> Result<(usize, usize)> {
+        let node_dir = self.nodes_dir.join(dir_name);
+        fs::create_dir_all(&node_dir)
+            .with_context(|| format!("Failed to create node dir: {:?}", node_dir))?;
+        for (hash, content) in snapshots {
+            let path = node_dir.join(hash);
+            if !path.exists() {
+                fs::write(path, content)?;
+            }
+        }
+
+        let history_file = node_dir.join("history.json");
+        let mut history: Vec<NodeHistoryEntry> = if history_file.exists() {
+            serde_json::from_str(&fs::read_to_string(&history_file)?)?
+        } else {
+            Vec::new()
+        };
+        let mut known: HashSet<String> = history.iter().map(|entry| entry.hash.clone()).collect();
+        let (total, before) = (entries.len(), history.len());
+        history.extend(entries.into_iter().filter(|entry| known.insert(entry.hash.clone())));
+        let added = history.len() - before;
+        history.sort_by_key(|entry| entry.timestamp);
+        fs::write(&history_file, serde_json::to_string(&history)?)?;
+        Ok((added, total - added))
+    }
+
+    /// 把节点历史迁移到新 ID（节点被重命名时）
+    ///
+    /// 新 ID 已有历史时按时间合并，快照文件一并移动
+    ///
+    /// # Returns
+    ///
+    /// 迁移的历史条目数
+    pub fn rename_node_history(&self, old_id: &str, new_id: &str) -> Result<usize> {
+        let old_dir = self.nodes_dir.join(sanitize_node_id_for_path(old_id));
+        let new_dir = self.nodes_dir.join(sanitize_node_id_for_path(new_id));
+        if old_id == new_id || !old_dir.exists() {
+            return Ok(0);
+        }
+
+        let moved = self.get_node_history(old_id)?;
+        if !new_dir.exists() {
+            fs::rename(&old_dir, &new_dir)
+                .with_context(|| format!("Failed to move node history {:?} to {:?}", old_dir, new_dir))?;
+            return Ok(moved.len());
+        }
+
+        for entry in fs::read_dir(&old_dir)? {
+            let entry = entry?;
+            let target = new_dir.join(entry.file_name());
+            if entry.file_name() != "history.json" && !target.exists() {
+                fs::rename(entry.path(), target)?;
+            }
+        }
+        let mut history = self.get_node_history(new_id)?;
+        history.extend(moved.iter().cloned());
+        history.sort_by_key(|entry| entry.timestamp);
+        fs::write(new_dir.join("history.json"), serde_json::to_string(&history)?)?;
+        fs::remove_dir_all(&old_dir)?;
+
+        Ok(moved.len())
+    }
+
     /// 获取节点指定版本的快照
     pub fn get_node_snapshot(&self, node_id: &str, hash: &str) -> Result<Option<String>> {
         let safe_id = sanitize_node_id_for_path(node_id);
@@ -370,6 +989,76 @@ impl GraphHistory {
         Ok(())
     }
 
+    /// 记录尚未记录过的推导关系（由 `DerivesFrom` 链接自动记录）
+    ///
+    /// 已有记录（任意提交）的子节点和父节点对被跳过，重复提交同一链接不会产生重复记录
+    ///
+    /// # Returns
+    ///
+    /// 新增的记录数
+    pub fn record_new_derivations(&self, pairs: &[(String, String)], commit_hash: &str) -> Result<usize> {
+        let derivations_file = self.derivations_dir.join("derivations.json");
+        let mut derivations: Vec<DerivationRecord> = if derivations_file.exists() {
+            serde_json::from_str(&fs::read_to_string(&derivations_file)?)?
+        } else {
+            Vec::new()
+        };
+
+        let mut known: HashSet<(String, String)> =
+            derivations.iter().map(|r| (r.child_id.clone(), r.parent_id.clone())).collect();
+        let before = derivations.len();
+        for (child_id, parent_id) in pairs {
+            if known.insert((child_id.clone(), parent_id.clone())) {
+                derivations.push(DerivationRecord {
+                    child_id: child_id.clone(),
+                    parent_id: parent_id.clone(),
+                    timestamp: Utc::now(),
+                    commit_hash: commit_hash.to_string(),
+                });
+            }
+        }
+        let added = derivations.len() - before;
+        if added > 0 {
+            fs::create_dir_all(&self.derivations_dir)?;
+            fs::write(&derivations_file, serde_json::to_string(&derivations)?)?;
+        }
+        Ok(added)
+    }
+
+    /// 合并导入的推导记录（`memexia history import`）
+    ///
+    /// # Returns
+    ///
+    /// (新增记录数, 已存在而跳过的记录数)；子节点、父节点和提交都相同的记录视为重复
+    pub fn merge_derivations(&self, entries: Vec<DerivationEntry>) -> Result<(usize, usize)> {
+        let derivations_file = self.derivations_dir.join("derivations.json");
+        let mut derivations: Vec<DerivationRecord> = if derivations_file.exists() {
+            serde_json::from_str(&fs::read_to_string(&derivations_file)?)?
+        } else {
+            Vec::new()
+        };
+
+        let mut known: HashSet<(String, String, String)> = derivations
+            .iter()
+            .map(|r| (r.child_id.clone(), r.parent_id.clone(), r.commit_hash.clone()))
+            .collect();
+        let (total, before) = (entries.len(), derivations.len());
+        for entry in entries {
+            if known.insert((entry.child_id.clone(), entry.parent_id.clone(), entry.commit_hash.clone())) {
+                derivations.push(DerivationRecord {
+                    child_id: entry.child_id,
+                    parent_id: entry.parent_id,
+                    timestamp: entry.timestamp,
+                    commit_hash: entry.commit_hash,
+                });
+            }
+        }
+        let added = derivations.len() - before;
+        fs::create_dir_all(&self.derivations_dir)?;
+        fs::write(&derivations_file, serde_json::to_string(&derivations)?)?;
+        Ok((added, total - added))
+    }
+
     /// 获取某节点的所有推导来源（父节点）
     pub fn get_derivations(&self, node_id: &str) -> Result<Vec<DerivationEntry>> {
         let derivations_file = self.derivations_dir.join("derivations.json");
@@ -416,55 +1105,41 @@ impl GraphHistory {
             .collect())
     }
 
-    /// 构建完整的推导链
-    ///
-    /// 返回从给定节点到根节点的完整推导路径
-    pub fn build_derivation_chain(&self, node_id: &str) -> Result<Vec<DerivationEntry>> {
-        let mut chain = Vec::new();
-        let mut current = node_id.to_string();
-        let mut visited = std::collections::HashSet::new();
-
-        // 向上追溯推导来源
-        while !visited.contains(&current) {
-            visited.insert(current.clone());
-
-            let parents = self.get_derivations(&current)?;
-            if let Some(first) = parents.first() {
-                chain.push(first.clone());
-                current = first.parent_id.clone();
-            } else {
-                break;
-            }
+    /// 一次性读取全部推导记录并建立索引，供遍历使用，见 [`traversal`](crate::vcs::traversal)
+    pub fn derivation_index(&self) -> Result<DerivationIndex> {
+        let derivations_file = self.derivations_dir.join("derivations.json");
+
+        if !derivations_file.exists() {
+            return Ok(DerivationIndex::default());
         }
 
-        Ok(chain)
-    }
+        let content = fs::read_to_string(&derivations_file)?;
+        let records: Vec<DerivationRecord> = serde_json::from_str(&content)?;
 
-    /// 构建反向推导链（从根到叶子）
-    pub fn build_reverse_derivation_chain(&self, root_id: &str, limit: usize) -> Result<Vec<DerivationEntry>> {
-        let mut chain = Vec::new();
-        let mut current = root_id.to_string();
-        let mut visited = std::collections::HashSet::new();
-        let mut depth = 0;
+        Ok(DerivationIndex::new(
+            records
+                .into_iter()
+                .map(|r| DerivationEntry {
+                    child_id: r.child_id,
+                    parent_id: r.parent_id,
+                    timestamp: r.timestamp,
+                    commit_hash: r.commit_hash,
+                })
+                .collect(),
+        ))
+    }
 
-        // 向下追溯推导出的节点
-        while depth < limit {
-            if visited.contains(&current) {
-                break;
-            }
-            visited.insert(current.clone());
-
-            let derived = self.get_derived_nodes(&current)?;
-            if let Some(first) = derived.first() {
-                chain.push(first.clone());
-                current = first.child_id.clone();
-                depth += 1;
-            } else {
-                break;
-            }
-        }
+    /// 构建完整的推导链
+    ///
+    /// 返回从给定节点到根节点的推导路径（沿每个节点的第一条推导来源）；
+    /// 链长受 `limits` 限制，回到链上已有节点时停止并报告环
+    pub fn build_derivation_chain(&self, node_id: &str, limits: &TraversalLimits) -> Result<Traversal<Vec<DerivationEntry>>> {
+        Ok(self.derivation_index()?.chain(node_id, true, limits))
+    }
 
-        Ok(chain)
+    /// 构建反向推导链（从根到叶子，沿每个节点推导出的第一个节点）
+    pub fn build_reverse_derivation_chain(&self, root_id: &str, limits: &TraversalLimits) -> Result<Traversal<Vec<DerivationEntry>>> {
+        Ok(self.derivation_index()?.chain(root_id, false, limits))
     }
 }
 
@@ -477,6 +1152,21 @@ pub struct GraphSnapshot {
     pub nquads: String,
     /// 创建时间
     pub timestamp: DateTime<Utc>,
+    /// 快照格式版本（见模块文档）
+    pub format: u32,
+}
+
+impl GraphSnapshot {
+    /// 快照中全部节点的标题（节点 ID -> 标题）
+    pub fn titles(&self) -> BTreeMap<String, String> {
+        self.nquads
+            .lines()
+            .filter(|line| line.contains("<memexia:title>"))
+            .filter_map(nquads::parse_nquads_line)
+            .filter(|(_, predicate, _)| predicate == "memexia:title")
+            .map(|(subject, _, title)| (subject, title))
+            .collect()
+    }
 }
 
 /// 快照元数据
@@ -485,6 +1175,13 @@ struct SnapshotMetadata {
     hash: String,
     size: usize,
     timestamp: DateTime<Utc>,
+    /// 旧快照的元数据没有该字段，视为版本 1
+    #[serde(default = "legacy_snapshot_format")]
+    format: u32,
+}
+
+fn legacy_snapshot_format() -> u32 {
+    1
 }
 
 /// 图差异
@@ -497,6 +1194,37 @@ pub struct GraphDelta {
 }
 
 impl GraphDelta {
+    /// 两个快照之间的差异；`old` 为 None 时（如第一个提交）全部行都是新增的
+    ///
+    /// 新增和删除的行各自排序，输出顺序稳定
+    pub fn between(old: Option<&GraphSnapshot>, new: &GraphSnapshot) -> Self {
+        fn lines(snapshot: &GraphSnapshot) -> BTreeSet<&str> {
+            snapshot.nquads.lines().filter(|line| !line.trim().is_empty()).collect()
+        }
+        let old_lines = old.map(lines).unwrap_or_default();
+        let new_lines = lines(new);
+        Self {
+            added_lines: new_lines.difference(&old_lines).map(|s| s.to_string()).collect(),
+            removed_lines: old_lines.difference(&new_lines).map(|s| s.to_string()).collect(),
+        }
+    }
+
+    /// 把逐行差异归纳为节点和边的变化
+    ///
+    /// 节点以类型三元组（`rdf:type`）的出现和消失判断增删，只改了类型的节点不算；
+    /// 边是宾语为 IRI 的关系谓词三元组，边的元数据不计入
+    pub fn summarize(&self) -> DeltaSummary {
+        let added = DeltaLines::parse(&self.added_lines);
+        let removed = DeltaLines::parse(&self.removed_lines);
+        DeltaSummary {
+            added_nodes: added.typed.difference(&removed.typed).cloned().collect(),
+            removed_nodes: removed.typed.difference(&added.typed).cloned().collect(),
+            added_edges: added.edges,
+            removed_edges: removed.edges,
+            titles: added.titles,
+        }
+    }
+
     /// 检查是否有变化
     pub fn is_empty(&self) -> bool {
         self.added_lines.is_empty() && self.removed_lines.is_empty()
@@ -508,6 +1236,92 @@ impl GraphDelta {
     }
 }
 
+/// 图差异中的一条边
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DeltaEdge {
+    /// 源节点 ID
+    pub from: String,
+    /// 关系谓词名（不含 `memexia:` 前缀），见 [`RelationType::predicate_name`]
+    pub relation: String,
+    /// 目标节点 ID
+    pub to: String,
+}
+
+impl DeltaEdge {
+    /// 关系类型
+    pub fn relation_type(&self) -> Option<RelationType> {
+        RelationType::from_predicate_name(&self.relation)
+    }
+}
+
+/// 按节点和边归纳的图差异，见 [`GraphDelta::summarize`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeltaSummary {
+    /// 新增的节点 ID（已排序）
+    pub added_nodes: Vec<String>,
+    /// 删除的节点 ID（已排序）
+    pub removed_nodes: Vec<String>,
+    /// 新增的边（已排序）
+    pub added_edges: Vec<DeltaEdge>,
+    /// 删除的边（已排序）
+    pub removed_edges: Vec<DeltaEdge>,
+    /// 新增行中出现的节点标题
+    pub titles: BTreeMap<String, String>,
+}
+
+/// 一组差异行中的类型、边和标题三元组
+#[derive(Default)]
+struct DeltaLines {
+    typed: BTreeSet<String>,
+    edges: Vec<DeltaEdge>,
+    titles: BTreeMap<String, String>,
+}
+
+impl DeltaLines {
+    fn parse(lines: &[String]) -> Self {
+        let mut parsed = Self::default();
+        for line in lines {
+            let Some((subject, predicate, object)) = nquads::parse_nquads_line(line) else {
+                continue;
+            };
+            let object_is_iri = line.trim_end().trim_end_matches('.').trim_end().ends_with('>');
+            if predicate == "rdf:type" || predicate == RDF_TYPE {
+                parsed.typed.insert(subject);
+            } else if predicate == "memexia:title" {
+                parsed.titles.insert(subject, object);
+            } else if let Some(relation) = predicate.strip_prefix("memexia:") {
+                if object_is_iri && RelationType::from_predicate_name(relation).is_some() {
+                    parsed.edges.push(DeltaEdge { from: subject, relation: relation.to_string(), to: object });
+                }
+            }
+        }
+        parsed.edges.sort();
+        parsed
+    }
+}
+
+/// 提交与图快照的关联
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitLink {
+    /// Git 提交哈希
+    pub commit_hash: String,
+    /// 图快照哈希
+    pub graph_hash: String,
+    /// 记录时间（旧格式且快照缺失时为 None）
+    pub recorded_at: Option<DateTime<Utc>>,
+    /// 快照是否已被保留策略清理
+    pub pruned: bool,
+}
+
+/// 保留策略的执行结果
+#[derive(Debug, Clone, Default)]
+pub struct RetentionReport {
+    /// 被标记为已清理的提交关联
+    pub pruned: Vec<CommitLink>,
+    /// 删除的快照哈希
+    pub removed_snapshots: Vec<String>,
+}
+
 /// 历史条目
 #[derive(Debug)]
 pub struct HistoryEntry {
@@ -549,8 +1363,8 @@ pub struct NodeHistoryEntry {
 
 // ==================== 推导链类型 ====================
 
-/// 推导链条目
-#[derive(Debug, Clone)]
+/// 推导链条目（序列化格式与 `derivations.json` 中的记录相同）
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DerivationEntry {
     /// 子节点 ID（推导出的节点）
     pub child_id: String,
@@ -601,6 +1415,75 @@ mod tests {
         assert_eq!(snapshot.hash, hash);
     }
 
+    #[test]
+    fn test_snapshot_is_canonical() {
+        use crate::storage::{Edge, Node, NodeType, RelationType};
+
+        let mut a = Node::new("urn:memexia:file:a.md", NodeType::Concept, "A");
+        a.tags = vec!["z".to_string(), "m".to_string()];
+        let b = Node::new("urn:memexia:file:b.md", NodeType::Question, "B");
+        let c = Node::new("urn:memexia:file:c.md", NodeType::Evidence, "C");
+        let ab = Edge::new("e1", &a.id, &b.id, RelationType::Supports);
+        let cb = Edge::new("e2", &c.id, &b.id, RelationType::Contradicts);
+
+        let build = |nodes: [&Node; 3], edges: [&Edge; 2]| {
+            let temp = TempDir::new().unwrap();
+            let storage = Storage::init(temp.path()).unwrap();
+            for node in nodes {
+                storage.graph().add_node(node).unwrap();
+            }
+            for edge in edges {
+                storage.graph().add_edge(edge).unwrap();
+            }
+            let history = GraphHistory::init(temp.path()).unwrap();
+            let hash = history.snapshot(&storage).unwrap();
+            let format = history.get_snapshot(&hash).unwrap().format;
+            (storage.graph().export_nquads().unwrap(), hash, format, temp)
+        };
+        let (first, first_hash, format, _first_temp) = build([&a, &b, &c], [&ab, &cb]);
+        let (second, second_hash, _, _second_temp) = build([&c, &b, &a], [&cb, &ab]);
+
+        assert_eq!(first, second);
+        assert_eq!(first_hash, second_hash);
+        assert_eq!(format, SNAPSHOT_FORMAT);
+        let lines: Vec<&str> = first.lines().collect();
+        assert!(lines.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn test_legacy_snapshot_format() {
+        let temp = TempDir::new().unwrap();
+        let history = GraphHistory::init(temp.path()).unwrap();
+        let dir = temp.path().join(".memexia/history/snapshots/ab");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("cdef"), "<urn:b> <p> \"x\" .\n<urn:a> <p> \"y\" .\n").unwrap();
+        fs::write(
+            dir.join("cdef.meta"),
+            r#"{"hash":"abcdef","size":40,"timestamp":"2024-01-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+
+        let snapshot = history.get_snapshot("abcdef").unwrap();
+        assert_eq!(snapshot.format, 1);
+        assert_eq!(snapshot.nquads.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_get_snapshot_not_found() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path();
+        Storage::init(path).unwrap();
+        let history = GraphHistory::init(path).unwrap();
+
+        // 不存在、过短和非十六进制的哈希都返回 SnapshotNotFound 而不是 panic
+        for hash in ["0123456789abcdef", "a", "not-a-hash"] {
+            assert!(matches!(
+                history.get_snapshot(hash),
+                Err(MemexiaError::SnapshotNotFound { .. })
+            ));
+        }
+    }
+
     #[test]
     fn test_commit_link() {
         let temp = TempDir::new().unwrap();
@@ -616,6 +1499,183 @@ mod tests {
         assert_eq!(graph_hash, Some("def456".to_string()));
     }
 
+    #[test]
+    fn test_resolve_at() {
+        let temp = TempDir::new().unwrap();
+        let history = GraphHistory::init(temp.path()).unwrap();
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+
+        history.record_at("c1", "aaa", at("2024-01-10T00:00:00Z")).unwrap();
+        history.record_at("c2", "bbb", at("2024-03-05T00:00:00Z")).unwrap();
+        // 旧格式行（无时间且快照缺失）不参与按时间解析
+        let link_file = temp.path().join(".memexia/history/commit-links");
+        let mut content = fs::read_to_string(&link_file).unwrap();
+        content.push_str("c0 -> 000\n");
+        fs::write(&link_file, content).unwrap();
+
+        assert!(history.resolve_at(at("2024-01-01T00:00:00Z")).unwrap().is_none());
+        assert_eq!(history.resolve_at(at("2024-02-01T00:00:00Z")).unwrap().unwrap().graph_hash, "aaa");
+        assert_eq!(history.resolve_at(at("2024-03-05T00:00:00Z")).unwrap().unwrap().commit_hash, "c2");
+        assert_eq!(history.resolve_at(at("2025-01-01T00:00:00Z")).unwrap().unwrap().graph_hash, "bbb");
+
+        assert_eq!(history.resolve_commit("c").unwrap().unwrap().commit_hash, "c0");
+        assert_eq!(history.resolve_commit("c1").unwrap().unwrap().graph_hash, "aaa");
+        assert!(history.resolve_commit("zz").unwrap().is_none());
+        assert_eq!(history.get_commit_graph_hash("c0").unwrap().as_deref(), Some("000"));
+        assert_eq!(history.get_history(10).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_auto_snapshot_dedup_and_prune() {
+        use crate::storage::{Node, NodeType};
+
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let history = GraphHistory::init(temp.path()).unwrap();
+        history.record("c1", "aaa").unwrap();
+
+        let first = history.auto_snapshot(&storage, 2).unwrap().unwrap();
+        assert!(first.commit_hash.starts_with(AUTO_SNAPSHOT_PREFIX));
+        // 图未变化时不重复快照
+        assert!(history.auto_snapshot(&storage, 2).unwrap().is_none());
+
+        for i in 0..3 {
+            let id = format!("urn:memexia:file:n{}.md", i);
+            storage.graph().add_node(&Node::new(&id, NodeType::Concept, &id)).unwrap();
+            assert!(history.auto_snapshot(&storage, 2).unwrap().is_some());
+        }
+
+        // 只保留最近两个自动快照，提交关联不受影响，最早的快照文件被删除
+        let autos = history.auto_snapshots().unwrap();
+        assert_eq!(autos.len(), 2);
+        assert!(autos.iter().all(|l| l.graph_hash != first.graph_hash));
+        assert!(history.get_snapshot(&first.graph_hash).is_err());
+        assert!(history.get_snapshot(&autos[1].graph_hash).is_ok());
+        assert_eq!(history.get_commit_graph_hash("c1").unwrap().as_deref(), Some("aaa"));
+
+        // 自动快照键可以像提交一样按前缀解析
+        let key = &autos[1].commit_hash;
+        assert_eq!(history.resolve_commit(key).unwrap().unwrap().graph_hash, autos[1].graph_hash);
+    }
+
+    #[test]
+    fn test_retention_keep_last() {
+        use crate::storage::{Node, NodeType};
+
+        let temp = TempDir::new().unwrap();
+        let storage = Storage::init(temp.path()).unwrap();
+        let history = GraphHistory::init(temp.path()).unwrap();
+
+        // 10 个提交，每个对应不同的快照；c1 是某个分支的末端
+        let mut hashes = Vec::new();
+        for i in 0..10 {
+            let id = format!("urn:memexia:file:n{}.md", i);
+            storage.graph().add_node(&Node::new(&id, NodeType::Concept, &id)).unwrap();
+            let hash = history.snapshot(&storage).unwrap();
+            history.record(&format!("c{}", i), &hash).unwrap();
+            hashes.push(hash);
+        }
+        let auto = history.auto_snapshot(&storage, 5).unwrap().unwrap();
+
+        let policy = RetentionPolicy::KeepLast(3);
+        let protected = ["c1".to_string()];
+        let preview = history.apply_retention(&policy, &protected, true).unwrap();
+        assert_eq!(preview.pruned.len(), 6);
+        assert!(history.get_snapshot(&hashes[0]).is_ok());
+
+        let report = history.apply_retention(&policy, &protected, false).unwrap();
+        let pruned: Vec<&str> = report.pruned.iter().map(|l| l.commit_hash.as_str()).collect();
+        assert_eq!(pruned, ["c0", "c2", "c3", "c4", "c5", "c6"]);
+        // c9 与自动快照的快照相同，c9 被保留
+        assert_eq!(report.removed_snapshots.len(), 6);
+
+        let survivors: Vec<String> = history
+            .commit_links()
+            .unwrap()
+            .into_iter()
+            .filter(|l| !l.pruned)
+            .map(|l| l.commit_hash)
+            .collect();
+        assert_eq!(survivors, ["c1", "c7", "c8", "c9", auto.commit_hash.as_str()]);
+        for (i, hash) in hashes.iter().enumerate() {
+            let kept = [1, 7, 8, 9].contains(&i);
+            assert_eq!(history.get_snapshot(hash).is_ok(), kept, "snapshot of c{}", i);
+            assert_eq!(
+                snapshot_meta_exists(temp.path(), hash),
+                kept,
+                "meta of c{}",
+                i
+            );
+        }
+
+        // 被清理的快照给出明确的错误
+        assert!(matches!(history.get_snapshot(&hashes[0]), Err(MemexiaError::SnapshotPruned { .. })));
+        assert!(matches!(history.diff(&hashes[2], &hashes[9]), Err(MemexiaError::SnapshotPruned { .. })));
+        assert!(history.resolve_commit("c3").unwrap().unwrap().pruned);
+
+        // 再次执行没有变化
+        assert!(history.apply_retention(&policy, &protected, false).unwrap().pruned.is_empty());
+    }
+
+    #[test]
+    fn test_verify_snapshots() {
+        use crate::storage::{Node, NodeType};
+
+        let temp = TempDir::new().unwrap();
+        let path = temp.path();
+        let storage = Storage::init(path).unwrap();
+        let history = GraphHistory::init(path).unwrap();
+        storage.graph().add_node(&Node::new("urn:memexia:file:a.md", NodeType::Concept, "A")).unwrap();
+        let first = history.snapshot(&storage).unwrap();
+        storage.graph().add_node(&Node::new("urn:memexia:file:b.md", NodeType::Concept, "B")).unwrap();
+        let second = history.snapshot(&storage).unwrap();
+        history.record("c1", &first).unwrap();
+        history.record("c2", &second).unwrap();
+        history.record("c3", "0123456789abcdef").unwrap();
+
+        let report = history.verify_snapshots(None, false).unwrap();
+        assert_eq!(report.snapshots.len(), 2);
+        assert_eq!(report.failed(), 0);
+        assert_eq!(report.dangling_links.len(), 1);
+        assert_eq!(report.dangling_links[0].commit_hash, "c3");
+
+        // 改动一个字节
+        let snapshot_path = |hash: &str| path.join(".memexia/history/snapshots").join(&hash[..2]).join(&hash[2..]);
+        let mut bytes = fs::read(snapshot_path(&first)).unwrap();
+        bytes[1] ^= 0x01;
+        fs::write(snapshot_path(&first), bytes).unwrap();
+        // 删除元数据
+        fs::remove_file(snapshot_path(&second).with_extension("meta")).unwrap();
+
+        let report = history.verify_snapshots(None, false).unwrap();
+        assert_eq!(report.failed(), 2);
+        let corrupted = report.snapshots.iter().find(|s| s.hash == first).unwrap();
+        assert!(matches!(corrupted.problems[0], SnapshotProblem::HashMismatch { .. }));
+        let missing = report.snapshots.iter().find(|s| s.hash == second).unwrap();
+        assert_eq!(missing.problems, [SnapshotProblem::MissingMeta]);
+
+        let report = history.verify_snapshots(Some(&second[..8]), true).unwrap();
+        assert_eq!(report.snapshots.len(), 1);
+        assert!(report.is_ok());
+        assert_eq!(report.repaired(), 1);
+        assert!(snapshot_meta_exists(path, &second));
+        assert_eq!(history.get_snapshot(&second).unwrap().format, SNAPSHOT_FORMAT);
+
+        // 内容损坏的快照不能修复
+        let report = history.verify_snapshots(None, true).unwrap();
+        assert_eq!(report.failed(), 1);
+        assert_eq!(report.repaired(), 0);
+        assert!(matches!(
+            history.verify_snapshots(Some("ffff"), false),
+            Err(MemexiaError::SnapshotNotFound { .. })
+        ));
+    }
+
+    fn snapshot_meta_exists(root: &Path, hash: &str) -> bool {
+        let (dir, file) = hash.split_at(2);
+        root.join(".memexia/history/snapshots").join(dir).join(file).with_extension("meta").exists()
+    }
+
     // ==================== 节点快照测试 ====================
 
     #[test]
@@ -689,6 +1749,30 @@ mod tests {
         assert!(not_found.is_none());
     }
 
+    #[test]
+    fn test_rename_node_history() {
+        let temp = TempDir::new().unwrap();
+        let history = GraphHistory::init(temp.path()).unwrap();
+
+        let old_id = "urn:memexia:file:old.md";
+        let new_id = "urn:memexia:file:new.md";
+        let hash1 = history.snapshot_node(old_id, "v1", "commit1").unwrap();
+        history.snapshot_node(old_id, "v2", "commit2").unwrap();
+
+        assert_eq!(history.rename_node_history(old_id, new_id).unwrap(), 2);
+        assert!(history.get_node_history(old_id).unwrap().is_empty());
+        assert_eq!(history.get_node_history(new_id).unwrap().len(), 2);
+        assert_eq!(history.get_node_snapshot(new_id, &hash1).unwrap().unwrap(), "v1");
+
+        // 新 ID 已有历史时合并
+        history.snapshot_node(old_id, "v0", "commit0").unwrap();
+        assert_eq!(history.rename_node_history(old_id, new_id).unwrap(), 1);
+        let merged = history.get_node_history(new_id).unwrap();
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged.last().unwrap().commit_hash, "commit0");
+        assert_eq!(history.rename_node_history(old_id, new_id).unwrap(), 0);
+    }
+
     // ==================== 推导链测试 ====================
 
     #[test]
@@ -708,6 +1792,25 @@ mod tests {
         assert_eq!(derivations[0].commit_hash, "commit123");
     }
 
+    #[test]
+    fn test_record_new_derivations_skips_known_pairs() {
+        let temp = TempDir::new().unwrap();
+        let history = GraphHistory::init(temp.path()).unwrap();
+
+        history.record_derivation("child", "a", "c0").unwrap();
+        let pairs = vec![
+            ("child".to_string(), "a".to_string()),
+            ("child".to_string(), "b".to_string()),
+            ("child".to_string(), "b".to_string()),
+        ];
+        assert_eq!(history.record_new_derivations(&pairs, "c1").unwrap(), 1);
+        assert_eq!(history.record_new_derivations(&pairs, "c2").unwrap(), 0);
+
+        let derivations = history.get_derivations("child").unwrap();
+        let commits: Vec<_> = derivations.iter().map(|d| (d.parent_id.as_str(), d.commit_hash.as_str())).collect();
+        assert_eq!(commits, [("a", "c0"), ("b", "c1")]);
+    }
+
     #[test]
     fn test_get_derived_nodes() {
         let temp = TempDir::new().unwrap();
@@ -740,7 +1843,7 @@ mod tests {
         history.record_derivation("child3", "child2", "c3").unwrap();
 
         // 构建推导链
-        let chain = history.build_derivation_chain("child3").unwrap();
+        let chain = history.build_derivation_chain("child3", &TraversalLimits::default()).unwrap().value;
         assert_eq!(chain.len(), 3);
         assert_eq!(chain[0].parent_id, "child2");
         assert_eq!(chain[1].parent_id, "child1");
@@ -760,7 +1863,7 @@ mod tests {
         history.record_derivation("child3", "child2", "c3").unwrap();
 
         // 构建反向推导链
-        let chain = history.build_reverse_derivation_chain("root", 5).unwrap();
+        let chain = history.build_reverse_derivation_chain("root", &TraversalLimits::default().with_max_depth(5)).unwrap().value;
         assert_eq!(chain.len(), 3);
         assert_eq!(chain[0].child_id, "child1");
         assert_eq!(chain[1].child_id, "child2");
@@ -778,9 +1881,37 @@ mod tests {
         history.record_derivation("node2", "node1", "c1").unwrap();
         history.record_derivation("node1", "node2", "c2").unwrap();
 
-        // 应该不会无限循环
-        let chain = history.build_derivation_chain("node2").unwrap();
-        assert!(chain.len() <= 2);
+        // 应该不会无限循环，并报告环上的节点
+        let chain = history.build_derivation_chain("node2", &TraversalLimits::default()).unwrap();
+        assert_eq!(chain.value.len(), 1);
+        assert_eq!(chain.outcome.cycles, vec![vec!["node2", "node1"]]);
+        assert!(!chain.outcome.depth_limit_hit);
+    }
+
+    #[test]
+    fn test_deep_derivation_chain_limit() {
+        let temp = TempDir::new().unwrap();
+        let history = GraphHistory::init(temp.path()).unwrap();
+
+        // 10000 层的推导链: n0 -> n1 -> ... -> n10000
+        let entries = (1..=10_000)
+            .map(|i| DerivationEntry {
+                child_id: format!("n{}", i),
+                parent_id: format!("n{}", i - 1),
+                timestamp: Utc::now(),
+                commit_hash: format!("c{}", i),
+            })
+            .collect();
+        history.merge_derivations(entries).unwrap();
+
+        let chain = history.build_derivation_chain("n10000", &TraversalLimits::default()).unwrap();
+        assert_eq!(chain.value.len(), crate::vcs::traversal::DEFAULT_MAX_DEPTH);
+        assert!(chain.outcome.depth_limit_hit);
+
+        let limits = TraversalLimits { max_depth: 20_000, max_nodes: 20_000 };
+        let chain = history.build_reverse_derivation_chain("n0", &limits).unwrap();
+        assert_eq!(chain.value.len(), 10_000);
+        assert!(chain.outcome.is_complete());
     }
 
     // ==================== 扩展测试 ====================
@@ -828,7 +1959,7 @@ mod tests {
         let history = GraphHistory::init(path).unwrap();
 
         // 没有推导关系的节点
-        let chain = history.build_derivation_chain("orphan").unwrap();
+        let chain = history.build_derivation_chain("orphan", &TraversalLimits::default()).unwrap().value;
         assert!(chain.is_empty());
     }
 
@@ -847,7 +1978,7 @@ mod tests {
         history.record_derivation("n5", "n4", "c5").unwrap();
 
         // 限制深度为 2
-        let chain = history.build_reverse_derivation_chain("root", 2).unwrap();
+        let chain = history.build_reverse_derivation_chain("root", &TraversalLimits::default().with_max_depth(2)).unwrap().value;
         assert_eq!(chain.len(), 2);
         assert_eq!(chain[0].child_id, "n1");
         assert_eq!(chain[1].child_id, "n2");
@@ -944,7 +2075,7 @@ mod tests {
         }
 
         // 构建完整的推导链
-        let chain = history.build_derivation_chain("node10").unwrap();
+        let chain = history.build_derivation_chain("node10", &TraversalLimits::default()).unwrap().value;
         assert_eq!(chain.len(), 10);
 
         // 验证顺序（从叶到根）
@@ -975,7 +2106,7 @@ mod tests {
         history.record_derivation("n5", "n4", "c6").unwrap();  // n5 有多个父节点
 
         // n5 的推导链应该追溯到一个父节点（第一个匹配的）
-        let chain = history.build_derivation_chain("n5").unwrap();
+        let chain = history.build_derivation_chain("n5", &TraversalLimits::default()).unwrap().value;
         assert!(!chain.is_empty());
         assert_eq!(chain.last().unwrap().parent_id, "root");
     }
@@ -0,0 +1,468 @@
+//! 历史包模块（`memexia history export/import`）
+//!
+//! 把 `.memexia/history` 中与节点相关的历史打包成一个 tar 文件，便于迁移仓库时不通过 Git 同步整个历史目录：
+//!
+//! - `manifest.json`：[`BundleManifest`]（格式版本、各类记录数、仓库标识）
+//! - `commit-links`：提交与图快照的关联
+//! - `nodes/<节点目录>/`：节点快照及其 `history.json` 索引
+//! - `derivations/derivations.json`：推导记录
+//!
+//! 完整的图快照（`snapshots/`）不打包。导入的提交关联在本地没有对应快照时标记为已清理，
+//! 时间旅行到这些提交会提示快照已被清理
+//!
+//! 仓库标识是 HEAD 可达的根提交：从同一仓库克隆的副本标识相同。导入时按快照哈希合并节点历史、
+//! 跳过重复的提交关联和推导记录，并检查引用的提交是否存在于本地 Git 仓库
+
+use crate::error::{MemexiaError, Result};
+use crate::vcs::graph_history::{DerivationEntry, GraphHistory, NodeHistoryEntry, AUTO_SNAPSHOT_PREFIX};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::Path;
+
+/// 当前历史包格式版本
+pub const BUNDLE_FORMAT: u32 = 1;
+
+/// 清单文件名
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// 提交关联文件名
+const COMMIT_LINKS_FILE: &str = "commit-links";
+
+/// 推导记录文件（相对历史目录）
+const DERIVATIONS_FILE: &str = "derivations/derivations.json";
+
+/// 节点历史索引文件名
+const NODE_HISTORY_FILE: &str = "history.json";
+
+/// tar 块大小
+const BLOCK: usize = 512;
+
+/// 历史包清单
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleManifest {
+    /// 格式版本
+    pub format: u32,
+    /// 导出仓库的标识（根提交），导出时还没有提交则为 None
+    pub repository_id: Option<String>,
+    /// 导出时间
+    pub created: DateTime<Utc>,
+    /// 提交关联数
+    pub commit_links: usize,
+    /// 有历史的节点数
+    pub nodes: usize,
+    /// 节点快照数
+    pub node_snapshots: usize,
+    /// 推导记录数
+    pub derivations: usize,
+}
+
+/// 导入结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportReport {
+    /// 历史包清单
+    pub manifest: BundleManifest,
+    /// 新增的提交关联数
+    pub commit_links: usize,
+    /// 新增的节点历史条目数
+    pub node_snapshots: usize,
+    /// 新增的推导记录数
+    pub derivations: usize,
+    /// 本地已存在而跳过的记录数
+    pub skipped: usize,
+    /// 引用了但本地 Git 仓库中不存在的提交（排序去重）
+    pub missing_commits: Vec<String>,
+    /// 历史包是否来自另一个仓库（根提交不同）
+    pub foreign_repository: bool,
+}
+
+/// 历史包中一个节点目录的内容
+#[derive(Default)]
+struct NodeBundle {
+    /// `history.json` 中的条目
+    entries: Vec<NodeHistoryEntry>,
+    /// 快照哈希 -> 快照内容
+    snapshots: BTreeMap<String, Vec<u8>>,
+}
+
+/// 导出历史包
+///
+/// # Arguments
+///
+/// * `history` - 图历史
+/// * `repository_id` - 仓库标识（根提交）
+/// * `file` - 输出的 tar 文件
+pub fn export(history: &GraphHistory, repository_id: Option<String>, file: &Path) -> Result<BundleManifest> {
+    let root = history.path();
+    let mut entries: Vec<(String, Vec<u8>)> = Vec::new();
+    let mut manifest = BundleManifest {
+        format: BUNDLE_FORMAT,
+        repository_id,
+        created: Utc::now(),
+        commit_links: 0,
+        nodes: 0,
+        node_snapshots: 0,
+        derivations: 0,
+    };
+
+    if let Some(content) = read_optional(&root.join(COMMIT_LINKS_FILE))? {
+        manifest.commit_links = String::from_utf8_lossy(&content).lines().filter(|l| l.contains(" -> ")).count();
+        entries.push((COMMIT_LINKS_FILE.to_string(), content));
+    }
+
+    let nodes_dir = root.join("nodes");
+    if nodes_dir.is_dir() {
+        for node_dir in sorted_entries(&nodes_dir)? {
+            if !node_dir.is_dir() {
+                continue;
+            }
+            let dir_name = file_name(&node_dir);
+            manifest.nodes += 1;
+            for file in sorted_entries(&node_dir)? {
+                let name = file_name(&file);
+                if name != NODE_HISTORY_FILE {
+                    manifest.node_snapshots += 1;
+                }
+                entries.push((format!("nodes/{}/{}", dir_name, name), fs::read(&file)?));
+            }
+        }
+    }
+
+    if let Some(content) = read_optional(&root.join(DERIVATIONS_FILE))? {
+        manifest.derivations = serde_json::from_slice::<Vec<DerivationEntry>>(&content)?.len();
+        entries.push((DERIVATIONS_FILE.to_string(), content));
+    }
+
+    entries.insert(0, (MANIFEST_FILE.to_string(), serde_json::to_vec_pretty(&manifest)?));
+    let mut out = BufWriter::new(fs::File::create(file)?);
+    write_tar(&mut out, &entries, manifest.created.timestamp())?;
+    out.flush()?;
+    Ok(manifest)
+}
+
+/// 导入历史包
+///
+/// # Arguments
+///
+/// * `history` - 本地图历史
+/// * `file` - 历史包
+/// * `local_id` - 本地仓库标识（根提交）
+/// * `merge` - 是否与已有历史合并；为 false 时本地已有历史会返回 `Validation` 错误
+/// * `commit_exists` - 检查提交是否存在于本地 Git 仓库
+pub fn import(
+    history: &GraphHistory,
+    file: &Path,
+    local_id: Option<&str>,
+    merge: bool,
+    commit_exists: impl Fn(&str) -> bool,
+) -> Result<ImportReport> {
+    let entries = read_tar(BufReader::new(fs::File::open(file)?))?;
+
+    let mut manifest = None;
+    let mut commit_links = None;
+    let mut derivations: Vec<DerivationEntry> = Vec::new();
+    let mut nodes: BTreeMap<String, NodeBundle> = BTreeMap::new();
+    for (name, content) in entries {
+        match name.as_str() {
+            MANIFEST_FILE => manifest = Some(serde_json::from_slice::<BundleManifest>(&content)?),
+            COMMIT_LINKS_FILE => commit_links = Some(String::from_utf8_lossy(&content).into_owned()),
+            DERIVATIONS_FILE => derivations = serde_json::from_slice(&content)?,
+            _ => {
+                let parts: Vec<&str> = name.split('/').collect();
+                let [_, dir, file] = parts.as_slice() else {
+                    return Err(unexpected_entry(&name));
+                };
+                if parts[0] != "nodes" || !is_safe_component(dir) || !is_safe_component(file) {
+                    return Err(unexpected_entry(&name));
+                }
+                let node = nodes.entry(dir.to_string()).or_default();
+                if *file == NODE_HISTORY_FILE {
+                    node.entries = serde_json::from_slice(&content)?;
+                } else {
+                    node.snapshots.insert(file.to_string(), content);
+                }
+            }
+        }
+    }
+
+    let manifest = manifest
+        .ok_or_else(|| MemexiaError::Validation(format!("{} is not a history bundle: missing {}", file.display(), MANIFEST_FILE)))?;
+    if manifest.format > BUNDLE_FORMAT {
+        return Err(MemexiaError::Validation(format!(
+            "History bundle format {} is newer than supported ({}); upgrade memexia",
+            manifest.format, BUNDLE_FORMAT
+        )));
+    }
+    if !merge && has_history(history)? {
+        return Err(MemexiaError::Validation(
+            "The repository already has history; use --merge to combine it with the bundle".to_string(),
+        ));
+    }
+
+    let mut referenced: BTreeSet<&str> = BTreeSet::new();
+    let links = commit_links.as_deref().unwrap_or_default();
+    referenced.extend(links.lines().filter_map(|line| line.split_once(" -> ")).map(|(commit, _)| commit.trim()));
+    referenced.extend(nodes.values().flat_map(|node| node.entries.iter().map(|e| e.commit_hash.as_str())));
+    referenced.extend(derivations.iter().map(|d| d.commit_hash.as_str()));
+    let missing_commits: Vec<String> = referenced
+        .into_iter()
+        .filter(|commit| !commit.is_empty() && !commit.starts_with(AUTO_SNAPSHOT_PREFIX) && !commit_exists(commit))
+        .map(str::to_string)
+        .collect();
+    let foreign_repository = matches!((manifest.repository_id.as_deref(), local_id), (Some(a), Some(b)) if a != b);
+
+    let mut report = ImportReport {
+        manifest,
+        commit_links: 0,
+        node_snapshots: 0,
+        derivations: 0,
+        skipped: 0,
+        missing_commits,
+        foreign_repository,
+    };
+
+    let added = history.merge_commit_links(links)?.len();
+    report.commit_links = added;
+    report.skipped += links.lines().filter(|l| l.contains(" -> ")).count().saturating_sub(added);
+    for (dir, node) in nodes {
+        let (added, skipped) = history.merge_node_history(&dir, node.entries, &node.snapshots)?;
+        report.node_snapshots += added;
+        report.skipped += skipped;
+    }
+    let (added, skipped) = history.merge_derivations(derivations)?;
+    report.derivations = added;
+    report.skipped += skipped;
+    Ok(report)
+}
+
+/// 本地是否已有可导出的历史
+fn has_history(history: &GraphHistory) -> Result<bool> {
+    let nodes_dir = history.path().join("nodes");
+    let has_nodes = nodes_dir.is_dir() && fs::read_dir(&nodes_dir)?.next().is_some();
+    let derivations = read_optional(&history.path().join(DERIVATIONS_FILE))?
+        .map(|content| serde_json::from_slice::<Vec<DerivationEntry>>(&content))
+        .transpose()?
+        .is_some_and(|records| !records.is_empty());
+    Ok(has_nodes || derivations || !history.commit_links()?.is_empty())
+}
+
+fn unexpected_entry(name: &str) -> MemexiaError {
+    MemexiaError::Validation(format!("Unexpected entry in history bundle: {}", name))
+}
+
+/// 路径分量不能跳出历史目录
+fn is_safe_component(part: &str) -> bool {
+    !part.is_empty() && part != "." && part != ".." && !part.contains('\\') && !part.contains(':')
+}
+
+/// 读取文件，不存在时为 None
+fn read_optional(path: &Path) -> Result<Option<Vec<u8>>> {
+    match fs::read(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// 按文件名排序的目录项
+fn sorted_entries(dir: &Path) -> Result<Vec<std::path::PathBuf>> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+    Ok(entries)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().unwrap_or_default().to_string_lossy().into_owned()
+}
+
+// ==================== tar（ustar）读写 ====================
+
+/// 写出 ustar 归档，只包含普通文件
+fn write_tar(out: &mut impl Write, entries: &[(String, Vec<u8>)], mtime: i64) -> Result<()> {
+    for (name, content) in entries {
+        out.write_all(&tar_header(name, content.len() as u64, mtime)?)?;
+        out.write_all(content)?;
+        out.write_all(&[0u8; BLOCK][..padding(content.len())])?;
+    }
+    // 归档以两个全零块结束
+    out.write_all(&[0u8; BLOCK * 2])?;
+    Ok(())
+}
+
+/// 读取 ustar 归档中的普通文件（目录和扩展头被跳过）
+fn read_tar(mut input: impl Read) -> Result<Vec<(String, Vec<u8>)>> {
+    let not_tar = || MemexiaError::Validation("History bundle is not a valid tar archive".to_string());
+    let mut entries = Vec::new();
+    let mut block = [0u8; BLOCK];
+    loop {
+        match input.read_exact(&mut block) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Err(not_tar()),
+            Err(e) => return Err(e.into()),
+        }
+        if block.iter().all(|b| *b == 0) {
+            break;
+        }
+        if &block[257..262] != b"ustar" || parse_octal(&block[148..156]) != Some(checksum(&block)) {
+            return Err(not_tar());
+        }
+        let size = parse_octal(&block[124..136]).ok_or_else(not_tar)? as usize;
+        let mut content = vec![0u8; size + padding(size)];
+        input.read_exact(&mut content).map_err(|_| not_tar())?;
+        content.truncate(size);
+        if matches!(block[156], b'0' | 0) {
+            let name = c_str(&block[..100]);
+            let prefix = c_str(&block[345..500]);
+            let path = if prefix.is_empty() { name } else { format!("{}/{}", prefix, name) };
+            entries.push((path, content));
+        }
+    }
+    Ok(entries)
+}
+
+/// 普通文件的 ustar 头
+fn tar_header(name: &str, size: u64, mtime: i64) -> Result<[u8; BLOCK]> {
+    let (prefix, name) = split_tar_name(name)?;
+    let mut block = [0u8; BLOCK];
+    block[..name.len()].copy_from_slice(name.as_bytes());
+    write_octal(&mut block[100..108], 0o644)?;
+    write_octal(&mut block[108..116], 0)?;
+    write_octal(&mut block[116..124], 0)?;
+    write_octal(&mut block[124..136], size)?;
+    write_octal(&mut block[136..148], mtime.max(0) as u64)?;
+    block[156] = b'0';
+    block[257..263].copy_from_slice(b"ustar\0");
+    block[263..265].copy_from_slice(b"00");
+    block[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+    let sum = format!("{:06o}\0 ", checksum(&block));
+    block[148..156].copy_from_slice(sum.as_bytes());
+    Ok(block)
+}
+
+/// 把超过 100 字节的路径在某个 `/` 处拆成前缀（至多 155 字节）和名称
+fn split_tar_name(name: &str) -> Result<(&str, &str)> {
+    if name.len() <= 100 {
+        return Ok(("", name));
+    }
+    name.match_indices('/')
+        .map(|(i, _)| (&name[..i], &name[i + 1..]))
+        .find(|(prefix, rest)| prefix.len() <= 155 && !rest.is_empty() && rest.len() <= 100)
+        .ok_or_else(|| MemexiaError::Validation(format!("Path too long for a tar archive: {}", name)))
+}
+
+/// 头部校验和：校验和字段按 8 个空格计算
+fn checksum(block: &[u8; BLOCK]) -> u64 {
+    block
+        .iter()
+        .enumerate()
+        .map(|(i, b)| if (148..156).contains(&i) { b' ' as u64 } else { *b as u64 })
+        .sum()
+}
+
+fn write_octal(field: &mut [u8], value: u64) -> Result<()> {
+    let text = format!("{:0width$o}\0", value, width = field.len() - 1);
+    if text.len() != field.len() {
+        return Err(MemexiaError::Validation(format!("Value too large for a tar header: {}", value)));
+    }
+    field.copy_from_slice(text.as_bytes());
+    Ok(())
+}
+
+fn parse_octal(field: &[u8]) -> Option<u64> {
+    let text = std::str::from_utf8(field).ok()?.trim_matches(|c| c == '\0' || c == ' ');
+    u64::from_str_radix(text, 8).ok()
+}
+
+fn c_str(field: &[u8]) -> String {
+    let end = field.iter().position(|b| *b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+/// 补齐到整块所需的字节数
+fn padding(size: usize) -> usize {
+    (BLOCK - size % BLOCK) % BLOCK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_tar_roundtrip() {
+        let long = format!("nodes/{}/{}", "urn_memexia_file_".to_string() + &"deep/".repeat(20).replace('/', "_"), "f".repeat(64));
+        let entries = vec![
+            ("manifest.json".to_string(), b"{}".to_vec()),
+            (long.clone(), vec![7u8; 1000]),
+            ("empty".to_string(), Vec::new()),
+        ];
+        let mut buffer = Vec::new();
+        write_tar(&mut buffer, &entries, 1_700_000_000).unwrap();
+        assert_eq!(buffer.len() % BLOCK, 0);
+        assert_eq!(read_tar(buffer.as_slice()).unwrap(), entries);
+
+        buffer[0] ^= 1;
+        assert!(read_tar(buffer.as_slice()).is_err());
+        assert!(read_tar(&b"not a tar"[..]).is_err());
+    }
+
+    #[test]
+    fn test_export_import_merge() {
+        let source = TempDir::new().unwrap();
+        let history = GraphHistory::init(source.path()).unwrap();
+        history.snapshot_node("urn:memexia:file:a.md", "v1", "c1").unwrap();
+        history.snapshot_node("urn:memexia:file:a.md", "v2", "c2").unwrap();
+        history.record_derivation("urn:memexia:file:b.md", "urn:memexia:file:a.md", "c2").unwrap();
+        history.record("c2", "ff00").unwrap();
+
+        let bundle = source.path().join("history.tar");
+        let manifest = export(&history, Some("root".to_string()), &bundle).unwrap();
+        assert_eq!((manifest.commit_links, manifest.nodes, manifest.node_snapshots, manifest.derivations), (1, 1, 2, 1));
+
+        let target = TempDir::new().unwrap();
+        let local = GraphHistory::init(target.path()).unwrap();
+        let report = import(&local, &bundle, Some("root"), false, |commit| commit != "c1").unwrap();
+        assert_eq!((report.commit_links, report.node_snapshots, report.derivations, report.skipped), (1, 2, 1, 0));
+        assert_eq!(report.missing_commits, ["c1"]);
+        assert!(!report.foreign_repository);
+        assert_eq!(local.get_node_history("urn:memexia:file:a.md").unwrap().len(), 2);
+        assert_eq!(local.get_derived_nodes("urn:memexia:file:a.md").unwrap().len(), 1);
+        // 本地没有图快照的提交关联标记为已清理
+        assert!(local.commit_links().unwrap()[0].pruned);
+
+        // 已有历史时必须显式合并，重复记录被跳过
+        assert!(import(&local, &bundle, Some("root"), false, |_| true).is_err());
+        let report = import(&local, &bundle, Some("other"), true, |_| true).unwrap();
+        assert_eq!((report.commit_links, report.node_snapshots, report.derivations, report.skipped), (0, 0, 0, 4));
+        assert!(report.foreign_repository);
+        assert_eq!(local.commit_links().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_import_rejects_unsafe_paths() {
+        let temp = TempDir::new().unwrap();
+        let bundle = temp.path().join("evil.tar");
+        let manifest = BundleManifest {
+            format: BUNDLE_FORMAT,
+            repository_id: None,
+            created: Utc::now(),
+            commit_links: 0,
+            nodes: 0,
+            node_snapshots: 0,
+            derivations: 0,
+        };
+        let entries = vec![
+            (MANIFEST_FILE.to_string(), serde_json::to_vec(&manifest).unwrap()),
+            ("nodes/../../escape".to_string(), b"x".to_vec()),
+        ];
+        write_tar(&mut fs::File::create(&bundle).unwrap(), &entries, 0).unwrap();
+
+        let history = GraphHistory::init(temp.path()).unwrap();
+        let err = import(&history, &bundle, None, false, |_| true).unwrap_err();
+        assert!(err.to_string().contains("Unexpected entry"), "{}", err);
+    }
+}
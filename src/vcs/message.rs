@@ -0,0 +1,173 @@
+//! 提交消息模块
+//!
+//! 处理 Git 风格的提交消息 trailer（消息末尾段落中的 `Key: value` 行）：
+//!
+//! - [`split_trailers`] - 拆分正文和 trailer
+//! - [`append_trailers`] - 在消息末尾追加 trailer
+//! - [`clean_message`] - 去掉编辑器模板中的 `#` 注释行
+//!
+//! Memexia 提交时自动追加以下 trailer：
+//!
+//! - `Affected-Nodes` - 本次提交的文件对应的节点 ID（逗号分隔）
+//! - `Graph-Snapshot` - 提交时的图快照哈希
+
+/// 受影响节点 trailer
+pub const AFFECTED_NODES: &str = "Affected-Nodes";
+
+/// 手写的受影响节点 trailer（与 `Affected-Nodes` 等价）
+pub const AFFECTS: &str = "Affects";
+
+/// 图快照 trailer
+pub const GRAPH_SNAPSHOT: &str = "Graph-Snapshot";
+
+/// 拆分提交消息的正文和 trailer
+///
+/// 最后一个段落的每一行都是 `Key: value`（或以空白开头的续行）时视为 trailer 段落
+///
+/// # Returns
+///
+/// 去掉 trailer 段落和末尾空白的正文，以及按出现顺序排列的 trailer
+pub fn split_trailers(message: &str) -> (String, Vec<(String, String)>) {
+    let trimmed = message.trim_end();
+    let (body, last) = match trimmed.rfind("\n\n") {
+        Some(idx) => (&trimmed[..idx], &trimmed[idx + 2..]),
+        None => ("", trimmed),
+    };
+
+    match parse_trailer_block(last) {
+        // 整条消息只有 trailer 时不拆分，避免把单行主题误判为 trailer
+        Some(trailers) if !body.trim().is_empty() => (body.trim_end().to_string(), trailers),
+        _ => (trimmed.to_string(), Vec::new()),
+    }
+}
+
+/// 解析 trailer 段落，存在非 trailer 行时返回 None
+fn parse_trailer_block(block: &str) -> Option<Vec<(String, String)>> {
+    let mut trailers: Vec<(String, String)> = Vec::new();
+
+    for line in block.lines() {
+        if line.starts_with(char::is_whitespace) {
+            // 续行拼接到上一个 trailer
+            let (_, value) = trailers.last_mut()?;
+            value.push(' ');
+            value.push_str(line.trim());
+            continue;
+        }
+
+        let (key, value) = line.split_once(':')?;
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return None;
+        }
+        trailers.push((key.to_string(), value.trim().to_string()));
+    }
+
+    (!trailers.is_empty()).then_some(trailers)
+}
+
+/// 在消息末尾追加 trailer
+///
+/// 已存在 trailer 段落时追加到该段落，否则以空行分隔新起一段；
+/// 值为空的 trailer 会被忽略
+pub fn append_trailers(message: &str, trailers: &[(&str, String)]) -> String {
+    let trailers: Vec<_> = trailers.iter().filter(|(_, value)| !value.is_empty()).collect();
+    let (body, mut existing) = split_trailers(message);
+    if trailers.is_empty() && existing.is_empty() {
+        return body;
+    }
+
+    for (key, value) in trailers {
+        existing.push((key.to_string(), value.clone()));
+    }
+
+    let block: Vec<String> = existing.iter().map(|(k, v)| format!("{}: {}", k, v)).collect();
+    if body.is_empty() {
+        block.join("\n")
+    } else {
+        format!("{}\n\n{}", body, block.join("\n"))
+    }
+}
+
+/// 获取 trailer 中提及的节点 ID（`Affected-Nodes` 和 `Affects`，逗号或空白分隔）
+pub fn affected_nodes(trailers: &[(String, String)]) -> Vec<&str> {
+    trailers
+        .iter()
+        .filter(|(key, _)| key.eq_ignore_ascii_case(AFFECTED_NODES) || key.eq_ignore_ascii_case(AFFECTS))
+        .flat_map(|(_, value)| value.split(|c: char| c == ',' || c.is_whitespace()))
+        .filter(|id| !id.is_empty())
+        .collect()
+}
+
+/// 清理编辑器中填写的提交消息
+///
+/// 与 Git 相同，去掉以 `#` 开头的注释行以及首尾空行
+pub fn clean_message(raw: &str) -> String {
+    raw.lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_trailers() {
+        let message = "Add idea\n\nLonger body.\n\nAffected-Nodes: urn:memexia:file:a.md, urn:memexia:file:b.md\nGraph-Snapshot: abc123\n";
+        let (body, trailers) = split_trailers(message);
+
+        assert_eq!(body, "Add idea\n\nLonger body.");
+        assert_eq!(trailers.len(), 2);
+        assert_eq!(trailers[1], ("Graph-Snapshot".to_string(), "abc123".to_string()));
+        assert_eq!(affected_nodes(&trailers), vec!["urn:memexia:file:a.md", "urn:memexia:file:b.md"]);
+    }
+
+    #[test]
+    fn test_split_trailers_without_block() {
+        // 最后一段不全是 trailer
+        let (body, trailers) = split_trailers("Fix\n\nNote: this is prose\nand more prose");
+        assert_eq!(body, "Fix\n\nNote: this is prose\nand more prose");
+        assert!(trailers.is_empty());
+
+        // 单行主题不视为 trailer
+        let (body, trailers) = split_trailers("Docs: update readme");
+        assert_eq!(body, "Docs: update readme");
+        assert!(trailers.is_empty());
+
+        // 键中不能有空格
+        assert!(split_trailers("Fix\n\nSee also: x").1.is_empty());
+    }
+
+    #[test]
+    fn test_trailer_continuation_and_affects() {
+        let (_, trailers) = split_trailers("Fix\n\nAffects: urn:memexia:file:a.md\n  urn:memexia:file:b.md");
+        assert_eq!(affected_nodes(&trailers), vec!["urn:memexia:file:a.md", "urn:memexia:file:b.md"]);
+    }
+
+    #[test]
+    fn test_append_trailers() {
+        let message = append_trailers("Add idea\n", &[(GRAPH_SNAPSHOT, "abc".to_string())]);
+        assert_eq!(message, "Add idea\n\nGraph-Snapshot: abc");
+
+        // 追加到用户已写的 trailer 段落，空值忽略
+        let message = append_trailers(
+            "Add idea\n\nAffects: urn:memexia:file:x.md",
+            &[(AFFECTED_NODES, String::new()), (GRAPH_SNAPSHOT, "abc".to_string())],
+        );
+        assert_eq!(message, "Add idea\n\nAffects: urn:memexia:file:x.md\nGraph-Snapshot: abc");
+
+        let (body, trailers) = split_trailers(&message);
+        assert_eq!(body, "Add idea");
+        assert_eq!(trailers.len(), 2);
+    }
+
+    #[test]
+    fn test_clean_message() {
+        let raw = "# Summarize the change\n\nAdd idea   \n# comment\n\nBody\n\n";
+        assert_eq!(clean_message(raw), "Add idea\n\nBody");
+        assert_eq!(clean_message("# only comments\n"), "");
+    }
+}
@@ -4,10 +4,18 @@
 
 pub mod git_engine;
 pub mod graph_history;
+pub mod history_bundle;
+pub mod message;
 pub mod rollback;
+pub mod snapshot_stats;
+pub mod snapshot_verify;
+pub mod traversal;
 
 pub use graph_history::{
+    AUTO_SNAPSHOT_PREFIX,
+    CommitLink,
     GraphHistory,
+    RetentionReport,
     NodeSnapshot,
     NodeHistoryEntry,
     DerivationEntry,
@@ -19,8 +27,17 @@ pub use rollback::{
     RollbackPreview,
 };
 
+pub use traversal::{
+    DerivationIndex,
+    Traversal,
+    TraversalLimits,
+    TraversalOutcome,
+};
+
 use std::path::{Path, PathBuf};
-use anyhow::Result;
+use crate::core::retention::RetentionPolicy;
+use crate::events::RepoEvent;
+use crate::error::Result;
 use crate::storage::Storage;
 
 /// 版本控制管理器
@@ -59,47 +76,99 @@ impl Vcs {
     /// 流程：
     /// 1. 导出当前图为 N-Quads 并计算哈希
     /// 2. 使用 git2 暂存文件
-    /// 3. 创建 Git 提交
+    /// 3. 创建 Git 提交（消息末尾追加 `Affected-Nodes` 和 `Graph-Snapshot` trailer）
     /// 4. 记录图历史
     /// 5. 返回提交 OID
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - 提交消息
+    /// * `files` - 要暂存的文件
+    /// * `affected_nodes` - 暂存文件对应的节点 ID
+    /// * `storage` - 存储
     pub fn commit(
         &mut self,
         message: &str,
         files: &[PathBuf],
+        affected_nodes: &[String],
         storage: &Storage,
     ) -> Result<String> {
         // 1. 导出图快照并计算哈希
-        let graph_hash = self.graph_history.snapshot(storage)?;
+        let graph_hash = tracing::info_span!("snapshot").in_scope(|| self.graph_history.snapshot(storage))?;
 
-        // 2. git add 暂存文件
-        self.git.add(files)?;
+        let full_message = message::append_trailers(
+            message,
+            &[
+                (message::AFFECTED_NODES, affected_nodes.join(", ")),
+                (message::GRAPH_SNAPSHOT, graph_hash.clone()),
+            ],
+        );
 
-        // 3. git commit 创建提交
-        let author = self.get_default_author()?;
-        let oid = self.git.commit(message, &author)?;
+        // 2. git add 暂存文件，3. git commit 创建提交
+        let oid = tracing::info_span!("git_commit").in_scope(|| -> Result<_> {
+            self.git.add(files)?;
+            let author = self.get_default_author()?;
+            self.git.commit(&full_message, &author)
+        })?;
 
         // 4. 记录图历史（提交哈希关联图快照哈希）
         self.graph_history.record(&oid.to_string(), &graph_hash)?;
+        storage.events().emit(RepoEvent::CommitCreated {
+            oid: oid.to_string(),
+            message: message.to_string(),
+        });
 
         // 5. 返回 commit hash
         Ok(oid.to_string())
     }
 
     /// 修改最后一次提交
+    ///
+    /// 保留原提交的 `Affected-Nodes` trailer，并用新的图快照更新 `Graph-Snapshot`
     pub fn amend(&mut self, message: &str, storage: &Storage) -> Result<()> {
         // 导出新的图快照
-        let graph_hash = self.graph_history.snapshot(storage)?;
+        let graph_hash = tracing::info_span!("snapshot").in_scope(|| self.graph_history.snapshot(storage))?;
+
+        let affected_nodes = self
+            .head_info()?
+            .map(|head| head.affected_nodes().join(", "))
+            .unwrap_or_default();
+        let full_message = message::append_trailers(
+            message,
+            &[
+                (message::AFFECTED_NODES, affected_nodes),
+                (message::GRAPH_SNAPSHOT, graph_hash.clone()),
+            ],
+        );
 
         // git commit --amend
-        let author = self.get_default_author()?;
-        let oid = self.git.amend(message, &author)?;
+        let oid = tracing::info_span!("git_commit").in_scope(|| -> Result<_> {
+            let author = self.get_default_author()?;
+            self.git.amend(&full_message, &author)
+        })?;
 
         // 更新图历史引用
         self.graph_history.record(&oid.to_string(), &graph_hash)?;
+        storage.events().emit(RepoEvent::CommitCreated {
+            oid: oid.to_string(),
+            message: message.to_string(),
+        });
 
         Ok(())
     }
 
+    /// 按保留策略清理提交快照，HEAD 和各分支末端的快照始终保留
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - 保留策略
+    /// * `dry_run` - 只计算不修改
+    pub fn apply_retention(&self, policy: &RetentionPolicy, dry_run: bool) -> Result<RetentionReport> {
+        let mut protected = self.git.branch_tips()?;
+        protected.extend(self.git.get_head_oid().map(|oid| oid.to_string()));
+        self.graph_history.apply_retention(policy, &protected, dry_run)
+    }
+
     /// 获取提交历史
     pub fn log(&self, limit: usize) -> Result<Vec<CommitInfo>> {
         self.git.log(limit)
@@ -110,6 +179,52 @@ impl Vcs {
         self.git.head_info()
     }
 
+    /// 解析修订表达式（缩写哈希、`HEAD~N`、分支名等）为完整提交 OID，
+    /// 见 [`git_engine::GitEngine::resolve_revision`]
+    pub fn resolve_revision(&self, rev: &str) -> Result<git2::Oid> {
+        self.git.resolve_revision(rev)
+    }
+
+    /// 导出节点历史、推导记录和提交关联（`memexia history export`），见 [`history_bundle`]
+    pub fn export_history(&self, file: &Path) -> Result<history_bundle::BundleManifest> {
+        history_bundle::export(&self.graph_history, self.git.root_commit()?, file)
+    }
+
+    /// 导入 [`export_history`](Self::export_history) 导出的历史包
+    ///
+    /// 引用的提交在本地 Git 仓库中不存在时给出警告，见 [`history_bundle::import`]
+    pub fn import_history(&self, file: &Path, merge: bool) -> Result<history_bundle::ImportReport> {
+        let local_id = self.git.root_commit()?;
+        history_bundle::import(&self.graph_history, file, local_id.as_deref(), merge, |oid| {
+            self.git.commit_exists(oid)
+        })
+    }
+
+    /// 暂存文件到 Git 索引
+    pub fn stage(&self, files: &[PathBuf]) -> Result<()> {
+        self.git.add(files)
+    }
+
+    /// 工作区中内容已变化的已跟踪文件（可选包含未跟踪文件），路径相对仓库根目录
+    pub fn worktree_changes(&self, include_untracked: bool) -> Result<Vec<PathBuf>> {
+        self.git.worktree_changes(include_untracked)
+    }
+
+    /// 修改过指定文件的提交（从新到旧，跟随重命名），见 [`git_engine::GitEngine::file_history`]
+    pub fn file_history(&self, relative: &Path) -> Result<Vec<FileRevision>> {
+        self.git.file_history(relative)
+    }
+
+    /// HEAD 与工作区之间被重命名的文件（旧路径，新路径），路径相对仓库根目录
+    pub fn worktree_renames(&self, pathspecs: &[String]) -> Result<Vec<(PathBuf, PathBuf)>> {
+        self.git.worktree_renames(pathspecs)
+    }
+
+    /// 从 HEAD 检出被删除的文件，返回是否写回了文件
+    pub fn restore_file(&self, relative: &Path) -> Result<bool> {
+        self.git.restore_file(relative)
+    }
+
     /// 获取默认作者信息
     fn get_default_author(&self) -> Result<String> {
         // 尝试从 git config 读取用户信息
@@ -132,8 +247,10 @@ impl Vcs {
 pub struct CommitInfo {
     /// Git OID
     pub oid: String,
-    /// 提交消息
+    /// 提交消息（不含 trailer）
     pub message: String,
+    /// 提交消息末尾的 trailer
+    pub trailers: Vec<(String, String)>,
     /// 图快照哈希
     pub graph_hash: Option<String>,
     /// 作者
@@ -145,7 +262,63 @@ pub struct CommitInfo {
 impl CommitInfo {
     /// 简短格式（用于 --oneline）
     pub fn to_short(&self) -> String {
-        let short_oid = &self.oid[..7];
+        let short_oid = &self.oid[..7.min(self.oid.len())];
         format!("{} - {}", short_oid, self.message)
     }
+
+    /// 从原始提交消息创建，拆分出 trailer
+    ///
+    /// `graph_hash` 默认取 `Graph-Snapshot` trailer，可由图历史覆盖
+    pub fn from_raw(oid: String, raw_message: &str, author: String, timestamp: String) -> Self {
+        let (message, trailers) = message::split_trailers(raw_message);
+        let graph_hash = trailers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(message::GRAPH_SNAPSHOT))
+            .map(|(_, value)| value.clone());
+
+        Self {
+            oid,
+            message,
+            trailers,
+            graph_hash,
+            author,
+            timestamp,
+        }
+    }
+
+    /// 提交时间（`timestamp` 为 UTC 的 `%Y-%m-%d %H:%M:%S`），无法解析时返回 None
+    pub fn time(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        chrono::NaiveDateTime::parse_from_str(&self.timestamp, "%Y-%m-%d %H:%M:%S")
+            .ok()
+            .map(|time| time.and_utc())
+    }
+
+    /// trailer 中记录的受影响节点 ID
+    pub fn affected_nodes(&self) -> Vec<&str> {
+        message::affected_nodes(&self.trailers)
+    }
+
+    /// 提交是否涉及指定节点
+    pub fn touches_node(&self, id: &str) -> bool {
+        self.affected_nodes().contains(&id)
+    }
+}
+
+/// 文件在一次提交中的变更（`memexia file log`）
+#[derive(Debug, Clone)]
+pub struct FileRevision {
+    /// Git OID
+    pub oid: String,
+    /// 提交消息（不含 trailer）
+    pub message: String,
+    /// 提交时间
+    pub time: chrono::DateTime<chrono::Utc>,
+    /// 此提交中的文件路径（相对仓库根目录）
+    pub path: PathBuf,
+    /// 此提交把文件从该路径重命名而来
+    pub renamed_from: Option<PathBuf>,
+    /// 提交后的内容；文件在此提交中被删除时为 None
+    pub content: Option<Vec<u8>>,
+    /// 提交前的内容；文件在此提交中创建时为 None
+    pub previous: Option<Vec<u8>>,
 }
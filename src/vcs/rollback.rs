@@ -3,9 +3,10 @@
 //! 提供节点和推导链的回退功能，支持回退到任意历史版本
 
 use std::path::{Path, PathBuf};
-use anyhow::{Result, Context};
+use crate::error::{MemexiaError, Result};
 
 use crate::vcs::graph_history::{GraphHistory, NodeSnapshot, NodeHistoryEntry, DerivationEntry};
+use crate::vcs::traversal::{Traversal, TraversalLimits};
 
 /// 回退管理器
 pub struct RollbackManager {
@@ -32,7 +33,9 @@ impl RollbackManager {
     pub fn rollback_node(&self, node_id: &str, target_hash: &str) -> Result<String> {
         // 获取目标快照
         let snapshot = self.history.get_node_snapshot(node_id, target_hash)?
-            .with_context(|| format!("Node snapshot not found: {}@{}", node_id, target_hash))?;
+            .ok_or_else(|| MemexiaError::SnapshotNotFound {
+                hash: format!("{}@{}", node_id, target_hash),
+            })?;
 
         Ok(snapshot)
     }
@@ -59,48 +62,21 @@ impl RollbackManager {
 
     /// 预览节点回退影响
     ///
-    /// 返回将受影响的节点列表
-    pub fn preview_node_rollback(&self, node_id: &str) -> Result<Vec<String>> {
-        let mut affected = Vec::new();
-        let mut visited = std::collections::HashSet::new();
-
-        // 查找所有从该节点推导出的节点（这些节点可能会受到影响）
-        self.collect_derived_nodes(node_id, &mut affected, &mut visited)?;
-
-        Ok(affected)
-    }
-
-    /// 递归收集所有推导出的节点
-    fn collect_derived_nodes(
-        &self,
-        node_id: &str,
-        affected: &mut Vec<String>,
-        visited: &mut std::collections::HashSet<String>,
-    ) -> Result<()> {
-        if visited.contains(node_id) {
-            return Ok(());
-        }
-        visited.insert(node_id.to_string());
-
-        let derived = self.history.get_derived_nodes(node_id)?;
-        for entry in derived {
-            if !affected.contains(&entry.child_id) {
-                affected.push(entry.child_id.clone());
-                self.collect_derived_nodes(&entry.child_id, affected, visited)?;
-            }
-        }
-
-        Ok(())
+    /// 返回将受影响的节点列表（从该节点推导出的所有节点，按广度优先顺序），
+    /// 遍历受 `limits` 限制，并报告推导记录中的环
+    pub fn preview_node_rollback(&self, node_id: &str, limits: &TraversalLimits) -> Result<Traversal<Vec<String>>> {
+        Ok(self.history.derivation_index()?.descendants(node_id, limits))
     }
 
     /// 回退推导链到根节点
     ///
-    /// 将推导链中的所有节点回退到根节点所在的状态
-    pub fn rollback_derivation_chain(&self, leaf_node_id: &str) -> Result<Vec<RollbackResult>> {
+    /// 将推导链中的所有节点回退到根节点所在的状态；推导链受 `limits` 限制，
+    /// 链被截断或成环时以链上最后到达的节点为根
+    pub fn rollback_derivation_chain(&self, leaf_node_id: &str, limits: &TraversalLimits) -> Result<Traversal<Vec<RollbackResult>>> {
         let mut results = Vec::new();
 
         // 构建推导链
-        let chain = self.history.build_derivation_chain(leaf_node_id)?;
+        let Traversal { value: chain, outcome } = self.history.build_derivation_chain(leaf_node_id, limits)?;
 
         // 找到根节点
         let root_id = if let Some(last) = chain.last() {
@@ -136,7 +112,7 @@ impl RollbackManager {
             }
         }
 
-        Ok(results)
+        Ok(Traversal { value: results, outcome })
     }
 
     /// 获取节点的完整历史
@@ -145,8 +121,8 @@ impl RollbackManager {
     }
 
     /// 获取节点的推导链
-    pub fn get_derivation_chain(&self, node_id: &str) -> Result<Vec<DerivationEntry>> {
-        self.history.build_derivation_chain(node_id)
+    pub fn get_derivation_chain(&self, node_id: &str, limits: &TraversalLimits) -> Result<Traversal<Vec<DerivationEntry>>> {
+        self.history.build_derivation_chain(node_id, limits)
     }
 
     /// 检查节点是否存在
@@ -251,7 +227,7 @@ mod tests {
         history.record_derivation("child2", "child1", "c2").unwrap();
 
         // 预览 root 节点回退影响
-        let affected = rollback.preview_node_rollback("root").unwrap();
+        let affected = rollback.preview_node_rollback("root", &TraversalLimits::default()).unwrap().value;
         assert_eq!(affected.len(), 2);
         assert!(affected.contains(&"child1".to_string()));
         assert!(affected.contains(&"child2".to_string()));
@@ -275,7 +251,7 @@ mod tests {
         history.snapshot_node("child2", r#"{"id":"child2"}"#, "c2").unwrap();
 
         // 回退推导链
-        let results = rollback.rollback_derivation_chain("child2").unwrap();
+        let results = rollback.rollback_derivation_chain("child2", &TraversalLimits::default()).unwrap().value;
         // 应该包含 root, child1, child2 的回退结果
         assert!(!results.is_empty());
     }
@@ -368,7 +344,7 @@ mod tests {
         let rollback = RollbackManager::new(path).unwrap();
 
         // 预览孤立节点
-        let affected = rollback.preview_node_rollback("orphan").unwrap();
+        let affected = rollback.preview_node_rollback("orphan", &TraversalLimits::default()).unwrap().value;
         assert!(affected.is_empty());
     }
 
@@ -388,10 +364,63 @@ mod tests {
         }
 
         // 预览 root 的影响
-        let affected = rollback.preview_node_rollback("root").unwrap();
+        let affected = rollback.preview_node_rollback("root", &TraversalLimits::default()).unwrap().value;
         assert_eq!(affected.len(), 5);
     }
 
+    #[test]
+    fn test_preview_node_rollback_limits() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path();
+
+        let history = GraphHistory::init(path).unwrap();
+        let rollback = RollbackManager::new(path).unwrap();
+
+        // 10000 层的推导链: n0 -> n1 -> ... -> n10000，不应栈溢出
+        let entries = (1..=10_000)
+            .map(|i| DerivationEntry {
+                child_id: format!("n{}", i),
+                parent_id: format!("n{}", i - 1),
+                timestamp: chrono::Utc::now(),
+                commit_hash: format!("c{}", i),
+            })
+            .collect();
+        history.merge_derivations(entries).unwrap();
+
+        let unlimited = TraversalLimits { max_depth: 20_000, max_nodes: 20_000 };
+        let affected = rollback.preview_node_rollback("n0", &unlimited).unwrap();
+        assert_eq!(affected.value.len(), 10_000);
+        assert!(affected.outcome.is_complete());
+
+        let affected = rollback.preview_node_rollback("n0", &TraversalLimits::default().with_max_depth(300)).unwrap();
+        assert_eq!(affected.value.len(), 300);
+        assert!(affected.outcome.depth_limit_hit);
+
+        let affected = rollback.preview_node_rollback("n0", &TraversalLimits { max_depth: 20_000, max_nodes: 42 }).unwrap();
+        assert_eq!(affected.value.len(), 42);
+        assert!(affected.outcome.node_limit_hit);
+    }
+
+    #[test]
+    fn test_preview_node_rollback_cycle() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path();
+
+        let history = GraphHistory::init(path).unwrap();
+        let rollback = RollbackManager::new(path).unwrap();
+
+        // root -> a -> b -> c -> a
+        history.record_derivation("a", "root", "c1").unwrap();
+        history.record_derivation("b", "a", "c2").unwrap();
+        history.record_derivation("c", "b", "c3").unwrap();
+        history.record_derivation("a", "c", "c4").unwrap();
+
+        let affected = rollback.preview_node_rollback("root", &TraversalLimits::default()).unwrap();
+        assert_eq!(affected.value, ["a", "b", "c"]);
+        assert_eq!(affected.outcome.cycles, vec![vec!["a", "b", "c"]]);
+        assert!(!affected.outcome.depth_limit_hit && !affected.outcome.node_limit_hit);
+    }
+
     #[test]
     fn test_get_node_history() {
         let temp = TempDir::new().unwrap();
@@ -424,7 +453,7 @@ mod tests {
         history.record_derivation("child2", "child1", "c2").unwrap();
         history.record_derivation("child3", "child2", "c3").unwrap();
 
-        let chain = rollback.get_derivation_chain("child3").unwrap();
+        let chain = rollback.get_derivation_chain("child3", &TraversalLimits::default()).unwrap().value;
         assert_eq!(chain.len(), 3);
     }
 
@@ -455,7 +484,7 @@ mod tests {
         let rollback = RollbackManager::new(path).unwrap();
 
         // 没有推导关系的节点
-        let results = rollback.rollback_derivation_chain("orphan").unwrap();
+        let results = rollback.rollback_derivation_chain("orphan", &TraversalLimits::default()).unwrap().value;
         // 只有一个根节点回退结果
         assert!(results.is_empty() || results.len() == 1);
     }
@@ -478,7 +507,7 @@ mod tests {
         history.snapshot_node("C", r#"{"id":"C"}"#, "c2").unwrap();
 
         // 回退推导链
-        let results = rollback.rollback_derivation_chain("C").unwrap();
+        let results = rollback.rollback_derivation_chain("C", &TraversalLimits::default()).unwrap().value;
 
         // 应该包含 A 和 B 的回退结果
         let node_ids: Vec<_> = results.iter().map(|r| r.node_id.clone()).collect();
@@ -0,0 +1,147 @@
+//! 图快照校验模块（`memexia snapshot verify`）
+//!
+//! 逐个检查 `.memexia/history/snapshots` 下的快照：
+//!
+//! - 内容哈希与目录名加文件名一致
+//! - `.meta` 存在，且其中记录的哈希和大小与内容一致
+//! - 每一行都是合法的 N-Quads
+//!
+//! 校验全部快照时还检查 `commit-links` 中未被清理的关联指向的快照都存在。
+//! `--repair-meta` 按快照内容重新生成缺失或过期的 `.meta`；内容与哈希不符的快照
+//! 无法修复，也不会为它生成 `.meta`，只能从其他副本恢复
+
+use crate::storage::nquads::NQuadsDecoder;
+use crate::vcs::CommitLink;
+use std::fmt;
+
+/// 快照的问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotProblem {
+    /// 快照文件无法读取
+    Unreadable { message: String },
+    /// 内容哈希与文件名不一致
+    HashMismatch { actual: String },
+    /// 第 `line` 行不是合法的 N-Quads
+    InvalidNQuads { line: usize, message: String },
+    /// 缺少 `.meta`
+    MissingMeta,
+    /// `.meta` 无法解析，或记录的哈希、大小与内容不一致
+    StaleMeta { reason: String },
+}
+
+impl SnapshotProblem {
+    /// 是否为 `.meta` 的问题（可由 `--repair-meta` 修复）
+    pub fn is_meta(&self) -> bool {
+        matches!(self, SnapshotProblem::MissingMeta | SnapshotProblem::StaleMeta { .. })
+    }
+}
+
+impl fmt::Display for SnapshotProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotProblem::Unreadable { message } => write!(f, "unreadable: {}", message),
+            SnapshotProblem::HashMismatch { actual } => write!(f, "content hash is {}", actual),
+            SnapshotProblem::InvalidNQuads { line, message } => write!(f, "invalid N-Quads at line {}: {}", line, message),
+            SnapshotProblem::MissingMeta => write!(f, ".meta is missing"),
+            SnapshotProblem::StaleMeta { reason } => write!(f, ".meta is stale: {}", reason),
+        }
+    }
+}
+
+/// 单个快照的校验结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotStatus {
+    /// 快照哈希（取自目录名和文件名）
+    pub hash: String,
+    /// 快照文件大小（字节）
+    pub size: u64,
+    /// 发现且未修复的问题
+    pub problems: Vec<SnapshotProblem>,
+    /// 已重新生成的 `.meta` 修复的问题
+    pub repaired: Option<SnapshotProblem>,
+}
+
+impl SnapshotStatus {
+    /// 是否没有未修复的问题
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// 校验报告
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VerifyReport {
+    /// 每个快照的结果（按哈希排序）
+    pub snapshots: Vec<SnapshotStatus>,
+    /// 指向不存在的快照且未标记为已清理的提交关联（只在校验全部快照时检查）
+    pub dangling_links: Vec<CommitLink>,
+}
+
+impl VerifyReport {
+    /// 是否没有未修复的问题
+    pub fn is_ok(&self) -> bool {
+        self.snapshots.iter().all(SnapshotStatus::is_ok) && self.dangling_links.is_empty()
+    }
+
+    /// 有未修复问题的快照数
+    pub fn failed(&self) -> usize {
+        self.snapshots.iter().filter(|s| !s.is_ok()).count()
+    }
+
+    /// 修复了 `.meta` 的快照数
+    pub fn repaired(&self) -> usize {
+        self.snapshots.iter().filter(|s| s.repaired.is_some()).count()
+    }
+}
+
+/// 逐行检查 N-Quads，返回第一个错误的行号和原因
+pub fn check_nquads(content: &[u8]) -> Option<(usize, String)> {
+    let mut decoder = NQuadsDecoder::new(content);
+    loop {
+        match decoder.read_triple() {
+            Ok(Some(_)) => {}
+            Ok(None) => return None,
+            Err(e) => return Some((decoder.line_number(), e.to_string())),
+        }
+    }
+}
+
+/// 内容是否按规范顺序排列（用于为缺少 `.meta` 的快照推断格式版本）
+///
+/// 规范顺序按（主语, 谓语, 宾语）排序且无重复，见 [`crate::storage::nquads`]
+pub fn is_canonical(content: &str) -> bool {
+    let key = |line: &'_ str| -> Option<(String, String, String)> {
+        let line = line.trim_end().strip_suffix(" .")?;
+        let (subject, rest) = line.split_once(' ')?;
+        let (predicate, object) = rest.split_once(' ')?;
+        Some((subject.to_string(), predicate.to_string(), object.to_string()))
+    };
+    let mut previous = None;
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        let Some(current) = key(line) else {
+            return false;
+        };
+        if previous.as_ref().is_some_and(|previous| previous >= &current) {
+            return false;
+        }
+        previous = Some(current);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_nquads_and_canonical_order() {
+        let good = "<a> <p> \"x\" .\n<a> <q> <b> .\n";
+        assert_eq!(check_nquads(good.as_bytes()), None);
+        assert!(is_canonical(good));
+        assert!(!is_canonical("<a> <q> <b> .\n<a> <p> \"x\" .\n"));
+
+        let (line, _) = check_nquads(b"<a> <p> \"x\" .\ngarbage\n").unwrap();
+        assert_eq!(line, 2);
+        assert!(check_nquads(b"<a> <p> \"\xff\" .\n").is_some());
+    }
+}
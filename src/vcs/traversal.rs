@@ -0,0 +1,294 @@
+//! 推导记录遍历模块
+//!
+//! 推导链、推导树和回退预览都沿推导记录遍历。推导记录由用户和导入产生，
+//! 可能很深，也可能成环，所有遍历都：
+//!
+//! - 用显式队列迭代，不依赖递归深度
+//! - 受 [`TraversalLimits`] 限制最大深度和最多访问的节点数
+//! - 在 [`TraversalOutcome`] 中报告是否触及限制，以及发现的环（环上节点的 ID）
+//!
+//! 限制通过配置调整：
+//!
+//! ```toml
+//! [history.traversal]
+//! max_depth = 1000
+//! max_nodes = 10000
+//! ```
+
+use crate::vcs::DerivationEntry;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+/// 默认最大深度
+pub const DEFAULT_MAX_DEPTH: usize = 1000;
+
+/// 默认最多访问的节点数
+pub const DEFAULT_MAX_NODES: usize = 10_000;
+
+/// 遍历限制
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraversalLimits {
+    /// 最大深度（起点为 0）
+    #[serde(default = "default_max_depth")]
+    pub max_depth: usize,
+    /// 最多访问的节点数（不含起点）
+    #[serde(default = "default_max_nodes")]
+    pub max_nodes: usize,
+}
+
+impl Default for TraversalLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_nodes: DEFAULT_MAX_NODES,
+        }
+    }
+}
+
+impl TraversalLimits {
+    /// 替换最大深度
+    pub fn with_max_depth(self, max_depth: usize) -> Self {
+        Self { max_depth, ..self }
+    }
+}
+
+fn default_max_depth() -> usize {
+    DEFAULT_MAX_DEPTH
+}
+
+fn default_max_nodes() -> usize {
+    DEFAULT_MAX_NODES
+}
+
+/// 遍历情况
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TraversalOutcome {
+    /// 是否因最大深度停止展开
+    pub depth_limit_hit: bool,
+    /// 是否因节点数上限停止遍历
+    pub node_limit_hit: bool,
+    /// 发现的环，每个环按遍历方向列出环上的节点 ID
+    pub cycles: Vec<Vec<String>>,
+}
+
+impl TraversalOutcome {
+    /// 遍历是否完整（没有触及限制也没有环）
+    pub fn is_complete(&self) -> bool {
+        !self.depth_limit_hit && !self.node_limit_hit && self.cycles.is_empty()
+    }
+
+    /// 合并另一次遍历的情况
+    pub fn merge(&mut self, other: TraversalOutcome) {
+        self.depth_limit_hit |= other.depth_limit_hit;
+        self.node_limit_hit |= other.node_limit_hit;
+        for cycle in other.cycles {
+            self.record_cycle(cycle);
+        }
+    }
+
+    /// 记录一个环（同一组节点的环只记录一次）
+    pub fn record_cycle(&mut self, cycle: Vec<String>) {
+        let members: BTreeSet<&String> = cycle.iter().collect();
+        if !self.cycles.iter().any(|known| known.iter().collect::<BTreeSet<_>>() == members) {
+            self.cycles.push(cycle);
+        }
+    }
+}
+
+/// 遍历结果和遍历情况
+#[derive(Debug, Clone)]
+pub struct Traversal<T> {
+    /// 遍历结果
+    pub value: T,
+    /// 遍历情况
+    pub outcome: TraversalOutcome,
+}
+
+/// 推导记录的内存索引，遍历前一次性读取 `derivations.json`
+#[derive(Debug, Clone, Default)]
+pub struct DerivationIndex {
+    /// 子节点 -> 推导记录（按记录顺序）
+    parents: HashMap<String, Vec<DerivationEntry>>,
+    /// 父节点 -> 推导记录（按记录顺序）
+    children: HashMap<String, Vec<DerivationEntry>>,
+}
+
+impl DerivationIndex {
+    /// 由推导记录建立索引
+    pub fn new(entries: Vec<DerivationEntry>) -> Self {
+        let mut index = Self::default();
+        for entry in entries {
+            index.parents.entry(entry.child_id.clone()).or_default().push(entry.clone());
+            index.children.entry(entry.parent_id.clone()).or_default().push(entry);
+        }
+        index
+    }
+
+    /// 节点的推导来源
+    pub fn parents(&self, id: &str) -> &[DerivationEntry] {
+        self.parents.get(id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// 由节点推导出的记录
+    pub fn children(&self, id: &str) -> &[DerivationEntry] {
+        self.children.get(id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// 沿每个节点的第一条推导记录走出的链
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - 起点
+    /// * `upstream` - true 时沿推导来源（叶到根），否则沿推导出的节点（根到叶）
+    /// * `limits` - 遍历限制，链长同时受最大深度和节点数限制
+    pub fn chain(&self, start: &str, upstream: bool, limits: &TraversalLimits) -> Traversal<Vec<DerivationEntry>> {
+        let mut chain: Vec<DerivationEntry> = Vec::new();
+        let mut outcome = TraversalOutcome::default();
+        let mut path = vec![start.to_string()];
+        let mut positions: HashMap<String, usize> = HashMap::from([(start.to_string(), 0)]);
+
+        loop {
+            let current = path.last().unwrap();
+            let next = if upstream { self.parents(current).first() } else { self.children(current).first() };
+            let Some(entry) = next else {
+                break;
+            };
+            let id = if upstream { &entry.parent_id } else { &entry.child_id };
+            if let Some(&position) = positions.get(id) {
+                outcome.record_cycle(path[position..].to_vec());
+                break;
+            }
+            if chain.len() >= limits.max_depth {
+                outcome.depth_limit_hit = true;
+                break;
+            }
+            if chain.len() >= limits.max_nodes {
+                outcome.node_limit_hit = true;
+                break;
+            }
+            positions.insert(id.clone(), path.len());
+            path.push(id.clone());
+            chain.push(entry.clone());
+        }
+
+        Traversal { value: chain, outcome }
+    }
+
+    /// 广度优先收集由起点推导出的所有节点（按发现顺序，不含起点），并检测其中的环
+    pub fn descendants(&self, start: &str, limits: &TraversalLimits) -> Traversal<Vec<String>> {
+        let next = |id: &str| -> Vec<String> { self.children(id).iter().map(|e| e.child_id.clone()).collect() };
+        let mut outcome = TraversalOutcome::default();
+        let mut found = Vec::new();
+        let mut visited: HashSet<String> = HashSet::from([start.to_string()]);
+        let mut queue = VecDeque::from([(start.to_string(), 0)]);
+
+        'walk: while let Some((id, depth)) = queue.pop_front() {
+            let neighbors = next(&id);
+            if depth >= limits.max_depth {
+                outcome.depth_limit_hit |= neighbors.iter().any(|n| !visited.contains(n));
+                continue;
+            }
+            for neighbor in neighbors {
+                if visited.contains(&neighbor) {
+                    continue;
+                }
+                if found.len() >= limits.max_nodes {
+                    outcome.node_limit_hit = true;
+                    break 'walk;
+                }
+                visited.insert(neighbor.clone());
+                found.push(neighbor.clone());
+                queue.push_back((neighbor, depth + 1));
+            }
+        }
+
+        for cycle in find_cycles(start, &visited, next) {
+            outcome.record_cycle(cycle);
+        }
+        Traversal { value: found, outcome }
+    }
+}
+
+/// 在 `within` 内从起点出发的环（迭代深度优先，每个节点只展开一次）
+///
+/// 沿遍历方向回到当前路径上的节点即构成环，环从该节点开始列出
+pub fn find_cycles(start: &str, within: &HashSet<String>, next: impl Fn(&str) -> Vec<String>) -> Vec<Vec<String>> {
+    let mut cycles = Vec::new();
+    let mut done: HashSet<String> = HashSet::new();
+    let mut path: Vec<String> = vec![start.to_string()];
+    let mut on_path: HashMap<String, usize> = HashMap::from([(start.to_string(), 0)]);
+    let mut pending: Vec<Vec<String>> = vec![next(start)];
+
+    while let Some(neighbors) = pending.last_mut() {
+        let Some(neighbor) = neighbors.pop() else {
+            pending.pop();
+            let id = path.pop().unwrap();
+            on_path.remove(&id);
+            done.insert(id);
+            continue;
+        };
+        if !within.contains(&neighbor) || done.contains(&neighbor) {
+            continue;
+        }
+        if let Some(&position) = on_path.get(&neighbor) {
+            cycles.push(path[position..].to_vec());
+            continue;
+        }
+        on_path.insert(neighbor.clone(), path.len());
+        pending.push(next(&neighbor));
+        path.push(neighbor);
+    }
+    cycles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn entry(child: &str, parent: &str) -> DerivationEntry {
+        DerivationEntry {
+            child_id: child.to_string(),
+            parent_id: parent.to_string(),
+            timestamp: Utc::now(),
+            commit_hash: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_deep_chain_is_iterative_and_limited() {
+        let entries = (1..=10_000).map(|i| entry(&format!("n{}", i), &format!("n{}", i - 1))).collect();
+        let index = DerivationIndex::new(entries);
+
+        let full = index.descendants("n0", &TraversalLimits { max_depth: 20_000, max_nodes: 20_000 });
+        assert_eq!(full.value.len(), 10_000);
+        assert!(full.outcome.is_complete());
+
+        let limited = index.descendants("n0", &TraversalLimits::default().with_max_depth(100));
+        assert_eq!(limited.value.len(), 100);
+        assert!(limited.outcome.depth_limit_hit && !limited.outcome.node_limit_hit);
+
+        let capped = index.descendants("n0", &TraversalLimits { max_depth: 20_000, max_nodes: 50 });
+        assert_eq!(capped.value.len(), 50);
+        assert!(capped.outcome.node_limit_hit);
+
+        let chain = index.chain("n10000", true, &TraversalLimits::default());
+        assert_eq!(chain.value.len(), DEFAULT_MAX_DEPTH);
+        assert!(chain.outcome.depth_limit_hit);
+    }
+
+    #[test]
+    fn test_cycles_are_reported() {
+        // a -> b -> c -> a，外加 c -> d
+        let index = DerivationIndex::new(vec![entry("b", "a"), entry("c", "b"), entry("a", "c"), entry("d", "c")]);
+
+        let down = index.descendants("a", &TraversalLimits::default());
+        assert_eq!(down.value.len(), 3);
+        assert_eq!(down.outcome.cycles, vec![vec!["a", "b", "c"]]);
+
+        let up = index.chain("b", true, &TraversalLimits::default());
+        assert_eq!(up.value.len(), 2);
+        assert_eq!(up.outcome.cycles, vec![vec!["b", "a", "c"]]);
+        assert!(!up.outcome.depth_limit_hit);
+    }
+}
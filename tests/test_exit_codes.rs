@@ -0,0 +1,209 @@
+//! CLI 退出码集成测试
+//!
+//! 直接运行编译出的二进制，断言各类失败的退出码和 stderr 格式
+
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+/// 在指定目录中运行 memexia
+fn memexia(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_memexia"))
+        .args(args)
+        .current_dir(dir)
+        .env("RUST_LOG", "off")
+        .env("MEMEXIA_LANG", "en")
+        .output()
+        .expect("failed to run memexia binary")
+}
+
+/// 创建已初始化仓库的临时目录
+fn init_repo() -> TempDir {
+    let temp = TempDir::new().unwrap();
+    let output = memexia(temp.path(), &["init", "."]);
+    assert!(output.status.success(), "init failed: {:?}", output);
+    temp
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
+
+#[test]
+fn test_exit_code_success() {
+    let repo = init_repo();
+    let output = memexia(repo.path(), &["status"]);
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn test_exit_code_not_a_repository() {
+    let temp = TempDir::new().unwrap();
+    let output = memexia(temp.path(), &["status"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    let err = stderr(&output);
+    assert!(err.starts_with("error: Not a Memexia repository"));
+    assert_eq!(err.trim_end().lines().count(), 1);
+}
+
+#[test]
+fn test_exit_code_nothing_to_commit() {
+    let repo = init_repo();
+    let output = memexia(repo.path(), &["commit", "-m", "empty"]);
+
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(stderr(&output).trim_end(), "error: Nothing to commit");
+}
+
+#[test]
+fn test_error_message_localized() {
+    let repo = init_repo();
+    let output = memexia(repo.path(), &["--lang", "zh", "commit", "-m", "empty"]);
+
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(stderr(&output).trim_end(), "错误: 没有可提交的内容");
+
+    // 无法识别的语言回退到英文
+    let output = memexia(repo.path(), &["--lang", "tlh", "commit", "-m", "empty"]);
+    assert_eq!(stderr(&output).trim_end(), "error: Nothing to commit");
+}
+
+#[test]
+fn test_exit_code_validation() {
+    let repo = init_repo();
+    let output = memexia(repo.path(), &["new", "Idea", "--type", "bogus"]);
+
+    assert_eq!(output.status.code(), Some(4));
+    assert_eq!(stderr(&output).trim_end(), "error: Unknown node type: bogus");
+
+    let output = memexia(repo.path(), &["export"]);
+    assert_eq!(output.status.code(), Some(4));
+}
+
+#[test]
+fn test_exit_code_internal_error() {
+    let repo = init_repo();
+    let output = memexia(repo.path(), &["add", "missing.md"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).starts_with("error: File not found"));
+}
+
+#[test]
+fn test_verbose_prints_error_chain() {
+    let repo = init_repo();
+    let output = memexia(repo.path(), &["-v", "add", "missing.md"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("caused by:"));
+}
+
+#[test]
+fn test_help_documents_exit_codes() {
+    let temp = TempDir::new().unwrap();
+    let output = memexia(temp.path(), &["--help"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(stdout.contains("Exit codes:"));
+    assert!(stdout.contains("3  Nothing to commit"));
+}
+
+#[test]
+fn test_exit_code_validate() {
+    let repo = init_repo();
+
+    let output = memexia(repo.path(), &["validate"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim_end(), "No violations found");
+
+    let output = memexia(repo.path(), &["validate", "--severity", "fatal"]);
+    assert_eq!(output.status.code(), Some(4));
+}
+
+#[test]
+fn test_exit_code_lint() {
+    let repo = init_repo();
+    let notes = repo.path().join("notes");
+    std::fs::create_dir_all(&notes).unwrap();
+    std::fs::write(notes.join("clean.md"), "---\ntitle: Clean\n---\n\n# Clean\n").unwrap();
+
+    let output = memexia(repo.path(), &["lint"]);
+    assert_eq!(output.status.code(), Some(0));
+
+    // 只有警告
+    std::fs::write(notes.join("warn.md"), "---\ntitle: Warn\n---\n\n# Other\n").unwrap();
+    let output = memexia(repo.path(), &["lint"]);
+    assert_eq!(output.status.code(), Some(6));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("notes/warn.md:5: warning [title_mismatch]"));
+
+    // 错误优先于警告
+    std::fs::write(notes.join("error.md"), "---\ntitle: Error\n---\n\n# Error\n\n[[ ]]\n").unwrap();
+    let output = memexia(repo.path(), &["lint", "--json"]);
+    assert_eq!(output.status.code(), Some(4));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let rules: Vec<&str> = report["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["rule"].as_str().unwrap())
+        .collect();
+    assert_eq!(rules, ["empty_link", "title_mismatch"]);
+    assert_eq!(report["diagnostics"][0]["path"], "notes/error.md");
+    assert_eq!(report["diagnostics"][0]["line"], 7);
+
+    // 只检查指定文件
+    let output = memexia(repo.path(), &["lint", "notes/clean.md"]);
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn test_read_only_flag() {
+    let repo = init_repo();
+    std::fs::write(repo.path().join("a.md"), "# A\n").unwrap();
+
+    let output = memexia(repo.path(), &["--read-only", "stats"]);
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+
+    let output = memexia(repo.path(), &["--read-only", "add", "a.md"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("opened read-only"), "{}", stderr(&output));
+    assert!(!repo.path().join(".memexia/index").exists());
+}
+
+#[test]
+fn test_corrupt_graph_store_recovery() {
+    let repo = init_repo();
+    std::fs::write(repo.path().join("a.md"), "# A\n").unwrap();
+    assert!(memexia(repo.path(), &["add", "a.md"]).status.success());
+    assert!(memexia(repo.path(), &["commit", "-m", "Add A"]).status.success());
+
+    // 截断图存储的所有文件
+    let graph = repo.path().join(".memexia/graph");
+    for entry in std::fs::read_dir(&graph).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_file() {
+            std::fs::File::create(&path).unwrap();
+        }
+    }
+
+    let output = memexia(repo.path(), &["stats"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("memexia reindex --full"), "{}", stderr(&output));
+
+    // 不需要图的命令仍可运行
+    assert_eq!(memexia(repo.path(), &["status"]).status.code(), Some(0));
+    assert_eq!(memexia(repo.path(), &["log", "--no-pager"]).status.code(), Some(0));
+    let output = memexia(repo.path(), &["doctor"]);
+    assert_eq!(output.status.code(), Some(6), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Graph store: cannot be opened"), "{}", stdout);
+    assert!(stdout.contains("memexia reindex --full"), "{}", stdout);
+
+    let output = memexia(repo.path(), &["reindex", "--full"]);
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+    assert!(stderr(&output).contains("could not be opened and was moved"), "{}", stderr(&output));
+    assert_eq!(memexia(repo.path(), &["stats"]).status.code(), Some(0));
+    assert_eq!(memexia(repo.path(), &["doctor"]).status.code(), Some(0));
+}
@@ -33,10 +33,29 @@ fn test_cli_parse_commit() {
     let args = vec!["memexia", "commit", "-m", "Test message"];
     let cli = Cli::try_parse_from(&args).unwrap();
     if let Commands::Commit(commit_args) = cli.command {
-        assert_eq!(commit_args.message, "Test message");
+        assert_eq!(commit_args.message.as_deref(), Some("Test message"));
     }
 }
 
+#[test]
+fn test_cli_parse_commit_all() {
+    let cli = Cli::try_parse_from(["memexia", "commit", "-a", "-m", "Edit notes"]).unwrap();
+    match cli.command {
+        Commands::Commit(args) => {
+            assert!(args.all);
+            assert!(!args.include_untracked);
+            assert_eq!(args.message.as_deref(), Some("Edit notes"));
+        }
+        _ => panic!("Expected Commit command"),
+    }
+
+    let cli = Cli::try_parse_from(["memexia", "commit", "--all", "--include-untracked", "-m", "x"]).unwrap();
+    assert!(matches!(cli.command, Commands::Commit(args) if args.all && args.include_untracked));
+
+    // --include-untracked 需要 --all
+    assert!(Cli::try_parse_from(["memexia", "commit", "--include-untracked", "-m", "x"]).is_err());
+}
+
 #[test]
 fn test_cli_parse_amend() {
     let args = vec!["memexia", "amend", "-m", "Amended message"];
@@ -64,6 +83,18 @@ fn test_cli_parse_log_default_limit() {
     }
 }
 
+#[test]
+fn test_cli_parse_log_node() {
+    let args = vec!["memexia", "log", "--node", "notes/idea.md", "--oneline"];
+    let cli = Cli::try_parse_from(&args).unwrap();
+    if let Commands::Log(log_args) = cli.command {
+        assert_eq!(log_args.node, Some(std::path::PathBuf::from("notes/idea.md")));
+        assert!(log_args.oneline);
+    } else {
+        panic!("expected log command");
+    }
+}
+
 #[test]
 fn test_cli_parse_verbose() {
     let args = vec!["memexia", "-v", "status"];
@@ -84,7 +115,11 @@ fn test_cli_parse_search() {
     let cli = Cli::try_parse_from(&args).unwrap();
     if let Commands::Search(search_args) = cli.command {
         assert_eq!(search_args.query, "test query");
+        assert_eq!(search_args.lang, None);
     }
+
+    let cli = Cli::try_parse_from(["memexia", "search", "自由", "--lang", "zh"]).unwrap();
+    assert!(matches!(cli.command, Commands::Search(args) if args.lang.as_deref() == Some("zh")));
 }
 
 #[test]
@@ -104,6 +139,21 @@ fn test_cli_parse_config() {
     }
 }
 
+#[test]
+fn test_cli_parse_new() {
+    let args = vec!["memexia", "new", "Free Will", "--type", "Question", "--tag", "a", "--tag", "b", "--template", "daily", "--edit"];
+    let cli = Cli::try_parse_from(&args).unwrap();
+    if let Commands::New(new_args) = cli.command {
+        assert_eq!(new_args.title, "Free Will");
+        assert_eq!(new_args.node_type.as_deref(), Some("Question"));
+        assert_eq!(new_args.tags, vec!["a", "b"]);
+        assert_eq!(new_args.template, Some("daily".to_string()));
+        assert!(new_args.edit);
+    } else {
+        panic!("Expected New command");
+    }
+}
+
 #[test]
 fn test_cli_parse_reindex() {
     let args = vec!["memexia", "reindex"];
@@ -131,5 +181,1065 @@ fn test_cli_parse_serve() {
     let cli = Cli::try_parse_from(&args).unwrap();
     if let Commands::Serve(serve_args) = cli.command {
         assert_eq!(serve_args.port, 8080);
+        assert!(serve_args.command.is_none());
+    }
+
+    let cli = Cli::try_parse_from(["memexia", "serve", "token", "create", "--scopes", "read,edges:write"]).unwrap();
+    match cli.command {
+        Commands::Serve(args) => match args.command {
+            Some(memexia::cli::ServeCommands::Token(args)) => {
+                let memexia::cli::ServeTokenCommands::Create(args) = args.command;
+                assert_eq!(args.scopes, ["read", "edges:write"]);
+            }
+            None => panic!("Expected token create"),
+        },
+        _ => panic!("Expected Serve command"),
+    }
+    assert!(Cli::try_parse_from(["memexia", "serve", "token", "create"]).is_err());
+}
+
+#[cfg(feature = "debug-tools")]
+#[test]
+fn test_cli_parse_debug_generate_vault() {
+    use memexia::cli::DebugCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "debug", "generate-vault", "vault", "--notes", "5000", "--seed", "7"]).unwrap();
+    match cli.command {
+        Commands::Debug(args) => {
+            let DebugCommands::GenerateVault(args) = args.command;
+            assert_eq!(args.dir.to_string_lossy(), "vault");
+            assert_eq!((args.notes, args.links_per_note, args.tags, args.seed), (5000, 4, 50, 7));
+            assert_eq!(args.broken_fraction, 0.0);
+        }
+        _ => panic!("Expected Debug command"),
+    }
+}
+
+#[test]
+fn test_cli_parse_export_csv() {
+    let args = vec!["memexia", "export", "--format", "csv", "--nodes", "nodes.csv", "--edges", "edges.csv"];
+    let cli = Cli::try_parse_from(&args).unwrap();
+    if let Commands::Export(export_args) = cli.command {
+        assert_eq!(export_args.format, "csv");
+        assert_eq!(export_args.nodes.unwrap().to_string_lossy(), "nodes.csv");
+        assert_eq!(export_args.edges.unwrap().to_string_lossy(), "edges.csv");
+    } else {
+        panic!("Expected Export command");
+    }
+}
+
+#[test]
+fn test_cli_parse_import_csv() {
+    let args = vec!["memexia", "import", "--edges", "edges.csv", "--create-placeholders"];
+    let cli = Cli::try_parse_from(&args).unwrap();
+    if let Commands::Import(import_args) = cli.command {
+        assert_eq!(import_args.format, "csv");
+        assert!(import_args.nodes.is_none());
+        assert!(import_args.create_placeholders);
+    } else {
+        panic!("Expected Import command");
+    }
+}
+
+#[test]
+fn test_cli_parse_export_ndjson() {
+    let args = vec!["memexia", "export", "--format", "ndjson", "--edges-only", "--since", "2024-06-01"];
+    let cli = Cli::try_parse_from(&args).unwrap();
+    if let Commands::Export(export_args) = cli.command {
+        assert_eq!(export_args.format, "ndjson");
+        assert!(export_args.edges_only && !export_args.nodes_only);
+        assert_eq!(export_args.since.as_deref(), Some("2024-06-01"));
+        assert!(export_args.output.is_none());
+    } else {
+        panic!("Expected Export command");
+    }
+
+    let args = vec!["memexia", "export", "--format", "ndjson", "--nodes-only", "--edges-only"];
+    assert!(Cli::try_parse_from(&args).is_err());
+
+    let args = vec!["memexia", "import", "--format", "ndjson", "-i", "graph.ndjson"];
+    let cli = Cli::try_parse_from(&args).unwrap();
+    if let Commands::Import(import_args) = cli.command {
+        assert_eq!(import_args.input.unwrap().to_string_lossy(), "graph.ndjson");
+    } else {
+        panic!("Expected Import command");
+    }
+}
+
+#[test]
+fn test_cli_parse_graph_mermaid() {
+    use memexia::cli::GraphCommands;
+
+    let args = vec!["memexia", "graph", "mermaid", "--center", "notes/a.md", "--depth", "2"];
+    let cli = Cli::try_parse_from(&args).unwrap();
+    if let Commands::Graph(graph_args) = cli.command {
+        if let GraphCommands::Mermaid(mermaid_args) = graph_args.command {
+            assert_eq!(mermaid_args.center.unwrap().to_string_lossy(), "notes/a.md");
+            assert_eq!(mermaid_args.depth, 2);
+            assert_eq!(mermaid_args.max_nodes, 200);
+        } else {
+            panic!("Expected Mermaid subcommand");
+        }
+    } else {
+        panic!("Expected Graph command");
+    }
+}
+
+#[test]
+fn test_cli_parse_no_pager() {
+    let cli = Cli::try_parse_from(["memexia", "log", "--no-pager"]).unwrap();
+    assert!(cli.no_pager);
+
+    let cli = Cli::try_parse_from(["memexia", "--no-pager", "graph", "show"]).unwrap();
+    assert!(cli.no_pager);
+
+    let cli = Cli::try_parse_from(["memexia", "log"]).unwrap();
+    assert!(!cli.no_pager);
+}
+
+#[test]
+fn test_cli_parse_time_flags() {
+    let cli = Cli::try_parse_from(["memexia", "log", "--iso", "--utc"]).unwrap();
+    assert!(cli.iso && cli.utc);
+
+    let cli = Cli::try_parse_from(["memexia", "--utc", "file", "info", "a.md"]).unwrap();
+    assert!(cli.utc && !cli.iso);
+}
+
+#[test]
+fn test_cli_parse_lang() {
+    let cli = Cli::try_parse_from(["memexia", "--lang", "zh", "status"]).unwrap();
+    assert_eq!(cli.lang.as_deref(), Some("zh"));
+
+    let cli = Cli::try_parse_from(["memexia", "status"]).unwrap();
+    assert!(cli.lang.is_none());
+}
+
+#[test]
+fn test_cli_parse_trace_json() {
+    let cli = Cli::try_parse_from(["memexia", "reindex", "--trace-json", "trace.jsonl"]).unwrap();
+    assert_eq!(cli.trace_json.unwrap().to_string_lossy(), "trace.jsonl");
+    assert_eq!(cli.command.name(), "reindex");
+}
+
+#[test]
+fn test_cli_parse_dashboard() {
+    let cli = Cli::try_parse_from(["memexia", "dashboard", "--json"]).unwrap();
+    assert_eq!(cli.command.name(), "dashboard");
+    assert!(matches!(cli.command, Commands::Dashboard(args) if args.json));
+}
+
+#[test]
+fn test_cli_parse_feed() {
+    let cli = Cli::try_parse_from(["memexia", "feed", "--since", "2026-01-01", "--format", "rss", "-o", "feed.xml"]).unwrap();
+    assert_eq!(cli.command.name(), "feed");
+    match cli.command {
+        Commands::Feed(args) => {
+            assert_eq!(args.since.as_deref(), Some("2026-01-01"));
+            assert_eq!(args.format, "rss");
+            assert_eq!(args.output, Some(std::path::PathBuf::from("feed.xml")));
+        }
+        _ => panic!("Expected Feed command"),
+    }
+
+    let cli = Cli::try_parse_from(["memexia", "feed"]).unwrap();
+    assert!(matches!(cli.command, Commands::Feed(args) if args.format == "atom" && args.since.is_none()));
+}
+
+#[test]
+fn test_cli_parse_stats() {
+    let cli = Cli::try_parse_from(["memexia", "stats", "--json"]).unwrap();
+    match cli.command {
+        Commands::Stats(args) => assert!(args.json),
+        _ => panic!("Expected Stats command"),
+    }
+
+    let cli = Cli::try_parse_from(["memexia", "stats"]).unwrap();
+    assert_eq!(cli.command.name(), "stats");
+
+    let cli = Cli::try_parse_from(["memexia", "stats", "--history", "--json"]).unwrap();
+    match cli.command {
+        Commands::Stats(args) => assert!(args.history && args.json),
+        _ => panic!("Expected Stats command"),
+    }
+    assert!(Cli::try_parse_from(["memexia", "stats", "--history", "--include-archived"]).is_err());
+}
+
+#[test]
+fn test_cli_parse_random() {
+    let cli = Cli::try_parse_from(["memexia", "random", "--type", "Question", "--tag", "mind"]).unwrap();
+    match cli.command {
+        Commands::Random(args) => {
+            assert_eq!(args.node_type.as_deref(), Some("Question"));
+            assert_eq!(args.tag.as_deref(), Some("mind"));
+        }
+        _ => panic!("Expected Random command"),
     }
 }
+
+#[test]
+fn test_cli_parse_review() {
+    let cli = Cli::try_parse_from(["memexia", "review"]).unwrap();
+    match cli.command {
+        Commands::Review(args) => {
+            assert_eq!(args.limit, 10);
+            assert_eq!(args.interval, 7);
+            assert!(args.mark_reviewed.is_none());
+        }
+        _ => panic!("Expected Review command"),
+    }
+
+    let cli = Cli::try_parse_from(["memexia", "review", "--limit", "3", "--mark-reviewed", "notes/a.md"]).unwrap();
+    match cli.command {
+        Commands::Review(args) => {
+            assert_eq!(args.limit, 3);
+            assert_eq!(args.mark_reviewed.unwrap().to_string_lossy(), "notes/a.md");
+        }
+        _ => panic!("Expected Review command"),
+    }
+}
+
+#[test]
+fn test_cli_parse_link_suggestions() {
+    use memexia::cli::{LinkCommands, SuggestionCommands};
+
+    let cli = Cli::try_parse_from(["memexia", "link", "suggestions", "accept-all"]).unwrap();
+    match cli.command {
+        Commands::Link(args) => match args.command {
+            LinkCommands::Suggestions(args) => match args.command {
+                SuggestionCommands::AcceptAll(args) => assert_eq!(args.min_confidence, 0.8),
+                _ => panic!("Expected AcceptAll"),
+            },
+            _ => panic!("Expected Suggestions"),
+        },
+        _ => panic!("Expected Link command"),
+    }
+
+    let cli = Cli::try_parse_from([
+        "memexia", "link", "suggestions", "reject", "urn:memexia:edge:urn:memexia:file:a.md-urn:memexia:file:b.md",
+    ])
+    .unwrap();
+    match cli.command {
+        Commands::Link(args) => match args.command {
+            LinkCommands::Suggestions(args) => match args.command {
+                SuggestionCommands::Reject(args) => assert!(args.edge_id.ends_with("b.md")),
+                _ => panic!("Expected Reject"),
+            },
+            _ => panic!("Expected Suggestions"),
+        },
+        _ => panic!("Expected Link command"),
+    }
+
+    assert!(Cli::try_parse_from(["memexia", "link", "suggestions", "list"]).is_ok());
+}
+
+#[test]
+fn test_cli_parse_link_propose() {
+    use memexia::cli::LinkCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "link", "propose"]).unwrap();
+    match cli.command {
+        Commands::Link(args) => match args.command {
+            LinkCommands::Propose(args) => {
+                assert!(!args.apply);
+                assert_eq!(args.min_shared_tags, 2);
+            }
+            _ => panic!("Expected Propose"),
+        },
+        _ => panic!("Expected Link command"),
+    }
+
+    let cli = Cli::try_parse_from(["memexia", "link", "propose", "--apply", "--min-shared-tags", "3"]).unwrap();
+    match cli.command {
+        Commands::Link(args) => match args.command {
+            LinkCommands::Propose(args) => {
+                assert!(args.apply);
+                assert_eq!(args.min_shared_tags, 3);
+            }
+            _ => panic!("Expected Propose"),
+        },
+        _ => panic!("Expected Link command"),
+    }
+}
+
+#[test]
+fn test_cli_parse_file_links_at() {
+    use memexia::cli::FileCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "file", "links", "note.md", "--at", "2024-03-01", "--at", "abc1234"])
+        .unwrap();
+    match cli.command {
+        Commands::File(args) => match args.command {
+            FileCommands::Links(args) => assert_eq!(args.at, vec!["2024-03-01", "abc1234"]),
+            _ => panic!("Expected Links"),
+        },
+        _ => panic!("Expected File command"),
+    }
+}
+
+#[test]
+fn test_cli_parse_file_links_verbose() {
+    use memexia::cli::FileCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "file", "links", "note.md", "-v"]).unwrap();
+    match cli.command {
+        Commands::File(args) => match args.command {
+            FileCommands::Links(args) => assert!(args.verbose && args.at.is_empty()),
+            _ => panic!("Expected Links"),
+        },
+        _ => panic!("Expected File command"),
+    }
+}
+
+#[test]
+fn test_cli_parse_graph_decay() {
+    use memexia::cli::GraphCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "graph", "decay", "--half-life-days", "90", "--dry-run"]).unwrap();
+    match cli.command {
+        Commands::Graph(args) => match args.command {
+            GraphCommands::Decay(args) => {
+                assert_eq!(args.half_life_days, 90.0);
+                assert!(args.dry_run);
+            }
+            _ => panic!("Expected Decay"),
+        },
+        _ => panic!("Expected Graph command"),
+    }
+
+    // 半衰期为必填项
+    assert!(Cli::try_parse_from(["memexia", "graph", "decay"]).is_err());
+}
+
+#[test]
+fn test_cli_parse_graph_layout() {
+    use memexia::cli::GraphCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "graph", "layout", "--iterations", "50", "--seed", "7", "-o", "layout.json"]).unwrap();
+    match cli.command {
+        Commands::Graph(args) => match args.command {
+            GraphCommands::Layout(args) => {
+                assert_eq!((args.iterations, args.seed), (50, 7));
+                assert_eq!(args.output.as_deref(), Some(std::path::Path::new("layout.json")));
+            }
+            _ => panic!("Expected Layout"),
+        },
+        _ => panic!("Expected Graph command"),
+    }
+
+    let cli = Cli::try_parse_from(["memexia", "graph", "layout"]).unwrap();
+    assert!(matches!(
+        cli.command,
+        Commands::Graph(args) if matches!(args.command, GraphCommands::Layout(ref a) if a.iterations == 300 && a.seed == 0 && a.output.is_none())
+    ));
+}
+
+#[test]
+fn test_cli_parse_validate() {
+    let cli = Cli::try_parse_from(["memexia", "validate"]).unwrap();
+    match cli.command {
+        Commands::Validate(args) => {
+            assert_eq!(args.severity, "warn");
+            assert!(!args.json);
+        }
+        _ => panic!("Expected Validate command"),
+    }
+
+    let cli = Cli::try_parse_from(["memexia", "validate", "--severity", "error"]).unwrap();
+    match cli.command {
+        Commands::Validate(args) => assert_eq!(args.severity, "error"),
+        _ => panic!("Expected Validate command"),
+    }
+}
+
+#[test]
+fn test_cli_parse_report_duplicates() {
+    use memexia::cli::ReportCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "report", "duplicates"]).unwrap();
+    match cli.command {
+        Commands::Report(args) => match args.command {
+            ReportCommands::Duplicates(args) => {
+                assert!(!args.content);
+                assert_eq!(args.threshold, 0.8);
+            }
+            _ => panic!("Expected Report Duplicates command"),
+        },
+        _ => panic!("Expected Report command"),
+    }
+
+    let cli = Cli::try_parse_from(["memexia", "report", "duplicates", "--content", "--threshold", "0.6"]).unwrap();
+    match cli.command {
+        Commands::Report(args) => match args.command {
+            ReportCommands::Duplicates(args) => {
+                assert!(args.content);
+                assert_eq!(args.threshold, 0.6);
+            }
+            _ => panic!("Expected Report Duplicates command"),
+        },
+        _ => panic!("Expected Report command"),
+    }
+}
+
+#[test]
+fn test_cli_parse_watch_and_snapshot() {
+    use memexia::cli::SnapshotCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "watch", "--snapshot-every", "6h"]).unwrap();
+    match cli.command {
+        Commands::Watch(args) => assert_eq!(args.snapshot_every.as_deref(), Some("6h")),
+        _ => panic!("Expected Watch command"),
+    }
+
+    let cli = Cli::try_parse_from(["memexia", "watch"]).unwrap();
+    assert!(matches!(cli.command, Commands::Watch(args) if args.snapshot_every.is_none()));
+
+    let cli = Cli::try_parse_from(["memexia", "snapshot", "now"]).unwrap();
+    assert!(matches!(cli.command, Commands::Snapshot(args) if matches!(args.command, SnapshotCommands::Now)));
+}
+
+#[test]
+fn test_cli_parse_snapshot_verify() {
+    use memexia::cli::SnapshotCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "snapshot", "verify", "--all", "--repair-meta"]).unwrap();
+    match cli.command {
+        Commands::Snapshot(args) => match args.command {
+            SnapshotCommands::Verify(args) => assert!(args.all && args.repair_meta && args.hash.is_none()),
+            _ => panic!("Expected Verify subcommand"),
+        },
+        _ => panic!("Expected Snapshot command"),
+    }
+
+    let cli = Cli::try_parse_from(["memexia", "snapshot", "verify", "abc123"]).unwrap();
+    assert!(matches!(
+        cli.command,
+        Commands::Snapshot(args) if matches!(&args.command, SnapshotCommands::Verify(v) if v.hash.as_deref() == Some("abc123") && !v.all)
+    ));
+
+    assert!(Cli::try_parse_from(["memexia", "snapshot", "verify"]).is_err());
+    assert!(Cli::try_parse_from(["memexia", "snapshot", "verify", "abc", "--all"]).is_err());
+}
+
+#[test]
+fn test_cli_parse_node_restore_and_gc() {
+    use memexia::cli::NodeCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "node", "restore", "notes/a.md"]).unwrap();
+    match cli.command {
+        Commands::Node(args) => match args.command {
+            NodeCommands::Restore(args) => assert_eq!(args.path, std::path::PathBuf::from("notes/a.md")),
+            _ => panic!("Expected Restore subcommand"),
+        },
+        _ => panic!("Expected Node command"),
+    }
+
+    let cli = Cli::try_parse_from([
+        "memexia", "node", "set", "notes/a.md", "--title", "A", "-t", "Question", "--tag", "x", "--tag", "y", "--untag", "z",
+    ])
+    .unwrap();
+    match cli.command {
+        Commands::Node(args) => match args.command {
+            NodeCommands::Set(args) => {
+                assert_eq!(args.title.as_deref(), Some("A"));
+                assert_eq!(args.node_type.as_deref(), Some("Question"));
+                assert_eq!(args.add_tags, ["x", "y"]);
+                assert_eq!(args.remove_tags, ["z"]);
+                assert!(args.aliases.is_empty());
+                assert!(!args.write_file);
+            }
+            _ => panic!("Expected Set subcommand"),
+        },
+        _ => panic!("Expected Node command"),
+    }
+
+    let cli = Cli::try_parse_from([
+        "memexia",
+        "node",
+        "set",
+        "urn:memexia:generated:x",
+        "--add-tag",
+        "x",
+        "--remove-tag",
+        "y",
+        "--title",
+        "X",
+        "--write-file",
+    ])
+    .unwrap();
+    match cli.command {
+        Commands::Node(args) => match args.command {
+            NodeCommands::Set(args) => {
+                assert_eq!(args.target, "urn:memexia:generated:x");
+                assert_eq!(args.add_tags, ["x"]);
+                assert_eq!(args.remove_tags, ["y"]);
+                assert!(args.write_file);
+            }
+            _ => panic!("Expected Set subcommand"),
+        },
+        _ => panic!("Expected Node command"),
+    }
+
+    let cli = Cli::try_parse_from(["memexia", "gc", "--purge-deleted", "--older-than", "30d"]).unwrap();
+    match cli.command {
+        Commands::Gc(args) => {
+            assert!(args.purge_deleted);
+            assert_eq!(args.older_than.as_deref(), Some("30d"));
+        }
+        _ => panic!("Expected Gc command"),
+    }
+
+    let cli = Cli::try_parse_from(["memexia", "gc"]).unwrap();
+    assert!(matches!(cli.command, Commands::Gc(args) if !args.purge_deleted && args.older_than.is_none()));
+
+    // --older-than 需要 --purge-deleted
+    assert!(Cli::try_parse_from(["memexia", "gc", "--older-than", "30d"]).is_err());
+}
+
+#[test]
+fn test_cli_parse_dry_run_flags() {
+    use memexia::cli::LinkCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "reindex", "--full", "--dry-run"]).unwrap();
+    assert!(matches!(cli.command, Commands::Reindex(args) if args.full && args.dry_run));
+
+    let cli = Cli::try_parse_from(["memexia", "link", "delete", "a.md", "b.md", "--dry-run"]).unwrap();
+    match cli.command {
+        Commands::Link(args) => match args.command {
+            LinkCommands::Delete(args) => assert!(args.dry_run),
+            _ => panic!("Expected Link Delete command"),
+        },
+        _ => panic!("Expected Link command"),
+    }
+
+    let cli = Cli::try_parse_from(["memexia", "gc", "--purge-deleted", "--dry-run"]).unwrap();
+    assert!(matches!(cli.command, Commands::Gc(args) if args.purge_deleted && args.dry_run));
+
+    let cli = Cli::try_parse_from(["memexia", "import", "--nodes", "n.csv", "--dry-run"]).unwrap();
+    assert!(matches!(cli.command, Commands::Import(args) if args.dry_run));
+
+    let cli = Cli::try_parse_from(["memexia", "reindex"]).unwrap();
+    assert!(matches!(cli.command, Commands::Reindex(args) if !args.dry_run));
+}
+
+#[test]
+fn test_cli_parse_undo() {
+    let cli = Cli::try_parse_from(["memexia", "undo"]).unwrap();
+    assert!(matches!(cli.command, Commands::Undo(_)));
+    assert_eq!(cli.command.name(), "undo");
+}
+
+#[test]
+fn test_cli_parse_doctor() {
+    let cli = Cli::try_parse_from(["memexia", "doctor"]).unwrap();
+    assert!(matches!(cli.command, Commands::Doctor(args) if !args.fix));
+
+    let cli = Cli::try_parse_from(["memexia", "doctor", "--fix"]).unwrap();
+    assert!(matches!(cli.command, Commands::Doctor(args) if args.fix && args.fix_dangling.is_none()));
+
+    let cli = Cli::try_parse_from(["memexia", "doctor", "--fix-dangling", "drop"]).unwrap();
+    assert!(matches!(cli.command, Commands::Doctor(args) if args.fix_dangling.as_deref() == Some("drop")));
+}
+
+#[test]
+fn test_cli_parse_init_notes() {
+    let cli = Cli::try_parse_from(["memexia", "init"]).unwrap();
+    assert!(matches!(cli.command, Commands::Init(args) if args.notes.is_empty()));
+
+    let cli = Cli::try_parse_from(["memexia", "init", "vault", "--notes", "content", "--notes", "journal"]).unwrap();
+    assert!(matches!(cli.command, Commands::Init(args) if args.notes == ["content", "journal"]));
+}
+
+#[test]
+fn test_cli_parse_link_annotate() {
+    use memexia::cli::LinkCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "link", "annotate", "a.md", "b.md", "--text", "Needs a citation"]).unwrap();
+    match cli.command {
+        Commands::Link(args) => match args.command {
+            LinkCommands::Annotate(args) => {
+                assert_eq!(args.source.to_str(), Some("a.md"));
+                assert_eq!(args.text, "Needs a citation");
+            }
+            _ => panic!("Expected Annotate"),
+        },
+        _ => panic!("Expected Link command"),
+    }
+
+    assert!(Cli::try_parse_from(["memexia", "link", "annotate", "a.md", "b.md"]).is_err());
+    let cli = Cli::try_parse_from(["memexia", "link", "annotations", "a.md", "b.md"]).unwrap();
+    assert!(matches!(cli.command, Commands::Link(args) if matches!(args.command, LinkCommands::Annotations(_))));
+}
+
+#[test]
+fn test_cli_parse_link_set() {
+    use memexia::cli::LinkCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "link", "set-strength", "a.md", "b.md", "0.4"]).unwrap();
+    match cli.command {
+        Commands::Link(args) => match args.command {
+            LinkCommands::SetStrength(args) => {
+                assert_eq!((args.source.to_str(), args.target.to_str()), (Some("a.md"), Some("b.md")));
+                assert_eq!(args.value, 0.4);
+            }
+            _ => panic!("Expected SetStrength"),
+        },
+        _ => panic!("Expected Link command"),
+    }
+
+    assert!(Cli::try_parse_from(["memexia", "link", "set-strength", "a.md", "b.md", "strong"]).is_err());
+    let cli = Cli::try_parse_from(["memexia", "link", "set-relation", "a.md", "b.md", "Refines"]).unwrap();
+    assert!(matches!(cli.command, Commands::Link(args) if matches!(args.command, LinkCommands::SetRelation(ref a) if a.value == "Refines")));
+    let cli = Cli::try_parse_from(["memexia", "link", "set-description", "a.md", "b.md", ""]).unwrap();
+    assert!(matches!(cli.command, Commands::Link(args) if matches!(args.command, LinkCommands::SetDescription(_))));
+}
+
+#[test]
+fn test_cli_parse_repos() {
+    use memexia::cli::ReposCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "repos", "list"]).unwrap();
+    assert!(matches!(cli.command, Commands::Repos(args) if matches!(args.command, ReposCommands::List)));
+
+    let cli = Cli::try_parse_from(["memexia", "repos", "add", "../work", "--name", "work"]).unwrap();
+    match cli.command {
+        Commands::Repos(args) => match args.command {
+            ReposCommands::Add(args) => assert_eq!(args.name.as_deref(), Some("work")),
+            _ => panic!("Expected Add"),
+        },
+        _ => panic!("Expected Repos command"),
+    }
+
+    let cli = Cli::try_parse_from(["memexia", "repos", "rename", "work", "job"]).unwrap();
+    assert!(matches!(cli.command, Commands::Repos(args) if matches!(args.command, ReposCommands::Rename(_))));
+}
+
+#[test]
+fn test_cli_parse_repo_selection() {
+    let cli = Cli::try_parse_from(["memexia", "stats", "--repo", "work"]).unwrap();
+    assert_eq!(cli.repo.as_deref(), Some("work"));
+
+    let cli = Cli::try_parse_from(["memexia", "-C", "../vault", "status"]).unwrap();
+    assert_eq!(cli.chdir.as_deref(), Some(std::path::Path::new("../vault")));
+
+    assert!(Cli::try_parse_from(["memexia", "-C", "../vault", "--repo", "work", "status"]).is_err());
+}
+
+#[test]
+fn test_cli_parse_graph_query_csv() {
+    use memexia::cli::GraphCommands;
+
+    let cli = Cli::try_parse_from([
+        "memexia", "graph", "query", "SELECT ?s WHERE { ?s ?p ?o }", "--output", "results.csv", "--excel", "--typed",
+    ])
+    .unwrap();
+    match cli.command {
+        Commands::Graph(args) => match args.command {
+            GraphCommands::Query(args) => {
+                assert_eq!(args.output.as_deref(), Some(std::path::Path::new("results.csv")));
+                assert!(args.excel && args.typed);
+            }
+            _ => panic!("Expected Query"),
+        },
+        _ => panic!("Expected Graph command"),
+    }
+
+    assert!(Cli::try_parse_from(["memexia", "graph", "query", "SELECT * {}", "--excel"]).is_err());
+}
+
+#[test]
+fn test_cli_parse_graph_query_template() {
+    use memexia::cli::GraphCommands;
+
+    let cli = Cli::try_parse_from([
+        "memexia", "graph", "query", "--template", "notes-tagged", "--param", "tag=哲学", "--param", "limit=5",
+    ])
+    .unwrap();
+    match cli.command {
+        Commands::Graph(args) => match args.command {
+            GraphCommands::Query(args) => {
+                assert!(args.query.is_none());
+                assert_eq!(args.template.as_deref(), Some("notes-tagged"));
+                assert_eq!(args.params, ["tag=哲学", "limit=5"]);
+            }
+            _ => panic!("Expected Query"),
+        },
+        _ => panic!("Expected Graph command"),
+    }
+
+    assert!(Cli::try_parse_from(["memexia", "graph", "query", "--list-templates"]).is_ok());
+    assert!(Cli::try_parse_from(["memexia", "graph", "query"]).is_err());
+    assert!(Cli::try_parse_from(["memexia", "graph", "query", "SELECT * {}", "--template", "x"]).is_err());
+}
+
+#[test]
+fn test_cli_parse_reindex_no_rename_detection() {
+    let cli = Cli::try_parse_from(["memexia", "reindex", "--no-rename-detection"]).unwrap();
+    assert!(matches!(cli.command, Commands::Reindex(args) if args.no_rename_detection));
+
+    let cli = Cli::try_parse_from(["memexia", "reindex"]).unwrap();
+    assert!(matches!(cli.command, Commands::Reindex(args) if !args.no_rename_detection));
+}
+
+#[test]
+fn test_cli_parse_report_contradictions() {
+    use memexia::cli::ReportCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "report", "contradictions", "--min-strength", "0.5", "--json"]).unwrap();
+    match cli.command {
+        Commands::Report(args) => match args.command {
+            ReportCommands::Contradictions(args) => {
+                assert_eq!(args.min_strength, 0.5);
+                assert!(args.json);
+            }
+            _ => panic!("Expected Report Contradictions command"),
+        },
+        _ => panic!("Expected Report command"),
+    }
+}
+
+#[test]
+fn test_cli_parse_file_log() {
+    use memexia::cli::FileCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "file", "log", "notes/a.md", "--json"]).unwrap();
+    match cli.command {
+        Commands::File(args) => match args.command {
+            FileCommands::Log(args) => {
+                assert_eq!(args.path, std::path::PathBuf::from("notes/a.md"));
+                assert!(args.json);
+            }
+            _ => panic!("Expected File Log command"),
+        },
+        _ => panic!("Expected File command"),
+    }
+}
+
+#[test]
+fn test_cli_parse_file_render_template() {
+    use memexia::cli::FileCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "file", "render-template", "notes/paper.md"]).unwrap();
+    match cli.command {
+        Commands::File(args) => match args.command {
+            FileCommands::RenderTemplate(args) => {
+                assert_eq!(args.path, std::path::PathBuf::from("notes/paper.md"));
+            }
+            _ => panic!("Expected File RenderTemplate command"),
+        },
+        _ => panic!("Expected File command"),
+    }
+}
+
+#[test]
+fn test_cli_parse_file_split() {
+    use memexia::cli::FileCommands;
+
+    let cli = Cli::try_parse_from([
+        "memexia", "file", "split", "notes/mind.md", "--heading", "Free Will", "-t", "Question", "--embed",
+    ])
+    .unwrap();
+    match cli.command {
+        Commands::File(args) => match args.command {
+            FileCommands::Split(args) => {
+                assert_eq!(args.path, std::path::PathBuf::from("notes/mind.md"));
+                assert_eq!(args.heading, "Free Will");
+                assert_eq!(args.node_type.as_deref(), Some("Question"));
+                assert!(args.embed);
+            }
+            _ => panic!("Expected File Split command"),
+        },
+        _ => panic!("Expected File command"),
+    }
+}
+
+#[test]
+fn test_cli_parse_append() {
+    let cli = Cli::try_parse_from([
+        "memexia", "append", "journal.md", "-", "--section", "Ideas", "--prepend", "--commit", "-m", "Capture",
+    ])
+    .unwrap();
+    match cli.command {
+        Commands::Append(args) => {
+            assert_eq!(args.path, std::path::PathBuf::from("journal.md"));
+            assert_eq!(args.text, "-");
+            assert_eq!(args.section.as_deref(), Some("Ideas"));
+            assert!(args.prepend);
+            assert!(args.commit);
+            assert_eq!(args.message.as_deref(), Some("Capture"));
+        }
+        _ => panic!("Expected Append command"),
+    }
+
+    // -m 需要 --commit
+    assert!(Cli::try_parse_from(["memexia", "append", "journal.md", "text", "-m", "Capture"]).is_err());
+}
+
+#[test]
+fn test_cli_parse_new_dir_without_type() {
+    let cli = Cli::try_parse_from(["memexia", "new", "Ada Lovelace", "--dir", "people"]).unwrap();
+    match cli.command {
+        Commands::New(args) => {
+            assert_eq!(args.dir.as_deref(), Some("people"));
+            assert!(args.node_type.is_none());
+        }
+        _ => panic!("Expected New command"),
+    }
+}
+
+#[test]
+fn test_cli_parse_tag() {
+    use memexia::cli::TagCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "tag", "rename", "old", "new", "--write-files", "-r"]).unwrap();
+    match cli.command {
+        Commands::Tag(args) => match args.command {
+            TagCommands::Rename(args) => {
+                assert_eq!((args.old.as_str(), args.new.as_str()), ("old", "new"));
+                assert!(args.write_files && args.recursive && !args.dry_run);
+            }
+            _ => panic!("Expected rename"),
+        },
+        _ => panic!("Expected Tag command"),
+    }
+
+    let cli = Cli::try_parse_from(["memexia", "tag", "remove", "draft", "--dry-run"]).unwrap();
+    match cli.command {
+        Commands::Tag(args) => match args.command {
+            TagCommands::Remove(args) => {
+                assert_eq!(args.tag, "draft");
+                assert!(args.dry_run && !args.write_files && !args.recursive);
+            }
+            _ => panic!("Expected remove"),
+        },
+        _ => panic!("Expected Tag command"),
+    }
+
+    let cli = Cli::try_parse_from(["memexia", "tag", "promote", "ml", "--type", "Concept", "--consume", "--write-files"]).unwrap();
+    match cli.command {
+        Commands::Tag(args) => match args.command {
+            TagCommands::Promote(args) => {
+                assert_eq!((args.tag.as_str(), args.node_type.as_deref()), ("ml", Some("Concept")));
+                assert_eq!(args.relation, "RelatedTo");
+                assert!(args.consume && args.write_files);
+            }
+            _ => panic!("Expected promote"),
+        },
+        _ => panic!("Expected Tag command"),
+    }
+    assert!(Cli::try_parse_from(["memexia", "tag", "promote", "ml", "--write-files"]).is_err());
+}
+
+#[test]
+fn test_cli_parse_lint() {
+    let cli = Cli::try_parse_from(["memexia", "lint", "notes", "a.md", "--json"]).unwrap();
+    match cli.command {
+        Commands::Lint(args) => {
+            assert_eq!(args.paths, [std::path::PathBuf::from("notes"), std::path::PathBuf::from("a.md")]);
+            assert!(args.json);
+        }
+        _ => panic!("Expected Lint command"),
+    }
+
+    let cli = Cli::try_parse_from(["memexia", "lint"]).unwrap();
+    assert!(matches!(cli.command, Commands::Lint(args) if args.paths.is_empty() && !args.json));
+}
+
+#[test]
+fn test_cli_parse_migrate() {
+    let cli = Cli::try_parse_from(["memexia", "migrate"]).unwrap();
+    assert!(matches!(cli.command, Commands::Migrate(args) if args.to == "latest"));
+
+    let cli = Cli::try_parse_from(["memexia", "migrate", "--to", "2"]).unwrap();
+    assert!(matches!(cli.command, Commands::Migrate(args) if args.to == "2"));
+}
+
+#[test]
+fn test_cli_parse_read_only() {
+    let cli = Cli::try_parse_from(["memexia", "stats", "--read-only"]).unwrap();
+    assert!(cli.read_only);
+
+    let cli = Cli::try_parse_from(["memexia", "stats"]).unwrap();
+    assert!(!cli.read_only);
+}
+
+#[test]
+fn test_cli_parse_no_daemon() {
+    let cli = Cli::try_parse_from(["memexia", "commit", "-m", "msg", "--no-daemon"]).unwrap();
+    assert!(cli.no_daemon);
+
+    let cli = Cli::try_parse_from(["memexia", "reindex"]).unwrap();
+    assert!(!cli.no_daemon);
+}
+
+#[test]
+fn test_cli_parse_export_corpus() {
+    let args = vec!["memexia", "export", "--format", "corpus", "out", "--chunk", "200", "--overlap", "20"];
+    let cli = Cli::try_parse_from(&args).unwrap();
+    if let Commands::Export(export_args) = cli.command {
+        assert_eq!(export_args.format, "corpus");
+        assert_eq!(export_args.dir.unwrap().to_string_lossy(), "out");
+        assert_eq!((export_args.chunk, export_args.overlap), (Some(200), Some(20)));
+    } else {
+        panic!("Expected Export command");
+    }
+
+    let args = vec!["memexia", "export", "--format", "corpus", "out", "--overlap", "20"];
+    assert!(Cli::try_parse_from(&args).is_err());
+}
+
+#[test]
+fn test_cli_parse_import_similarity() {
+    let cli = Cli::try_parse_from(["memexia", "import-similarity", "scores.csv", "--threshold", "0.9"]).unwrap();
+    match cli.command {
+        Commands::ImportSimilarity(args) => {
+            assert_eq!(args.file.to_string_lossy(), "scores.csv");
+            assert_eq!(args.threshold, 0.9);
+            assert!(!args.dry_run);
+        }
+        _ => panic!("Expected ImportSimilarity command"),
+    }
+
+    let cli = Cli::try_parse_from(["memexia", "import-similarity", "scores.csv"]).unwrap();
+    assert!(matches!(cli.command, Commands::ImportSimilarity(args) if args.threshold == 0.75));
+}
+
+#[test]
+fn test_cli_parse_archive() {
+    let cli = Cli::try_parse_from(["memexia", "archive", "notes/old.md"]).unwrap();
+    assert!(matches!(cli.command, Commands::Archive(args) if args.path.to_string_lossy() == "notes/old.md"));
+
+    let cli = Cli::try_parse_from(["memexia", "unarchive", "archive/notes/old.md"]).unwrap();
+    assert!(matches!(cli.command, Commands::Unarchive(_)));
+
+    let cli = Cli::try_parse_from(["memexia", "search", "gardens", "--include-archived"]).unwrap();
+    assert!(matches!(cli.command, Commands::Search(args) if args.include_archived && args.query == "gardens"));
+
+    let cli = Cli::try_parse_from(["memexia", "stats"]).unwrap();
+    assert!(matches!(cli.command, Commands::Stats(args) if !args.include_archived));
+}
+
+#[test]
+fn test_cli_parse_graph_stats_detailed() {
+    use memexia::cli::GraphCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "graph", "stats", "--detailed", "--json"]).unwrap();
+    match cli.command {
+        Commands::Graph(args) => match args.command {
+            GraphCommands::Stats(stats) => assert!(stats.detailed && stats.json),
+            _ => panic!("Expected graph stats"),
+        },
+        _ => panic!("Expected Graph command"),
+    }
+}
+
+#[test]
+fn test_cli_parse_link_apply() {
+    use memexia::cli::LinkCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "link", "apply", "reading.links", "-y", "--strict"]).unwrap();
+    match cli.command {
+        Commands::Link(args) => match args.command {
+            LinkCommands::Apply(args) => {
+                assert_eq!(args.file.to_str(), Some("reading.links"));
+                assert!(args.yes && args.strict);
+            }
+            _ => panic!("Expected Apply subcommand"),
+        },
+        _ => panic!("Expected Link command"),
+    }
+}
+
+#[test]
+fn test_cli_parse_link_refresh() {
+    use memexia::cli::LinkCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "link", "refresh", "--write-files"]).unwrap();
+    match cli.command {
+        Commands::Link(args) => match args.command {
+            LinkCommands::Refresh(args) => assert!(args.write_files),
+            _ => panic!("Expected Refresh subcommand"),
+        },
+        _ => panic!("Expected Link command"),
+    }
+
+    let cli = Cli::try_parse_from(["memexia", "link", "refresh"]).unwrap();
+    match cli.command {
+        Commands::Link(args) => match args.command {
+            LinkCommands::Refresh(args) => assert!(!args.write_files),
+            _ => panic!("Expected Refresh subcommand"),
+        },
+        _ => panic!("Expected Link command"),
+    }
+}
+
+#[test]
+fn test_cli_parse_graph_repl() {
+    use memexia::cli::GraphCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "graph", "repl"]).unwrap();
+    assert!(matches!(cli.command, Commands::Graph(args) if matches!(args.command, GraphCommands::Repl)));
+}
+
+#[test]
+fn test_cli_parse_history() {
+    use memexia::cli::HistoryCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "history", "export", "vault.tar"]).unwrap();
+    match cli.command {
+        Commands::History(args) => match args.command {
+            HistoryCommands::Export(args) => assert_eq!(args.file, std::path::PathBuf::from("vault.tar")),
+            _ => panic!("Expected history export"),
+        },
+        _ => panic!("Expected History command"),
+    }
+
+    let cli = Cli::try_parse_from(["memexia", "history", "import", "vault.tar", "--merge"]).unwrap();
+    assert!(matches!(
+        cli.command,
+        Commands::History(args) if matches!(args.command, HistoryCommands::Import(ref import) if import.merge)
+    ));
+    assert!(Cli::try_parse_from(["memexia", "history", "import"]).is_err());
+}
+
+#[test]
+fn test_cli_parse_derivation_tree() {
+    use memexia::cli::DerivationCommands;
+
+    let cli = Cli::try_parse_from(["memexia", "derivation", "tree", "notes/a.md"]).unwrap();
+    match cli.command {
+        Commands::Derivation(args) => match args.command {
+            DerivationCommands::Tree(args) => {
+                assert_eq!(args.node, "notes/a.md");
+                assert_eq!(args.direction, "down");
+                assert!(!args.dot);
+                assert_eq!(args.depth, None);
+            }
+        },
+        _ => panic!("Expected Derivation command"),
+    }
+
+    let cli = Cli::try_parse_from(["memexia", "derivation", "tree", "a.md", "--direction", "both", "--dot", "--depth", "2"]).unwrap();
+    let Commands::Derivation(args) = cli.command else { panic!("Expected Derivation command") };
+    let DerivationCommands::Tree(args) = args.command;
+    assert_eq!((args.direction.as_str(), args.dot, args.depth), ("both", true, Some(2)));
+}
+
+#[test]
+fn test_cli_parse_export_subgraph() {
+    let cli = Cli::try_parse_from(["memexia", "export", "--format", "dot", "--tag", "哲学", "--include-boundary"]).unwrap();
+    match cli.command {
+        Commands::Export(args) => {
+            assert_eq!(args.format, "dot");
+            assert_eq!(args.tag.as_deref(), Some("哲学"));
+            assert!(args.include_boundary);
+            assert!(args.query.is_none());
+        }
+        _ => panic!("Expected Export command"),
+    }
+
+    let cli = Cli::try_parse_from(["memexia", "export", "-f", "json", "--query", "SELECT ?node WHERE { ?node ?p ?o }"]).unwrap();
+    let Commands::Export(args) = cli.command else { panic!("Expected Export command") };
+    assert!(args.query.is_some());
+    assert!(Cli::try_parse_from(["memexia", "export", "--tag", "a", "--query", "SELECT ?node {}"]).is_err());
+}
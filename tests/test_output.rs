@@ -0,0 +1,52 @@
+//! CLI 输出格式集成测试
+//!
+//! 直接运行编译出的二进制，断言影响输出格式的全局参数
+
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+/// 在指定目录中运行 memexia
+fn memexia(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_memexia"))
+        .args(args)
+        .current_dir(dir)
+        .env("RUST_LOG", "off")
+        .env("MEMEXIA_LANG", "en")
+        .output()
+        .expect("failed to run memexia binary")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+/// 输出中 `Timestamp: ` 之后的时间
+fn log_timestamp(output: &Output) -> String {
+    let out = stdout(output);
+    out.lines()
+        .find_map(|line| line.strip_prefix("Timestamp: "))
+        .unwrap_or_else(|| panic!("no timestamp in {}", out))
+        .to_string()
+}
+
+#[test]
+fn test_log_time_formats() {
+    let temp = TempDir::new().unwrap();
+    let dir = temp.path();
+    assert!(memexia(dir, &["init", "."]).status.success());
+    std::fs::write(dir.join("a.md"), "# A\n").unwrap();
+    assert!(memexia(dir, &["add", "a.md"]).status.success());
+    let output = memexia(dir, &["commit", "-m", "Add A"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    // 刚创建的提交默认显示为相对时间
+    assert_eq!(log_timestamp(&memexia(dir, &["log", "--no-pager"])), "just now");
+
+    let iso = log_timestamp(&memexia(dir, &["log", "--no-pager", "--iso", "--utc"]));
+    assert!(chrono::DateTime::parse_from_rfc3339(&iso).is_ok(), "{}", iso);
+    assert!(iso.ends_with('Z'), "{}", iso);
+
+    let utc = log_timestamp(&memexia(dir, &["log", "--no-pager", "--utc"]));
+    assert!(utc.ends_with(" UTC"), "{}", utc);
+}
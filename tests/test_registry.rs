@@ -0,0 +1,45 @@
+//! 仓库注册表集成测试
+//!
+//! 注册表开关是进程级状态，单独放在一个测试二进制中，避免影响其他测试
+
+use memexia::core::registry::{self, Registry};
+use memexia::core::Repository;
+use tempfile::TempDir;
+
+#[test]
+fn test_registry_records_opened_repositories() {
+    let temp = TempDir::new().unwrap();
+    let config = temp.path().join("global/memexia/config.toml");
+    registry::enable(config.clone());
+
+    let work = temp.path().join("work");
+    let personal = temp.path().join("personal");
+    std::fs::create_dir_all(&work).unwrap();
+    std::fs::create_dir_all(&personal).unwrap();
+    let work_root = Repository::init(&work).unwrap().path().to_path_buf();
+    let personal_root = Repository::init(&personal).unwrap().path().to_path_buf();
+
+    let first = Registry::load(&config).unwrap();
+    assert_eq!(first.repos.len(), 2);
+    let before = first.find_path(&work_root).unwrap().last_used;
+
+    // 打开子目录也登记到仓库根目录，并刷新使用时间
+    std::fs::create_dir_all(work.join("notes/deep")).unwrap();
+    Repository::open(&work.join("notes/deep")).unwrap();
+    let registry = Registry::load(&config).unwrap();
+    assert_eq!(registry.repos.len(), 2);
+    assert!(registry.find_path(&work_root).unwrap().last_used >= before);
+
+    // 名称解析
+    assert_eq!(registry.resolve("work").unwrap(), work_root);
+    let mut registry = registry;
+    registry.rename("personal", "life").unwrap();
+    registry.save(&config).unwrap();
+    assert_eq!(Registry::load(&config).unwrap().resolve("life").unwrap(), personal_root);
+
+    // 路径被删除的条目保留但被标记
+    std::fs::remove_dir_all(&personal).unwrap();
+    let registry = Registry::load(&config).unwrap();
+    assert!(!registry.get("life").unwrap().exists());
+    assert!(registry.resolve("life").is_err());
+}
@@ -1,6 +1,7 @@
 //! Repository 模块集成测试
 
 use memexia::core::Repository;
+use memexia::MemexiaError;
 use std::fs;
 
 /// 辅助函数：规范化路径字符串用于比较（处理 Windows 短路径和大小写问题）
@@ -39,7 +40,7 @@ fn test_repository_init_already_exists() {
     configure_git_user(path);
     Repository::init(path).unwrap();
     let result = Repository::init(path);
-    assert!(result.is_err());
+    assert!(matches!(result, Err(MemexiaError::AlreadyInitialized { .. })));
 }
 
 #[test]
@@ -63,7 +64,7 @@ fn test_repository_open_not_exists() {
     let path = temp.path().join("not_exists");
 
     let result = Repository::open(&path);
-    assert!(result.is_err());
+    assert!(matches!(result, Err(MemexiaError::RepositoryNotFound { .. })));
 }
 
 #[test]
@@ -122,6 +123,44 @@ fn test_repository_add_multiple_files() {
     assert!(status.contains("test2.md"));
 }
 
+#[test]
+fn test_repository_staging_entries() {
+    use memexia::core::staging::StagingIndex;
+    use memexia::core::synthetic::{generate_vault, note_path, VaultSpec};
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+    configure_git_user(path);
+    let mut repo = Repository::init(path).unwrap();
+    let root = repo.path().to_path_buf();
+    let vault = generate_vault(&root, &VaultSpec { notes: 20, links_per_note: 2, tags: 5, ..VaultSpec::default() }).unwrap();
+
+    for file in &vault.files {
+        repo.add(&[root.join(file)]).unwrap();
+    }
+    repo.add(&[root.join(note_path(1))]).unwrap();
+    let index = StagingIndex::load(&root).unwrap();
+    assert_eq!(index.entries().len(), 20);
+    assert!(index.entries().iter().all(|entry| entry.hash.is_some() && entry.added.is_some()));
+
+    // 暂存后修改的文件在 status 中标出
+    fs::write(root.join(note_path(3)), "# N3 changed\n").unwrap();
+    let status = repo.status().unwrap();
+    assert!(status.contains(&format!("{} (modified since add)", note_path(3))), "{}", status);
+    assert!(status.contains(&format!("{}\n", note_path(4))));
+
+    repo.commit("Add notes").unwrap();
+    assert_eq!(repo.status().unwrap(), "No changes staged.");
+
+    // 旧格式（每行一个路径）的暂存区仍可提交
+    fs::write(root.join(note_path(0)), "# N0 changed\n").unwrap();
+    fs::write(root.join(".memexia/index"), format!("{}\n", note_path(0))).unwrap();
+    assert_eq!(repo.status().unwrap(), format!("Staged files:\n{}\n", note_path(0)));
+    repo.commit("Update n0").unwrap();
+    assert!(StagingIndex::load(&root).unwrap().is_empty());
+}
+
 #[test]
 fn test_repository_status_empty() {
     use tempfile::TempDir;
@@ -179,7 +218,42 @@ fn test_repository_commit_no_staged() {
     configure_git_user(path);
     let mut repo = Repository::init(path).unwrap();
     let result = repo.commit("No files");
-    assert!(result.is_err());
+    assert!(matches!(result, Err(MemexiaError::NothingToCommit)));
+}
+
+#[test]
+fn test_repository_commit_failure_leaves_graph_and_index_untouched() {
+    use memexia::core::staging::StagingIndex;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+
+    configure_git_user(path);
+    let mut repo = Repository::init(path).unwrap();
+    let files: Vec<_> = ["a.md", "b.md", "c.md"].iter().map(|name| path.join(name)).collect();
+    for (file, name) in files.iter().zip(["A", "B", "C"]) {
+        fs::write(file, format!("# {}\n\n[[Target {}]]", name, name)).unwrap();
+    }
+    repo.add(&files).unwrap();
+
+    // 暂存后把中间的文件换成目录，使其无法读取
+    fs::remove_file(&files[1]).unwrap();
+    fs::create_dir(&files[1]).unwrap();
+
+    let err = repo.commit("Three notes").unwrap_err();
+    assert!(matches!(&err, MemexiaError::StagedFile { file, .. } if file == "b.md"), "{}", err);
+    assert!(repo.storage().unwrap().graph().list_nodes().unwrap().is_empty());
+    assert!(repo.storage().unwrap().graph().list_edges().unwrap().is_empty());
+    assert!(repo.last_commit().unwrap().is_none());
+    assert_eq!(StagingIndex::load(path).unwrap().entries().len(), 3);
+
+    // 修复后整组提交
+    fs::remove_dir(&files[1]).unwrap();
+    fs::write(&files[1], "# B").unwrap();
+    repo.commit("Three notes").unwrap();
+    assert_eq!(repo.storage().unwrap().graph().list_nodes().unwrap().len(), 5);
+    assert!(StagingIndex::load(path).unwrap().is_empty());
 }
 
 #[test]
@@ -220,6 +294,133 @@ fn test_repository_log() {
     assert_eq!(logs[0].message, "First");
 }
 
+#[test]
+fn test_repository_log_for_node() {
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+
+    configure_git_user(path);
+    let mut repo = Repository::init(path).unwrap();
+    let a = path.join("a.md");
+    let b = path.join("b.md");
+    fs::write(&a, "# A").unwrap();
+    fs::write(&b, "# B").unwrap();
+
+    repo.add(std::slice::from_ref(&a)).unwrap();
+    repo.commit("Add a").unwrap();
+    repo.add(std::slice::from_ref(&b)).unwrap();
+    repo.commit("Add b").unwrap();
+    fs::write(&a, "# A\n\nRevised").unwrap();
+    repo.add(&[a.clone(), b.clone()]).unwrap();
+    repo.commit("Revise both").unwrap();
+
+    // trailer 从消息中拆出，图快照哈希与图历史一致
+    let head = repo.last_commit().unwrap().unwrap();
+    assert_eq!(head.message, "Revise both");
+    assert_eq!(head.affected_nodes(), vec!["urn:memexia:file:a.md", "urn:memexia:file:b.md"]);
+    assert!(head.trailers.iter().any(|(k, v)| k == "Graph-Snapshot" && Some(v) == head.graph_hash.as_ref()));
+
+    let messages = |commits: Vec<memexia::vcs::CommitInfo>| -> Vec<String> {
+        commits.into_iter().map(|c| c.message).collect()
+    };
+    assert_eq!(messages(repo.log_for_node(&a, 10).unwrap()), vec!["Revise both", "Add a"]);
+    assert_eq!(messages(repo.log_for_node(&b, 10).unwrap()), vec!["Revise both", "Add b"]);
+    assert_eq!(messages(repo.log_for_node(&b, 1).unwrap()), vec!["Revise both"]);
+    assert!(repo.log_for_node(&path.join("missing.md"), 10).unwrap().is_empty());
+
+    // amend 保留受影响节点
+    repo.amend("Revise a and b").unwrap();
+    assert_eq!(messages(repo.log_for_node(&b, 10).unwrap()), vec!["Revise a and b", "Add b"]);
+}
+
+#[test]
+fn test_repository_graph_at() {
+    use memexia::core::time_travel::At;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+
+    configure_git_user(path);
+    let mut repo = Repository::init(path).unwrap();
+    let note = path.join("note.md");
+
+    fs::write(&note, "# Note\n\n[[old.md]]").unwrap();
+    repo.add(std::slice::from_ref(&note)).unwrap();
+    let first = repo.commit("Link old").unwrap();
+
+    fs::write(&note, "# Note\n\n[[new.md]]").unwrap();
+    repo.add(std::slice::from_ref(&note)).unwrap();
+    repo.commit("Link new").unwrap();
+
+    let targets = |graph: &dyn memexia::storage::GraphStorage| -> Vec<String> {
+        let mut targets: Vec<String> = graph
+            .get_edges_by_source("urn:memexia:file:note.md")
+            .unwrap()
+            .into_iter()
+            .map(|e| e.to)
+            .collect();
+        targets.sort();
+        targets
+    };
+
+    // 重新索引不删除旧边，当前视图包含两条链接；第一次提交时只有 old
+    assert!(targets(repo.storage().unwrap().graph()).contains(&"urn:memexia:file:new.md".to_string()));
+    let view = repo.graph_at(&At::parse(&first[..8]).unwrap()).unwrap();
+    assert_eq!(view.link.commit_hash, first);
+    assert_eq!(targets(view.graph()), vec!["urn:memexia:file:old.md"]);
+
+    // 按时间解析：当前时间对应最后一次提交，早于所有提交时报错
+    let latest = repo.graph_at(&At::Time(chrono::Utc::now())).unwrap();
+    assert_eq!(targets(latest.graph()), targets(repo.storage().unwrap().graph()));
+    assert!(repo.graph_at(&At::parse("2000-01-01").unwrap()).is_err());
+
+    // 历史视图不影响当前存储
+    assert!(repo.storage().unwrap().graph().node_exists("urn:memexia:file:new.md").unwrap());
+}
+
+#[test]
+fn test_repository_pre_commit_validation() {
+    use memexia::core::RepoConfig;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+
+    configure_git_user(path);
+    let mut repo = Repository::init(path).unwrap();
+    RepoConfig::set(path, "hooks.pre_commit_validate", "true").unwrap();
+
+    // 自链接违反默认为 error 的 no_self_loops 规则
+    let note = path.join("loop.md");
+    fs::write(&note, "# Loop\n\n[[loop.md]]").unwrap();
+    repo.add(std::slice::from_ref(&note)).unwrap();
+    assert!(matches!(repo.commit("Self link"), Err(MemexiaError::Validation(_))));
+    assert!(repo.last_commit().unwrap().is_none());
+    // 中止的提交撤销图谱写入
+    assert!(repo.storage().unwrap().graph().list_nodes().unwrap().is_empty());
+
+    // 降级为警告后可以提交
+    RepoConfig::set(path, "validate.rules.no_self_loops", "warn").unwrap();
+    repo.commit("Self link").unwrap();
+}
+
+#[test]
+fn test_repository_commit_template() {
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+
+    let repo = Repository::init(path).unwrap();
+    assert!(repo.commit_template().unwrap().is_none());
+
+    fs::write(path.join(memexia::core::repository::COMMIT_TEMPLATE_PATH), "Summary\n\n# Why?\n").unwrap();
+    assert_eq!(repo.commit_template().unwrap().as_deref(), Some("Summary\n\n# Why?\n"));
+}
+
 #[test]
 fn test_repository_log_limit() {
     use tempfile::TempDir;
@@ -328,7 +529,7 @@ fn test_repository_storage_getter() {
 
     configure_git_user(path);
     let repo = Repository::init(path).unwrap();
-    let storage = repo.storage();
+    let storage = repo.storage().unwrap();
     assert!(!storage.root().to_string_lossy().is_empty());
 }
 
@@ -341,10 +542,1578 @@ fn test_repository_vcs_getter() {
 
     configure_git_user(path);
     let repo = Repository::init(path).unwrap();
-    let vcs = repo.vcs();
+    let vcs = repo.vcs().unwrap();
     assert!(!vcs.head_info().unwrap().is_some());
 }
 
+#[test]
+fn test_repository_create_note() {
+    use memexia::core::note::NoteOptions;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+
+    configure_git_user(path);
+    let repo = Repository::init(path).unwrap();
+
+    let (file, node_id) = repo.create_note(&NoteOptions::new("Free Will")).unwrap();
+    assert!(file.ends_with("notes/free-will.md"));
+    assert_eq!(node_id, "urn:memexia:file:notes/free-will.md");
+
+    // 创建后立即出现在图谱中，并已加入暂存区
+    let node = repo.storage().unwrap().graph().get_node(&node_id).unwrap().unwrap();
+    assert_eq!(node.title, "Free Will");
+    assert!(repo.status().unwrap().contains("notes/free-will.md"));
+}
+
+#[test]
+fn test_repository_stats() {
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+
+    configure_git_user(path);
+    let mut repo = Repository::init(path).unwrap();
+
+    // 空仓库没有提交
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.notes, 0);
+    assert!(stats.commits_per_month.is_empty());
+
+    let file = path.join("idea.md");
+    fs::write(&file, "---\ncreated: 2023-05-04\ntags: [a, b]\n---\nfour words right here").unwrap();
+    repo.add(&[file]).unwrap();
+    repo.commit("Add idea").unwrap();
+
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.notes, 1);
+    assert_eq!(stats.total_words, 4);
+    assert_eq!(stats.tags, 2);
+    assert_eq!(stats.notes_per_month[0].month, "2023-05");
+    assert_eq!(stats.commits_per_month.iter().map(|m| m.count).sum::<usize>(), 1);
+}
+
+#[test]
+fn test_repository_dashboard() {
+    use memexia::core::dashboard::Metric;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    configure_git_user(root);
+    let mut repo = Repository::init(root).unwrap();
+
+    let dashboard = repo.dashboard().unwrap();
+    assert_eq!(dashboard.score, 100);
+    assert!(dashboard.last_commit.is_none());
+
+    fs::write(root.join("a.md"), "---\ntags: [mind]\n---\n# A\n").unwrap();
+    fs::write(root.join("q.md"), "---\ntype: Question\ntags: [mind, open]\n---\n# Q\n\n[[a.md]]\n").unwrap();
+    fs::write(root.join("lonely.md"), "# Lonely\n").unwrap();
+    repo.add(&[root.join("a.md"), root.join("q.md"), root.join("lonely.md")]).unwrap();
+    repo.commit("Add notes").unwrap();
+
+    let dashboard = repo.dashboard().unwrap();
+    assert_eq!((dashboard.counts.notes, dashboard.counts.orphans), (3, 1));
+    assert_eq!((dashboard.counts.questions, dashboard.counts.open_questions), (1, 1));
+    assert_eq!(dashboard.counts.stale, 0);
+    assert_eq!(dashboard.top_tags[0].tag, "mind");
+    assert_eq!(dashboard.last_commit.as_ref().unwrap().summary, "Add notes");
+
+    let orphans = dashboard.checks.iter().find(|c| c.metric == Metric::Orphans).unwrap();
+    assert!(!orphans.ok);
+    assert!(dashboard.score < 100);
+
+    let json = serde_json::to_value(&dashboard).unwrap();
+    assert_eq!(json["checks"][0]["metric"], "orphans");
+    assert_eq!(json["counts"]["open_questions"], 1);
+}
+
+#[test]
+fn test_repository_review_mark_reviewed() {
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+
+    configure_git_user(path);
+    let mut repo = Repository::init(path).unwrap();
+
+    let file = path.join("idea.md");
+    fs::write(&file, "# Idea\n\nRevisit me").unwrap();
+    repo.add(std::slice::from_ref(&file)).unwrap();
+    repo.commit("Add idea").unwrap();
+
+    let queue = repo.review_queue(10, 7).unwrap();
+    assert_eq!(queue.len(), 1);
+    assert_eq!(queue[0].path.as_deref(), Some("idea.md"));
+
+    let node = repo.mark_reviewed(&file).unwrap();
+    assert!(node.last_reviewed().is_some());
+    assert!(repo.review_queue(10, 7).unwrap().is_empty());
+    assert_eq!(repo.review_queue(10, 0).unwrap().len(), 1);
+
+    assert!(repo.mark_reviewed(&path.join("missing.md")).is_err());
+}
+
+#[test]
+fn test_repository_publish() {
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+
+    configure_git_user(path);
+    let mut repo = Repository::init(path).unwrap();
+    fs::write(
+        path.join(".memexia/config/config.toml"),
+        "[publish]\nexclude_tags = [\"private\"]\n",
+    )
+    .unwrap();
+
+    let notes = [
+        ("自由意志.md", "---\ntags: [哲学]\n---\n# 自由意志\n\nDo we choose?"),
+        ("a.md", "# A\n\nSee [[自由意志.md]] and [[secret.md]]."),
+        ("secret.md", "---\ntags: [private]\n---\n# Secret\n\nAbout [[自由意志.md]]."),
+    ];
+    let files: Vec<_> = notes
+        .iter()
+        .map(|(name, content)| {
+            let file = path.join(name);
+            fs::write(&file, content).unwrap();
+            file
+        })
+        .collect();
+    repo.add(&files).unwrap();
+    repo.commit("Add notes").unwrap();
+
+    let out = path.join("site");
+    let summary = repo.publish(&out).unwrap();
+    assert_eq!((summary.pages, summary.tags, summary.excluded), (2, 1, 1));
+
+    // 输出目录结构
+    for page in ["index.html", "graph.json", "notes/a.html", "notes/自由意志.html", "tags/哲学.html"] {
+        assert!(out.join(page).is_file(), "missing {}", page);
+    }
+
+    // 中文路径的链接经过 percent 编码，反向链接指向来源页面
+    let a = fs::read_to_string(out.join("notes/a.html")).unwrap();
+    assert!(a.contains("href=\"%E8%87%AA%E7%94%B1%E6%84%8F%E5%BF%97.html\""));
+    let target = fs::read_to_string(out.join("notes/自由意志.html")).unwrap();
+    let backlinks = &target[target.find("class=\"backlinks\"").unwrap()..];
+    assert!(backlinks.contains("<a href=\"a.html\">A</a>"));
+    assert!(target.contains("href=\"../tags/%E5%93%B2%E5%AD%A6.html\""));
+
+    // 被排除的笔记不出现在任何地方
+    assert!(!out.join("notes/secret.html").exists());
+    assert!(!backlinks.contains("Secret"));
+    assert!(!a.contains("secret.html"));
+    assert!(!fs::read_to_string(out.join("index.html")).unwrap().contains("Secret"));
+    assert!(!fs::read_to_string(out.join("graph.json")).unwrap().contains("secret"));
+
+    // 输出目录位于仓库内时不会被当作笔记
+    assert_eq!(repo.publish(&out).unwrap().pages, 2);
+}
+
+#[test]
+fn test_repository_snapshot_now() {
+    use memexia::core::time_travel::At;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+
+    configure_git_user(path);
+    let mut repo = Repository::init(path).unwrap();
+    let note = path.join("note.md");
+    fs::write(&note, "# Note\n\n[[other.md]]").unwrap();
+    repo.add(std::slice::from_ref(&note)).unwrap();
+    repo.commit("Add note").unwrap();
+
+    let link = repo.snapshot_now().unwrap().unwrap();
+    assert!(link.commit_hash.starts_with("auto:"));
+    // 图未变化时不重复快照
+    assert!(repo.snapshot_now().unwrap().is_none());
+
+    // 自动快照键可作为 --at 参数
+    let view = repo.graph_at(&At::parse(&link.commit_hash).unwrap()).unwrap();
+    assert_eq!(view.link.graph_hash, link.graph_hash);
+    assert!(view.graph().node_exists("urn:memexia:file:note.md").unwrap());
+}
+
+#[test]
+fn test_repository_soft_delete_restore_and_purge() {
+    use memexia::core::{FileEvent, Indexer};
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+
+    configure_git_user(path);
+    let mut repo = Repository::init(path).unwrap();
+    let note = path.join("note.md");
+    fs::write(&note, "# Note\n\n[[other.md]]").unwrap();
+    repo.add(std::slice::from_ref(&note)).unwrap();
+    repo.commit("Add note").unwrap();
+
+    let id = "urn:memexia:file:note.md";
+    let graph = repo.storage().unwrap().graph();
+    assert_eq!(graph.list_edges().unwrap().len(), 1);
+
+    // 删除文件：节点进入回收站，边不可见
+    fs::remove_file(&note).unwrap();
+    let indexer = Indexer::new(repo.storage().unwrap().clone());
+    indexer.handle_event(&FileEvent::Deleted(note.to_string_lossy().into_owned())).unwrap();
+    assert!(graph.list_nodes().unwrap().iter().all(|n| n.id != id));
+    assert!(graph.list_edges().unwrap().is_empty());
+    assert_eq!(graph.list_edges_filtered(true).unwrap().len(), 1);
+    assert_eq!(repo.deleted_nodes().unwrap().len(), 1);
+
+    // 恢复：标记清除、边恢复，文件从 Git 检出
+    let outcome = repo.restore_node(std::path::Path::new("note.md")).unwrap();
+    assert_eq!(outcome.node_id, id);
+    assert!(outcome.file_restored);
+    assert!(fs::read_to_string(&note).unwrap().contains("[[other.md]]"));
+    assert!(graph.list_nodes().unwrap().iter().any(|n| n.id == id));
+    assert_eq!(graph.list_edges().unwrap().len(), 1);
+    assert!(matches!(
+        repo.restore_node(std::path::Path::new("note.md")),
+        Err(MemexiaError::Validation(_))
+    ));
+
+    // 再次删除后清理：未到期的节点保留，全部清理后永久删除
+    indexer.handle_event(&FileEvent::Deleted(note.to_string_lossy().into_owned())).unwrap();
+    assert!(repo.purge_deleted(Some(chrono::Duration::days(30))).unwrap().is_empty());
+    assert_eq!(repo.purge_deleted(None).unwrap(), vec![id.to_string()]);
+    assert!(!graph.node_exists(id).unwrap());
+    assert!(graph.list_edges_filtered(true).unwrap().is_empty());
+    assert!(matches!(
+        repo.restore_node(std::path::Path::new("note.md")),
+        Err(MemexiaError::NodeNotFound { .. })
+    ));
+}
+
+#[test]
+fn test_repository_commit_all_stages_changed_notes() {
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+
+    configure_git_user(path);
+    let mut repo = Repository::init(path).unwrap();
+    let notes: Vec<_> = ["a.md", "b.md", "c.md"].iter().map(|n| path.join(n)).collect();
+    for note in &notes {
+        fs::write(note, "# Note").unwrap();
+    }
+    repo.add(&notes).unwrap();
+    repo.commit("Add notes").unwrap();
+
+    let committed = |repo: &Repository| -> Vec<String> {
+        let commit = repo.last_commit().unwrap().unwrap();
+        let mut nodes: Vec<String> = commit.affected_nodes().iter().map(|s| s.to_string()).collect();
+        nodes.sort();
+        nodes
+    };
+
+    // 修改两篇已跟踪笔记，新建一篇笔记和一个非笔记文件
+    fs::write(&notes[0], "# A\n\nedited").unwrap();
+    fs::write(&notes[1], "# B\n\nedited").unwrap();
+    fs::write(path.join("new.md"), "# New").unwrap();
+    fs::write(path.join("scratch.txt"), "ignored").unwrap();
+
+    let staged = repo.stage_changes(false).unwrap();
+    assert_eq!(staged, vec![PathBuf::from("a.md"), PathBuf::from("b.md")]);
+    repo.commit("Edit notes").unwrap();
+    assert_eq!(committed(&repo), vec!["urn:memexia:file:a.md", "urn:memexia:file:b.md"]);
+
+    // 没有变化时不再暂存已提交的笔记
+    assert!(repo.stage_changes(false).unwrap().is_empty());
+
+    // --include-untracked 同时暂存新笔记
+    fs::write(&notes[2], "# C\n\nedited").unwrap();
+    let staged = repo.stage_changes(true).unwrap();
+    assert_eq!(staged, vec![PathBuf::from("c.md"), PathBuf::from("new.md")]);
+    repo.commit("Edit and add").unwrap();
+    assert_eq!(committed(&repo), vec!["urn:memexia:file:c.md", "urn:memexia:file:new.md"]);
+    assert!(repo.stage_changes(true).unwrap().is_empty());
+}
+
+#[test]
+fn test_repository_apply_links() {
+    use memexia::core::link_batch;
+    use memexia::storage::{Edge, NodeType};
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+
+    configure_git_user(path);
+    let repo = Repository::init(path).unwrap();
+    fs::write(path.join("a.md"), "# A").unwrap();
+    fs::write(path.join("b.md"), "# B").unwrap();
+    repo.add(&[path.join("a.md"), path.join("b.md")]).unwrap();
+
+    let parsed = link_batch::parse("a.md --Supports(0.8)--> b.md # 数据支持
+../x.md --Refines--> a.md
+b.md --Cites--> paper.pdf
+");
+    assert!(parsed.errors.is_empty());
+    let graph = repo.storage().unwrap().graph();
+
+    // 严格模式：第 2 行在仓库外，整批不写入
+    let err = repo.apply_links(&parsed.links, true).unwrap_err();
+    assert!(err.to_string().contains("line 2"), "{}", err);
+    assert!(graph.get_edge(&Edge::make_id("urn:memexia:file:a.md", "urn:memexia:file:b.md")).unwrap().is_none());
+    assert!(!graph.node_exists("urn:memexia:file:paper.pdf").unwrap());
+
+    // 非严格模式：跳过失败的行，其余写入
+    let report = repo.apply_links(&parsed.links, false).unwrap();
+    assert_eq!(report.applied.len(), 2);
+    assert_eq!(report.errors.iter().map(|e| e.line).collect::<Vec<_>>(), [2]);
+    let edge = graph.get_edge(&Edge::make_id("urn:memexia:file:a.md", "urn:memexia:file:b.md")).unwrap().unwrap();
+    assert_eq!(edge.strength, 0.8);
+    assert_eq!(edge.description.as_deref(), Some("数据支持"));
+    let placeholder = graph.get_node("urn:memexia:file:paper.pdf").unwrap().unwrap();
+    assert_eq!(placeholder.node_type, NodeType::Resource);
+
+    // 整批一次撤销
+    assert_eq!(repo.undo().unwrap().operation, "link apply");
+    assert!(graph.get_edge(&edge.id).unwrap().is_none());
+    assert!(!graph.node_exists("urn:memexia:file:paper.pdf").unwrap());
+}
+
+#[test]
+fn test_repository_undo_purge_restores_nodes_and_edges() {
+    use memexia::core::{FileEvent, Indexer};
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+
+    configure_git_user(path);
+    let mut repo = Repository::init(path).unwrap();
+    let a = path.join("a.md");
+    let b = path.join("b.md");
+    fs::write(&a, "# A\n\n[[b.md]]").unwrap();
+    fs::write(&b, "# B\n\n[[c.md]]").unwrap();
+    repo.add(&[a.clone(), b.clone()]).unwrap();
+    repo.commit("Add notes").unwrap();
+
+    let indexer = Indexer::new(repo.storage().unwrap().clone());
+    for file in [&a, &b] {
+        fs::remove_file(file).unwrap();
+        indexer.handle_event(&FileEvent::Deleted(file.to_string_lossy().into_owned())).unwrap();
+    }
+    let graph = repo.storage().unwrap().graph();
+    let edges_before = graph.list_edges_filtered(true).unwrap().len();
+    assert_eq!(repo.purge_deleted(None).unwrap().len(), 2);
+    assert!(repo.deleted_nodes().unwrap().is_empty());
+
+    // 撤销清理：两个节点回到回收站，出边恢复
+    let journal = repo.undo().unwrap();
+    assert_eq!(journal.operation, "gc --purge-deleted");
+    let deleted: Vec<_> = repo.deleted_nodes().unwrap().into_iter().map(|n| n.id).collect();
+    assert_eq!(deleted.len(), 2);
+    assert!(deleted.contains(&"urn:memexia:file:a.md".to_string()));
+    assert!(deleted.contains(&"urn:memexia:file:b.md".to_string()));
+    assert_eq!(graph.list_edges_filtered(true).unwrap().len(), edges_before);
+
+    // 日志只生效一次
+    assert!(matches!(repo.undo(), Err(MemexiaError::Validation(_))));
+}
+
+#[test]
+fn test_repository_ids_consistent_across_creation_paths() {
+    use memexia::core::Indexer;
+    use memexia::storage::{ids, Node};
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+
+    configure_git_user(path);
+    let mut repo = Repository::init(path).unwrap();
+    let names = ["自由 意志.md", "决定论 100% #1.md"];
+    let source = path.join(names[0]);
+    let target = path.join(names[1]);
+    fs::write(&source, format!("# 自由意志\n\n[[{}]]", names[1])).unwrap();
+    fs::write(&target, "# 决定论").unwrap();
+
+    // 提交、重新索引和 Node::from_file_path 得到相同的 ID
+    repo.add(&[source.clone(), target.clone()]).unwrap();
+    repo.commit("Add notes").unwrap();
+    Indexer::new(repo.storage().unwrap().clone()).index_all(path).unwrap();
+
+    let graph = repo.storage().unwrap().graph();
+    let mut node_ids: Vec<_> = graph.list_nodes().unwrap().into_iter().map(|n| n.id).collect();
+    node_ids.sort();
+    let mut expected: Vec<_> = names.iter().map(|name| ids::file_id(name)).collect();
+    expected.sort();
+    assert_eq!(node_ids, expected);
+
+    for name in names {
+        assert_eq!(Node::from_file_path(std::path::Path::new(name)).id, ids::file_id(name));
+        assert_eq!(repo.node_id_for_path(&path.join(name)).unwrap(), ids::file_id(name));
+        assert_eq!(ids::display_file_id(&ids::file_id(name)), name);
+    }
+
+    let edges = graph.list_edges().unwrap();
+    assert_eq!(edges.len(), 1);
+    assert_eq!(edges[0].to, ids::file_id(names[1]));
+    assert!(repo.legacy_ids().unwrap().is_empty());
+}
+
+#[test]
+fn test_repository_note_roots_limit_automatic_walks() {
+    use memexia::core::note::NoteOptions;
+    use memexia::core::{Indexer, RepoConfig};
+    use tempfile::TempDir;
+
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path();
+    let notes = vec!["content".to_string(), "journal".to_string()];
+    let repo = Repository::init_with_notes(path, &notes).unwrap();
+    configure_git_user(path);
+    let root = repo.path().to_path_buf();
+
+    assert!(root.join("content").is_dir());
+    assert!(root.join("journal").is_dir());
+    assert!(!root.join("notes").exists());
+
+    fs::write(root.join("content/a.md"), "# A\n").unwrap();
+    fs::write(root.join("journal/b.md"), "# B\n").unwrap();
+    fs::create_dir_all(root.join("drafts")).unwrap();
+    fs::write(root.join("drafts/c.md"), "# C\n").unwrap();
+
+    let config = RepoConfig::load(&root).unwrap();
+    assert_eq!(config.paths.walk_dirs(), ["content", "journal"]);
+    let mut indexer = Indexer::new(repo.storage().unwrap().clone());
+    indexer.set_note_roots(config.paths.walk_dirs());
+    let summary = indexer.index_all(&root).unwrap();
+    assert_eq!(summary.files_indexed, 2);
+
+    let graph = repo.storage().unwrap().graph();
+    assert!(graph.node_exists(&repo.node_id_for_path(&root.join("content/a.md")).unwrap()).unwrap());
+    assert!(graph.node_exists(&repo.node_id_for_path(&root.join("journal/b.md")).unwrap()).unwrap());
+    let drafts_id = repo.node_id_for_path(&root.join("drafts/c.md")).unwrap();
+    assert!(!graph.node_exists(&drafts_id).unwrap());
+
+    // 显式索引根目录之外的文件仍然有效
+    indexer.index_file(&root.join("drafts/c.md")).unwrap();
+    assert!(graph.node_exists(&drafts_id).unwrap());
+
+    // 新笔记写入第一个笔记根目录
+    let (file, _) = repo.create_note(&NoteOptions::new("Fresh")).unwrap();
+    assert_eq!(file, root.join("content/fresh.md"));
+}
+
+#[test]
+fn test_repository_link_annotations_survive_reindex() {
+    use memexia::core::Indexer;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+    configure_git_user(path);
+    let repo = Repository::init(path).unwrap();
+    let root = repo.path().to_path_buf();
+    let a = root.join("a.md");
+    fs::write(&a, "# A\n\n[[b]]\n").unwrap();
+
+    let indexer = Indexer::new(repo.storage().unwrap().clone());
+    indexer.index_file(&a).unwrap();
+
+    let b = root.join("b");
+    assert!(repo.annotate_link(&a, &b, "  ").is_err());
+    assert!(repo.annotate_link(&b, &a, "no such link").is_err());
+    repo.annotate_link(&a, &b, "Only holds for small samples").unwrap();
+    let edge = repo.annotate_link(&a, &b, "Confirmed in the 2023 replication").unwrap();
+    assert_eq!(edge.annotations.len(), 2);
+
+    // 重新索引源文件（正文修改后）不会丢失注释
+    fs::write(&a, "# A revised\n\n[[b]]\n").unwrap();
+    indexer.index_file(&a).unwrap();
+
+    let edge = repo.link_between(&a, &b).unwrap();
+    let texts: Vec<_> = edge.annotations.iter().map(|n| n.text.as_str()).collect();
+    assert_eq!(texts, ["Only holds for small samples", "Confirmed in the 2023 replication"]);
+    assert!(edge.annotations[0].created_at <= edge.annotations[1].created_at);
+
+    let outgoing = repo.storage().unwrap().graph().get_edges_by_source(&edge.from).unwrap();
+    assert_eq!(outgoing[0].latest_annotation().unwrap().text, "Confirmed in the 2023 replication");
+
+    // 撤销最近一次注释
+    let journal = repo.undo().unwrap();
+    assert_eq!(journal.operation, "link annotate");
+    assert_eq!(repo.link_between(&a, &b).unwrap().annotations.len(), 1);
+}
+
+#[test]
+fn test_repository_reindex_carries_over_renamed_notes() {
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+    configure_git_user(path);
+    let mut repo = Repository::init(path).unwrap();
+    let root = repo.path().to_path_buf();
+    let body = "# Alpha\n\nA note long enough for Git to recognise it after a small edit.\n\
+                It has a few lines of text.\nAnd one more line.\n";
+    fs::write(root.join("alpha.md"), body).unwrap();
+    fs::write(root.join("beta.md"), "# Beta\n\n[[alpha.md]]\n").unwrap();
+    fs::write(root.join("gamma.md"), "# Gamma\n\nUnchanged when moved.\n").unwrap();
+    repo.add(&[root.join("alpha.md"), root.join("beta.md"), root.join("gamma.md")]).unwrap();
+    repo.commit("Initial notes").unwrap();
+
+    let graph = repo.storage().unwrap().graph();
+    let alpha = repo.node_id_for_path(&root.join("alpha.md")).unwrap();
+    let created = graph.get_node(&alpha).unwrap().unwrap().created_at;
+    repo.vcs().unwrap().graph_history.snapshot_node(&alpha, "alpha v1", "c1").unwrap();
+
+    // git mv 并修改正文（内容哈希不再匹配，依靠 Git 的重命名检测）；gamma 直接在文件系统中移动
+    std::process::Command::new("git")
+        .args(["mv", "alpha.md", "omega.md"])
+        .current_dir(&root)
+        .output()
+        .unwrap();
+    fs::write(root.join("omega.md"), format!("{}Edited after the move.\n", body)).unwrap();
+    fs::rename(root.join("gamma.md"), root.join("delta.md")).unwrap();
+
+    let summary = repo.reindex(&root, false, true).unwrap();
+    assert_eq!(summary.renamed.len(), 2);
+
+    let omega = repo.node_id_for_path(&root.join("omega.md")).unwrap();
+    assert_ne!(omega, alpha);
+    assert!(!graph.node_exists(&alpha).unwrap());
+    assert_eq!(graph.get_node(&omega).unwrap().unwrap().created_at.timestamp(), created.timestamp());
+
+    let backlinks = graph.get_edges_by_target(&omega).unwrap();
+    let beta = repo.node_id_for_path(&root.join("beta.md")).unwrap();
+    assert_eq!(backlinks.len(), 1);
+    assert_eq!(backlinks[0].from, beta);
+
+    let history = &repo.vcs().unwrap().graph_history;
+    assert!(history.get_node_history(&alpha).unwrap().is_empty());
+    assert_eq!(history.get_node_history(&omega).unwrap()[0].commit_hash, "c1");
+
+    let gamma = repo.node_id_for_path(&root.join("gamma.md")).unwrap();
+    assert!(!graph.node_exists(&gamma).unwrap());
+    assert!(graph.node_exists(&repo.node_id_for_path(&root.join("delta.md")).unwrap()).unwrap());
+
+    // 关闭检测时移动的文件作为新笔记索引
+    fs::rename(root.join("delta.md"), root.join("epsilon.md")).unwrap();
+    let summary = repo.reindex(&root, false, false).unwrap();
+    assert!(summary.renamed.is_empty());
+    assert!(graph.node_exists(&repo.node_id_for_path(&root.join("delta.md")).unwrap()).unwrap());
+}
+
+#[test]
+fn test_repository_file_log_merges_commits_snapshots_and_derivations() {
+    use memexia::core::file_log::{FileChange, FileLogEvent};
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+    configure_git_user(path);
+    let mut repo = Repository::init(path).unwrap();
+    let note = repo.path().join("note.md");
+
+    fs::write(&note, "# Note\n\nFirst draft.\n").unwrap();
+    repo.add(std::slice::from_ref(&note)).unwrap();
+    repo.commit("Draft").unwrap();
+    fs::write(&note, "# Note\n\nFirst draft, now with many more words about [[other]].\n").unwrap();
+    repo.add(std::slice::from_ref(&note)).unwrap();
+    repo.commit("Expand").unwrap();
+    fs::write(&note, "# Note\n\nTrimmed.\n").unwrap();
+    repo.add(std::slice::from_ref(&note)).unwrap();
+    repo.commit("Trim").unwrap();
+
+    let node_id = repo.node_id_for_path(&note).unwrap();
+    let history = &repo.vcs().unwrap().graph_history;
+    history.snapshot_node(&node_id, "{}", "snapshot-commit").unwrap();
+    history.record_derivation("urn:memexia:file:child.md", &node_id, "derive-commit").unwrap();
+
+    let entries = repo.file_log(&note).unwrap();
+    assert_eq!(entries.len(), 5);
+    assert!(entries.windows(2).all(|w| w[0].timestamp >= w[1].timestamp));
+    assert!(matches!(&entries[0].event, FileLogEvent::DerivedInto { child, .. } if child == "urn:memexia:file:child.md"));
+    assert!(matches!(&entries[1].event, FileLogEvent::Snapshot { commit, .. } if commit == "snapshot-commit"));
+
+    let commits: Vec<(&str, &[FileChange])> = entries[2..]
+        .iter()
+        .map(|entry| match &entry.event {
+            FileLogEvent::Commit { message, changes, .. } => (message.as_str(), changes.as_slice()),
+            other => panic!("Expected commit, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(commits.iter().map(|(m, _)| *m).collect::<Vec<_>>(), ["Trim", "Expand", "Draft"]);
+    assert!(matches!(commits[0].1[0], FileChange::ContentChanged { word_delta } if word_delta < 0));
+    assert!(matches!(commits[0].1[1], FileChange::LinkRemoved { .. }));
+    assert!(matches!(commits[1].1[0], FileChange::ContentChanged { word_delta } if word_delta > 0));
+    assert!(matches!(&commits[1].1[1], FileChange::LinkAdded { target } if target == "other"));
+    assert!(matches!(commits[2].1[0], FileChange::Created { .. }));
+}
+
+#[test]
+fn test_repository_folder_defaults_for_new_and_indexed_notes() {
+    use memexia::core::note::NoteOptions;
+    use memexia::storage::NodeType;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+    configure_git_user(path);
+    let repo = Repository::init(path).unwrap();
+    let root = repo.path().to_path_buf();
+    fs::create_dir_all(root.join(".memexia/templates")).unwrap();
+    fs::write(
+        root.join(".memexia/templates/_defaults.toml"),
+        "[[folder]]\nglob = \"people/**\"\nfrontmatter = { type = \"Person\" }\n\n\
+         [[folder]]\nglob = \"literature\"\ntemplate = \"literature\"\nfrontmatter = { type = \"Resource\", source = \"\" }\n",
+    )
+    .unwrap();
+    fs::write(root.join(".memexia/templates/literature.md"), "# {{title}}\n\n## Summary\n").unwrap();
+    let node_type = |id: &str| repo.storage().unwrap().graph().get_node(id).unwrap().unwrap().node_type;
+
+    // 通过 new 创建
+    let (_, ada) = repo
+        .create_note(&NoteOptions { dir: Some("people".to_string()), ..NoteOptions::new("Ada Lovelace") })
+        .unwrap();
+    assert_eq!(node_type(&ada), NodeType::Person);
+    let (file, sicp) = repo
+        .create_note(&NoteOptions { dir: Some("literature".to_string()), ..NoteOptions::new("SICP") })
+        .unwrap();
+    assert_eq!(node_type(&sicp), NodeType::Resource);
+    let content = fs::read_to_string(file).unwrap();
+    assert!(content.contains("source: \"\"\n"));
+    assert!(content.contains("## Summary"));
+
+    // 显式指定的类型优先
+    let (_, explicit) = repo
+        .create_note(&NoteOptions {
+            dir: Some("people".to_string()),
+            node_type: Some(NodeType::Question),
+            ..NoteOptions::new("Who was Babbage")
+        })
+        .unwrap();
+    assert_eq!(node_type(&explicit), NodeType::Question);
+
+    // 直接写入文件后重新索引
+    fs::create_dir_all(root.join("people/team")).unwrap();
+    fs::write(root.join("people/team/bob.md"), "# Bob\n").unwrap();
+    fs::write(root.join("literature/gof.md"), "# Design Patterns\n").unwrap();
+    fs::write(root.join("people/carol.md"), "---\ntype: Concept\n---\n# Carol\n").unwrap();
+    fs::write(root.join("idea.md"), "# Idea\n").unwrap();
+    repo.reindex(&root, false, true).unwrap();
+
+    let id = |rel: &str| repo.node_id_for_path(&root.join(rel)).unwrap();
+    assert_eq!(node_type(&id("people/team/bob.md")), NodeType::Person);
+    assert_eq!(node_type(&id("literature/gof.md")), NodeType::Resource);
+    assert_eq!(node_type(&id("people/carol.md")), NodeType::Concept);
+    assert_eq!(node_type(&id("idea.md")), NodeType::Concept);
+}
+
+#[test]
+fn test_repository_events() {
+    use memexia::core::Indexer;
+    use memexia::events::RepoEvent;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+    configure_git_user(path);
+    let mut repo = Repository::init(path).unwrap();
+    let root = repo.path().to_path_buf();
+    let events = repo.subscribe();
+
+    fs::write(root.join("a.md"), "# A\n\n[[b.md|Supports]]\n").unwrap();
+    Indexer::new(repo.storage().unwrap().clone()).index_file(&root.join("a.md")).unwrap();
+    repo.add(&[root.join("a.md")]).unwrap();
+    let oid = repo.commit("Add a").unwrap();
+
+    let received: Vec<RepoEvent> = events.try_iter().collect();
+    let names: Vec<&str> = received
+        .iter()
+        .map(|event| match event {
+            RepoEvent::NodeIndexed { .. } => "node_indexed",
+            RepoEvent::EdgeAdded { .. } => "edge_added",
+            RepoEvent::SnapshotTaken { .. } => "snapshot_taken",
+            RepoEvent::CommitCreated { .. } => "commit_created",
+            other => panic!("unexpected event {:?}", other),
+        })
+        .collect();
+    // 提交时重新处理 a.md，出边没有变化
+    assert_eq!(names, ["node_indexed", "edge_added", "node_indexed", "snapshot_taken", "commit_created"]);
+
+    let a = "urn:memexia:file:a.md";
+    assert!(matches!(&received[0], RepoEvent::NodeIndexed { id, title, path, .. } if id == a && title == "A" && path == "a.md"));
+    assert!(matches!(&received[1], RepoEvent::EdgeAdded { from, to, relation, .. }
+        if from == a && to == "urn:memexia:file:b.md" && relation == "Supports"));
+    assert_eq!(received[4], RepoEvent::CommitCreated { oid, message: "Add a".to_string() });
+}
+
+#[test]
+fn test_repository_edit_node_writes_frontmatter() {
+    use memexia::core::writer::{NodeEdit, WriteBack};
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+    configure_git_user(path);
+    let mut repo = Repository::init(path).unwrap();
+    let root = repo.path().to_path_buf();
+    fs::write(root.join("a.md"), "---\ntitle: A\nsource: book\n---\n\nBody.\n").unwrap();
+    repo.add(&[root.join("a.md")]).unwrap();
+    repo.commit("Add a").unwrap();
+
+    let edit = NodeEdit {
+        title: Some("Alpha".to_string()),
+        add_tags: vec!["greek".to_string()],
+        ..Default::default()
+    };
+    let (id, outcome) = repo.edit_node(std::path::Path::new("a.md"), &edit).unwrap();
+    assert_eq!(outcome, Some(WriteBack::Written));
+    assert_eq!(
+        fs::read_to_string(root.join("a.md")).unwrap(),
+        "---\ntitle: Alpha\nsource: book\ntags: [greek]\n---\n\nBody.\n"
+    );
+    let node = repo.storage().unwrap().graph().get_node(&id).unwrap().unwrap();
+    assert_eq!(node.title, "Alpha");
+    assert_eq!(node.tags, ["greek"]);
+
+    // 文件在上次同步后被修改：以文件为准
+    fs::write(root.join("a.md"), "---\ntitle: Edited\n---\n").unwrap();
+    let (_, outcome) = repo.edit_node(std::path::Path::new("a.md"), &edit).unwrap();
+    assert_eq!(outcome, Some(WriteBack::Conflict));
+    assert_eq!(fs::read_to_string(root.join("a.md")).unwrap(), "---\ntitle: Edited\n---\n");
+    assert_eq!(repo.storage().unwrap().graph().get_node(&id).unwrap().unwrap().title, "Edited");
+}
+
+#[test]
+fn test_repository_set_node_generated_node() {
+    use memexia::core::writer::{FieldChange, NodeEdit};
+    use memexia::storage::{Node, NodeType};
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let repo = Repository::init(temp.path()).unwrap();
+    let id = "urn:memexia:generated:placeholder";
+    let mut node = Node::new(id, NodeType::Concept, "Placeholder");
+    node.created_at = chrono::Utc::now() - chrono::Duration::days(7);
+    node.updated_at = node.created_at;
+    node.tags = vec!["draft".to_string(), "old".to_string()];
+    repo.storage().unwrap().graph().add_node(&node).unwrap();
+
+    let edit = NodeEdit {
+        title: Some("Gödel".to_string()),
+        node_type: Some(NodeType::Person),
+        add_tags: vec!["logic".to_string(), "draft".to_string()],
+        remove_tags: vec!["old".to_string()],
+        ..Default::default()
+    };
+    let outcome = repo.set_node(id, &edit, false).unwrap();
+    assert_eq!(outcome.id, id);
+    assert_eq!(outcome.write_back, None);
+    assert_eq!(
+        outcome.changes,
+        [
+            FieldChange { field: "title", before: "Placeholder".to_string(), after: "Gödel".to_string() },
+            FieldChange { field: "type", before: "Concept".to_string(), after: "Person".to_string() },
+            FieldChange { field: "tags", before: "draft, old".to_string(), after: "draft, logic".to_string() },
+        ]
+    );
+    let stored = repo.storage().unwrap().graph().get_node(id).unwrap().unwrap();
+    assert_eq!(stored.title, "Gödel");
+    assert_eq!(stored.node_type, NodeType::Person);
+    let mut tags = stored.tags.clone();
+    tags.sort();
+    assert_eq!(tags, ["draft", "logic"]);
+    assert_eq!(stored.created_at.timestamp(), node.created_at.timestamp());
+
+    // 添加已有标签、移除不存在的标签：没有改动
+    let edit = NodeEdit {
+        add_tags: vec!["logic".to_string()],
+        remove_tags: vec!["missing".to_string()],
+        ..Default::default()
+    };
+    assert!(repo.set_node(id, &edit, false).unwrap().changes.is_empty());
+
+    assert!(matches!(
+        repo.set_node("urn:memexia:generated:missing", &edit, false),
+        Err(MemexiaError::NodeNotFound { .. })
+    ));
+}
+
+#[test]
+fn test_repository_set_node_file_backed_title_requires_write_file() {
+    use memexia::core::writer::{NodeEdit, WriteBack};
+    use memexia::storage::NodeType;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+    configure_git_user(path);
+    let mut repo = Repository::init(path).unwrap();
+    let root = repo.path().to_path_buf();
+    fs::write(root.join("a.md"), "---\ntitle: A\ntags: [x]\n---\n\nBody.\n").unwrap();
+    repo.add(&[root.join("a.md")]).unwrap();
+    repo.commit("Add a").unwrap();
+
+    let rename = NodeEdit { title: Some("Alpha".to_string()), ..Default::default() };
+    assert!(matches!(repo.set_node("a.md", &rename, false), Err(MemexiaError::Validation(_))));
+    assert_eq!(fs::read_to_string(root.join("a.md")).unwrap(), "---\ntitle: A\ntags: [x]\n---\n\nBody.\n");
+
+    // 类型和标签不需要 --write-file，按节点 ID 修改并写回 frontmatter
+    let retype = NodeEdit {
+        node_type: Some(NodeType::Question),
+        add_tags: vec!["y".to_string()],
+        remove_tags: vec!["x".to_string()],
+        ..Default::default()
+    };
+    let outcome = repo.set_node("urn:memexia:file:a.md", &retype, false).unwrap();
+    assert_eq!(outcome.write_back, Some(WriteBack::Written));
+    let fields: Vec<_> = outcome.changes.iter().map(|change| change.field).collect();
+    assert_eq!(fields, ["type", "tags"]);
+    assert_eq!(
+        fs::read_to_string(root.join("a.md")).unwrap(),
+        "---\ntitle: A\ntags: [y]\ntype: Question\n---\n\nBody.\n"
+    );
+
+    let outcome = repo.set_node("a.md", &rename, true).unwrap();
+    assert_eq!(outcome.write_back, Some(WriteBack::Written));
+    assert_eq!(outcome.changes.len(), 1);
+    assert_eq!((outcome.changes[0].before.as_str(), outcome.changes[0].after.as_str()), ("A", "Alpha"));
+    let node = repo.storage().unwrap().graph().get_node(&outcome.id).unwrap().unwrap();
+    assert_eq!(node.title, "Alpha");
+    assert!(fs::read_to_string(root.join("a.md")).unwrap().contains("title: Alpha\n"));
+}
+
+/// 检查 XML 是否格式良好，返回依次出现的元素路径（如 `feed/entry/title`）
+fn xml_element_paths(xml: &str) -> Vec<String> {
+    use quick_xml::events::Event;
+
+    let mut reader = quick_xml::Reader::from_str(xml);
+    let mut stack: Vec<String> = Vec::new();
+    let mut paths = Vec::new();
+    loop {
+        match reader.read_event().unwrap_or_else(|e| panic!("invalid XML: {}\n{}", e, xml)) {
+            Event::Start(e) => {
+                stack.push(String::from_utf8(e.name().as_ref().to_vec()).unwrap());
+                paths.push(stack.join("/"));
+            }
+            Event::Empty(e) => {
+                paths.push(format!("{}/{}", stack.join("/"), String::from_utf8(e.name().as_ref().to_vec()).unwrap()));
+            }
+            Event::End(_) => {
+                stack.pop();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    assert!(stack.is_empty(), "unclosed elements: {:?}", stack);
+    paths
+}
+
+#[test]
+fn test_repository_feed() {
+    use memexia::core::feed::FeedFormat;
+    use memexia::core::RepoConfig;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+    configure_git_user(path);
+    let mut repo = Repository::init(path).unwrap();
+    let root = repo.path().to_path_buf();
+    RepoConfig::set(&root, "publish.base_url", "https://notes.example.com").unwrap();
+
+    fs::write(root.join("a.md"), "# Alpha\n\nFirst note.\n").unwrap();
+    repo.add(&[root.join("a.md")]).unwrap();
+    repo.commit("Add alpha").unwrap();
+    // 提交时间精确到秒，等到下一秒再提交第二个，用于检查 --since
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    let between = chrono::DateTime::from_timestamp(chrono::Utc::now().timestamp(), 0).unwrap();
+    fs::write(root.join("b.md"), "# Beta & co\n\n[[a.md|Supports]]\n").unwrap();
+    repo.add(&[root.join("b.md")]).unwrap();
+    repo.commit("Add beta").unwrap();
+
+    let feed = repo.feed(None).unwrap();
+    let titles: Vec<&str> = feed.entries.iter().map(|e| e.title.as_str()).collect();
+    assert_eq!(titles, ["Add beta", "Add alpha"]);
+    let beta = &feed.entries[0];
+    let notes: Vec<&str> = beta.new_notes.iter().map(|n| n.title.as_str()).collect();
+    assert_eq!(notes, ["Beta & co"]);
+    assert_eq!(beta.link.as_deref(), Some("https://notes.example.com/notes/b.html"));
+    assert!(beta.body_html().contains("Beta &amp; co now Supports Alpha"), "{}", beta.body_html());
+    assert_eq!(feed.entries[1].new_notes.len(), 1);
+    assert!(feed.entries[1].new_edges.is_empty());
+
+    let atom = feed.render(FeedFormat::Atom);
+    let paths = xml_element_paths(&atom);
+    assert_eq!(paths.iter().filter(|p| *p == "feed/entry").count(), 2);
+    for element in ["feed/id", "feed/title", "feed/updated", "feed/entry/id", "feed/entry/updated", "feed/entry/content", "feed/entry/link"] {
+        assert!(paths.iter().any(|p| p == element), "missing {} in {}", element, atom);
+    }
+    let updated = atom.split("<updated>").nth(2).unwrap().split("</updated>").next().unwrap();
+    assert!(chrono::DateTime::parse_from_rfc3339(updated).is_ok(), "{}", updated);
+
+    let rss = feed.render(FeedFormat::Rss);
+    let paths = xml_element_paths(&rss);
+    assert_eq!(paths.iter().filter(|p| *p == "rss/channel/item").count(), 2);
+    for element in ["rss/channel/title", "rss/channel/link", "rss/channel/description", "rss/channel/item/guid"] {
+        assert!(paths.iter().any(|p| p == element), "missing {} in {}", element, rss);
+    }
+    let pub_date = rss.split("<pubDate>").nth(1).unwrap().split("</pubDate>").next().unwrap();
+    assert!(chrono::DateTime::parse_from_rfc2822(pub_date).is_ok(), "{}", pub_date);
+
+    // --since 过滤掉更早的提交
+    let since = chrono::Utc::now() + chrono::Duration::hours(1);
+    assert!(repo.feed(Some(since)).unwrap().entries.is_empty());
+    let recent = repo.feed(Some(between)).unwrap();
+    let titles: Vec<&str> = recent.entries.iter().map(|e| e.title.as_str()).collect();
+    assert_eq!(titles, ["Add beta"]);
+    let paths = xml_element_paths(&recent.render(FeedFormat::Rss));
+    assert_eq!(paths.iter().filter(|p| *p == "rss/channel/item").count(), 1);
+}
+
+#[test]
+fn test_repository_query_templates() {
+    use memexia::core::query_templates::{self, QUERIES_DIR};
+    use memexia::storage::{Node, NodeType};
+    use std::collections::BTreeMap;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let repo = Repository::init(temp.path()).unwrap();
+    let root = repo.path().to_path_buf();
+    assert!(root.join(QUERIES_DIR).join("notes-tagged.rq").is_file());
+
+    let graph = repo.storage().unwrap().graph();
+    let mut quoted = Node::new("urn:memexia:file:a.md", NodeType::Concept, "A");
+    quoted.tags = vec!["say \"hi\"".to_string()];
+    graph.add_node(&quoted).unwrap();
+    let mut plain = Node::new("urn:memexia:file:b.md", NodeType::Concept, "B");
+    plain.tags = vec!["say".to_string()];
+    graph.add_node(&plain).unwrap();
+
+    let values = BTreeMap::from([("tag".to_string(), "say \"hi\"".to_string())]);
+    let query = query_templates::load(&root, "notes-tagged").unwrap().render(&values).unwrap();
+    let result = graph.query(&query).unwrap();
+    assert_eq!(result.bindings.len(), 1);
+    assert!(result.bindings[0]["?title"].contains('A'));
+}
+
+#[test]
+fn test_repository_append_note() {
+    use memexia::core::capture::Insert;
+    use memexia::storage::ids;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+    configure_git_user(path);
+    let mut repo = Repository::init(path).unwrap();
+    let root = repo.path().to_path_buf();
+    fs::write(root.join("journal.md"), "---\ntitle: Journal\n---\n\n## Ideas\n\n- first\n").unwrap();
+    repo.add(&[root.join("journal.md")]).unwrap();
+    repo.commit("Add journal").unwrap();
+
+    let insert = Insert {
+        prepend: false,
+        section: Some("Links".to_string()),
+    };
+    let id = repo.append_note(&root.join("journal.md"), "See [[other.md]].", &insert).unwrap();
+    assert_eq!(
+        fs::read_to_string(root.join("journal.md")).unwrap(),
+        "---\ntitle: Journal\n---\n\n## Ideas\n\n- first\n\n## Links\n\nSee [[other.md]].\n"
+    );
+
+    // 追加的链接已重新索引，文件已暂存
+    let edges = repo.storage().unwrap().graph().get_edges_by_source(&id).unwrap();
+    assert!(edges.iter().any(|edge| edge.to == ids::file_id("other.md")));
+    repo.commit("Capture").unwrap();
+    assert_eq!(repo.vcs().unwrap().head_info().unwrap().unwrap().message.trim(), "Capture");
+}
+
+#[test]
+fn test_repository_render_graph_template() {
+    use memexia::core::Indexer;
+    use memexia::storage::ids;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    let repo = Repository::init(root).unwrap();
+    let notes = [
+        ("paper.md", "# Paper\n\n#mind #ethics\n\n[[chapter.md|Contains]] [[aside.md]]\n\n## Cited by\n\n{{backlinks}}\n\n{{related limit=1}}\n\n```\n{{children}}\n```\n{{children}}\n"),
+        ("smith.md", "# Smith\n\n[[paper.md|Supports]]\n"),
+        ("chapter.md", "# Chapter\n\n#mind #ethics\n"),
+        ("aside.md", "# Aside\n\n#mind\n"),
+    ];
+    let indexer = Indexer::new(repo.storage().unwrap().clone());
+    for (name, content) in notes {
+        fs::write(root.join(name), content).unwrap();
+        indexer.index_file(&root.join(name)).unwrap();
+    }
+
+    let paper = root.join("paper.md");
+    assert!(repo.render_graph_template(&paper).unwrap());
+    let rendered = fs::read_to_string(&paper).unwrap();
+    assert!(rendered.contains(
+        "<!-- memexia:begin backlinks -->\n- [[smith.md]] Smith\n<!-- memexia:end -->\n\n\
+         <!-- memexia:begin related limit=1 -->\n- [[chapter.md]] Chapter\n<!-- memexia:end -->\n\n\
+         ```\n{{children}}\n```\n\
+         <!-- memexia:begin children -->\n- [[chapter.md]] Chapter\n<!-- memexia:end -->\n"
+    ), "{}", rendered);
+    assert!(!repo.render_graph_template(&paper).unwrap());
+
+    // 生成块中的链接不作为出链索引
+    indexer.index_file(&paper).unwrap();
+    let targets: Vec<String> = repo
+        .storage()
+        .unwrap()
+        .graph()
+        .get_edges_by_source(&ids::file_id("paper.md"))
+        .unwrap()
+        .into_iter()
+        .map(|edge| edge.to)
+        .collect();
+    assert_eq!(targets.len(), 2);
+    assert!(!targets.contains(&ids::file_id("smith.md")));
+
+    // 新的反向链接在重新渲染时替换旧内容，不重复插入
+    fs::write(root.join("aside.md"), "# Aside\n\n#mind\n\n[[paper.md]]\n").unwrap();
+    indexer.index_file(&root.join("aside.md")).unwrap();
+    assert!(repo.render_graph_template(&paper).unwrap());
+    let rerendered = fs::read_to_string(&paper).unwrap();
+    assert!(rerendered.contains("<!-- memexia:begin backlinks -->\n- [[aside.md]] Aside\n- [[smith.md]] Smith\n<!-- memexia:end -->\n"));
+    assert_eq!(rerendered.matches("<!-- memexia:begin").count(), 3);
+    assert_eq!(rerendered.matches("- [[smith.md]]").count(), 1);
+}
+
+#[test]
+fn test_repository_split_note() {
+    use memexia::storage::ids;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+    configure_git_user(path);
+    let mut repo = Repository::init(path).unwrap();
+    let root = repo.path().to_path_buf();
+    fs::create_dir_all(root.join("notes")).unwrap();
+    fs::write(
+        root.join("notes/mind.md"),
+        "# Mind\n\nSee [[notes/brain.md]].\n\n## Free Will\n\nSee [[notes/determinism.md]].\n\n## Qualia\n\nRed.\n",
+    )
+    .unwrap();
+    repo.add(&[root.join("notes/mind.md")]).unwrap();
+    repo.commit("Add mind").unwrap();
+
+    let err = repo
+        .split_note(&root.join("notes/mind.md"), "Fre Will", None, false)
+        .unwrap_err();
+    assert!(err.to_string().contains("did you mean \"Free Will\""));
+
+    let outcome = repo
+        .split_note(&root.join("notes/mind.md"), "Free Will", None, false)
+        .unwrap();
+    assert_eq!(outcome.path, "notes/free-will.md");
+    assert_eq!(
+        fs::read_to_string(root.join("notes/mind.md")).unwrap(),
+        "# Mind\n\nSee [[notes/brain.md]].\n\n[[notes/free-will.md|Contains]]\n\n## Qualia\n\nRed.\n"
+    );
+    assert_eq!(
+        fs::read_to_string(root.join("notes/free-will.md")).unwrap(),
+        "---\ntitle: \"Free Will\"\ntype: Concept\n---\n\n# Free Will\n\nSee [[notes/determinism.md]].\n"
+    );
+
+    // 小节中的链接改由新节点发出
+    let graph = repo.storage().unwrap().graph();
+    let determinism = ids::file_id("notes/determinism.md");
+    let sources: Vec<String> = graph
+        .list_edges()
+        .unwrap()
+        .into_iter()
+        .filter(|edge| edge.to == determinism)
+        .map(|edge| edge.from)
+        .collect();
+    assert_eq!(sources, [outcome.node_id.as_str()]);
+    let original_targets: Vec<String> = graph
+        .get_edges_by_source(&outcome.original_id)
+        .unwrap()
+        .into_iter()
+        .map(|edge| edge.to)
+        .collect();
+    assert!(original_targets.contains(&ids::file_id("notes/brain.md")));
+    assert!(original_targets.contains(&outcome.node_id));
+
+    let head = repo.vcs().unwrap().head_info().unwrap().unwrap().oid;
+    let derivations = repo.vcs().unwrap().graph_history.get_derivations(&outcome.node_id).unwrap();
+    assert_eq!(derivations.len(), 1);
+    assert_eq!(derivations[0].parent_id, outcome.original_id);
+    assert_eq!(derivations[0].commit_hash, head);
+}
+
+#[test]
+fn test_repository_retag_graph_and_files() {
+    use memexia::core::retag::TagOp;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+    configure_git_user(path);
+    let mut repo = Repository::init(path).unwrap();
+    let root = repo.path().to_path_buf();
+    fs::write(
+        root.join("a.md"),
+        "---\ntags: [old, keep]\n---\n\n# A\n\nFiled under #old, and (#old). Not #older.\n",
+    )
+    .unwrap();
+    fs::write(root.join("b.md"), "---\ntags:\n  - old/child\n---\n\n# B\n\nSee #old/child.\n").unwrap();
+    repo.add(&[root.join("a.md"), root.join("b.md")]).unwrap();
+    repo.commit("Tagged notes").unwrap();
+
+    let graph = repo.storage().unwrap().graph();
+    let a = repo.node_id_for_path(&root.join("a.md")).unwrap();
+    let b = repo.node_id_for_path(&root.join("b.md")).unwrap();
+    let tags = |id: &str| {
+        let mut tags = graph.get_node(id).unwrap().unwrap().tags;
+        tags.sort();
+        tags
+    };
+
+    // 只改图：文件保持不变，子标签不受影响
+    let summary = repo.retag(&TagOp::rename("old", "topic", false).unwrap(), false).unwrap();
+    assert_eq!(summary.nodes, [a.as_str()]);
+    assert!(summary.files.is_empty());
+    assert_eq!(tags(&a), ["keep", "older", "topic"]);
+    assert_eq!(tags(&b), ["old/child"]);
+    assert!(fs::read_to_string(root.join("a.md")).unwrap().contains("[old, keep]"));
+
+    // 递归改名并改写文件
+    let summary = repo.retag(&TagOp::rename("old", "topic", true).unwrap(), true).unwrap();
+    assert_eq!(summary.nodes, [b.as_str()]);
+    assert_eq!(summary.files, [std::path::PathBuf::from("a.md"), std::path::PathBuf::from("b.md")]);
+    assert_eq!(tags(&b), ["topic/child"]);
+    assert_eq!(
+        fs::read_to_string(root.join("a.md")).unwrap(),
+        "---\ntags: [topic, keep]\n---\n\n# A\n\nFiled under #topic, and (#old). Not #older.\n"
+    );
+    assert_eq!(
+        fs::read_to_string(root.join("b.md")).unwrap(),
+        "---\ntags:\n  - topic/child\n---\n\n# B\n\nSee #topic/child.\n"
+    );
+    let staged = memexia::core::staging::StagingIndex::load(&root).unwrap();
+    assert!(staged.contains("a.md"));
+    assert!(staged.contains("b.md"));
+
+    // dry-run 不改图也不改文件
+    repo.enable_dry_run();
+    let summary = repo.retag(&TagOp::remove("topic", true).unwrap(), true).unwrap();
+    assert_eq!(summary.files.len(), 2);
+    assert_eq!(repo.storage().unwrap().graph().get_node(&b).unwrap().unwrap().tags, ["topic/child"]);
+    assert!(fs::read_to_string(root.join("b.md")).unwrap().contains("#topic/child"));
+}
+
+#[test]
+fn test_repository_ndjson_round_trip() {
+    use memexia::storage::ndjson::{read_ndjson, write_ndjson, NdjsonExportOptions};
+    use tempfile::TempDir;
+
+    let source = TempDir::new().unwrap();
+    configure_git_user(source.path());
+    let mut repo = Repository::init(source.path()).unwrap();
+    let root = repo.path().to_path_buf();
+    fs::write(root.join("a.md"), "# A\n\n[[b.md|Supports:0.7]] #topic\n").unwrap();
+    fs::write(root.join("b.md"), "# B\n").unwrap();
+    repo.add(&[root.join("a.md"), root.join("b.md")]).unwrap();
+    repo.commit("Notes").unwrap();
+
+    let mut out = Vec::new();
+    let exported = write_ndjson(repo.storage().unwrap().graph(), &mut out, NdjsonExportOptions::default()).unwrap();
+    assert_eq!((exported.nodes, exported.edges), (2, 1));
+
+    let target = TempDir::new().unwrap();
+    configure_git_user(target.path());
+    let copy = Repository::init(target.path()).unwrap();
+    let imported = read_ndjson(copy.storage().unwrap().graph(), out.as_slice(), false).unwrap();
+    assert_eq!((imported.nodes_imported, imported.edges_imported), (2, 1));
+
+    let a = repo.node_id_for_path(&root.join("a.md")).unwrap();
+    let original = repo.storage().unwrap().graph().get_node(&a).unwrap().unwrap();
+    let restored = copy.storage().unwrap().graph().get_node(&a).unwrap().unwrap();
+    assert_eq!(restored.title, original.title);
+    assert_eq!(restored.tags, original.tags);
+    assert_eq!(restored.updated_at, original.updated_at);
+    let edges = copy.storage().unwrap().graph().get_edges_by_source(&a).unwrap();
+    assert_eq!(edges.len(), 1);
+    assert_eq!(edges[0].strength, 0.7);
+}
+
+#[test]
+fn test_repository_open_read_only() {
+    use memexia::error::MemexiaError;
+    use memexia::storage::{Node, NodeType};
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    configure_git_user(temp.path());
+    let mut repo = Repository::init(temp.path()).unwrap();
+    let root = repo.path().to_path_buf();
+    fs::write(root.join("a.md"), "# A\n\n[[b.md]] #topic\n").unwrap();
+    fs::write(root.join("b.md"), "# B\n").unwrap();
+    repo.add(&[root.join("a.md"), root.join("b.md")]).unwrap();
+    repo.commit("Notes").unwrap();
+    drop(repo);
+    let index_before = fs::read_to_string(root.join(".memexia/index")).unwrap_or_default();
+
+    let mut repo = Repository::open_read_only(&root).unwrap();
+    assert!(repo.is_read_only());
+    assert!(!repo.storage().unwrap().is_dry_run());
+
+    // 读操作正常
+    assert_eq!(repo.stats().unwrap().notes, 2);
+    assert_eq!(repo.log(10).unwrap().len(), 1);
+    assert!(!repo.export_nquads().unwrap().is_empty());
+    assert!(repo.validate().is_ok());
+    let a = repo.node_id_for_path(&root.join("a.md")).unwrap();
+    assert_eq!(repo.storage().unwrap().graph().get_edges_by_source(&a).unwrap().len(), 1);
+
+    // 写操作返回只读错误，仓库不变
+    fs::write(root.join("c.md"), "# C\n").unwrap();
+    let read_only = |err: MemexiaError| matches!(err, MemexiaError::ReadOnlyRepository { .. });
+    assert!(read_only(repo.add(&[root.join("c.md")]).unwrap_err()));
+    assert!(read_only(repo.commit("More").unwrap_err()));
+    let node = Node::new("urn:memexia:file:c.md", NodeType::Concept, "C");
+    assert!(read_only(repo.storage().unwrap().graph().add_node(&node).unwrap_err()));
+
+    // dry-run 不会解除只读
+    repo.enable_dry_run();
+    assert!(read_only(repo.storage().unwrap().graph().add_node(&node).unwrap_err()));
+    assert_eq!(fs::read_to_string(root.join(".memexia/index")).unwrap_or_default(), index_before);
+    assert_eq!(repo.stats().unwrap().notes, 2);
+}
+
+#[test]
+fn test_repository_export_corpus() {
+    use memexia::core::corpus::{ChunkOptions, ManifestEntry, MANIFEST_FILE};
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    configure_git_user(temp.path());
+    let mut repo = Repository::init(temp.path()).unwrap();
+    let root = repo.path().to_path_buf();
+    fs::create_dir_all(root.join("notes")).unwrap();
+    fs::write(root.join("notes/a.md"), "---\ntags: [topic]\n---\n# A\n\nSee [[b.md|the B note]].\n").unwrap();
+    fs::write(root.join("notes/b.md"), "# B\n\none two three four five six\n").unwrap();
+    fs::write(root.join("notes/draft.md"), "# Not indexed\n").unwrap();
+    repo.add(&[root.join("notes/a.md"), root.join("notes/b.md")]).unwrap();
+    repo.commit("Notes").unwrap();
+
+    // 输出目录在仓库内时不会被当作笔记
+    let out = root.join("corpus");
+    let summary = repo.export_corpus(&out, None).unwrap();
+    assert_eq!((summary.notes, summary.files), (2, 2));
+    let text = fs::read_to_string(out.join("notes/a.txt")).unwrap();
+    assert!(text.starts_with("A\n\n"));
+    assert!(text.contains("See the B note."));
+
+    let manifest: Vec<ManifestEntry> = fs::read_to_string(out.join(MANIFEST_FILE))
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(manifest.len(), 2);
+    assert_eq!(manifest[0].source, "notes/a.md");
+    assert_eq!(manifest[0].id, repo.node_id_for_path(&root.join("notes/a.md")).unwrap());
+    assert_eq!(manifest[0].tags, ["topic"]);
+
+    let summary = repo.export_corpus(&out, Some(ChunkOptions::new(4, 1).unwrap())).unwrap();
+    // a：7 词分 2 块；b：9 词分 3 块
+    assert_eq!((summary.notes, summary.files), (2, 5));
+    assert!(out.join("notes/b.2.txt").exists());
+}
+
+#[test]
+fn test_repository_archive_linked_note() {
+    use memexia::storage::EdgeDirection;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    configure_git_user(temp.path());
+    let mut repo = Repository::init(temp.path()).unwrap();
+    let root = repo.path().to_path_buf();
+    fs::create_dir_all(root.join("notes")).unwrap();
+    fs::write(root.join("notes/old.md"), "# Old idea\n\nAn abandoned draft about gardens.\n").unwrap();
+    fs::write(root.join("notes/current.md"), "# Current\n\nBuilds on [[notes/old.md]] and gardens.\n").unwrap();
+    repo.add(&[root.join("notes/old.md"), root.join("notes/current.md")]).unwrap();
+    repo.commit("Notes").unwrap();
+
+    let old_id = repo.node_id_for_path(&root.join("notes/old.md")).unwrap();
+    let current_id = repo.node_id_for_path(&root.join("notes/current.md")).unwrap();
+    assert_eq!(repo.search("gardens", false, None).unwrap().len(), 2);
+
+    let outcome = repo.archive(&root.join("notes/old.md")).unwrap();
+    assert_eq!(outcome.to, "archive/notes/old.md");
+    assert!(!root.join("notes/old.md").exists());
+    assert!(root.join("archive/notes/old.md").exists());
+
+    // 归档笔记默认不出现在搜索和统计中
+    let hits = repo.search("gardens", false, None).unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].node.id, current_id);
+    assert_eq!(repo.search("gardens", true, None).unwrap().len(), 2);
+    assert_eq!(repo.stats().unwrap().notes + 1, repo.stats_filtered(true).unwrap().notes);
+
+    // 反向链接随节点迁移到新 ID
+    let graph = repo.storage().unwrap().graph();
+    let archived = graph.get_node(&outcome.node_id).unwrap().unwrap();
+    assert!(archived.is_archived());
+    assert!(archived.tags.contains(&"archived".to_string()));
+    assert!(!graph.node_exists(&old_id).unwrap());
+    let backlinks = graph.get_edges_for_node(&outcome.node_id, EdgeDirection::Incoming).unwrap();
+    assert!(backlinks.iter().any(|e| e.from == current_id));
+
+    assert!(repo.archive(&root.join("archive/notes/old.md")).is_err());
+    repo.commit("Archive old idea").unwrap();
+
+    let outcome = repo.unarchive(&root.join("archive/notes/old.md")).unwrap();
+    assert_eq!(outcome.to, "notes/old.md");
+    assert_eq!(outcome.node_id, old_id);
+    let graph = repo.storage().unwrap().graph();
+    assert!(!graph.get_node(&old_id).unwrap().unwrap().is_archived());
+    let backlinks = graph.get_edges_for_node(&old_id, EdgeDirection::Incoming).unwrap();
+    assert!(backlinks.iter().any(|e| e.from == current_id));
+    assert_eq!(repo.search("gardens", false, None).unwrap().len(), 2);
+}
+
+#[test]
+fn test_repository_snapshot_retention() {
+    use memexia::core::RepoConfig;
+    use memexia::error::MemexiaError;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    configure_git_user(temp.path());
+    let mut repo = Repository::init(temp.path()).unwrap();
+    let root = repo.path().to_path_buf();
+    RepoConfig::set_value(&root, "history.retention.keep_last", toml::Value::Integer(1)).unwrap();
+
+    let mut commits = Vec::new();
+    for i in 0..3 {
+        let file = root.join(format!("n{}.md", i));
+        fs::write(&file, format!("# Note {}\n", i)).unwrap();
+        repo.add(&[file]).unwrap();
+        commits.push(repo.commit(&format!("Note {}", i)).unwrap());
+        if i == 0 {
+            // 分支末端的快照永远不会被清理
+            let git = git2::Repository::open(&root).unwrap();
+            let commit = git.find_commit(git2::Oid::from_str(&commits[0]).unwrap()).unwrap();
+            git.branch("keep", &commit, false).unwrap();
+        }
+    }
+
+    let history = &repo.vcs().unwrap().graph_history;
+    let links = history.commit_links().unwrap();
+    let pruned: Vec<bool> = links.iter().map(|l| l.pruned).collect();
+    assert_eq!(pruned, [false, true, false]);
+    assert!(history.get_snapshot(&links[0].graph_hash).is_ok());
+    assert!(matches!(history.get_snapshot(&links[1].graph_hash), Err(MemexiaError::SnapshotPruned { .. })));
+    assert!(history.get_snapshot(&links[2].graph_hash).is_ok());
+
+    // gc 再次执行时没有可清理的快照
+    assert!(repo.apply_retention().unwrap().pruned.is_empty());
+}
+
+#[test]
+fn test_repository_refresh_links_after_title_change() {
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    configure_git_user(temp.path());
+    let mut repo = Repository::init(temp.path()).unwrap();
+    let root = repo.path().to_path_buf();
+    fs::create_dir_all(root.join("notes")).unwrap();
+    let link_note = "# Reader\n\nOld Name stays. See [[Old Name|Supports:0.8:keep: this]] and [[notes/a.md]].\n";
+    fs::write(root.join("notes/a.md"), "# Old Name\n\nBody.\n").unwrap();
+    fs::write(root.join("notes/b.md"), link_note).unwrap();
+    repo.add(&[root.join("notes/a.md"), root.join("notes/b.md")]).unwrap();
+    repo.commit("Notes").unwrap();
+    assert!(repo.refresh_links(false).unwrap().is_empty());
+
+    fs::write(root.join("notes/a.md"), "# New Name\n\nBody.\n").unwrap();
+    repo.add(&[root.join("notes/a.md")]).unwrap();
+    repo.commit("Rename title").unwrap();
+    let a_id = repo.node_id_for_path(&root.join("notes/a.md")).unwrap();
+    let node = repo.storage().unwrap().graph().get_node(&a_id).unwrap().unwrap();
+    assert_eq!(node.previous_title(), Some("Old Name"));
+
+    // 默认只报告，不改文件
+    let refreshed = repo.refresh_links(false).unwrap();
+    assert_eq!(refreshed.len(), 1);
+    assert_eq!(refreshed[0].path, std::path::PathBuf::from("notes/b.md"));
+    assert_eq!(refreshed[0].changes[0].old, "[[Old Name|Supports:0.8:keep: this]]");
+    assert_eq!(refreshed[0].changes[0].new, "[[New Name|Supports:0.8:keep: this]]");
+    assert_eq!(fs::read_to_string(root.join("notes/b.md")).unwrap(), link_note);
+
+    repo.refresh_links(true).unwrap();
+    assert_eq!(
+        fs::read_to_string(root.join("notes/b.md")).unwrap(),
+        "# Reader\n\nOld Name stays. See [[New Name|Supports:0.8:keep: this]] and [[notes/a.md]].\n"
+    );
+    let git = git2::Repository::open(&root).unwrap();
+    assert!(git.status_file(std::path::Path::new("notes/b.md")).unwrap().contains(git2::Status::INDEX_MODIFIED));
+    assert!(repo.refresh_links(false).unwrap().is_empty());
+}
+
+#[test]
+fn test_repository_render_node_html() {
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    configure_git_user(temp.path());
+    let mut repo = Repository::init(temp.path()).unwrap();
+    let root = repo.path().to_path_buf();
+    fs::create_dir_all(root.join("notes")).unwrap();
+    fs::write(
+        root.join("notes/a.md"),
+        "---\ntitle: A\n---\n# A\n\n## Links\n\nSee [[notes/b.md|Supports]] and [[notes/missing.md]]. #topic\n",
+    )
+    .unwrap();
+    fs::write(root.join("notes/b.md"), "# B\n").unwrap();
+    repo.add(&[root.join("notes/a.md"), root.join("notes/b.md")]).unwrap();
+    repo.commit("Notes").unwrap();
+
+    let a_id = repo.node_id_for_path(&root.join("notes/a.md")).unwrap();
+    let b_id = repo.node_id_for_path(&root.join("notes/b.md")).unwrap();
+    let rendered = repo.render_node_html(&a_id).unwrap();
+    assert!(rendered.html.contains(&format!("<a data-node-id=\"{}\">notes/b</a>", b_id)));
+    assert!(rendered.html.contains("<a class=\"broken\" data-target=\"notes/missing.md\">notes/missing</a>"));
+    assert!(rendered.html.contains("<a data-tag=\"topic\">#topic</a>"));
+    assert!(!rendered.html.contains("title: A"));
+    let toc: Vec<_> = rendered.toc.iter().map(|e| (e.level, e.text.as_str(), e.anchor.as_str())).collect();
+    assert_eq!(toc, [(1, "A", "a"), (2, "Links", "links")]);
+
+    // 也可以用 wiki 链接目标指定笔记
+    assert_eq!(repo.render_node_html("notes/a.md").unwrap(), rendered);
+
+    // 占位节点没有源文件
+    let missing = repo.render_node_html("notes/missing.md").unwrap_err();
+    assert!(matches!(missing, MemexiaError::Validation(_)));
+    assert!(matches!(repo.render_node_html("notes/none.md"), Err(MemexiaError::NodeNotFound { .. })));
+}
+
+#[test]
+fn test_repository_note_language() {
+    use memexia::storage::ids;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+    configure_git_user(path);
+    let mut repo = Repository::init(path).unwrap();
+    let root = repo.path().to_path_buf();
+    fs::write(root.join("zh.md"), "# 自由意志\n\n自由意志与决定论是否相容？\n").unwrap();
+    fs::write(root.join("en.md"), "# Free will\n\nFree will and determinism are compatible.\n").unwrap();
+    fs::write(root.join("mixed.md"), "# Rust 笔记\n\n今天学习了 Rust 的所有权和借用。\n").unwrap();
+    fs::write(root.join("override.md"), "---\nlang: en\n---\n\n# 翻译\n\n这是一篇英文笔记的中文草稿。\n").unwrap();
+    let files = ["zh.md", "en.md", "mixed.md", "override.md"].map(|f| root.join(f));
+    repo.add(&files).unwrap();
+    repo.commit("Add notes").unwrap();
+
+    let graph = repo.storage().unwrap().graph();
+    let language = |file: &str| graph.get_node(&ids::file_id(file)).unwrap().unwrap().language().map(String::from);
+    assert_eq!(language("zh.md").as_deref(), Some("zh"));
+    assert_eq!(language("en.md").as_deref(), Some("en"));
+    assert_eq!(language("mixed.md").as_deref(), Some("zh"));
+    // frontmatter 的 lang: 覆盖检测结果
+    assert_eq!(language("override.md").as_deref(), Some("en"));
+
+    let titles = |lang: Option<&str>| -> Vec<String> {
+        repo.search("自由", false, lang).unwrap().into_iter().map(|hit| hit.node.title).collect()
+    };
+    assert_eq!(titles(Some("zh")), ["自由意志"]);
+    assert!(titles(Some("en")).is_empty());
+
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.languages.get("zh"), Some(&2));
+    assert_eq!(stats.languages.get("en"), Some(&2));
+}
+
+#[test]
+fn test_repository_history_export_import() {
+    use memexia::storage::ids;
+    use memexia::vcs::RollbackManager;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let source_root = temp.path().join("source");
+    fs::create_dir_all(&source_root).unwrap();
+    configure_git_user(&source_root);
+    let bundle = temp.path().join("history.tar");
+    let a = ids::file_id("a.md");
+    let b = ids::file_id("b.md");
+    let (commit, hash) = {
+        let mut repo = Repository::init(&source_root).unwrap();
+        fs::write(source_root.join("a.md"), "# A\n").unwrap();
+        fs::write(source_root.join("b.md"), "# B\n").unwrap();
+        repo.add(&[source_root.join("a.md"), source_root.join("b.md")]).unwrap();
+        let commit = repo.commit("Add notes").unwrap();
+        let history = &repo.vcs().unwrap().graph_history;
+        let hash = history.snapshot_node(&a, "{\"title\":\"A\"}", &commit).unwrap();
+        history.record_derivation(&b, &a, &commit).unwrap();
+
+        let manifest = repo.export_history(&bundle).unwrap();
+        assert_eq!((manifest.nodes, manifest.node_snapshots, manifest.derivations, manifest.commit_links), (1, 1, 1, 1));
+        assert!(manifest.repository_id.is_some());
+        (commit, hash)
+    };
+
+    // 共享同一 Git 历史、但没有 .memexia/history 的副本
+    let target_root = temp.path().join("target");
+    copy_dir(&source_root, &target_root);
+    fs::remove_dir_all(target_root.join(".memexia/history")).unwrap();
+    let repo = Repository::open(&target_root).unwrap();
+
+    let report = repo.import_history(&bundle, false).unwrap();
+    assert_eq!((report.node_snapshots, report.derivations, report.commit_links, report.skipped), (1, 1, 1, 0));
+    assert!(report.missing_commits.is_empty());
+    assert!(!report.foreign_repository);
+
+    // 导入后回退和推导查询立即可用
+    let rollback = RollbackManager::new(&target_root).unwrap();
+    assert_eq!(rollback.rollback_node(&a, &hash).unwrap(), "{\"title\":\"A\"}");
+    assert_eq!(rollback.rollback_node_to_history(&a, &commit).unwrap().as_deref(), Some("{\"title\":\"A\"}"));
+    let derived = repo.vcs().unwrap().graph_history.get_derived_nodes(&a).unwrap();
+    assert_eq!(derived.len(), 1);
+    assert_eq!(derived[0].child_id, b);
+
+    // 再次导入需要 --merge，重复记录全部跳过
+    assert!(repo.import_history(&bundle, false).is_err());
+    let report = repo.import_history(&bundle, true).unwrap();
+    assert_eq!((report.node_snapshots, report.derivations, report.commit_links, report.skipped), (0, 0, 0, 3));
+}
+
+/// 递归复制目录
+fn copy_dir(from: &std::path::Path, to: &std::path::Path) {
+    fs::create_dir_all(to).unwrap();
+    for entry in fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        let target = to.join(entry.file_name());
+        if entry.file_type().unwrap().is_dir() {
+            copy_dir(&entry.path(), &target);
+        } else {
+            fs::copy(entry.path(), &target).unwrap();
+        }
+    }
+}
+
 /// 配置 Git 用户信息（Windows 需要）
 fn configure_git_user(path: &std::path::Path) {
     let git_dir = path.join(".git");
@@ -374,3 +2143,473 @@ fn configure_git_user(path: &std::path::Path) {
     );
     let _ = fs::write(&config_path, config_content);
 }
+
+#[test]
+fn test_repository_stats_history() {
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    configure_git_user(root);
+    let mut repo = Repository::init(root).unwrap();
+
+    fs::write(root.join("a.md"), "# A\n").unwrap();
+    repo.add(&[root.join("a.md")]).unwrap();
+    repo.commit("One note").unwrap();
+    fs::write(root.join("b.md"), "---\ntype: Question\n---\n# B\n\n[[a.md]]\n").unwrap();
+    repo.add(&[root.join("b.md")]).unwrap();
+    repo.commit("Two notes").unwrap();
+    fs::write(root.join("c.md"), "# C\n\n[[a.md]] [[b.md]]\n").unwrap();
+    repo.add(&[root.join("c.md")]).unwrap();
+    repo.commit("Three notes").unwrap();
+
+    let points = repo.stats_history().unwrap();
+    assert_eq!(points.len(), 3);
+    let counts: Vec<(usize, usize)> = points
+        .iter()
+        .map(|point| point.stats.as_ref().map(|stats| (stats.nodes, stats.edges)).unwrap())
+        .collect();
+    assert_eq!(counts, [(1, 0), (2, 1), (3, 3)]);
+    assert_eq!(points[2].stats.as_ref().unwrap().types.get("Question"), Some(&1));
+
+    // 统计写入快照旁的缓存，再次统计直接读取缓存
+    let hash = &points[2].graph_hash;
+    let snapshot = root.join(".memexia/history/snapshots").join(&hash[..2]).join(&hash[2..]);
+    let cache = snapshot.with_extension("stats.json");
+    assert!(cache.exists());
+    fs::write(&cache, r#"{"nodes":99,"edges":0,"types":{}}"#).unwrap();
+    assert_eq!(repo.stats_history().unwrap()[2].stats.as_ref().unwrap().nodes, 99);
+
+    // 快照缺失的提交仍在时间线中，没有统计
+    let first = &points[0].graph_hash;
+    fs::remove_file(root.join(".memexia/history/snapshots").join(&first[..2]).join(&first[2..])).unwrap();
+    let points = repo.stats_history().unwrap();
+    assert_eq!(points.len(), 3);
+    assert!(points[0].stats.is_none());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_repository_rebuild_index_restores_graph_on_failure() {
+    use memexia::core::RepoConfig;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    configure_git_user(root);
+    let mut repo = Repository::init(root).unwrap();
+    let root = repo.path().to_path_buf();
+    fs::write(root.join("a.md"), "# A\n\n[[b.md]]\n").unwrap();
+    fs::write(root.join("b.md"), "# B\n").unwrap();
+    repo.reindex(&root, false, false).unwrap();
+
+    let contents = |repo: &Repository| {
+        let graph = repo.storage().unwrap().graph();
+        let mut ids: Vec<String> = graph.get_all_nodes().unwrap().into_iter().map(|n| n.id).collect();
+        ids.sort();
+        (ids, graph.get_all_edges().unwrap().len())
+    };
+    let backups = || {
+        fs::read_dir(root.join(".memexia"))
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().file_name().to_string_lossy().starts_with("graph.bak."))
+            .count()
+    };
+    let before = contents(&repo);
+    assert_eq!(before, (vec!["urn:memexia:file:a.md".to_string(), "urn:memexia:file:b.md".to_string()], 1));
+
+    // 解密命令失败的加密笔记让重建中途出错，原有的图原样恢复
+    RepoConfig::set(&root, "index.decrypt_cmd", "exit 1").unwrap();
+    fs::write(root.join("secret.md.age"), "ciphertext").unwrap();
+    let err = repo.rebuild_index(&root).unwrap_err();
+    assert!(err.to_string().contains("secret.md.age"), "{}", err);
+    assert_eq!(contents(&repo), before);
+    assert_eq!(backups(), 0);
+
+    // 去掉出错的文件后重建成功，不属于任何节点的四元组不再残留
+    fs::remove_file(root.join("secret.md.age")).unwrap();
+    let storage = repo.storage().unwrap();
+    let stray = "<urn:memexia:commit:legacy> <http://purl.org/dc/terms/creator> \"someone\" .\n";
+    let nquads = format!("{}{}", storage.graph().export_nquads().unwrap(), stray);
+    storage.graph_storage().replace_contents(&nquads).unwrap();
+
+    let summary = repo.rebuild_index(&root).unwrap();
+    assert_eq!(summary.files_indexed, 2);
+    assert_eq!(contents(&repo), before);
+    assert!(!repo.storage().unwrap().graph().export_nquads().unwrap().contains("urn:memexia:commit:legacy"));
+    assert_eq!(backups(), 0);
+}
+
+#[test]
+fn test_repository_export_subgraph_by_tag() {
+    use memexia::core::subgraph;
+    use memexia::storage::nquads;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    configure_git_user(root);
+    let repo = Repository::init(root).unwrap();
+    fs::write(root.join("mind.md"), "---\ntags: [哲学]\n---\n# Mind\n\n[[body.md|Supports]] [[cooking.md]]\n").unwrap();
+    fs::write(root.join("body.md"), "---\ntags: [哲学]\n---\n# Body\n\n[[mind.md]]\n").unwrap();
+    fs::write(root.join("cooking.md"), "---\ntags: [food]\n---\n# Cooking\n\n[[bread.md]]\n").unwrap();
+    fs::write(root.join("bread.md"), "---\ntags: [food]\n---\n# Bread\n").unwrap();
+    repo.reindex(root, false, false).unwrap();
+    let graph = repo.storage().unwrap().graph();
+
+    let ids = subgraph::select_by_tag(graph, "哲学").unwrap();
+    let inner = subgraph::extract(graph, &ids, false).unwrap();
+    let titles: Vec<&str> = inner.nodes.iter().map(|n| n.title.as_str()).collect();
+    assert_eq!(titles, ["Body", "Mind"]);
+    let mut edges: Vec<(&str, &str)> = inner.edges.iter().map(|e| (e.from.as_str(), e.to.as_str())).collect();
+    edges.sort();
+    assert_eq!(
+        edges,
+        [("urn:memexia:file:body.md", "urn:memexia:file:mind.md"), ("urn:memexia:file:mind.md", "urn:memexia:file:body.md")]
+    );
+
+    let mut buffer = Vec::new();
+    nquads::write_nquads(&inner.nodes, &inner.edges, &mut buffer).unwrap();
+    let exported = String::from_utf8(buffer).unwrap();
+    assert!(exported.contains("<urn:memexia:file:mind.md>"));
+    assert!(!exported.contains("cooking") && !exported.contains("bread"));
+
+    // 带边界：集合外的端点作为存根，集合外部之间的边不导出
+    let outer = subgraph::extract(graph, &ids, true).unwrap();
+    assert_eq!(outer.edges.len(), 3);
+    let stubs: Vec<&str> = outer.stubs.iter().map(|n| n.title.as_str()).collect();
+    assert_eq!(stubs, ["Cooking"]);
+    assert!(outer.stubs[0].tags.is_empty() && outer.stubs[0].content.is_none());
+
+    // 查询选择与标签选择一致
+    let by_query =
+        subgraph::select_by_query(graph, "SELECT ?node WHERE { ?node <memexia:tag> \"哲学\" }").unwrap();
+    assert_eq!(by_query, ids);
+    assert!(subgraph::select_by_query(graph, "SELECT ?x WHERE { ?x <memexia:tag> \"哲学\" }").is_err());
+}
+
+#[test]
+fn test_repository_link_provenance() {
+    use memexia::core::Indexer;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    configure_git_user(root);
+    let mut repo = Repository::init(root).unwrap();
+
+    fs::write(root.join("a.md"), "# A\n\nFirst draft. [[b.md]]\n").unwrap();
+    repo.add(&[root.join("a.md")]).unwrap();
+    let first = repo.commit("Link a to b").unwrap();
+    let links = repo.node_links("a.md").unwrap();
+    assert_eq!(links[0].introduced_in_commit.as_deref(), Some(first.as_str()));
+
+    // 修改无关正文并重新索引后再次提交，链接仍记为第一次提交，新链接记为第二次
+    fs::write(root.join("a.md"), "# A\n\nSecond draft. [[b.md]] [[c.md]]\n").unwrap();
+    Indexer::new(repo.storage().unwrap().clone()).index_file(&root.join("a.md")).unwrap();
+    repo.add(&[root.join("a.md")]).unwrap();
+    let second = repo.commit("Edit a").unwrap();
+    assert_ne!(first, second);
+
+    let provenance: Vec<(String, Option<String>)> = repo
+        .node_links("a.md")
+        .unwrap()
+        .into_iter()
+        .map(|edge| (edge.to, edge.introduced_in_commit))
+        .collect();
+    assert!(provenance.contains(&("urn:memexia:file:b.md".to_string(), Some(first.clone()))));
+    assert!(provenance.contains(&("urn:memexia:file:c.md".to_string(), Some(second))));
+}
+
+#[test]
+fn test_repository_title_index() {
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    configure_git_user(root);
+    let repo = Repository::init(root).unwrap();
+    let root = repo.path().to_path_buf();
+    let graph = repo.storage().unwrap().graph();
+    let ids = |title: &str| -> Vec<String> {
+        graph.find_by_title(title, false).unwrap().into_iter().map(|node| node.id).collect()
+    };
+
+    fs::write(root.join("a.md"), "# Alpha\n\nFirst note.\n").unwrap();
+    fs::write(root.join("b.md"), "# Beta\n\nSecond note.\n").unwrap();
+    repo.reindex(&root, false, true).unwrap();
+    assert_eq!(ids("alpha"), ["urn:memexia:file:a.md"]);
+
+    // 移动文件、修改标题后增量索引，索引指向新节点和新标题
+    fs::rename(root.join("a.md"), root.join("c.md")).unwrap();
+    fs::write(root.join("b.md"), "# Gamma\n\nSecond note.\n").unwrap();
+    assert_eq!(repo.reindex(&root, false, true).unwrap().renamed.len(), 1);
+    assert_eq!(ids("ALPHA"), ["urn:memexia:file:c.md"]);
+    assert!(ids("beta").is_empty());
+    assert_eq!(ids("gamma"), ["urn:memexia:file:b.md"]);
+    assert!(repo.title_index_mismatches().unwrap().is_empty());
+
+    // 模拟索引与标题不同步，doctor 的检查发现并修复
+    let nquads: String = graph
+        .export_nquads()
+        .unwrap()
+        .lines()
+        .filter(|line| !line.contains("titleKey"))
+        .map(|line| format!("{}\n", line))
+        .collect();
+    repo.storage().unwrap().graph_storage().replace_contents(&nquads).unwrap();
+    assert!(ids("gamma").is_empty());
+    assert_eq!(
+        repo.title_index_mismatches().unwrap(),
+        ["urn:memexia:file:b.md", "urn:memexia:file:c.md"]
+    );
+    assert_eq!(repo.rebuild_title_index().unwrap(), 2);
+    assert!(repo.title_index_mismatches().unwrap().is_empty());
+    assert_eq!(ids("gamma"), ["urn:memexia:file:b.md"]);
+}
+
+#[test]
+fn test_repository_auto_summary() {
+    use memexia::core::RepoConfig;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    configure_git_user(root);
+    let repo = Repository::init(root).unwrap();
+    let root = repo.path().to_path_buf();
+    let id = "urn:memexia:file:will.md";
+    let summary = || {
+        let node = repo.storage().unwrap().graph().get_node(id).unwrap().unwrap();
+        (node.summary().map(str::to_string), node.is_auto_summary())
+    };
+
+    // 默认不提取
+    fs::write(root.join("will.md"), "# Free Will\n\n```\ncode\n```\n\nWe choose, see [[Agency|Supports]].\n").unwrap();
+    repo.reindex(&root, false, false).unwrap();
+    assert_eq!(summary(), (None, false));
+
+    RepoConfig::set(&root, "index.auto_summary", "true").unwrap();
+    repo.reindex(&root, true, false).unwrap();
+    assert_eq!(summary(), (Some("We choose, see Agency.".to_string()), true));
+
+    // 正文变化后重新提取
+    fs::write(root.join("will.md"), "# Free Will\n\nWe do not choose.\n").unwrap();
+    repo.reindex(&root, false, false).unwrap();
+    assert_eq!(summary(), (Some("We do not choose.".to_string()), true));
+
+    // frontmatter 摘要优先，删掉后恢复自动摘要
+    fs::write(root.join("will.md"), "---\nsummary: Hand-written\n---\n# Free Will\n\nWe do not choose.\n").unwrap();
+    repo.reindex(&root, false, false).unwrap();
+    assert_eq!(summary(), (Some("Hand-written".to_string()), false));
+    fs::write(root.join("will.md"), "# Free Will\n\nWe do choose.\n").unwrap();
+    repo.reindex(&root, false, false).unwrap();
+    assert_eq!(summary(), (Some("We do choose.".to_string()), true));
+}
+
+#[test]
+fn test_repository_promote_tag() {
+    use memexia::storage::{NodeType, RelationType};
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let root = temp.path();
+    configure_git_user(root);
+    let repo = Repository::init(root).unwrap();
+    let root = repo.path().to_path_buf();
+    for name in ["a", "b", "c"] {
+        fs::write(root.join(format!("{}.md", name)), format!("---\ntags: [ml, draft]\n---\n# {}\n", name)).unwrap();
+    }
+    fs::write(root.join("d.md"), "---\ntags: [draft]\n---\n# d\n").unwrap();
+    repo.reindex(&root, false, false).unwrap();
+
+    let promotion = repo.promote_tag("ml", Some(NodeType::Concept), RelationType::RelatedTo, true, true).unwrap();
+    let tagged = ["urn:memexia:file:a.md", "urn:memexia:file:b.md", "urn:memexia:file:c.md"];
+    assert_eq!(promotion.tagged, tagged);
+    assert_eq!((promotion.edges, promotion.derivations, promotion.untagged), (3, 3, 3));
+    assert_eq!(promotion.files.len(), 3);
+
+    let graph = repo.storage().unwrap().graph();
+    let node = graph.get_node(&promotion.node_id).unwrap().unwrap();
+    assert_eq!((node.title.as_str(), &node.node_type), ("ml", &NodeType::Concept));
+    assert!(root.join(&promotion.path).exists());
+    for id in tagged {
+        let edges = graph.get_edges_by_source(id).unwrap();
+        assert!(edges.iter().any(|e| e.to == promotion.node_id && e.relation == RelationType::RelatedTo));
+        assert_eq!(graph.get_node(id).unwrap().unwrap().tags, ["draft"]);
+    }
+    assert!(graph.get_edges_by_source("urn:memexia:file:d.md").unwrap().is_empty());
+
+    let history = &repo.vcs().unwrap().graph_history;
+    let mut parents: Vec<String> =
+        history.get_derivations(&promotion.node_id).unwrap().into_iter().map(|d| d.parent_id).collect();
+    parents.sort();
+    assert_eq!(parents, tagged);
+    assert!(!fs::read_to_string(root.join("a.md")).unwrap().contains("ml"));
+
+    // 没有笔记带该标签时报错
+    assert!(matches!(
+        repo.promote_tag("ml", None, RelationType::RelatedTo, false, false),
+        Err(MemexiaError::Validation(_))
+    ));
+}
+
+#[test]
+fn test_repository_reindex_generated_vault() {
+    use memexia::core::synthetic::{generate_vault, VaultSpec};
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    configure_git_user(temp.path());
+    let mut repo = Repository::init(temp.path()).unwrap();
+    let root = repo.path().to_path_buf();
+    let spec = VaultSpec { notes: 300, links_per_note: 3, tags: 20, seed: 9, broken_fraction: 0.1 };
+    let vault = generate_vault(&root, &spec).unwrap();
+    assert_eq!((vault.dangling_links, vault.malformed_frontmatter), (15, 15));
+    let counts = |repo: &Repository| {
+        let graph = repo.storage().unwrap().graph();
+        (graph.list_nodes().unwrap().len(), graph.list_edges().unwrap().len())
+    };
+
+    // 悬空链接生成占位节点，无法解析的 frontmatter 只产生警告
+    let summary = repo.reindex(&root, false, false).unwrap();
+    assert_eq!(summary.files_indexed, 300);
+    assert_eq!(summary.warnings.len(), 15);
+    assert!(summary.errors.is_empty(), "{:?}", summary.errors);
+    assert_eq!(counts(&repo), (315, 900));
+
+    // 未修改的文件不再索引，完整重建得到相同的图
+    assert_eq!(repo.reindex(&root, false, false).unwrap().files_indexed, 0);
+    repo.rebuild_index(&root).unwrap();
+    assert_eq!(counts(&repo), (315, 900));
+}
+
+#[test]
+fn test_repository_commit_records_link_derivations() {
+    use memexia::core::RepoConfig;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    configure_git_user(temp.path());
+    let mut repo = Repository::init(temp.path()).unwrap();
+    let root = repo.path().to_path_buf();
+    fs::write(root.join("a.md"), "# A\n").unwrap();
+    fs::write(root.join("b.md"), "# B\n").unwrap();
+    fs::write(root.join("c.md"), "# C\n\n[[a.md|DerivesFrom]] and [[b.md|DerivesFrom]], see [[a.md|Supports]]\n").unwrap();
+    repo.add(&[root.join("a.md"), root.join("b.md"), root.join("c.md")]).unwrap();
+    let commit = repo.commit("Derive C").unwrap();
+
+    let parents = |repo: &Repository| {
+        let mut entries: Vec<(String, String)> = repo
+            .vcs()
+            .unwrap()
+            .graph_history
+            .get_derivations("urn:memexia:file:c.md")
+            .unwrap()
+            .into_iter()
+            .map(|d| (d.parent_id, d.commit_hash))
+            .collect();
+        entries.sort();
+        entries
+    };
+    let expected = vec![
+        ("urn:memexia:file:a.md".to_string(), commit.clone()),
+        ("urn:memexia:file:b.md".to_string(), commit.clone()),
+    ];
+    assert_eq!(parents(&repo), expected);
+
+    // 再次提交同一笔记不产生重复记录
+    fs::write(root.join("c.md"), "# C\n\n[[a.md|DerivesFrom]] and [[b.md|DerivesFrom]]\n\nMore.\n").unwrap();
+    repo.add(&[root.join("c.md")]).unwrap();
+    repo.commit("Edit C").unwrap();
+    assert_eq!(parents(&repo), expected);
+
+    // index 模式：reindex 后关联 HEAD
+    RepoConfig::set(&root, "history.derivations", "index").unwrap();
+    fs::write(root.join("d.md"), "# D\n\n[[c.md|DerivesFrom]]\n").unwrap();
+    repo.reindex(&root, false, false).unwrap();
+    let head = repo.last_commit().unwrap().unwrap().oid;
+    let derivations = repo.vcs().unwrap().graph_history.get_derivations("urn:memexia:file:d.md").unwrap();
+    assert_eq!(derivations.len(), 1);
+    assert_eq!(
+        (derivations[0].parent_id.as_str(), derivations[0].commit_hash.as_str()),
+        ("urn:memexia:file:c.md", head.as_str())
+    );
+    assert_eq!(parents(&repo), expected);
+}
+
+#[test]
+fn test_repository_edit_link() {
+    use memexia::storage::{EdgeEdit, EdgeField, RelationType};
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    configure_git_user(temp.path());
+    let mut repo = Repository::init(temp.path()).unwrap();
+    let root = repo.path().to_path_buf();
+    fs::write(root.join("a.md"), "# A\n\n[[b.md|Supports:0.9]]\n").unwrap();
+    fs::write(root.join("b.md"), "# B\n").unwrap();
+    repo.add(&[root.join("a.md"), root.join("b.md")]).unwrap();
+    repo.commit("Link A to B").unwrap();
+    let (a, b) = (root.join("a.md"), root.join("b.md"));
+    let original = repo.link_between(&a, &b).unwrap();
+
+    let edit = EdgeEdit { strength: Some(0.4), description: Some("weaker than claimed".to_string()), ..Default::default() };
+    let edge = repo.edit_link(&a, &b, &edit).unwrap();
+    assert_eq!((edge.strength, edge.description.as_deref()), (0.4, Some("weaker than claimed")));
+    let stored = repo.link_between(&a, &b).unwrap();
+    assert_eq!(stored.history.len(), 2);
+    assert_eq!(
+        stored.history.iter().find(|c| c.field == EdgeField::Strength).unwrap().previous.as_deref(),
+        Some("0.9")
+    );
+    assert!(stored.updated_at.is_some());
+    assert_eq!(stored.introduced_in_commit, original.introduced_in_commit);
+
+    // 值不变时不记录修改
+    repo.edit_link(&a, &b, &EdgeEdit { strength: Some(0.4), ..Default::default() }).unwrap();
+    assert_eq!(repo.link_between(&a, &b).unwrap().history.len(), 2);
+
+    // 改变关系后仍只有一条边
+    repo.edit_link(&a, &b, &EdgeEdit { relation: Some(RelationType::Refines), ..Default::default() }).unwrap();
+    let edges = repo.storage().unwrap().graph().get_edges_by_source("urn:memexia:file:a.md").unwrap();
+    assert_eq!(edges.len(), 1);
+    assert_eq!((edges[0].relation.clone(), edges[0].history.len()), (RelationType::Refines, 3));
+
+    let invalid = EdgeEdit { strength: Some(1.5), ..Default::default() };
+    assert!(matches!(repo.edit_link(&a, &b, &invalid), Err(MemexiaError::Validation(_))));
+    assert!(matches!(repo.edit_link(&a, &b, &EdgeEdit::default()), Err(MemexiaError::Validation(_))));
+
+    // 撤销恢复修改前的边
+    repo.undo().unwrap();
+    let restored = repo.link_between(&a, &b).unwrap();
+    assert_eq!((restored.relation, restored.history.len()), (RelationType::Supports, 2));
+}
+
+#[test]
+fn test_repository_recovers_missing_graph_store() {
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    configure_git_user(temp.path());
+    let mut repo = Repository::init(temp.path()).unwrap();
+    let root = repo.path().to_path_buf();
+    fs::write(root.join("a.md"), "# A\n").unwrap();
+    repo.add(&[root.join("a.md")]).unwrap();
+    repo.commit("Add A").unwrap();
+    drop(repo);
+
+    fs::remove_dir_all(root.join(".memexia/graph")).unwrap();
+    let mut repo = Repository::open(&root).unwrap();
+    let err = repo.storage().unwrap_err();
+    assert!(matches!(err, MemexiaError::StoreCorrupt { .. }), "{:?}", err);
+    assert!(err.to_string().contains("memexia reindex --full"), "{}", err);
+
+    // 不需要图的操作仍可用
+    assert_eq!(repo.log(10).unwrap().len(), 1);
+    repo.status().unwrap();
+
+    let summary = repo.rebuild_index(&root).unwrap();
+    assert_eq!(summary.files_indexed, 1);
+    assert!(repo.storage().unwrap().graph().get_node("urn:memexia:file:a.md").unwrap().is_some());
+}
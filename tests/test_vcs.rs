@@ -2,12 +2,14 @@
 
 use memexia::vcs::{Vcs, CommitInfo};
 use memexia::storage::Storage;
+use memexia::error::MemexiaError;
 
 #[test]
 fn test_commit_info_short() {
     let info = CommitInfo {
         oid: "abc123456789".to_string(),
         message: "Test commit".to_string(),
+        trailers: Vec::new(),
         graph_hash: Some("hash123".to_string()),
         author: "Test <test@example.com>".to_string(),
         timestamp: "2024-01-01T00:00:00Z".to_string(),
@@ -22,6 +24,7 @@ fn test_commit_info_short_long_oid() {
     let info = CommitInfo {
         oid: "1234567890abcdef".to_string(),
         message: "Another commit".to_string(),
+        trailers: Vec::new(),
         graph_hash: None,
         author: "User <user@test.com>".to_string(),
         timestamp: "2024-01-01T00:00:00Z".to_string(),
@@ -80,7 +83,7 @@ fn test_vcs_commit_info() {
     std::fs::write(&test_file, "test content").unwrap();
 
     // 提交
-    let oid = vcs.commit("Test commit", &[test_file.clone()], &storage).unwrap();
+    let oid = vcs.commit("Test commit", &[test_file.clone()], &[], &storage).unwrap();
     assert!(!oid.is_empty());
 
     // 获取日志
@@ -106,7 +109,7 @@ fn test_vcs_log_limit() {
     for i in 1..=5 {
         let test_file = path.join(format!("test{}.txt", i));
         std::fs::write(&test_file, format!("content {}", i)).unwrap();
-        vcs.commit(&format!("Commit {}", i), &[test_file.clone()], &storage).unwrap();
+        vcs.commit(&format!("Commit {}", i), &[test_file.clone()], &[], &storage).unwrap();
     }
 
     // 限制获取数量
@@ -133,7 +136,7 @@ fn test_vcs_head_info() {
     // 创建提交
     let test_file = path.join("test.txt");
     std::fs::write(&test_file, "content").unwrap();
-    vcs.commit("First commit", &[test_file.clone()], &storage).unwrap();
+    vcs.commit("First commit", &[test_file.clone()], &[], &storage).unwrap();
 
     // 应该有 HEAD 信息
     let head = vcs.head_info().unwrap();
@@ -156,7 +159,7 @@ fn test_vcs_amend() {
     // 创建初始提交
     let test_file = path.join("test.txt");
     std::fs::write(&test_file, "content").unwrap();
-    let _oid1 = vcs.commit("Original message", &[test_file.clone()], &storage).unwrap();
+    let _oid1 = vcs.commit("Original message", &[test_file.clone()], &[], &storage).unwrap();
 
     // 修改提交
     std::fs::write(&test_file, "updated content").unwrap();
@@ -167,6 +170,76 @@ fn test_vcs_amend() {
     assert_eq!(logs[0].message, "Amended message");
 }
 
+#[test]
+fn test_commit_info_short_guards_short_oid() {
+    let info = CommitInfo {
+        oid: "abc".to_string(),
+        message: "Short".to_string(),
+        trailers: Vec::new(),
+        graph_hash: None,
+        author: "User <user@test.com>".to_string(),
+        timestamp: "2024-01-01T00:00:00Z".to_string(),
+    };
+
+    assert_eq!(info.to_short(), "abc - Short");
+}
+
+#[test]
+fn test_vcs_resolve_revision() {
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+
+    let storage = Storage::init(path).unwrap();
+    let mut vcs = Vcs::init(path).unwrap();
+    configure_git_user(path);
+
+    let mut oids = Vec::new();
+    for i in 1..=3 {
+        let test_file = path.join(format!("test{}.txt", i));
+        std::fs::write(&test_file, format!("content {}", i)).unwrap();
+        oids.push(vcs.commit(&format!("Commit {}", i), &[test_file.clone()], &[], &storage).unwrap());
+    }
+
+    assert_eq!(vcs.resolve_revision("HEAD").unwrap().to_string(), oids[2]);
+    assert_eq!(vcs.resolve_revision("HEAD~1").unwrap().to_string(), oids[1]);
+    assert_eq!(vcs.resolve_revision(&oids[0][..8]).unwrap().to_string(), oids[0]);
+
+    // 分支名
+    let repo = git2::Repository::open(path).unwrap();
+    let first = repo.find_commit(git2::Oid::from_str(&oids[0]).unwrap()).unwrap();
+    repo.branch("draft", &first, false).unwrap();
+    assert_eq!(vcs.resolve_revision("draft").unwrap().to_string(), oids[0]);
+
+    let err = vcs.resolve_revision("no-such-branch").unwrap_err();
+    assert!(matches!(err, MemexiaError::Validation(_)));
+    assert!(err.to_string().contains("Unknown revision"));
+}
+
+#[test]
+fn test_vcs_resolve_revision_ambiguous_prefix() {
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    let temp = TempDir::new().unwrap();
+    let path = temp.path();
+    let vcs = Vcs::init(path).unwrap();
+
+    // 写入对象直到两个对象的 4 位前缀相同
+    let repo = git2::Repository::open(path).unwrap();
+    let mut seen = HashMap::new();
+    let prefix = (0..).find_map(|i: u32| {
+        let oid = repo.blob(format!("blob {}", i).as_bytes()).unwrap().to_string();
+        let prefix = oid[..4].to_string();
+        seen.insert(prefix.clone(), oid).map(|_| prefix)
+    });
+
+    let err = vcs.resolve_revision(&prefix.unwrap()).unwrap_err();
+    assert!(matches!(err, MemexiaError::Validation(_)));
+    assert!(err.to_string().contains("Ambiguous revision"));
+}
+
 /// 配置 Git 用户信息（Windows 需要）
 fn configure_git_user(path: &std::path::Path) {
     let git_dir = path.join(".git");